@@ -0,0 +1,1823 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::conflicts::Strategy;
+use crate::db::Db;
+use crate::error::{Error, Result};
+use crate::geo::{self, Point};
+use crate::geofence;
+use crate::gps::{self, Receiver};
+use crate::mirror;
+use crate::offline_queue::{self, NewMutation, Operation};
+
+pub const POSITION_EVENT: &str = "avl://position";
+pub const STATUS_EVENT: &str = "avl://status";
+
+const CONFIG_FILE: &str = "avl.json";
+const APRS_IS_SERVER: &str = "rotate.aprs2.net:14580";
+const DEFAULT_RETENTION_DAYS: u32 = 30;
+const MAX_RETENTION_DAYS: u32 = 366;
+/// How often a blocked listener wakes up to check for stop.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const RETRY_DELAY: Duration = Duration::from_secs(10);
+/// APRS-IS servers send a comment line every 20 s or so.
+const APRS_IS_SILENCE: Duration = Duration::from_secs(90);
+const HTTP_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_HEAD: u64 = 16 * 1024;
+const MAX_BODY: usize = 1024 * 1024;
+/// A tracker reporting every second would bury a day's track in noise;
+/// keep a breadcrumb this often, or sooner if the unit moved.
+const MIN_SPACING: Duration = Duration::from_secs(5);
+const MIN_MOVE_M: f64 = 25.0;
+/// How often a moving resource's shared record is updated.
+const SYNC_INTERVAL: Duration = Duration::from_secs(60);
+/// Report times further ahead than this are a tracker with a bad clock.
+const MAX_CLOCK_SKEW_S: i64 = 300;
+const PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+const DEFAULT_LATEST_MINUTES: u32 = 24 * 60;
+const MAX_TRACK_POINTS: u32 = 50_000;
+/// Unknown devices and NMEA decoders kept in memory.
+const MAX_DEVICES: usize = 500;
+const KNOTS_TO_KMH: f64 = 1.852;
+const FEET_TO_M: f64 = 0.3048;
+const MIN_TOKEN_LEN: usize = 12;
+/// Third-party packets nested deeper than this are dropped.
+const MAX_THIRD_PARTY_DEPTH: usize = 4;
+
+// ─── Units ──────────────────────────────────────────────────────
+
+/// What a tracker is attached to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubjectKind {
+    Resource,
+    Team,
+}
+
+impl SubjectKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            SubjectKind::Resource => "resource",
+            SubjectKind::Team => "team",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "resource" => Some(SubjectKind::Resource),
+            "team" => Some(SubjectKind::Team),
+            _ => None,
+        }
+    }
+
+    fn table(self) -> &'static str {
+        match self {
+            SubjectKind::Resource => "resources",
+            SubjectKind::Team => "teams",
+        }
+    }
+}
+
+/// A tracker (APRS callsign, NMEA device, gateway id) assigned to a unit.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Unit {
+    pub device_id: String,
+    pub subject_kind: SubjectKind,
+    pub subject_id: String,
+    pub label: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewUnit {
+    pub device_id: String,
+    pub subject_kind: SubjectKind,
+    pub subject_id: String,
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+/// Callsigns and device ids are matched case-insensitively.
+fn normalize_device(id: &str) -> String {
+    id.trim().to_uppercase()
+}
+
+fn unit_from_row(row: &Row) -> rusqlite::Result<Unit> {
+    let kind: String = row.get("subject_kind")?;
+    Ok(Unit {
+        device_id: row.get("device_id")?,
+        subject_kind: SubjectKind::parse(&kind).unwrap_or(SubjectKind::Resource),
+        subject_id: row.get("subject_id")?,
+        label: row.get("label")?,
+        created_at: row.get("created_at")?,
+    })
+}
+
+fn units(conn: &Connection) -> Result<Vec<Unit>> {
+    let mut stmt = conn.prepare("SELECT * FROM avl_units ORDER BY device_id")?;
+    let rows = stmt.query_map([], unit_from_row)?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+fn unit_for(conn: &Connection, device_id: &str) -> Result<Option<(SubjectKind, String)>> {
+    Ok(conn
+        .query_row(
+            "SELECT * FROM avl_units WHERE device_id = ?1",
+            [device_id],
+            unit_from_row,
+        )
+        .optional()?
+        .map(|unit| (unit.subject_kind, unit.subject_id)))
+}
+
+/// The unit's name from the mirror, else the label it was assigned with.
+fn label(conn: &Connection, kind: SubjectKind, id: &str) -> Result<Option<String>> {
+    let name = mirror::get(conn, kind.table(), id)?.and_then(|record| {
+        record
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::to_owned)
+    });
+    if name.is_some() {
+        return Ok(name);
+    }
+    Ok(conn
+        .query_row(
+            "SELECT label FROM avl_units
+             WHERE subject_kind = ?1 AND subject_id = ?2 AND label IS NOT NULL
+             LIMIT 1",
+            params![kind.as_str(), id],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+// ─── Reports ────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportSource {
+    Nmea,
+    Json,
+    Aprs,
+    /// Entered or relayed from within the app.
+    Manual,
+}
+
+impl ReportSource {
+    fn as_str(self) -> &'static str {
+        match self {
+            ReportSource::Nmea => "nmea",
+            ReportSource::Json => "json",
+            ReportSource::Aprs => "aprs",
+            ReportSource::Manual => "manual",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "nmea" => ReportSource::Nmea,
+            "json" => ReportSource::Json,
+            "aprs" => ReportSource::Aprs,
+            _ => ReportSource::Manual,
+        }
+    }
+}
+
+/// A position report as it arrives, before it is tied to a unit. Gateways
+/// only name the tracker (`deviceId`); which unit that is comes from the
+/// assignments made in the app, never from the packet.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Report {
+    #[serde(default)]
+    pub device_id: Option<String>,
+    #[serde(alias = "latitude")]
+    pub lat: f64,
+    #[serde(alias = "longitude", alias = "lon")]
+    pub lng: f64,
+    /// RFC 3339; the time of receipt when missing.
+    #[serde(default, alias = "timestamp")]
+    pub time: Option<String>,
+    #[serde(default)]
+    pub speed_kmh: Option<f64>,
+    #[serde(default)]
+    pub course_deg: Option<f64>,
+    #[serde(default)]
+    pub altitude_m: Option<f64>,
+}
+
+/// One breadcrumb.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Position {
+    pub subject_kind: SubjectKind,
+    pub subject_id: String,
+    /// The unit's name, on live and latest positions.
+    pub label: Option<String>,
+    pub device_id: Option<String>,
+    pub point: Point,
+    pub speed_kmh: Option<f64>,
+    /// Degrees true.
+    pub course_deg: Option<f64>,
+    pub altitude_m: Option<f64>,
+    pub source: ReportSource,
+    pub reported_at: String,
+    pub received_at: String,
+}
+
+/// Stored timestamps share one UTC format so they compare as text.
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value.trim())
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+fn position_from_row(row: &Row) -> rusqlite::Result<Position> {
+    let kind: String = row.get("subject_kind")?;
+    let source: String = row.get("source")?;
+    Ok(Position {
+        subject_kind: SubjectKind::parse(&kind).unwrap_or(SubjectKind::Resource),
+        subject_id: row.get("subject_id")?,
+        label: None,
+        device_id: row.get("device_id")?,
+        point: Point {
+            lat: row.get("latitude")?,
+            lng: row.get("longitude")?,
+        },
+        speed_kmh: row.get("speed_kmh")?,
+        course_deg: row.get("course_deg")?,
+        altitude_m: row.get("altitude_m")?,
+        source: ReportSource::parse(&source),
+        reported_at: row.get("reported_at")?,
+        received_at: row.get("received_at")?,
+    })
+}
+
+fn insert(conn: &Connection, position: &Position) -> Result<()> {
+    conn.execute(
+        "INSERT INTO avl_positions (
+            subject_kind, subject_id, device_id, latitude, longitude,
+            speed_kmh, course_deg, altitude_m, source, reported_at, received_at
+         ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            position.subject_kind.as_str(),
+            position.subject_id,
+            position.device_id,
+            position.point.lat,
+            position.point.lng,
+            position.speed_kmh,
+            position.course_deg,
+            position.altitude_m,
+            position.source.as_str(),
+            position.reported_at,
+            position.received_at,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Delete breadcrumbs older than the retention period.
+fn prune(conn: &Connection, retention_days: u32) -> Result<usize> {
+    let cutoff = Utc::now() - chrono::Duration::days(i64::from(retention_days));
+    Ok(conn.execute(
+        "DELETE FROM avl_positions WHERE reported_at < ?1",
+        [timestamp(cutoff)],
+    )?)
+}
+
+// ─── APRS ───────────────────────────────────────────────────────
+
+/// What an APRS position packet says about where its sender is.
+#[derive(Debug, Clone, PartialEq)]
+struct AprsPosition {
+    /// Source callsign, or the object or item name.
+    callsign: String,
+    point: Point,
+    course_deg: Option<f64>,
+    speed_kmh: Option<f64>,
+    altitude_m: Option<f64>,
+}
+
+impl From<AprsPosition> for Report {
+    fn from(position: AprsPosition) -> Self {
+        Report {
+            device_id: Some(position.callsign),
+            lat: position.point.lat,
+            lng: position.point.lng,
+            speed_kmh: position.speed_kmh,
+            course_deg: position.course_deg,
+            altitude_m: position.altitude_m,
+            ..Report::default()
+        }
+    }
+}
+
+/// Position fields shared by the packet formats.
+#[derive(Debug, Default)]
+struct Decoded {
+    point: Option<Point>,
+    course_deg: Option<f64>,
+    speed_kmh: Option<f64>,
+    altitude_m: Option<f64>,
+}
+
+/// A TNC2-format packet, `SRC>DEST,PATH:payload`, if it carries a
+/// position: plain, compressed, Mic-E, object or item.
+fn parse_aprs(line: &str) -> Option<AprsPosition> {
+    let mut packet = line.trim();
+    // Third-party traffic wraps a whole packet, possibly more than once
+    for _ in 0..=MAX_THIRD_PARTY_DEPTH {
+        let (header, info) = packet.split_once(':')?;
+        let (source, path) = header.split_once('>')?;
+        if source.is_empty()
+            || source.len() > 9
+            || !source
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return None;
+        }
+        match info.strip_prefix('}') {
+            Some(inner) => packet = inner,
+            None => return aprs_payload(source, path.split(',').next()?, info),
+        }
+    }
+    None
+}
+
+/// The information field of a packet from `source` to `destination`.
+fn aprs_payload(source: &str, destination: &str, info: &str) -> Option<AprsPosition> {
+    let (callsign, decoded) = match info.get(..1)? {
+        "!" | "=" => (source, aprs_position(&info[1..])?),
+        // Skip the seven-character timestamp; we use the time of receipt
+        "/" | "@" => (source, aprs_position(info.get(8..)?)?),
+        "`" | "'" => (source, mic_e(destination, &info[1..])?),
+        ";" => {
+            let name = info.get(1..10)?;
+            // `_` marks a killed object
+            if info.get(10..11)? != "*" {
+                return None;
+            }
+            (name.trim(), aprs_position(info.get(18..)?)?)
+        }
+        ")" => {
+            let end = info
+                .get(1..)?
+                .find(['!', '_'])
+                .filter(|end| (3..=9).contains(end))?
+                + 1;
+            if &info[end..end + 1] == "_" {
+                return None;
+            }
+            (info[1..end].trim(), aprs_position(&info[end + 1..])?)
+        }
+        _ => return None,
+    };
+    let point = decoded.point?;
+    point.validate().ok()?;
+    Some(AprsPosition {
+        callsign: normalize_device(callsign),
+        point,
+        course_deg: decoded.course_deg,
+        speed_kmh: decoded.speed_kmh,
+        altitude_m: decoded.altitude_m,
+    })
+}
+
+/// `/A=001234` in the comment is altitude in feet.
+fn comment_altitude(comment: &str) -> Option<f64> {
+    let at = comment.find("/A=")?;
+    let feet: f64 = comment.get(at + 3..at + 9)?.parse().ok()?;
+    Some(feet * FEET_TO_M)
+}
+
+fn base91(chars: &str) -> Option<f64> {
+    chars.bytes().try_fold(0.0, |acc, b| {
+        (33..=123)
+            .contains(&b)
+            .then(|| acc * 91.0 + f64::from(b - 33))
+    })
+}
+
+/// A plain (`4903.50N/07201.75W>`) or compressed position, with its
+/// extensions.
+fn aprs_position(data: &str) -> Option<Decoded> {
+    let first = data.bytes().next()?;
+    if first.is_ascii_digit() || first == b' ' {
+        // Ambiguous positions blank trailing digits; read them as zero
+        let lat = data.get(..8)?.replace(' ', "0");
+        let lng = data.get(9..18)?.replace(' ', "0");
+        let point = Point {
+            lat: gps::nmea_degrees(lat.get(..7)?, lat.get(7..)?)?,
+            lng: gps::nmea_degrees(lng.get(..8)?, lng.get(8..)?)?,
+        };
+        let comment = data.get(19..).unwrap_or("");
+        let mut decoded = Decoded {
+            point: Some(point),
+            altitude_m: comment_altitude(comment),
+            ..Decoded::default()
+        };
+        // `ccc/sss`: course in degrees (0 when unknown), speed in knots
+        if let Some((course, speed)) = comment.get(..7).and_then(|cs| cs.split_once('/')) {
+            if let (Ok(course), Ok(speed)) = (course.parse::<f64>(), speed.parse::<f64>()) {
+                decoded.course_deg = (course > 0.0).then_some(course % 360.0);
+                decoded.speed_kmh = Some(speed * KNOTS_TO_KMH);
+            }
+        }
+        return Some(decoded);
+    }
+
+    let y = base91(data.get(1..5)?)?;
+    let x = base91(data.get(5..9)?)?;
+    let mut decoded = Decoded {
+        point: Some(Point {
+            lat: 90.0 - y / 380_926.0,
+            lng: -180.0 + x / 190_463.0,
+        }),
+        altitude_m: comment_altitude(data.get(13..).unwrap_or("")),
+        ..Decoded::default()
+    };
+    let cs = data.get(10..12).map(str::as_bytes);
+    let kind = data.as_bytes().get(12).map(|t| t.wrapping_sub(33));
+    if let (Some(&[c, s]), Some(kind)) = (cs, kind) {
+        if c != b' ' && s <= b'{' {
+            // Both bytes are base-91; anything below `!` is a broken packet
+            let (c, s) = (f64::from(c.checked_sub(33)?), f64::from(s.checked_sub(33)?));
+            if kind & 0x18 == 0x10 {
+                // GGA-sourced: the two bytes are altitude
+                decoded.altitude_m = Some(1.002_f64.powf(c * 91.0 + s) * FEET_TO_M);
+            } else if c <= 89.0 {
+                decoded.course_deg = (c > 0.0).then_some(c * 4.0);
+                decoded.speed_kmh = Some((1.08_f64.powf(s) - 1.0) * KNOTS_TO_KMH);
+            }
+        }
+    }
+    Some(decoded)
+}
+
+/// Mic-E packs latitude into the destination address and longitude,
+/// speed and course into the first bytes of the payload.
+fn mic_e(destination: &str, data: &str) -> Option<Decoded> {
+    let dest = destination.split('-').next()?.as_bytes();
+    let info = data.as_bytes();
+    if dest.len() != 6 || info.len() < 8 || info[..6].iter().any(|&b| b < 28) {
+        return None;
+    }
+    let mut digits = [0u8; 6];
+    for (digit, &c) in digits.iter_mut().zip(dest) {
+        *digit = match c {
+            b'0'..=b'9' => c - b'0',
+            b'A'..=b'J' => c - b'A',
+            b'P'..=b'Y' => c - b'P',
+            // Position ambiguity
+            b'K' | b'L' | b'Z' => 0,
+            _ => return None,
+        };
+    }
+    let upper = |c: u8| (b'P'..=b'Z').contains(&c);
+    let (north, offset, west) = (upper(dest[3]), upper(dest[4]), upper(dest[5]));
+
+    let pair = |i: usize| f64::from(digits[i] * 10 + digits[i + 1]);
+    let lat = pair(0) + (pair(2) + pair(4) / 100.0) / 60.0;
+
+    let [d, m, h, sp, dc, se] = [0, 1, 2, 3, 4, 5].map(|i| i32::from(info[i]) - 28);
+    let mut degrees = d + if offset { 100 } else { 0 };
+    if (180..=189).contains(&degrees) {
+        degrees -= 80;
+    } else if (190..=199).contains(&degrees) {
+        degrees -= 190;
+    }
+    let minutes = if m >= 60 { m - 60 } else { m };
+    let lng = f64::from(degrees) + (f64::from(minutes) + f64::from(h) / 100.0) / 60.0;
+
+    let mut speed = sp * 10 + dc / 10;
+    if speed >= 800 {
+        speed -= 800;
+    }
+    let mut course = (dc % 10) * 100 + se;
+    if course >= 400 {
+        course -= 400;
+    }
+    let comment = data.get(8..).unwrap_or("");
+    // Optional `xxx}`: base-91 metres above -10 km
+    let altitude_m = comment
+        .find('}')
+        .filter(|&end| end >= 3)
+        .and_then(|end| base91(comment.get(end - 3..end)?))
+        .map(|v| v - 10_000.0);
+
+    Some(Decoded {
+        point: Some(Point {
+            lat: if north { lat } else { -lat },
+            lng: if west { -lng } else { lng },
+        }),
+        course_deg: (course > 0).then_some(f64::from(course % 360)),
+        speed_kmh: Some(f64::from(speed) * KNOTS_TO_KMH),
+        altitude_m,
+    })
+}
+
+// ─── Decoding ───────────────────────────────────────────────────
+
+/// Turns lines from any listener into reports. NMEA spreads a fix over
+/// several sentences, so each device gets its own receiver.
+#[derive(Default)]
+struct Decoder {
+    receivers: HashMap<String, Receiver>,
+}
+
+impl Decoder {
+    /// One line from `peer`: a JSON report, an NMEA sentence (optionally
+    /// prefixed with a device id and a space) or an APRS packet. Anonymous
+    /// reports are attributed to the sender's address.
+    fn decode(&mut self, line: &str, peer: &str) -> Option<(Report, ReportSource)> {
+        let line = line.trim();
+        if line.starts_with('{') {
+            let mut report: Report = match serde_json::from_str(line) {
+                Ok(report) => report,
+                Err(e) => {
+                    eprintln!("[avl] Unreadable report from {peer}: {e}");
+                    return None;
+                }
+            };
+            if report.device_id.is_none() {
+                report.device_id = Some(peer.to_owned());
+            }
+            return Some((report, ReportSource::Json));
+        }
+
+        let (device, sentence) = match line.split_once(' ') {
+            Some((device, rest)) if rest.starts_with('$') => (device, rest),
+            _ if line.starts_with('$') => (peer, line),
+            _ => return parse_aprs(line).map(|p| (p.into(), ReportSource::Aprs)),
+        };
+        let device = normalize_device(device);
+        if self.receivers.len() >= MAX_DEVICES && !self.receivers.contains_key(&device) {
+            self.receivers.clear();
+        }
+        let receiver = self.receivers.entry(device.clone()).or_default();
+        if !receiver.apply(sentence) {
+            return None;
+        }
+        let fix = receiver.fix()?;
+        let report = Report {
+            device_id: Some(device),
+            lat: fix.point.lat,
+            lng: fix.point.lng,
+            time: fix.time,
+            speed_kmh: fix.speed_kmh,
+            course_deg: fix.course_deg,
+            altitude_m: fix.altitude_m,
+        };
+        Some((report, ReportSource::Nmea))
+    }
+}
+
+// ─── Configuration ──────────────────────────────────────────────
+
+/// A read-only APRS-IS feed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AprsIs {
+    /// `host:port`; the worldwide rotation when empty.
+    #[serde(default)]
+    pub server: Option<String>,
+    /// Our login. We never transmit, so no passcode is needed.
+    pub callsign: String,
+    /// Server-side filter, e.g. `r/-33.9/151.2/50`. Without one we ask for
+    /// the assigned callsigns.
+    #[serde(default)]
+    pub filter: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AvlConfig {
+    pub enabled: bool,
+    /// Where trackers send NMEA, APRS or JSON datagrams, e.g. `0.0.0.0:5010`.
+    pub udp: Option<String>,
+    /// Where a local gateway POSTs JSON reports, e.g. `127.0.0.1:8088`.
+    pub http: Option<String>,
+    /// Shared secret every UDP datagram and HTTP request must carry; the
+    /// listeners won't start without one.
+    #[serde(alias = "httpToken")]
+    pub token: Option<String>,
+    pub aprs_is: Option<AprsIs>,
+    /// Breadcrumbs older than this are deleted.
+    pub retention_days: u32,
+    /// Move resources on the shared map too, not just on this one.
+    pub sync_resources: bool,
+}
+
+impl Default for AvlConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            udp: None,
+            http: None,
+            token: None,
+            aprs_is: None,
+            retention_days: DEFAULT_RETENTION_DAYS,
+            sync_resources: true,
+        }
+    }
+}
+
+impl AvlConfig {
+    /// Blank fields mean "not configured".
+    fn normalized(mut self) -> Self {
+        let blank = |value: &mut Option<String>| {
+            if value.as_deref().is_some_and(|v| v.trim().is_empty()) {
+                *value = None;
+            }
+        };
+        blank(&mut self.udp);
+        blank(&mut self.http);
+        blank(&mut self.token);
+        if let Some(aprs) = &mut self.aprs_is {
+            blank(&mut aprs.server);
+            blank(&mut aprs.filter);
+        }
+        self
+    }
+
+    fn validate(&self) -> Result<()> {
+        for (name, bind) in [("UDP", &self.udp), ("HTTP", &self.http)] {
+            if let Some(bind) = bind {
+                bind.trim().parse::<SocketAddr>().map_err(|_| {
+                    Error::Invalid(format!("{name} listen address must be ip:port, not {bind}"))
+                })?;
+            }
+        }
+        if (self.udp.is_some() || self.http.is_some())
+            && self
+                .token
+                .as_deref()
+                .is_none_or(|token| token.trim().len() < MIN_TOKEN_LEN)
+        {
+            return Err(Error::Invalid(format!(
+                "UDP and HTTP listeners need a shared token of at least {MIN_TOKEN_LEN} characters"
+            )));
+        }
+        if let Some(aprs) = &self.aprs_is {
+            let call = aprs.callsign.trim();
+            if call.is_empty()
+                || call.len() > 9
+                || !call.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            {
+                return Err(Error::Invalid(
+                    "APRS-IS needs a callsign to log in with".into(),
+                ));
+            }
+        }
+        if !(1..=MAX_RETENTION_DAYS).contains(&self.retention_days) {
+            return Err(Error::Invalid(format!(
+                "keep tracks for 1 to {MAX_RETENTION_DAYS} days"
+            )));
+        }
+        Ok(())
+    }
+
+    fn listeners(&self) -> Vec<Listener> {
+        let mut listeners = Vec::new();
+        let token = self.token.as_deref().unwrap_or_default().trim().to_owned();
+        if let Some(bind) = &self.udp {
+            listeners.push(Listener::Udp {
+                bind: bind.trim().to_owned(),
+                token: token.clone(),
+            });
+        }
+        if let Some(bind) = &self.http {
+            listeners.push(Listener::Http {
+                bind: bind.trim().to_owned(),
+                token,
+            });
+        }
+        if let Some(aprs) = &self.aprs_is {
+            listeners.push(Listener::AprsIs(aprs.clone()));
+        }
+        listeners
+    }
+}
+
+fn config_path(app: &AppHandle) -> Result<PathBuf> {
+    Ok(app.path().app_config_dir()?.join(CONFIG_FILE))
+}
+
+fn load_config(app: &AppHandle) -> AvlConfig {
+    config_path(app)
+        .ok()
+        .and_then(|path| std::fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn save_config(app: &AppHandle, config: &AvlConfig) -> Result<()> {
+    let path = config_path(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(config)?)?;
+    std::fs::rename(tmp, path)?;
+    Ok(())
+}
+
+// ─── Service ────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ListenerKind {
+    Udp,
+    Http,
+    AprsIs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ListenerState {
+    Connecting,
+    Listening,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListenerStatus {
+    pub kind: ListenerKind,
+    pub address: String,
+    pub state: ListenerState,
+    pub error: Option<String>,
+    /// Reports received since the listener started.
+    pub reports: u64,
+    pub last_report_at: Option<String>,
+}
+
+/// A tracker we hear from that is not assigned to a unit.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnknownDevice {
+    pub device_id: String,
+    pub source: ReportSource,
+    pub point: Point,
+    pub last_seen: String,
+    pub reports: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AvlStatus {
+    pub enabled: bool,
+    pub listeners: Vec<ListenerStatus>,
+    pub unassigned: Vec<UnknownDevice>,
+}
+
+enum Listener {
+    Udp { bind: String, token: String },
+    Http { bind: String, token: String },
+    AprsIs(AprsIs),
+}
+
+impl Listener {
+    fn status(&self) -> ListenerStatus {
+        let (kind, address) = match self {
+            Listener::Udp { bind, .. } => (ListenerKind::Udp, bind.clone()),
+            Listener::Http { bind, .. } => (ListenerKind::Http, bind.clone()),
+            Listener::AprsIs(aprs) => (
+                ListenerKind::AprsIs,
+                aprs.server.clone().unwrap_or_else(|| APRS_IS_SERVER.into()),
+            ),
+        };
+        ListenerStatus {
+            kind,
+            address,
+            state: ListenerState::Connecting,
+            error: None,
+            reports: 0,
+            last_report_at: None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    config: AvlConfig,
+    stop: Option<Arc<AtomicBool>>,
+    listeners: Vec<ListenerStatus>,
+    unassigned: BTreeMap<String, UnknownDevice>,
+    /// Last breadcrumb per unit, to thin out chatty trackers.
+    last: HashMap<(SubjectKind, String), (Point, Instant)>,
+    /// When each resource's shared record was last moved.
+    synced: HashMap<String, Instant>,
+}
+
+/// Listeners for tracker reports, and what they have heard.
+#[derive(Default)]
+pub struct Avl {
+    inner: Mutex<Inner>,
+}
+
+impl Avl {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn status(&self) -> AvlStatus {
+        let inner = self.lock();
+        let mut unassigned: Vec<UnknownDevice> = inner.unassigned.values().cloned().collect();
+        unassigned.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+        AvlStatus {
+            enabled: inner.config.enabled,
+            listeners: inner.listeners.clone(),
+            unassigned,
+        }
+    }
+
+    fn emit_status(&self, app: &AppHandle) {
+        let _ = app.emit(STATUS_EVENT, self.status());
+    }
+
+    /// Update one listener, unless it belongs to a generation that has
+    /// since been stopped.
+    fn update_listener(
+        &self,
+        stop: &Arc<AtomicBool>,
+        index: usize,
+        update: impl FnOnce(&mut ListenerStatus),
+    ) -> bool {
+        let mut inner = self.lock();
+        if !inner.stop.as_ref().is_some_and(|s| Arc::ptr_eq(s, stop)) {
+            return false;
+        }
+        inner.listeners.get_mut(index).map(update).is_some()
+    }
+
+    fn heard_unknown(&self, app: &AppHandle, device_id: &str, source: ReportSource, point: Point) {
+        let added = {
+            let mut inner = self.lock();
+            let now = timestamp(Utc::now());
+            match inner.unassigned.get_mut(device_id) {
+                Some(device) => {
+                    device.point = point;
+                    device.source = source;
+                    device.last_seen = now;
+                    device.reports += 1;
+                    false
+                }
+                None => {
+                    if inner.unassigned.len() >= MAX_DEVICES {
+                        let oldest = inner
+                            .unassigned
+                            .values()
+                            .min_by(|a, b| a.last_seen.cmp(&b.last_seen))
+                            .map(|d| d.device_id.clone());
+                        if let Some(oldest) = oldest {
+                            inner.unassigned.remove(&oldest);
+                        }
+                    }
+                    inner.unassigned.insert(
+                        device_id.to_owned(),
+                        UnknownDevice {
+                            device_id: device_id.to_owned(),
+                            source,
+                            point,
+                            last_seen: now,
+                            reports: 1,
+                        },
+                    );
+                    true
+                }
+            }
+        };
+        if added {
+            self.emit_status(app);
+        }
+    }
+
+    /// Whether a report adds to the unit's track: enough time has passed,
+    /// or it has moved.
+    fn worth_keeping(&self, kind: SubjectKind, id: &str, point: Point) -> bool {
+        let mut inner = self.lock();
+        let key = (kind, id.to_owned());
+        if let Some((last, at)) = inner.last.get(&key) {
+            if at.elapsed() < MIN_SPACING && geo::distance_km(*last, point) * 1000.0 < MIN_MOVE_M {
+                return false;
+            }
+        }
+        inner.last.insert(key, (point, Instant::now()));
+        true
+    }
+
+    fn due_for_sync(&self, resource_id: &str) -> bool {
+        let mut inner = self.lock();
+        if !inner.config.sync_resources
+            || inner
+                .synced
+                .get(resource_id)
+                .is_some_and(|at| at.elapsed() < SYNC_INTERVAL)
+        {
+            return false;
+        }
+        inner.synced.insert(resource_id.to_owned(), Instant::now());
+        true
+    }
+
+    fn stop(&self, app: &AppHandle) {
+        {
+            let mut inner = self.lock();
+            if let Some(stop) = inner.stop.take() {
+                stop.store(true, Ordering::Release);
+            }
+            inner.listeners.clear();
+        }
+        self.emit_status(app);
+    }
+
+    fn start(&self, app: &AppHandle, config: AvlConfig) {
+        self.stop(app);
+        let listeners = if config.enabled {
+            config.listeners()
+        } else {
+            Vec::new()
+        };
+        let stop = Arc::new(AtomicBool::new(false));
+        {
+            let mut inner = self.lock();
+            inner.config = config;
+            inner.listeners = listeners.iter().map(Listener::status).collect();
+            if !listeners.is_empty() {
+                inner.stop = Some(stop.clone());
+            }
+        }
+        for (index, listener) in listeners.into_iter().enumerate() {
+            let ctx = Ctx {
+                app: app.clone(),
+                stop: stop.clone(),
+                index,
+            };
+            std::thread::spawn(move || run(&ctx, &listener));
+        }
+        self.emit_status(app);
+    }
+}
+
+/// Store a report against its unit and publish it. The unit is `unit` when
+/// the app itself names it, otherwise the one the device is assigned to.
+/// `None` when the device is not assigned to a unit yet, or the report adds
+/// nothing to the track.
+pub fn ingest(
+    app: &AppHandle,
+    report: Report,
+    source: ReportSource,
+    unit: Option<(SubjectKind, String)>,
+) -> Result<Option<Position>> {
+    let point = Point {
+        lat: report.lat,
+        lng: report.lng,
+    };
+    point.validate()?;
+    let now = Utc::now();
+    let reported_at = report
+        .time
+        .as_deref()
+        .and_then(parse_time)
+        .filter(|t| (*t - now).num_seconds() <= MAX_CLOCK_SKEW_S)
+        .unwrap_or(now);
+    let device_id = report
+        .device_id
+        .as_deref()
+        .map(normalize_device)
+        .filter(|d| !d.is_empty());
+
+    let avl = app.state::<Avl>();
+    let db = app.state::<Db>();
+    let (kind, subject_id) = match unit {
+        Some(subject) => subject,
+        None => {
+            let Some(device) = device_id.as_deref() else {
+                return Err(Error::Invalid("a report needs a deviceId".into()));
+            };
+            match db.with(|conn| unit_for(conn, device))? {
+                Some(subject) => subject,
+                None => {
+                    avl.heard_unknown(app, device, source, point);
+                    return Ok(None);
+                }
+            }
+        }
+    };
+    if !avl.worth_keeping(kind, &subject_id, point) {
+        return Ok(None);
+    }
+
+    let position = db.with(|conn| {
+        let position = Position {
+            label: label(conn, kind, &subject_id)?,
+            subject_kind: kind,
+            subject_id,
+            device_id,
+            point,
+            speed_kmh: report.speed_kmh,
+            course_deg: report.course_deg,
+            altitude_m: report.altitude_m,
+            source,
+            reported_at: timestamp(reported_at),
+            received_at: timestamp(now),
+        };
+        insert(conn, &position)?;
+        Ok(position)
+    })?;
+    let _ = app.emit(POSITION_EVENT, &position);
+
+    if kind == SubjectKind::Resource && avl.due_for_sync(&position.subject_id) {
+        if let Err(e) = sync_resource(app, &position) {
+            eprintln!("[avl] Could not move {}: {e}", position.subject_id);
+        }
+    }
+    Ok(Some(position))
+}
+
+/// Queue the new position for the shared resource record, and check it
+/// against its staging area.
+fn sync_resource(app: &AppHandle, position: &Position) -> Result<()> {
+    let id = &position.subject_id;
+    let (record, remaining) = app.state::<Db>().with(|conn| {
+        offline_queue::enqueue(
+            conn,
+            NewMutation {
+                table: "resources".into(),
+                operation: Operation::Update,
+                payload: json!({
+                    "id": id,
+                    "latitude": position.point.lat,
+                    "longitude": position.point.lng,
+                }),
+                base_updated_at: None,
+                base: None,
+                // A newer fix is always right
+                strategy: Some(Strategy::LastWriterWins),
+            },
+        )?;
+        let mut record = mirror::get(conn, "resources", id)?.unwrap_or_else(|| json!({ "id": id }));
+        record["latitude"] = json!(position.point.lat);
+        record["longitude"] = json!(position.point.lng);
+        Ok((record, offline_queue::count(conn)?))
+    })?;
+    let _ = app.emit(offline_queue::CHANGED_EVENT, remaining);
+    geofence::check(app, "resources", &record)?;
+    Ok(())
+}
+
+// ─── Listeners ──────────────────────────────────────────────────
+
+/// What a listener thread needs to report back.
+struct Ctx {
+    app: AppHandle,
+    stop: Arc<AtomicBool>,
+    index: usize,
+}
+
+impl Ctx {
+    fn stopped(&self) -> bool {
+        self.stop.load(Ordering::Acquire)
+    }
+
+    fn set_state(&self, state: ListenerState, error: Option<String>) {
+        let avl = self.app.state::<Avl>();
+        let changed = avl.update_listener(&self.stop, self.index, |listener| {
+            listener.state = state;
+            listener.error = error;
+        });
+        if changed {
+            avl.emit_status(&self.app);
+        }
+    }
+
+    fn report(&self, report: Report, source: ReportSource) -> Result<Option<Position>> {
+        self.app
+            .state::<Avl>()
+            .update_listener(&self.stop, self.index, |listener| {
+                listener.reports += 1;
+                listener.last_report_at = Some(timestamp(Utc::now()));
+            });
+        ingest(&self.app, report, source, None)
+    }
+
+    /// Sleep in steps so a stop takes effect promptly.
+    fn sleep(&self, duration: Duration) {
+        let until = Instant::now() + duration;
+        while Instant::now() < until && !self.stopped() {
+            std::thread::sleep(Duration::from_millis(250));
+        }
+    }
+}
+
+/// Keep one listener up until stopped.
+fn run(ctx: &Ctx, listener: &Listener) {
+    while !ctx.stopped() {
+        ctx.set_state(ListenerState::Connecting, None);
+        let result = match listener {
+            Listener::Udp { bind, token } => listen_udp(ctx, bind, token),
+            Listener::Http { bind, token } => listen_http(ctx, bind, token),
+            Listener::AprsIs(aprs) => listen_aprs_is(ctx, aprs),
+        };
+        if ctx.stopped() {
+            return;
+        }
+        if let Err(e) = result {
+            eprintln!("[avl] {}: {e}", listener.status().address);
+            ctx.set_state(ListenerState::Error, Some(e.to_string()));
+        }
+        ctx.sleep(RETRY_DELAY);
+    }
+}
+
+fn log_dropped(result: Result<Option<Position>>) {
+    if let Err(e) = result {
+        eprintln!("[avl] Dropped a report: {e}");
+    }
+}
+
+/// Compare a presented token without leaking how much of it matched.
+fn token_matches(expected: &str, presented: &str) -> bool {
+    expected.len() == presented.len()
+        && expected
+            .bytes()
+            .zip(presented.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Every datagram starts with a line holding the shared token; the rest
+/// are reports.
+fn listen_udp(ctx: &Ctx, bind: &str, token: &str) -> Result<()> {
+    let socket = UdpSocket::bind(bind)?;
+    socket.set_read_timeout(Some(POLL_INTERVAL))?;
+    ctx.set_state(ListenerState::Listening, None);
+    let mut decoder = Decoder::default();
+    let mut buf = vec![0u8; 65_535];
+    while !ctx.stopped() {
+        let (len, peer) = match socket.recv_from(&mut buf) {
+            Ok(received) => received,
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let text = String::from_utf8_lossy(&buf[..len]);
+        let peer = peer.ip().to_string();
+        let mut lines = text.lines();
+        if !lines
+            .next()
+            .is_some_and(|line| token_matches(token, line.trim()))
+        {
+            eprintln!("[avl] Dropped a datagram from {peer} without the token");
+            continue;
+        }
+        for line in lines {
+            if let Some((report, source)) = decoder.decode(line, &peer) {
+                log_dropped(ctx.report(report, source));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn listen_aprs_is(ctx: &Ctx, aprs: &AprsIs) -> Result<()> {
+    let server = aprs.server.as_deref().unwrap_or(APRS_IS_SERVER);
+    let address = server
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| Error::Invalid(format!("{server} did not resolve")))?;
+    let mut stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?;
+    stream.set_read_timeout(Some(POLL_INTERVAL))?;
+
+    let filter = match &aprs.filter {
+        Some(filter) => filter.trim().to_owned(),
+        // Buddy list of everything we track by callsign
+        None => {
+            let calls = ctx.app.state::<Db>().with(|conn| units(conn))?;
+            let calls: Vec<String> = calls.into_iter().map(|u| u.device_id).collect();
+            format!("b/{}", calls.join("/"))
+        }
+    };
+    write!(
+        stream,
+        "user {} pass -1 vers {} {} filter {filter}\r\n",
+        normalize_device(&aprs.callsign),
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+    )?;
+    ctx.set_state(ListenerState::Listening, None);
+
+    let mut reader = BufReader::new(stream);
+    let mut line = Vec::new();
+    let mut heard = Instant::now();
+    while !ctx.stopped() {
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => return Err(Error::Invalid(format!("{server} closed the connection"))),
+            Ok(_) => {}
+            // Keep the partial line; the rest arrives with the next read
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                if heard.elapsed() > APRS_IS_SILENCE {
+                    return Err(Error::Invalid(format!("{server} went quiet")));
+                }
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        }
+        heard = Instant::now();
+        let text = String::from_utf8_lossy(&line).trim().to_owned();
+        line.clear();
+        // Server comments and keepalives
+        if text.starts_with('#') {
+            continue;
+        }
+        if let Some(position) = parse_aprs(&text) {
+            log_dropped(ctx.report(position.into(), ReportSource::Aprs));
+        }
+    }
+    Ok(())
+}
+
+// ─── HTTP gateway ───────────────────────────────────────────────
+
+struct Request {
+    method: String,
+    path: String,
+    authorization: Option<String>,
+    body: Vec<u8>,
+}
+
+/// Status code and JSON body.
+type Reply = (u16, Value);
+
+fn failure(status: u16, message: &str) -> Reply {
+    (status, json!({ "error": message }))
+}
+
+/// One POST at a time is plenty for a local gateway.
+fn listen_http(ctx: &Ctx, bind: &str, token: &str) -> Result<()> {
+    let listener = TcpListener::bind(bind)?;
+    listener.set_nonblocking(true)?;
+    ctx.set_state(ListenerState::Listening, None);
+    let mut decoder = Decoder::default();
+    while !ctx.stopped() {
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                if let Err(e) = serve(ctx, stream, &peer.ip().to_string(), token, &mut decoder) {
+                    eprintln!("[avl] Request from {peer} failed: {e}");
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => std::thread::sleep(POLL_INTERVAL),
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(())
+}
+
+fn serve(
+    ctx: &Ctx,
+    mut stream: TcpStream,
+    peer: &str,
+    token: &str,
+    decoder: &mut Decoder,
+) -> Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(HTTP_TIMEOUT))?;
+    stream.set_write_timeout(Some(HTTP_TIMEOUT))?;
+    let reply = match read_request(&stream) {
+        Ok(request) => handle(ctx, &request, peer, token, decoder),
+        Err(reply) => reply,
+    };
+    respond(&mut stream, reply)
+}
+
+fn read_request(stream: &TcpStream) -> std::result::Result<Request, Reply> {
+    let unreadable = |_| failure(400, "unreadable request");
+    let mut reader = BufReader::new(stream).take(MAX_HEAD);
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(unreadable)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(failure(400, "malformed request line"));
+    };
+    let method = method.to_owned();
+    let path = target.split('?').next().unwrap_or_default().to_owned();
+
+    let mut content_length = 0;
+    let mut authorization = None;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).map_err(unreadable)? == 0 {
+            return Err(failure(431, "headers too long or cut short"));
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            return Err(failure(400, "malformed header"));
+        };
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => {
+                content_length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| failure(400, "bad Content-Length"))?;
+            }
+            "authorization" => authorization = Some(value.trim().to_owned()),
+            "transfer-encoding" => {
+                return Err(failure(411, "send a Content-Length instead of chunks"));
+            }
+            _ => {}
+        }
+    }
+    if content_length > MAX_BODY {
+        return Err(failure(413, "too many reports in one request"));
+    }
+    let mut body = vec![0; content_length];
+    reader
+        .into_inner()
+        .read_exact(&mut body)
+        .map_err(|_| failure(400, "body shorter than Content-Length"))?;
+    Ok(Request {
+        method,
+        path,
+        authorization,
+        body,
+    })
+}
+
+fn handle(ctx: &Ctx, request: &Request, peer: &str, token: &str, decoder: &mut Decoder) -> Reply {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/health") => return (200, json!({ "status": "ok" })),
+        ("POST", "/positions") => {}
+        (_, "/positions" | "/health") => return failure(405, "method not allowed"),
+        _ => return failure(404, "POST reports to /positions"),
+    }
+    let presented = request
+        .authorization
+        .as_deref()
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if !presented.is_some_and(|presented| token_matches(token, presented.trim())) {
+        return failure(401, "missing or wrong bearer token");
+    }
+    let Ok(text) = std::str::from_utf8(&request.body) else {
+        return failure(400, "body is not UTF-8");
+    };
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Batch {
+        One(Report),
+        Many(Vec<Report>),
+    }
+
+    let text = text.trim();
+    let reports: Vec<(Report, ReportSource)> = if text.starts_with(['{', '[']) {
+        let batch = match serde_json::from_str::<Batch>(text) {
+            Ok(Batch::One(report)) => vec![report],
+            Ok(Batch::Many(reports)) => reports,
+            Err(e) => return failure(400, &e.to_string()),
+        };
+        batch
+            .into_iter()
+            .map(|mut report| {
+                if report.device_id.is_none() {
+                    report.device_id = Some(peer.to_owned());
+                }
+                (report, ReportSource::Json)
+            })
+            .collect()
+    } else {
+        // NMEA sentences or APRS packets, one per line
+        text.lines()
+            .filter_map(|line| decoder.decode(line, peer))
+            .collect()
+    };
+
+    let (mut accepted, mut ignored, mut errors) = (0, 0, Vec::new());
+    for (report, source) in reports {
+        match ctx.report(report, source) {
+            Ok(Some(_)) => accepted += 1,
+            Ok(None) => ignored += 1,
+            Err(e) => errors.push(e.to_string()),
+        }
+    }
+    let status = if accepted + ignored == 0 && !errors.is_empty() {
+        400
+    } else {
+        202
+    };
+    (
+        status,
+        json!({ "accepted": accepted, "ignored": ignored, "errors": errors }),
+    )
+}
+
+fn respond(stream: &mut TcpStream, (status, body): Reply) -> Result<()> {
+    let body = serde_json::to_vec(&body)?;
+    let reason = match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        _ => "Error",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    stream.write_all(&body)?;
+    Ok(())
+}
+
+// ─── Startup ────────────────────────────────────────────────────
+
+/// Start the saved listeners, if tracking was on when the app closed.
+pub fn start_saved(app: &AppHandle) {
+    let config = load_config(app);
+    if config.validate().is_ok() {
+        app.state::<Avl>().start(app, config);
+    }
+}
+
+/// Drop breadcrumbs past the retention period, hourly.
+pub fn spawn_pruner(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        let retention = app.state::<Avl>().lock().config.retention_days;
+        match app.state::<Db>().with(|conn| prune(conn, retention)) {
+            Ok(0) | Err(Error::Locked) => {}
+            Ok(n) => eprintln!("[avl] Pruned {n} breadcrumbs older than {retention} days"),
+            Err(e) => eprintln!("[avl] Pruning failed: {e}"),
+        }
+        std::thread::sleep(PRUNE_INTERVAL);
+    });
+}
+
+// ─── Commands ───────────────────────────────────────────────────
+
+#[tauri::command]
+pub fn avl_status(avl: State<'_, Avl>) -> AvlStatus {
+    avl.status()
+}
+
+#[tauri::command]
+pub fn avl_config(app: AppHandle) -> AvlConfig {
+    load_config(&app)
+}
+
+/// Save the listener settings and restart them.
+#[tauri::command]
+pub fn avl_configure(app: AppHandle, avl: State<'_, Avl>, config: AvlConfig) -> Result<AvlStatus> {
+    let config = config.normalized();
+    config.validate()?;
+    save_config(&app, &config)?;
+    avl.start(&app, config);
+    Ok(avl.status())
+}
+
+#[tauri::command]
+pub fn avl_units(db: State<'_, Db>) -> Result<Vec<Unit>> {
+    db.with(|conn| units(conn))
+}
+
+/// Assign a tracker to a resource or team, replacing any earlier
+/// assignment.
+#[tauri::command]
+pub fn avl_unit_assign(
+    app: AppHandle,
+    db: State<'_, Db>,
+    avl: State<'_, Avl>,
+    unit: NewUnit,
+) -> Result<Unit> {
+    let device_id = normalize_device(&unit.device_id);
+    if device_id.is_empty() || unit.subject_id.trim().is_empty() {
+        return Err(Error::Invalid(
+            "a tracker needs a device id and a unit".into(),
+        ));
+    }
+    let label = unit.label.filter(|l| !l.trim().is_empty());
+    let assigned = db.with(|conn| {
+        conn.execute(
+            "INSERT INTO avl_units (device_id, subject_kind, subject_id, label, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT (device_id) DO UPDATE SET
+                subject_kind = excluded.subject_kind,
+                subject_id = excluded.subject_id,
+                label = excluded.label",
+            params![
+                device_id,
+                unit.subject_kind.as_str(),
+                unit.subject_id.trim(),
+                label,
+                timestamp(Utc::now()),
+            ],
+        )?;
+        conn.query_row(
+            "SELECT * FROM avl_units WHERE device_id = ?1",
+            [&device_id],
+            unit_from_row,
+        )
+        .map_err(Error::from)
+    })?;
+    avl.lock().unassigned.remove(&device_id);
+    avl.emit_status(&app);
+    Ok(assigned)
+}
+
+#[tauri::command]
+pub fn avl_unit_remove(db: State<'_, Db>, device_id: String) -> Result<()> {
+    let removed = db.with(|conn| {
+        Ok(conn.execute(
+            "DELETE FROM avl_units WHERE device_id = ?1",
+            [normalize_device(&device_id)],
+        )?)
+    })?;
+    if removed == 0 {
+        return Err(Error::NotFound(format!("tracker {device_id}")));
+    }
+    Ok(())
+}
+
+/// A report entered in the app, which may name the unit directly.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManualReport {
+    #[serde(flatten)]
+    pub report: Report,
+    #[serde(default)]
+    pub resource_id: Option<String>,
+    #[serde(default)]
+    pub team_id: Option<String>,
+}
+
+/// Record a position reported from within the app, e.g. relayed by radio.
+#[tauri::command]
+pub async fn avl_report(app: AppHandle, report: ManualReport) -> Result<Option<Position>> {
+    let unit = match (report.resource_id, report.team_id) {
+        (Some(id), _) => Some((SubjectKind::Resource, id)),
+        (None, Some(id)) => Some((SubjectKind::Team, id)),
+        (None, None) => None,
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        ingest(&app, report.report, ReportSource::Manual, unit)
+    })
+    .await?
+}
+
+/// Where each unit was last reported, within `maxAgeMinutes` (a day by
+/// default).
+#[tauri::command]
+pub fn avl_latest(db: State<'_, Db>, max_age_minutes: Option<u32>) -> Result<Vec<Position>> {
+    let minutes = max_age_minutes.unwrap_or(DEFAULT_LATEST_MINUTES);
+    let since = Utc::now() - chrono::Duration::minutes(i64::from(minutes));
+    db.with(|conn| {
+        // SQLite takes the bare columns from the row holding the MAX
+        let mut stmt = conn.prepare(
+            "SELECT *, MAX(reported_at) FROM avl_positions
+             WHERE reported_at >= ?1
+             GROUP BY subject_kind, subject_id",
+        )?;
+        let rows = stmt.query_map([timestamp(since)], position_from_row)?;
+        let mut positions = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+        for position in &mut positions {
+            position.label = label(conn, position.subject_kind, &position.subject_id)?;
+        }
+        Ok(positions)
+    })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackQuery {
+    pub subject_kind: SubjectKind,
+    pub subject_id: String,
+    /// RFC 3339 bounds; the whole retained history when omitted.
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub to: Option<String>,
+}
+
+/// A unit's breadcrumbs in time order, for playback.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Track {
+    pub subject_kind: SubjectKind,
+    pub subject_id: String,
+    pub label: Option<String>,
+    pub positions: Vec<Position>,
+    pub distance_km: f64,
+    pub max_speed_kmh: Option<f64>,
+    /// True when the range held more than the points returned.
+    pub truncated: bool,
+}
+
+fn bound(value: Option<&str>, fallback: &str) -> Result<String> {
+    match value {
+        Some(value) => parse_time(value)
+            .map(timestamp)
+            .ok_or_else(|| Error::Invalid(format!("{value} is not an RFC 3339 time"))),
+        None => Ok(fallback.to_owned()),
+    }
+}
+
+#[tauri::command]
+pub fn avl_track(db: State<'_, Db>, query: TrackQuery) -> Result<Track> {
+    // Timestamps compare as text; these sort before and after any of them
+    let from = bound(query.from.as_deref(), "")?;
+    let to = bound(query.to.as_deref(), "~")?;
+    db.with(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT * FROM avl_positions
+             WHERE subject_kind = ?1 AND subject_id = ?2
+               AND reported_at >= ?3 AND reported_at <= ?4
+             ORDER BY reported_at
+             LIMIT ?5",
+        )?;
+        let rows = stmt.query_map(
+            params![
+                query.subject_kind.as_str(),
+                query.subject_id,
+                from,
+                to,
+                MAX_TRACK_POINTS + 1
+            ],
+            position_from_row,
+        )?;
+        let mut positions = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+        let truncated = positions.len() > MAX_TRACK_POINTS as usize;
+        positions.truncate(MAX_TRACK_POINTS as usize);
+
+        let distance_km = positions
+            .windows(2)
+            .map(|pair| geo::distance_km(pair[0].point, pair[1].point))
+            .sum();
+        let max_speed_kmh = positions
+            .iter()
+            .filter_map(|p| p.speed_kmh)
+            .reduce(f64::max);
+        Ok(Track {
+            label: label(conn, query.subject_kind, &query.subject_id)?,
+            subject_kind: query.subject_kind,
+            subject_id: query.subject_id,
+            positions,
+            distance_km,
+            max_speed_kmh,
+            truncated,
+        })
+    })
+}
+
+/// A unit with breadcrumbs on record.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackedUnit {
+    pub subject_kind: SubjectKind,
+    pub subject_id: String,
+    pub label: Option<String>,
+    pub first_at: String,
+    pub last_at: String,
+    pub points: u32,
+}
+
+/// Every unit with a track, for picking one to play back.
+#[tauri::command]
+pub fn avl_tracked(db: State<'_, Db>) -> Result<Vec<TrackedUnit>> {
+    db.with(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT subject_kind, subject_id, MIN(reported_at), MAX(reported_at), COUNT(*)
+             FROM avl_positions
+             GROUP BY subject_kind, subject_id
+             ORDER BY MAX(reported_at) DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let kind: String = row.get(0)?;
+            Ok(TrackedUnit {
+                subject_kind: SubjectKind::parse(&kind).unwrap_or(SubjectKind::Resource),
+                subject_id: row.get(1)?,
+                label: None,
+                first_at: row.get(2)?,
+                last_at: row.get(3)?,
+                points: row.get(4)?,
+            })
+        })?;
+        let mut tracked = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+        for unit in &mut tracked {
+            unit.label = label(conn, unit.subject_kind, &unit.subject_id)?;
+        }
+        Ok(tracked)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn plain_position() {
+        let p = parse_aprs("n0call>APRS,WIDE1-1:!4903.50N/07201.75W>088/036/A=001234").unwrap();
+        assert_eq!(p.callsign, "N0CALL");
+        assert!(close(p.point.lat, 49.058_333));
+        assert!(close(p.point.lng, -72.029_166));
+        assert_eq!(p.course_deg, Some(88.0));
+        assert!(close(p.speed_kmh.unwrap(), 36.0 * KNOTS_TO_KMH));
+        assert!(close(p.altitude_m.unwrap(), 1234.0 * FEET_TO_M));
+    }
+
+    #[test]
+    fn timestamped_position() {
+        let p = parse_aprs("N0CALL>APRS:@092345z4903.50N/07201.75W>").unwrap();
+        assert!(close(p.point.lat, 49.058_333));
+    }
+
+    #[test]
+    fn compressed_position() {
+        let p = parse_aprs("N0CALL>APRS:!/5L!!<*e7>7P[").unwrap();
+        assert!(close(p.point.lat, 49.5));
+        assert!(close(p.point.lng, -72.75));
+        assert_eq!(p.course_deg, Some(88.0));
+        assert!((p.speed_kmh.unwrap() / KNOTS_TO_KMH - 36.2).abs() < 0.1);
+    }
+
+    #[test]
+    fn mic_e_position() {
+        let p = parse_aprs("N0CALL>S32U6T:`d#Nl0v>/").unwrap();
+        assert!(close(p.point.lat, 33.0 + 25.64 / 60.0));
+        assert!(close(p.point.lng, -(72.0 + 7.5 / 60.0)));
+        assert_eq!(p.course_deg, Some(90.0));
+        assert!(close(p.speed_kmh.unwrap(), 2.0 * KNOTS_TO_KMH));
+    }
+
+    #[test]
+    fn third_party_takes_the_inner_sender() {
+        let p = parse_aprs("IGATE>APRS:}W1AW>APRS,TCPIP,IGATE*:!4903.50N/07201.75W-").unwrap();
+        assert_eq!(p.callsign, "W1AW");
+    }
+
+    #[test]
+    fn deep_third_party_nesting_is_dropped() {
+        let wrap = |depth: usize| {
+            let mut packet = "W1AW>APRS:!4903.50N/07201.75W-".to_owned();
+            for _ in 0..depth {
+                packet = format!("IGATE>APRS:}}{packet}");
+            }
+            packet
+        };
+        assert!(parse_aprs(&wrap(MAX_THIRD_PARTY_DEPTH)).is_some());
+        assert!(parse_aprs(&wrap(MAX_THIRD_PARTY_DEPTH + 1)).is_none());
+        assert!(parse_aprs(&wrap(100_000)).is_none());
+    }
+
+    #[test]
+    fn bytes_below_base91_are_rejected() {
+        assert!(parse_aprs("N0CALL>APRS:!/5L!!<*e7>\x1fP[").is_none());
+        assert!(parse_aprs("N0CALL>APRS:!/5L!!<*e7>7\x1f[").is_none());
+        assert!(parse_aprs("N0CALL>APRS:!/5L\x1f!<*e7>7P[").is_none());
+        assert!(parse_aprs("N0CALL>S32U6T:`d#\x10l0v>/").is_none());
+    }
+
+    #[test]
+    fn non_positions_are_ignored() {
+        assert!(parse_aprs("N0CALL>APRS:>status text").is_none());
+        assert!(parse_aprs("N0CALL>APRS:!9903.50N/07201.75W-").is_none());
+        assert!(parse_aprs("NOT A PACKET").is_none());
+    }
+
+    #[test]
+    fn tokens_must_match_exactly() {
+        assert!(token_matches("s3cret-token", "s3cret-token"));
+        assert!(!token_matches("s3cret-token", "s3cret-toke"));
+        assert!(!token_matches("s3cret-token", "s3cret-tokem"));
+    }
+}
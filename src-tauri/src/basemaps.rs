@@ -0,0 +1,433 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::db::Db;
+use crate::error::{Error, Result};
+use crate::pmtiles::PmTiles;
+
+/// Scheme the webview loads basemap tiles from:
+/// `basemap://localhost/{id}/{z}/{x}/{y}` (`http://basemap.localhost/...` on
+/// Windows).
+pub const SCHEME: &str = "basemap";
+const BASEMAP_DIR: &str = "basemaps";
+
+pub const CHANGED_EVENT: &str = "basemaps://changed";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveKind {
+    Mbtiles,
+    Pmtiles,
+}
+
+impl ArchiveKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            ArchiveKind::Mbtiles => "mbtiles",
+            ArchiveKind::Pmtiles => "pmtiles",
+        }
+    }
+
+    fn parse(s: &str) -> Result<Self> {
+        match s {
+            "mbtiles" => Ok(ArchiveKind::Mbtiles),
+            "pmtiles" => Ok(ArchiveKind::Pmtiles),
+            other => Err(Error::Invalid(format!("unknown basemap kind {other}"))),
+        }
+    }
+
+    fn from_path(path: &Path) -> Result<Self> {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase);
+        match ext.as_deref() {
+            Some("mbtiles") => Ok(ArchiveKind::Mbtiles),
+            Some("pmtiles") => Ok(ArchiveKind::Pmtiles),
+            _ => Err(Error::Invalid(
+                "basemaps must be .mbtiles or .pmtiles files".into(),
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Basemap {
+    pub id: String,
+    pub name: String,
+    pub kind: ArchiveKind,
+    /// Tile image format: `png`, `jpg` or `webp`.
+    pub format: String,
+    pub min_zoom: u8,
+    pub max_zoom: u8,
+    /// `[west, south, east, north]`.
+    pub bounds: Option<[f64; 4]>,
+    pub attribution: Option<String>,
+    pub size_bytes: u64,
+    pub imported_at: String,
+    pub active: bool,
+}
+
+/// What an archive says about itself.
+struct Description {
+    name: Option<String>,
+    format: String,
+    min_zoom: u8,
+    max_zoom: u8,
+    bounds: Option<[f64; 4]>,
+    attribution: Option<String>,
+}
+
+// ─── Archives ───────────────────────────────────────────────────
+
+enum Archive {
+    MbTiles(Mutex<Connection>),
+    PmTiles(PmTiles),
+}
+
+impl Archive {
+    fn open(kind: ArchiveKind, path: &Path) -> Result<Self> {
+        Ok(match kind {
+            ArchiveKind::Mbtiles => Archive::MbTiles(Mutex::new(Connection::open_with_flags(
+                path,
+                OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+            )?)),
+            ArchiveKind::Pmtiles => Archive::PmTiles(PmTiles::open(path)?),
+        })
+    }
+
+    fn tile(&self, z: u8, x: u32, y: u32) -> Result<Option<Vec<u8>>> {
+        match self {
+            Archive::MbTiles(conn) => {
+                let conn = conn.lock().unwrap_or_else(|e| e.into_inner());
+                // MBTiles rows count from the bottom (TMS)
+                let row = (1u32 << z) - 1 - y;
+                Ok(conn
+                    .query_row(
+                        "SELECT tile_data FROM tiles
+                         WHERE zoom_level = ?1 AND tile_column = ?2 AND tile_row = ?3",
+                        params![z, x, row],
+                        |r| r.get(0),
+                    )
+                    .optional()?)
+            }
+            Archive::PmTiles(pm) => pm.tile(z, x, y),
+        }
+    }
+
+    fn describe(&self) -> Result<Description> {
+        match self {
+            Archive::MbTiles(conn) => {
+                let conn = conn.lock().unwrap_or_else(|e| e.into_inner());
+                let mut stmt = conn.prepare("SELECT name, value FROM metadata")?;
+                let meta: HashMap<String, String> = stmt
+                    .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?
+                    .collect::<rusqlite::Result<_>>()?;
+                let (min, max): (Option<u8>, Option<u8>) = conn.query_row(
+                    "SELECT min(zoom_level), max(zoom_level) FROM tiles",
+                    [],
+                    |r| Ok((r.get(0)?, r.get(1)?)),
+                )?;
+                let bounds = meta.get("bounds").and_then(|b| {
+                    let v: Vec<f64> = b.split(',').filter_map(|n| n.trim().parse().ok()).collect();
+                    <[f64; 4]>::try_from(v).ok()
+                });
+                Ok(Description {
+                    name: meta.get("name").cloned(),
+                    format: meta.get("format").cloned().unwrap_or_else(|| "png".into()),
+                    min_zoom: meta
+                        .get("minzoom")
+                        .and_then(|z| z.parse().ok())
+                        .or(min)
+                        .unwrap_or(0),
+                    max_zoom: meta
+                        .get("maxzoom")
+                        .and_then(|z| z.parse().ok())
+                        .or(max)
+                        .unwrap_or(0),
+                    bounds,
+                    attribution: meta.get("attribution").cloned(),
+                })
+            }
+            Archive::PmTiles(pm) => {
+                let meta = pm.metadata()?;
+                let text = |key: &str| meta.get(key).and_then(|v| v.as_str()).map(str::to_owned);
+                let format = match pm.header.tile_type {
+                    1 => "pbf",
+                    2 => "png",
+                    3 => "jpg",
+                    4 => "webp",
+                    5 => "avif",
+                    _ => "unknown",
+                };
+                Ok(Description {
+                    name: text("name"),
+                    format: format.into(),
+                    min_zoom: pm.header.min_zoom,
+                    max_zoom: pm.header.max_zoom,
+                    bounds: Some(pm.header.bounds),
+                    attribution: text("attribution"),
+                })
+            }
+        }
+    }
+}
+
+fn content_type(format: &str) -> &'static str {
+    match format {
+        "jpg" | "jpeg" => "image/jpeg",
+        "webp" => "image/webp",
+        _ => "image/png",
+    }
+}
+
+/// Archives opened so far, keyed by basemap id.
+#[derive(Default)]
+pub struct Basemaps {
+    open: Mutex<HashMap<String, Arc<Archive>>>,
+}
+
+impl Basemaps {
+    fn archive(&self, app: &AppHandle, basemap: &Basemap) -> Result<Arc<Archive>> {
+        let mut open = self.open.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(archive) = open.get(&basemap.id) {
+            return Ok(archive.clone());
+        }
+        let archive = Arc::new(Archive::open(
+            basemap.kind,
+            &archive_path(app, &basemap.id, basemap.kind)?,
+        )?);
+        open.insert(basemap.id.clone(), archive.clone());
+        Ok(archive)
+    }
+
+    fn forget(&self, id: &str) {
+        self.open
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(id);
+    }
+}
+
+fn archive_dir(app: &AppHandle) -> Result<PathBuf> {
+    let dir = app.path().app_data_dir()?.join(BASEMAP_DIR);
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn archive_path(app: &AppHandle, id: &str, kind: ArchiveKind) -> Result<PathBuf> {
+    Ok(archive_dir(app)?.join(format!("{id}.{}", kind.as_str())))
+}
+
+// ─── Storage ────────────────────────────────────────────────────
+
+const COLUMNS: &str = "id, name, kind, format, min_zoom, max_zoom, west, south, east, north,
+                       attribution, size_bytes, imported_at, active";
+
+fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Basemap> {
+    let kind: String = row.get(2)?;
+    let bounds: (Option<f64>, Option<f64>, Option<f64>, Option<f64>) =
+        (row.get(6)?, row.get(7)?, row.get(8)?, row.get(9)?);
+    Ok(Basemap {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        kind: ArchiveKind::parse(&kind).unwrap_or(ArchiveKind::Mbtiles),
+        format: row.get(3)?,
+        min_zoom: row.get(4)?,
+        max_zoom: row.get(5)?,
+        bounds: match bounds {
+            (Some(w), Some(s), Some(e), Some(n)) => Some([w, s, e, n]),
+            _ => None,
+        },
+        attribution: row.get(10)?,
+        size_bytes: row.get(11)?,
+        imported_at: row.get(12)?,
+        active: row.get(13)?,
+    })
+}
+
+fn insert(conn: &Connection, basemap: &Basemap) -> Result<()> {
+    let [w, s, e, n] = basemap.bounds.map_or([None; 4], |b| b.map(Some));
+    conn.execute(
+        &format!("INSERT INTO basemaps ({COLUMNS}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)"),
+        params![
+            basemap.id,
+            basemap.name,
+            basemap.kind.as_str(),
+            basemap.format,
+            basemap.min_zoom,
+            basemap.max_zoom,
+            w,
+            s,
+            e,
+            n,
+            basemap.attribution,
+            basemap.size_bytes,
+            basemap.imported_at,
+            basemap.active,
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn list(conn: &Connection) -> Result<Vec<Basemap>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {COLUMNS} FROM basemaps ORDER BY name COLLATE NOCASE"
+    ))?;
+    let rows = stmt.query_map([], from_row)?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+fn get(conn: &Connection, id: &str) -> Result<Basemap> {
+    conn.query_row(
+        &format!("SELECT {COLUMNS} FROM basemaps WHERE id = ?1"),
+        [id],
+        from_row,
+    )
+    .optional()?
+    .ok_or_else(|| Error::NotFound(format!("basemap {id}")))
+}
+
+/// Make `id` the basemap in use, or go back to the online OSM layer with `None`.
+fn set_active(conn: &mut Connection, id: Option<&str>) -> Result<()> {
+    let tx = conn.transaction()?;
+    tx.execute("UPDATE basemaps SET active = 0 WHERE active = 1", [])?;
+    if let Some(id) = id {
+        if tx.execute("UPDATE basemaps SET active = 1 WHERE id = ?1", [id])? == 0 {
+            return Err(Error::NotFound(format!("basemap {id}")));
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+// ─── Protocol ───────────────────────────────────────────────────
+
+fn respond(status: StatusCode, content_type: &str, body: Vec<u8>) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(body)
+        .unwrap_or_else(|_| Response::new(Vec::new()))
+}
+
+fn parse_request(path: &str) -> Option<(String, u8, u32, u32)> {
+    let mut parts = path.trim_matches('/').split('/');
+    let id = parts.next()?.to_owned();
+    let z: u8 = parts.next()?.parse().ok()?;
+    let x: u32 = parts.next()?.parse().ok()?;
+    let y: u32 = parts.next()?.split('.').next()?.parse().ok()?;
+    let n = 1u32.checked_shl(u32::from(z))?;
+    (parts.next().is_none() && x < n && y < n).then_some((id, z, x, y))
+}
+
+/// Serve `basemap://localhost/{id}/{z}/{x}/{y}` from an imported archive.
+pub fn serve(app: &AppHandle, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let Some((id, z, x, y)) = parse_request(request.uri().path()) else {
+        return respond(StatusCode::BAD_REQUEST, "text/plain", Vec::new());
+    };
+    let tile = app
+        .state::<Db>()
+        .with(|conn| get(conn, &id))
+        .and_then(|basemap| {
+            let archive = app.state::<Basemaps>().archive(app, &basemap)?;
+            Ok((basemap.format, archive.tile(z, x, y)?))
+        });
+    match tile {
+        Ok((format, Some(bytes))) => respond(StatusCode::OK, content_type(&format), bytes),
+        Ok((_, None)) => respond(StatusCode::NOT_FOUND, "text/plain", Vec::new()),
+        Err(e) => {
+            eprintln!("[basemaps] Could not serve {id}/{z}/{x}/{y}: {e}");
+            respond(StatusCode::INTERNAL_SERVER_ERROR, "text/plain", Vec::new())
+        }
+    }
+}
+
+// ─── Commands ───────────────────────────────────────────────────
+
+/// Copy an MBTiles or PMTiles archive into app data and register it.
+#[tauri::command]
+pub async fn basemap_import(app: AppHandle, path: String, name: Option<String>) -> Result<Basemap> {
+    let source = PathBuf::from(path);
+    let kind = ArchiveKind::from_path(&source)?;
+    let id = uuid::Uuid::new_v4().to_string();
+    let target = archive_path(&app, &id, kind)?;
+
+    // Archives run to gigabytes; keep the copy off the main thread
+    let basemap = tauri::async_runtime::spawn_blocking(move || -> Result<Basemap> {
+        let description = Archive::open(kind, &source)?.describe()?;
+        if !["png", "jpg", "jpeg", "webp"].contains(&description.format.as_str()) {
+            return Err(Error::Invalid(format!(
+                "{} tiles are not supported; only raster (PNG, JPEG, WebP) basemaps are",
+                description.format
+            )));
+        }
+        let size_bytes = std::fs::copy(&source, &target)?;
+        let fallback_name = source
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "Basemap".into());
+        Ok(Basemap {
+            id,
+            name: name.or(description.name).unwrap_or(fallback_name),
+            kind,
+            format: description.format,
+            min_zoom: description.min_zoom,
+            max_zoom: description.max_zoom,
+            bounds: description.bounds,
+            attribution: description.attribution,
+            size_bytes,
+            imported_at: chrono::Utc::now().to_rfc3339(),
+            active: false,
+        })
+    })
+    .await??;
+
+    if let Err(e) = app.state::<Db>().with(|conn| insert(conn, &basemap)) {
+        let _ = std::fs::remove_file(archive_path(&app, &basemap.id, kind)?);
+        return Err(e);
+    }
+    let _ = app.emit(CHANGED_EVENT, ());
+    Ok(basemap)
+}
+
+#[tauri::command]
+pub fn basemaps_list(db: State<'_, Db>) -> Result<Vec<Basemap>> {
+    db.with(|conn| list(conn))
+}
+
+#[tauri::command]
+pub fn basemap_activate(app: AppHandle, db: State<'_, Db>, id: Option<String>) -> Result<()> {
+    db.with(|conn| set_active(conn, id.as_deref()))?;
+    let _ = app.emit(CHANGED_EVENT, ());
+    Ok(())
+}
+
+#[tauri::command]
+pub fn basemap_remove(
+    app: AppHandle,
+    db: State<'_, Db>,
+    basemaps: State<'_, Basemaps>,
+    id: String,
+) -> Result<()> {
+    let basemap = db.with(|conn| get(conn, &id))?;
+    db.with(|conn| {
+        conn.execute("DELETE FROM basemaps WHERE id = ?1", [&id])?;
+        Ok(())
+    })?;
+    basemaps.forget(&id);
+    let path = archive_path(&app, &id, basemap.kind)?;
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    let _ = app.emit(CHANGED_EVENT, ());
+    Ok(())
+}
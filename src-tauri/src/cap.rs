@@ -0,0 +1,1531 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, FixedOffset, SecondsFormat, Utc};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_dialog::DialogExt;
+
+use crate::cap_feed::CapWatcher;
+use crate::db::Db;
+use crate::error::{Error, Result};
+use crate::geo::{self, Point};
+use crate::geofence::{self, FenceKind, Shape};
+use crate::interchange::{self, escape, ExportReport, Node};
+use crate::mirror;
+
+/// OASIS Common Alerting Protocol 1.2.
+pub const NAMESPACE: &str = "urn:oasis:names:tc:emergency:cap:1.2";
+const OTHER_VERSIONS: &str = "urn:oasis:names:tc:emergency:cap:";
+
+const CONFIG_FILE: &str = "cap.json";
+const DEFAULT_LANGUAGE: &str = "en-US";
+const DEFAULT_POLL_MINUTES: u32 = 5;
+const MAX_POLL_MINUTES: u32 = 24 * 60;
+const MAX_FEEDS: usize = 20;
+const MAX_REGION_KM: f64 = 5000.0;
+const SEVERITIES: &[&str] = &["low", "medium", "high", "critical"];
+/// A CAP file holds one message; anything this big is something else.
+const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// CAP asks for headlines that fit a text message.
+const MAX_HEADLINE_CHARS: usize = 160;
+
+// ─── Schema ─────────────────────────────────────────────────────
+
+/// Child elements, in the order the schema's sequences require.
+const ALERT_ELEMENTS: &[&str] = &[
+    "identifier",
+    "sender",
+    "sent",
+    "status",
+    "msgType",
+    "source",
+    "scope",
+    "restriction",
+    "addresses",
+    "code",
+    "note",
+    "references",
+    "incidents",
+    "info",
+];
+/// Elements from other namespaces the schema lets a signed or encrypted
+/// message carry after its `<info>` blocks.
+const ALERT_EXTENSIONS: &[&str] = &["Signature", "EncryptedData"];
+const INFO_ELEMENTS: &[&str] = &[
+    "language",
+    "category",
+    "event",
+    "responseType",
+    "urgency",
+    "severity",
+    "certainty",
+    "audience",
+    "eventCode",
+    "effective",
+    "onset",
+    "expires",
+    "senderName",
+    "headline",
+    "description",
+    "instruction",
+    "web",
+    "contact",
+    "parameter",
+    "resource",
+    "area",
+];
+const RESOURCE_ELEMENTS: &[&str] = &[
+    "resourceDesc",
+    "mimeType",
+    "size",
+    "uri",
+    "derefUri",
+    "digest",
+];
+const AREA_ELEMENTS: &[&str] = &[
+    "areaDesc", "polygon", "circle", "geocode", "altitude", "ceiling",
+];
+/// `<eventCode>`, `<parameter>` and `<geocode>` are all name/value pairs.
+const PAIR_ELEMENTS: &[&str] = &["valueName", "value"];
+
+const STATUSES: &[&str] = &["Actual", "Exercise", "System", "Test", "Draft"];
+const MSG_TYPES: &[&str] = &["Alert", "Update", "Cancel", "Ack", "Error"];
+const SCOPES: &[&str] = &["Public", "Restricted", "Private"];
+const CATEGORIES: &[&str] = &[
+    "Geo",
+    "Met",
+    "Safety",
+    "Security",
+    "Rescue",
+    "Fire",
+    "Health",
+    "Env",
+    "Transport",
+    "Infra",
+    "CBRNE",
+    "Other",
+];
+const RESPONSE_TYPES: &[&str] = &[
+    "Shelter", "Evacuate", "Prepare", "Execute", "Avoid", "Monitor", "Assess", "AllClear", "None",
+];
+const URGENCIES: &[&str] = &["Immediate", "Expected", "Future", "Past", "Unknown"];
+const CAP_SEVERITIES: &[&str] = &["Extreme", "Severe", "Moderate", "Minor", "Unknown"];
+const CERTAINTIES: &[&str] = &["Observed", "Likely", "Possible", "Unlikely", "Unknown"];
+
+/// Where a message breaks the schema, or why it can't become an alert.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Issue {
+    /// Element path such as `alert/info[2]/area[1]`; empty for the file
+    /// as a whole.
+    pub path: String,
+    pub message: String,
+}
+
+impl Issue {
+    pub fn file(message: impl std::fmt::Display) -> Self {
+        Self {
+            path: String::new(),
+            message: message.to_string(),
+        }
+    }
+}
+
+/// A CAP date-time: `YYYY-MM-DDThh:mm:ss±hh:mm`, with no fractions and no
+/// `Z` (UTC is written `-00:00`).
+fn cap_time(text: &str) -> Option<DateTime<FixedOffset>> {
+    let bytes = text.as_bytes();
+    let shaped = bytes.len() == 25
+        && bytes.iter().enumerate().all(|(i, b)| match i {
+            4 | 7 => *b == b'-',
+            10 => *b == b'T',
+            13 | 16 | 22 => *b == b':',
+            19 => matches!(*b, b'+' | b'-'),
+            _ => b.is_ascii_digit(),
+        });
+    if !shaped {
+        return None;
+    }
+    let text = match text.strip_suffix("-00:00") {
+        Some(local) => format!("{local}+00:00"),
+        None => text.to_owned(),
+    };
+    DateTime::parse_from_rfc3339(&text).ok()
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%dT%H:%M:%S-00:00").to_string()
+}
+
+/// Identifiers and senders may not hold spaces, commas or XML specials,
+/// since `<references>` lists them comma- and space-separated.
+fn is_token(text: &str) -> bool {
+    !text.is_empty()
+        && !text
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, ',' | '<' | '&'))
+}
+
+fn parse_point(pair: &str) -> Option<Point> {
+    let (lat, lng) = pair.split_once(',')?;
+    let point = Point {
+        lat: lat.trim().parse().ok()?,
+        lng: lng.trim().parse().ok()?,
+    };
+    point.validate().ok().map(|_| point)
+}
+
+/// `<polygon>`: at least four `lat,lon` pairs, the last repeating the
+/// first.
+fn parse_polygon(text: &str) -> std::result::Result<Vec<Point>, String> {
+    let points = text
+        .split_whitespace()
+        .map(|pair| parse_point(pair).ok_or_else(|| format!("\"{pair}\" is not a lat,lon pair")))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    if points.len() < 4 {
+        return Err("a polygon needs at least four points".into());
+    }
+    if points.first() != points.last() {
+        return Err("a polygon must end on the point it starts from".into());
+    }
+    Ok(points)
+}
+
+/// `<circle>`: `lat,lon radius`, the radius in km.
+fn parse_circle(text: &str) -> std::result::Result<(Point, f64), String> {
+    let (center, radius) = text
+        .trim()
+        .split_once(char::is_whitespace)
+        .ok_or("a circle is written \"lat,lon radius\"")?;
+    let center =
+        parse_point(center).ok_or_else(|| format!("\"{center}\" is not a lat,lon pair"))?;
+    let radius = radius
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|r| r.is_finite() && *r >= 0.0)
+        .ok_or_else(|| format!("\"{}\" is not a radius in km", radius.trim()))?;
+    Ok((center, radius))
+}
+
+/// One `sender,identifier,sent` entry of `<references>`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reference {
+    pub sender: String,
+    pub identifier: String,
+    pub sent: String,
+}
+
+fn parse_reference(entry: &str) -> Option<Reference> {
+    let mut parts = entry.splitn(3, ',');
+    let (sender, identifier, sent) = (parts.next()?, parts.next()?, parts.next()?);
+    cap_time(sent)?;
+    (!sender.is_empty() && !identifier.is_empty()).then(|| Reference {
+        sender: sender.to_owned(),
+        identifier: identifier.to_owned(),
+        sent: sent.to_owned(),
+    })
+}
+
+#[derive(Default)]
+struct Validator {
+    issues: Vec<Issue>,
+}
+
+impl Validator {
+    fn report(&mut self, path: &str, message: impl Into<String>) {
+        self.issues.push(Issue {
+            path: path.to_owned(),
+            message: message.into(),
+        });
+    }
+
+    /// Every child is one the schema knows, in the order it lists them.
+    fn sequence(&mut self, node: &Node, path: &str, order: &[&str], extensions: &[&str]) {
+        let mut last = 0;
+        for child in &node.children {
+            let Some(index) = order.iter().position(|name| *name == child.name) else {
+                if !extensions.contains(&child.name.as_str()) {
+                    self.report(path, format!("unexpected element <{}>", child.name));
+                }
+                continue;
+            };
+            if index < last {
+                self.report(
+                    path,
+                    format!("<{}> must come before <{}>", child.name, order[last]),
+                );
+            } else {
+                last = index;
+            }
+        }
+    }
+
+    /// Text of an element that may appear at most once; blank counts as
+    /// absent.
+    fn optional<'a>(&mut self, node: &'a Node, path: &str, name: &str) -> Option<&'a str> {
+        let mut found = node.children.iter().filter(|c| c.name == name);
+        let first = found.next();
+        if found.next().is_some() {
+            self.report(path, format!("only one <{name}> is allowed"));
+        }
+        first.map(|n| n.text.trim()).filter(|t| !t.is_empty())
+    }
+
+    fn required<'a>(&mut self, node: &'a Node, path: &str, name: &str) -> Option<&'a str> {
+        let value = self.optional(node, path, name);
+        if value.is_none() {
+            self.report(path, format!("<{name}> is missing or empty"));
+        }
+        value
+    }
+
+    fn one_of(&mut self, path: &str, name: &str, value: Option<&str>, allowed: &[&str]) {
+        if let Some(value) = value.filter(|v| !allowed.contains(v)) {
+            self.report(
+                path,
+                format!(
+                    "<{name}> must be one of {}, not \"{value}\"",
+                    allowed.join(", ")
+                ),
+            );
+        }
+    }
+
+    fn time(&mut self, path: &str, name: &str, value: Option<&str>) {
+        if let Some(value) = value.filter(|v| cap_time(v).is_none()) {
+            self.report(
+                path,
+                format!(
+                    "<{name}> \"{value}\" is not a CAP date-time like 2024-05-01T16:49:00-07:00"
+                ),
+            );
+        }
+    }
+
+    fn pairs(&mut self, node: &Node, path: &str, name: &str) {
+        for (i, pair) in node.children.iter().filter(|c| c.name == name).enumerate() {
+            let path = format!("{path}/{name}[{}]", i + 1);
+            self.sequence(pair, &path, PAIR_ELEMENTS, &[]);
+            self.required(pair, &path, "valueName");
+            self.required(pair, &path, "value");
+        }
+    }
+
+    fn alert(&mut self, alert: &Node) {
+        let path = "alert";
+        self.sequence(alert, path, ALERT_ELEMENTS, ALERT_EXTENSIONS);
+        for name in ["identifier", "sender"] {
+            if let Some(value) = self.required(alert, path, name).filter(|v| !is_token(v)) {
+                self.report(
+                    path,
+                    format!("<{name}> \"{value}\" may not contain spaces, commas, < or &"),
+                );
+            }
+        }
+        let sent = self.required(alert, path, "sent");
+        self.time(path, "sent", sent);
+        let status = self.required(alert, path, "status");
+        self.one_of(path, "status", status, STATUSES);
+        let msg_type = self.required(alert, path, "msgType");
+        self.one_of(path, "msgType", msg_type, MSG_TYPES);
+        self.optional(alert, path, "source");
+        let scope = self.required(alert, path, "scope");
+        self.one_of(path, "scope", scope, SCOPES);
+        let restriction = self.optional(alert, path, "restriction");
+        if scope == Some("Restricted") && restriction.is_none() {
+            self.report(path, "<restriction> is required when <scope> is Restricted");
+        }
+        let addresses = self.optional(alert, path, "addresses");
+        if scope == Some("Private") && addresses.is_none() {
+            self.report(path, "<addresses> is required when <scope> is Private");
+        }
+        self.optional(alert, path, "note");
+        if let Some(references) = self.optional(alert, path, "references") {
+            for entry in references.split_whitespace() {
+                if parse_reference(entry).is_none() {
+                    self.report(
+                        path,
+                        format!("<references> entry \"{entry}\" is not sender,identifier,sent"),
+                    );
+                }
+            }
+        }
+        self.optional(alert, path, "incidents");
+        for (i, info) in alert.children_named("info").enumerate() {
+            self.info(info, &format!("{path}/info[{}]", i + 1));
+        }
+    }
+
+    fn info(&mut self, info: &Node, path: &str) {
+        self.sequence(info, path, INFO_ELEMENTS, &[]);
+        self.optional(info, path, "language");
+        let categories: Vec<&str> = info
+            .children_named("category")
+            .map(|c| c.text.trim())
+            .collect();
+        if categories.is_empty() {
+            self.report(path, "at least one <category> is required");
+        }
+        for category in categories {
+            self.one_of(path, "category", Some(category), CATEGORIES);
+        }
+        self.required(info, path, "event");
+        for response in info.children_named("responseType") {
+            self.one_of(
+                path,
+                "responseType",
+                Some(response.text.trim()),
+                RESPONSE_TYPES,
+            );
+        }
+        let urgency = self.required(info, path, "urgency");
+        self.one_of(path, "urgency", urgency, URGENCIES);
+        let severity = self.required(info, path, "severity");
+        self.one_of(path, "severity", severity, CAP_SEVERITIES);
+        let certainty = self.required(info, path, "certainty");
+        self.one_of(path, "certainty", certainty, CERTAINTIES);
+        self.optional(info, path, "audience");
+        self.pairs(info, path, "eventCode");
+        for name in ["effective", "onset", "expires"] {
+            let value = self.optional(info, path, name);
+            self.time(path, name, value);
+        }
+        for name in [
+            "senderName",
+            "headline",
+            "description",
+            "instruction",
+            "web",
+            "contact",
+        ] {
+            self.optional(info, path, name);
+        }
+        self.pairs(info, path, "parameter");
+
+        for (i, resource) in info.children_named("resource").enumerate() {
+            let path = format!("{path}/resource[{}]", i + 1);
+            self.sequence(resource, &path, RESOURCE_ELEMENTS, &[]);
+            self.required(resource, &path, "resourceDesc");
+            self.required(resource, &path, "mimeType");
+            if let Some(size) = self
+                .optional(resource, &path, "size")
+                .filter(|s| s.parse::<u64>().is_err())
+            {
+                self.report(
+                    &path,
+                    format!("<size> \"{size}\" is not a whole number of bytes"),
+                );
+            }
+        }
+
+        for (i, area) in info.children_named("area").enumerate() {
+            let path = format!("{path}/area[{}]", i + 1);
+            self.sequence(area, &path, AREA_ELEMENTS, &[]);
+            self.required(area, &path, "areaDesc");
+            for polygon in area.children_named("polygon") {
+                if let Err(message) = parse_polygon(&polygon.text) {
+                    self.report(&path, format!("<polygon>: {message}"));
+                }
+            }
+            for circle in area.children_named("circle") {
+                if let Err(message) = parse_circle(&circle.text) {
+                    self.report(&path, format!("<circle>: {message}"));
+                }
+            }
+            self.pairs(area, &path, "geocode");
+            let altitude = self.optional(area, &path, "altitude");
+            let ceiling = self.optional(area, &path, "ceiling");
+            for (name, value) in [("altitude", altitude), ("ceiling", ceiling)] {
+                if let Some(value) = value.filter(|v| v.parse::<f64>().is_err()) {
+                    self.report(&path, format!("<{name}> \"{value}\" is not a number"));
+                }
+            }
+            if ceiling.is_some() && altitude.is_none() {
+                self.report(&path, "<ceiling> needs an <altitude>");
+            }
+        }
+    }
+}
+
+/// Check a parsed document against the CAP 1.2 schema: one `<alert>` in
+/// the 1.2 namespace, required elements present and in order, and every
+/// enumeration, date-time and geometry well formed.
+pub fn validate(document: &Node) -> Vec<Issue> {
+    match document.children.as_slice() {
+        [alert] if alert.name == "alert" => validate_alert(alert, &[]),
+        [other] => vec![Issue::file(format!(
+            "not a CAP message: the root element is <{}>",
+            other.name
+        ))],
+        _ => vec![Issue::file("not a CAP message: expected a single <alert>")],
+    }
+}
+
+/// Check one `<alert>` element, which may sit inside a feed that declares
+/// its namespace (`outer`, the feed's attributes).
+fn validate_alert(alert: &Node, outer: &[(String, String)]) -> Vec<Issue> {
+    let mut validator = Validator::default();
+    let namespaces: Vec<&str> = alert
+        .attrs
+        .iter()
+        .chain(outer)
+        .map(|(_, v)| v.as_str())
+        .collect();
+    if !namespaces.contains(&NAMESPACE) {
+        let message = match namespaces.iter().find(|ns| ns.starts_with(OTHER_VERSIONS)) {
+            Some(ns) => format!(
+                "CAP {} is not supported; expected CAP 1.2",
+                ns.trim_start_matches(OTHER_VERSIONS)
+            ),
+            None => format!("<alert> is not in the CAP 1.2 namespace ({NAMESPACE})"),
+        };
+        validator.report("alert", message);
+        return validator.issues;
+    }
+    validator.alert(alert);
+    validator.issues
+}
+
+// ─── Mappings ───────────────────────────────────────────────────
+
+/// CAP urgency and certainty for an alert type.
+fn urgency_of(alert_type: &str) -> (&'static str, &'static str) {
+    match alert_type {
+        "emergency" => ("Immediate", "Observed"),
+        "warning" => ("Expected", "Likely"),
+        "advisory" => ("Future", "Possible"),
+        _ => ("Unknown", "Unknown"),
+    }
+}
+
+/// Alert type for a CAP urgency and certainty; something unlikely is
+/// only information however soon it would happen.
+fn alert_type_of(urgency: &str, certainty: &str) -> &'static str {
+    match (urgency, certainty) {
+        (_, "Unlikely") => "information",
+        ("Immediate", _) => "emergency",
+        ("Expected", _) => "warning",
+        ("Future", _) => "advisory",
+        _ => "information",
+    }
+}
+
+fn cap_severity(severity: &str) -> &'static str {
+    match severity {
+        "critical" => "Extreme",
+        "high" => "Severe",
+        "medium" => "Moderate",
+        "low" => "Minor",
+        _ => "Unknown",
+    }
+}
+
+fn severity_of(cap: &str) -> Option<&'static str> {
+    match cap {
+        "Extreme" => Some("critical"),
+        "Severe" => Some("high"),
+        "Moderate" => Some("medium"),
+        "Minor" => Some("low"),
+        _ => None,
+    }
+}
+
+/// CAP category for the linked incident's type.
+fn category_of(incident_type: Option<&str>) -> &'static str {
+    match incident_type {
+        Some("natural_disaster" | "earthquake") => "Geo",
+        Some("flood") => "Met",
+        Some("fire") => "Fire",
+        Some("medical_emergency") => "Health",
+        Some("infrastructure_failure") => "Infra",
+        Some("industrial_accident") => "Safety",
+        Some("security_incident") => "Security",
+        _ => "Other",
+    }
+}
+
+/// Incident type for a CAP message, from its event name when that is
+/// specific and otherwise from its first category.
+pub fn incident_type_of(categories: &[String], event: &str) -> &'static str {
+    let event = event.to_lowercase();
+    if event.contains("flood") {
+        return "flood";
+    }
+    if event.contains("earthquake") {
+        return "earthquake";
+    }
+    if event.contains("fire") {
+        return "fire";
+    }
+    match categories.first().map(String::as_str) {
+        Some("Geo" | "Met" | "Env") => "natural_disaster",
+        Some("Fire") => "fire",
+        Some("Health") => "medical_emergency",
+        Some("Infra" | "Transport") => "infrastructure_failure",
+        Some("Safety" | "CBRNE") => "industrial_accident",
+        Some("Security") => "security_incident",
+        _ => "other",
+    }
+}
+
+/// `natural_disaster` → `Natural disaster`.
+fn humanize(value: &str) -> String {
+    let mut text = value.replace('_', " ");
+    if let Some(first) = text.get(..1) {
+        let upper = first.to_ascii_uppercase();
+        text.replace_range(..1, &upper);
+    }
+    text
+}
+
+// ─── CAP → alert drafts ─────────────────────────────────────────
+
+/// An incoming CAP message turned into a new alert.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Draft {
+    pub identifier: String,
+    pub sender: String,
+    /// When the message was sent, RFC 3339 in UTC.
+    pub sent: String,
+    pub status: String,
+    pub msg_type: String,
+    /// Earlier messages this one updates or cancels.
+    pub references: Vec<Reference>,
+    pub event: String,
+    pub categories: Vec<String>,
+    /// Language of the `<info>` block used.
+    pub language: String,
+    /// `alerts` row with a fresh id; the frontend adds `created_by`.
+    pub record: Value,
+    /// Where the alert is not exactly what the message said.
+    pub warnings: Vec<String>,
+}
+
+/// The `<info>` block in `language`, or failing that in the same base
+/// language, or the first one.
+fn pick_info<'a>(infos: &[&'a Node], language: &str) -> Option<&'a Node> {
+    let language_of = |info: &Node| {
+        info.text_of("language")
+            .unwrap_or_else(|| DEFAULT_LANGUAGE.into())
+    };
+    let base = |tag: &str| {
+        tag.split('-')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase()
+    };
+    infos
+        .iter()
+        .find(|info| language_of(info).eq_ignore_ascii_case(language))
+        .or_else(|| {
+            infos
+                .iter()
+                .find(|info| base(&language_of(info)) == base(language))
+        })
+        .or_else(|| infos.first())
+        .copied()
+}
+
+/// The centre of a closed ring and the radius (km) that covers it.
+fn covering_circle(ring: &[Point]) -> (Point, f64) {
+    let open = &ring[..ring.len() - 1];
+    let n = open.len() as f64;
+    let center = Point {
+        lat: open.iter().map(|p| p.lat).sum::<f64>() / n,
+        lng: open.iter().map(|p| p.lng).sum::<f64>() / n,
+    };
+    let radius = open
+        .iter()
+        .map(|p| geo::distance_km(center, *p))
+        .fold(0.0, f64::max);
+    (center, (radius * 10.0).ceil() / 10.0)
+}
+
+/// Map a valid message onto an alert. Fails only for messages that carry
+/// no alert at all.
+fn draft(alert: &Node, language: &str) -> std::result::Result<Draft, String> {
+    let text = |node: &Node, name: &str| node.text_of(name).unwrap_or_default();
+    let msg_type = text(alert, "msgType");
+    if matches!(msg_type.as_str(), "Ack" | "Error") {
+        return Err(format!(
+            "an {msg_type} message answers another message and carries no alert"
+        ));
+    }
+    let infos: Vec<&Node> = alert.children_named("info").collect();
+    let info = pick_info(&infos, language)
+        .ok_or("the message has no <info> block to make an alert from")?;
+    let mut warnings = Vec::new();
+
+    let alert_type = alert_type_of(&text(info, "urgency"), &text(info, "certainty"));
+    let severity = severity_of(&text(info, "severity")).unwrap_or_else(|| {
+        warnings.push("severity is Unknown; imported as medium".into());
+        "medium"
+    });
+    let event = text(info, "event");
+    let title = info.text_of("headline").unwrap_or_else(|| event.clone());
+    let message = match (info.text_of("description"), info.text_of("instruction")) {
+        (Some(description), Some(instruction)) => format!("{description}\n\n{instruction}"),
+        (Some(text), None) | (None, Some(text)) => text,
+        (None, None) => title.clone(),
+    };
+
+    let areas: Vec<&Node> = info.children_named("area").collect();
+    let area_desc: Vec<String> = areas.iter().filter_map(|a| a.text_of("areaDesc")).collect();
+    let mut shapes = areas.iter().filter_map(|area| {
+        if let Some(circle) = area.text_of("circle") {
+            return parse_circle(&circle).ok().map(|c| (c, false));
+        }
+        let polygon = parse_polygon(&area.text_of("polygon")?).ok()?;
+        Some((covering_circle(&polygon), true))
+    });
+    let shape = shapes.next();
+    if shape.is_some_and(|(_, from_polygon)| from_polygon) {
+        warnings.push("the area's polygon was imported as the circle around it".into());
+    }
+    if shapes.next().is_some() {
+        warnings.push("only the first area's shape was kept".into());
+    }
+
+    let expires = info
+        .text_of("expires")
+        .and_then(|t| cap_time(&t))
+        .map(|t| t.with_timezone(&Utc));
+    let status = text(alert, "status");
+    let mut active = true;
+    if msg_type == "Cancel" {
+        warnings.push("this message cancels an earlier one; imported as inactive".into());
+        active = false;
+    }
+    if status != "Actual" {
+        warnings.push(format!("status is {status}; imported as inactive"));
+        active = false;
+    }
+    if expires.is_some_and(|t| t <= Utc::now()) {
+        warnings.push("the message has expired; imported as inactive".into());
+        active = false;
+    }
+
+    let sent = cap_time(&text(alert, "sent"))
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(Utc::now);
+    let references = alert
+        .text_of("references")
+        .map(|r| r.split_whitespace().filter_map(parse_reference).collect())
+        .unwrap_or_default();
+
+    Ok(Draft {
+        identifier: text(alert, "identifier"),
+        sender: text(alert, "sender"),
+        sent: sent.to_rfc3339_opts(SecondsFormat::Secs, true),
+        status,
+        msg_type,
+        references,
+        event,
+        categories: info
+            .children_named("category")
+            .map(|c| c.text.trim().to_owned())
+            .collect(),
+        language: info
+            .text_of("language")
+            .unwrap_or_else(|| DEFAULT_LANGUAGE.into()),
+        record: json!({
+            "id": uuid::Uuid::new_v4().to_string(),
+            "title": title,
+            "type": alert_type,
+            "severity": severity,
+            "message": message,
+            "affected_area": (!area_desc.is_empty()).then(|| area_desc.join("; ")),
+            "latitude": shape.map(|((center, _), _)| center.lat),
+            "longitude": shape.map(|((center, _), _)| center.lng),
+            "radius_km": shape.map(|((_, radius), _)| radius),
+            "incident_id": null,
+            "is_active": active,
+            "expires_at": expires.map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        }),
+        warnings,
+    })
+}
+
+/// Parse, validate and map a CAP message, preferring the `<info>` block in
+/// `language`.
+pub fn read(text: &str, language: &str) -> std::result::Result<Draft, Vec<Issue>> {
+    let document = interchange::parse_xml(text).map_err(|e| vec![Issue::file(e)])?;
+    let issues = validate(&document);
+    if !issues.is_empty() {
+        return Err(issues);
+    }
+    let alert = document
+        .child("alert")
+        .ok_or_else(|| vec![Issue::file("not a CAP message")])?;
+    to_draft(alert, language)
+}
+
+/// Validate and map an `<alert>` carried inside a feed whose root has the
+/// attributes `outer`.
+pub fn read_alert(
+    alert: &Node,
+    outer: &[(String, String)],
+    language: &str,
+) -> std::result::Result<Draft, Vec<Issue>> {
+    let issues = validate_alert(alert, outer);
+    if !issues.is_empty() {
+        return Err(issues);
+    }
+    to_draft(alert, language)
+}
+
+fn to_draft(alert: &Node, language: &str) -> std::result::Result<Draft, Vec<Issue>> {
+    draft(alert, language).map_err(|message| {
+        vec![Issue {
+            path: "alert".into(),
+            message,
+        }]
+    })
+}
+
+/// A CAP file's text, refusing anything too big or not UTF-8.
+pub fn read_file(path: &Path) -> Result<String> {
+    if std::fs::metadata(path)?.len() > MAX_FILE_BYTES {
+        return Err(Error::Invalid(format!(
+            "files over {} MB are not CAP messages",
+            MAX_FILE_BYTES / (1024 * 1024)
+        )));
+    }
+    String::from_utf8(std::fs::read(path)?)
+        .map_err(|_| Error::Invalid("file is not UTF-8 text".into()))
+}
+
+/// One message read from a file or pasted text.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapImport {
+    /// `None` for pasted text.
+    pub file_name: Option<String>,
+    pub draft: Option<Draft>,
+    /// Schema and mapping errors; empty when there is a draft.
+    pub errors: Vec<Issue>,
+}
+
+impl CapImport {
+    fn new(file_name: Option<String>, result: std::result::Result<Draft, Vec<Issue>>) -> Self {
+        match result {
+            Ok(draft) => Self {
+                file_name,
+                draft: Some(draft),
+                errors: Vec::new(),
+            },
+            Err(errors) => Self {
+                file_name,
+                draft: None,
+                errors,
+            },
+        }
+    }
+}
+
+fn import_file(path: &Path, language: &str) -> CapImport {
+    let result = read_file(path)
+        .map_err(|e| vec![Issue::file(e)])
+        .and_then(|text| read(&text, language));
+    CapImport::new(
+        path.file_name().map(|n| n.to_string_lossy().into_owned()),
+        result,
+    )
+}
+
+// ─── Alerts → CAP ───────────────────────────────────────────────
+
+/// Each export of an edited alert is a new message, so the identifier
+/// carries the time it was sent.
+fn message_id(alert_id: &str, sent: DateTime<Utc>) -> String {
+    format!("{alert_id}.{}", sent.timestamp())
+}
+
+fn record_time(record: &Value, key: &str) -> Option<DateTime<Utc>> {
+    let text = record.get(key)?.as_str()?;
+    DateTime::parse_from_rfc3339(text)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+fn record_text<'a>(record: &'a Value, key: &str) -> Option<&'a str> {
+    record
+        .get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+fn tag(out: &mut String, indent: usize, name: &str, value: &str) {
+    let _ = writeln!(out, "{:indent$}<{name}>{}</{name}>", "", escape(value));
+}
+
+/// An alert as a CAP 1.2 message, with its area from the alert itself or
+/// from the linked incident and its drawn zones. Returns the message's
+/// identifier and XML.
+fn to_cap(
+    config: &CapConfig,
+    alert: &Value,
+    incident: Option<&Value>,
+    zones: &[Shape],
+) -> Result<(String, String)> {
+    let sender = config.sender.as_deref().ok_or_else(|| {
+        Error::Invalid("set a CAP sender id in Settings before exporting alerts".into())
+    })?;
+    let id = record_text(alert, "id").ok_or_else(|| Error::Invalid("alert has no id".into()))?;
+    let title = record_text(alert, "title").unwrap_or("Alert");
+    let created = record_time(alert, "created_at").unwrap_or_else(Utc::now);
+    let sent = record_time(alert, "updated_at")
+        .unwrap_or(created)
+        .max(created);
+    let identifier = message_id(id, sent);
+    let active = alert
+        .get("is_active")
+        .and_then(Value::as_bool)
+        .unwrap_or(true);
+    let msg_type = if !active {
+        "Cancel"
+    } else if sent > created {
+        "Update"
+    } else {
+        "Alert"
+    };
+    let (urgency, certainty) = urgency_of(record_text(alert, "type").unwrap_or_default());
+    let incident_type = incident.and_then(|i| record_text(i, "type"));
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(out, "<alert xmlns=\"{NAMESPACE}\">");
+    tag(&mut out, 2, "identifier", &identifier);
+    tag(&mut out, 2, "sender", sender);
+    tag(&mut out, 2, "sent", &format_time(sent));
+    tag(&mut out, 2, "status", "Actual");
+    tag(&mut out, 2, "msgType", msg_type);
+    tag(&mut out, 2, "scope", "Public");
+    if msg_type != "Alert" {
+        let original = format!(
+            "{sender},{},{}",
+            message_id(id, created),
+            format_time(created)
+        );
+        tag(&mut out, 2, "references", &original);
+    }
+    if let Some(incident_id) = record_text(alert, "incident_id") {
+        tag(&mut out, 2, "incidents", incident_id);
+    }
+
+    out.push_str("  <info>\n");
+    tag(&mut out, 4, "language", &config.language);
+    tag(&mut out, 4, "category", category_of(incident_type));
+    let event = incident_type.map_or_else(|| title.to_owned(), humanize);
+    tag(&mut out, 4, "event", &event);
+    tag(&mut out, 4, "urgency", urgency);
+    tag(
+        &mut out,
+        4,
+        "severity",
+        cap_severity(record_text(alert, "severity").unwrap_or_default()),
+    );
+    tag(&mut out, 4, "certainty", certainty);
+    if let Some(expires) = record_time(alert, "expires_at") {
+        tag(&mut out, 4, "expires", &format_time(expires));
+    }
+    if let Some(name) = &config.sender_name {
+        tag(&mut out, 4, "senderName", name);
+    }
+    let headline: String = title.chars().take(MAX_HEADLINE_CHARS).collect();
+    tag(&mut out, 4, "headline", &headline);
+    if let Some(message) = record_text(alert, "message") {
+        tag(&mut out, 4, "description", message);
+    }
+
+    // The alert's own circle wins over the incident's
+    let radius = |record: &Value, key: &str| {
+        record
+            .get(key)
+            .and_then(Value::as_f64)
+            .filter(|r| r.is_finite() && *r >= 0.0)
+    };
+    let circle = Point::from_record(alert)
+        .map(|center| (center, radius(alert, "radius_km").unwrap_or(0.0)))
+        .or_else(|| {
+            let incident = incident?;
+            Some((
+                Point::from_record(incident)?,
+                radius(incident, "affected_radius_km").unwrap_or(0.0),
+            ))
+        });
+    let area_desc = record_text(alert, "affected_area")
+        .or_else(|| incident.and_then(|i| record_text(i, "location_name")))
+        .or_else(|| incident.and_then(|i| record_text(i, "title")));
+    if area_desc.is_some() || circle.is_some() || !zones.is_empty() {
+        out.push_str("    <area>\n");
+        tag(
+            &mut out,
+            6,
+            "areaDesc",
+            area_desc.unwrap_or("Affected area"),
+        );
+        for zone in zones {
+            if let Shape::Polygon { points } = zone {
+                let mut ring = points.clone();
+                if ring.first() != ring.last() {
+                    ring.push(ring[0]);
+                }
+                let pairs: Vec<String> = ring
+                    .iter()
+                    .map(|p| format!("{},{}", p.lat, p.lng))
+                    .collect();
+                tag(&mut out, 6, "polygon", &pairs.join(" "));
+            }
+        }
+        let zone_circles = zones.iter().filter_map(|zone| match zone {
+            Shape::Circle { center, radius_km } => Some((*center, *radius_km)),
+            Shape::Polygon { .. } => None,
+        });
+        for (center, radius_km) in circle.into_iter().chain(zone_circles) {
+            tag(
+                &mut out,
+                6,
+                "circle",
+                &format!("{},{} {radius_km}", center.lat, center.lng),
+            );
+        }
+        out.push_str("    </area>\n");
+    }
+    out.push_str("  </info>\n</alert>\n");
+
+    // Never hand partners something their validators will reject
+    if let Some(issue) = validate(&interchange::parse_xml(&out)?).first() {
+        return Err(Error::Invalid(format!(
+            "\"{title}\" would not be valid CAP: {}: {}",
+            issue.path, issue.message
+        )));
+    }
+    Ok((identifier, out))
+}
+
+/// A cached alert as CAP, with its linked incident's zones.
+fn message(conn: &Connection, config: &CapConfig, alert: &Value) -> Result<(String, String)> {
+    let incident_id = record_text(alert, "incident_id");
+    let incident = match incident_id {
+        Some(id) => mirror::get(conn, "incidents", id)?,
+        None => None,
+    };
+    let zones: Vec<Shape> = match (incident_id, &incident) {
+        (Some(id), Some(_)) => geofence::fences(conn, Some(id))?
+            .into_iter()
+            .filter(|fence| fence.kind == FenceKind::IncidentZone)
+            .map(|fence| fence.shape)
+            .collect(),
+        _ => Vec::new(),
+    };
+    to_cap(config, alert, incident.as_ref(), &zones)
+}
+
+// ─── Settings ───────────────────────────────────────────────────
+
+/// Incoming messages are only suggested when their area reaches this
+/// circle.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Region {
+    pub center: Point,
+    pub radius_km: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CapConfig {
+    /// How partners know us, e.g. `alerts@agency.example`; unique to this
+    /// agency and with no spaces or commas.
+    pub sender: Option<String>,
+    /// Agency name shown to the public.
+    pub sender_name: Option<String>,
+    /// Language of exported alerts, and the one preferred on import.
+    pub language: String,
+    /// Partner CAP feeds (Atom or RSS, or a single CAP file) to poll.
+    pub feeds: Vec<String>,
+    /// Folder partners drop CAP files into.
+    pub drop_folder: Option<String>,
+    pub poll_minutes: u32,
+    /// Incoming messages less severe than this are not suggested.
+    pub min_severity: String,
+    pub region: Option<Region>,
+}
+
+impl Default for CapConfig {
+    fn default() -> Self {
+        Self {
+            sender: None,
+            sender_name: None,
+            language: DEFAULT_LANGUAGE.into(),
+            feeds: Vec::new(),
+            drop_folder: None,
+            poll_minutes: DEFAULT_POLL_MINUTES,
+            min_severity: "low".into(),
+            region: None,
+        }
+    }
+}
+
+impl CapConfig {
+    fn normalized(mut self) -> Self {
+        let trim =
+            |value: Option<String>| value.map(|v| v.trim().to_owned()).filter(|v| !v.is_empty());
+        self.sender = trim(self.sender);
+        self.sender_name = trim(self.sender_name);
+        self.language = trim(Some(self.language)).unwrap_or_else(|| DEFAULT_LANGUAGE.into());
+        let mut feeds: Vec<String> = Vec::new();
+        for feed in std::mem::take(&mut self.feeds) {
+            let feed = feed.trim().to_owned();
+            if !feed.is_empty() && !feeds.contains(&feed) {
+                feeds.push(feed);
+            }
+        }
+        self.feeds = feeds;
+        self.drop_folder = trim(self.drop_folder);
+        self
+    }
+
+    fn validate(&self) -> Result<()> {
+        if let Some(sender) = self.sender.as_deref().filter(|s| !is_token(s)) {
+            return Err(Error::Invalid(format!(
+                "CAP sender \"{sender}\" may not contain spaces, commas, < or &"
+            )));
+        }
+        if !self
+            .language
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return Err(Error::Invalid(format!(
+                "\"{}\" is not a language tag like en-US",
+                self.language
+            )));
+        }
+        if self.feeds.len() > MAX_FEEDS {
+            return Err(Error::Invalid(format!(
+                "at most {MAX_FEEDS} CAP feeds can be watched"
+            )));
+        }
+        for feed in &self.feeds {
+            let url = url::Url::parse(feed)
+                .map_err(|e| Error::Invalid(format!("feed \"{feed}\" is not a URL: {e}")))?;
+            if !matches!(url.scheme(), "http" | "https") {
+                return Err(Error::Invalid(format!(
+                    "feed \"{feed}\" must be an http or https URL"
+                )));
+            }
+        }
+        if let Some(folder) = self
+            .drop_folder
+            .as_deref()
+            .filter(|f| !Path::new(f).is_dir())
+        {
+            return Err(Error::Invalid(format!(
+                "drop folder \"{folder}\" is not a folder"
+            )));
+        }
+        if !(1..=MAX_POLL_MINUTES).contains(&self.poll_minutes) {
+            return Err(Error::Invalid(format!(
+                "feeds are polled every 1 to {MAX_POLL_MINUTES} minutes"
+            )));
+        }
+        if !SEVERITIES.contains(&self.min_severity.as_str()) {
+            return Err(Error::Invalid(format!(
+                "unknown severity \"{}\"",
+                self.min_severity
+            )));
+        }
+        if let Some(region) = &self.region {
+            region.center.validate()?;
+            if !(region.radius_km > 0.0 && region.radius_km <= MAX_REGION_KM) {
+                return Err(Error::Invalid(format!(
+                    "region radius must be between 0 and {MAX_REGION_KM} km"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether there is anything to watch.
+    pub fn watches(&self) -> bool {
+        !self.feeds.is_empty() || self.drop_folder.is_some()
+    }
+
+    /// Whether an incoming alert draft is worth suggesting: severe enough,
+    /// and in the region if one is set. Alerts with no shape are kept,
+    /// since only their area description says where they are.
+    pub fn is_relevant(&self, record: &Value) -> bool {
+        let rank = |severity: &str| SEVERITIES.iter().position(|s| *s == severity);
+        let severity = record
+            .get("severity")
+            .and_then(Value::as_str)
+            .unwrap_or("medium");
+        if rank(severity) < rank(&self.min_severity) {
+            return false;
+        }
+        let (Some(region), Some(center)) = (&self.region, Point::from_record(record)) else {
+            return true;
+        };
+        let radius = record
+            .get("radius_km")
+            .and_then(Value::as_f64)
+            .unwrap_or(0.0);
+        geo::distance_km(region.center, center) <= region.radius_km + radius
+    }
+}
+
+fn config_path(app: &AppHandle) -> Result<PathBuf> {
+    Ok(app.path().app_config_dir()?.join(CONFIG_FILE))
+}
+
+pub fn load_config(app: &AppHandle) -> CapConfig {
+    config_path(app)
+        .ok()
+        .and_then(|path| std::fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn save_config(app: &AppHandle, config: &CapConfig) -> Result<()> {
+    let path = config_path(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(config)?)?;
+    std::fs::rename(tmp, path)?;
+    Ok(())
+}
+
+// ─── Commands ───────────────────────────────────────────────────
+
+#[tauri::command]
+pub fn cap_config(app: AppHandle) -> CapConfig {
+    load_config(&app)
+}
+
+#[tauri::command]
+pub fn cap_configure(app: AppHandle, config: CapConfig) -> Result<CapConfig> {
+    let config = config.normalized();
+    config.validate()?;
+    save_config(&app, &config)?;
+    app.state::<CapWatcher>().start(&app, config.clone());
+    Ok(config)
+}
+
+/// Write cached alerts as CAP 1.2: one alert to a file the user picks,
+/// several as one file each in a folder they pick. Returns `None` if the
+/// dialog was cancelled.
+#[tauri::command]
+pub async fn cap_export(
+    app: AppHandle,
+    db: State<'_, Db>,
+    ids: Vec<String>,
+) -> Result<Option<ExportReport>> {
+    let config = load_config(&app);
+    let messages: Vec<(String, String)> = db.with(|conn| {
+        let mut messages = Vec::new();
+        for id in &ids {
+            if let Some(alert) = mirror::get(conn, "alerts", id)? {
+                messages.push(message(conn, &config, &alert)?);
+            }
+        }
+        Ok(messages)
+    })?;
+    if messages.is_empty() {
+        return Err(Error::NotFound("alert".into()));
+    }
+
+    let path = if let [(identifier, xml)] = messages.as_slice() {
+        let dialog = app
+            .dialog()
+            .file()
+            .add_filter("CAP 1.2", &["xml", "cap"])
+            .set_file_name(format!("{identifier}.xml"));
+        let Some(file) =
+            tauri::async_runtime::spawn_blocking(move || dialog.blocking_save_file()).await?
+        else {
+            return Ok(None);
+        };
+        let path = interchange::path_of(file)?;
+        std::fs::write(&path, xml)?;
+        path
+    } else {
+        let dialog = app.dialog().file();
+        let Some(folder) =
+            tauri::async_runtime::spawn_blocking(move || dialog.blocking_pick_folder()).await?
+        else {
+            return Ok(None);
+        };
+        let folder = interchange::path_of(folder)?;
+        for (identifier, xml) in &messages {
+            std::fs::write(folder.join(format!("{identifier}.xml")), xml)?;
+        }
+        folder
+    };
+
+    Ok(Some(ExportReport {
+        path: path.display().to_string(),
+        exported: messages.len(),
+        skipped: ids.len() - messages.len(),
+    }))
+}
+
+/// Read CAP files the user picks into alert drafts. Returns `None` if the
+/// open dialog was cancelled.
+#[tauri::command]
+pub async fn cap_import(app: AppHandle) -> Result<Option<Vec<CapImport>>> {
+    let dialog = app.dialog().file().add_filter("CAP 1.2", &["xml", "cap"]);
+    let Some(files) =
+        tauri::async_runtime::spawn_blocking(move || dialog.blocking_pick_files()).await?
+    else {
+        return Ok(None);
+    };
+    let paths = files
+        .into_iter()
+        .map(interchange::path_of)
+        .collect::<Result<Vec<_>>>()?;
+    let language = load_config(&app).language;
+    let imports: Vec<CapImport> = tauri::async_runtime::spawn_blocking(move || {
+        paths
+            .iter()
+            .map(|path| import_file(path, &language))
+            .collect()
+    })
+    .await?;
+    Ok(Some(imports))
+}
+
+/// Read a pasted CAP message into an alert draft.
+#[tauri::command]
+pub fn cap_parse(app: AppHandle, xml: String) -> CapImport {
+    CapImport::new(None, read(&xml, &load_config(&app).language))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<alert xmlns="urn:oasis:names:tc:emergency:cap:1.2">
+  <identifier>KSTO1055887203</identifier>
+  <sender>KSTO@NWS.NOAA.GOV</sender>
+  <sent>2003-06-17T14:57:00-07:00</sent>
+  <status>Actual</status>
+  <msgType>Update</msgType>
+  <scope>Public</scope>
+  <references>KSTO@NWS.NOAA.GOV,KSTO1055880000,2003-06-17T12:00:00-07:00</references>
+  <info>
+    <category>Met</category>
+    <event>SEVERE THUNDERSTORM</event>
+    <urgency>Immediate</urgency>
+    <severity>Severe</severity>
+    <certainty>Observed</certainty>
+    <headline>Storms &amp; hail</headline>
+    <area>
+      <areaDesc>EXTREME NORTH CENTRAL TUOLUMNE COUNTY</areaDesc>
+      <circle>38.47,-120.14 5</circle>
+    </area>
+  </info>
+</alert>"#;
+
+    fn issues(xml: &str) -> Vec<String> {
+        validate(&interchange::parse_xml(xml).unwrap())
+            .into_iter()
+            .map(|i| i.message)
+            .collect()
+    }
+
+    fn config() -> CapConfig {
+        CapConfig {
+            sender: Some("alerts@agency.example".into()),
+            ..CapConfig::default()
+        }
+    }
+
+    #[test]
+    fn valid_message_reads_as_a_draft() {
+        let draft = read(MESSAGE, "en-US").unwrap();
+        assert_eq!(draft.sender, "KSTO@NWS.NOAA.GOV");
+        assert_eq!(draft.msg_type, "Update");
+        assert_eq!(draft.sent, "2003-06-17T21:57:00Z");
+        assert_eq!(draft.references[0].identifier, "KSTO1055880000");
+        assert_eq!(draft.record["title"], "Storms & hail");
+        assert_eq!(draft.record["type"], "emergency");
+        assert_eq!(draft.record["severity"], "high");
+        assert_eq!(draft.record["radius_km"], 5.0);
+    }
+
+    #[test]
+    fn out_of_order_elements_are_reported() {
+        let xml = MESSAGE.replace(
+            "<sent>2003-06-17T14:57:00-07:00</sent>\n  <status>Actual</status>",
+            "<status>Actual</status>\n  <sent>2003-06-17T14:57:00-07:00</sent>",
+        );
+        let found = issues(&xml);
+        assert!(
+            found
+                .iter()
+                .any(|m| m == "<sent> must come before <status>"),
+            "{found:?}"
+        );
+        let xml = MESSAGE.replace(
+            "<scope>Public</scope>",
+            "<scope>Public</scope><color>red</color>",
+        );
+        assert!(issues(&xml).contains(&"unexpected element <color>".to_owned()));
+    }
+
+    #[test]
+    fn bad_enumerations_are_reported() {
+        let xml = MESSAGE
+            .replace("<status>Actual</status>", "<status>Real</status>")
+            .replace("<severity>Severe</severity>", "<severity>Huge</severity>")
+            .replace("<category>Met</category>", "<category>Weather</category>");
+        let found = issues(&xml);
+        assert_eq!(found.len(), 3, "{found:?}");
+        assert!(found[0].starts_with("<status> must be one of"));
+    }
+
+    #[test]
+    fn missing_elements_and_other_versions_are_reported() {
+        let xml = MESSAGE.replace("<event>SEVERE THUNDERSTORM</event>", "");
+        assert!(issues(&xml).contains(&"<event> is missing or empty".to_owned()));
+        let xml = MESSAGE.replace("cap:1.2", "cap:1.1");
+        assert_eq!(issues(&xml), ["CAP 1.1 is not supported; expected CAP 1.2"]);
+    }
+
+    #[test]
+    fn cap_times() {
+        let utc = cap_time("2024-05-01T16:49:00-00:00").unwrap();
+        assert_eq!(utc.offset().local_minus_utc(), 0);
+        assert_eq!(
+            format_time(utc.with_timezone(&Utc)),
+            "2024-05-01T16:49:00-00:00"
+        );
+        let local = cap_time("2024-05-01T16:49:00+05:30").unwrap();
+        assert_eq!(
+            local.with_timezone(&Utc).to_rfc3339(),
+            "2024-05-01T11:19:00+00:00"
+        );
+        for bad in [
+            "2024-05-01T16:49:00Z",
+            "2024-05-01T16:49:00.5-07:00",
+            "2024-05-01 16:49:00-07:00",
+            "2024-13-01T16:49:00-07:00",
+        ] {
+            assert!(cap_time(bad).is_none(), "{bad}");
+        }
+        let xml = MESSAGE.replace("14:57:00-07:00</sent>", "14:57:00Z</sent>");
+        assert_eq!(issues(&xml).len(), 1);
+    }
+
+    #[test]
+    fn polygon_and_circle_geometry() {
+        assert_eq!(parse_polygon("1,1 1,2 2,2 1,1").unwrap().len(), 4);
+        assert!(parse_polygon("1,1 1,2 1,1").is_err());
+        assert!(parse_polygon("1,1 1,2 2,2 2,1").is_err());
+        assert!(parse_polygon("1,1 1,2 91,2 1,1").is_err());
+        let (center, radius) = parse_circle(" 38.47,-120.14 0 ").unwrap();
+        assert_eq!((center.lat, center.lng, radius), (38.47, -120.14, 0.0));
+        assert!(parse_circle("38.47,-120.14").is_err());
+        assert!(parse_circle("38.47,-120.14 -1").is_err());
+        assert!(parse_circle("38.47,-220.14 1").is_err());
+
+        let xml = MESSAGE.replace(
+            "<circle>38.47,-120.14 5</circle>",
+            "<polygon>1,1 1,2 2,2</polygon>",
+        );
+        assert_eq!(
+            issues(&xml),
+            ["<polygon>: a polygon needs at least four points"]
+        );
+    }
+
+    #[test]
+    fn exported_alerts_read_back() {
+        let expires =
+            (Utc::now() + chrono::Duration::days(1)).to_rfc3339_opts(SecondsFormat::Secs, true);
+        let alert = json!({
+            "id": "a1",
+            "title": "Smoke <& ash>",
+            "type": "warning",
+            "severity": "critical",
+            "message": "Stay \"indoors\" & keep windows shut",
+            "latitude": 47.5,
+            "longitude": 8.25,
+            "radius_km": 12.5,
+            "affected_area": "Zürich & surroundings",
+            "is_active": true,
+            "created_at": "2024-05-01T10:00:00Z",
+            "expires_at": expires,
+        });
+        let zones = [Shape::Polygon {
+            points: vec![
+                Point {
+                    lat: 47.0,
+                    lng: 8.0,
+                },
+                Point {
+                    lat: 47.0,
+                    lng: 9.0,
+                },
+                Point {
+                    lat: 48.0,
+                    lng: 9.0,
+                },
+            ],
+        }];
+        let (identifier, xml) = to_cap(&config(), &alert, None, &zones).unwrap();
+        assert!(xml.contains("<headline>Smoke &lt;&amp; ash&gt;</headline>"));
+        assert!(xml.contains("<polygon>47,8 47,9 48,9 47,8</polygon>"));
+
+        let draft = read(&xml, "en-US").unwrap();
+        assert_eq!(draft.identifier, identifier);
+        assert_eq!(draft.sender, "alerts@agency.example");
+        assert_eq!(draft.msg_type, "Alert");
+        assert_eq!(draft.record["title"], "Smoke <& ash>");
+        assert_eq!(
+            draft.record["message"],
+            "Stay \"indoors\" & keep windows shut"
+        );
+        assert_eq!(draft.record["affected_area"], "Zürich & surroundings");
+        assert_eq!(draft.record["type"], "warning");
+        assert_eq!(draft.record["severity"], "critical");
+        assert_eq!(draft.record["latitude"], 47.5);
+        assert_eq!(draft.record["radius_km"], 12.5);
+        assert_eq!(draft.record["is_active"], true);
+        assert_eq!(draft.record["expires_at"], expires);
+    }
+
+    #[test]
+    fn deactivated_alerts_export_as_cancels() {
+        let alert = json!({
+            "id": "a1",
+            "title": "Flood",
+            "is_active": false,
+            "created_at": "2024-05-01T10:00:00Z",
+            "updated_at": "2024-05-01T12:00:00Z",
+        });
+        let (_, xml) = to_cap(&config(), &alert, None, &[]).unwrap();
+        let draft = read(&xml, "en-US").unwrap();
+        assert_eq!(draft.msg_type, "Cancel");
+        assert_eq!(draft.references[0].identifier, "a1.1714557600");
+        assert_eq!(draft.references[0].sent, "2024-05-01T10:00:00-00:00");
+        assert_eq!(draft.record["is_active"], false);
+    }
+
+    #[test]
+    fn export_needs_a_sender() {
+        let alert = json!({ "id": "a1", "title": "Flood" });
+        assert!(to_cap(&CapConfig::default(), &alert, None, &[]).is_err());
+    }
+}
@@ -0,0 +1,1426 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::header::ACCEPT;
+use reqwest::Client;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::cap::{self, CapConfig, Draft, Issue};
+use crate::db::Db;
+use crate::error::{Error, Result};
+use crate::interchange::{self, Node};
+use crate::mirror;
+use crate::offline_queue::{self, NewMutation, Operation};
+
+pub const REVIEW_EVENT: &str = "cap://review";
+pub const STATUS_EVENT: &str = "cap://status";
+
+const USER_AGENT: &str = concat!("DisasterConnect/", env!("CARGO_PKG_VERSION"));
+/// Feeds serve CAP, Atom or RSS from the same URL depending on this.
+const ACCEPT_TYPES: &str = "application/cap+xml, application/atom+xml, application/rss+xml, \
+                            application/xml;q=0.9, */*;q=0.5";
+/// How often the drop folder is scanned and feeds are checked for being
+/// due.
+const TICK: Duration = Duration::from_secs(15);
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_FETCH_BYTES: usize = 5 * 1024 * 1024;
+/// A feed's first poll shouldn't fetch its whole archive at once.
+const MAX_ENTRIES_PER_POLL: usize = 50;
+/// Files changed more recently than this may still be being written.
+const SETTLE_TIME: Duration = Duration::from_secs(2);
+/// Seen messages, feed entries and decided reviews are forgotten after
+/// this long.
+const RETENTION_DAYS: i64 = 90;
+const PRUNE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+const PROCESSED_DIR: &str = "processed";
+const REJECTED_DIR: &str = "rejected";
+const DEFAULT_LIST_LIMIT: u32 = 200;
+
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+// ─── Review queue ───────────────────────────────────────────────
+
+/// What a message should become if a reviewer accepts it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Suggestion {
+    Alert,
+    Incident,
+}
+
+impl Suggestion {
+    fn as_str(self) -> &'static str {
+        match self {
+            Suggestion::Alert => "alert",
+            Suggestion::Incident => "incident",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "alert" => Some(Suggestion::Alert),
+            "incident" => Some(Suggestion::Incident),
+            _ => None,
+        }
+    }
+
+    fn table(self) -> &'static str {
+        match self {
+            Suggestion::Alert => "alerts",
+            Suggestion::Incident => "incidents",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewStatus {
+    Pending,
+    Accepted,
+    Dismissed,
+    /// A later message of the same thread arrived before anyone decided.
+    Superseded,
+}
+
+impl ReviewStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            ReviewStatus::Pending => "pending",
+            ReviewStatus::Accepted => "accepted",
+            ReviewStatus::Dismissed => "dismissed",
+            ReviewStatus::Superseded => "superseded",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(ReviewStatus::Pending),
+            "accepted" => Some(ReviewStatus::Accepted),
+            "dismissed" => Some(ReviewStatus::Dismissed),
+            "superseded" => Some(ReviewStatus::Superseded),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordRef {
+    pub table: String,
+    pub id: String,
+}
+
+/// An incoming message waiting for, or past, a reviewer's decision.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewItem {
+    pub id: String,
+    /// Feed URL or dropped file it came from.
+    pub source: String,
+    pub suggestion: Suggestion,
+    pub draft: Draft,
+    /// Record an earlier message of the thread became; accepting this one
+    /// updates or closes it.
+    pub target: Option<RecordRef>,
+    pub status: ReviewStatus,
+    pub superseded_by: Option<String>,
+    /// Record accepting it created or updated.
+    pub record: Option<RecordRef>,
+    pub received_at: String,
+    pub decided_at: Option<String>,
+}
+
+const REVIEW_COLUMNS: &str = "id, source, suggestion, draft, target_table, target_id, status,
+     superseded_by, record_table, record_id, received_at, decided_at";
+
+fn record_ref(table: Option<String>, id: Option<String>) -> Option<RecordRef> {
+    Some(RecordRef {
+        table: table?,
+        id: id?,
+    })
+}
+
+/// `None` for rows a newer build wrote in a shape we don't know.
+fn review_item(row: &Row) -> rusqlite::Result<Option<ReviewItem>> {
+    let suggestion: String = row.get(2)?;
+    let draft: String = row.get(3)?;
+    let status: String = row.get(6)?;
+    let (Some(suggestion), Ok(draft), Some(status)) = (
+        Suggestion::parse(&suggestion),
+        serde_json::from_str(&draft),
+        ReviewStatus::parse(&status),
+    ) else {
+        return Ok(None);
+    };
+    Ok(Some(ReviewItem {
+        id: row.get(0)?,
+        source: row.get(1)?,
+        suggestion,
+        draft,
+        target: record_ref(row.get(4)?, row.get(5)?),
+        status,
+        superseded_by: row.get(7)?,
+        record: record_ref(row.get(8)?, row.get(9)?),
+        received_at: row.get(10)?,
+        decided_at: row.get(11)?,
+    }))
+}
+
+fn get_item(conn: &Connection, id: &str) -> Result<Option<ReviewItem>> {
+    Ok(conn
+        .query_row(
+            &format!("SELECT {REVIEW_COLUMNS} FROM cap_review WHERE id = ?1"),
+            [id],
+            review_item,
+        )
+        .optional()?
+        .flatten())
+}
+
+fn list_items(
+    conn: &Connection,
+    status: Option<ReviewStatus>,
+    limit: u32,
+) -> Result<Vec<ReviewItem>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {REVIEW_COLUMNS} FROM cap_review
+         WHERE ?1 IS NULL OR status = ?1
+         ORDER BY received_at DESC
+         LIMIT ?2"
+    ))?;
+    let rows = stmt.query_map(
+        params![status.map(ReviewStatus::as_str), limit],
+        review_item,
+    )?;
+    let mut items = Vec::new();
+    for row in rows {
+        items.extend(row?);
+    }
+    Ok(items)
+}
+
+fn pending_count(conn: &Connection) -> Result<u32> {
+    Ok(conn.query_row(
+        "SELECT COUNT(*) FROM cap_review WHERE status = 'pending'",
+        [],
+        |row| row.get(0),
+    )?)
+}
+
+// ─── Ingest ─────────────────────────────────────────────────────
+
+enum Outcome {
+    Suggested(Box<ReviewItem>),
+    /// Seen before, by sender and identifier.
+    Duplicate,
+    /// Recorded, with the reason, but not worth a reviewer's time.
+    Skipped,
+}
+
+/// Record an incoming message and, when it is new and relevant, queue it
+/// for review. Messages that update or cancel earlier ones supersede those
+/// still pending, and target whatever record the thread already became.
+fn ingest(
+    conn: &mut Connection,
+    config: &CapConfig,
+    draft: Draft,
+    source: &str,
+) -> Result<Outcome> {
+    let tx = conn.transaction()?;
+    let seen = tx
+        .query_row(
+            "SELECT 1 FROM cap_messages WHERE sender = ?1 AND identifier = ?2",
+            params![draft.sender, draft.identifier],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some();
+    if seen {
+        return Ok(Outcome::Duplicate);
+    }
+
+    // Review items of the messages this one refers to, and the record the
+    // thread became, if any
+    let mut referenced: Vec<String> = Vec::new();
+    let mut target: Option<RecordRef> = None;
+    for reference in &draft.references {
+        let review_id: Option<String> = tx
+            .query_row(
+                "SELECT review_id FROM cap_messages WHERE sender = ?1 AND identifier = ?2",
+                params![reference.sender, reference.identifier],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        let Some(review_id) = review_id else {
+            continue;
+        };
+        let thread = tx
+            .query_row(
+                "SELECT record_table, record_id, target_table, target_id
+                 FROM cap_review WHERE id = ?1",
+                [&review_id],
+                |row| {
+                    Ok(record_ref(row.get(0)?, row.get(1)?)
+                        .or(record_ref(row.get(2)?, row.get(3)?)))
+                },
+            )
+            .optional()?
+            .flatten();
+        target = target.or(thread);
+        referenced.push(review_id);
+    }
+
+    let now = Utc::now();
+    let expired = draft
+        .record
+        .get("expires_at")
+        .and_then(Value::as_str)
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .is_some_and(|t| t <= now);
+    let skipped = if draft.status != "Actual" {
+        Some(format!("status is {}", draft.status))
+    } else if target.is_some() {
+        None
+    } else if draft.msg_type == "Cancel" {
+        Some(if referenced.is_empty() {
+            "cancels a message that was never suggested".to_owned()
+        } else {
+            "cancels messages still awaiting review".to_owned()
+        })
+    } else if expired {
+        Some("already expired".into())
+    } else if !config.is_relevant(&draft.record) {
+        Some("below the minimum severity or outside the region".into())
+    } else {
+        None
+    };
+
+    let received_at = timestamp(now);
+    let review_id = skipped.is_none().then(|| uuid::Uuid::new_v4().to_string());
+    tx.execute(
+        "INSERT INTO cap_messages
+            (sender, identifier, sent, msg_type, source, received_at, review_id, skipped)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            draft.sender,
+            draft.identifier,
+            draft.sent,
+            draft.msg_type,
+            source,
+            received_at,
+            review_id,
+            skipped,
+        ],
+    )?;
+    // Whatever happens to this message, earlier ones of its thread are
+    // no longer the latest word
+    for earlier in &referenced {
+        tx.execute(
+            "UPDATE cap_review SET status = 'superseded', superseded_by = ?2, decided_at = ?3
+             WHERE id = ?1 AND status = 'pending'",
+            params![earlier, review_id, received_at],
+        )?;
+    }
+
+    let Some(id) = review_id else {
+        tx.commit()?;
+        return Ok(Outcome::Skipped);
+    };
+    let suggestion = match &target {
+        Some(target) if target.table == "incidents" => Suggestion::Incident,
+        Some(_) => Suggestion::Alert,
+        None if draft.record.get("type").and_then(Value::as_str) == Some("emergency") => {
+            Suggestion::Incident
+        }
+        None => Suggestion::Alert,
+    };
+    tx.execute(
+        "INSERT INTO cap_review
+            (id, sender, identifier, source, suggestion, draft, target_table, target_id,
+             received_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            id,
+            draft.sender,
+            draft.identifier,
+            source,
+            suggestion.as_str(),
+            serde_json::to_string(&draft)?,
+            target.as_ref().map(|t| t.table.as_str()),
+            target.as_ref().map(|t| t.id.as_str()),
+            received_at,
+        ],
+    )?;
+    tx.commit()?;
+
+    Ok(Outcome::Suggested(Box::new(ReviewItem {
+        id,
+        source: source.to_owned(),
+        suggestion,
+        draft,
+        target,
+        status: ReviewStatus::Pending,
+        superseded_by: None,
+        record: None,
+        received_at,
+        decided_at: None,
+    })))
+}
+
+/// A new incident from a message, for reviewers who accept it as one.
+fn incident_record(draft: &Draft) -> Value {
+    let field = |key: &str| draft.record.get(key).cloned().unwrap_or(Value::Null);
+    json!({
+        "id": uuid::Uuid::new_v4().to_string(),
+        "title": field("title"),
+        "description": field("message"),
+        "type": cap::incident_type_of(&draft.categories, &draft.event),
+        "severity": field("severity"),
+        "status": "reported",
+        "location_name": field("affected_area"),
+        "latitude": field("latitude"),
+        "longitude": field("longitude"),
+        "affected_radius_km": field("radius_km"),
+    })
+}
+
+/// Changes a later message of a thread makes to the record the thread
+/// became: a cancel closes it, anything else brings it up to date.
+fn update_payload(draft: &Draft, target: &RecordRef) -> Value {
+    let field = |key: &str| draft.record.get(key).cloned().unwrap_or(Value::Null);
+    let cancelled = draft.msg_type == "Cancel";
+    let mut payload = match (target.table.as_str(), cancelled) {
+        ("incidents", true) => json!({
+            "status": "resolved",
+            "resolution_notes": format!("Cancelled by {}", draft.sender),
+        }),
+        ("incidents", false) => json!({
+            "title": field("title"),
+            "description": field("message"),
+            "severity": field("severity"),
+        }),
+        (_, true) => json!({ "is_active": false }),
+        (_, false) => json!({
+            "title": field("title"),
+            "type": field("type"),
+            "severity": field("severity"),
+            "message": field("message"),
+            "expires_at": field("expires_at"),
+            "is_active": field("is_active"),
+        }),
+    };
+    // Keep the record's area unless the message has one
+    if !cancelled && draft.record.get("latitude").is_some_and(|v| !v.is_null()) {
+        let (area, radius) = if target.table == "incidents" {
+            ("location_name", "affected_radius_km")
+        } else {
+            ("affected_area", "radius_km")
+        };
+        payload[area] = field("affected_area");
+        payload["latitude"] = field("latitude");
+        payload["longitude"] = field("longitude");
+        payload[radius] = field("radius_km");
+    }
+    payload["id"] = json!(target.id);
+    payload
+}
+
+/// Queue the record a reviewed message becomes and mark it accepted.
+/// `kind` overrides the suggestion for messages that start a thread.
+fn accept(
+    conn: &mut Connection,
+    id: &str,
+    kind: Option<Suggestion>,
+    created_by: &str,
+) -> Result<ReviewItem> {
+    let item = get_item(conn, id)?.ok_or_else(|| Error::NotFound("review item".into()))?;
+    if item.status != ReviewStatus::Pending {
+        return Err(Error::Invalid(format!(
+            "this message was already {}",
+            item.status.as_str()
+        )));
+    }
+    let (table, operation, payload, base) = match &item.target {
+        Some(target) => (
+            target.table.clone(),
+            Operation::Update,
+            update_payload(&item.draft, target),
+            mirror::get(conn, &target.table, &target.id)?,
+        ),
+        None => {
+            let kind = kind.unwrap_or(item.suggestion);
+            let mut record = match kind {
+                Suggestion::Alert => item.draft.record.clone(),
+                Suggestion::Incident => incident_record(&item.draft),
+            };
+            record["created_by"] = json!(created_by);
+            (kind.table().to_owned(), Operation::Insert, record, None)
+        }
+    };
+    let record_id = payload
+        .get("id")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_owned();
+
+    let tx = conn.transaction()?;
+    offline_queue::enqueue(
+        &tx,
+        NewMutation {
+            table: table.clone(),
+            operation,
+            payload,
+            base_updated_at: base
+                .as_ref()
+                .and_then(|b| b.get("updated_at"))
+                .and_then(Value::as_str)
+                .map(str::to_owned),
+            base,
+            strategy: None,
+        },
+    )?;
+    tx.execute(
+        "UPDATE cap_review SET status = 'accepted', record_table = ?2, record_id = ?3,
+             decided_at = ?4
+         WHERE id = ?1",
+        params![id, table, record_id, timestamp(Utc::now())],
+    )?;
+    tx.commit()?;
+    get_item(conn, id)?.ok_or_else(|| Error::NotFound("review item".into()))
+}
+
+fn dismiss(conn: &Connection, id: &str) -> Result<ReviewItem> {
+    let changed = conn.execute(
+        "UPDATE cap_review SET status = 'dismissed', decided_at = ?2
+         WHERE id = ?1 AND status = 'pending'",
+        params![id, timestamp(Utc::now())],
+    )?;
+    let item = get_item(conn, id)?.ok_or_else(|| Error::NotFound("review item".into()))?;
+    if changed == 0 {
+        return Err(Error::Invalid(format!(
+            "this message was already {}",
+            item.status.as_str()
+        )));
+    }
+    Ok(item)
+}
+
+fn prune(conn: &Connection) -> Result<()> {
+    let cutoff = timestamp(Utc::now() - chrono::Duration::days(RETENTION_DAYS));
+    conn.execute("DELETE FROM cap_feed_entries WHERE seen_at < ?1", [&cutoff])?;
+    conn.execute(
+        "DELETE FROM cap_review WHERE status <> 'pending' AND decided_at < ?1",
+        [&cutoff],
+    )?;
+    conn.execute(
+        "DELETE FROM cap_messages WHERE received_at < ?1
+         AND (review_id IS NULL OR review_id NOT IN (SELECT id FROM cap_review))",
+        [&cutoff],
+    )?;
+    Ok(())
+}
+
+// ─── Sources ────────────────────────────────────────────────────
+
+/// What one look at a source turned up.
+#[derive(Debug, Default)]
+struct Tally {
+    suggested: Vec<ReviewItem>,
+    skipped: u32,
+    rejected: u32,
+    /// Feed entries whose message could not be fetched.
+    unreachable: u32,
+    /// Why the latest message was rejected or entry could not be fetched.
+    problem: Option<String>,
+}
+
+impl Tally {
+    fn is_empty(&self) -> bool {
+        self.suggested.is_empty()
+            && self.skipped == 0
+            && self.rejected == 0
+            && self.unreachable == 0
+    }
+
+    fn reject(&mut self, source: &str, issues: &[Issue]) {
+        self.rejected += 1;
+        self.problem = Some(format!(
+            "Rejected message from {source}: {}",
+            describe_issues(issues)
+        ));
+    }
+
+    fn add(&mut self, outcome: Outcome) {
+        match outcome {
+            Outcome::Suggested(item) => self.suggested.push(*item),
+            Outcome::Duplicate | Outcome::Skipped => self.skipped += 1,
+        }
+    }
+}
+
+fn describe_issues(issues: &[Issue]) -> String {
+    issues
+        .iter()
+        .map(|issue| match issue.path.as_str() {
+            "" => issue.message.clone(),
+            path => format!("{path}: {}", issue.message),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Move a handled file into `sub` of its folder, never over another.
+fn move_into(folder: &Path, sub: &str, file: &Path) -> Result<PathBuf> {
+    let dir = folder.join(sub);
+    std::fs::create_dir_all(&dir)?;
+    let name = file
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut target = dir.join(&name);
+    if target.exists() {
+        target = dir.join(format!("{}-{name}", Utc::now().format("%Y%m%dT%H%M%S%.3f")));
+    }
+    std::fs::rename(file, &target)?;
+    Ok(target)
+}
+
+fn is_cap_file(path: &Path) -> bool {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+    matches!(ext.as_deref(), Some("xml" | "cap"))
+}
+
+/// Read every settled CAP file in the drop folder, then move it to
+/// `processed/`, or to `rejected/` with a note of what was wrong.
+fn scan_folder(app: &AppHandle, folder: &Path, config: &CapConfig) -> Result<Tally> {
+    let db = app.state::<Db>();
+    let mut tally = Tally::default();
+    for entry in std::fs::read_dir(folder)? {
+        let path = entry?.path();
+        let settled = std::fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age >= SETTLE_TIME);
+        if !path.is_file() || !is_cap_file(&path) || !settled {
+            continue;
+        }
+        let result = cap::read_file(&path)
+            .map_err(|e| vec![Issue::file(e)])
+            .and_then(|text| cap::read(&text, &config.language));
+        match result {
+            Ok(draft) => {
+                // A locked vault leaves the file for the next scan
+                let source = path.display().to_string();
+                tally.add(db.with(|conn| ingest(conn, config, draft, &source))?);
+                move_into(folder, PROCESSED_DIR, &path)?;
+            }
+            Err(issues) => {
+                tally.reject(&path.display().to_string(), &issues);
+                let moved = move_into(folder, REJECTED_DIR, &path)?;
+                let mut note = moved.into_os_string();
+                note.push(".errors.txt");
+                std::fs::write(note, describe_issues(&issues))?;
+            }
+        }
+    }
+    Ok(tally)
+}
+
+/// An Atom entry or RSS item: where to fetch its CAP message, or the
+/// message itself when the feed carries it inline.
+struct FeedEntry<'a> {
+    link: Option<String>,
+    updated: Option<String>,
+    embedded: Option<&'a Node>,
+}
+
+/// The entry's CAP link: one typed as CAP, else the main link.
+fn atom_link(entry: &Node) -> Option<String> {
+    let links: Vec<&Node> = entry.children_named("link").collect();
+    let is_cap = |link: &&&Node| link.attr("type").is_some_and(|t| t.contains("cap"));
+    let is_main = |link: &&&Node| matches!(link.attr("rel"), None | Some("alternate"));
+    links
+        .iter()
+        .find(is_cap)
+        .or_else(|| links.iter().find(is_main))
+        .or_else(|| links.first())
+        .and_then(|link| link.attr("href"))
+        .map(str::to_owned)
+}
+
+fn first_alert(node: &Node) -> Option<&Node> {
+    let mut alerts = Vec::new();
+    node.descendants("alert", &mut alerts);
+    alerts.first().copied()
+}
+
+fn feed_entries(root: &Node) -> Vec<FeedEntry<'_>> {
+    let mut entries = Vec::new();
+    if root.name == "feed" {
+        for entry in root.children_named("entry") {
+            entries.push(FeedEntry {
+                link: atom_link(entry),
+                updated: entry
+                    .text_of("updated")
+                    .or_else(|| entry.text_of("published")),
+                embedded: first_alert(entry),
+            });
+        }
+    } else {
+        // RSS 2.0 items sit in <channel>, RSS 1.0 items beside it
+        let mut items = Vec::new();
+        root.descendants("item", &mut items);
+        for item in items {
+            let enclosure = item
+                .children_named("enclosure")
+                .find(|e| e.attr("type").is_some_and(|t| t.contains("cap")))
+                .and_then(|e| e.attr("url"))
+                .map(str::to_owned);
+            entries.push(FeedEntry {
+                link: enclosure.or_else(|| item.text_of("link")),
+                updated: item.text_of("pubDate").or_else(|| item.text_of("date")),
+                embedded: first_alert(item),
+            });
+        }
+    }
+    entries
+}
+
+async fn fetch(client: &Client, url: &str) -> Result<String> {
+    let response = client
+        .get(url)
+        .header(ACCEPT, ACCEPT_TYPES)
+        .send()
+        .await?
+        .error_for_status()?;
+    if response
+        .content_length()
+        .is_some_and(|len| len > MAX_FETCH_BYTES as u64)
+    {
+        return Err(Error::Invalid(format!("{url} is too big to be a CAP feed")));
+    }
+    let bytes = response.bytes().await?;
+    if bytes.len() > MAX_FETCH_BYTES {
+        return Err(Error::Invalid(format!("{url} is too big to be a CAP feed")));
+    }
+    String::from_utf8(bytes.to_vec())
+        .map_err(|_| Error::Invalid(format!("{url} did not return UTF-8 text")))
+}
+
+fn entry_seen(conn: &Connection, url: &str, updated: Option<&str>) -> Result<bool> {
+    let seen: Option<Option<String>> = conn
+        .query_row(
+            "SELECT updated FROM cap_feed_entries WHERE url = ?1",
+            [url],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(seen.is_some_and(|seen| seen.as_deref() == updated))
+}
+
+fn mark_entry(conn: &Connection, url: &str, updated: Option<&str>) -> Result<()> {
+    conn.execute(
+        "INSERT INTO cap_feed_entries (url, updated, seen_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT (url) DO UPDATE SET updated = excluded.updated, seen_at = excluded.seen_at",
+        params![url, updated, timestamp(Utc::now())],
+    )?;
+    Ok(())
+}
+
+/// Queue a message read from a feed, or note why it isn't valid CAP.
+fn consider(
+    db: &Db,
+    config: &CapConfig,
+    tally: &mut Tally,
+    result: std::result::Result<Draft, Vec<Issue>>,
+    source: &str,
+) -> Result<()> {
+    match result {
+        Ok(draft) => tally.add(db.with(|conn| ingest(conn, config, draft, source))?),
+        Err(issues) => tally.reject(source, &issues),
+    }
+    Ok(())
+}
+
+/// Poll one feed: a single CAP message, or an Atom or RSS feed whose
+/// entries link to (or carry) CAP messages. Entries already fetched at
+/// their current update are skipped without a request.
+async fn poll_feed(
+    app: &AppHandle,
+    client: &Client,
+    feed: &str,
+    config: &CapConfig,
+) -> Result<Tally> {
+    let db = app.state::<Db>();
+    let mut tally = Tally::default();
+    let body = fetch(client, feed).await?;
+    let document = interchange::parse_xml(&body)?;
+    let root = document
+        .children
+        .first()
+        .ok_or_else(|| Error::Invalid(format!("{feed} returned an empty document")))?;
+
+    match root.name.as_str() {
+        "alert" => consider(
+            &db,
+            config,
+            &mut tally,
+            cap::read(&body, &config.language),
+            feed,
+        )?,
+        "feed" | "rss" | "RDF" => {
+            let base = url::Url::parse(feed).ok();
+            let mut fetched = 0;
+            for entry in feed_entries(root) {
+                let link = entry.link.as_deref().map(|link| {
+                    base.as_ref()
+                        .and_then(|base| base.join(link).ok())
+                        .map_or_else(|| link.to_owned(), |url| url.to_string())
+                });
+                if let Some(alert) = entry.embedded {
+                    let source = link.as_deref().unwrap_or(feed);
+                    consider(
+                        &db,
+                        config,
+                        &mut tally,
+                        cap::read_alert(alert, &root.attrs, &config.language),
+                        source,
+                    )?;
+                    continue;
+                }
+                let Some(link) = link else {
+                    continue;
+                };
+                let updated = entry.updated.as_deref();
+                if db.with(|conn| entry_seen(conn, &link, updated))? {
+                    continue;
+                }
+                if fetched == MAX_ENTRIES_PER_POLL {
+                    break;
+                }
+                fetched += 1;
+                match fetch(client, &link).await {
+                    Ok(text) => consider(
+                        &db,
+                        config,
+                        &mut tally,
+                        cap::read(&text, &config.language),
+                        &link,
+                    )?,
+                    Err(e) => {
+                        // Tried again next poll
+                        tally.unreachable += 1;
+                        tally.problem = Some(format!("Could not fetch {link}: {e}"));
+                        continue;
+                    }
+                }
+                db.with(|conn| mark_entry(conn, &link, updated))?;
+            }
+        }
+        other => {
+            return Err(Error::Invalid(format!(
+                "{feed} is not a CAP message or an Atom or RSS feed (root <{other}>)"
+            )))
+        }
+    }
+    Ok(tally)
+}
+
+// ─── Service ────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceKind {
+    Feed,
+    Folder,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceStatus {
+    pub kind: SourceKind,
+    /// Feed URL or folder path.
+    pub location: String,
+    pub last_checked_at: Option<String>,
+    pub error: Option<String>,
+    /// Messages queued for review since the watcher started.
+    pub suggested: u32,
+    /// Messages already seen, or not relevant.
+    pub skipped: u32,
+    /// Messages that are not valid CAP.
+    pub rejected: u32,
+    /// Feed entries whose message could not be fetched; retried next poll.
+    pub unreachable: u32,
+    /// Why the latest message was rejected or entry could not be fetched.
+    pub last_problem: Option<String>,
+}
+
+impl SourceStatus {
+    fn new(kind: SourceKind, location: &str) -> Self {
+        Self {
+            kind,
+            location: location.to_owned(),
+            last_checked_at: None,
+            error: None,
+            suggested: 0,
+            skipped: 0,
+            rejected: 0,
+            unreachable: 0,
+            last_problem: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchStatus {
+    pub watching: bool,
+    pub sources: Vec<SourceStatus>,
+    /// Messages waiting for review; 0 while the vault is locked.
+    pub pending: u32,
+}
+
+#[derive(Default)]
+struct Inner {
+    config: Option<CapConfig>,
+    stop: Option<Arc<AtomicBool>>,
+    /// Feeds in configured order, then the drop folder.
+    sources: Vec<SourceStatus>,
+}
+
+/// Polls partner CAP feeds and the drop folder in the background, feeding
+/// the review queue.
+#[derive(Default)]
+pub struct CapWatcher {
+    inner: Mutex<Inner>,
+}
+
+impl CapWatcher {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn status(&self, app: &AppHandle) -> WatchStatus {
+        let pending = app
+            .state::<Db>()
+            .with(|conn| pending_count(conn))
+            .unwrap_or(0);
+        let inner = self.lock();
+        WatchStatus {
+            watching: inner.stop.is_some(),
+            sources: inner.sources.clone(),
+            pending,
+        }
+    }
+
+    fn emit_status(&self, app: &AppHandle) {
+        let _ = app.emit(STATUS_EVENT, self.status(app));
+    }
+
+    /// The running configuration and its stop flag.
+    fn current(&self) -> Option<(CapConfig, Arc<AtomicBool>)> {
+        let inner = self.lock();
+        Some((inner.config.clone()?, inner.stop.clone()?))
+    }
+
+    /// Record a look at one source, unless it belongs to a generation that
+    /// has since been stopped.
+    fn record(&self, stop: &Arc<AtomicBool>, index: usize, result: &Result<Tally>) -> bool {
+        let mut inner = self.lock();
+        if !inner.stop.as_ref().is_some_and(|s| Arc::ptr_eq(s, stop)) {
+            return false;
+        }
+        let Some(source) = inner.sources.get_mut(index) else {
+            return false;
+        };
+        source.last_checked_at = Some(timestamp(Utc::now()));
+        match result {
+            Ok(tally) => {
+                source.error = None;
+                source.suggested += tally.suggested.len() as u32;
+                source.skipped += tally.skipped;
+                source.rejected += tally.rejected;
+                source.unreachable += tally.unreachable;
+                if tally.problem.is_some() {
+                    source.last_problem.clone_from(&tally.problem);
+                }
+            }
+            Err(e) => source.error = Some(e.to_string()),
+        }
+        true
+    }
+
+    fn stop(&self) {
+        let mut inner = self.lock();
+        if let Some(stop) = inner.stop.take() {
+            stop.store(true, Ordering::Release);
+        }
+        inner.config = None;
+        inner.sources.clear();
+    }
+
+    pub fn start(&self, app: &AppHandle, config: CapConfig) {
+        self.stop();
+        if config.watches() {
+            let stop = Arc::new(AtomicBool::new(false));
+            {
+                let mut inner = self.lock();
+                inner.sources = config
+                    .feeds
+                    .iter()
+                    .map(|feed| SourceStatus::new(SourceKind::Feed, feed))
+                    .chain(
+                        config
+                            .drop_folder
+                            .as_deref()
+                            .map(|folder| SourceStatus::new(SourceKind::Folder, folder)),
+                    )
+                    .collect();
+                inner.config = Some(config.clone());
+                inner.stop = Some(stop.clone());
+            }
+            tauri::async_runtime::spawn(run(app.clone(), config, stop));
+        }
+        self.emit_status(app);
+    }
+}
+
+/// Tell the reviewer: an event for open windows, a desktop notification,
+/// and the count on the tray icon.
+fn announce(app: &AppHandle, new: &[ReviewItem]) {
+    let pending = app
+        .state::<Db>()
+        .with(|conn| pending_count(conn))
+        .unwrap_or(0);
+    let _ = app.emit(REVIEW_EVENT, pending);
+    refresh_tray(app, pending);
+    let Some(first) = new.first() else {
+        return;
+    };
+    let title = first
+        .draft
+        .record
+        .get("title")
+        .and_then(Value::as_str)
+        .unwrap_or(&first.draft.event);
+    let body = match new.len() {
+        1 => format!("{title} ({})", first.draft.sender),
+        n => format!("{title} and {} more", n - 1),
+    };
+    if let Err(e) = app
+        .notification()
+        .builder()
+        .title("CAP messages to review")
+        .body(body)
+        .show()
+    {
+        eprintln!("[cap] Notification failed: {e}");
+    }
+}
+
+fn refresh_tray(app: &AppHandle, pending: u32) {
+    if let Some(tray) = app.tray_by_id(crate::TRAY_ID) {
+        let tooltip = match pending {
+            0 => "DisasterConnect".to_owned(),
+            1 => "DisasterConnect · 1 CAP message to review".to_owned(),
+            n => format!("DisasterConnect · {n} CAP messages to review"),
+        };
+        let _ = tray.set_tooltip(Some(tooltip));
+    }
+}
+
+/// One look at every source: the drop folder always, feeds when `feeds`
+/// is set.
+async fn pass(
+    app: &AppHandle,
+    client: &Client,
+    config: &CapConfig,
+    stop: &Arc<AtomicBool>,
+    feeds: bool,
+) {
+    let watcher = app.state::<CapWatcher>();
+    let mut new = Vec::new();
+    let mut changed = false;
+
+    if let Some(folder) = &config.drop_folder {
+        let (scan_app, scan_config, folder) = (app.clone(), config.clone(), PathBuf::from(folder));
+        let result = match tauri::async_runtime::spawn_blocking(move || {
+            scan_folder(&scan_app, &folder, &scan_config)
+        })
+        .await
+        {
+            Ok(result) => result,
+            Err(e) => Err(e.into()),
+        };
+        // Quiet scans don't need to wake the UI
+        let busy = !matches!(&result, Ok(tally) if tally.is_empty());
+        changed |= watcher.record(stop, config.feeds.len(), &result) && busy;
+        if let Ok(tally) = result {
+            new.extend(tally.suggested);
+        }
+    }
+
+    if feeds {
+        for (index, feed) in config.feeds.iter().enumerate() {
+            if stop.load(Ordering::Acquire) {
+                return;
+            }
+            let result = poll_feed(app, client, feed, config).await;
+            if let Err(e) = &result {
+                eprintln!("[cap] Polling {feed} failed: {e}");
+            }
+            changed |= watcher.record(stop, index, &result);
+            if let Ok(tally) = result {
+                new.extend(tally.suggested);
+            }
+        }
+    }
+
+    if !new.is_empty() {
+        announce(app, &new);
+    }
+    if changed {
+        watcher.emit_status(app);
+    }
+}
+
+async fn run(app: AppHandle, config: CapConfig, stop: Arc<AtomicBool>) {
+    let client = match Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(FETCH_TIMEOUT)
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("[cap] Could not build HTTP client: {e}");
+            return;
+        }
+    };
+    let poll_every = Duration::from_secs(u64::from(config.poll_minutes) * 60);
+    let mut last_poll: Option<Instant> = None;
+    let mut last_prune: Option<Instant> = None;
+    let mut ticker = tokio::time::interval(TICK);
+    loop {
+        ticker.tick().await;
+        if stop.load(Ordering::Acquire) {
+            break;
+        }
+        let feeds_due = match last_poll {
+            Some(at) => at.elapsed() >= poll_every,
+            None => true,
+        };
+        pass(&app, &client, &config, &stop, feeds_due).await;
+        if feeds_due {
+            last_poll = Some(Instant::now());
+        }
+        let prune_due = match last_prune {
+            Some(at) => at.elapsed() >= PRUNE_INTERVAL,
+            None => true,
+        };
+        if prune_due && app.state::<Db>().with(|conn| prune(conn)).is_ok() {
+            last_prune = Some(Instant::now());
+        }
+    }
+}
+
+/// Start watching whatever was configured last time.
+pub fn start_saved(app: &AppHandle) {
+    app.state::<CapWatcher>().start(app, cap::load_config(app));
+    let pending = app
+        .state::<Db>()
+        .with(|conn| pending_count(conn))
+        .unwrap_or(0);
+    refresh_tray(app, pending);
+}
+
+// ─── Commands ───────────────────────────────────────────────────
+
+#[tauri::command]
+pub fn cap_watch_status(app: AppHandle, watcher: State<'_, CapWatcher>) -> WatchStatus {
+    watcher.status(&app)
+}
+
+/// Check every feed and the drop folder now rather than at the next poll.
+#[tauri::command]
+pub async fn cap_watch_poll(app: AppHandle) -> Result<WatchStatus> {
+    let watcher = app.state::<CapWatcher>();
+    let (config, stop) = watcher
+        .current()
+        .ok_or_else(|| Error::Invalid("no CAP feeds or drop folder are configured".into()))?;
+    let client = Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(FETCH_TIMEOUT)
+        .build()?;
+    pass(&app, &client, &config, &stop, true).await;
+    Ok(watcher.status(&app))
+}
+
+#[tauri::command]
+pub fn cap_review_list(
+    db: State<'_, Db>,
+    status: Option<ReviewStatus>,
+    limit: Option<u32>,
+) -> Result<Vec<ReviewItem>> {
+    db.with(|conn| list_items(conn, status, limit.unwrap_or(DEFAULT_LIST_LIMIT)))
+}
+
+/// Accept a suggestion: queue the new alert or incident (or the update to
+/// the record its thread became) and mark the message accepted.
+#[tauri::command]
+pub fn cap_review_accept(
+    app: AppHandle,
+    db: State<'_, Db>,
+    id: String,
+    kind: Option<Suggestion>,
+    created_by: String,
+) -> Result<ReviewItem> {
+    let (item, pending, queued) = db.with(|conn| {
+        let item = accept(conn, &id, kind, &created_by)?;
+        Ok((item, pending_count(conn)?, offline_queue::count(conn)?))
+    })?;
+    let _ = app.emit(offline_queue::CHANGED_EVENT, queued);
+    let _ = app.emit(REVIEW_EVENT, pending);
+    refresh_tray(&app, pending);
+    Ok(item)
+}
+
+#[tauri::command]
+pub fn cap_review_dismiss(app: AppHandle, db: State<'_, Db>, id: String) -> Result<ReviewItem> {
+    let (item, pending) = db.with(|conn| Ok((dismiss(conn, &id)?, pending_count(conn)?)))?;
+    let _ = app.emit(REVIEW_EVENT, pending);
+    refresh_tray(&app, pending);
+    Ok(item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cap::{Reference, Region};
+    use crate::geo::Point;
+
+    const SENDER: &str = "alerts@partner.example";
+
+    fn db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        for migration in crate::migrations::MIGRATIONS {
+            conn.execute_batch(migration.sql).unwrap();
+        }
+        conn
+    }
+
+    fn message(identifier: &str, msg_type: &str, references: &[&str]) -> Draft {
+        Draft {
+            identifier: identifier.into(),
+            sender: SENDER.into(),
+            sent: "2024-05-01T10:00:00Z".into(),
+            status: "Actual".into(),
+            msg_type: msg_type.into(),
+            references: references
+                .iter()
+                .map(|id| Reference {
+                    sender: SENDER.into(),
+                    identifier: (*id).into(),
+                    sent: "2024-05-01T09:00:00-00:00".into(),
+                })
+                .collect(),
+            event: "Flood".into(),
+            categories: vec!["Met".into()],
+            language: "en-US".into(),
+            record: json!({
+                "id": uuid::Uuid::new_v4().to_string(),
+                "title": format!("Flood {identifier}"),
+                "type": "warning",
+                "severity": "high",
+                "message": "Move to higher ground",
+                "latitude": 47.37,
+                "longitude": 8.54,
+                "radius_km": 10.0,
+                "is_active": msg_type != "Cancel",
+            }),
+            warnings: Vec::new(),
+        }
+    }
+
+    fn suggested(outcome: Outcome) -> ReviewItem {
+        match outcome {
+            Outcome::Suggested(item) => *item,
+            Outcome::Duplicate => panic!("expected a suggestion, got a duplicate"),
+            Outcome::Skipped => panic!("expected a suggestion, got a skip"),
+        }
+    }
+
+    fn skip_reason(conn: &Connection, identifier: &str) -> Option<String> {
+        conn.query_row(
+            "SELECT skipped FROM cap_messages WHERE identifier = ?1",
+            [identifier],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn duplicates_are_dropped_by_sender_and_identifier() {
+        let mut conn = db();
+        let config = CapConfig::default();
+        suggested(ingest(&mut conn, &config, message("m1", "Alert", &[]), "feed").unwrap());
+        assert!(matches!(
+            ingest(
+                &mut conn,
+                &config,
+                message("m1", "Alert", &[]),
+                "other feed"
+            )
+            .unwrap(),
+            Outcome::Duplicate
+        ));
+        // The same identifier from someone else is a different message
+        let mut other = message("m1", "Alert", &[]);
+        other.sender = "someone@else.example".into();
+        suggested(ingest(&mut conn, &config, other, "feed").unwrap());
+        assert_eq!(pending_count(&conn).unwrap(), 2);
+    }
+
+    #[test]
+    fn updates_supersede_pending_messages() {
+        let mut conn = db();
+        let config = CapConfig::default();
+        let first =
+            suggested(ingest(&mut conn, &config, message("m1", "Alert", &[]), "feed").unwrap());
+        let update = suggested(
+            ingest(&mut conn, &config, message("m2", "Update", &["m1"]), "feed").unwrap(),
+        );
+        assert!(update.target.is_none());
+
+        let first = get_item(&conn, &first.id).unwrap().unwrap();
+        assert_eq!(first.status, ReviewStatus::Superseded);
+        assert_eq!(first.superseded_by.as_deref(), Some(update.id.as_str()));
+        assert!(dismiss(&conn, &first.id).is_err());
+        assert_eq!(pending_count(&conn).unwrap(), 1);
+    }
+
+    #[test]
+    fn cancel_without_an_accepted_target_is_skipped() {
+        let mut conn = db();
+        let config = CapConfig::default();
+        let first =
+            suggested(ingest(&mut conn, &config, message("m1", "Alert", &[]), "feed").unwrap());
+        assert!(matches!(
+            ingest(&mut conn, &config, message("m2", "Cancel", &["m1"]), "feed").unwrap(),
+            Outcome::Skipped
+        ));
+        assert_eq!(
+            skip_reason(&conn, "m2").as_deref(),
+            Some("cancels messages still awaiting review")
+        );
+        let first = get_item(&conn, &first.id).unwrap().unwrap();
+        assert_eq!(first.status, ReviewStatus::Superseded);
+        assert_eq!(first.superseded_by, None);
+
+        assert!(matches!(
+            ingest(
+                &mut conn,
+                &config,
+                message("m3", "Cancel", &["unknown"]),
+                "feed"
+            )
+            .unwrap(),
+            Outcome::Skipped
+        ));
+        assert_eq!(
+            skip_reason(&conn, "m3").as_deref(),
+            Some("cancels a message that was never suggested")
+        );
+    }
+
+    #[test]
+    fn cancel_of_an_accepted_message_closes_its_record() {
+        let mut conn = db();
+        let config = CapConfig::default();
+        let first =
+            suggested(ingest(&mut conn, &config, message("m1", "Alert", &[]), "feed").unwrap());
+        let accepted = accept(&mut conn, &first.id, None, "user-1").unwrap();
+        let record = accepted.record.unwrap();
+        assert_eq!(record.table, "alerts");
+
+        let cancel = suggested(
+            ingest(&mut conn, &config, message("m2", "Cancel", &["m1"]), "feed").unwrap(),
+        );
+        assert_eq!(cancel.target.as_ref(), Some(&record));
+        assert_eq!(cancel.suggestion, Suggestion::Alert);
+        // Accepted messages are not superseded
+        assert_eq!(
+            get_item(&conn, &first.id).unwrap().unwrap().status,
+            ReviewStatus::Accepted
+        );
+
+        accept(&mut conn, &cancel.id, None, "user-1").unwrap();
+        let queued = offline_queue::list(&conn).unwrap();
+        assert_eq!(queued.len(), 2);
+        let close = &queued[1];
+        assert_eq!(close.operation, Operation::Update);
+        assert_eq!(close.payload["id"], json!(record.id));
+        assert_eq!(close.payload["is_active"], json!(false));
+    }
+
+    #[test]
+    fn severity_region_and_status_filter_suggestions() {
+        let mut conn = db();
+        let mut config = CapConfig {
+            min_severity: "critical".into(),
+            ..CapConfig::default()
+        };
+        assert!(matches!(
+            ingest(&mut conn, &config, message("m1", "Alert", &[]), "feed").unwrap(),
+            Outcome::Skipped
+        ));
+
+        config.min_severity = "high".into();
+        config.region = Some(Region {
+            center: Point {
+                lat: 40.71,
+                lng: -74.0,
+            },
+            radius_km: 50.0,
+        });
+        assert!(matches!(
+            ingest(&mut conn, &config, message("m2", "Alert", &[]), "feed").unwrap(),
+            Outcome::Skipped
+        ));
+        assert_eq!(
+            skip_reason(&conn, "m2").as_deref(),
+            Some("below the minimum severity or outside the region")
+        );
+
+        // The message's own radius counts towards reaching the region
+        config.region = Some(Region {
+            center: Point {
+                lat: 47.45,
+                lng: 8.54,
+            },
+            radius_km: 1.0,
+        });
+        suggested(ingest(&mut conn, &config, message("m3", "Alert", &[]), "feed").unwrap());
+
+        let mut test = message("m4", "Alert", &[]);
+        test.status = "Test".into();
+        assert!(matches!(
+            ingest(&mut conn, &config, test, "feed").unwrap(),
+            Outcome::Skipped
+        ));
+        assert_eq!(skip_reason(&conn, "m4").as_deref(), Some("status is Test"));
+    }
+}
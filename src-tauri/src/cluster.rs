@@ -0,0 +1,501 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::State;
+
+use crate::db::Db;
+use crate::error::{Error, Result};
+use crate::geo::{self, GeoEntity, Point};
+use crate::tiles::BoundingBox;
+
+/// Markers closer than this on screen are drawn as one cluster.
+const CLUSTER_CELL_PX: f64 = 60.0;
+/// Heatmap resolution on screen.
+const HEAT_CELL_PX: f64 = 24.0;
+/// From this zoom on every marker is drawn on its own.
+const MAX_CLUSTER_ZOOM: u8 = 17;
+const MAX_ZOOM: u8 = 22;
+/// Web Mercator stops here.
+const MAX_LAT: f64 = 85.051_128_78;
+const TILE_PX: f64 = 256.0;
+
+/// Statuses the map shows, like the dashboard hooks.
+const ACTIVE_INCIDENT_STATUSES: &[&str] = &["reported", "verified", "in_progress"];
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MapQuery {
+    /// The viewport, with longitudes as Leaflet reports them; one that
+    /// crosses the antimeridian (east past 180, or west > east) is searched
+    /// on both sides.
+    pub bounds: BoundingBox,
+    pub zoom: u8,
+    pub entities: Vec<GeoEntity>,
+    /// Only open incidents, usable resources and live SOS broadcasts.
+    #[serde(default)]
+    pub active_only: bool,
+    /// Column equality applied to every entity, like `MirrorQuery::eq`.
+    #[serde(default)]
+    pub eq: Map<String, Value>,
+    /// Also aggregate incident and SOS intensity into heatmap cells.
+    #[serde(default)]
+    pub heatmap: bool,
+}
+
+/// One marker or a group of nearby markers of the same entity.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Cluster {
+    pub entity: GeoEntity,
+    /// Mean position of the members.
+    pub point: Point,
+    pub count: usize,
+    /// Area to zoom to when the cluster is clicked.
+    pub bounds: BoundingBox,
+    /// Highest severity among the members, if they have one.
+    pub severity: Option<String>,
+    /// The record itself when `count` is 1. A missing `location_name` is
+    /// looked up when the marker is opened, not for every viewport.
+    pub record: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeatCell {
+    pub point: Point,
+    /// Sum of member intensities.
+    pub weight: f64,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MapAggregate {
+    pub clusters: Vec<Cluster>,
+    pub heat: Vec<HeatCell>,
+    /// Largest heat cell weight, to scale the heat layer.
+    pub max_weight: f64,
+    /// Records in view across all entities.
+    pub total: usize,
+}
+
+/// Position in world pixels at `zoom` (Web Mercator).
+fn world_px(point: Point, zoom: u8) -> (f64, f64) {
+    let size = TILE_PX * f64::from(1u32 << zoom);
+    let lat = point.lat.clamp(-MAX_LAT, MAX_LAT).to_radians();
+    let x = (point.lng + 180.0) / 360.0 * size;
+    let y = (1.0 - lat.tan().asinh() / PI) / 2.0 * size;
+    (x, y)
+}
+
+fn cell(point: Point, zoom: u8, cell_px: f64) -> (i64, i64) {
+    let (x, y) = world_px(point, zoom);
+    ((x / cell_px).floor() as i64, (y / cell_px).floor() as i64)
+}
+
+fn severity_rank(severity: &str) -> u8 {
+    match severity {
+        "critical" => 4,
+        "high" => 3,
+        "medium" => 2,
+        "low" => 1,
+        _ => 0,
+    }
+}
+
+/// Heat contribution of one record; SOS broadcasts count as critical.
+fn intensity(entity: GeoEntity, record: &Value) -> f64 {
+    if entity == GeoEntity::Sos {
+        return 1.0;
+    }
+    match record.get("severity").and_then(Value::as_str) {
+        Some("critical") => 1.0,
+        Some("high") => 0.7,
+        Some("medium") => 0.4,
+        _ => 0.2,
+    }
+}
+
+fn is_active(entity: GeoEntity, record: &Value) -> bool {
+    let status = record.get("status").and_then(Value::as_str);
+    match entity {
+        GeoEntity::Incident => status.is_some_and(|s| ACTIVE_INCIDENT_STATUSES.contains(&s)),
+        GeoEntity::Resource => status != Some("unavailable"),
+        GeoEntity::Sos => record.get("is_active").and_then(Value::as_bool) != Some(false),
+        GeoEntity::Team => true,
+    }
+}
+
+#[derive(Default)]
+struct Group {
+    lat: f64,
+    lng: f64,
+    bounds: Option<BoundingBox>,
+    severity: Option<String>,
+    first: Option<Value>,
+}
+
+impl Group {
+    fn add(&mut self, point: Point, record: Value) {
+        self.lat += point.lat;
+        self.lng += point.lng;
+        let b = self.bounds.get_or_insert(BoundingBox {
+            west: point.lng,
+            south: point.lat,
+            east: point.lng,
+            north: point.lat,
+        });
+        b.west = b.west.min(point.lng);
+        b.south = b.south.min(point.lat);
+        b.east = b.east.max(point.lng);
+        b.north = b.north.max(point.lat);
+        if let Some(severity) = record.get("severity").and_then(Value::as_str) {
+            if self
+                .severity
+                .as_deref()
+                .is_none_or(|s| severity_rank(s) < severity_rank(severity))
+            {
+                self.severity = Some(severity.to_owned());
+            }
+        }
+        // Only a lone member is sent back
+        if self.first.is_none() {
+            self.first = Some(record);
+        }
+    }
+}
+
+/// The viewport as one box within ±180°, or two when it crosses the
+/// antimeridian.
+fn viewport(bounds: &BoundingBox) -> Result<Vec<BoundingBox>> {
+    let side = |west: f64, east: f64| BoundingBox {
+        west,
+        south: bounds.south.clamp(-90.0, 90.0),
+        east,
+        north: bounds.north.clamp(-90.0, 90.0),
+    };
+    let mut span = bounds.east - bounds.west;
+    if span < 0.0 {
+        span += 360.0;
+    }
+    let areas = if span >= 360.0 {
+        vec![side(-180.0, 180.0)]
+    } else {
+        let west = (bounds.west + 180.0).rem_euclid(360.0) - 180.0;
+        let east = west + span;
+        if east <= 180.0 {
+            vec![side(west, east)]
+        } else {
+            vec![side(west, 180.0), side(-180.0, east - 360.0)]
+        }
+    };
+    for area in &areas {
+        area.validate()?;
+    }
+    Ok(areas)
+}
+
+/// Clusters (and optionally heat cells) for everything of `query.entities`
+/// in the viewport.
+pub fn aggregate(conn: &rusqlite::Connection, query: &MapQuery) -> Result<MapAggregate> {
+    if query.zoom > MAX_ZOOM {
+        return Err(Error::Invalid(format!("zoom must be at most {MAX_ZOOM}")));
+    }
+    let areas = viewport(&query.bounds)?;
+
+    let mut groups: HashMap<(GeoEntity, i64, i64), (usize, Group)> = HashMap::new();
+    let mut heat: HashMap<(i64, i64), (f64, f64, f64)> = HashMap::new();
+    let mut total = 0;
+    for &entity in &query.entities {
+        let hits = areas
+            .iter()
+            .map(|area| geo::within_bounds(conn, entity, area, &query.eq))
+            .collect::<Result<Vec<_>>>()?;
+        for hit in hits.into_iter().flatten() {
+            if query.active_only && !is_active(entity, &hit.record) {
+                continue;
+            }
+            total += 1;
+
+            if query.heatmap && matches!(entity, GeoEntity::Incident | GeoEntity::Sos) {
+                let w = intensity(entity, &hit.record);
+                let h = heat
+                    .entry(cell(hit.point, query.zoom, HEAT_CELL_PX))
+                    .or_default();
+                // Weighted centre keeps cells from snapping to a visible grid
+                h.0 += hit.point.lat * w;
+                h.1 += hit.point.lng * w;
+                h.2 += w;
+            }
+
+            let key = if query.zoom >= MAX_CLUSTER_ZOOM {
+                // One group per record
+                (entity, total as i64, 0)
+            } else {
+                let (cx, cy) = cell(hit.point, query.zoom, CLUSTER_CELL_PX);
+                (entity, cx, cy)
+            };
+            let (count, group) = groups.entry(key).or_default();
+            *count += 1;
+            group.add(hit.point, hit.record);
+        }
+    }
+
+    let clusters = groups
+        .into_iter()
+        .filter_map(|((entity, _, _), (count, mut group))| {
+            let n = count as f64;
+            Some(Cluster {
+                entity,
+                point: Point {
+                    lat: group.lat / n,
+                    lng: group.lng / n,
+                },
+                count,
+                bounds: group.bounds?,
+                severity: group.severity,
+                record: if count == 1 { group.first.take() } else { None },
+            })
+        })
+        .collect();
+
+    let heat: Vec<HeatCell> = heat
+        .into_values()
+        .filter(|(_, _, w)| *w > 0.0)
+        .map(|(lat, lng, w)| HeatCell {
+            point: Point {
+                lat: lat / w,
+                lng: lng / w,
+            },
+            weight: w,
+        })
+        .collect();
+    let max_weight = heat.iter().map(|c| c.weight).fold(0.0, f64::max);
+
+    Ok(MapAggregate {
+        clusters,
+        heat,
+        max_weight,
+        total,
+    })
+}
+
+// ─── Commands ───────────────────────────────────────────────────
+
+/// Pre-clustered markers and heatmap cells for the map viewport, from the
+/// local cache.
+#[tauri::command]
+pub fn map_aggregate(db: State<'_, Db>, query: MapQuery) -> Result<MapAggregate> {
+    db.with(|conn| aggregate(conn, &query))
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::{params, Connection};
+    use serde_json::json;
+
+    use super::*;
+
+    fn db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        for migration in crate::migrations::MIGRATIONS {
+            conn.execute_batch(migration.sql).unwrap();
+        }
+        conn
+    }
+
+    fn add(conn: &Connection, table: &str, record: Value) {
+        conn.execute(
+            "INSERT INTO mirror_records (table_name, id, data) VALUES (?1, ?2, ?3)",
+            params![table, record["id"].as_str().unwrap(), record],
+        )
+        .unwrap();
+    }
+
+    fn incident(id: &str, lat: f64, lng: f64, severity: &str, status: &str) -> Value {
+        json!({"id": id, "latitude": lat, "longitude": lng, "severity": severity, "status": status})
+    }
+
+    /// Three incidents a few hundred metres apart in one town, one in
+    /// another town, and an SOS next to the first three.
+    fn map() -> Connection {
+        let conn = db();
+        add(
+            &conn,
+            "incidents",
+            incident("a", -27.470, 153.020, "low", "reported"),
+        );
+        add(
+            &conn,
+            "incidents",
+            incident("b", -27.472, 153.022, "critical", "in_progress"),
+        );
+        add(
+            &conn,
+            "incidents",
+            incident("c", -27.471, 153.025, "medium", "resolved"),
+        );
+        add(
+            &conn,
+            "incidents",
+            incident("d", -19.258, 146.816, "high", "verified"),
+        );
+        add(
+            &conn,
+            "sos_broadcasts",
+            json!({"id": "s", "latitude": -27.471, "longitude": 153.021}),
+        );
+        conn
+    }
+
+    fn query(zoom: u8, entities: &[GeoEntity]) -> MapQuery {
+        MapQuery {
+            bounds: BoundingBox {
+                west: 140.0,
+                south: -35.0,
+                east: 160.0,
+                north: -10.0,
+            },
+            zoom,
+            entities: entities.to_vec(),
+            active_only: false,
+            eq: Map::new(),
+            heatmap: false,
+        }
+    }
+
+    fn by_count(mut clusters: Vec<Cluster>) -> Vec<Cluster> {
+        clusters.sort_by_key(|c| std::cmp::Reverse(c.count));
+        clusters
+    }
+
+    #[test]
+    fn nearby_markers_of_one_entity_cluster_together() {
+        let conn = map();
+        let result = aggregate(&conn, &query(8, &[GeoEntity::Incident, GeoEntity::Sos])).unwrap();
+        assert_eq!(result.total, 5);
+
+        let clusters = by_count(result.clusters);
+        assert_eq!(clusters.len(), 3);
+        let town = &clusters[0];
+        assert_eq!((town.entity, town.count), (GeoEntity::Incident, 3));
+        assert_eq!(town.severity.as_deref(), Some("critical"));
+        assert!(town.record.is_none());
+        assert_eq!((town.bounds.west, town.bounds.east), (153.020, 153.025));
+        assert!((town.point.lat - -27.471).abs() < 1e-9);
+        // The SOS in the same place stays its own marker, with its record
+        let sos = clusters
+            .iter()
+            .find(|c| c.entity == GeoEntity::Sos)
+            .unwrap();
+        assert_eq!(sos.count, 1);
+        assert_eq!(sos.record.as_ref().unwrap()["id"], "s");
+    }
+
+    #[test]
+    fn close_zoom_draws_every_marker() {
+        let conn = map();
+        let result = aggregate(&conn, &query(MAX_CLUSTER_ZOOM, &[GeoEntity::Incident])).unwrap();
+        assert_eq!(result.clusters.len(), 4);
+        assert!(result
+            .clusters
+            .iter()
+            .all(|c| c.count == 1 && c.record.is_some()));
+    }
+
+    #[test]
+    fn active_only_and_eq_filter_before_clustering() {
+        let conn = map();
+        let mut q = query(8, &[GeoEntity::Incident]);
+        q.active_only = true;
+        assert_eq!(aggregate(&conn, &q).unwrap().total, 3);
+
+        q.eq.insert("severity".into(), json!("high"));
+        let result = aggregate(&conn, &q).unwrap();
+        assert_eq!(result.total, 1);
+        assert_eq!(result.clusters[0].record.as_ref().unwrap()["id"], "d");
+    }
+
+    #[test]
+    fn heat_weighs_severity_and_counts_sos_as_critical() {
+        let conn = map();
+        let mut q = query(8, &[GeoEntity::Incident, GeoEntity::Sos]);
+        q.heatmap = true;
+        let result = aggregate(&conn, &q).unwrap();
+
+        let total: f64 = result.heat.iter().map(|c| c.weight).sum();
+        // low 0.2 + critical 1.0 + medium 0.4 + high 0.7 + SOS 1.0
+        assert!((total - 3.3).abs() < 1e-9);
+        assert!((result.max_weight - 2.6).abs() < 1e-9);
+        assert!(aggregate(&conn, &query(8, &[GeoEntity::Incident]))
+            .unwrap()
+            .heat
+            .is_empty());
+    }
+
+    #[test]
+    fn markers_across_the_antimeridian_are_found() {
+        let conn = db();
+        add(
+            &conn,
+            "incidents",
+            incident("e", -17.0, 179.9, "low", "reported"),
+        );
+        add(
+            &conn,
+            "incidents",
+            incident("w", -17.0, -179.9, "low", "reported"),
+        );
+        let mut q = query(10, &[GeoEntity::Incident]);
+        q.bounds = BoundingBox {
+            west: 179.0,
+            south: -20.0,
+            east: 181.0,
+            north: -10.0,
+        };
+        assert_eq!(aggregate(&conn, &q).unwrap().total, 2);
+    }
+
+    #[test]
+    fn zoom_past_the_limit_is_rejected() {
+        assert!(aggregate(&db(), &query(MAX_ZOOM + 1, &[GeoEntity::Incident])).is_err());
+    }
+
+    fn bounds(west: f64, east: f64) -> BoundingBox {
+        BoundingBox {
+            west,
+            south: -10.0,
+            east,
+            north: 10.0,
+        }
+    }
+
+    fn sides(areas: &[BoundingBox]) -> Vec<(f64, f64)> {
+        areas.iter().map(|a| (a.west, a.east)).collect()
+    }
+
+    #[test]
+    fn viewport_inside_the_world_is_one_box() {
+        assert_eq!(
+            sides(&viewport(&bounds(10.0, 20.0)).unwrap()),
+            [(10.0, 20.0)]
+        );
+    }
+
+    #[test]
+    fn viewport_past_the_antimeridian_is_split() {
+        let expected = [(170.0, 180.0), (-180.0, -170.0)];
+        assert_eq!(sides(&viewport(&bounds(170.0, 190.0)).unwrap()), expected);
+        assert_eq!(sides(&viewport(&bounds(-190.0, -170.0)).unwrap()), expected);
+        assert_eq!(sides(&viewport(&bounds(170.0, -170.0)).unwrap()), expected);
+    }
+
+    #[test]
+    fn viewport_wider_than_the_world_is_the_world() {
+        assert_eq!(
+            sides(&viewport(&bounds(-300.0, 300.0)).unwrap()),
+            [(-180.0, 180.0)]
+        );
+    }
+}
@@ -0,0 +1,537 @@
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::db::Db;
+use crate::error::{Error, Result};
+use crate::mirror;
+use crate::offline_queue::{self, NewMutation, Operation, QueuedMutation};
+use crate::supabase::Supabase;
+
+pub const CONFLICT_EVENT: &str = "sync://conflict";
+
+// Columns that describe the row rather than the edit
+const BOOKKEEPING: &[&str] = &["id", "created_at", "updated_at"];
+
+/// How an offline `update` is reconciled when the record changed on the
+/// server after we based our edit on it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    /// Apply our edit regardless, overwriting the other change.
+    LastWriterWins,
+    /// Apply fields nobody else touched; anything edited on both sides goes
+    /// to review.
+    #[default]
+    Merge,
+    /// Any concurrent change goes to review.
+    Manual,
+}
+
+impl Strategy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Strategy::LastWriterWins => "last_writer_wins",
+            Strategy::Merge => "merge",
+            Strategy::Manual => "manual",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "last_writer_wins" => Some(Strategy::LastWriterWins),
+            "merge" => Some(Strategy::Merge),
+            "manual" => Some(Strategy::Manual),
+            _ => None,
+        }
+    }
+}
+
+/// An update that could not be replayed automatically and needs a human.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Conflict {
+    pub id: String,
+    pub table: String,
+    pub record_id: String,
+    /// Our queued edit, without the `id`.
+    pub local: Value,
+    /// The record as it was when we made the edit, if the caller sent it.
+    pub base: Option<Value>,
+    /// The record as it is on the server now, `None` if it was deleted.
+    pub remote: Option<Value>,
+    /// Fields edited on both sides.
+    pub fields: Vec<String>,
+    pub base_updated_at: Option<String>,
+    pub remote_updated_at: Option<String>,
+    pub queued_at: String,
+    pub detected_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Resolution {
+    KeepLocal,
+    KeepRemote,
+    Merged { payload: Value },
+}
+
+enum Outcome {
+    /// The merged patch and the remote `updated_at` it was merged against.
+    Apply(Value, String),
+    Conflict(Box<Conflict>),
+}
+
+// ─── Detection ──────────────────────────────────────────────────
+
+/// Send a queued `update`. `patch` is the payload without its `id`. The
+/// write only lands if the row still has the `updated_at` we based the
+/// edit on, so an edit made between our read and our write is never
+/// overwritten; returns the conflict when it can't be applied.
+pub async fn apply(
+    client: &Supabase,
+    mutation: &QueuedMutation,
+    record_id: &str,
+    patch: Value,
+) -> Result<Option<Box<Conflict>>> {
+    if patch.as_object().is_none_or(|o| o.is_empty()) {
+        return Ok(None);
+    }
+    let Some(base_updated_at) = mutation.base_updated_at.as_deref() else {
+        // Nothing to compare against (e.g. alerts have no updated_at)
+        client.update(&mutation.table, record_id, &patch).await?;
+        return Ok(None);
+    };
+    if mutation.strategy == Strategy::LastWriterWins {
+        client.update(&mutation.table, record_id, &patch).await?;
+        return Ok(None);
+    }
+
+    if client
+        .update_if_unchanged(&mutation.table, record_id, base_updated_at, &patch)
+        .await?
+        > 0
+    {
+        return Ok(None);
+    }
+
+    // Someone got there first; reconcile against what they wrote
+    let remote = client
+        .select(&mutation.table, &[("id", format!("eq.{record_id}"))])
+        .await?
+        .into_iter()
+        .next();
+    let remote_updated_at = remote
+        .as_ref()
+        .and_then(|r| r.get("updated_at"))
+        .and_then(Value::as_str)
+        .map(str::to_owned);
+
+    let seen = remote.clone();
+    let mut conflict = match reconcile(
+        mutation,
+        record_id,
+        patch.clone(),
+        remote,
+        remote_updated_at,
+    ) {
+        Outcome::Apply(merged, remote_updated_at) => {
+            if merged.as_object().is_none_or(|o| o.is_empty()) {
+                return Ok(None);
+            }
+            let applied = client
+                .update_if_unchanged(&mutation.table, record_id, &remote_updated_at, &merged)
+                .await?;
+            if applied > 0 {
+                return Ok(None);
+            }
+            // Edited again while we merged; leave it to a human
+            let fields = merged
+                .as_object()
+                .map(|o| o.keys().cloned().collect())
+                .unwrap_or_default();
+            let mut conflict = conflict_for(mutation, record_id, patch, seen, fields);
+            conflict.remote_updated_at = Some(remote_updated_at);
+            conflict
+        }
+        Outcome::Conflict(conflict) => *conflict,
+    };
+    conflict.base_updated_at = Some(base_updated_at.to_owned());
+    Ok(Some(Box::new(conflict)))
+}
+
+/// Merge our edit into the remote row per the mutation's strategy.
+fn reconcile(
+    mutation: &QueuedMutation,
+    record_id: &str,
+    patch: Value,
+    remote: Option<Value>,
+    remote_updated_at: Option<String>,
+) -> Outcome {
+    let empty = Map::new();
+    let local = patch.as_object().unwrap_or(&empty);
+    let remote_row = remote.as_ref().and_then(Value::as_object).unwrap_or(&empty);
+    let base_row = mutation.base.as_ref().and_then(Value::as_object);
+
+    let mut merged = Map::new();
+    let mut fields = Vec::new();
+    for (key, value) in local {
+        if BOOKKEEPING.contains(&key.as_str()) {
+            continue;
+        }
+        let theirs = remote_row.get(key);
+        if theirs == Some(value) {
+            continue;
+        }
+        let untouched = base_row.is_some_and(|base| base.get(key) == theirs);
+        if mutation.strategy == Strategy::Merge && untouched {
+            merged.insert(key.clone(), value.clone());
+        } else {
+            fields.push(key.clone());
+        }
+    }
+
+    if let (Some(_), Some(at), true) = (&remote, &remote_updated_at, fields.is_empty()) {
+        return Outcome::Apply(Value::Object(merged), at.clone());
+    }
+
+    let mut conflict = conflict_for(mutation, record_id, patch, remote, fields);
+    conflict.remote_updated_at = remote_updated_at;
+    Outcome::Conflict(Box::new(conflict))
+}
+
+fn conflict_for(
+    mutation: &QueuedMutation,
+    record_id: &str,
+    local: Value,
+    remote: Option<Value>,
+    fields: Vec<String>,
+) -> Conflict {
+    Conflict {
+        id: uuid::Uuid::new_v4().to_string(),
+        table: mutation.table.clone(),
+        record_id: record_id.to_owned(),
+        local,
+        base: mutation.base.clone(),
+        remote,
+        fields,
+        base_updated_at: mutation.base_updated_at.clone(),
+        remote_updated_at: None,
+        queued_at: mutation.queued_at.clone(),
+        detected_at: chrono::Utc::now().to_rfc3339(),
+    }
+}
+
+// ─── Storage ────────────────────────────────────────────────────
+
+fn from_row(row: &Row) -> rusqlite::Result<Conflict> {
+    let fields: Value = row.get("fields")?;
+    Ok(Conflict {
+        id: row.get("id")?,
+        table: row.get("table_name")?,
+        record_id: row.get("record_id")?,
+        local: row.get("local")?,
+        base: row.get("base")?,
+        remote: row.get("remote")?,
+        fields: serde_json::from_value(fields).unwrap_or_default(),
+        base_updated_at: row.get("base_updated_at")?,
+        remote_updated_at: row.get("remote_updated_at")?,
+        queued_at: row.get("queued_at")?,
+        detected_at: row.get("detected_at")?,
+    })
+}
+
+pub fn insert(conn: &Connection, conflict: &Conflict) -> Result<()> {
+    conn.execute(
+        "INSERT INTO sync_conflicts (id, table_name, record_id, local, base, remote, fields,
+             base_updated_at, remote_updated_at, queued_at, detected_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            conflict.id,
+            conflict.table,
+            conflict.record_id,
+            conflict.local,
+            conflict.base,
+            conflict.remote,
+            serde_json::to_value(&conflict.fields)?,
+            conflict.base_updated_at,
+            conflict.remote_updated_at,
+            conflict.queued_at,
+            conflict.detected_at,
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn list(conn: &Connection) -> Result<Vec<Conflict>> {
+    let mut stmt = conn.prepare("SELECT * FROM sync_conflicts ORDER BY detected_at")?;
+    let rows = stmt.query_map([], from_row)?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+fn get(conn: &Connection, id: &str) -> Result<Conflict> {
+    let mut stmt = conn.prepare("SELECT * FROM sync_conflicts WHERE id = ?1")?;
+    let mut rows = stmt.query_map([id], from_row)?;
+    rows.next()
+        .transpose()?
+        .ok_or_else(|| Error::NotFound(format!("conflict {id}")))
+}
+
+/// Settle a conflict. Anything that has to reach the server goes back
+/// through the offline queue, based on the remote version we just saw, so
+/// resolving works offline too and a second concurrent edit is caught again.
+pub fn resolve(conn: &mut Connection, id: &str, resolution: Resolution) -> Result<()> {
+    let conflict = get(conn, id)?;
+    let payload = match resolution {
+        Resolution::KeepRemote => None,
+        Resolution::KeepLocal => Some(conflict.local.clone()),
+        Resolution::Merged { payload } => Some(payload),
+    };
+
+    let tx = conn.transaction()?;
+    if let Some(payload) = payload {
+        let Value::Object(patch) = payload else {
+            return Err(Error::Invalid("resolved payload must be an object".into()));
+        };
+        let (operation, mut object, base_updated_at) = match &conflict.remote {
+            Some(_) => (Operation::Update, patch, conflict.remote_updated_at.clone()),
+            // Deleted on the server; recreate it from our copy with the edit on top
+            None => (Operation::Insert, recreated(&tx, &conflict, patch)?, None),
+        };
+        object.insert("id".into(), Value::String(conflict.record_id.clone()));
+
+        offline_queue::enqueue(
+            &tx,
+            NewMutation {
+                table: conflict.table,
+                operation,
+                payload: Value::Object(object),
+                base_updated_at,
+                base: conflict.remote,
+                strategy: Some(Strategy::Manual),
+            },
+        )?;
+    }
+    tx.execute("DELETE FROM sync_conflicts WHERE id = ?1", [id])?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// The whole row to insert again for a record deleted on the server: the
+/// mirrored copy, or the record the edit was based on, with `patch` applied.
+/// A patch alone would miss the columns the server requires.
+fn recreated(
+    conn: &Connection,
+    conflict: &Conflict,
+    patch: Map<String, Value>,
+) -> Result<Map<String, Value>> {
+    let copy = mirror::get(conn, &conflict.table, &conflict.record_id)
+        .ok()
+        .flatten()
+        .or_else(|| conflict.base.clone());
+    let Some(Value::Object(mut row)) = copy else {
+        return Err(Error::Invalid(format!(
+            "{} {} was deleted on the server and there is no local copy to restore; \
+             keep the server version instead",
+            conflict.table, conflict.record_id
+        )));
+    };
+    row.remove("updated_at");
+    row.extend(patch);
+    Ok(row)
+}
+
+// ─── Commands ───────────────────────────────────────────────────
+
+#[tauri::command]
+pub fn sync_conflicts_list(db: State<'_, Db>) -> Result<Vec<Conflict>> {
+    db.with(|conn| list(conn))
+}
+
+#[tauri::command]
+pub fn sync_conflict_resolve(
+    app: AppHandle,
+    db: State<'_, Db>,
+    id: String,
+    resolution: Resolution,
+) -> Result<()> {
+    let remaining = db.with(|conn| {
+        resolve(conn, &id, resolution)?;
+        offline_queue::count(conn)
+    })?;
+    let _ = app.emit(CONFLICT_EVENT, ());
+    let _ = app.emit(offline_queue::CHANGED_EVENT, remaining);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const RECORD: &str = "7f1c2b9e-4d5a-4c3b-9e8f-1a2b3c4d5e6f";
+
+    fn db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        for migration in crate::migrations::MIGRATIONS {
+            conn.execute_batch(migration.sql).unwrap();
+        }
+        conn
+    }
+
+    fn mutation(strategy: Strategy, base: Value) -> QueuedMutation {
+        QueuedMutation {
+            id: "m1".into(),
+            table: "incidents".into(),
+            operation: Operation::Update,
+            payload: json!({}),
+            queued_at: "2024-05-01T10:00:00Z".into(),
+            retries: 0,
+            last_error: None,
+            base_updated_at: Some("t1".into()),
+            base: Some(base),
+            strategy,
+            next_attempt_at: None,
+        }
+    }
+
+    fn base() -> Value {
+        json!({"id": RECORD, "title": "Flood", "status": "reported", "severity": "low"})
+    }
+
+    fn remote() -> Value {
+        json!({"id": RECORD, "title": "Flood", "status": "active", "severity": "low", "updated_at": "t2"})
+    }
+
+    fn parked(conn: &Connection, remote: Option<Value>) -> Conflict {
+        let mut conflict = conflict_for(
+            &mutation(Strategy::Manual, base()),
+            RECORD,
+            json!({"severity": "high"}),
+            remote,
+            vec!["severity".into()],
+        );
+        conflict.remote_updated_at = conflict.remote.as_ref().map(|_| "t2".into());
+        insert(conn, &conflict).unwrap();
+        conflict
+    }
+
+    #[test]
+    fn merge_applies_fields_nobody_else_touched() {
+        let outcome = reconcile(
+            &mutation(Strategy::Merge, base()),
+            RECORD,
+            json!({"severity": "high", "updated_at": "t1"}),
+            Some(remote()),
+            Some("t2".into()),
+        );
+        let Outcome::Apply(merged, at) = outcome else {
+            panic!("expected a merge");
+        };
+        assert_eq!(merged, json!({"severity": "high"}));
+        assert_eq!(at, "t2");
+    }
+
+    #[test]
+    fn fields_edited_on_both_sides_conflict() {
+        let outcome = reconcile(
+            &mutation(Strategy::Merge, base()),
+            RECORD,
+            json!({"status": "resolved", "severity": "high"}),
+            Some(remote()),
+            Some("t2".into()),
+        );
+        let Outcome::Conflict(conflict) = outcome else {
+            panic!("expected a conflict");
+        };
+        assert_eq!(conflict.fields, vec!["status"]);
+        assert_eq!(conflict.remote_updated_at.as_deref(), Some("t2"));
+    }
+
+    #[test]
+    fn manual_sends_any_concurrent_change_to_review() {
+        let outcome = reconcile(
+            &mutation(Strategy::Manual, base()),
+            RECORD,
+            json!({"severity": "high"}),
+            Some(remote()),
+            Some("t2".into()),
+        );
+        assert!(matches!(outcome, Outcome::Conflict(c) if c.fields == ["severity"]));
+    }
+
+    #[test]
+    fn edits_to_a_deleted_record_conflict() {
+        let outcome = reconcile(
+            &mutation(Strategy::Merge, base()),
+            RECORD,
+            json!({"severity": "high"}),
+            None,
+            None,
+        );
+        assert!(matches!(outcome, Outcome::Conflict(c) if c.remote.is_none()));
+    }
+
+    #[test]
+    fn keeping_local_queues_an_update_against_the_remote_version() {
+        let mut conn = db();
+        let conflict = parked(&conn, Some(remote()));
+        resolve(&mut conn, &conflict.id, Resolution::KeepLocal).unwrap();
+
+        let queued = offline_queue::list(&conn).unwrap();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].operation, Operation::Update);
+        assert_eq!(queued[0].payload, json!({"id": RECORD, "severity": "high"}));
+        assert_eq!(queued[0].base_updated_at.as_deref(), Some("t2"));
+        assert!(list(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn keeping_remote_only_drops_the_conflict() {
+        let mut conn = db();
+        let conflict = parked(&conn, Some(remote()));
+        resolve(&mut conn, &conflict.id, Resolution::KeepRemote).unwrap();
+        assert!(offline_queue::list(&conn).unwrap().is_empty());
+        assert!(list(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn keeping_local_recreates_a_deleted_record_in_full() {
+        let mut conn = db();
+        conn.execute(
+            "INSERT INTO mirror_records (table_name, id, data, updated_at) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                "incidents",
+                RECORD,
+                json!({"id": RECORD, "title": "Flood", "status": "active", "severity": "low",
+                       "created_by": "u1", "updated_at": "t2"}),
+                "t2"
+            ],
+        )
+        .unwrap();
+        let conflict = parked(&conn, None);
+        resolve(&mut conn, &conflict.id, Resolution::KeepLocal).unwrap();
+
+        let queued = offline_queue::list(&conn).unwrap();
+        assert_eq!(queued[0].operation, Operation::Insert);
+        assert_eq!(
+            queued[0].payload,
+            json!({"id": RECORD, "title": "Flood", "status": "active", "severity": "high",
+                   "created_by": "u1"})
+        );
+    }
+
+    #[test]
+    fn a_deleted_record_without_a_copy_is_not_recreated() {
+        let mut conn = db();
+        let conflict = parked(&conn, None);
+        conn.execute("UPDATE sync_conflicts SET base = NULL", [])
+            .unwrap();
+        assert!(resolve(&mut conn, &conflict.id, Resolution::KeepLocal).is_err());
+        // Left for the user to settle another way
+        assert_eq!(list(&conn).unwrap().len(), 1);
+        assert!(offline_queue::list(&conn).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,1100 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::db::Db;
+use crate::error::{Error, Result};
+use crate::gazetteer::{self, GeocodeQuery};
+use crate::geo::Point;
+
+const PREFERENCE_FILE: &str = "coordinate-format.json";
+
+// WGS84 and the UTM projection
+const A: f64 = 6_378_137.0;
+const F: f64 = 1.0 / 298.257_223_563;
+const K0: f64 = 0.9996;
+const FALSE_EASTING: f64 = 500_000.0;
+const FALSE_NORTHING: f64 = 10_000_000.0;
+/// UTM (and so MGRS) stops here; the poles need UPS.
+const UTM_MIN_LAT: f64 = -80.0;
+const UTM_MAX_LAT: f64 = 84.0;
+
+/// Latitude bands, 8° each from 80°S; X stretches to 84°N.
+const BANDS: &[u8] = b"CDEFGHJKLMNPQRSTUVWX";
+const SQUARE_COLUMNS: [&[u8]; 3] = [b"ABCDEFGH", b"JKLMNPQR", b"STUVWXYZ"];
+const SQUARE_ROWS: &[u8] = b"ABCDEFGHJKLMNPQRSTUV";
+
+/// Open Location Code digits.
+const PLUS_ALPHABET: &[u8] = b"23456789CFGHJMPQRVWX";
+const PLUS_SEPARATOR_AT: usize = 8;
+/// 10 pair digits and one grid digit: about 3 m.
+const PLUS_CODE_LENGTH: usize = 11;
+const PLUS_PAIR_DIGITS: usize = 10;
+const PLUS_GRID_ROWS: i64 = 5;
+const PLUS_GRID_COLUMNS: i64 = 4;
+
+/// How a position is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Notation {
+    /// `40.712800, -74.006000`
+    #[default]
+    Decimal,
+    /// `40°42'46.1"N 074°00'21.6"W`
+    Dms,
+    /// `40°42.768'N 074°00.360'W`
+    Ddm,
+    /// `18T 583959 4507351`
+    Utm,
+    /// `18TWL8395907350`
+    Mgrs,
+    /// `18T WL 83959 07350`, MGRS with spaces.
+    Usng,
+    /// `87G7PX7V+4JC`
+    PlusCode,
+}
+
+impl Notation {
+    pub const ALL: [Notation; 7] = [
+        Notation::Decimal,
+        Notation::Dms,
+        Notation::Ddm,
+        Notation::Utm,
+        Notation::Mgrs,
+        Notation::Usng,
+        Notation::PlusCode,
+    ];
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoordinatePreference {
+    pub notation: Notation,
+}
+
+fn preference_path(app: &AppHandle) -> Result<PathBuf> {
+    Ok(app.path().app_config_dir()?.join(PREFERENCE_FILE))
+}
+
+/// The saved preference, or decimal degrees.
+pub fn load_preference(app: &AppHandle) -> CoordinatePreference {
+    preference_path(app)
+        .ok()
+        .and_then(|path| std::fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn save_preference(app: &AppHandle, preference: &CoordinatePreference) -> Result<()> {
+    let path = preference_path(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(preference)?)?;
+    std::fs::rename(tmp, path)?;
+    Ok(())
+}
+
+// ─── UTM ────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Utm {
+    pub zone: u8,
+    pub band: char,
+    pub easting: f64,
+    pub northing: f64,
+}
+
+impl Utm {
+    fn is_north(&self) -> bool {
+        self.band >= 'N'
+    }
+}
+
+fn e2() -> f64 {
+    F * (2.0 - F)
+}
+
+fn central_meridian(zone: u8) -> f64 {
+    f64::from(zone) * 6.0 - 183.0
+}
+
+fn band_for(lat: f64) -> char {
+    let index = (((lat - UTM_MIN_LAT) / 8.0).floor() as usize).min(BANDS.len() - 1);
+    BANDS[index] as char
+}
+
+/// Zone for a position, with the Norway and Svalbard exceptions.
+fn zone_for(point: Point) -> u8 {
+    let (lat, lng) = (point.lat, point.lng);
+    if (56.0..64.0).contains(&lat) && (3.0..12.0).contains(&lng) {
+        return 32;
+    }
+    if (72.0..=UTM_MAX_LAT).contains(&lat) && (0.0..42.0).contains(&lng) {
+        return match lng {
+            l if l < 9.0 => 31,
+            l if l < 21.0 => 33,
+            l if l < 33.0 => 35,
+            _ => 37,
+        };
+    }
+    (((lng + 180.0) / 6.0).floor() as i64).rem_euclid(60) as u8 + 1
+}
+
+/// Meridian arc length from the equator to `phi`.
+fn meridian_arc(phi: f64) -> f64 {
+    let e2 = e2();
+    let (e4, e6) = (e2 * e2, e2 * e2 * e2);
+    A * ((1.0 - e2 / 4.0 - 3.0 * e4 / 64.0 - 5.0 * e6 / 256.0) * phi
+        - (3.0 * e2 / 8.0 + 3.0 * e4 / 32.0 + 45.0 * e6 / 1024.0) * (2.0 * phi).sin()
+        + (15.0 * e4 / 256.0 + 45.0 * e6 / 1024.0) * (4.0 * phi).sin()
+        - (35.0 * e6 / 3072.0) * (6.0 * phi).sin())
+}
+
+/// Transverse Mercator projection in the position's own zone.
+pub fn to_utm(point: Point) -> Result<Utm> {
+    point.validate()?;
+    to_utm_in(point, zone_for(point))
+}
+
+fn to_utm_in(point: Point, zone: u8) -> Result<Utm> {
+    if !(UTM_MIN_LAT..=UTM_MAX_LAT).contains(&point.lat) {
+        return Err(Error::Invalid(
+            "UTM and MGRS only cover 80°S to 84°N".into(),
+        ));
+    }
+    let e2 = e2();
+    let ep2 = e2 / (1.0 - e2);
+    let phi = point.lat.to_radians();
+    let dlambda = (point.lng - central_meridian(zone) + 540.0).rem_euclid(360.0) - 180.0;
+
+    let n = A / (1.0 - e2 * phi.sin().powi(2)).sqrt();
+    let t = phi.tan().powi(2);
+    let c = ep2 * phi.cos().powi(2);
+    let a = phi.cos() * dlambda.to_radians();
+
+    let easting = K0
+        * n
+        * (a + (1.0 - t + c) * a.powi(3) / 6.0
+            + (5.0 - 18.0 * t + t * t + 72.0 * c - 58.0 * ep2) * a.powi(5) / 120.0)
+        + FALSE_EASTING;
+    let mut northing = K0
+        * (meridian_arc(phi)
+            + n * phi.tan()
+                * (a * a / 2.0
+                    + (5.0 - t + 9.0 * c + 4.0 * c * c) * a.powi(4) / 24.0
+                    + (61.0 - 58.0 * t + t * t + 600.0 * c - 330.0 * ep2) * a.powi(6) / 720.0));
+    if point.lat < 0.0 {
+        northing += FALSE_NORTHING;
+    }
+    Ok(Utm {
+        zone,
+        band: band_for(point.lat),
+        easting,
+        northing,
+    })
+}
+
+pub fn from_utm(utm: &Utm) -> Result<Point> {
+    if !(1..=60).contains(&utm.zone) {
+        return Err(Error::Invalid(format!("{} is not a UTM zone", utm.zone)));
+    }
+    if !BANDS.contains(&(utm.band as u8)) {
+        return Err(Error::Invalid(format!(
+            "{} is not a latitude band",
+            utm.band
+        )));
+    }
+    if !(100_000.0..=900_000.0).contains(&utm.easting)
+        || !(0.0..=FALSE_NORTHING).contains(&utm.northing)
+    {
+        return Err(Error::Invalid("easting or northing is out of range".into()));
+    }
+
+    let e2 = e2();
+    let ep2 = e2 / (1.0 - e2);
+    let (e4, e6) = (e2 * e2, e2 * e2 * e2);
+    let northing = if utm.is_north() {
+        utm.northing
+    } else {
+        utm.northing - FALSE_NORTHING
+    };
+    let m = northing / K0;
+    let mu = m / (A * (1.0 - e2 / 4.0 - 3.0 * e4 / 64.0 - 5.0 * e6 / 256.0));
+    let e1 = (1.0 - (1.0 - e2).sqrt()) / (1.0 + (1.0 - e2).sqrt());
+    let phi1 = mu
+        + (3.0 * e1 / 2.0 - 27.0 * e1.powi(3) / 32.0) * (2.0 * mu).sin()
+        + (21.0 * e1 * e1 / 16.0 - 55.0 * e1.powi(4) / 32.0) * (4.0 * mu).sin()
+        + (151.0 * e1.powi(3) / 96.0) * (6.0 * mu).sin()
+        + (1097.0 * e1.powi(4) / 512.0) * (8.0 * mu).sin();
+
+    let sin2 = phi1.sin().powi(2);
+    let c1 = ep2 * phi1.cos().powi(2);
+    let t1 = phi1.tan().powi(2);
+    let n1 = A / (1.0 - e2 * sin2).sqrt();
+    let r1 = A * (1.0 - e2) / (1.0 - e2 * sin2).powf(1.5);
+    let d = (utm.easting - FALSE_EASTING) / (n1 * K0);
+
+    let lat = phi1
+        - (n1 * phi1.tan() / r1)
+            * (d * d / 2.0
+                - (5.0 + 3.0 * t1 + 10.0 * c1 - 4.0 * c1 * c1 - 9.0 * ep2) * d.powi(4) / 24.0
+                + (61.0 + 90.0 * t1 + 298.0 * c1 + 45.0 * t1 * t1 - 252.0 * ep2 - 3.0 * c1 * c1)
+                    * d.powi(6)
+                    / 720.0);
+    let lng = (d - (1.0 + 2.0 * t1 + c1) * d.powi(3) / 6.0
+        + (5.0 - 2.0 * c1 + 28.0 * t1 - 3.0 * c1 * c1 + 8.0 * ep2 + 24.0 * t1 * t1) * d.powi(5)
+            / 120.0)
+        / phi1.cos();
+
+    let point = Point {
+        lat: lat.to_degrees(),
+        lng: (central_meridian(utm.zone) + lng.to_degrees() + 540.0).rem_euclid(360.0) - 180.0,
+    };
+    point.validate()?;
+    Ok(point)
+}
+
+// ─── MGRS / USNG ────────────────────────────────────────────────
+
+fn square_set(zone: u8) -> usize {
+    usize::from((zone - 1) % 3)
+}
+
+/// Northing where `band` starts, at its lowest point in the zone.
+fn band_min_northing(zone: u8, band: char) -> Result<f64> {
+    let index = BANDS
+        .iter()
+        .position(|b| *b as char == band)
+        .ok_or_else(|| Error::Invalid(format!("{band} is not a latitude band")))?;
+    let lat = UTM_MIN_LAT + 8.0 * index as f64;
+    let cm = central_meridian(zone);
+    // Parallels bow poleward away from the central meridian
+    let at_centre = to_utm_in(Point { lat, lng: cm }, zone)?.northing;
+    let at_edge = to_utm_in(Point { lat, lng: cm + 3.0 }, zone)?.northing;
+    Ok(at_centre.min(at_edge))
+}
+
+/// MGRS square letters and the metres inside the 100 km square.
+fn mgrs_parts(point: Point) -> Result<(Utm, char, char, u32, u32)> {
+    let utm = to_utm(point)?;
+    let e = utm.easting.floor() as i64;
+    let n = utm.northing.floor() as i64;
+    let column = SQUARE_COLUMNS[square_set(utm.zone)][(e / 100_000 - 1).clamp(0, 7) as usize];
+    let offset = if utm.zone.is_multiple_of(2) { 5 } else { 0 };
+    let row = SQUARE_ROWS[((n / 100_000 + offset) % 20) as usize];
+    Ok((
+        utm,
+        column as char,
+        row as char,
+        (e % 100_000) as u32,
+        (n % 100_000) as u32,
+    ))
+}
+
+/// 1 m MGRS reference; `spaced` writes it the USNG way.
+pub fn to_mgrs(point: Point, spaced: bool) -> Result<String> {
+    let (utm, column, row, e, n) = mgrs_parts(point)?;
+    Ok(if spaced {
+        format!("{}{} {column}{row} {e:05} {n:05}", utm.zone, utm.band)
+    } else {
+        format!("{}{}{column}{row}{e:05}{n:05}", utm.zone, utm.band)
+    })
+}
+
+/// Centre of the square an MGRS or USNG reference names, at whatever
+/// precision it was given.
+fn parse_mgrs(text: &str) -> Option<Result<Point>> {
+    let compact: String = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_uppercase();
+    let digits = compact.chars().take_while(char::is_ascii_digit).count();
+    if !(1..=2).contains(&digits) {
+        return None;
+    }
+    let rest = &compact.as_bytes()[digits..];
+    if rest.len() < 3 || !rest[..3].iter().all(u8::is_ascii_alphabetic) {
+        return None;
+    }
+    let numbers = &rest[3..];
+    if !numbers.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    Some(mgrs_point(&compact[..digits], rest, numbers))
+}
+
+fn mgrs_point(zone: &str, letters: &[u8], numbers: &[u8]) -> Result<Point> {
+    let zone: u8 = zone
+        .parse()
+        .ok()
+        .filter(|z| (1..=60).contains(z))
+        .ok_or_else(|| Error::Invalid(format!("{zone} is not a UTM zone")))?;
+    if !numbers.len().is_multiple_of(2) || numbers.len() > 10 {
+        return Err(Error::Invalid(
+            "an MGRS reference needs an even number of digits, at most 10".into(),
+        ));
+    }
+    let band = letters[0] as char;
+    let column = SQUARE_COLUMNS[square_set(zone)]
+        .iter()
+        .position(|c| *c == letters[1])
+        .ok_or_else(|| {
+            Error::Invalid(format!(
+                "{} is not a 100 km square column in zone {zone}",
+                letters[1] as char
+            ))
+        })?;
+    let offset = if zone.is_multiple_of(2) { 5 } else { 0 };
+    let row = SQUARE_ROWS
+        .iter()
+        .position(|r| *r == letters[2])
+        .ok_or_else(|| {
+            Error::Invalid(format!("{} is not a 100 km square row", letters[2] as char))
+        })?;
+
+    let half = numbers.len() / 2;
+    let precision = 10f64.powi(5 - half as i32);
+    let read = |digits: &[u8]| -> f64 {
+        let text = std::str::from_utf8(digits).unwrap_or("0");
+        text.parse::<f64>().unwrap_or(0.0) * precision
+    };
+    let easting = (column as f64 + 1.0) * 100_000.0 + read(&numbers[..half]) + precision / 2.0;
+    let mut northing =
+        ((row + 20 - offset) % 20) as f64 * 100_000.0 + read(&numbers[half..]) + precision / 2.0;
+    // Rows repeat every 2000 km; the band says which repeat
+    let min = band_min_northing(zone, band)?;
+    while northing < min - 100_000.0 {
+        northing += 2_000_000.0;
+    }
+    from_utm(&Utm {
+        zone,
+        band,
+        easting,
+        northing,
+    })
+}
+
+// ─── Plus codes ─────────────────────────────────────────────────
+
+/// Full Open Location Code for a position, about 3 m across.
+pub fn to_plus_code(point: Point) -> Result<String> {
+    point.validate()?;
+    let lat_scale = 8000 * PLUS_GRID_ROWS.pow(5);
+    let lng_scale = 8000 * PLUS_GRID_COLUMNS.pow(5);
+    let mut lat = (((point.lat + 90.0) * lat_scale as f64).floor() as i64).min(180 * lat_scale - 1);
+    let mut lng = (((point.lng + 180.0).rem_euclid(360.0)) * lng_scale as f64).floor() as i64;
+
+    // Built backwards from the finest grid digit
+    let mut reversed = Vec::with_capacity(15);
+    for _ in 0..5 {
+        let digit = (lat % PLUS_GRID_ROWS) * PLUS_GRID_COLUMNS + lng % PLUS_GRID_COLUMNS;
+        reversed.push(PLUS_ALPHABET[digit as usize]);
+        lat /= PLUS_GRID_ROWS;
+        lng /= PLUS_GRID_COLUMNS;
+    }
+    for _ in 0..PLUS_PAIR_DIGITS / 2 {
+        reversed.push(PLUS_ALPHABET[(lng % 20) as usize]);
+        reversed.push(PLUS_ALPHABET[(lat % 20) as usize]);
+        lat /= 20;
+        lng /= 20;
+    }
+    let digits: String = reversed
+        .into_iter()
+        .rev()
+        .take(PLUS_CODE_LENGTH)
+        .map(char::from)
+        .collect();
+    Ok(format!(
+        "{}+{}",
+        &digits[..PLUS_SEPARATOR_AT],
+        &digits[PLUS_SEPARATOR_AT..]
+    ))
+}
+
+/// Centre and size (degrees of latitude, longitude) of a full code.
+fn decode_plus_code(code: &str) -> Result<(Point, f64, f64)> {
+    let digits: Vec<usize> = code
+        .chars()
+        .filter(|c| *c != '+' && *c != '0')
+        .map(|c| {
+            PLUS_ALPHABET
+                .iter()
+                .position(|a| *a as char == c)
+                .ok_or_else(|| Error::Invalid(format!("{c} is not a plus code digit")))
+        })
+        .collect::<Result<_>>()?;
+    if digits.len() < 2 || (!digits.len().is_multiple_of(2) && digits.len() <= PLUS_PAIR_DIGITS) {
+        return Err(Error::Invalid("the plus code is incomplete".into()));
+    }
+
+    let (mut lat, mut lng) = (-90.0, -180.0);
+    let mut resolution = 20.0;
+    for pair in digits[..digits.len().min(PLUS_PAIR_DIGITS)].chunks(2) {
+        lat += pair[0] as f64 * resolution;
+        lng += pair[1] as f64 * resolution;
+        resolution /= 20.0;
+    }
+    let (mut lat_size, mut lng_size) = (resolution * 20.0, resolution * 20.0);
+    for digit in digits.iter().skip(PLUS_PAIR_DIGITS) {
+        lat_size /= PLUS_GRID_ROWS as f64;
+        lng_size /= PLUS_GRID_COLUMNS as f64;
+        lat += (*digit as i64 / PLUS_GRID_COLUMNS) as f64 * lat_size;
+        lng += (*digit as i64 % PLUS_GRID_COLUMNS) as f64 * lng_size;
+    }
+    let point = Point {
+        lat: (lat + lat_size / 2.0).min(90.0),
+        lng: lng + lng_size / 2.0,
+    };
+    Ok((point, lat_size, lng_size))
+}
+
+/// A full or short plus code, e.g. `8FVC9G8F+6X` or `9G8F+6X Zürich`. Short
+/// codes are recovered near the named locality, looked up in the gazetteer,
+/// or else near `near`.
+fn parse_plus_code(
+    conn: &rusqlite::Connection,
+    text: &str,
+    near: Option<Point>,
+) -> Option<Result<Point>> {
+    let mut words = text.split_whitespace();
+    let code = words.next()?.trim_end_matches(',').to_uppercase();
+    let separator = code.find('+')?;
+    if separator > PLUS_SEPARATOR_AT || separator % 2 != 0 {
+        return Some(Err(Error::Invalid("the plus code is malformed".into())));
+    }
+    if separator == PLUS_SEPARATOR_AT {
+        return Some(decode_plus_code(&code).map(|(point, _, _)| point));
+    }
+
+    let locality = words.collect::<Vec<_>>().join(" ");
+    let reference = if locality.is_empty() {
+        near
+    } else {
+        let query = GeocodeQuery {
+            text: locality.clone(),
+            near,
+            limit: Some(1),
+        };
+        match gazetteer::search(conn, &query) {
+            Ok(places) => places.first().map(|p| p.point),
+            Err(e) => return Some(Err(e)),
+        }
+    };
+    let Some(reference) = reference else {
+        return Some(Err(Error::Invalid(if locality.is_empty() {
+            "a short plus code needs a locality, e.g. \"9G8F+6X Zürich\"".into()
+        } else {
+            format!("{locality} is not in the gazetteer")
+        })));
+    };
+    Some(recover_short_code(&code, separator, reference))
+}
+
+fn recover_short_code(code: &str, separator: usize, reference: Point) -> Result<Point> {
+    let padding = PLUS_SEPARATOR_AT - separator;
+    let full_reference = to_plus_code(reference)?;
+    let (mut point, _, _) = decode_plus_code(&format!("{}{code}", &full_reference[..padding]))?;
+    let resolution = 20f64.powi(2 - (padding / 2) as i32);
+    let half = resolution / 2.0;
+    if reference.lat + half < point.lat && point.lat - resolution >= -90.0 {
+        point.lat -= resolution;
+    } else if reference.lat - half > point.lat && point.lat + resolution <= 90.0 {
+        point.lat += resolution;
+    }
+    if reference.lng + half < point.lng {
+        point.lng -= resolution;
+    } else if reference.lng - half > point.lng {
+        point.lng += resolution;
+    }
+    point.lng = (point.lng + 540.0).rem_euclid(360.0) - 180.0;
+    Ok(point)
+}
+
+// ─── Degrees ────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Number(f64, bool),
+    Hemisphere(char),
+    Comma,
+}
+
+fn tokenize(text: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut number = String::new();
+    let flush = |number: &mut String, tokens: &mut Vec<Token>| -> Option<()> {
+        if !number.is_empty() {
+            let negative = number.starts_with('-');
+            tokens.push(Token::Number(number.parse().ok()?, negative));
+            number.clear();
+        }
+        Some(())
+    };
+    for c in text.chars() {
+        match c {
+            '0'..='9' | '.' => number.push(c),
+            '-' | '+' | '−' if number.is_empty() => {
+                if c != '+' {
+                    number.push('-');
+                }
+            }
+            ',' | ';' => {
+                flush(&mut number, &mut tokens)?;
+                tokens.push(Token::Comma);
+            }
+            'N' | 'S' | 'E' | 'W' | 'n' | 's' | 'e' | 'w' => {
+                flush(&mut number, &mut tokens)?;
+                tokens.push(Token::Hemisphere(c.to_ascii_uppercase()));
+            }
+            '°' | 'º' | '\'' | '"' | '′' | '″' | '’' | '”' | ':' => {
+                flush(&mut number, &mut tokens)?
+            }
+            c if c.is_whitespace() => flush(&mut number, &mut tokens)?,
+            _ => return None,
+        }
+    }
+    flush(&mut number, &mut tokens)?;
+    Some(tokens)
+}
+
+/// One coordinate's `(value, negative)` numbers and its hemisphere letter.
+type Half = (Vec<(f64, bool)>, Option<char>);
+
+/// `d`, `d m` or `d m s` to signed degrees.
+fn degrees(parts: &[(f64, bool)], hemisphere: Option<char>) -> Result<f64> {
+    let invalid = || Error::Invalid("minutes and seconds must be below 60".into());
+    let (value, negative) = match parts {
+        [(d, n)] => (d.abs(), *n),
+        [(d, n), (m, _)] if *m < 60.0 => (d.abs() + m / 60.0, *n),
+        [(d, n), (m, _), (s, _)] if *m < 60.0 && *s < 60.0 => (d.abs() + m / 60.0 + s / 3600.0, *n),
+        [_, _] | [_, _, _] => return Err(invalid()),
+        _ => {
+            return Err(Error::Invalid(
+                "expected degrees, minutes and seconds".into(),
+            ))
+        }
+    };
+    let south_or_west = matches!(hemisphere, Some('S' | 'W'));
+    if negative && hemisphere.is_some() {
+        return Err(Error::Invalid(
+            "use either a minus sign or a hemisphere, not both".into(),
+        ));
+    }
+    Ok(if negative || south_or_west {
+        -value
+    } else {
+        value
+    })
+}
+
+/// Decimal degrees, DMS or DDM, with hemisphere letters before or after
+/// each half, or signs. Latitude comes first unless the letters say
+/// otherwise.
+fn parse_degrees(text: &str) -> Option<Result<(Point, Notation)>> {
+    let tokens = tokenize(text)?;
+    let numbers: Vec<(f64, bool)> = tokens
+        .iter()
+        .filter_map(|t| match t {
+            Token::Number(v, n) => Some((*v, *n)),
+            _ => None,
+        })
+        .collect();
+    let hemispheres: Vec<char> = tokens
+        .iter()
+        .filter_map(|t| match t {
+            Token::Hemisphere(h) => Some(*h),
+            _ => None,
+        })
+        .collect();
+    if numbers.len() < 2 {
+        return None;
+    }
+
+    let mut halves: Vec<Half> = Vec::new();
+    if hemispheres.len() == 2 {
+        let leading = matches!(tokens.first(), Some(Token::Hemisphere(_)));
+        let mut current = Vec::new();
+        let mut hemisphere = None;
+        for token in &tokens {
+            match *token {
+                Token::Number(v, n) => current.push((v, n)),
+                Token::Hemisphere(h) if leading => {
+                    if hemisphere.is_some() {
+                        halves.push((std::mem::take(&mut current), hemisphere));
+                    }
+                    hemisphere = Some(h);
+                }
+                Token::Hemisphere(h) => halves.push((std::mem::take(&mut current), Some(h))),
+                Token::Comma => {}
+            }
+        }
+        if leading {
+            halves.push((current, hemisphere));
+        } else if !current.is_empty() {
+            return None;
+        }
+    } else if !hemispheres.is_empty() {
+        return None;
+    } else if let Some(comma) = tokens.iter().position(|t| *t == Token::Comma) {
+        let side = |tokens: &[Token]| {
+            tokens
+                .iter()
+                .filter_map(|t| match t {
+                    Token::Number(v, n) => Some((*v, *n)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        halves.push((side(&tokens[..comma]), None));
+        halves.push((side(&tokens[comma + 1..]), None));
+    } else if numbers.len().is_multiple_of(2) && numbers.len() <= 6 {
+        let (lat, lng) = numbers.split_at(numbers.len() / 2);
+        halves.push((lat.to_vec(), None));
+        halves.push((lng.to_vec(), None));
+    } else {
+        return None;
+    }
+
+    let [(first, first_hemisphere), (second, second_hemisphere)] = halves.as_slice() else {
+        return None;
+    };
+    let notation = match first.len().max(second.len()) {
+        1 => Notation::Decimal,
+        2 => Notation::Ddm,
+        _ => Notation::Dms,
+    };
+    Some(
+        degree_pair((first, *first_hemisphere), (second, *second_hemisphere))
+            .map(|point| (point, notation)),
+    )
+}
+
+fn degree_pair(
+    (first, first_hemisphere): (&[(f64, bool)], Option<char>),
+    (second, second_hemisphere): (&[(f64, bool)], Option<char>),
+) -> Result<Point> {
+    let a = degrees(first, first_hemisphere)?;
+    let b = degrees(second, second_hemisphere)?;
+    let swapped =
+        matches!(first_hemisphere, Some('E' | 'W')) && matches!(second_hemisphere, Some('N' | 'S'));
+    if !swapped
+        && (matches!(first_hemisphere, Some('E' | 'W'))
+            || matches!(second_hemisphere, Some('N' | 'S')))
+    {
+        return Err(Error::Invalid(
+            "give one latitude (N/S) and one longitude (E/W)".into(),
+        ));
+    }
+    let point = if swapped {
+        Point { lat: b, lng: a }
+    } else {
+        Point { lat: a, lng: b }
+    };
+    point.validate()?;
+    Ok(point)
+}
+
+/// `12°34'56.7"N`, or `12°34.945'N` without `seconds`.
+fn format_degrees(value: f64, positive: char, negative: char, seconds: bool) -> String {
+    let hemisphere = if value < 0.0 { negative } else { positive };
+    let width = if positive == 'E' { 3 } else { 2 };
+    if seconds {
+        let tenths = (value.abs() * 36_000.0).round() as i64;
+        let (d, rest) = (tenths / 36_000, tenths % 36_000);
+        let (m, s) = (rest / 600, rest % 600);
+        format!("{d:0width$}°{m:02}'{:02}.{}\"{hemisphere}", s / 10, s % 10)
+    } else {
+        let thousandths = (value.abs() * 60_000.0).round() as i64;
+        let (d, rest) = (thousandths / 60_000, thousandths % 60_000);
+        format!(
+            "{d:0width$}°{:02}.{:03}'{hemisphere}",
+            rest / 1000,
+            rest % 1000
+        )
+    }
+}
+
+// ─── Parse and format ───────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedCoordinate {
+    pub point: Point,
+    /// What the text was written in.
+    pub notation: Notation,
+}
+
+/// UTM as `18T 583959 4507351`, optionally with `mE`/`mN` or `E`/`N`
+/// suffixes and the band split off the zone.
+fn parse_utm(text: &str) -> Option<Result<Point>> {
+    let words: Vec<String> = text
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|w| !w.is_empty())
+        .map(str::to_uppercase)
+        .collect();
+    let (zone_band, rest) = match words.as_slice() {
+        [zone, band, rest @ ..] if band.len() == 1 && zone.chars().all(|c| c.is_ascii_digit()) => {
+            (format!("{zone}{band}"), rest)
+        }
+        [zone_band, rest @ ..] => (zone_band.clone(), rest),
+        _ => return None,
+    };
+    let [easting, northing] = rest else {
+        return None;
+    };
+    let band = zone_band.chars().last().filter(char::is_ascii_alphabetic)?;
+    let zone: u8 = zone_band[..zone_band.len() - 1].parse().ok()?;
+    let metres = |word: &str, suffix: char| {
+        word.trim_end_matches(suffix)
+            .trim_end_matches('M')
+            .parse::<f64>()
+            .ok()
+    };
+    let (easting, northing) = (metres(easting, 'E')?, metres(northing, 'N')?);
+    Some(from_utm(&Utm {
+        zone,
+        band,
+        easting,
+        northing,
+    }))
+}
+
+/// Work out which notation `text` is in and where it points. `near` helps
+/// recover short plus codes.
+pub fn parse(
+    conn: &rusqlite::Connection,
+    text: &str,
+    near: Option<Point>,
+) -> Result<ParsedCoordinate> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(Error::Invalid("enter a coordinate".into()));
+    }
+    let found =
+        |point: Result<Point>, notation| point.map(|point| ParsedCoordinate { point, notation });
+    if text.contains('+') && !text.starts_with('+') {
+        if let Some(point) = parse_plus_code(conn, text, near) {
+            return found(point, Notation::PlusCode);
+        }
+    }
+    if let Some(point) = parse_utm(text) {
+        return found(point, Notation::Utm);
+    }
+    if let Some(point) = parse_mgrs(text) {
+        let spaced = text.split_whitespace().count() > 1;
+        return found(
+            point,
+            if spaced {
+                Notation::Usng
+            } else {
+                Notation::Mgrs
+            },
+        );
+    }
+    if let Some(parsed) = parse_degrees(text) {
+        return parsed.map(|(point, notation)| ParsedCoordinate { point, notation });
+    }
+    Err(Error::Invalid(format!(
+        "\"{text}\" is not a coordinate in any supported format"
+    )))
+}
+
+pub fn format(point: Point, notation: Notation) -> Result<String> {
+    point.validate()?;
+    Ok(match notation {
+        Notation::Decimal => format!("{:.6}, {:.6}", point.lat, point.lng),
+        Notation::Dms | Notation::Ddm => {
+            let seconds = notation == Notation::Dms;
+            format!(
+                "{} {}",
+                format_degrees(point.lat, 'N', 'S', seconds),
+                format_degrees(point.lng, 'E', 'W', seconds)
+            )
+        }
+        Notation::Utm => {
+            let utm = to_utm(point)?;
+            format!(
+                "{}{} {:.0} {:.0}",
+                utm.zone,
+                utm.band,
+                utm.easting.round(),
+                utm.northing.round()
+            )
+        }
+        Notation::Mgrs => to_mgrs(point, false)?,
+        Notation::Usng => to_mgrs(point, true)?,
+        Notation::PlusCode => to_plus_code(point)?,
+    })
+}
+
+// ─── Commands ───────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormattedCoordinate {
+    pub notation: Notation,
+    /// `None` where the notation doesn't reach, e.g. MGRS near the poles.
+    pub text: Option<String>,
+}
+
+/// Read a typed or radioed position in any supported notation.
+#[tauri::command]
+pub fn coords_parse(
+    db: State<'_, Db>,
+    text: String,
+    near: Option<Point>,
+) -> Result<ParsedCoordinate> {
+    db.with(|conn| parse(conn, &text, near))
+}
+
+/// Write positions in `notation`, or the user's preferred one. Positions
+/// the notation can't express come back in decimal degrees.
+#[tauri::command]
+pub fn coords_format(
+    app: AppHandle,
+    points: Vec<Point>,
+    notation: Option<Notation>,
+) -> Result<Vec<String>> {
+    let notation = notation.unwrap_or(load_preference(&app).notation);
+    points
+        .into_iter()
+        .map(|point| format(point, notation).or_else(|_| format(point, Notation::Decimal)))
+        .collect()
+}
+
+/// One position in every notation.
+#[tauri::command]
+pub fn coords_convert(point: Point) -> Result<Vec<FormattedCoordinate>> {
+    point.validate()?;
+    Ok(Notation::ALL
+        .into_iter()
+        .map(|notation| FormattedCoordinate {
+            notation,
+            text: format(point, notation).ok(),
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub fn coords_preference(app: AppHandle) -> CoordinatePreference {
+    load_preference(&app)
+}
+
+#[tauri::command]
+pub fn coords_preference_set(app: AppHandle, preference: CoordinatePreference) -> Result<()> {
+    save_preference(&app, &preference)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NEW_YORK: Point = Point {
+        lat: 40.7128,
+        lng: -74.006,
+    };
+
+    /// Spread over both hemispheres, both sides of the antimeridian, the
+    /// Norway and Svalbard zones and the last bands before the poles.
+    const PLACES: [Point; 10] = [
+        NEW_YORK,
+        Point {
+            lat: -33.8688,
+            lng: 151.2093,
+        },
+        Point {
+            lat: 0.0001,
+            lng: 0.0001,
+        },
+        Point {
+            lat: -0.5,
+            lng: -179.9,
+        },
+        Point {
+            lat: 60.39,
+            lng: 5.32,
+        },
+        Point {
+            lat: 78.22,
+            lng: 15.65,
+        },
+        Point {
+            lat: 79.0,
+            lng: 40.0,
+        },
+        Point {
+            lat: 83.9,
+            lng: -30.0,
+        },
+        Point {
+            lat: -79.9,
+            lng: 166.7,
+        },
+        Point {
+            lat: -54.8,
+            lng: -68.3,
+        },
+    ];
+
+    fn close(a: Point, b: Point, tolerance: f64) -> bool {
+        (a.lat - b.lat).abs() < tolerance && (a.lng - b.lng).abs() < tolerance
+    }
+
+    #[test]
+    fn utm_matches_a_known_position() {
+        assert_eq!(
+            format(NEW_YORK, Notation::Utm).unwrap(),
+            "18T 583959 4507351"
+        );
+    }
+
+    #[test]
+    fn utm_round_trips() {
+        for place in PLACES {
+            let utm = to_utm(place).unwrap();
+            assert!(close(from_utm(&utm).unwrap(), place, 1e-6), "{place:?}");
+        }
+    }
+
+    #[test]
+    fn norway_and_svalbard_zones() {
+        let zone = |lat, lng| to_utm(Point { lat, lng }).unwrap().zone;
+        // Bergen is in 32V, which is widened west over 31V
+        assert_eq!(zone(60.39, 5.32), 32);
+        assert_eq!(zone(60.39, 2.0), 31);
+        // Svalbard has only the odd zones 31, 33, 35 and 37
+        assert_eq!(zone(78.0, 5.0), 31);
+        assert_eq!(zone(78.0, 10.0), 33);
+        assert_eq!(zone(78.0, 20.0), 33);
+        assert_eq!(zone(78.0, 25.0), 35);
+        assert_eq!(zone(78.0, 40.0), 37);
+        // South of band X the usual zones apply
+        assert_eq!(zone(71.0, 10.0), 32);
+    }
+
+    #[test]
+    fn polar_bands() {
+        let band = |lat| to_utm(Point { lat, lng: 0.0 }).unwrap().band;
+        assert_eq!(band(-80.0), 'C');
+        assert_eq!(band(-0.1), 'M');
+        assert_eq!(band(0.0), 'N');
+        assert_eq!(band(72.0), 'X');
+        assert_eq!(band(84.0), 'X');
+        for lat in [84.1, -80.1, 90.0, -90.0] {
+            let pole = Point { lat, lng: 0.0 };
+            assert!(to_utm(pole).is_err());
+            assert!(to_mgrs(pole, false).is_err());
+            // Degrees and plus codes still work there
+            assert!(format(pole, Notation::Dms).is_ok());
+            assert!(to_plus_code(pole).is_ok());
+        }
+    }
+
+    #[test]
+    fn mgrs_matches_a_known_position() {
+        assert_eq!(to_mgrs(NEW_YORK, false).unwrap(), "18TWL8395907350");
+        assert_eq!(to_mgrs(NEW_YORK, true).unwrap(), "18T WL 83959 07350");
+    }
+
+    #[test]
+    fn mgrs_and_usng_round_trip() {
+        for place in PLACES {
+            for spaced in [false, true] {
+                let text = to_mgrs(place, spaced).unwrap();
+                let back = parse_mgrs(&text).unwrap().unwrap();
+                assert!(close(back, place, 2e-5), "{text} came back as {back:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn short_mgrs_is_the_square_centre() {
+        // 1 km square WL 83 07: centre 500 m in from the corner
+        let back = parse_mgrs("18TWL8307").unwrap().unwrap();
+        let utm = to_utm(back).unwrap();
+        assert!((utm.easting - 583_500.0).abs() < 1.0);
+        assert!((utm.northing - 4_507_500.0).abs() < 1.0);
+        assert!(parse_mgrs("18TWL830").unwrap().is_err());
+    }
+
+    #[test]
+    fn dms_and_ddm_round_trip() {
+        for place in PLACES {
+            for (notation, tolerance) in [(Notation::Dms, 3e-5), (Notation::Ddm, 2e-5)] {
+                let text = format(place, notation).unwrap();
+                let (back, parsed) = parse_degrees(&text).unwrap().unwrap();
+                assert_eq!(parsed, notation, "{text}");
+                assert!(
+                    close(back, place, tolerance),
+                    "{text} came back as {back:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn dms_matches_a_known_position() {
+        assert_eq!(
+            format(NEW_YORK, Notation::Dms).unwrap(),
+            "40°42'46.1\"N 074°00'21.6\"W"
+        );
+        assert_eq!(
+            format(NEW_YORK, Notation::Ddm).unwrap(),
+            "40°42.768'N 074°00.360'W"
+        );
+    }
+
+    #[test]
+    fn degrees_accept_hemispheres_on_either_side() {
+        let (point, _) = parse_degrees("N 40 42 46.1, W 74 0 21.6").unwrap().unwrap();
+        assert!(close(point, NEW_YORK, 1e-4));
+        let (point, _) = parse_degrees("74.006W 40.7128N").unwrap().unwrap();
+        assert!(close(point, NEW_YORK, 1e-9));
+        assert!(parse_degrees("-40.7 S, 74 W").unwrap().is_err());
+        assert!(parse_degrees("40 61 0 N 74 0 0 W").unwrap().is_err());
+    }
+
+    #[test]
+    fn plus_code_matches_a_known_position() {
+        let code = to_plus_code(Point {
+            lat: 47.365_590,
+            lng: 8.524_997,
+        })
+        .unwrap();
+        assert!(code.starts_with("8FVC9G8F+6X"), "{code}");
+    }
+
+    #[test]
+    fn plus_codes_round_trip() {
+        for place in PLACES {
+            let code = to_plus_code(place).unwrap();
+            let (back, lat_size, lng_size) = decode_plus_code(&code).unwrap();
+            assert!((back.lat - place.lat).abs() <= lat_size, "{code}");
+            assert!((back.lng - place.lng).abs() <= lng_size, "{code}");
+        }
+    }
+
+    #[test]
+    fn short_plus_codes_recover_near_the_reference() {
+        let zurich = Point {
+            lat: 47.3656,
+            lng: 8.525,
+        };
+        let full = to_plus_code(zurich).unwrap();
+        let short = &full[4..];
+        let back = recover_short_code(
+            short,
+            4,
+            Point {
+                lat: 47.4,
+                lng: 8.6,
+            },
+        )
+        .unwrap();
+        assert!(close(back, zurich, 1e-3), "{short} came back as {back:?}");
+    }
+}
@@ -0,0 +1,123 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use hkdf::Hkdf;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::error::{Error, Result};
+
+pub const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 24;
+/// Prefix of sealed blobs, so we can tell them from plaintext and bump the
+/// format later.
+const MAGIC: &[u8; 4] = b"DCE1";
+
+/// 256-bit secret that is wiped from memory when dropped.
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct Key([u8; KEY_LEN]);
+
+impl Key {
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; KEY_LEN];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        Self(bytes)
+    }
+
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        let bytes: [u8; KEY_LEN] = bytes
+            .try_into()
+            .map_err(|_| Error::Invalid("key has the wrong length".into()))?;
+        Ok(Self(bytes))
+    }
+
+    pub fn as_bytes(&self) -> &[u8; KEY_LEN] {
+        &self.0
+    }
+
+    /// SQLCipher raw key literal, `x'…'`, so SQLCipher skips its own KDF.
+    pub fn sqlcipher_literal(&self) -> String {
+        let hex: String = self.0.iter().map(|b| format!("{b:02x}")).collect();
+        format!("x'{hex}'")
+    }
+
+    /// Independent key for one purpose, so the database key is never used
+    /// directly for anything else.
+    pub fn derive(&self, purpose: &str) -> Key {
+        let mut out = [0u8; KEY_LEN];
+        Hkdf::<Sha256>::new(None, &self.0)
+            .expand(purpose.as_bytes(), &mut out)
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        Key(out)
+    }
+}
+
+/// Argon2id cost, stored next to the salt so it can be raised later
+/// without breaking existing keystores.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        // OWASP's baseline for Argon2id
+        Self {
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+pub fn random_salt() -> [u8; 16] {
+    let mut salt = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut salt);
+    salt
+}
+
+pub fn derive_from_passphrase(passphrase: &str, salt: &[u8], params: KdfParams) -> Result<Key> {
+    let params = Params::new(
+        params.memory_kib,
+        params.iterations,
+        params.parallelism,
+        Some(KEY_LEN),
+    )
+    .map_err(|e| Error::Invalid(format!("bad key derivation parameters: {e}")))?;
+    let mut out = [0u8; KEY_LEN];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut out)
+        .map_err(|e| Error::Invalid(format!("key derivation failed: {e}")))?;
+    Ok(Key(out))
+}
+
+/// Encrypt and authenticate `plaintext` as `MAGIC || nonce || ciphertext`.
+pub fn seal(key: &Key, plaintext: &[u8]) -> Result<Vec<u8>> {
+    let cipher = XChaCha20Poly1305::new(key.as_bytes().into());
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let ciphertext = cipher
+        .encrypt(XNonce::from_slice(&nonce), plaintext)
+        .map_err(|_| Error::Crypto)?;
+
+    let mut out = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+pub fn open(key: &Key, sealed: &[u8]) -> Result<Vec<u8>> {
+    let body = sealed.strip_prefix(MAGIC.as_slice()).ok_or(Error::Crypto)?;
+    if body.len() < NONCE_LEN {
+        return Err(Error::Crypto);
+    }
+    let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+    XChaCha20Poly1305::new(key.as_bytes().into())
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| Error::Crypto)
+}
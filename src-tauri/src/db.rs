@@ -0,0 +1,163 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rusqlite::{Connection, DatabaseName};
+use tauri::{AppHandle, Manager};
+
+use crate::crypto::Key;
+use crate::error::{Error, Result};
+
+/// Same file the frontend gets from `Database.load("sqlite:disasterconnect.db")`,
+/// since tauri-plugin-sql resolves relative paths against the app config dir.
+pub const DB_FILE: &str = "disasterconnect.db";
+
+/// Handle to the local SQLite database shared by the native modules. The
+/// database is encrypted with SQLCipher, so there is no connection until
+/// `unlock` has been given the key.
+pub struct Db {
+    conn: Mutex<Option<Connection>>,
+    path: PathBuf,
+}
+
+impl Db {
+    pub fn new(app: &AppHandle) -> Result<Self> {
+        let dir = app.path().app_config_dir()?;
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self::at(dir.join(DB_FILE)))
+    }
+
+    /// A locked handle to the database file at `path`.
+    pub fn at(path: PathBuf) -> Self {
+        Self {
+            conn: Mutex::new(None),
+            path,
+        }
+    }
+
+    /// Open the database with `key` and bring the schema up to date.
+    pub fn unlock(&self, key: &Key) -> Result<()> {
+        let mut conn = Connection::open(&self.path)?;
+        conn.pragma_update(None, "key", key.sqlcipher_literal())?;
+        // SQLCipher only notices a wrong key on first read
+        conn.query_row("SELECT count(*) FROM sqlite_master", [], |_| Ok(()))
+            .map_err(|_| Error::Crypto)?;
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+
+        crate::migrations::run(&mut conn, &self.path)?;
+
+        *self.slot() = Some(conn);
+        Ok(())
+    }
+
+    /// Close the connection; everything fails with `Error::Locked` until the
+    /// next `unlock`.
+    pub fn lock(&self) {
+        self.slot().take();
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.slot().is_some()
+    }
+
+    /// Whether the file on disk is an unencrypted SQLite database, which
+    /// SQLCipher files never look like.
+    pub fn is_plaintext(&self) -> bool {
+        let mut header = [0u8; 16];
+        std::fs::File::open(&self.path)
+            .and_then(|mut file| file.read_exact(&mut header))
+            .is_ok_and(|_| &header == b"SQLite format 3\0")
+    }
+
+    /// Convert a plaintext database file to SQLCipher under `key`. Leaves
+    /// the database locked; a missing file is left for `unlock` to create.
+    pub fn encrypt(&self, key: &Key) -> Result<()> {
+        self.lock();
+        if !self.path.exists() {
+            return Ok(());
+        }
+
+        let tmp = self.path.with_extension("db.encrypting");
+        let _ = std::fs::remove_file(&tmp);
+        {
+            let conn = Connection::open(&self.path)?;
+            conn.pragma_update(None, "wal_checkpoint", "TRUNCATE")?;
+            let version: u32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+            conn.execute(
+                "ATTACH DATABASE ?1 AS encrypted KEY ?2",
+                [tmp.to_string_lossy().into_owned(), key.sqlcipher_literal()],
+            )?;
+            conn.query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))?;
+            // sqlcipher_export leaves the schema version behind
+            conn.pragma_update(
+                Some(DatabaseName::Attached("encrypted")),
+                "user_version",
+                version,
+            )?;
+            conn.execute("DETACH DATABASE encrypted", [])?;
+        }
+
+        std::fs::rename(&tmp, &self.path)?;
+        for suffix in ["-wal", "-shm"] {
+            let _ = std::fs::remove_file(with_suffix(&self.path, suffix));
+        }
+        remove_plaintext_backups(&self.path);
+        Ok(())
+    }
+
+    /// Re-encrypt the open database under a new key.
+    pub fn rekey(&self, key: &Key) -> Result<()> {
+        self.with(|conn| {
+            // SQLCipher can't rekey a database in WAL mode
+            conn.pragma_update(None, "journal_mode", "DELETE")?;
+            conn.pragma_update(None, "rekey", key.sqlcipher_literal())?;
+            conn.pragma_update(None, "journal_mode", "WAL")?;
+            Ok(())
+        })
+    }
+
+    /// Run `f` with exclusive access to the connection. Never hold this across
+    /// an `.await`.
+    pub fn with<T>(&self, f: impl FnOnce(&mut Connection) -> Result<T>) -> Result<T> {
+        let mut slot = self.slot();
+        let conn = slot.as_mut().ok_or(Error::Locked)?;
+        f(conn)
+    }
+
+    fn slot(&self) -> std::sync::MutexGuard<'_, Option<Connection>> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Pre-migration backups of a plaintext database would leak the data we
+/// just encrypted.
+fn remove_plaintext_backups(db_path: &Path) {
+    let (Some(dir), Some(file_name)) = (
+        db_path.parent(),
+        db_path.file_name().and_then(|n| n.to_str()),
+    ) else {
+        return;
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let prefix = format!("{file_name}.v");
+    for path in entries.filter_map(|e| e.ok().map(|e| e.path())) {
+        let is_backup = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with(&prefix) && n.ends_with(".bak"));
+        if is_backup {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use rusqlite::{params, Connection, Row};
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::conflicts::Strategy;
+use crate::db::Db;
+use crate::error::{Error, Result};
+use crate::offline_queue::{self, Operation, QueuedMutation};
+
+pub const CHANGED_EVENT: &str = "dead-letter://changed";
+
+/// A mutation the server rejected for good. Kept so someone can fix the
+/// payload and send it again instead of losing it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetter {
+    pub id: String,
+    pub table: String,
+    pub operation: Operation,
+    pub payload: Value,
+    pub queued_at: String,
+    pub retries: u32,
+    pub error: String,
+    pub failed_at: String,
+    pub base_updated_at: Option<String>,
+    pub strategy: Strategy,
+}
+
+fn from_row(row: &Row) -> rusqlite::Result<DeadLetter> {
+    let operation: String = row.get("operation")?;
+    Ok(DeadLetter {
+        id: row.get("id")?,
+        table: row.get("table_name")?,
+        operation: Operation::parse(&operation).ok_or_else(|| {
+            rusqlite::Error::InvalidColumnType(0, operation, rusqlite::types::Type::Text)
+        })?,
+        payload: row.get("payload")?,
+        queued_at: row.get("queued_at")?,
+        retries: row.get("retries")?,
+        error: row.get("error")?,
+        failed_at: row.get("failed_at")?,
+        base_updated_at: row.get("base_updated_at")?,
+        strategy: Strategy::parse(&row.get::<_, String>("strategy")?).unwrap_or_default(),
+    })
+}
+
+/// Move a queued mutation into the dead-letter store.
+pub fn bury(conn: &mut Connection, id: &str, error: &str) -> Result<()> {
+    let tx = conn.transaction()?;
+    let moved = tx.execute(
+        "INSERT INTO dead_letters (id, table_name, operation, payload, queued_at, retries,
+             error, failed_at, base_updated_at, base, strategy)
+         SELECT id, table_name, operation, payload, queued_at, retries + 1,
+             ?2, ?3, base_updated_at, base, strategy
+         FROM offline_queue WHERE id = ?1",
+        params![id, error, chrono::Utc::now().to_rfc3339()],
+    )?;
+    if moved == 0 {
+        return Err(Error::NotFound(format!("queued mutation {id}")));
+    }
+    offline_queue::remove(&tx, id)?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Move a rejected mutation into the dead-letter store together with the
+/// later ones queued for the same record, which would otherwise replay
+/// against a row that isn't there. Returns how many were moved.
+pub fn bury_chain(
+    conn: &mut Connection,
+    head: &QueuedMutation,
+    key: Option<&(String, String)>,
+    error: &str,
+) -> Result<u32> {
+    let dependents: Vec<String> = match key {
+        Some(key) => offline_queue::list(conn)?
+            .into_iter()
+            .skip_while(|m| m.id != head.id)
+            .skip(1)
+            .filter(|m| offline_queue::chain_key(m).as_ref() == Some(key))
+            .map(|m| m.id)
+            .collect(),
+        None => Vec::new(),
+    };
+    bury(conn, &head.id, error)?;
+    let held = format!("an earlier change to this record was rejected: {error}");
+    for id in &dependents {
+        bury(conn, id, &held)?;
+    }
+    Ok(1 + dependents.len() as u32)
+}
+
+pub fn list(conn: &Connection) -> Result<Vec<DeadLetter>> {
+    let mut stmt = conn.prepare("SELECT * FROM dead_letters ORDER BY failed_at DESC")?;
+    let rows = stmt.query_map([], from_row)?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+pub fn edit(conn: &Connection, id: &str, payload: &Value) -> Result<()> {
+    if !payload.is_object() {
+        return Err(Error::Invalid("payload must be an object".into()));
+    }
+    let changed = conn.execute(
+        "UPDATE dead_letters SET payload = ?2 WHERE id = ?1",
+        params![id, payload],
+    )?;
+    if changed == 0 {
+        return Err(Error::NotFound(format!("dead letter {id}")));
+    }
+    Ok(())
+}
+
+/// Put a dead letter back in the queue with a fresh retry budget. It keeps
+/// its place, so retrying a rejected insert and then the updates buried with
+/// it replays them in their original order.
+pub fn requeue(conn: &mut Connection, id: &str) -> Result<()> {
+    let tx = conn.transaction()?;
+    let moved = tx.execute(
+        "INSERT INTO offline_queue (id, table_name, operation, payload, queued_at,
+             base_updated_at, base, strategy)
+         SELECT id, table_name, operation, payload, queued_at, base_updated_at, base, strategy
+         FROM dead_letters WHERE id = ?1",
+        [id],
+    )?;
+    if moved == 0 {
+        return Err(Error::NotFound(format!("dead letter {id}")));
+    }
+    tx.execute("DELETE FROM dead_letters WHERE id = ?1", [id])?;
+    tx.commit()?;
+    Ok(())
+}
+
+pub fn remove(conn: &Connection, id: &str) -> Result<()> {
+    if conn.execute("DELETE FROM dead_letters WHERE id = ?1", [id])? == 0 {
+        return Err(Error::NotFound(format!("dead letter {id}")));
+    }
+    Ok(())
+}
+
+// ─── Commands ───────────────────────────────────────────────────
+
+#[tauri::command]
+pub fn dead_letters_list(db: State<'_, Db>) -> Result<Vec<DeadLetter>> {
+    db.with(|conn| list(conn))
+}
+
+#[tauri::command]
+pub fn dead_letter_edit(
+    app: AppHandle,
+    db: State<'_, Db>,
+    id: String,
+    payload: Value,
+) -> Result<()> {
+    db.with(|conn| edit(conn, &id, &payload))?;
+    let _ = app.emit(CHANGED_EVENT, ());
+    Ok(())
+}
+
+#[tauri::command]
+pub fn dead_letter_retry(app: AppHandle, db: State<'_, Db>, id: String) -> Result<()> {
+    let remaining = db.with(|conn| {
+        requeue(conn, &id)?;
+        offline_queue::count(conn)
+    })?;
+    let _ = app.emit(CHANGED_EVENT, ());
+    let _ = app.emit(offline_queue::CHANGED_EVENT, remaining);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn dead_letter_discard(app: AppHandle, db: State<'_, Db>, id: String) -> Result<()> {
+    db.with(|conn| remove(conn, &id))?;
+    let _ = app.emit(CHANGED_EVENT, ());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::offline_queue::NewMutation;
+
+    fn db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        for migration in crate::migrations::MIGRATIONS {
+            conn.execute_batch(migration.sql).unwrap();
+        }
+        conn
+    }
+
+    fn queue(conn: &Connection, operation: Operation, payload: Value) -> QueuedMutation {
+        let mutation = NewMutation {
+            table: "tasks".into(),
+            operation,
+            payload,
+            base_updated_at: None,
+            base: None,
+            strategy: None,
+        };
+        offline_queue::enqueue(conn, mutation).unwrap()
+    }
+
+    fn queued_ids(conn: &Connection) -> Vec<String> {
+        offline_queue::list(conn)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.id)
+            .collect()
+    }
+
+    #[test]
+    fn a_rejected_insert_takes_its_records_later_changes_with_it() {
+        let mut conn = db();
+        let insert = queue(
+            &conn,
+            Operation::Insert,
+            serde_json::json!({ "id": "a", "title": "" }),
+        );
+        let rename = queue(
+            &conn,
+            Operation::Update,
+            serde_json::json!({ "id": "a", "title": "x" }),
+        );
+        let other = queue(
+            &conn,
+            Operation::Update,
+            serde_json::json!({ "id": "b", "done": true }),
+        );
+        let close = queue(&conn, Operation::Delete, serde_json::json!({ "id": "a" }));
+
+        let key = offline_queue::chain_key(&insert);
+        let buried =
+            bury_chain(&mut conn, &insert, key.as_ref(), "title must not be empty").unwrap();
+        assert_eq!(buried, 3);
+        assert_eq!(queued_ids(&conn), [other.id]);
+
+        let letters = list(&conn).unwrap();
+        let error = |id: &str| letters.iter().find(|l| l.id == id).unwrap().error.clone();
+        assert_eq!(error(&insert.id), "title must not be empty");
+        assert_eq!(
+            error(&close.id),
+            "an earlier change to this record was rejected: title must not be empty"
+        );
+        assert_eq!(
+            letters.iter().find(|l| l.id == rename.id).unwrap().retries,
+            1
+        );
+    }
+
+    #[test]
+    fn only_the_head_goes_when_it_names_no_record() {
+        let mut conn = db();
+        let insert = queue(
+            &conn,
+            Operation::Insert,
+            serde_json::json!({ "title": "new" }),
+        );
+        let other = queue(
+            &conn,
+            Operation::Insert,
+            serde_json::json!({ "title": "other" }),
+        );
+        assert_eq!(bury_chain(&mut conn, &insert, None, "rejected").unwrap(), 1);
+        assert_eq!(queued_ids(&conn), [other.id]);
+    }
+
+    #[test]
+    fn requeued_chains_replay_in_their_original_order() {
+        let mut conn = db();
+        let insert = queue(&conn, Operation::Insert, serde_json::json!({ "id": "a" }));
+        let other = queue(&conn, Operation::Insert, serde_json::json!({ "id": "b" }));
+        let update = queue(&conn, Operation::Update, serde_json::json!({ "id": "a" }));
+        let key = offline_queue::chain_key(&insert);
+        bury_chain(&mut conn, &insert, key.as_ref(), "rejected").unwrap();
+
+        // Fixed and retried in the wrong order, they still queue in the right one
+        edit(
+            &conn,
+            &insert.id,
+            &serde_json::json!({ "id": "a", "title": "fixed" }),
+        )
+        .unwrap();
+        requeue(&mut conn, &update.id).unwrap();
+        requeue(&mut conn, &insert.id).unwrap();
+        assert!(list(&conn).unwrap().is_empty());
+
+        let queued = offline_queue::list(&conn).unwrap();
+        let ids: Vec<&str> = queued.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            [insert.id.as_str(), other.id.as_str(), update.id.as_str()]
+        );
+        assert_eq!(queued[0].payload["title"], "fixed");
+        assert!(queued
+            .iter()
+            .all(|m| m.retries == 0 && m.next_attempt_at.is_none()));
+
+        assert!(matches!(
+            requeue(&mut conn, &insert.id),
+            Err(Error::NotFound(_))
+        ));
+        assert!(edit(&conn, &insert.id, &Value::Null).is_err());
+    }
+}
@@ -0,0 +1,419 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+use url::Url;
+
+use crate::crypto::Key;
+use crate::error::{Error, Result};
+use crate::share;
+use crate::supabase::SupabaseState;
+
+pub const SCHEME: &str = "disasterconnect";
+/// Longer than any link we hand out; anything bigger is junk or an attack.
+const MAX_URL_LEN: usize = 4096;
+/// Links held while the webview is still loading.
+const MAX_PENDING: usize = 16;
+/// First part of the link (`disasterconnect://{host}/...`) we act on at all.
+const ALLOWED_HOSTS: &[&str] = &[
+    "auth",
+    "dashboard",
+    "incidents",
+    "tasks",
+    "alerts",
+    "map",
+    "sos",
+    "evacuation",
+];
+const MAX_ZOOM: u8 = 22;
+
+pub const OPEN_EVENT: &str = "deep-link://open";
+
+/// A validated `disasterconnect://` link, as delivered to the frontend.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DeepLink {
+    /// Supabase email link (sign-up confirmation, password recovery) with the
+    /// session tokens from the URL fragment.
+    #[serde(rename_all = "camelCase")]
+    AuthCallback {
+        access_token: String,
+        refresh_token: String,
+        /// `signup`, `recovery`, `magiclink`, ...
+        link_type: Option<String>,
+    },
+    /// Jump to a screen or record.
+    Open {
+        route: Route,
+        /// Frontend path for `route`, ready for the router.
+        path: String,
+        /// Carries a valid share signature (see `share.rs`), or was raised
+        /// by the app itself.
+        signed: bool,
+    },
+}
+
+/// Screens and records a link can point at.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "screen", rename_all = "snake_case")]
+pub enum Route {
+    Dashboard,
+    Incidents,
+    /// Incoming CAP messages waiting for review.
+    CapReview,
+    Incident {
+        id: String,
+    },
+    Task {
+        id: String,
+    },
+    Alert {
+        id: String,
+    },
+    Sos {
+        id: String,
+    },
+    #[serde(rename = "evacuation_route")]
+    Evacuation {
+        id: String,
+    },
+    Map {
+        lat: f64,
+        lng: f64,
+        zoom: Option<u8>,
+    },
+}
+
+impl Route {
+    pub fn path(&self) -> String {
+        match self {
+            Route::Dashboard => "/dashboard".into(),
+            Route::Incidents => "/incidents".into(),
+            Route::CapReview => "/alerts?review=cap".into(),
+            Route::Incident { id } => format!("/incidents/{id}"),
+            Route::Task { id } => format!("/tasks?task={id}"),
+            Route::Alert { id } => format!("/alerts?alert={id}"),
+            Route::Sos { id } => format!("/dashboard?sos={id}"),
+            Route::Evacuation { id } => format!("/evacuation?route={id}"),
+            Route::Map { lat, lng, zoom } => match zoom {
+                Some(zoom) => format!("/map?lat={lat}&lng={lng}&zoom={zoom}"),
+                None => format!("/map?lat={lat}&lng={lng}"),
+            },
+        }
+    }
+
+    fn parse(host: &str, segments: &[&str], url: &Url) -> Result<Self> {
+        let route = match (host, segments) {
+            ("dashboard", []) => Route::Dashboard,
+            ("incidents", []) => Route::Incidents,
+            ("incidents", [id]) => Route::Incident { id: record_id(id)? },
+            ("tasks", [id]) => Route::Task { id: record_id(id)? },
+            ("alerts", ["review"]) => Route::CapReview,
+            ("alerts", [id]) => Route::Alert { id: record_id(id)? },
+            ("sos", [id]) => Route::Sos { id: record_id(id)? },
+            ("evacuation", [id]) => Route::Evacuation { id: record_id(id)? },
+            ("map", []) => {
+                let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+                let number = |name: &str| -> Result<f64> {
+                    query
+                        .get(name)
+                        .and_then(|v| v.parse::<f64>().ok())
+                        .filter(|v| v.is_finite())
+                        .ok_or_else(|| Error::Invalid(format!("map link needs a numeric {name}")))
+                };
+                let (lat, lng) = (number("lat")?, number("lng")?);
+                if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lng) {
+                    return Err(Error::Invalid("map link is off the globe".into()));
+                }
+                let zoom = match query.get("zoom") {
+                    Some(zoom) => Some(
+                        zoom.parse::<u8>()
+                            .ok()
+                            .filter(|z| *z <= MAX_ZOOM)
+                            .ok_or_else(|| Error::Invalid("bad map zoom".into()))?,
+                    ),
+                    None => None,
+                };
+                Route::Map { lat, lng, zoom }
+            }
+            _ => {
+                return Err(Error::Invalid(format!(
+                    "unsupported deep link {host}/{}",
+                    segments.join("/")
+                )))
+            }
+        };
+        Ok(route)
+    }
+}
+
+/// `host/id` part of the link to a single record; what share links sign.
+pub fn link_target(route: &Route) -> Option<String> {
+    let (host, id) = match route {
+        Route::Incident { id } => ("incidents", id),
+        Route::Task { id } => ("tasks", id),
+        Route::Alert { id } => ("alerts", id),
+        Route::Sos { id } => ("sos", id),
+        Route::Evacuation { id } => ("evacuation", id),
+        Route::Dashboard | Route::Incidents | Route::CapReview | Route::Map { .. } => return None,
+    };
+    Some(format!("{host}/{id}"))
+}
+
+/// Check the `exp` and `sig` of a share link. Before sign-in there is no
+/// signing key to check against, and a link from another deployment was
+/// signed with a key we don't have, so both count as unsigned rather than
+/// being dropped; record access is still down to row-level security.
+fn check_signature(route: &Route, url: &Url, key: Option<&Key>) -> Result<bool> {
+    let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+    let exp = match query.get("exp") {
+        Some(exp) => Some(
+            exp.parse::<i64>()
+                .map_err(|_| Error::Invalid("bad link expiry".into()))?,
+        ),
+        None => None,
+    };
+    if exp.is_some_and(|exp| exp < chrono::Utc::now().timestamp()) {
+        return Err(Error::Invalid("link has expired".into()));
+    }
+
+    let (Some(sig), Some(target), Some(key)) = (query.get("sig"), link_target(route), key) else {
+        return Ok(false);
+    };
+    Ok(share::verify(key, &target, exp, sig))
+}
+
+/// Record ids are UUIDs; anything else never came from us.
+fn record_id(raw: &str) -> Result<String> {
+    uuid::Uuid::parse_str(raw)
+        .map(|id| id.to_string())
+        .map_err(|_| Error::Invalid(format!("{raw:?} is not a record id")))
+}
+
+pub fn parse(raw: &str, signing_key: Option<&Key>) -> Result<DeepLink> {
+    if raw.len() > MAX_URL_LEN {
+        return Err(Error::Invalid("deep link is too long".into()));
+    }
+    if raw.chars().any(char::is_control) {
+        return Err(Error::Invalid(
+            "deep link contains control characters".into(),
+        ));
+    }
+    let url = Url::parse(raw).map_err(|e| Error::Invalid(format!("bad deep link: {e}")))?;
+    if url.scheme() != SCHEME {
+        return Err(Error::Invalid(format!("not a {SCHEME}:// link")));
+    }
+
+    let host = url.host_str().unwrap_or_default();
+    if !ALLOWED_HOSTS.contains(&host) {
+        return Err(Error::Invalid(format!("unsupported deep link {host:?}")));
+    }
+    let segments: Vec<&str> = url
+        .path_segments()
+        .map(|s| s.filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default();
+
+    if let ("auth", ["callback"]) = (host, segments.as_slice()) {
+        let params: HashMap<String, String> =
+            url::form_urlencoded::parse(url.fragment().unwrap_or_default().as_bytes())
+                .into_owned()
+                .collect();
+        let token = |name: &str| {
+            params
+                .get(name)
+                .filter(|v| !v.is_empty())
+                .cloned()
+                .ok_or_else(|| Error::Invalid(format!("auth callback without {name}")))
+        };
+        return Ok(DeepLink::AuthCallback {
+            access_token: token("access_token")?,
+            refresh_token: token("refresh_token")?,
+            link_type: params.get("type").cloned(),
+        });
+    }
+
+    let route = Route::parse(host, &segments, &url)?;
+    let signed = check_signature(&route, &url, signing_key)?;
+    Ok(DeepLink::Open {
+        path: route.path(),
+        route,
+        signed,
+    })
+}
+
+/// Links that arrived before the frontend was listening.
+#[derive(Default)]
+pub struct DeepLinks {
+    state: Mutex<Inbox>,
+}
+
+#[derive(Default)]
+struct Inbox {
+    ready: bool,
+    pending: Vec<DeepLink>,
+}
+
+fn focus_main(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Bring the window forward and hand `link` to the frontend, or hold on to
+/// it until `deep_link_ready` if the webview hasn't loaded yet.
+fn deliver(app: &AppHandle, link: DeepLink) {
+    focus_main(app);
+
+    let links = app.state::<DeepLinks>();
+    let mut inbox = links.state.lock().unwrap_or_else(|e| e.into_inner());
+    if inbox.ready {
+        let _ = app.emit(OPEN_EVENT, link);
+    } else if inbox.pending.len() < MAX_PENDING {
+        inbox.pending.push(link);
+    }
+}
+
+/// Validate an incoming URL and deliver it.
+pub fn handle(app: &AppHandle, raw: &str) {
+    let key = share::signing_key(&app.state::<SupabaseState>());
+    match parse(raw, key.as_ref()) {
+        Ok(link) => deliver(app, link),
+        Err(e) => eprintln!("[deep-link] Ignoring link: {e}"),
+    }
+}
+
+/// Navigate from native UI (tray menu, notifications) the same way a link would.
+pub fn open(app: &AppHandle, route: Route) {
+    deliver(
+        app,
+        DeepLink::Open {
+            path: route.path(),
+            route,
+            signed: true,
+        },
+    );
+}
+
+/// Called by the frontend once it listens for `OPEN_EVENT`; returns the
+/// links that came in before that.
+#[tauri::command]
+pub fn deep_link_ready(links: State<'_, DeepLinks>) -> Vec<DeepLink> {
+    let mut inbox = links.state.lock().unwrap_or_else(|e| e.into_inner());
+    inbox.ready = true;
+    std::mem::take(&mut inbox.pending)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "0b6d7a52-3f43-4c1e-9a51-2a0c8f3e5d11";
+
+    fn open(raw: &str, key: Option<&Key>) -> (Route, bool) {
+        match parse(raw, key).unwrap() {
+            DeepLink::Open { route, signed, .. } => (route, signed),
+            other => panic!("expected a route, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn unsigned_record_links_open() {
+        let (route, signed) = open(&format!("disasterconnect://incidents/{ID}"), None);
+        assert_eq!(route, Route::Incident { id: ID.into() });
+        assert!(!signed);
+        assert_eq!(route.path(), format!("/incidents/{ID}"));
+    }
+
+    #[test]
+    fn control_characters_and_long_links_are_rejected() {
+        assert!(parse("disasterconnect://dashboard\n", None).is_err());
+        assert!(parse("disasterconnect://dash\u{7}board", None).is_err());
+        let long = format!(
+            "disasterconnect://map?lat=1&lng=2&pad={}",
+            "x".repeat(MAX_URL_LEN)
+        );
+        assert!(parse(&long, None).is_err());
+    }
+
+    #[test]
+    fn only_allowed_screens_and_record_ids_parse() {
+        assert!(parse("https://incidents/x", None).is_err());
+        assert!(parse("disasterconnect://settings", None).is_err());
+        assert!(parse("disasterconnect://incidents/not-a-uuid", None).is_err());
+        assert!(parse(&format!("disasterconnect://incidents/{ID}/edit"), None).is_err());
+        assert_eq!(
+            open("disasterconnect://alerts/review", None).0,
+            Route::CapReview
+        );
+    }
+
+    #[test]
+    fn map_links_are_bounded() {
+        let (route, _) = open("disasterconnect://map?lat=-33.9&lng=151.2&zoom=22", None);
+        assert_eq!(
+            route,
+            Route::Map {
+                lat: -33.9,
+                lng: 151.2,
+                zoom: Some(22)
+            }
+        );
+        assert!(parse("disasterconnect://map?lat=1&lng=2&zoom=23", None).is_err());
+        assert!(parse("disasterconnect://map?lat=1&lng=2&zoom=-1", None).is_err());
+        assert!(parse("disasterconnect://map?lat=91&lng=2", None).is_err());
+        assert!(parse("disasterconnect://map?lat=NaN&lng=2", None).is_err());
+        assert!(parse("disasterconnect://map?lat=1", None).is_err());
+    }
+
+    #[test]
+    fn signatures_are_checked_against_the_key() {
+        let key = Key::generate();
+        let target = format!("incidents/{ID}");
+        let exp = chrono::Utc::now().timestamp() + 3600;
+        let link = format!(
+            "disasterconnect://{target}?exp={exp}&sig={}",
+            share::sign(&key, &target, Some(exp))
+        );
+        assert!(open(&link, Some(&key)).1);
+        // Another deployment's link, or one seen before sign-in
+        assert!(!open(&link, Some(&Key::generate())).1);
+        assert!(!open(&link, None).1);
+        // A moved expiry no longer matches the signature
+        let moved = link.replace(&format!("exp={exp}"), &format!("exp={}", exp + 1));
+        assert!(!open(&moved, Some(&key)).1);
+    }
+
+    #[test]
+    fn expired_links_are_dropped() {
+        let key = Key::generate();
+        let target = format!("tasks/{ID}");
+        let link = format!(
+            "disasterconnect://{target}?exp=1000&sig={}",
+            share::sign(&key, &target, Some(1000))
+        );
+        assert!(parse(&link, Some(&key)).is_err());
+    }
+
+    #[test]
+    fn auth_callbacks_carry_the_tokens() {
+        let link = parse(
+            "disasterconnect://auth/callback#access_token=a&refresh_token=r&type=recovery",
+            None,
+        )
+        .unwrap();
+        let DeepLink::AuthCallback {
+            access_token,
+            refresh_token,
+            link_type,
+        } = link
+        else {
+            panic!("expected an auth callback");
+        };
+        assert_eq!((access_token.as_str(), refresh_token.as_str()), ("a", "r"));
+        assert_eq!(link_type.as_deref(), Some("recovery"));
+        assert!(parse("disasterconnect://auth/callback#access_token=a", None).is_err());
+    }
+}
@@ -0,0 +1,50 @@
+use serde::{Serialize, Serializer};
+
+/// Errors returned by the native commands. They are serialised as plain
+/// strings so the frontend can show them in a toast as-is.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Tauri(#[from] tauri::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("database error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("network error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("supabase error {status}: {message}")]
+    Supabase {
+        status: u16,
+        code: Option<String>,
+        message: String,
+    },
+    #[error(
+        "the local database was written by a newer version of DisasterConnect \
+         (schema v{found}, this build supports up to v{supported}); please update the app"
+    )]
+    SchemaTooNew { found: u32, supported: u32 },
+    #[error("the local database is locked")]
+    Locked,
+    #[error("the local database is not encrypted")]
+    NotEncrypted,
+    #[error("wrong passphrase")]
+    WrongPassphrase,
+    #[error("decryption failed: wrong key or corrupted data")]
+    Crypto,
+    #[error("not signed in to Supabase")]
+    NotConfigured,
+    #[error("{0} not found")]
+    NotFound(String),
+    #[error("{0}")]
+    Invalid(String),
+}
+
+impl Serialize for Error {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,441 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::error::{Error, Result};
+use crate::geo::{self, Point, EARTH_RADIUS_KM};
+
+const PROFILES_FILE: &str = "speed-profiles.json";
+/// Consecutive waypoints closer than this are the same point clicked twice.
+const REPEAT_THRESHOLD_KM: f64 = 0.001;
+/// Self-intersection checks are quadratic; hand-drawn routes are far shorter.
+pub const MAX_WAYPOINTS: usize = 5000;
+
+/// How fast evacuees move on a route.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeedProfile {
+    pub speed_kmh: f64,
+    /// Fixed time on top of travel: loading, boarding, marshalling.
+    pub overhead_minutes: f64,
+}
+
+impl SpeedProfile {
+    fn minutes(&self, distance_km: f64) -> f64 {
+        self.overhead_minutes + distance_km / self.speed_kmh * 60.0
+    }
+
+    fn validate(&self, name: &str) -> Result<()> {
+        let usable = self.speed_kmh.is_finite()
+            && self.speed_kmh > 0.0
+            && self.overhead_minutes.is_finite()
+            && self.overhead_minutes >= 0.0;
+        if !usable {
+            return Err(Error::Invalid(format!(
+                "the {name} profile needs a positive speed and overhead"
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Speeds used for travel-time estimates. The defaults assume congested
+/// evacuation traffic rather than free-flowing roads.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeedProfiles {
+    pub walking: SpeedProfile,
+    pub driving: SpeedProfile,
+    pub bus: SpeedProfile,
+}
+
+impl Default for SpeedProfiles {
+    fn default() -> Self {
+        Self {
+            walking: SpeedProfile {
+                speed_kmh: 4.0,
+                overhead_minutes: 0.0,
+            },
+            driving: SpeedProfile {
+                speed_kmh: 30.0,
+                overhead_minutes: 5.0,
+            },
+            bus: SpeedProfile {
+                speed_kmh: 20.0,
+                overhead_minutes: 15.0,
+            },
+        }
+    }
+}
+
+impl SpeedProfiles {
+    fn validate(&self) -> Result<()> {
+        self.walking.validate("walking")?;
+        self.driving.validate("driving")?;
+        self.bus.validate("bus")
+    }
+}
+
+fn profiles_path(app: &AppHandle) -> Result<PathBuf> {
+    Ok(app.path().app_config_dir()?.join(PROFILES_FILE))
+}
+
+/// Saved profiles, or the defaults if none were saved or the file is bad.
+pub fn load_profiles(app: &AppHandle) -> SpeedProfiles {
+    profiles_path(app)
+        .ok()
+        .and_then(|path| std::fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice::<SpeedProfiles>(&bytes).ok())
+        .filter(|profiles| profiles.validate().is_ok())
+        .unwrap_or_default()
+}
+
+fn save_profiles(app: &AppHandle, profiles: &SpeedProfiles) -> Result<()> {
+    let path = profiles_path(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(profiles)?)?;
+    std::fs::rename(tmp, path)?;
+    Ok(())
+}
+
+// ─── Metrics ────────────────────────────────────────────────────
+
+/// Something wrong with a route's geometry. Indexes are into `waypoints`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RouteIssue {
+    TooFewWaypoints,
+    InvalidWaypoint {
+        index: usize,
+    },
+    /// Same point as the one before it.
+    RepeatedWaypoint {
+        index: usize,
+    },
+    ZeroLength,
+    /// The segment starting at waypoint `first` crosses the one starting at
+    /// waypoint `second`.
+    SelfIntersection {
+        first: usize,
+        second: usize,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TravelTimes {
+    pub walking: u32,
+    pub driving: u32,
+    pub bus: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteMetrics {
+    pub distance_km: f64,
+    /// Whole minutes, rounded up.
+    pub minutes: TravelTimes,
+    pub issues: Vec<RouteIssue>,
+}
+
+/// Local planar coordinates in km around `origin`; good enough for
+/// intersection tests over a few hundred km.
+fn project(origin: Point, p: Point) -> (f64, f64) {
+    let mut dlng = p.lng - origin.lng;
+    // Keep routes across the antimeridian continuous
+    if dlng > 180.0 {
+        dlng -= 360.0;
+    } else if dlng < -180.0 {
+        dlng += 360.0;
+    }
+    let x = dlng.to_radians() * origin.lat.to_radians().cos() * EARTH_RADIUS_KM;
+    let y = (p.lat - origin.lat).to_radians() * EARTH_RADIUS_KM;
+    (x, y)
+}
+
+fn cross(o: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+}
+
+/// Whether segments `a1-a2` and `b1-b2` share any point.
+fn segments_intersect(a1: (f64, f64), a2: (f64, f64), b1: (f64, f64), b2: (f64, f64)) -> bool {
+    let d1 = cross(b1, b2, a1);
+    let d2 = cross(b1, b2, a2);
+    let d3 = cross(a1, a2, b1);
+    let d4 = cross(a1, a2, b2);
+    if ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+    {
+        return true;
+    }
+    let on = |p: (f64, f64), q: (f64, f64), r: (f64, f64)| {
+        r.0 >= p.0.min(q.0) && r.0 <= p.0.max(q.0) && r.1 >= p.1.min(q.1) && r.1 <= p.1.max(q.1)
+    };
+    (d1 == 0.0 && on(b1, b2, a1))
+        || (d2 == 0.0 && on(b1, b2, a2))
+        || (d3 == 0.0 && on(a1, a2, b1))
+        || (d4 == 0.0 && on(a1, a2, b2))
+}
+
+/// Crossing segments, numbered by the waypoint each starts at; `indexes`
+/// maps `points` back to the route's original waypoints.
+fn self_intersections(points: &[Point], indexes: &[usize]) -> Vec<RouteIssue> {
+    let Some(&origin) = points.first() else {
+        return Vec::new();
+    };
+    let xy: Vec<(f64, f64)> = points.iter().map(|p| project(origin, *p)).collect();
+    let segments = xy.len().saturating_sub(1);
+    let closed =
+        segments > 2 && geo::distance_km(points[0], points[segments]) < REPEAT_THRESHOLD_KM;
+    let mut issues = Vec::new();
+    for i in 0..segments {
+        // Neighbouring segments share a waypoint, so start two further on
+        for j in i + 2..segments {
+            // A loop's last segment meets its first at the start point
+            if closed && i == 0 && j == segments - 1 {
+                continue;
+            }
+            if segments_intersect(xy[i], xy[i + 1], xy[j], xy[j + 1]) {
+                issues.push(RouteIssue::SelfIntersection {
+                    first: indexes[i],
+                    second: indexes[j],
+                });
+            }
+        }
+    }
+    issues
+}
+
+/// Length, travel times and geometry problems of a route through `waypoints`.
+pub fn metrics(waypoints: &[Point], profiles: &SpeedProfiles) -> RouteMetrics {
+    let mut issues = Vec::new();
+    if waypoints.len() < 2 {
+        issues.push(RouteIssue::TooFewWaypoints);
+    }
+
+    let mut points: Vec<Point> = Vec::with_capacity(waypoints.len());
+    let mut indexes: Vec<usize> = Vec::with_capacity(waypoints.len());
+    for (index, point) in waypoints.iter().enumerate() {
+        if point.validate().is_err() {
+            issues.push(RouteIssue::InvalidWaypoint { index });
+            continue;
+        }
+        if points
+            .last()
+            .is_some_and(|last| geo::distance_km(*last, *point) < REPEAT_THRESHOLD_KM)
+        {
+            issues.push(RouteIssue::RepeatedWaypoint { index });
+            continue;
+        }
+        points.push(*point);
+        indexes.push(index);
+    }
+
+    let distance_km: f64 = points
+        .windows(2)
+        .map(|pair| geo::distance_km(pair[0], pair[1]))
+        .sum();
+    if waypoints.len() >= 2 && distance_km == 0.0 {
+        issues.push(RouteIssue::ZeroLength);
+    }
+    issues.extend(self_intersections(&points, &indexes));
+
+    let minutes = |profile: &SpeedProfile| {
+        if distance_km == 0.0 {
+            0
+        } else {
+            profile.minutes(distance_km).ceil() as u32
+        }
+    };
+    RouteMetrics {
+        // Metres are as precise as hand-placed waypoints get
+        distance_km: (distance_km * 1000.0).round() / 1000.0,
+        minutes: TravelTimes {
+            walking: minutes(&profiles.walking),
+            driving: minutes(&profiles.driving),
+            bus: minutes(&profiles.bus),
+        },
+        issues,
+    }
+}
+
+// ─── Commands ───────────────────────────────────────────────────
+
+/// Compute an evacuation route's length and travel times from its waypoints.
+#[tauri::command]
+pub fn evacuation_route_metrics(app: AppHandle, waypoints: Vec<Point>) -> Result<RouteMetrics> {
+    if waypoints.len() > MAX_WAYPOINTS {
+        return Err(Error::Invalid(format!(
+            "routes are limited to {MAX_WAYPOINTS} waypoints"
+        )));
+    }
+    Ok(metrics(&waypoints, &load_profiles(&app)))
+}
+
+#[tauri::command]
+pub fn evacuation_speed_profiles(app: AppHandle) -> SpeedProfiles {
+    load_profiles(&app)
+}
+
+#[tauri::command]
+pub fn evacuation_speed_profiles_set(app: AppHandle, profiles: SpeedProfiles) -> Result<()> {
+    profiles.validate()?;
+    save_profiles(&app, &profiles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(lat: f64, lng: f64) -> Point {
+        Point { lat, lng }
+    }
+
+    /// One degree of latitude, in km.
+    const DEGREE_KM: f64 = EARTH_RADIUS_KM * std::f64::consts::PI / 180.0;
+
+    fn crossings(metrics: &RouteMetrics) -> Vec<(usize, usize)> {
+        metrics
+            .issues
+            .iter()
+            .filter_map(|issue| match issue {
+                RouteIssue::SelfIntersection { first, second } => Some((*first, *second)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn travel_times_follow_the_profiles() {
+        let route = [
+            at(0.0, 0.0),
+            at(5.0 / DEGREE_KM, 0.0),
+            at(10.0 / DEGREE_KM, 0.0),
+        ];
+        let metrics = metrics(&route, &SpeedProfiles::default());
+        assert_eq!(metrics.distance_km, 10.0);
+        assert!(metrics.issues.is_empty());
+        // 10 km at 4 km/h; at 30 km/h plus 5 minutes; at 20 km/h plus 15
+        assert_eq!(
+            (
+                metrics.minutes.walking,
+                metrics.minutes.driving,
+                metrics.minutes.bus
+            ),
+            (150, 25, 45)
+        );
+
+        // Partial minutes round up
+        let short = [at(0.0, 0.0), at(0.1 / DEGREE_KM, 0.0)];
+        assert_eq!(
+            super::metrics(&short, &SpeedProfiles::default())
+                .minutes
+                .walking,
+            2
+        );
+    }
+
+    #[test]
+    fn bad_waypoints_are_reported_and_skipped() {
+        let profiles = SpeedProfiles::default();
+        assert_eq!(
+            metrics(&[], &profiles).issues,
+            [RouteIssue::TooFewWaypoints]
+        );
+
+        let route = [at(0.0, 0.0), at(0.0, 0.0), at(95.0, 0.0), at(0.01, 0.0)];
+        let result = metrics(&route, &profiles);
+        assert_eq!(
+            result.issues,
+            [
+                RouteIssue::RepeatedWaypoint { index: 1 },
+                RouteIssue::InvalidWaypoint { index: 2 },
+            ]
+        );
+        assert!((result.distance_km - 1.112).abs() < 0.001);
+
+        // Nowhere to go: no travel time, not even the overhead
+        let result = metrics(&[at(1.0, 1.0), at(1.0, 1.0)], &profiles);
+        assert_eq!(
+            result.issues,
+            [
+                RouteIssue::RepeatedWaypoint { index: 1 },
+                RouteIssue::ZeroLength
+            ]
+        );
+        assert_eq!(result.minutes.bus, 0);
+    }
+
+    #[test]
+    fn segments_intersect_when_they_share_a_point() {
+        let (o, a, b, c) = ((0.0, 0.0), (2.0, 2.0), (0.0, 2.0), (2.0, 0.0));
+        assert!(segments_intersect(o, a, b, c));
+        // Parallel and apart
+        assert!(!segments_intersect(o, c, b, a));
+        // Would cross if extended
+        assert!(!segments_intersect(o, (1.0, 1.0), (3.0, 0.0), (2.0, 1.5)));
+        // Touching at an end
+        assert!(segments_intersect(o, a, (1.0, 1.0), (2.0, 0.0)));
+        assert!(segments_intersect(o, a, a, (3.0, 0.0)));
+        // Collinear, overlapping and not
+        assert!(segments_intersect(o, (2.0, 0.0), (1.0, 0.0), (3.0, 0.0)));
+        assert!(!segments_intersect(o, (1.0, 0.0), (2.0, 0.0), (3.0, 0.0)));
+    }
+
+    #[test]
+    fn closed_loops_are_not_crossings() {
+        let profiles = SpeedProfiles::default();
+        let d = 1.0 / DEGREE_KM;
+        let square = [at(0.0, 0.0), at(0.0, d), at(d, d), at(d, 0.0), at(0.0, 0.0)];
+        assert!(metrics(&square, &profiles).issues.is_empty());
+        let triangle = [at(0.0, 0.0), at(0.0, d), at(d, d), at(0.0, 0.0)];
+        assert!(metrics(&triangle, &profiles).issues.is_empty());
+
+        // A bow tie crosses itself in the middle, closed or not
+        let bow_tie = [at(0.0, 0.0), at(d, d), at(d, 0.0), at(0.0, d), at(0.0, 0.0)];
+        assert_eq!(crossings(&metrics(&bow_tie, &profiles)), [(0, 2)]);
+        assert_eq!(crossings(&metrics(&bow_tie[..4], &profiles)), [(0, 2)]);
+
+        // Coming back through the start partway along is a real crossing,
+        // met by both segments that touch it
+        let back = [
+            at(0.0, 0.0),
+            at(0.0, d),
+            at(d, d),
+            at(0.0, 0.0),
+            at(-d, 0.0),
+        ];
+        assert_eq!(crossings(&metrics(&back, &profiles)), [(0, 2), (0, 3)]);
+    }
+
+    #[test]
+    fn crossings_use_the_original_waypoint_numbers() {
+        let d = 1.0 / DEGREE_KM;
+        let route = [at(0.0, 0.0), at(d, d), at(d, d), at(d, 0.0), at(0.0, d)];
+        let result = metrics(&route, &SpeedProfiles::default());
+        assert_eq!(crossings(&result), [(0, 3)]);
+    }
+
+    #[test]
+    fn routes_across_the_antimeridian_stay_short() {
+        let route = [at(-17.0, 179.99), at(-17.0, -179.99), at(-17.01, -179.98)];
+        let result = metrics(&route, &SpeedProfiles::default());
+        assert!(result.distance_km < 5.0);
+        assert!(result.issues.is_empty());
+    }
+
+    #[test]
+    fn profiles_need_positive_speeds() {
+        let mut profiles = SpeedProfiles::default();
+        assert!(profiles.validate().is_ok());
+        profiles.bus.speed_kmh = 0.0;
+        assert!(profiles.validate().is_err());
+        profiles.bus.speed_kmh = 20.0;
+        profiles.walking.overhead_minutes = f64::NAN;
+        assert!(profiles.validate().is_err());
+    }
+}
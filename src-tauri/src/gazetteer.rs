@@ -0,0 +1,818 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use osmpbf::{BlobDecode, BlobReader, Element};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::db::Db;
+use crate::error::{Error, Result};
+use crate::geo::{self, Point};
+use crate::search::fts_query;
+
+pub const IMPORTED_EVENT: &str = "gazetteer://imported";
+
+/// Places further away than this don't describe a position.
+const MAX_REVERSE_KM: f64 = 50.0;
+/// First radius reverse lookups try; doubled up to `MAX_REVERSE_KM`.
+const REVERSE_START_KM: f64 = 2.0;
+/// Closer than this, a position is simply "in" the place.
+const AT_PLACE_KM: f64 = 2.0;
+/// FTS matches ranked in Rust; more only ever adds tiny hamlets.
+const SEARCH_CANDIDATES: usize = 200;
+const MAX_SEARCH_RESULTS: usize = 50;
+/// Places written per transaction while importing, so the database is only
+/// held for a moment at a time.
+const IMPORT_BATCH: usize = 5000;
+/// GeoNames alternate names run to kilobytes for big cities.
+const MAX_ALTERNATE_NAMES: usize = 1000;
+/// OSM `place=*` values worth geocoding to.
+const OSM_PLACES: &[&str] = &[
+    "city",
+    "town",
+    "village",
+    "hamlet",
+    "suburb",
+    "quarter",
+    "neighbourhood",
+    "locality",
+    "isolated_dwelling",
+];
+
+static IMPORTING: AtomicBool = AtomicBool::new(false);
+
+struct ImportGuard;
+
+impl ImportGuard {
+    fn acquire() -> Option<Self> {
+        IMPORTING
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| ImportGuard)
+    }
+}
+
+impl Drop for ImportGuard {
+    fn drop(&mut self) {
+        IMPORTING.store(false, Ordering::Release);
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Place {
+    pub id: i64,
+    pub name: String,
+    /// `city`, `town`, `village`, `hamlet`, `suburb`, ...
+    pub kind: String,
+    /// State or province.
+    pub admin1: Option<String>,
+    /// County or district.
+    pub admin2: Option<String>,
+    /// ISO 3166 alpha-2 where the source has it.
+    pub country: Option<String>,
+    pub population: i64,
+    pub point: Point,
+}
+
+impl Place {
+    /// "Name, Admin1, CC", skipping parts that are missing or repeat.
+    pub fn label(&self) -> String {
+        let mut parts: Vec<&str> = vec![&self.name];
+        for part in [self.admin1.as_deref(), self.country.as_deref()]
+            .into_iter()
+            .flatten()
+        {
+            if !part.is_empty() && !parts.contains(&part) {
+                parts.push(part);
+            }
+        }
+        parts.join(", ")
+    }
+
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Place {
+            id: row.get("id")?,
+            name: row.get("name")?,
+            kind: row.get("kind")?,
+            admin1: row.get("admin1")?,
+            admin2: row.get("admin2")?,
+            country: row.get("country")?,
+            population: row.get("population")?,
+            point: Point {
+                lat: row.get("latitude")?,
+                lng: row.get("longitude")?,
+            },
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GazetteerStatus {
+    pub source: String,
+    /// `geonames` or `osm`.
+    pub format: String,
+    pub places: u32,
+    pub imported_at: String,
+}
+
+// ─── Import ─────────────────────────────────────────────────────
+
+struct NewPlace {
+    name: String,
+    alternate_names: String,
+    kind: String,
+    admin1: Option<String>,
+    admin2: Option<String>,
+    country: Option<String>,
+    population: i64,
+    point: Point,
+}
+
+fn non_empty(s: &str) -> Option<String> {
+    let s = s.trim();
+    (!s.is_empty()).then(|| s.to_owned())
+}
+
+/// GeoNames admin code files (`admin1CodesASCII.txt`, `admin2Codes.txt`)
+/// map `CC.code` to a name. They are optional; without them places carry
+/// no admin area.
+fn admin_codes(path: &Path) -> HashMap<String, String> {
+    let Ok(file) = File::open(path) else {
+        return HashMap::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(|line| line.ok())
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            Some((fields.next()?.to_owned(), non_empty(fields.next()?)?))
+        })
+        .collect()
+}
+
+fn geonames_kind(code: &str, population: i64) -> &'static str {
+    match code {
+        "PPLX" => "suburb",
+        "PPLL" | "PPLF" => "hamlet",
+        "PPLQ" | "PPLH" | "PPLW" => "locality",
+        _ if population >= 100_000 => "city",
+        _ if population >= 10_000 => "town",
+        _ => "village",
+    }
+}
+
+/// Populated places (feature class P) from a GeoNames dump such as
+/// `cities500.txt` or a country file, handed to `sink` as they are read.
+fn read_geonames(path: &Path, sink: &mut impl FnMut(NewPlace) -> Result<()>) -> Result<()> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let admin1 = admin_codes(&dir.join("admin1CodesASCII.txt"));
+    let admin2 = admin_codes(&dir.join("admin2Codes.txt"));
+
+    for (number, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+        let line = line.map_err(|e| Error::Invalid(format!("line {}: {e}", number + 1)))?;
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 15 {
+            return Err(Error::Invalid(format!(
+                "line {} is not a GeoNames record",
+                number + 1
+            )));
+        }
+        if fields[6] != "P" {
+            continue;
+        }
+        let (Ok(lat), Ok(lng)) = (fields[4].parse::<f64>(), fields[5].parse::<f64>()) else {
+            continue;
+        };
+        let point = Point { lat, lng };
+        let Some(name) = non_empty(fields[1]) else {
+            continue;
+        };
+        if point.validate().is_err() {
+            continue;
+        }
+        let population = fields[14].parse::<i64>().unwrap_or(0);
+        let country = fields[8];
+        let admin1_key = format!("{country}.{}", fields[10]);
+        let mut alternate_names = format!("{} {}", fields[2], fields[3].replace(',', " "));
+        if alternate_names.len() > MAX_ALTERNATE_NAMES {
+            let cut = (0..=MAX_ALTERNATE_NAMES)
+                .rev()
+                .find(|i| alternate_names.is_char_boundary(*i))
+                .unwrap_or(0);
+            alternate_names.truncate(cut);
+        }
+        sink(NewPlace {
+            name,
+            alternate_names,
+            kind: geonames_kind(fields[7], population).to_owned(),
+            admin1: admin1.get(&admin1_key).cloned(),
+            admin2: admin2.get(&format!("{admin1_key}.{}", fields[11])).cloned(),
+            country: non_empty(country),
+            population,
+            point,
+        })?;
+    }
+    Ok(())
+}
+
+fn osm_place<'a>(tags: impl Iterator<Item = (&'a str, &'a str)>, point: Point) -> Option<NewPlace> {
+    let tags: HashMap<&str, &str> = tags.collect();
+    let kind = *tags.get("place")?;
+    if !OSM_PLACES.contains(&kind) {
+        return None;
+    }
+    let name = non_empty(tags.get("name")?)?;
+    let alternate_names = [
+        "name:en",
+        "int_name",
+        "alt_name",
+        "old_name",
+        "official_name",
+    ]
+    .iter()
+    .filter_map(|key| tags.get(key))
+    .map(|v| v.replace(';', " "))
+    .collect::<Vec<_>>()
+    .join(" ");
+    let first = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| tags.get(key).and_then(|v| non_empty(v)))
+    };
+    Some(NewPlace {
+        name,
+        alternate_names,
+        kind: kind.to_owned(),
+        admin1: first(&[
+            "is_in:state",
+            "addr:state",
+            "is_in:province",
+            "addr:province",
+        ]),
+        admin2: first(&[
+            "is_in:county",
+            "addr:county",
+            "is_in:district",
+            "addr:district",
+        ]),
+        country: first(&["is_in:country_code", "addr:country", "country_code"])
+            .map(|c| c.to_uppercase()),
+        population: tags
+            .get("population")
+            .and_then(|p| p.replace([',', ' '], "").parse().ok())
+            .unwrap_or(0),
+        point,
+    })
+}
+
+/// `place=*` nodes with a name from an OpenStreetMap extract, handed to
+/// `sink` one block at a time.
+fn read_osm(path: &Path, sink: &mut impl FnMut(NewPlace) -> Result<()>) -> Result<()> {
+    let bad = |e: osmpbf::Error| Error::Invalid(format!("could not read the OSM extract: {e}"));
+    for blob in BlobReader::from_path(path).map_err(bad)? {
+        let BlobDecode::OsmData(block) = blob.map_err(bad)?.decode().map_err(bad)? else {
+            continue;
+        };
+        for element in block.elements() {
+            let place = match element {
+                Element::Node(node) => osm_place(
+                    node.tags(),
+                    Point {
+                        lat: node.lat(),
+                        lng: node.lon(),
+                    },
+                ),
+                Element::DenseNode(node) => osm_place(
+                    node.tags(),
+                    Point {
+                        lat: node.lat(),
+                        lng: node.lon(),
+                    },
+                ),
+                _ => None,
+            };
+            if let Some(place) = place.filter(|p| p.point.validate().is_ok()) {
+                sink(place)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn clear(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "DELETE FROM places;
+         DELETE FROM places_index;
+         INSERT INTO places_fts (places_fts) VALUES ('delete-all');
+         DELETE FROM gazetteer_source;",
+    )?;
+    Ok(())
+}
+
+fn insert_places(conn: &Connection, places: &[NewPlace]) -> Result<()> {
+    let mut place = conn.prepare_cached(
+        "INSERT INTO places (name, kind, admin1, admin2, country, population, latitude, longitude)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+    )?;
+    let mut index = conn.prepare_cached(
+        "INSERT INTO places_index (id, min_lat, max_lat, min_lng, max_lng)
+         VALUES (?1, ?2, ?2, ?3, ?3)",
+    )?;
+    let mut fts = conn.prepare_cached(
+        "INSERT INTO places_fts (rowid, name, alternate_names) VALUES (?1, ?2, ?3)",
+    )?;
+    for p in places {
+        place.execute(params![
+            p.name,
+            p.kind,
+            p.admin1,
+            p.admin2,
+            p.country,
+            p.population,
+            p.point.lat,
+            p.point.lng
+        ])?;
+        let id = conn.last_insert_rowid();
+        index.execute(params![id, p.point.lat, p.point.lng])?;
+        fts.execute(params![id, p.name, p.alternate_names])?;
+    }
+    Ok(())
+}
+
+/// Writes places into the gazetteer as a reader yields them, a batch per
+/// transaction, taking the database lock only for each batch so the queue,
+/// the mirror and map tiles keep working through a long import. The old
+/// gazetteer goes with the first batch, so a file without places leaves it
+/// alone.
+struct Loader<'a> {
+    db: &'a Db,
+    batch: Vec<NewPlace>,
+    stored: u32,
+}
+
+impl<'a> Loader<'a> {
+    fn new(db: &'a Db) -> Self {
+        Self {
+            db,
+            batch: Vec::with_capacity(IMPORT_BATCH),
+            stored: 0,
+        }
+    }
+
+    fn push(&mut self, place: NewPlace) -> Result<()> {
+        self.batch.push(place);
+        if self.batch.len() >= IMPORT_BATCH {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+        let first = self.stored == 0;
+        self.db.with(|conn| {
+            let tx = conn.transaction()?;
+            if first {
+                clear(&tx)?;
+            }
+            insert_places(&tx, &self.batch)?;
+            tx.commit()?;
+            Ok(())
+        })?;
+        self.stored += self.batch.len() as u32;
+        self.batch.clear();
+        Ok(())
+    }
+
+    /// Write what is left and record where the places came from.
+    fn finish(&mut self, source: String, format: &str) -> Result<GazetteerStatus> {
+        self.flush()?;
+        if self.stored == 0 {
+            return Err(Error::Invalid("the file contains no named places".into()));
+        }
+        let status = GazetteerStatus {
+            source,
+            format: format.into(),
+            places: self.stored,
+            imported_at: chrono::Utc::now().to_rfc3339(),
+        };
+        self.db.with(|conn| {
+            conn.execute(
+                "INSERT INTO gazetteer_source (id, source, format, places, imported_at)
+                 VALUES (1, ?1, ?2, ?3, ?4)",
+                params![
+                    status.source,
+                    status.format,
+                    status.places,
+                    status.imported_at
+                ],
+            )?;
+            Ok(())
+        })?;
+        Ok(status)
+    }
+}
+
+fn status(conn: &Connection) -> Result<Option<GazetteerStatus>> {
+    Ok(conn
+        .query_row(
+            "SELECT source, format, places, imported_at FROM gazetteer_source WHERE id = 1",
+            [],
+            |row| {
+                Ok(GazetteerStatus {
+                    source: row.get(0)?,
+                    format: row.get(1)?,
+                    places: row.get(2)?,
+                    imported_at: row.get(3)?,
+                })
+            },
+        )
+        .optional()?)
+}
+
+// ─── Reverse ────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReverseHit {
+    pub place: Place,
+    pub distance_km: f64,
+    /// Ready to use as a `location_name`.
+    pub label: String,
+}
+
+fn compass(from: Point, to: Point) -> &'static str {
+    let (lat1, lat2) = (from.lat.to_radians(), to.lat.to_radians());
+    let dlng = (to.lng - from.lng).to_radians();
+    let y = dlng.sin() * lat2.cos();
+    let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlng.cos();
+    let bearing = y.atan2(x).to_degrees().rem_euclid(360.0);
+    const POINTS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+    POINTS[((bearing + 22.5) / 45.0) as usize % 8]
+}
+
+/// The named place nearest to `point`, within `MAX_REVERSE_KM`.
+pub fn reverse(conn: &Connection, point: Point) -> Result<Option<ReverseHit>> {
+    point.validate()?;
+    let mut stmt = conn.prepare_cached(
+        "SELECT p.* FROM places_index i JOIN places p ON p.id = i.id
+         WHERE i.min_lat <= ?1 AND i.max_lat >= ?2 AND i.min_lng <= ?3 AND i.max_lng >= ?4",
+    )?;
+    let mut radius = REVERSE_START_KM;
+    loop {
+        let mut best: Option<(f64, Place)> = None;
+        for b in geo::radius_boxes(point, radius) {
+            let rows =
+                stmt.query_map(params![b.north, b.south, b.east, b.west], Place::from_row)?;
+            for place in rows {
+                let place = place?;
+                let distance = geo::distance_km(point, place.point);
+                if distance <= radius && !best.as_ref().is_some_and(|(d, _)| distance >= *d) {
+                    best = Some((distance, place));
+                }
+            }
+        }
+        if let Some((distance_km, place)) = best {
+            let label = if distance_km < AT_PLACE_KM {
+                place.label()
+            } else {
+                format!(
+                    "{:.0} km {} of {}",
+                    distance_km.max(1.0),
+                    compass(place.point, point),
+                    place.label()
+                )
+            };
+            return Ok(Some(ReverseHit {
+                place,
+                distance_km,
+                label,
+            }));
+        }
+        if radius >= MAX_REVERSE_KM {
+            return Ok(None);
+        }
+        radius = (radius * 2.0).min(MAX_REVERSE_KM);
+    }
+}
+
+/// A `location_name` for a record that has coordinates but no name.
+pub fn describe(conn: &Connection, point: Point) -> Result<Option<String>> {
+    Ok(reverse(conn, point)?.map(|hit| hit.label))
+}
+
+/// Fill in a missing `location_name` on a record with coordinates. Returns
+/// whether it changed.
+pub fn fill_location_name(conn: &Connection, record: &mut Value) -> Result<bool> {
+    let named = record
+        .get("location_name")
+        .and_then(Value::as_str)
+        .is_some_and(|name| !name.trim().is_empty());
+    let Some(point) = Point::from_record(record).filter(|_| !named) else {
+        return Ok(false);
+    };
+    let (Some(label), Some(object)) = (describe(conn, point)?, record.as_object_mut()) else {
+        return Ok(false);
+    };
+    object.insert("location_name".into(), Value::String(label));
+    Ok(true)
+}
+
+// ─── Forward ────────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeocodeQuery {
+    pub text: String,
+    /// Prefer places close to here, e.g. the map centre.
+    pub near: Option<Point>,
+    pub limit: Option<usize>,
+}
+
+/// Places whose name starts with what was typed, bigger and closer ones
+/// first.
+pub fn search(conn: &Connection, query: &GeocodeQuery) -> Result<Vec<Place>> {
+    let Some(matcher) = fts_query(&query.text) else {
+        return Ok(Vec::new());
+    };
+    let mut stmt = conn.prepare_cached(
+        "SELECT p.*, bm25(places_fts, 10.0, 1.0) AS rank
+         FROM places_fts JOIN places p ON p.id = places_fts.rowid
+         WHERE places_fts MATCH ?1
+         ORDER BY rank
+         LIMIT ?2",
+    )?;
+    let rows = stmt.query_map(params![matcher, SEARCH_CANDIDATES], |row| {
+        Ok((Place::from_row(row)?, row.get::<_, f64>("rank")?))
+    })?;
+
+    let typed = query.text.trim().to_lowercase();
+    let mut scored: Vec<(f64, Place)> = Vec::new();
+    for row in rows {
+        let (place, rank) = row?;
+        let name = place.name.to_lowercase();
+        // bm25 is lower-is-better and negative
+        let mut score = -rank + (place.population.max(0) as f64 + 1.0).log10();
+        if name == typed {
+            score += 3.0;
+        } else if name.starts_with(&typed) {
+            score += 1.5;
+        }
+        if let Some(near) = query.near {
+            score -= (geo::distance_km(near, place.point) / 200.0).min(3.0);
+        }
+        scored.push((score, place));
+    }
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored.truncate(query.limit.unwrap_or(10).min(MAX_SEARCH_RESULTS));
+    Ok(scored.into_iter().map(|(_, place)| place).collect())
+}
+
+// ─── Commands ───────────────────────────────────────────────────
+
+/// Load a GeoNames dump (`.txt`) or the places of an OpenStreetMap extract
+/// (`.osm.pbf`), replacing the current gazetteer.
+#[tauri::command]
+pub async fn gazetteer_import(app: AppHandle, path: String) -> Result<GazetteerStatus> {
+    let Some(_guard) = ImportGuard::acquire() else {
+        return Err(Error::Invalid(
+            "a gazetteer import is already running".into(),
+        ));
+    };
+    let source = PathBuf::from(path);
+    let handle = app.clone();
+
+    let status = tauri::async_runtime::spawn_blocking(move || -> Result<_> {
+        let name = source
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let lower = name.to_lowercase();
+        let db = handle.state::<Db>();
+        let mut loader = Loader::new(&db);
+        let mut sink = |place| loader.push(place);
+        let read = if lower.ends_with(".pbf") {
+            read_osm(&source, &mut sink).map(|_| "osm")
+        } else if lower.ends_with(".txt") || lower.ends_with(".tsv") {
+            read_geonames(&source, &mut sink).map(|_| "geonames")
+        } else if lower.ends_with(".zip") {
+            return Err(Error::Invalid("unzip the GeoNames dump first".into()));
+        } else {
+            return Err(Error::Invalid(
+                "choose a GeoNames .txt dump or an .osm.pbf extract".into(),
+            ));
+        };
+        let result = read.and_then(|format| loader.finish(name, format));
+        if result.is_err() && loader.stored > 0 {
+            // Don't leave half a gazetteer behind
+            db.with(|conn| clear(conn))?;
+        }
+        result
+    })
+    .await??;
+
+    eprintln!(
+        "[gazetteer] Imported {} places from {}",
+        status.places, status.source
+    );
+    let _ = app.emit(IMPORTED_EVENT, &status);
+    Ok(status)
+}
+
+#[tauri::command]
+pub fn gazetteer_status(db: State<'_, Db>) -> Result<Option<GazetteerStatus>> {
+    db.with(|conn| status(conn))
+}
+
+#[tauri::command]
+pub fn gazetteer_clear(db: State<'_, Db>) -> Result<()> {
+    db.with(|conn| clear(conn))
+}
+
+/// Nearest named place and admin area for a position.
+#[tauri::command]
+pub fn geocode_reverse(db: State<'_, Db>, point: Point) -> Result<Option<ReverseHit>> {
+    db.with(|conn| reverse(conn, point))
+}
+
+/// Autocomplete place names.
+#[tauri::command]
+pub fn geocode_search(db: State<'_, Db>, query: GeocodeQuery) -> Result<Vec<Place>> {
+    db.with(|conn| search(conn, &query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        for migration in crate::migrations::MIGRATIONS {
+            conn.execute_batch(migration.sql).unwrap();
+        }
+        conn
+    }
+
+    fn place(name: &str, population: i64, lat: f64, lng: f64) -> NewPlace {
+        NewPlace {
+            name: name.into(),
+            alternate_names: String::new(),
+            kind: geonames_kind("PPL", population).into(),
+            admin1: Some("Queensland".into()),
+            admin2: None,
+            country: Some("AU".into()),
+            population,
+            point: Point { lat, lng },
+        }
+    }
+
+    fn at(lat: f64, lng: f64) -> Point {
+        Point { lat, lng }
+    }
+
+    fn gazetteer() -> Connection {
+        let conn = db();
+        insert_places(
+            &conn,
+            &[
+                place("Brisbane", 2_000_000, -27.4698, 153.0251),
+                place("Bribie Island", 20_000, -27.0667, 153.1667),
+                place("Ipswich", 230_000, -27.6167, 152.7667),
+                place("Suva", 90_000, -18.1416, 178.4419),
+                place("Taveuni", 9_000, -16.85, -179.95),
+            ],
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn reverse_names_the_place_or_where_from_it() {
+        let conn = gazetteer();
+        let hit = reverse(&conn, at(-27.47, 153.03)).unwrap().unwrap();
+        assert_eq!(hit.label, "Brisbane, Queensland, AU");
+
+        // Out past the first radius, so the search has to widen
+        let hit = reverse(&conn, at(-27.62, 152.85)).unwrap().unwrap();
+        assert_eq!(hit.place.name, "Ipswich");
+        assert!(
+            hit.label.ends_with("km E of Ipswich, Queensland, AU"),
+            "{}",
+            hit.label
+        );
+    }
+
+    #[test]
+    fn reverse_gives_up_past_the_limit() {
+        let conn = gazetteer();
+        assert!(reverse(&conn, at(-25.0, 150.0)).unwrap().is_none());
+        assert!(reverse(&conn, at(-95.0, 0.0)).is_err());
+    }
+
+    #[test]
+    fn reverse_looks_across_the_antimeridian() {
+        let conn = gazetteer();
+        let hit = reverse(&conn, at(-16.85, 179.98)).unwrap().unwrap();
+        assert_eq!(hit.place.name, "Taveuni");
+    }
+
+    #[test]
+    fn describe_fills_only_missing_names() {
+        let conn = gazetteer();
+        let mut record = serde_json::json!({"latitude": -27.47, "longitude": 153.03});
+        assert!(fill_location_name(&conn, &mut record).unwrap());
+        assert_eq!(record["location_name"], "Brisbane, Queensland, AU");
+
+        let mut named = serde_json::json!({
+            "latitude": -27.47, "longitude": 153.03, "location_name": "Depot 4"
+        });
+        assert!(!fill_location_name(&conn, &mut named).unwrap());
+    }
+
+    fn names(conn: &Connection, text: &str, near: Option<Point>) -> Vec<String> {
+        let query = GeocodeQuery {
+            text: text.into(),
+            near,
+            limit: None,
+        };
+        search(conn, &query)
+            .unwrap()
+            .into_iter()
+            .map(|p| p.name)
+            .collect()
+    }
+
+    #[test]
+    fn search_matches_prefixes_bigger_places_first() {
+        let conn = gazetteer();
+        assert_eq!(names(&conn, "bri", None), ["Brisbane", "Bribie Island"]);
+        assert_eq!(names(&conn, "bribie isl", None), ["Bribie Island"]);
+        assert!(names(&conn, "  ", None).is_empty());
+        // FTS syntax is taken literally
+        assert!(names(&conn, "bri OR \"ips", None).is_empty());
+    }
+
+    #[test]
+    fn search_prefers_places_near_the_map() {
+        let conn = db();
+        insert_places(
+            &conn,
+            &[
+                place("Springfield", 12_000, 39.80, -89.64),
+                place("Springfield", 10_000, -27.68, 152.91),
+            ],
+        )
+        .unwrap();
+        let near = Some(at(-27.47, 153.03));
+        let found = search(
+            &conn,
+            &GeocodeQuery {
+                text: "springfield".into(),
+                near,
+                limit: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(found.len(), 2);
+        assert!(found[0].point.lat < 0.0);
+    }
+
+    #[test]
+    fn geonames_rows_stream_to_the_sink() {
+        let dir = std::env::temp_dir().join(format!("gazetteer-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("AU.txt");
+        let row = |name: &str, class: &str, code: &str, population: u32| {
+            format!(
+                "1\t{name}\t{name}\tAlt,Other\t-27.5\t153.0\t{class}\t{code}\tAU\t\t04\t\t\t\t{population}\n"
+            )
+        };
+        std::fs::write(
+            &path,
+            row("Brisbane", "P", "PPLA", 2_000_000) + &row("Mount Coot-tha", "T", "MT", 0),
+        )
+        .unwrap();
+        std::fs::write(dir.join("admin1CodesASCII.txt"), "AU.04\tQueensland\n").unwrap();
+
+        let mut places = Vec::new();
+        read_geonames(&path, &mut |p| {
+            places.push(p);
+            Ok(())
+        })
+        .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(places.len(), 1);
+        assert_eq!(places[0].kind, "city");
+        assert_eq!(places[0].admin1.as_deref(), Some("Queensland"));
+        assert_eq!(places[0].alternate_names, "Brisbane Alt Other");
+    }
+}
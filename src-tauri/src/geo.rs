@@ -0,0 +1,706 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::State;
+
+use crate::db::Db;
+use crate::error::{Error, Result};
+use crate::mirror;
+use crate::tiles::BoundingBox;
+
+/// Mean Earth radius (IUGG), in km.
+pub const EARTH_RADIUS_KM: f64 = 6371.0088;
+/// Nothing on the globe is further away than this.
+const HALF_CIRCUMFERENCE_KM: f64 = PI * EARTH_RADIUS_KM;
+/// First radius `nearest` tries; doubled until enough records turn up.
+const NEAREST_START_KM: f64 = 5.0;
+const MAX_RESULTS: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Point {
+    pub lat: f64,
+    pub lng: f64,
+}
+
+impl Point {
+    pub fn validate(&self) -> Result<()> {
+        if !(-90.0..=90.0).contains(&self.lat) || !(-180.0..=180.0).contains(&self.lng) {
+            return Err(Error::Invalid(format!(
+                "{}, {} is not a valid position",
+                self.lat, self.lng
+            )));
+        }
+        Ok(())
+    }
+
+    /// `latitude`/`longitude` of a mirrored record, if it has a position.
+    pub fn from_record(record: &Value) -> Option<Point> {
+        let point = Point {
+            lat: record.get("latitude")?.as_f64()?,
+            lng: record.get("longitude")?.as_f64()?,
+        };
+        point.validate().ok().map(|_| point)
+    }
+}
+
+/// Great-circle distance in km (haversine on the mean sphere; within 0.5% of
+/// the ellipsoidal distance, which is plenty for dispatch).
+pub fn distance_km(a: Point, b: Point) -> f64 {
+    let (lat1, lat2) = (a.lat.to_radians(), b.lat.to_radians());
+    let dlat = lat2 - lat1;
+    let dlng = (b.lng - a.lng).to_radians();
+    let h = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlng / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * h.sqrt().min(1.0).asin()
+}
+
+/// Boxes that together cover every point within `radius_km` of `center`;
+/// two when the circle crosses the antimeridian.
+pub fn radius_boxes(center: Point, radius_km: f64) -> Vec<BoundingBox> {
+    let angle = radius_km / EARTH_RADIUS_KM;
+    let dlat = angle.to_degrees();
+    let south = center.lat - dlat;
+    let north = center.lat + dlat;
+    let whole_world = |south: f64, north: f64| BoundingBox {
+        west: -180.0,
+        south: south.max(-90.0),
+        east: 180.0,
+        north: north.min(90.0),
+    };
+    // Circles over a pole take in every longitude
+    if north >= 90.0 || south <= -90.0 || angle >= PI / 2.0 {
+        return vec![whole_world(south, north)];
+    }
+    let ratio = angle.sin() / center.lat.to_radians().cos();
+    if ratio >= 1.0 {
+        return vec![whole_world(south, north)];
+    }
+    let dlng = ratio.asin().to_degrees();
+    let (west, east) = (center.lng - dlng, center.lng + dlng);
+    if west < -180.0 {
+        vec![
+            BoundingBox {
+                west: west + 360.0,
+                south,
+                east: 180.0,
+                north,
+            },
+            BoundingBox {
+                west: -180.0,
+                south,
+                east,
+                north,
+            },
+        ]
+    } else if east > 180.0 {
+        vec![
+            BoundingBox {
+                west,
+                south,
+                east: 180.0,
+                north,
+            },
+            BoundingBox {
+                west: -180.0,
+                south,
+                east: east - 360.0,
+                north,
+            },
+        ]
+    } else {
+        vec![BoundingBox {
+            west,
+            south,
+            east,
+            north,
+        }]
+    }
+}
+
+/// Records with a position on the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GeoEntity {
+    Incident,
+    Resource,
+    /// Teams have no position of their own; they are where their current
+    /// incident is.
+    Team,
+    Sos,
+}
+
+impl GeoEntity {
+    fn table(self) -> &'static str {
+        match self {
+            GeoEntity::Incident => "incidents",
+            GeoEntity::Resource => "resources",
+            GeoEntity::Team => "teams",
+            GeoEntity::Sos => "sos_broadcasts",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeoHit {
+    pub entity: GeoEntity,
+    pub id: String,
+    pub point: Point,
+    /// From the query center; `None` for bounding-box queries.
+    pub distance_km: Option<f64>,
+    pub record: Value,
+}
+
+/// Column equality on the record, like `MirrorQuery::eq`; `null` and empty
+/// strings are ignored.
+fn matches(record: &Value, eq: &Map<String, Value>) -> bool {
+    eq.iter().all(|(column, value)| match value {
+        Value::Null => true,
+        Value::String(s) if s.is_empty() => true,
+        _ => record.get(column) == Some(value),
+    })
+}
+
+// ─── Index ──────────────────────────────────────────────────────
+
+/// Records of `table` whose indexed position falls in one of `boxes`.
+fn indexed(conn: &Connection, table: &str, boxes: &[BoundingBox]) -> Result<Vec<Value>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT m.data FROM geo_index g
+         JOIN mirror_records m ON m.rowid = g.id
+         WHERE m.table_name = ?1
+           AND g.min_lat <= ?2 AND g.max_lat >= ?3
+           AND g.min_lng <= ?4 AND g.max_lng >= ?5",
+    )?;
+    let mut records = Vec::new();
+    for b in boxes {
+        let rows = stmt.query_map(params![table, b.north, b.south, b.east, b.west], |row| {
+            row.get::<_, Value>(0)
+        })?;
+        for row in rows {
+            records.push(row?);
+        }
+    }
+    Ok(records)
+}
+
+/// Positioned records of `entity` inside `boxes`. The index stores 32-bit
+/// floats rounded outwards, so callers still check exact positions.
+fn candidates(
+    conn: &Connection,
+    entity: GeoEntity,
+    boxes: &[BoundingBox],
+) -> Result<Vec<(Point, Value)>> {
+    if entity != GeoEntity::Team {
+        return Ok(indexed(conn, entity.table(), boxes)?
+            .into_iter()
+            .filter_map(|record| Some((Point::from_record(&record)?, record)))
+            .collect());
+    }
+
+    let incidents: HashMap<String, Point> = indexed(conn, "incidents", boxes)?
+        .iter()
+        .filter_map(|incident| {
+            let id = incident.get("id")?.as_str()?.to_owned();
+            Some((id, Point::from_record(incident)?))
+        })
+        .collect();
+    if incidents.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(mirror::all(conn, "teams")?
+        .into_iter()
+        .filter_map(|team| {
+            let incident = team.get("current_incident_id")?.as_str()?;
+            Some((*incidents.get(incident)?, team))
+        })
+        .collect())
+}
+
+fn hit(entity: GeoEntity, point: Point, distance_km: Option<f64>, record: Value) -> Option<GeoHit> {
+    Some(GeoHit {
+        entity,
+        id: record.get("id")?.as_str()?.to_owned(),
+        point,
+        distance_km,
+        record,
+    })
+}
+
+fn by_distance(hits: &mut [GeoHit]) {
+    hits.sort_by(|a, b| {
+        a.distance_km
+            .unwrap_or_default()
+            .total_cmp(&b.distance_km.unwrap_or_default())
+    });
+}
+
+/// Records of `entity` within `radius_km` of `center`, nearest first.
+pub fn within_radius(
+    conn: &Connection,
+    entity: GeoEntity,
+    center: Point,
+    radius_km: f64,
+    eq: &Map<String, Value>,
+) -> Result<Vec<GeoHit>> {
+    let mut hits: Vec<GeoHit> = candidates(conn, entity, &radius_boxes(center, radius_km))?
+        .into_iter()
+        .filter(|(_, record)| matches(record, eq))
+        .filter_map(|(point, record)| {
+            let distance = distance_km(center, point);
+            if distance > radius_km {
+                return None;
+            }
+            hit(entity, point, Some(distance), record)
+        })
+        .collect();
+    by_distance(&mut hits);
+    Ok(hits)
+}
+
+/// Records of `entity` inside `area`.
+pub fn within_bounds(
+    conn: &Connection,
+    entity: GeoEntity,
+    area: &BoundingBox,
+    eq: &Map<String, Value>,
+) -> Result<Vec<GeoHit>> {
+    let inside = |p: Point| {
+        (area.south..=area.north).contains(&p.lat) && (area.west..=area.east).contains(&p.lng)
+    };
+    Ok(candidates(conn, entity, std::slice::from_ref(area))?
+        .into_iter()
+        .filter(|(point, record)| inside(*point) && matches(record, eq))
+        .filter_map(|(point, record)| hit(entity, point, None, record))
+        .collect())
+}
+
+/// The `k` records of `entity` closest to `center`, no further than
+/// `max_km`. Searches a growing radius so the index does the work.
+pub fn nearest(
+    conn: &Connection,
+    entity: GeoEntity,
+    center: Point,
+    k: usize,
+    max_km: f64,
+    eq: &Map<String, Value>,
+) -> Result<Vec<GeoHit>> {
+    let max_km = max_km.min(HALF_CIRCUMFERENCE_KM);
+    let mut radius = NEAREST_START_KM.min(max_km);
+    loop {
+        let mut hits = within_radius(conn, entity, center, radius, eq)?;
+        if hits.len() >= k || radius >= max_km {
+            hits.truncate(k);
+            return Ok(hits);
+        }
+        radius = (radius * 2.0).min(max_km);
+    }
+}
+
+// ─── Commands ───────────────────────────────────────────────────
+
+/// Where a query is centered: a position, or an incident's location.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Origin {
+    pub center: Option<Point>,
+    pub incident_id: Option<String>,
+}
+
+impl Origin {
+    /// The center point and, for incidents, the incident itself.
+    fn resolve(&self, conn: &Connection) -> Result<(Point, Option<Value>)> {
+        if let Some(id) = &self.incident_id {
+            let incident = mirror::get(conn, "incidents", id)?
+                .ok_or_else(|| Error::NotFound(format!("incident {id}")))?;
+            let point = Point::from_record(&incident)
+                .ok_or_else(|| Error::Invalid("the incident has no location".into()))?;
+            return Ok((point, Some(incident)));
+        }
+        let center = self
+            .center
+            .ok_or_else(|| Error::Invalid("a query needs a center or an incident".into()))?;
+        center.validate()?;
+        Ok((center, None))
+    }
+
+    /// Leave the center incident out of its own results.
+    fn exclude(&self, entity: GeoEntity, hits: &mut Vec<GeoHit>) {
+        if let (GeoEntity::Incident, Some(id)) = (entity, &self.incident_id) {
+            hits.retain(|hit| &hit.id != id);
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RadiusQuery {
+    pub entity: GeoEntity,
+    #[serde(flatten)]
+    pub origin: Origin,
+    /// Defaults to the incident's `affected_radius_km`.
+    pub radius_km: Option<f64>,
+    #[serde(default)]
+    pub eq: Map<String, Value>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoundsQuery {
+    pub entity: GeoEntity,
+    pub bounds: BoundingBox,
+    #[serde(default)]
+    pub eq: Map<String, Value>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NearestQuery {
+    pub entity: GeoEntity,
+    #[serde(flatten)]
+    pub origin: Origin,
+    pub k: usize,
+    pub max_distance_km: Option<f64>,
+    #[serde(default)]
+    pub eq: Map<String, Value>,
+}
+
+fn limit(limit: Option<usize>) -> usize {
+    limit.unwrap_or(MAX_RESULTS).min(MAX_RESULTS)
+}
+
+/// Distance in km between two positions.
+#[tauri::command]
+pub fn geo_distance(from: Point, to: Point) -> Result<f64> {
+    from.validate()?;
+    to.validate()?;
+    Ok(distance_km(from, to))
+}
+
+/// Cached records within a radius, nearest first; e.g. SOS broadcasts inside
+/// an incident's affected area.
+#[tauri::command]
+pub fn geo_within_radius(db: State<'_, Db>, query: RadiusQuery) -> Result<Vec<GeoHit>> {
+    db.with(|conn| {
+        let (center, incident) = query.origin.resolve(conn)?;
+        let radius_km = query
+            .radius_km
+            .or_else(|| incident?.get("affected_radius_km")?.as_f64())
+            .ok_or_else(|| Error::Invalid("a radius query needs a radius".into()))?;
+        if !radius_km.is_finite() || radius_km < 0.0 {
+            return Err(Error::Invalid("radius must be a positive distance".into()));
+        }
+        let mut hits = within_radius(conn, query.entity, center, radius_km, &query.eq)?;
+        query.origin.exclude(query.entity, &mut hits);
+        hits.truncate(limit(query.limit));
+        Ok(hits)
+    })
+}
+
+#[tauri::command]
+pub fn geo_within_bounds(db: State<'_, Db>, query: BoundsQuery) -> Result<Vec<GeoHit>> {
+    query.bounds.validate()?;
+    db.with(|conn| {
+        let mut hits = within_bounds(conn, query.entity, &query.bounds, &query.eq)?;
+        hits.truncate(limit(query.limit));
+        Ok(hits)
+    })
+}
+
+/// The closest cached records; e.g. the five nearest available resources.
+#[tauri::command]
+pub fn geo_nearest(db: State<'_, Db>, query: NearestQuery) -> Result<Vec<GeoHit>> {
+    let max_km = query.max_distance_km.unwrap_or(HALF_CIRCUMFERENCE_KM);
+    if !max_km.is_finite() || max_km < 0.0 {
+        return Err(Error::Invalid("maximum distance must be positive".into()));
+    }
+    db.with(|conn| {
+        let (center, _) = query.origin.resolve(conn)?;
+        // One extra in case the center incident comes back
+        let k = limit(Some(query.k));
+        let mut hits = nearest(conn, query.entity, center, k + 1, max_km, &query.eq)?;
+        query.origin.exclude(query.entity, &mut hits);
+        hits.truncate(k);
+        Ok(hits)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        for migration in crate::migrations::MIGRATIONS {
+            conn.execute_batch(migration.sql).unwrap();
+        }
+        conn
+    }
+
+    fn add(conn: &Connection, table: &str, record: Value) {
+        conn.execute(
+            "INSERT INTO mirror_records (table_name, id, data) VALUES (?1, ?2, ?3)",
+            params![table, record["id"].as_str().unwrap(), record],
+        )
+        .unwrap();
+    }
+
+    fn at(lat: f64, lng: f64) -> Point {
+        Point { lat, lng }
+    }
+
+    /// The point `km` east of `from` along its parallel.
+    fn east_of(from: Point, km: f64) -> Point {
+        let dlng = (km / EARTH_RADIUS_KM).to_degrees() / from.lat.to_radians().cos();
+        at(from.lat, from.lng + dlng)
+    }
+
+    fn ids(hits: &[GeoHit]) -> Vec<&str> {
+        hits.iter().map(|h| h.id.as_str()).collect()
+    }
+
+    #[test]
+    fn distances_on_the_sphere() {
+        let sydney = at(-33.8688, 151.2093);
+        let melbourne = at(-37.8136, 144.9631);
+        assert!((distance_km(sydney, melbourne) - 713.4).abs() < 2.0);
+        assert_eq!(distance_km(sydney, sydney), 0.0);
+        // Across the antimeridian is the short way round
+        assert!((distance_km(at(0.0, 179.5), at(0.0, -179.5)) - 111.2).abs() < 0.1);
+        let antipode = distance_km(at(10.0, 20.0), at(-10.0, -160.0));
+        assert!((antipode - HALF_CIRCUMFERENCE_KM).abs() < 0.01);
+    }
+
+    #[test]
+    fn radius_boxes_split_at_the_antimeridian() {
+        let boxes = radius_boxes(at(-17.0, 179.9), 50.0);
+        assert_eq!(boxes.len(), 2);
+        assert_eq!((boxes[0].east, boxes[1].west), (180.0, -180.0));
+        assert!(boxes[0].west > 179.0 && boxes[1].east < -179.0);
+
+        let boxes = radius_boxes(at(-17.0, -179.9), 50.0);
+        assert_eq!(boxes.len(), 2);
+        assert_eq!((boxes[0].east, boxes[1].west), (180.0, -180.0));
+        assert!(boxes[0].west > 179.0 && boxes[1].east < -179.0);
+
+        let boxes = radius_boxes(at(-17.0, 170.0), 50.0);
+        assert_eq!(boxes.len(), 1);
+        assert!(boxes[0].west < 170.0 && boxes[0].east > 170.0);
+        // Every point on the circle is inside
+        for bearing in 0..36 {
+            let theta = f64::from(bearing * 10).to_radians();
+            let angle = 49.9 / EARTH_RADIUS_KM;
+            let lat = (-17f64).to_radians();
+            let p_lat = (lat.sin() * angle.cos() + lat.cos() * angle.sin() * theta.cos()).asin();
+            let dlng = (theta.sin() * angle.sin() * lat.cos())
+                .atan2(angle.cos() - lat.sin() * p_lat.sin());
+            let p = at(p_lat.to_degrees(), 170.0 + dlng.to_degrees());
+            let b = &boxes[0];
+            assert!((b.south..=b.north).contains(&p.lat) && (b.west..=b.east).contains(&p.lng));
+        }
+    }
+
+    #[test]
+    fn radius_boxes_take_in_every_longitude_over_a_pole() {
+        for center in [at(89.9, 0.0), at(-89.9, 120.0)] {
+            let boxes = radius_boxes(center, 50.0);
+            assert_eq!(boxes.len(), 1);
+            let b = &boxes[0];
+            assert_eq!((b.west, b.east), (-180.0, 180.0));
+            assert!(b.north <= 90.0 && b.south >= -90.0);
+        }
+        // Short of the pole the box still widens fast: at 88° a 200 km
+        // circle spans 128° of longitude
+        let b = &radius_boxes(at(88.0, 0.0), 200.0)[0];
+        assert!((b.east - 64.07).abs() < 0.01 && (b.west + 64.07).abs() < 0.01);
+        assert!(b.north < 90.0);
+        // A quarter of the globe or more is the whole map
+        let b = &radius_boxes(at(0.0, 0.0), 20_000.0)[0];
+        assert_eq!(
+            (b.west, b.south, b.east, b.north),
+            (-180.0, -90.0, 180.0, 90.0)
+        );
+    }
+
+    #[test]
+    fn radius_queries_reach_across_the_antimeridian() {
+        let conn = db();
+        add(
+            &conn,
+            "incidents",
+            json!({"id": "east", "latitude": -17.0, "longitude": 179.95}),
+        );
+        add(
+            &conn,
+            "incidents",
+            json!({"id": "west", "latitude": -17.0, "longitude": -179.95}),
+        );
+        add(
+            &conn,
+            "incidents",
+            json!({"id": "far", "latitude": -17.0, "longitude": -179.0}),
+        );
+
+        let hits = within_radius(
+            &conn,
+            GeoEntity::Incident,
+            at(-17.0, -179.99),
+            20.0,
+            &Map::new(),
+        )
+        .unwrap();
+        assert_eq!(ids(&hits), ["west", "east"]);
+        assert!(hits[0].distance_km.unwrap() < hits[1].distance_km.unwrap());
+    }
+
+    #[test]
+    fn nearest_widens_the_search_until_it_has_enough() {
+        let conn = db();
+        let center = at(-27.47, 153.02);
+        for (id, km, status) in [
+            ("near", 2.0, "available"),
+            ("town", 30.0, "deployed"),
+            ("city", 300.0, "available"),
+            ("state", 1500.0, "available"),
+        ] {
+            let p = east_of(center, km);
+            add(
+                &conn,
+                "resources",
+                json!({"id": id, "latitude": p.lat, "longitude": p.lng, "status": status}),
+            );
+        }
+        let none = Map::new();
+        let nearest = |k, max_km, eq: &Map<String, Value>| {
+            ids(&nearest(&conn, GeoEntity::Resource, center, k, max_km, eq).unwrap())
+                .into_iter()
+                .map(str::to_owned)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(nearest(1, 5000.0, &none), ["near"]);
+        assert_eq!(nearest(3, 5000.0, &none), ["near", "town", "city"]);
+        assert_eq!(
+            nearest(10, 5000.0, &none),
+            ["near", "town", "city", "state"]
+        );
+        // Never beyond the maximum, however few turn up
+        assert_eq!(nearest(10, 100.0, &none), ["near", "town"]);
+        assert!(nearest(1, 1.0, &none).is_empty());
+
+        let available = json!({"status": "available"}).as_object().unwrap().clone();
+        assert_eq!(nearest(2, 5000.0, &available), ["near", "city"]);
+    }
+
+    #[test]
+    fn teams_are_where_their_incident_is() {
+        let conn = db();
+        add(
+            &conn,
+            "incidents",
+            json!({"id": "flood", "latitude": -27.47, "longitude": 153.02}),
+        );
+        add(
+            &conn,
+            "incidents",
+            json!({"id": "fire", "latitude": -33.87, "longitude": 151.21}),
+        );
+        add(
+            &conn,
+            "teams",
+            json!({"id": "swift", "current_incident_id": "flood"}),
+        );
+        add(
+            &conn,
+            "teams",
+            json!({"id": "rural", "current_incident_id": "fire"}),
+        );
+        add(
+            &conn,
+            "teams",
+            json!({"id": "idle", "current_incident_id": null}),
+        );
+        add(
+            &conn,
+            "teams",
+            json!({"id": "lost", "current_incident_id": "gone"}),
+        );
+
+        let hits =
+            within_radius(&conn, GeoEntity::Team, at(-27.5, 153.0), 50.0, &Map::new()).unwrap();
+        assert_eq!(ids(&hits), ["swift"]);
+        assert_eq!(hits[0].point, at(-27.47, 153.02));
+
+        let australia = BoundingBox {
+            west: 110.0,
+            south: -45.0,
+            east: 155.0,
+            north: -10.0,
+        };
+        let mut all = ids(&within_bounds(&conn, GeoEntity::Team, &australia, &Map::new()).unwrap())
+            .into_iter()
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+        all.sort();
+        assert_eq!(all, ["rural", "swift"]);
+
+        let hits = nearest(
+            &conn,
+            GeoEntity::Team,
+            at(-33.9, 151.2),
+            1,
+            5000.0,
+            &Map::new(),
+        )
+        .unwrap();
+        assert_eq!(ids(&hits), ["rural"]);
+    }
+
+    #[test]
+    fn incident_origins_leave_themselves_out() {
+        let conn = db();
+        add(
+            &conn,
+            "incidents",
+            json!({"id": "a", "latitude": -27.47, "longitude": 153.02}),
+        );
+        add(
+            &conn,
+            "incidents",
+            json!({"id": "b", "latitude": -27.48, "longitude": 153.03}),
+        );
+        add(&conn, "incidents", json!({"id": "nowhere"}));
+
+        let origin = Origin {
+            center: None,
+            incident_id: Some("a".into()),
+        };
+        let (center, incident) = origin.resolve(&conn).unwrap();
+        assert_eq!(center, at(-27.47, 153.02));
+        assert_eq!(incident.unwrap()["id"], "a");
+        let mut hits =
+            within_radius(&conn, GeoEntity::Incident, center, 10.0, &Map::new()).unwrap();
+        origin.exclude(GeoEntity::Incident, &mut hits);
+        assert_eq!(ids(&hits), ["b"]);
+
+        let missing = |id: &str| Origin {
+            center: None,
+            incident_id: Some(id.into()),
+        };
+        assert!(matches!(
+            missing("zzz").resolve(&conn),
+            Err(Error::NotFound(_))
+        ));
+        assert!(matches!(
+            missing("nowhere").resolve(&conn),
+            Err(Error::Invalid(_))
+        ));
+        assert!(Origin::default().resolve(&conn).is_err());
+    }
+}
@@ -0,0 +1,889 @@
+use std::sync::Mutex;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::db::Db;
+use crate::error::{Error, Result};
+use crate::gazetteer;
+use crate::geo::{self, Point};
+use crate::mirror;
+
+pub const ALERT_EVENT: &str = "geofence://alert";
+
+/// An SOS this old when first seen is history from a first sync, not news.
+const SOS_MAX_AGE_HOURS: i64 = 6;
+const MAX_RADIUS_KM: f64 = 1000.0;
+const MAX_VERTICES: usize = 10_000;
+/// Incidents in these states no longer have a zone.
+const CLOSED_STATUSES: &[&str] = &["resolved", "closed"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Shape {
+    Circle {
+        center: Point,
+        #[serde(rename = "radiusKm")]
+        radius_km: f64,
+    },
+    /// A ring of at least three points; closing it is optional.
+    Polygon { points: Vec<Point> },
+}
+
+impl Shape {
+    pub(crate) fn validate(&self) -> Result<()> {
+        match self {
+            Shape::Circle { center, radius_km } => {
+                center.validate()?;
+                if !(radius_km.is_finite() && *radius_km > 0.0 && *radius_km <= MAX_RADIUS_KM) {
+                    return Err(Error::Invalid(format!(
+                        "radius must be between 0 and {MAX_RADIUS_KM} km"
+                    )));
+                }
+            }
+            Shape::Polygon { points } => {
+                if points.len() < 3 || points.len() > MAX_VERTICES {
+                    return Err(Error::Invalid(format!(
+                        "a zone needs between 3 and {MAX_VERTICES} points"
+                    )));
+                }
+                for point in points {
+                    point.validate()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        match self {
+            Shape::Circle { center, radius_km } => geo::distance_km(*center, point) <= *radius_km,
+            Shape::Polygon { points } => in_polygon(points, point),
+        }
+    }
+}
+
+/// Longitude of `lng` relative to `origin`, kept continuous across the
+/// antimeridian.
+fn unwrap_lng(origin: f64, lng: f64) -> f64 {
+    let d = lng - origin;
+    if d > 180.0 {
+        d - 360.0
+    } else if d < -180.0 {
+        d + 360.0
+    } else {
+        d
+    }
+}
+
+/// Even-odd ray casting in plain lat/lng, fine for zones a few hundred km
+/// across.
+fn in_polygon(ring: &[Point], point: Point) -> bool {
+    let Some(origin) = ring.first().map(|p| p.lng) else {
+        return false;
+    };
+    let x = unwrap_lng(origin, point.lng);
+    let y = point.lat;
+    let mut inside = false;
+    let mut j = ring.len() - 1;
+    for i in 0..ring.len() {
+        let (xi, yi) = (unwrap_lng(origin, ring[i].lng), ring[i].lat);
+        let (xj, yj) = (unwrap_lng(origin, ring[j].lng), ring[j].lat);
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+// ─── Geofences ──────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FenceKind {
+    /// Extra area of an incident, on top of its centre and radius.
+    IncidentZone,
+    /// Where a resource is meant to stay.
+    Staging,
+}
+
+impl FenceKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            FenceKind::IncidentZone => "incident_zone",
+            FenceKind::Staging => "staging",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "incident_zone" => Some(FenceKind::IncidentZone),
+            "staging" => Some(FenceKind::Staging),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Geofence {
+    pub id: String,
+    pub kind: FenceKind,
+    /// Incident for zones, resource for staging areas.
+    pub subject_id: String,
+    pub name: Option<String>,
+    pub shape: Shape,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewGeofence {
+    pub kind: FenceKind,
+    pub subject_id: String,
+    pub name: Option<String>,
+    pub shape: Shape,
+}
+
+pub fn fences(conn: &Connection, subject_id: Option<&str>) -> Result<Vec<Geofence>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, kind, subject_id, name, shape, created_at FROM geofences
+         WHERE ?1 IS NULL OR subject_id = ?1
+         ORDER BY created_at",
+    )?;
+    let rows = stmt.query_map([subject_id], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, Option<String>>(3)?,
+            row.get::<_, String>(4)?,
+            row.get::<_, String>(5)?,
+        ))
+    })?;
+    let mut out = Vec::new();
+    for row in rows {
+        let (id, kind, subject_id, name, shape, created_at) = row?;
+        // Skip rows a newer build wrote in a shape we don't know
+        let (Some(kind), Ok(shape)) = (FenceKind::parse(&kind), serde_json::from_str(&shape))
+        else {
+            continue;
+        };
+        out.push(Geofence {
+            id,
+            kind,
+            subject_id,
+            name,
+            shape,
+            created_at,
+        });
+    }
+    Ok(out)
+}
+
+// ─── Zones ──────────────────────────────────────────────────────
+
+/// An area of an active incident that SOS broadcasts are checked against.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Zone {
+    pub incident_id: String,
+    pub title: String,
+    pub severity: Option<String>,
+    pub shape: Shape,
+}
+
+fn is_active_incident(record: &Value) -> bool {
+    let status = record.get("status").and_then(Value::as_str);
+    !status.is_some_and(|s| CLOSED_STATUSES.contains(&s))
+}
+
+/// Zones of every open incident: its centre and affected radius, plus any
+/// drawn polygons.
+fn load_zones(conn: &Connection) -> Result<Vec<Zone>> {
+    let incidents: Vec<Value> = mirror::all(conn, "incidents")?
+        .into_iter()
+        .filter(is_active_incident)
+        .collect();
+    let text =
+        |record: &Value, key: &str| record.get(key).and_then(Value::as_str).map(str::to_owned);
+
+    let mut zones = Vec::new();
+    for incident in &incidents {
+        let (Some(id), Some(center)) = (text(incident, "id"), Point::from_record(incident)) else {
+            continue;
+        };
+        let radius_km = incident
+            .get("affected_radius_km")
+            .and_then(Value::as_f64)
+            .filter(|r| r.is_finite() && *r > 0.0);
+        if let Some(radius_km) = radius_km {
+            zones.push(Zone {
+                incident_id: id,
+                title: text(incident, "title").unwrap_or_else(|| "Incident".into()),
+                severity: text(incident, "severity"),
+                shape: Shape::Circle { center, radius_km },
+            });
+        }
+    }
+
+    for fence in fences(conn, None)? {
+        if fence.kind != FenceKind::IncidentZone {
+            continue;
+        }
+        let Some(incident) = incidents
+            .iter()
+            .find(|i| i.get("id").and_then(Value::as_str) == Some(fence.subject_id.as_str()))
+        else {
+            continue;
+        };
+        zones.push(Zone {
+            incident_id: fence.subject_id,
+            title: fence
+                .name
+                .or_else(|| text(incident, "title"))
+                .unwrap_or_else(|| "Incident".into()),
+            severity: text(incident, "severity"),
+            shape: fence.shape,
+        });
+    }
+    Ok(zones)
+}
+
+/// Active incident zones, rebuilt from the mirror after every sync or
+/// geofence edit rather than on every check.
+#[derive(Default)]
+pub struct Geofencer {
+    zones: Mutex<Option<Vec<Zone>>>,
+}
+
+impl Geofencer {
+    fn zones(&self, conn: &Connection) -> Result<Vec<Zone>> {
+        let mut zones = self.zones.lock().unwrap_or_else(|e| e.into_inner());
+        if zones.is_none() {
+            *zones = Some(load_zones(conn)?);
+        }
+        Ok(zones.clone().unwrap_or_default())
+    }
+
+    pub fn invalidate(&self) {
+        *self.zones.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+// ─── Alerts ─────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    SosInZone,
+    ResourceLeftStaging,
+}
+
+impl AlertKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            AlertKind::SosInZone => "sos_in_zone",
+            AlertKind::ResourceLeftStaging => "resource_left_staging",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeofenceAlert {
+    pub id: i64,
+    pub kind: String,
+    /// The SOS broadcast or resource.
+    pub subject_id: String,
+    /// The incident for SOS alerts, the staging geofence for resources.
+    pub zone_id: String,
+    pub title: String,
+    pub body: String,
+    pub point: Option<Point>,
+    pub raised_at: String,
+    pub cleared_at: Option<String>,
+}
+
+/// An alert about to be recorded.
+struct Draft<'a> {
+    key: String,
+    kind: AlertKind,
+    subject_id: &'a str,
+    zone_id: &'a str,
+    title: String,
+    body: String,
+    point: Point,
+}
+
+/// Record an alert unless the same one is still open. Returns it only if
+/// it is new.
+fn raise(conn: &Connection, draft: Draft) -> Result<Option<GeofenceAlert>> {
+    let raised_at = chrono::Utc::now().to_rfc3339();
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO geofence_alerts
+             (key, kind, subject_id, zone_id, title, body, latitude, longitude, raised_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            draft.key,
+            draft.kind.as_str(),
+            draft.subject_id,
+            draft.zone_id,
+            draft.title,
+            draft.body,
+            draft.point.lat,
+            draft.point.lng,
+            raised_at
+        ],
+    )?;
+    if inserted == 0 {
+        return Ok(None);
+    }
+    Ok(Some(GeofenceAlert {
+        id: conn.last_insert_rowid(),
+        kind: draft.kind.as_str().into(),
+        subject_id: draft.subject_id.into(),
+        zone_id: draft.zone_id.into(),
+        title: draft.title,
+        body: draft.body,
+        point: Some(draft.point),
+        raised_at,
+        cleared_at: None,
+    }))
+}
+
+fn clear(conn: &Connection, key: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE geofence_alerts SET cleared_at = ?2 WHERE key = ?1 AND cleared_at IS NULL",
+        params![key, chrono::Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+fn is_recent(record: &Value) -> bool {
+    let Some(created_at) = record.get("created_at").and_then(Value::as_str) else {
+        return true;
+    };
+    match chrono::DateTime::parse_from_rfc3339(created_at) {
+        Ok(at) => {
+            chrono::Utc::now().signed_duration_since(at)
+                < chrono::Duration::hours(SOS_MAX_AGE_HOURS)
+        }
+        Err(_) => true,
+    }
+}
+
+/// Alerts for an active, recent SOS broadcast inside any incident zone.
+fn check_sos(conn: &Connection, zones: &[Zone], sos: &Value) -> Result<Vec<GeofenceAlert>> {
+    let Some(id) = sos.get("id").and_then(Value::as_str) else {
+        return Ok(Vec::new());
+    };
+    let Some(point) = Point::from_record(sos) else {
+        return Ok(Vec::new());
+    };
+    if sos.get("is_active").and_then(Value::as_bool) == Some(false) || !is_recent(sos) {
+        return Ok(Vec::new());
+    }
+    // A polygon and the radius of one incident can both match; alert once
+    let mut matched: Vec<&Zone> = Vec::new();
+    for zone in zones.iter().filter(|z| z.shape.contains(point)) {
+        if !matched.iter().any(|m| m.incident_id == zone.incident_id) {
+            matched.push(zone);
+        }
+    }
+    // Naming the place is a gazetteer search; skip it when no zone matches
+    if matched.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let message = sos
+        .get("message")
+        .and_then(Value::as_str)
+        .unwrap_or("SOS broadcast");
+    let place = sos
+        .get("location_name")
+        .and_then(Value::as_str)
+        .filter(|name| !name.trim().is_empty())
+        .map(str::to_owned)
+        .or_else(|| gazetteer::describe(conn, point).ok().flatten());
+    let body = match place {
+        Some(place) => format!("{message} ({place})"),
+        None => message.to_owned(),
+    };
+    let mut alerts = Vec::new();
+    for zone in matched {
+        let draft = Draft {
+            key: format!("sos:{id}:{}", zone.incident_id),
+            kind: AlertKind::SosInZone,
+            subject_id: id,
+            zone_id: &zone.incident_id,
+            title: format!("SOS inside {}", zone.title),
+            body: body.clone(),
+            point,
+        };
+        if let Some(alert) = raise(conn, draft)? {
+            alerts.push(alert);
+        }
+    }
+    Ok(alerts)
+}
+
+/// An alert when a resource with a staging area is outside all of them;
+/// clears the open one once it's back.
+fn check_resource(
+    conn: &Connection,
+    staging: &[Geofence],
+    resource: &Value,
+) -> Result<Option<GeofenceAlert>> {
+    let Some(id) = resource.get("id").and_then(Value::as_str) else {
+        return Ok(None);
+    };
+    let areas: Vec<&Geofence> = staging.iter().filter(|f| f.subject_id == id).collect();
+    let (Some(first), Some(point)) = (areas.first(), Point::from_record(resource)) else {
+        return Ok(None);
+    };
+
+    let key = format!("resource:{id}");
+    if areas.iter().any(|f| f.shape.contains(point)) {
+        clear(conn, &key)?;
+        return Ok(None);
+    }
+    let name = resource
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or("A resource");
+    let area = first.name.as_deref().unwrap_or("its staging area");
+    // The stored name is where it was staged; describe where it is now
+    let place = match gazetteer::describe(conn, point).ok().flatten() {
+        Some(place) => place,
+        None => resource
+            .get("location_name")
+            .and_then(Value::as_str)
+            .map_or_else(
+                || format!("{:.5}, {:.5}", point.lat, point.lng),
+                str::to_owned,
+            ),
+    };
+    raise(
+        conn,
+        Draft {
+            key,
+            kind: AlertKind::ResourceLeftStaging,
+            subject_id: id,
+            zone_id: &first.id,
+            title: format!("{name} left {area}"),
+            body: format!("Last reported at {place}"),
+            point,
+        },
+    )
+}
+
+fn staging_fences(conn: &Connection) -> Result<Vec<Geofence>> {
+    Ok(fences(conn, None)?
+        .into_iter()
+        .filter(|f| f.kind == FenceKind::Staging)
+        .collect())
+}
+
+/// Let the UI and the OS know. Notifications are best-effort: permission
+/// may not have been granted.
+fn dispatch(app: &AppHandle, alerts: &[GeofenceAlert]) {
+    for alert in alerts {
+        let _ = app.emit(ALERT_EVENT, alert);
+        if let Err(e) = app
+            .notification()
+            .builder()
+            .title(&alert.title)
+            .body(&alert.body)
+            .show()
+        {
+            eprintln!("[geofence] Notification failed: {e}");
+        }
+    }
+}
+
+/// Check every cached SOS broadcast and resource against the zones, after
+/// a mirror pull.
+pub fn evaluate(app: &AppHandle) -> Result<Vec<GeofenceAlert>> {
+    let geofencer = app.state::<Geofencer>();
+    geofencer.invalidate();
+    let alerts = app.state::<Db>().with(|conn| {
+        let zones = geofencer.zones(conn)?;
+        let staging = staging_fences(conn)?;
+        let mut alerts = Vec::new();
+        for sos in mirror::all(conn, "sos_broadcasts")? {
+            alerts.extend(check_sos(conn, &zones, &sos)?);
+        }
+        for resource in mirror::all(conn, "resources")? {
+            alerts.extend(check_resource(conn, &staging, &resource)?);
+        }
+        Ok(alerts)
+    })?;
+    dispatch(app, &alerts);
+    Ok(alerts)
+}
+
+/// Check one record whose position just changed and raise what it trips.
+pub fn check(app: &AppHandle, table: &str, record: &Value) -> Result<Vec<GeofenceAlert>> {
+    let geofencer = app.state::<Geofencer>();
+    let alerts = app.state::<Db>().with(|conn| match table {
+        "sos_broadcasts" => check_sos(conn, &geofencer.zones(conn)?, record),
+        "resources" => Ok(check_resource(conn, &staging_fences(conn)?, record)?
+            .into_iter()
+            .collect()),
+        _ => Err(Error::Invalid(format!("{table} has no geofence checks"))),
+    })?;
+    dispatch(app, &alerts);
+    Ok(alerts)
+}
+
+/// Delete a geofence; false if there was none.
+fn remove(conn: &Connection, id: &str) -> Result<bool> {
+    let fence: Option<(String, String)> = conn
+        .query_row(
+            "SELECT kind, subject_id FROM geofences WHERE id = ?1",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let Some((kind, subject)) = fence else {
+        return Ok(false);
+    };
+    conn.execute("DELETE FROM geofences WHERE id = ?1", [id])?;
+    // A resource with no staging area left can't be outside it
+    if FenceKind::parse(&kind) == Some(FenceKind::Staging) {
+        let staged: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM geofences WHERE kind = ?1 AND subject_id = ?2)",
+            params![FenceKind::Staging.as_str(), subject],
+            |row| row.get(0),
+        )?;
+        if !staged {
+            clear(conn, &format!("resource:{subject}"))?;
+        }
+    }
+    Ok(true)
+}
+
+// ─── Commands ───────────────────────────────────────────────────
+
+/// Check a record that just arrived over Realtime, before the mirror has it.
+#[tauri::command]
+pub fn geofence_check(app: AppHandle, table: String, record: Value) -> Result<Vec<GeofenceAlert>> {
+    check(&app, &table, &record)
+}
+
+#[tauri::command]
+pub fn geofence_zones(db: State<'_, Db>, geofencer: State<'_, Geofencer>) -> Result<Vec<Zone>> {
+    db.with(|conn| geofencer.zones(conn))
+}
+
+/// Geofences of one incident or resource, or all of them.
+#[tauri::command]
+pub fn geofences_list(db: State<'_, Db>, subject_id: Option<String>) -> Result<Vec<Geofence>> {
+    db.with(|conn| fences(conn, subject_id.as_deref()))
+}
+
+#[tauri::command]
+pub fn geofence_add(
+    db: State<'_, Db>,
+    geofencer: State<'_, Geofencer>,
+    fence: NewGeofence,
+) -> Result<Geofence> {
+    fence.shape.validate()?;
+    if fence.subject_id.trim().is_empty() {
+        return Err(Error::Invalid(
+            "a geofence needs an incident or resource".into(),
+        ));
+    }
+    let geofence = Geofence {
+        id: uuid::Uuid::new_v4().to_string(),
+        kind: fence.kind,
+        subject_id: fence.subject_id,
+        name: fence.name.filter(|n| !n.trim().is_empty()),
+        shape: fence.shape,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    db.with(|conn| {
+        conn.execute(
+            "INSERT INTO geofences (id, kind, subject_id, name, shape, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                geofence.id,
+                geofence.kind.as_str(),
+                geofence.subject_id,
+                geofence.name,
+                serde_json::to_string(&geofence.shape)?,
+                geofence.created_at
+            ],
+        )?;
+        Ok(())
+    })?;
+    geofencer.invalidate();
+    Ok(geofence)
+}
+
+#[tauri::command]
+pub fn geofence_remove(
+    db: State<'_, Db>,
+    geofencer: State<'_, Geofencer>,
+    id: String,
+) -> Result<()> {
+    let removed = db.with(|conn| remove(conn, &id))?;
+    if !removed {
+        return Err(Error::NotFound("geofence".into()));
+    }
+    geofencer.invalidate();
+    Ok(())
+}
+
+/// Most recent alerts first.
+#[tauri::command]
+pub fn geofence_alerts(db: State<'_, Db>, limit: Option<u32>) -> Result<Vec<GeofenceAlert>> {
+    db.with(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, kind, subject_id, zone_id, title, body, latitude, longitude,
+                    raised_at, cleared_at
+             FROM geofence_alerts ORDER BY id DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map([limit.unwrap_or(100)], |row| {
+            let point = match (row.get::<_, Option<f64>>(6)?, row.get::<_, Option<f64>>(7)?) {
+                (Some(lat), Some(lng)) => Some(Point { lat, lng }),
+                _ => None,
+            };
+            Ok(GeofenceAlert {
+                id: row.get(0)?,
+                kind: row.get(1)?,
+                subject_id: row.get(2)?,
+                zone_id: row.get(3)?,
+                title: row.get(4)?,
+                body: row.get(5)?,
+                point,
+                raised_at: row.get(8)?,
+                cleared_at: row.get(9)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        for migration in crate::migrations::MIGRATIONS {
+            conn.execute_batch(migration.sql).unwrap();
+        }
+        conn
+    }
+
+    fn at(lat: f64, lng: f64) -> Point {
+        Point { lat, lng }
+    }
+
+    fn square(lat: f64, lng: f64, half: f64) -> Shape {
+        Shape::Polygon {
+            points: vec![
+                at(lat - half, lng - half),
+                at(lat - half, lng + half),
+                at(lat + half, lng + half),
+                at(lat + half, lng - half),
+            ],
+        }
+    }
+
+    fn add(conn: &Connection, id: &str, kind: FenceKind, subject: &str, shape: Shape) {
+        conn.execute(
+            "INSERT INTO geofences (id, kind, subject_id, name, shape, created_at)
+             VALUES (?1, ?2, ?3, NULL, ?4, ?5)",
+            params![
+                id,
+                kind.as_str(),
+                subject,
+                serde_json::to_string(&shape).unwrap(),
+                chrono::Utc::now().to_rfc3339()
+            ],
+        )
+        .unwrap();
+    }
+
+    fn open_alerts(conn: &Connection) -> Vec<String> {
+        let mut stmt = conn
+            .prepare("SELECT key FROM geofence_alerts WHERE cleared_at IS NULL ORDER BY key")
+            .unwrap();
+        let rows = stmt.query_map([], |row| row.get(0)).unwrap();
+        rows.collect::<rusqlite::Result<_>>().unwrap()
+    }
+
+    #[test]
+    fn polygons_across_the_antimeridian() {
+        // Fiji, straddling 180°
+        let ring = [
+            at(-19.0, 179.0),
+            at(-19.0, -179.0),
+            at(-15.0, -179.0),
+            at(-15.0, 179.0),
+        ];
+        assert!(in_polygon(&ring, at(-17.0, 179.9)));
+        assert!(in_polygon(&ring, at(-17.0, -179.9)));
+        assert!(!in_polygon(&ring, at(-17.0, 0.0)));
+        assert!(!in_polygon(&ring, at(-17.0, 178.0)));
+        assert!(!in_polygon(&ring, at(-20.0, 180.0)));
+        assert!(!in_polygon(&[], at(0.0, 0.0)));
+    }
+
+    #[test]
+    fn circles_and_shapes_are_validated() {
+        let circle = Shape::Circle {
+            center: at(10.0, 10.0),
+            radius_km: 5.0,
+        };
+        assert!(circle.contains(at(10.0, 10.04)));
+        assert!(!circle.contains(at(10.0, 10.05)));
+        assert!(circle.validate().is_ok());
+        assert!(Shape::Circle {
+            center: at(10.0, 10.0),
+            radius_km: 0.0,
+        }
+        .validate()
+        .is_err());
+        assert!(Shape::Polygon {
+            points: vec![at(0.0, 0.0), at(1.0, 1.0)],
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn sos_in_two_zones_of_one_incident_alerts_once() {
+        let conn = db();
+        let zone = |incident: &str, shape: Shape| Zone {
+            incident_id: incident.into(),
+            title: format!("Flood {incident}"),
+            severity: None,
+            shape,
+        };
+        let zones = [
+            zone(
+                "a",
+                Shape::Circle {
+                    center: at(0.0, 0.0),
+                    radius_km: 50.0,
+                },
+            ),
+            zone("a", square(0.0, 0.0, 0.1)),
+            zone("b", square(0.05, 0.05, 0.1)),
+            zone("c", square(5.0, 5.0, 0.1)),
+        ];
+        let sos = serde_json::json!({
+            "id": "sos-1",
+            "latitude": 0.01,
+            "longitude": 0.01,
+            "message": "Trapped on roof",
+            "location_name": "Riverside",
+        });
+
+        let alerts = check_sos(&conn, &zones, &sos).unwrap();
+        let mut incidents: Vec<&str> = alerts.iter().map(|a| a.zone_id.as_str()).collect();
+        incidents.sort();
+        assert_eq!(incidents, ["a", "b"]);
+        assert_eq!(alerts[0].body, "Trapped on roof (Riverside)");
+        // Still open, so not raised again
+        assert!(check_sos(&conn, &zones, &sos).unwrap().is_empty());
+        assert_eq!(open_alerts(&conn), ["sos:sos-1:a", "sos:sos-1:b"]);
+
+        let mut stale = sos.clone();
+        stale["id"] = "sos-2".into();
+        stale["created_at"] = "2020-01-01T00:00:00Z".into();
+        assert!(check_sos(&conn, &zones, &stale).unwrap().is_empty());
+        let mut inactive = sos.clone();
+        inactive["id"] = "sos-3".into();
+        inactive["is_active"] = false.into();
+        assert!(check_sos(&conn, &zones, &inactive).unwrap().is_empty());
+    }
+
+    #[test]
+    fn resources_raise_and_clear_when_leaving_staging() {
+        let conn = db();
+        add(
+            &conn,
+            "yard",
+            FenceKind::Staging,
+            "truck",
+            square(0.0, 0.0, 0.1),
+        );
+        let staging = staging_fences(&conn).unwrap();
+        let truck = |lat: f64| serde_json::json!({ "id": "truck", "name": "Truck 4", "latitude": lat, "longitude": 0.0 });
+
+        assert!(check_resource(&conn, &staging, &truck(0.0))
+            .unwrap()
+            .is_none());
+        let alert = check_resource(&conn, &staging, &truck(1.0))
+            .unwrap()
+            .unwrap();
+        assert_eq!(alert.title, "Truck 4 left its staging area");
+        assert_eq!(alert.zone_id, "yard");
+        assert!(check_resource(&conn, &staging, &truck(1.0))
+            .unwrap()
+            .is_none());
+        assert_eq!(open_alerts(&conn), ["resource:truck"]);
+
+        // Back in the yard clears it, and leaving again is a new alert
+        assert!(check_resource(&conn, &staging, &truck(0.0))
+            .unwrap()
+            .is_none());
+        assert!(open_alerts(&conn).is_empty());
+        assert!(check_resource(&conn, &staging, &truck(1.0))
+            .unwrap()
+            .is_some());
+
+        // Resources without a staging area are never out of it
+        let other = serde_json::json!({ "id": "boat", "latitude": 9.0, "longitude": 9.0 });
+        assert!(check_resource(&conn, &staging, &other).unwrap().is_none());
+    }
+
+    #[test]
+    fn removing_the_last_staging_area_clears_the_alert() {
+        let conn = db();
+        add(
+            &conn,
+            "yard",
+            FenceKind::Staging,
+            "truck",
+            square(0.0, 0.0, 0.1),
+        );
+        add(
+            &conn,
+            "depot",
+            FenceKind::Staging,
+            "truck",
+            square(2.0, 0.0, 0.1),
+        );
+        add(
+            &conn,
+            "zone",
+            FenceKind::IncidentZone,
+            "truck",
+            square(1.0, 0.0, 0.1),
+        );
+        let truck = serde_json::json!({ "id": "truck", "latitude": 1.0, "longitude": 0.0 });
+        let staging = staging_fences(&conn).unwrap();
+        assert!(check_resource(&conn, &staging, &truck).unwrap().is_some());
+
+        // The depot is still a staging area the truck is outside of
+        assert!(remove(&conn, "yard").unwrap());
+        assert_eq!(open_alerts(&conn), ["resource:truck"]);
+        // Not a staging area at all
+        assert!(remove(&conn, "zone").unwrap());
+        assert_eq!(open_alerts(&conn), ["resource:truck"]);
+
+        assert!(remove(&conn, "depot").unwrap());
+        assert!(open_alerts(&conn).is_empty());
+        assert!(!remove(&conn, "depot").unwrap());
+    }
+}
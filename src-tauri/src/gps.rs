@@ -0,0 +1,947 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs, UdpSocket};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::error::{Error, Result};
+use crate::geo::Point;
+
+pub const FIX_EVENT: &str = "gps://fix";
+pub const STATUS_EVENT: &str = "gps://status";
+
+const CONFIG_FILE: &str = "gps-source.json";
+const GPSD_ADDRESS: &str = "127.0.0.1:2947";
+const GPSD_WATCH: &str = "?WATCH={\"enable\":true,\"json\":true}\n";
+/// How often a blocked read wakes up to check for stop and staleness.
+const READ_TIMEOUT: Duration = Duration::from_secs(2);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+/// A fix older than this is not where we are any more.
+const STALE_AFTER: Duration = Duration::from_secs(10);
+/// Receivers send several sentences per second; the UI needs one fix.
+const EMIT_INTERVAL: Duration = Duration::from_millis(900);
+/// NMEA sentences are at most 82 bytes and gpsd reports a few kB; a
+/// source that never sends a newline must not grow the buffer forever.
+const MAX_LINE: usize = 16 * 1024;
+/// Longest pause between replayed fixes, whatever the log's timestamps say.
+const MAX_REPLAY_GAP_S: f64 = 5.0;
+/// Typical user-equivalent range error, to turn HDOP into metres.
+const UERE_M: f64 = 5.0;
+const KNOTS_TO_KMH: f64 = 1.852;
+
+// ─── Sources ────────────────────────────────────────────────────
+
+/// Where NMEA sentences (or gpsd reports) come from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Source {
+    /// A USB or RS-232 receiver, e.g. `/dev/ttyACM0` or `COM3`.
+    Serial {
+        path: String,
+        #[serde(rename = "baudRate")]
+        baud_rate: u32,
+    },
+    /// An NMEA stream served over TCP, e.g. by a network GPS or a phone app.
+    Tcp { address: String },
+    /// NMEA datagrams broadcast to a local port, e.g. `0.0.0.0:10110`.
+    Udp { bind: String },
+    /// A local gpsd; `127.0.0.1:2947` when no address is given.
+    Gpsd { address: Option<String> },
+    /// A recorded NMEA log, played back at its own pace times `speed`.
+    Replay {
+        path: String,
+        #[serde(default = "default_speed")]
+        speed: f64,
+        #[serde(default)]
+        repeat: bool,
+    },
+}
+
+fn default_speed() -> f64 {
+    1.0
+}
+
+impl Source {
+    fn validate(&self) -> Result<()> {
+        match self {
+            Source::Serial { path, baud_rate } => {
+                if path.trim().is_empty() || *baud_rate == 0 {
+                    return Err(Error::Invalid(
+                        "a serial receiver needs a port and baud rate".into(),
+                    ));
+                }
+            }
+            Source::Tcp { address } | Source::Udp { bind: address } => {
+                if address.trim().is_empty() {
+                    return Err(Error::Invalid("enter a host and port".into()));
+                }
+            }
+            Source::Gpsd { .. } => {}
+            Source::Replay { path, speed, .. } => {
+                if !(speed.is_finite() && *speed > 0.0) {
+                    return Err(Error::Invalid("replay speed must be positive".into()));
+                }
+                if !PathBuf::from(path).is_file() {
+                    return Err(Error::NotFound(path.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Source::Serial { path, baud_rate } => format!("{path} at {baud_rate} baud"),
+            Source::Tcp { address } => format!("tcp://{address}"),
+            Source::Udp { bind } => format!("udp://{bind}"),
+            Source::Gpsd { address } => {
+                format!("gpsd at {}", address.as_deref().unwrap_or(GPSD_ADDRESS))
+            }
+            Source::Replay { path, .. } => format!("replay of {path}"),
+        }
+    }
+}
+
+/// The saved receiver, started again with the app while `enabled`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GpsConfig {
+    pub source: Option<Source>,
+    pub enabled: bool,
+}
+
+fn config_path(app: &AppHandle) -> Result<PathBuf> {
+    Ok(app.path().app_config_dir()?.join(CONFIG_FILE))
+}
+
+fn load_config(app: &AppHandle) -> GpsConfig {
+    config_path(app)
+        .ok()
+        .and_then(|path| std::fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn save_config(app: &AppHandle, config: &GpsConfig) -> Result<()> {
+    let path = config_path(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(config)?)?;
+    std::fs::rename(tmp, path)?;
+    Ok(())
+}
+
+// ─── Fixes ──────────────────────────────────────────────────────
+
+/// GGA fix quality.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FixQuality {
+    #[default]
+    NoFix,
+    Gps,
+    Dgps,
+    Pps,
+    RtkFixed,
+    RtkFloat,
+    /// Dead reckoning.
+    Estimated,
+    Manual,
+    Simulated,
+}
+
+impl FixQuality {
+    fn from_gga(code: &str) -> Self {
+        match code {
+            "1" => FixQuality::Gps,
+            "2" => FixQuality::Dgps,
+            "3" => FixQuality::Pps,
+            "4" => FixQuality::RtkFixed,
+            "5" => FixQuality::RtkFloat,
+            "6" => FixQuality::Estimated,
+            "7" => FixQuality::Manual,
+            "8" => FixQuality::Simulated,
+            _ => FixQuality::NoFix,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum FixMode {
+    #[default]
+    #[serde(rename = "none")]
+    None,
+    #[serde(rename = "2d")]
+    TwoD,
+    #[serde(rename = "3d")]
+    ThreeD,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fix {
+    pub point: Point,
+    pub altitude_m: Option<f64>,
+    /// Estimated horizontal error: from GST or gpsd when the receiver
+    /// reports it, otherwise HDOP times a typical range error.
+    pub accuracy_m: Option<f64>,
+    pub quality: FixQuality,
+    pub mode: FixMode,
+    pub satellites: Option<u32>,
+    pub hdop: Option<f64>,
+    pub speed_kmh: Option<f64>,
+    /// Degrees true.
+    pub course_deg: Option<f64>,
+    /// UTC time from the receiver, once it knows the date.
+    pub time: Option<String>,
+    /// When the app received it.
+    pub received_at: String,
+}
+
+/// NMEA `ddmm.mmmm` plus hemisphere to signed degrees.
+pub fn nmea_degrees(value: &str, hemisphere: &str) -> Option<f64> {
+    let dot = value.find('.').unwrap_or(value.len());
+    if dot < 2 || !value.is_ascii() {
+        return None;
+    }
+    let degrees: f64 = value[..dot - 2].parse().ok()?;
+    let minutes: f64 = value[dot - 2..].parse().ok()?;
+    let value = degrees + minutes / 60.0;
+    match hemisphere {
+        "N" | "E" => Some(value),
+        "S" | "W" => Some(-value),
+        _ => None,
+    }
+}
+
+fn number<T: std::str::FromStr>(field: Option<&&str>) -> Option<T> {
+    field.filter(|f| !f.is_empty())?.parse().ok()
+}
+
+/// `hhmmss.ss` to seconds since midnight.
+fn seconds_of_day(time: &str) -> Option<f64> {
+    if time.len() < 6 || !time.is_ascii() {
+        return None;
+    }
+    let h: f64 = time[..2].parse().ok()?;
+    let m: f64 = time[2..4].parse().ok()?;
+    let s: f64 = time[4..].parse().ok()?;
+    Some(h * 3600.0 + m * 60.0 + s)
+}
+
+/// The sentence type (`GGA`, `RMC`, ...) and its fields, if the checksum
+/// holds.
+fn sentence(line: &str) -> Option<(&str, Vec<&str>)> {
+    let body = line.strip_prefix('$')?;
+    let body = match body.split_once('*') {
+        Some((body, checksum)) => {
+            let expected = u8::from_str_radix(checksum.get(..2)?, 16).ok()?;
+            if body.bytes().fold(0, |acc, b| acc ^ b) != expected {
+                return None;
+            }
+            body
+        }
+        None => body,
+    };
+    let fields: Vec<&str> = body.split(',').collect();
+    // Two-letter talker (GP, GN, GL, ...); proprietary P sentences are skipped
+    let kind = fields.first()?.get(2..)?;
+    Some((kind, fields))
+}
+
+/// Everything the receiver has told us so far, merged across sentences.
+#[derive(Debug, Default)]
+pub struct Receiver {
+    point: Option<Point>,
+    altitude_m: Option<f64>,
+    quality: FixQuality,
+    mode: FixMode,
+    satellites: Option<u32>,
+    hdop: Option<f64>,
+    error_m: Option<f64>,
+    speed_kmh: Option<f64>,
+    course_deg: Option<f64>,
+    /// `YYYY-MM-DD` from RMC.
+    date: Option<String>,
+    /// `hh:mm:ss.ss` from GGA or RMC.
+    time: Option<String>,
+    /// Full timestamp from gpsd.
+    timestamp: Option<String>,
+    seconds: Option<f64>,
+}
+
+impl Receiver {
+    fn set_time(&mut self, field: Option<&&str>) {
+        let Some(time) = field.filter(|t| t.len() >= 6 && t.is_ascii()) else {
+            return;
+        };
+        if let Some(seconds) = seconds_of_day(time) {
+            self.seconds = Some(seconds);
+            self.time = Some(format!("{}:{}:{}", &time[..2], &time[2..4], &time[4..]));
+        }
+    }
+
+    fn set_point(
+        &mut self,
+        lat: Option<&&str>,
+        ns: Option<&&str>,
+        lng: Option<&&str>,
+        ew: Option<&&str>,
+    ) {
+        let (Some(lat), Some(ns), Some(lng), Some(ew)) = (lat, ns, lng, ew) else {
+            return;
+        };
+        if let (Some(lat), Some(lng)) = (nmea_degrees(lat, ns), nmea_degrees(lng, ew)) {
+            let point = Point { lat, lng };
+            if point.validate().is_ok() {
+                self.point = Some(point);
+            }
+        }
+    }
+
+    /// Merge one line, NMEA or gpsd JSON. Returns true when it completes
+    /// a position report worth publishing.
+    pub fn apply(&mut self, line: &str) -> bool {
+        if line.starts_with('{') {
+            return serde_json::from_str::<Value>(line)
+                .is_ok_and(|report| self.apply_gpsd(&report));
+        }
+        let Some((kind, f)) = sentence(line) else {
+            return false;
+        };
+        match kind {
+            "GGA" => {
+                self.set_time(f.get(1));
+                self.quality = FixQuality::from_gga(f.get(6).copied().unwrap_or(""));
+                self.set_point(f.get(2), f.get(3), f.get(4), f.get(5));
+                self.satellites = number(f.get(7));
+                self.hdop = number(f.get(8));
+                self.altitude_m = number(f.get(9));
+                true
+            }
+            "RMC" => {
+                self.set_time(f.get(1));
+                if let Some(date) = f.get(9).filter(|d| d.len() == 6 && d.is_ascii()) {
+                    // Two-digit years; old receivers and logs are from the 1980s on
+                    let century = if &date[4..6] >= "80" { "19" } else { "20" };
+                    self.date = Some(format!(
+                        "{century}{}-{}-{}",
+                        &date[4..6],
+                        &date[2..4],
+                        &date[..2]
+                    ));
+                }
+                if f.get(2) != Some(&"A") {
+                    self.quality = FixQuality::NoFix;
+                    return true;
+                }
+                if self.quality == FixQuality::NoFix {
+                    self.quality = match f.get(12).and_then(|m| m.chars().next()) {
+                        Some('D') => FixQuality::Dgps,
+                        Some('E') => FixQuality::Estimated,
+                        _ => FixQuality::Gps,
+                    };
+                }
+                self.set_point(f.get(3), f.get(4), f.get(5), f.get(6));
+                self.speed_kmh = number::<f64>(f.get(7)).map(|knots| knots * KNOTS_TO_KMH);
+                self.course_deg = number(f.get(8));
+                true
+            }
+            "GSA" => {
+                self.mode = match f.get(2).copied() {
+                    Some("2") => FixMode::TwoD,
+                    Some("3") => FixMode::ThreeD,
+                    _ => FixMode::None,
+                };
+                if let Some(hdop) = number(f.get(16)) {
+                    self.hdop = Some(hdop);
+                }
+                false
+            }
+            "GST" => {
+                let (lat, lng): (Option<f64>, Option<f64>) = (number(f.get(6)), number(f.get(7)));
+                if let (Some(lat), Some(lng)) = (lat, lng) {
+                    self.error_m = Some(lat.hypot(lng));
+                }
+                false
+            }
+            _ => false,
+        }
+    }
+
+    fn apply_gpsd(&mut self, report: &Value) -> bool {
+        let field = |key: &str| report.get(key).and_then(Value::as_f64);
+        match report.get("class").and_then(Value::as_str) {
+            Some("TPV") => {
+                self.mode = match report.get("mode").and_then(Value::as_u64) {
+                    Some(2) => FixMode::TwoD,
+                    Some(3) => FixMode::ThreeD,
+                    _ => FixMode::None,
+                };
+                self.quality = match (self.mode, report.get("status").and_then(Value::as_u64)) {
+                    (FixMode::None, _) => FixQuality::NoFix,
+                    (_, Some(2)) => FixQuality::Dgps,
+                    (_, Some(3)) => FixQuality::RtkFixed,
+                    (_, Some(4)) => FixQuality::RtkFloat,
+                    (_, Some(5)) => FixQuality::Estimated,
+                    (_, Some(6)) => FixQuality::Manual,
+                    (_, Some(7)) => FixQuality::Simulated,
+                    _ => FixQuality::Gps,
+                };
+                if let (Some(lat), Some(lng)) = (field("lat"), field("lon")) {
+                    let point = Point { lat, lng };
+                    if point.validate().is_ok() {
+                        self.point = Some(point);
+                    }
+                }
+                self.altitude_m = field("altHAE").or_else(|| field("alt"));
+                self.error_m = field("eph").or_else(|| Some(field("epx")?.hypot(field("epy")?)));
+                // gpsd reports m/s
+                self.speed_kmh = field("speed").map(|s| s * 3.6);
+                self.course_deg = field("track");
+                if let Some(time) = report.get("time").and_then(Value::as_str) {
+                    self.seconds = chrono::DateTime::parse_from_rfc3339(time)
+                        .ok()
+                        .map(|t| t.timestamp_millis() as f64 / 1000.0 % 86_400.0);
+                    self.timestamp = Some(time.to_owned());
+                }
+                true
+            }
+            Some("SKY") => {
+                self.hdop = field("hdop").or(self.hdop);
+                if let Some(used) = report.get("uSat").and_then(Value::as_u64) {
+                    self.satellites = Some(used as u32);
+                } else if let Some(satellites) = report.get("satellites").and_then(Value::as_array)
+                {
+                    let used = satellites
+                        .iter()
+                        .filter(|s| s.get("used").and_then(Value::as_bool) == Some(true))
+                        .count();
+                    self.satellites = Some(used as u32);
+                }
+                false
+            }
+            _ => false,
+        }
+    }
+
+    pub fn fix(&self) -> Option<Fix> {
+        if self.quality == FixQuality::NoFix {
+            return None;
+        }
+        Some(Fix {
+            point: self.point?,
+            altitude_m: self.altitude_m,
+            accuracy_m: self.error_m.or(self.hdop.map(|h| h * UERE_M)),
+            quality: self.quality,
+            mode: self.mode,
+            satellites: self.satellites,
+            hdop: self.hdop,
+            speed_kmh: self.speed_kmh,
+            course_deg: self.course_deg,
+            time: self.timestamp.clone().or_else(|| {
+                Some(format!(
+                    "{}T{}Z",
+                    self.date.as_deref()?,
+                    self.time.as_deref()?
+                ))
+            }),
+            received_at: chrono::Utc::now().to_rfc3339(),
+        })
+    }
+}
+
+// ─── Service ────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GpsState {
+    #[default]
+    Stopped,
+    Connecting,
+    /// Connected, but the receiver has no (fresh) fix.
+    Searching,
+    Fixed,
+    Error,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GpsStatus {
+    pub source: Option<Source>,
+    pub state: GpsState,
+    pub error: Option<String>,
+    /// The last fix, even once stale.
+    pub fix: Option<Fix>,
+}
+
+#[derive(Default)]
+struct Inner {
+    status: GpsStatus,
+    fixed_at: Option<Instant>,
+    stop: Option<Arc<AtomicBool>>,
+}
+
+/// The receiver connection and the latest fix from it.
+#[derive(Default)]
+pub struct Gps {
+    inner: Mutex<Inner>,
+}
+
+impl Gps {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn status(&self) -> GpsStatus {
+        self.lock().status.clone()
+    }
+
+    /// The current fix, if there is one and it is fresh.
+    pub fn current(&self) -> Option<Fix> {
+        let inner = self.lock();
+        let fresh = inner.fixed_at.is_some_and(|at| at.elapsed() < STALE_AFTER);
+        (inner.status.state == GpsState::Fixed && fresh)
+            .then(|| inner.status.fix.clone())
+            .flatten()
+    }
+
+    fn set_state(&self, app: &AppHandle, state: GpsState, error: Option<String>) {
+        let status = {
+            let mut inner = self.lock();
+            if inner.status.state == state && inner.status.error == error {
+                return;
+            }
+            inner.status.state = state;
+            inner.status.error = error;
+            inner.status.clone()
+        };
+        let _ = app.emit(STATUS_EVENT, &status);
+    }
+
+    fn record(&self, app: &AppHandle, fix: Fix) {
+        {
+            let mut inner = self.lock();
+            inner.fixed_at = Some(Instant::now());
+            inner.status.fix = Some(fix);
+        }
+        self.set_state(app, GpsState::Fixed, None);
+    }
+
+    /// Drop to searching once the last fix has gone stale.
+    fn check_stale(&self, app: &AppHandle) {
+        let stale = {
+            let inner = self.lock();
+            inner.status.state == GpsState::Fixed
+                && inner.fixed_at.is_none_or(|at| at.elapsed() >= STALE_AFTER)
+        };
+        if stale {
+            self.set_state(app, GpsState::Searching, None);
+        }
+    }
+
+    fn stop(&self, app: &AppHandle) {
+        if let Some(stop) = self.lock().stop.take() {
+            stop.store(true, Ordering::Release);
+        }
+        self.set_state(app, GpsState::Stopped, None);
+    }
+
+    fn start(&self, app: &AppHandle, source: Source) {
+        self.stop(app);
+        let stop = Arc::new(AtomicBool::new(false));
+        {
+            let mut inner = self.lock();
+            inner.stop = Some(stop.clone());
+            inner.status.source = Some(source.clone());
+            inner.status.fix = None;
+            inner.fixed_at = None;
+        }
+        let app = app.clone();
+        std::thread::spawn(move || run(&app, source, &stop));
+    }
+}
+
+/// UDP has no stream; each datagram holds one or more sentences.
+struct UdpReader(UdpSocket);
+
+impl Read for UdpReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.recv(buf)
+    }
+}
+
+/// Splits a source into lines, dropping any longer than `MAX_LINE` whole.
+#[derive(Default)]
+struct Lines {
+    buf: Vec<u8>,
+    /// Inside an oversized line whose end hasn't arrived yet.
+    skipping: bool,
+}
+
+impl Lines {
+    /// The next line, trimmed; `None` once the source ends. A timeout comes
+    /// back as an error and keeps the partial line for the next call.
+    fn next(&mut self, reader: &mut dyn BufRead) -> std::io::Result<Option<String>> {
+        loop {
+            let limit = (MAX_LINE + 1 - self.buf.len()) as u64;
+            if (&mut *reader)
+                .take(limit)
+                .read_until(b'\n', &mut self.buf)?
+                == 0
+            {
+                return Ok(None);
+            }
+            if self.buf.len() > MAX_LINE || self.skipping {
+                self.skipping = !self.buf.ends_with(b"\n");
+                self.buf.clear();
+                continue;
+            }
+            let text = String::from_utf8_lossy(&self.buf).trim().to_owned();
+            self.buf.clear();
+            return Ok(Some(text));
+        }
+    }
+}
+
+fn resolve(address: &str) -> Result<std::net::SocketAddr> {
+    address
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| Error::Invalid(format!("{address} did not resolve")))
+}
+
+fn connect(source: &Source) -> Result<Box<dyn BufRead + Send>> {
+    Ok(match source {
+        Source::Serial { path, baud_rate } => {
+            let port = serialport::new(path, *baud_rate)
+                .timeout(READ_TIMEOUT)
+                .open()
+                .map_err(|e| Error::Invalid(format!("could not open {path}: {e}")))?;
+            Box::new(BufReader::new(port))
+        }
+        Source::Tcp { address } => {
+            let stream = TcpStream::connect_timeout(&resolve(address)?, CONNECT_TIMEOUT)?;
+            stream.set_read_timeout(Some(READ_TIMEOUT))?;
+            Box::new(BufReader::new(stream))
+        }
+        Source::Udp { bind } => {
+            let socket = UdpSocket::bind(bind)?;
+            socket.set_read_timeout(Some(READ_TIMEOUT))?;
+            Box::new(BufReader::new(UdpReader(socket)))
+        }
+        Source::Gpsd { address } => {
+            let address = resolve(address.as_deref().unwrap_or(GPSD_ADDRESS))?;
+            let mut stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?;
+            stream.set_read_timeout(Some(READ_TIMEOUT))?;
+            stream.write_all(GPSD_WATCH.as_bytes())?;
+            Box::new(BufReader::new(stream))
+        }
+        Source::Replay { path, .. } => Box::new(BufReader::new(File::open(path)?)),
+    })
+}
+
+/// Read lines until the source ends or `stop` is set, publishing fixes.
+fn pump(
+    app: &AppHandle,
+    reader: &mut dyn BufRead,
+    stop: &AtomicBool,
+    pace: Option<f64>,
+) -> Result<()> {
+    let gps = app.state::<Gps>();
+    let mut receiver = Receiver::default();
+    let mut lines = Lines::default();
+    let mut last_emit: Option<Instant> = None;
+    let mut last_seconds: Option<f64> = None;
+    gps.set_state(app, GpsState::Searching, None);
+
+    while !stop.load(Ordering::Acquire) {
+        let text = match lines.next(reader) {
+            Ok(Some(text)) => text,
+            Ok(None) => return Ok(()),
+            // Keep the partial line; the rest arrives with the next read
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                gps.check_stale(app);
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if !receiver.apply(&text) {
+            continue;
+        }
+
+        if let (Some(speed), Some(seconds)) = (pace, receiver.seconds) {
+            if let Some(last) = last_seconds.filter(|last| seconds > *last) {
+                let gap = (seconds - last).min(MAX_REPLAY_GAP_S) / speed;
+                std::thread::sleep(Duration::from_secs_f64(gap));
+            }
+            last_seconds = Some(seconds);
+        }
+
+        match receiver.fix() {
+            Some(fix) => {
+                if last_emit.is_some_and(|at| at.elapsed() < EMIT_INTERVAL) && pace.is_none() {
+                    continue;
+                }
+                last_emit = Some(Instant::now());
+                let _ = app.emit(FIX_EVENT, &fix);
+                gps.record(app, fix);
+            }
+            None => gps.set_state(app, GpsState::Searching, None),
+        }
+    }
+    Ok(())
+}
+
+/// Connection loop for one source; reconnects until stopped.
+fn run(app: &AppHandle, source: Source, stop: &AtomicBool) {
+    let gps = app.state::<Gps>();
+    let pace = match &source {
+        Source::Replay { speed, .. } => Some(*speed),
+        _ => None,
+    };
+    eprintln!("[gps] Reading {}", source.describe());
+
+    while !stop.load(Ordering::Acquire) {
+        gps.set_state(app, GpsState::Connecting, None);
+        let result = connect(&source).and_then(|mut reader| pump(app, reader.as_mut(), stop, pace));
+        if stop.load(Ordering::Acquire) {
+            break;
+        }
+        match (&source, result) {
+            (Source::Replay { repeat: true, .. }, Ok(())) => continue,
+            (Source::Replay { .. }, Ok(())) => {
+                gps.set_state(app, GpsState::Stopped, None);
+                return;
+            }
+            (_, Ok(())) => gps.set_state(
+                app,
+                GpsState::Error,
+                Some("the receiver disconnected".into()),
+            ),
+            (_, Err(e)) => {
+                eprintln!("[gps] {}: {e}", source.describe());
+                gps.set_state(app, GpsState::Error, Some(e.to_string()));
+            }
+        }
+        // Sleep in steps so a stop or a new source takes effect promptly
+        let until = Instant::now() + RECONNECT_DELAY;
+        while Instant::now() < until && !stop.load(Ordering::Acquire) {
+            std::thread::sleep(Duration::from_millis(250));
+        }
+    }
+}
+
+/// Start the saved receiver, if it was running when the app closed.
+pub fn start_saved(app: &AppHandle) {
+    let config = load_config(app);
+    if let (true, Some(source)) = (config.enabled, config.source) {
+        if source.validate().is_ok() {
+            app.state::<Gps>().start(app, source);
+        }
+    }
+}
+
+// ─── Commands ───────────────────────────────────────────────────
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerialPortInfo {
+    pub path: String,
+    /// Maker and product for USB receivers.
+    pub description: Option<String>,
+}
+
+/// Connect to a receiver and remember it for next launch.
+#[tauri::command]
+pub fn gps_start(app: AppHandle, gps: State<'_, Gps>, source: Source) -> Result<GpsStatus> {
+    source.validate()?;
+    save_config(
+        &app,
+        &GpsConfig {
+            source: Some(source.clone()),
+            enabled: true,
+        },
+    )?;
+    gps.start(&app, source);
+    Ok(gps.status())
+}
+
+#[tauri::command]
+pub fn gps_stop(app: AppHandle, gps: State<'_, Gps>) -> Result<()> {
+    gps.stop(&app);
+    let mut config = load_config(&app);
+    config.enabled = false;
+    save_config(&app, &config)
+}
+
+#[tauri::command]
+pub fn gps_status(gps: State<'_, Gps>) -> GpsStatus {
+    gps.status()
+}
+
+/// Where we are now, or `None` without a fresh fix.
+#[tauri::command]
+pub fn gps_fix(gps: State<'_, Gps>) -> Option<Fix> {
+    gps.current()
+}
+
+#[tauri::command]
+pub fn gps_config(app: AppHandle) -> GpsConfig {
+    load_config(&app)
+}
+
+#[tauri::command]
+pub fn gps_serial_ports() -> Result<Vec<SerialPortInfo>> {
+    let ports = serialport::available_ports()
+        .map_err(|e| Error::Invalid(format!("could not list serial ports: {e}")))?;
+    Ok(ports
+        .into_iter()
+        .map(|port| SerialPortInfo {
+            description: match port.port_type {
+                serialport::SerialPortType::UsbPort(usb) => {
+                    let parts: Vec<String> = [usb.manufacturer, usb.product]
+                        .into_iter()
+                        .flatten()
+                        .collect();
+                    (!parts.is_empty()).then(|| parts.join(" "))
+                }
+                _ => None,
+            },
+            path: port.port_name,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GGA: &str = "$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47";
+    const RMC: &str = "$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A";
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn gga_gives_a_fix() {
+        let mut receiver = Receiver::default();
+        assert!(receiver.apply(GGA));
+        let fix = receiver.fix().unwrap();
+        assert!(close(fix.point.lat, 48.0 + 7.038 / 60.0));
+        assert!(close(fix.point.lng, 11.0 + 31.0 / 60.0));
+        assert_eq!(fix.quality, FixQuality::Gps);
+        assert_eq!(fix.satellites, Some(8));
+        assert_eq!(fix.hdop, Some(0.9));
+        assert_eq!(fix.altitude_m, Some(545.4));
+        // No date until an RMC arrives
+        assert_eq!(fix.time, None);
+    }
+
+    #[test]
+    fn rmc_gives_speed_course_and_date() {
+        let mut receiver = Receiver::default();
+        assert!(receiver.apply(RMC));
+        let fix = receiver.fix().unwrap();
+        assert!(close(fix.point.lat, 48.0 + 7.038 / 60.0));
+        assert!(close(fix.speed_kmh.unwrap(), 22.4 * KNOTS_TO_KMH));
+        assert_eq!(fix.course_deg, Some(84.4));
+        assert_eq!(fix.time.as_deref(), Some("1994-03-23T12:35:19Z"));
+    }
+
+    #[test]
+    fn void_rmc_is_no_fix() {
+        let mut receiver = Receiver::default();
+        assert!(receiver.apply("$GPRMC,123519,V,,,,,,,230394,,"));
+        assert!(receiver.fix().is_none());
+    }
+
+    #[test]
+    fn bad_checksum_is_ignored() {
+        let mut receiver = Receiver::default();
+        assert!(!receiver.apply(&GGA.replace("*47", "*48")));
+        assert!(!receiver.apply(&GGA.replace("*47", "*ZZ")));
+        assert!(!receiver.apply(&GGA.replace("*47", "*")));
+        assert!(receiver.fix().is_none());
+    }
+
+    #[test]
+    fn missing_checksum_is_accepted() {
+        let mut receiver = Receiver::default();
+        assert!(receiver.apply(GGA.trim_end_matches("*47")));
+        assert!(receiver.fix().is_some());
+    }
+
+    #[test]
+    fn non_ascii_time_and_date_are_skipped() {
+        assert_eq!(seconds_of_day("1é3456"), None);
+        assert_eq!(seconds_of_day("12é456"), None);
+        assert_eq!(nmea_degrees("é1.5", "N"), None);
+
+        let mut receiver = Receiver::default();
+        receiver.apply("$GPRMC,1é3519,A,4807.038,N,01131.000,E,022.4,084.4,2é0394,003.1,W");
+        receiver.apply("$GPGGA,12351é,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,");
+        receiver.apply("$GPGGA,123519,4é07.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,");
+        let fix = receiver.fix().unwrap();
+        assert_eq!(fix.time, None);
+        assert!(close(fix.point.lat, 48.0 + 7.038 / 60.0));
+    }
+
+    /// Hands out the chunks in order, with a timeout wherever a chunk is `None`.
+    struct Chunks(Vec<Option<Vec<u8>>>);
+
+    impl Read for Chunks {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.0.is_empty() {
+                return Ok(0);
+            }
+            match self.0.remove(0) {
+                Some(mut chunk) => {
+                    let n = chunk.len().min(buf.len());
+                    buf[..n].copy_from_slice(&chunk[..n]);
+                    if n < chunk.len() {
+                        self.0.insert(0, Some(chunk.split_off(n)));
+                    }
+                    Ok(n)
+                }
+                None => Err(ErrorKind::WouldBlock.into()),
+            }
+        }
+    }
+
+    #[test]
+    fn oversized_lines_are_dropped_whole() {
+        let long = vec![b'x'; MAX_LINE * 2];
+        let mut reader = BufReader::new(Chunks(vec![
+            Some(b"$GPGGA,1".to_vec()),
+            None,
+            Some(b"23\r\n".to_vec()),
+            Some(long[..MAX_LINE].to_vec()),
+            None,
+            Some(long[MAX_LINE..].to_vec()),
+            None,
+            Some(b"tail of the long line\nnext\nlast".to_vec()),
+        ]));
+        let mut lines = Lines::default();
+        let mut read = Vec::new();
+        loop {
+            match lines.next(&mut reader) {
+                Ok(Some(text)) => read.push(text),
+                Ok(None) => break,
+                Err(e) => assert_eq!(e.kind(), ErrorKind::WouldBlock),
+            }
+            assert!(lines.buf.len() <= MAX_LINE);
+        }
+        assert_eq!(read, ["$GPGGA,123", "next", "last"]);
+    }
+}
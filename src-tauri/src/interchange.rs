@@ -0,0 +1,1261 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tauri::{AppHandle, State};
+use tauri_plugin_dialog::DialogExt;
+
+use crate::db::Db;
+use crate::error::{Error, Result};
+use crate::evacuation::{self, RouteIssue, SpeedProfiles};
+use crate::geo::Point;
+use crate::geofence::{FenceKind, NewGeofence, Shape};
+use crate::mirror;
+
+/// Partner files bigger than this are not hand-made zone or route lists.
+const MAX_IMPORT_BYTES: u64 = 50 * 1024 * 1024;
+
+const INCIDENT_TYPES: &[&str] = &[
+    "natural_disaster",
+    "medical_emergency",
+    "infrastructure_failure",
+    "industrial_accident",
+    "security_incident",
+    "fire",
+    "flood",
+    "earthquake",
+    "other",
+];
+const RESOURCE_TYPES: &[&str] = &[
+    "medical",
+    "food",
+    "water",
+    "shelter",
+    "transportation",
+    "vehicle",
+    "medical_equipment",
+    "personnel",
+    "emergency_supplies",
+    "communication_equipment",
+    "other",
+];
+const SEVERITIES: &[&str] = &["low", "medium", "high", "critical"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Format {
+    Geojson,
+    Kml,
+    Gpx,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> Result<Self> {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase);
+        match ext.as_deref() {
+            Some("geojson" | "json") => Ok(Format::Geojson),
+            Some("kml") => Ok(Format::Kml),
+            Some("gpx") => Ok(Format::Gpx),
+            _ => Err(Error::Invalid(
+                "choose a .geojson, .kml or .gpx file".into(),
+            )),
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Geojson => "geojson",
+            Format::Kml => "kml",
+            Format::Gpx => "gpx",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Format::Geojson => "GeoJSON",
+            Format::Kml => "KML",
+            Format::Gpx => "GPX",
+        }
+    }
+}
+
+/// Records that can be exchanged with partner agencies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    Incident,
+    Resource,
+    Sos,
+    EvacuationRoute,
+}
+
+impl Kind {
+    fn table(self) -> &'static str {
+        match self {
+            Kind::Incident => "incidents",
+            Kind::Resource => "resources",
+            Kind::Sos => "sos_broadcasts",
+            Kind::EvacuationRoute => "evacuation_routes",
+        }
+    }
+
+    /// Record field shown as the feature's name.
+    fn title_field(self) -> &'static str {
+        match self {
+            Kind::Incident => "title",
+            Kind::Resource | Kind::EvacuationRoute => "name",
+            Kind::Sos => "message",
+        }
+    }
+}
+
+// ─── Features ───────────────────────────────────────────────────
+
+#[derive(Debug, Clone)]
+enum Geometry {
+    Point(Point),
+    Line(Vec<Point>),
+    /// Outer ring only; holes don't matter for incident zones and staging
+    /// areas.
+    Polygon(Vec<Point>),
+}
+
+/// One feature of an imported file, whatever the format.
+#[derive(Debug, Default)]
+struct Feature {
+    /// Position in the file, from 0, for error messages.
+    index: usize,
+    name: Option<String>,
+    description: Option<String>,
+    geometry: Option<Geometry>,
+    properties: Map<String, Value>,
+}
+
+/// Why one feature of an import was skipped.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeatureError {
+    /// Position of the feature in the file, from 0.
+    pub index: usize,
+    pub name: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub file_name: String,
+    pub format: Format,
+    /// New records (with fresh ids) ready to insert; the frontend adds
+    /// `created_by` / `sender_id` and queues them.
+    pub records: Vec<Value>,
+    /// Polygons of imported incidents and resources, to add as incident
+    /// zones and staging areas once their records are queued.
+    pub zones: Vec<NewGeofence>,
+    pub errors: Vec<FeatureError>,
+}
+
+fn position(lng: f64, lat: f64) -> std::result::Result<Point, String> {
+    let point = Point { lat, lng };
+    point
+        .validate()
+        .map(|_| point)
+        .map_err(|_| format!("{lat}, {lng} is not a valid position"))
+}
+
+fn text(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_owned()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+// ─── GeoJSON ────────────────────────────────────────────────────
+
+fn geojson_position(value: &Value) -> std::result::Result<Point, String> {
+    match value.as_array().map(Vec::as_slice) {
+        Some([lng, lat, ..]) => match (lng.as_f64(), lat.as_f64()) {
+            (Some(lng), Some(lat)) => position(lng, lat),
+            _ => Err("coordinates must be numbers".into()),
+        },
+        _ => Err("a position needs a longitude and a latitude".into()),
+    }
+}
+
+fn geojson_positions(value: &Value) -> std::result::Result<Vec<Point>, String> {
+    value
+        .as_array()
+        .ok_or("coordinates must be an array")?
+        .iter()
+        .map(geojson_position)
+        .collect()
+}
+
+fn geojson_geometry(geometry: &Value) -> std::result::Result<Option<Geometry>, String> {
+    if geometry.is_null() {
+        return Ok(None);
+    }
+    let coordinates = geometry.get("coordinates").unwrap_or(&Value::Null);
+    match geometry.get("type").and_then(Value::as_str) {
+        Some("Point") => Ok(Some(Geometry::Point(geojson_position(coordinates)?))),
+        Some("LineString") => Ok(Some(Geometry::Line(geojson_positions(coordinates)?))),
+        Some("Polygon") => {
+            let outer = coordinates.get(0).ok_or("a polygon needs an outer ring")?;
+            Ok(Some(Geometry::Polygon(geojson_positions(outer)?)))
+        }
+        Some(other) => Err(format!("{other} geometries are not supported")),
+        None => Err("geometry has no type".into()),
+    }
+}
+
+fn parse_geojson(bytes: &[u8]) -> Result<Vec<std::result::Result<Feature, FeatureError>>> {
+    let root: Value = serde_json::from_slice(bytes)
+        .map_err(|e| Error::Invalid(format!("not valid GeoJSON: {e}")))?;
+    let features: Vec<Value> = match root.get("type").and_then(Value::as_str) {
+        Some("FeatureCollection") => root
+            .get("features")
+            .and_then(Value::as_array)
+            .cloned()
+            .ok_or_else(|| Error::Invalid("FeatureCollection without features".into()))?,
+        Some("Feature") => vec![root],
+        Some(_) => vec![json!({ "type": "Feature", "geometry": root, "properties": {} })],
+        None => return Err(Error::Invalid("not a GeoJSON object".into())),
+    };
+
+    Ok(features
+        .into_iter()
+        .enumerate()
+        .map(|(index, feature)| {
+            let properties = feature
+                .get("properties")
+                .and_then(Value::as_object)
+                .cloned()
+                .unwrap_or_default();
+            let name = text(properties.get("name")).or_else(|| text(properties.get("title")));
+            let fail = |message: String| FeatureError {
+                index,
+                name: name.clone(),
+                message,
+            };
+            if feature.get("type").and_then(Value::as_str) != Some("Feature") {
+                return Err(fail("not a GeoJSON Feature".into()));
+            }
+            let geometry =
+                geojson_geometry(feature.get("geometry").unwrap_or(&Value::Null)).map_err(fail)?;
+            Ok(Feature {
+                index,
+                description: text(properties.get("description")),
+                name,
+                geometry,
+                properties,
+            })
+        })
+        .collect())
+}
+
+// ─── XML (KML, GPX) ─────────────────────────────────────────────
+
+/// Just enough of a DOM to walk KML, GPX and CAP. Names are local (no
+/// prefix).
+#[derive(Debug, Default)]
+pub struct Node {
+    pub name: String,
+    pub attrs: Vec<(String, String)>,
+    pub children: Vec<Node>,
+    pub text: String,
+}
+
+impl Node {
+    pub fn child(&self, name: &str) -> Option<&Node> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Node> {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+
+    /// Every element called `name` below this one, in document order.
+    pub fn descendants<'a>(&'a self, name: &str, out: &mut Vec<&'a Node>) {
+        for child in &self.children {
+            if child.name == name {
+                out.push(child);
+            }
+            child.descendants(name, out);
+        }
+    }
+
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn text_of(&self, name: &str) -> Option<String> {
+        let text = self.child(name)?.text.trim();
+        (!text.is_empty()).then(|| text.to_owned())
+    }
+}
+
+fn xml_error(e: impl std::fmt::Display) -> Error {
+    Error::Invalid(format!("not valid XML: {e}"))
+}
+
+fn element(e: &quick_xml::events::BytesStart) -> Result<Node> {
+    let mut attrs = Vec::new();
+    for attr in e.attributes() {
+        let attr = attr.map_err(xml_error)?;
+        attrs.push((
+            String::from_utf8_lossy(attr.key.local_name().as_ref()).into_owned(),
+            attr.unescape_value().map_err(xml_error)?.into_owned(),
+        ));
+    }
+    Ok(Node {
+        name: String::from_utf8_lossy(e.local_name().as_ref()).into_owned(),
+        attrs,
+        ..Default::default()
+    })
+}
+
+pub fn parse_xml(text: &str) -> Result<Node> {
+    let mut reader = Reader::from_str(text);
+    reader.config_mut().trim_text(true);
+    let mut stack = vec![Node::default()];
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(e) => stack.push(element(&e)?),
+            Event::Empty(e) => {
+                let node = element(&e)?;
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(node);
+                }
+            }
+            Event::End(_) => {
+                let node = stack.pop().ok_or_else(|| xml_error("unbalanced tags"))?;
+                stack
+                    .last_mut()
+                    .ok_or_else(|| xml_error("unbalanced tags"))?
+                    .children
+                    .push(node);
+            }
+            Event::Text(t) => {
+                if let Some(node) = stack.last_mut() {
+                    node.text.push_str(&t.unescape().map_err(xml_error)?);
+                }
+            }
+            Event::CData(c) => {
+                if let Some(node) = stack.last_mut() {
+                    node.text.push_str(&String::from_utf8_lossy(&c));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    match (stack.pop(), stack.is_empty()) {
+        (Some(document), true) => Ok(document),
+        _ => Err(xml_error("document ends inside an element")),
+    }
+}
+
+/// KML `<coordinates>`: whitespace-separated `lng,lat[,alt]` tuples.
+fn kml_coordinates(node: Option<&Node>) -> std::result::Result<Vec<Point>, String> {
+    let text = node.map(|n| n.text.as_str()).unwrap_or_default();
+    let points = text
+        .split_whitespace()
+        .map(|tuple| {
+            let mut parts = tuple.split(',').map(str::parse::<f64>);
+            match (parts.next(), parts.next()) {
+                (Some(Ok(lng)), Some(Ok(lat))) => position(lng, lat),
+                _ => Err(format!("bad coordinate {tuple:?}")),
+            }
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    if points.is_empty() {
+        return Err("geometry has no coordinates".into());
+    }
+    Ok(points)
+}
+
+fn kml_geometry(placemark: &Node) -> std::result::Result<Option<Geometry>, String> {
+    let mut container = placemark;
+    if let Some(multi) = placemark.child("MultiGeometry") {
+        if multi.children.len() != 1 {
+            return Err("MultiGeometry with several parts is not supported".into());
+        }
+        container = multi;
+    }
+    if let Some(point) = container.child("Point") {
+        let points = kml_coordinates(point.child("coordinates"))?;
+        return Ok(Some(Geometry::Point(points[0])));
+    }
+    if let Some(line) = container.child("LineString") {
+        return Ok(Some(Geometry::Line(kml_coordinates(
+            line.child("coordinates"),
+        )?)));
+    }
+    if let Some(polygon) = container.child("Polygon") {
+        let ring = polygon
+            .child("outerBoundaryIs")
+            .and_then(|b| b.child("LinearRing"))
+            .ok_or("polygon without an outer boundary")?;
+        return Ok(Some(Geometry::Polygon(kml_coordinates(
+            ring.child("coordinates"),
+        )?)));
+    }
+    if container
+        .children
+        .iter()
+        .any(|c| matches!(c.name.as_str(), "Model" | "Track" | "MultiTrack"))
+    {
+        return Err("only points, lines and polygons are supported".into());
+    }
+    Ok(None)
+}
+
+fn parse_kml(text: &str) -> Result<Vec<std::result::Result<Feature, FeatureError>>> {
+    let document = parse_xml(text)?;
+    if document.child("kml").is_none() {
+        return Err(Error::Invalid("not a KML document".into()));
+    }
+    let mut placemarks = Vec::new();
+    document.descendants("Placemark", &mut placemarks);
+
+    Ok(placemarks
+        .into_iter()
+        .enumerate()
+        .map(|(index, placemark)| {
+            let name = placemark.text_of("name");
+            let mut properties = Map::new();
+            if let Some(data) = placemark.child("ExtendedData") {
+                for field in data.children_named("Data") {
+                    if let (Some(key), Some(value)) = (field.attr("name"), field.text_of("value")) {
+                        properties.insert(key.to_owned(), Value::String(value));
+                    }
+                }
+                for schema in data.children_named("SchemaData") {
+                    for field in schema.children_named("SimpleData") {
+                        if let Some(key) = field.attr("name") {
+                            properties.insert(
+                                key.to_owned(),
+                                Value::String(field.text.trim().to_owned()),
+                            );
+                        }
+                    }
+                }
+            }
+            let geometry = kml_geometry(placemark).map_err(|message| FeatureError {
+                index,
+                name: name.clone(),
+                message,
+            })?;
+            Ok(Feature {
+                index,
+                description: placemark.text_of("description"),
+                name,
+                geometry,
+                properties,
+            })
+        })
+        .collect())
+}
+
+fn gpx_point(node: &Node) -> std::result::Result<Point, String> {
+    let number = |name: &str| {
+        node.attr(name)
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .ok_or_else(|| format!("<{}> without a numeric {name}", node.name))
+    };
+    position(number("lon")?, number("lat")?)
+}
+
+fn parse_gpx(text: &str) -> Result<Vec<std::result::Result<Feature, FeatureError>>> {
+    let document = parse_xml(text)?;
+    let gpx = document
+        .child("gpx")
+        .ok_or_else(|| Error::Invalid("not a GPX document".into()))?;
+
+    // In file order, so feature numbers in errors match the document
+    Ok(gpx
+        .children
+        .iter()
+        .filter(|node| matches!(node.name.as_str(), "wpt" | "rte" | "trk"))
+        .enumerate()
+        .map(|(index, node)| {
+            let name = node.text_of("name");
+            let fail = |message: String| FeatureError {
+                index,
+                name: name.clone(),
+                message,
+            };
+            let geometry = match node.name.as_str() {
+                "wpt" => Geometry::Point(gpx_point(node).map_err(fail)?),
+                "rte" => Geometry::Line(
+                    node.children_named("rtept")
+                        .map(gpx_point)
+                        .collect::<std::result::Result<_, _>>()
+                        .map_err(fail)?,
+                ),
+                _ => Geometry::Line(
+                    node.children_named("trkseg")
+                        .flat_map(|seg| seg.children_named("trkpt"))
+                        .map(gpx_point)
+                        .collect::<std::result::Result<_, _>>()
+                        .map_err(fail)?,
+                ),
+            };
+            let mut properties = Map::new();
+            if let Some(kind) = node.text_of("type") {
+                properties.insert("type".into(), Value::String(kind));
+            }
+            Ok(Feature {
+                index,
+                description: node.text_of("desc").or_else(|| node.text_of("cmt")),
+                name,
+                geometry: Some(geometry),
+                properties,
+            })
+        })
+        .collect())
+}
+
+// ─── Features → records ─────────────────────────────────────────
+
+/// A property if it is one of `allowed`, else `default`.
+fn choice(feature: &Feature, key: &str, allowed: &[&str], default: &str) -> String {
+    text(feature.properties.get(key))
+        .map(|v| v.to_lowercase().replace([' ', '-'], "_"))
+        .filter(|v| allowed.contains(&v.as_str()))
+        .unwrap_or_else(|| default.to_owned())
+}
+
+fn number(feature: &Feature, key: &str) -> Value {
+    match feature.properties.get(key) {
+        Some(Value::Number(n)) => Value::Number(n.clone()),
+        Some(Value::String(s)) => s
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map_or(Value::Null, Value::Number),
+        _ => Value::Null,
+    }
+}
+
+fn optional_text(feature: &Feature, key: &str) -> Value {
+    text(feature.properties.get(key)).map_or(Value::Null, Value::String)
+}
+
+/// A point for the record: the point itself, or a polygon's centre along
+/// with the polygon, which is kept as a geofence of `kind`.
+fn place(
+    geometry: &Geometry,
+    kind: FenceKind,
+    subject_id: &str,
+) -> std::result::Result<(Point, Option<NewGeofence>), String> {
+    match geometry {
+        Geometry::Point(p) => Ok((*p, None)),
+        Geometry::Polygon(ring) => {
+            let shape = Shape::Polygon {
+                points: ring.clone(),
+            };
+            shape.validate().map_err(|e| e.to_string())?;
+            // a closed ring repeats its first point
+            let open = match (ring.first(), ring.last()) {
+                (Some(a), Some(b)) if ring.len() > 1 && a.lat == b.lat && a.lng == b.lng => {
+                    &ring[..ring.len() - 1]
+                }
+                _ => &ring[..],
+            };
+            let n = open.len() as f64;
+            let c = Point {
+                lat: open.iter().map(|p| p.lat).sum::<f64>() / n,
+                lng: open.iter().map(|p| p.lng).sum::<f64>() / n,
+            };
+            let zone = NewGeofence {
+                kind,
+                subject_id: subject_id.to_owned(),
+                name: None,
+                shape,
+            };
+            Ok((c, Some(zone)))
+        }
+        Geometry::Line(_) => Err("a line can only be imported as an evacuation route".into()),
+    }
+}
+
+fn to_record(
+    kind: Kind,
+    feature: &Feature,
+    profiles: &SpeedProfiles,
+) -> std::result::Result<(Value, Option<NewGeofence>), String> {
+    let geometry = feature.geometry.as_ref().ok_or("feature has no geometry")?;
+    let name = || {
+        feature
+            .name
+            .clone()
+            .ok_or_else(|| "feature has no name".to_owned())
+    };
+    let id = uuid::Uuid::new_v4().to_string();
+    let location_name = optional_text(feature, "location_name");
+    let mut zone = None;
+
+    let record = match kind {
+        Kind::Incident => {
+            let (point, polygon) = place(geometry, FenceKind::IncidentZone, &id)?;
+            zone = polygon;
+            json!({
+                "id": id,
+                "title": name()?,
+                "description": feature.description,
+                "type": choice(feature, "type", INCIDENT_TYPES, "other"),
+                "severity": choice(feature, "severity", SEVERITIES, "medium"),
+                "status": "reported",
+                "location_name": location_name,
+                "latitude": point.lat,
+                "longitude": point.lng,
+                "affected_radius_km": number(feature, "affected_radius_km"),
+                "estimated_affected_people": number(feature, "estimated_affected_people"),
+            })
+        }
+        Kind::Resource => {
+            let (point, polygon) = place(geometry, FenceKind::Staging, &id)?;
+            zone = polygon;
+            json!({
+                "id": id,
+                "name": name()?,
+                "description": feature.description,
+                "type": choice(feature, "type", RESOURCE_TYPES, "other"),
+                "status": "available",
+                "capacity": number(feature, "capacity"),
+                "contact_info": optional_text(feature, "contact_info"),
+                "location_name": location_name,
+                "latitude": point.lat,
+                "longitude": point.lng,
+            })
+        }
+        Kind::Sos => {
+            let Geometry::Point(point) = geometry else {
+                return Err("an SOS broadcast must be a point".into());
+            };
+            json!({
+                "id": id,
+                "message": feature.description.clone().or_else(|| feature.name.clone()),
+                "severity": choice(feature, "severity", SEVERITIES, "high"),
+                "location_name": location_name,
+                "latitude": point.lat,
+                "longitude": point.lng,
+                "is_active": true,
+            })
+        }
+        Kind::EvacuationRoute => {
+            let Geometry::Line(points) = geometry else {
+                return Err("an evacuation route must be a line".into());
+            };
+            if points.len() > evacuation::MAX_WAYPOINTS {
+                return Err(format!(
+                    "routes are limited to {} waypoints",
+                    evacuation::MAX_WAYPOINTS
+                ));
+            }
+            let metrics = evacuation::metrics(points, profiles);
+            for issue in &metrics.issues {
+                match issue {
+                    RouteIssue::TooFewWaypoints => {
+                        return Err("a route needs at least two points".into())
+                    }
+                    RouteIssue::ZeroLength => return Err("route has no length".into()),
+                    _ => {}
+                }
+            }
+            json!({
+                "id": id,
+                "name": name()?,
+                "description": feature.description,
+                "waypoints": points,
+                "distance_km": metrics.distance_km,
+                "estimated_time_minutes": metrics.minutes.driving,
+                "capacity": number(feature, "capacity"),
+                "is_active": true,
+            })
+        }
+    };
+    Ok((record, zone))
+}
+
+fn import(path: &Path, kind: Kind, profiles: &SpeedProfiles) -> Result<ImportReport> {
+    let format = Format::from_path(path)?;
+    if std::fs::metadata(path)?.len() > MAX_IMPORT_BYTES {
+        return Err(Error::Invalid(format!(
+            "files over {} MB can't be imported",
+            MAX_IMPORT_BYTES / (1024 * 1024)
+        )));
+    }
+    let bytes = std::fs::read(path)?;
+    let features = match format {
+        Format::Geojson => parse_geojson(&bytes)?,
+        Format::Kml | Format::Gpx => {
+            let text = String::from_utf8(bytes)
+                .map_err(|_| Error::Invalid("file is not UTF-8 text".into()))?;
+            if format == Format::Kml {
+                parse_kml(&text)?
+            } else {
+                parse_gpx(&text)?
+            }
+        }
+    };
+
+    let mut records = Vec::new();
+    let mut zones = Vec::new();
+    let mut errors = Vec::new();
+    for feature in features {
+        match feature {
+            Ok(feature) => match to_record(kind, &feature, profiles) {
+                Ok((record, zone)) => {
+                    records.push(record);
+                    zones.extend(zone);
+                }
+                Err(message) => errors.push(FeatureError {
+                    index: feature.index,
+                    name: feature.name,
+                    message,
+                }),
+            },
+            Err(error) => errors.push(error),
+        }
+    }
+    Ok(ImportReport {
+        file_name: path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        format,
+        records,
+        zones,
+        errors,
+    })
+}
+
+// ─── Records → files ────────────────────────────────────────────
+
+fn record_geometry(kind: Kind, record: &Value) -> Option<Geometry> {
+    if kind == Kind::EvacuationRoute {
+        let points: Vec<Point> = serde_json::from_value(record.get("waypoints")?.clone()).ok()?;
+        return (points.len() >= 2).then_some(Geometry::Line(points));
+    }
+    Point::from_record(record).map(Geometry::Point)
+}
+
+/// Record fields carried as feature properties; position fields live in
+/// the geometry instead.
+fn properties(record: &Value) -> Map<String, Value> {
+    let mut properties = record.as_object().cloned().unwrap_or_default();
+    for key in ["latitude", "longitude", "waypoints"] {
+        properties.remove(key);
+    }
+    properties
+}
+
+fn title(kind: Kind, record: &Value) -> String {
+    text(record.get(kind.title_field())).unwrap_or_else(|| match kind {
+        Kind::Sos => "SOS".into(),
+        _ => "Untitled".into(),
+    })
+}
+
+fn to_geojson(kind: Kind, records: &[(Geometry, &Value)]) -> Result<String> {
+    let features: Vec<Value> = records
+        .iter()
+        .map(|(geometry, record)| {
+            let geometry = match geometry {
+                Geometry::Point(p) => json!({ "type": "Point", "coordinates": [p.lng, p.lat] }),
+                Geometry::Line(points) => json!({
+                    "type": "LineString",
+                    "coordinates": points.iter().map(|p| [p.lng, p.lat]).collect::<Vec<_>>(),
+                }),
+                Geometry::Polygon(points) => json!({
+                    "type": "Polygon",
+                    "coordinates": [points.iter().map(|p| [p.lng, p.lat]).collect::<Vec<_>>()],
+                }),
+            };
+            let mut properties = properties(record);
+            properties.insert("name".into(), Value::String(title(kind, record)));
+            json!({
+                "type": "Feature",
+                "id": record.get("id"),
+                "geometry": geometry,
+                "properties": properties,
+            })
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&json!({
+        "type": "FeatureCollection",
+        "features": features,
+    }))?)
+}
+
+pub fn escape(text: &str) -> String {
+    quick_xml::escape::escape(text).into_owned()
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn kml_tuples(points: &[Point]) -> String {
+    let tuples: Vec<String> = points
+        .iter()
+        .map(|p| format!("{},{}", p.lng, p.lat))
+        .collect();
+    tuples.join(" ")
+}
+
+fn to_kml(kind: Kind, records: &[(Geometry, &Value)]) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <kml xmlns=\"http://www.opengis.net/kml/2.2\">\n<Document>\n",
+    );
+    let _ = writeln!(out, "<name>DisasterConnect {}</name>", kind.table());
+    for (geometry, record) in records {
+        out.push_str("<Placemark>\n");
+        let _ = writeln!(out, "  <name>{}</name>", escape(&title(kind, record)));
+        if let Some(description) = text(record.get("description")) {
+            let _ = writeln!(out, "  <description>{}</description>", escape(&description));
+        }
+        out.push_str("  <ExtendedData>\n");
+        for (key, value) in properties(record) {
+            if let Some(value) = scalar(&value) {
+                let _ = writeln!(
+                    out,
+                    "    <Data name=\"{}\"><value>{}</value></Data>",
+                    escape(&key),
+                    escape(&value)
+                );
+            }
+        }
+        out.push_str("  </ExtendedData>\n");
+        match geometry {
+            Geometry::Point(p) => {
+                let _ = writeln!(
+                    out,
+                    "  <Point><coordinates>{},{}</coordinates></Point>",
+                    p.lng, p.lat
+                );
+            }
+            Geometry::Line(points) => {
+                let _ = writeln!(
+                    out,
+                    "  <LineString><tessellate>1</tessellate><coordinates>{}</coordinates></LineString>",
+                    kml_tuples(points)
+                );
+            }
+            Geometry::Polygon(points) => {
+                let _ = writeln!(
+                    out,
+                    "  <Polygon><outerBoundaryIs><LinearRing><coordinates>{}</coordinates></LinearRing></outerBoundaryIs></Polygon>",
+                    kml_tuples(points)
+                );
+            }
+        }
+        out.push_str("</Placemark>\n");
+    }
+    out.push_str("</Document>\n</kml>\n");
+    out
+}
+
+fn to_gpx(kind: Kind, records: &[(Geometry, &Value)]) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <gpx version=\"1.1\" creator=\"DisasterConnect\" xmlns=\"http://www.topografix.com/GPX/1/1\">\n",
+    );
+    let description = |record: &Value| {
+        text(record.get("description"))
+            .map(|d| format!("<desc>{}</desc>", escape(&d)))
+            .unwrap_or_default()
+    };
+    let kind_of = |record: &Value| {
+        record
+            .get("type")
+            .and_then(scalar)
+            .map(|t| format!("<type>{}</type>", escape(&t)))
+            .unwrap_or_default()
+    };
+    // GPX wants every waypoint before any route
+    for (geometry, record) in records {
+        if let Geometry::Point(p) = geometry {
+            let _ = writeln!(
+                out,
+                "<wpt lat=\"{}\" lon=\"{}\"><name>{}</name>{}{}</wpt>",
+                p.lat,
+                p.lng,
+                escape(&title(kind, record)),
+                description(record),
+                kind_of(record)
+            );
+        }
+    }
+    for (geometry, record) in records {
+        if let Geometry::Line(points) | Geometry::Polygon(points) = geometry {
+            let _ = writeln!(
+                out,
+                "<rte><name>{}</name>{}",
+                escape(&title(kind, record)),
+                description(record)
+            );
+            for p in points {
+                let _ = writeln!(out, "  <rtept lat=\"{}\" lon=\"{}\"/>", p.lat, p.lng);
+            }
+            out.push_str("</rte>\n");
+        }
+    }
+    out.push_str("</gpx>\n");
+    out
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportReport {
+    pub path: String,
+    pub exported: usize,
+    /// Records left out because they have no location.
+    pub skipped: usize,
+}
+
+// ─── Commands ───────────────────────────────────────────────────
+
+pub fn path_of(file: tauri_plugin_dialog::FilePath) -> Result<PathBuf> {
+    file.into_path()
+        .map_err(|e| Error::Invalid(format!("can't use that file: {e}")))
+}
+
+/// Export cached records (all of `kind`, or just `ids`) to a file the user
+/// picks. Returns `None` if the save dialog was cancelled.
+#[tauri::command]
+pub async fn interchange_export(
+    app: AppHandle,
+    db: State<'_, Db>,
+    kind: Kind,
+    format: Format,
+    ids: Option<Vec<String>>,
+) -> Result<Option<ExportReport>> {
+    let records: Vec<Value> = db.with(|conn| match &ids {
+        Some(ids) => ids
+            .iter()
+            .filter_map(|id| mirror::get(conn, kind.table(), id).transpose())
+            .collect(),
+        None => mirror::all(conn, kind.table()),
+    })?;
+
+    let dialog = app
+        .dialog()
+        .file()
+        .add_filter(format.label(), &[format.extension()])
+        .set_file_name(format!("{}.{}", kind.table(), format.extension()));
+    let Some(file) =
+        tauri::async_runtime::spawn_blocking(move || dialog.blocking_save_file()).await?
+    else {
+        return Ok(None);
+    };
+    let path = path_of(file)?;
+
+    let located: Vec<(Geometry, &Value)> = records
+        .iter()
+        .filter_map(|record| Some((record_geometry(kind, record)?, record)))
+        .collect();
+    let contents = match format {
+        Format::Geojson => to_geojson(kind, &located)?,
+        Format::Kml => to_kml(kind, &located),
+        Format::Gpx => to_gpx(kind, &located),
+    };
+    std::fs::write(&path, contents)?;
+
+    Ok(Some(ExportReport {
+        path: path.display().to_string(),
+        exported: located.len(),
+        skipped: records.len() - located.len(),
+    }))
+}
+
+/// Read a GeoJSON, KML or GPX file the user picks into new `kind` records.
+/// Features that can't become a record are reported, not fatal. Returns
+/// `None` if the open dialog was cancelled.
+#[tauri::command]
+pub async fn interchange_import(app: AppHandle, kind: Kind) -> Result<Option<ImportReport>> {
+    let dialog = app
+        .dialog()
+        .file()
+        .add_filter("GeoJSON, KML or GPX", &["geojson", "json", "kml", "gpx"]);
+    let Some(file) =
+        tauri::async_runtime::spawn_blocking(move || dialog.blocking_pick_file()).await?
+    else {
+        return Ok(None);
+    };
+    let path = path_of(file)?;
+    let profiles = evacuation::load_profiles(&app);
+    let report =
+        tauri::async_runtime::spawn_blocking(move || import(&path, kind, &profiles)).await??;
+    Ok(Some(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gpx_features_keep_document_order() {
+        let gpx = r#"<gpx version="1.1" xmlns="http://www.topografix.com/GPX/1/1">
+            <trk><name>track</name><trkseg><trkpt lat="1" lon="1"/><trkpt lat="2" lon="2"/></trkseg></trk>
+            <wpt lat="3" lon="3"><name>first stop</name></wpt>
+            <rte><name>route</name><rtept lat="4" lon="4"/><rtept lat="5" lon="5"/></rte>
+            <wpt lat="6" lon="6"><name>second stop</name></wpt>
+        </gpx>"#;
+        let features = parse_gpx(gpx).unwrap();
+        let names: Vec<(usize, Option<String>)> = features
+            .into_iter()
+            .map(|f| f.map(|f| (f.index, f.name)).unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                (0, Some("track".into())),
+                (1, Some("first stop".into())),
+                (2, Some("route".into())),
+                (3, Some("second stop".into())),
+            ]
+        );
+    }
+
+    fn features(
+        parsed: Vec<std::result::Result<Feature, FeatureError>>,
+    ) -> (Vec<Feature>, Vec<FeatureError>) {
+        let mut ok = Vec::new();
+        let mut failed = Vec::new();
+        for feature in parsed {
+            match feature {
+                Ok(feature) => ok.push(feature),
+                Err(error) => failed.push(error),
+            }
+        }
+        (ok, failed)
+    }
+
+    fn import_text(name: &str, text: &str, kind: Kind) -> ImportReport {
+        let dir = std::env::temp_dir().join(format!("interchange-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, text).unwrap();
+        let report = import(&path, kind, &SpeedProfiles::default());
+        let _ = std::fs::remove_dir_all(&dir);
+        report.unwrap()
+    }
+
+    const SQUARE: &str = "[[10, 20], [10.1, 20], [10.1, 20.1], [10, 20.1], [10, 20]]";
+
+    #[test]
+    fn geojson_features_collections_and_bare_geometries() {
+        let collection = format!(
+            r#"{{"type": "FeatureCollection", "features": [
+                {{"type": "Feature", "geometry": {{"type": "Point", "coordinates": [151.2, -33.9]}},
+                  "properties": {{"title": "Bridge out", "description": "closed", "severity": "high"}}}},
+                {{"type": "Feature", "geometry": {{"type": "Polygon", "coordinates": [{SQUARE}]}},
+                  "properties": {{"name": "Flood zone"}}}},
+                {{"type": "Feature", "geometry": {{"type": "MultiPoint", "coordinates": []}},
+                  "properties": {{"name": "many"}}}},
+                {{"type": "Feature", "geometry": {{"type": "Point", "coordinates": [200, 0]}}}},
+                {{"type": "Feature", "geometry": null, "properties": {{"name": "nowhere"}}}}
+            ]}}"#
+        );
+        let (ok, failed) = features(parse_geojson(collection.as_bytes()).unwrap());
+
+        assert_eq!(ok.len(), 3);
+        assert_eq!(ok[0].name.as_deref(), Some("Bridge out"));
+        assert_eq!(ok[0].description.as_deref(), Some("closed"));
+        assert_eq!(ok[0].properties["severity"], "high");
+        assert!(
+            matches!(ok[0].geometry, Some(Geometry::Point(p)) if p.lng == 151.2 && p.lat == -33.9)
+        );
+        assert!(matches!(&ok[1].geometry, Some(Geometry::Polygon(ring)) if ring.len() == 5));
+        assert_eq!((ok[2].index, ok[2].geometry.is_none()), (4, true));
+
+        let failed: Vec<(usize, Option<&str>)> = failed
+            .iter()
+            .map(|e| (e.index, e.name.as_deref()))
+            .collect();
+        assert_eq!(failed, [(2, Some("many")), (3, None)]);
+
+        let bare = parse_geojson(br#"{"type": "LineString", "coordinates": [[0, 0], [1, 1]]}"#);
+        let (ok, _) = features(bare.unwrap());
+        assert!(matches!(&ok[0].geometry, Some(Geometry::Line(points)) if points.len() == 2));
+
+        assert!(parse_geojson(b"not json").is_err());
+        assert!(parse_geojson(br#"{"features": []}"#).is_err());
+    }
+
+    #[test]
+    fn kml_placemarks_with_extended_data() {
+        let kml = r#"<?xml version="1.0"?>
+            <kml xmlns="http://www.opengis.net/kml/2.2"><Document><Folder>
+              <Placemark><name>Shelter &amp; food</name><description>school gym</description>
+                <ExtendedData>
+                  <Data name="type"><value>shelter</value></Data>
+                  <SchemaData><SimpleData name="capacity"> 120 </SimpleData></SchemaData>
+                </ExtendedData>
+                <Point><coordinates>151.2,-33.9,0</coordinates></Point></Placemark>
+              <Placemark><name>Zone</name><MultiGeometry><Polygon><outerBoundaryIs><LinearRing>
+                <coordinates>10,20 10.1,20 10.1,20.1 10,20.1 10,20</coordinates>
+              </LinearRing></outerBoundaryIs></Polygon></MultiGeometry></Placemark>
+              <Placemark><name>Road</name><LineString><coordinates>0,0 1,1</coordinates></LineString></Placemark>
+              <Placemark><name>bad</name><Point><coordinates>east,north</coordinates></Point></Placemark>
+              <Placemark><name>model</name><Model/></Placemark>
+            </Folder></Document></kml>"#;
+        let (ok, failed) = features(parse_kml(kml).unwrap());
+
+        assert_eq!(ok.len(), 3);
+        assert_eq!(ok[0].name.as_deref(), Some("Shelter & food"));
+        assert_eq!(ok[0].description.as_deref(), Some("school gym"));
+        assert_eq!(ok[0].properties["type"], "shelter");
+        assert_eq!(ok[0].properties["capacity"], "120");
+        assert!(
+            matches!(ok[0].geometry, Some(Geometry::Point(p)) if p.lng == 151.2 && p.lat == -33.9)
+        );
+        assert!(matches!(&ok[1].geometry, Some(Geometry::Polygon(ring)) if ring.len() == 5));
+        assert!(matches!(&ok[2].geometry, Some(Geometry::Line(points)) if points.len() == 2));
+
+        let failed: Vec<(usize, Option<&str>)> = failed
+            .iter()
+            .map(|e| (e.index, e.name.as_deref()))
+            .collect();
+        assert_eq!(failed, [(3, Some("bad")), (4, Some("model"))]);
+
+        assert!(parse_kml("<gpx/>").is_err());
+    }
+
+    #[test]
+    fn polygon_incidents_keep_their_zone() {
+        let geojson = format!(
+            r#"{{"type": "FeatureCollection", "features": [
+                {{"type": "Feature", "geometry": {{"type": "Polygon", "coordinates": [{SQUARE}]}},
+                  "properties": {{"name": "Flood zone", "type": "flood", "severity": "nope"}}}},
+                {{"type": "Feature", "geometry": {{"type": "Point", "coordinates": [1, 2]}},
+                  "properties": {{"name": "Fire", "affected_radius_km": "2.5"}}}},
+                {{"type": "Feature", "geometry": {{"type": "LineString", "coordinates": [[0, 0], [1, 1]]}},
+                  "properties": {{"name": "Road"}}}},
+                {{"type": "Feature", "geometry": {{"type": "Polygon", "coordinates": [[[0, 0], [1, 1]]]}},
+                  "properties": {{"name": "Sliver"}}}}
+            ]}}"#
+        );
+        let report = import_text("zones.geojson", &geojson, Kind::Incident);
+
+        assert_eq!(report.file_name, "zones.geojson");
+        assert_eq!(report.format, Format::Geojson);
+        assert_eq!(report.records.len(), 2);
+        let zone = &report.records[0];
+        assert_eq!(zone["title"], "Flood zone");
+        assert_eq!(zone["type"], "flood");
+        assert_eq!(zone["severity"], "medium");
+        assert_eq!(zone["status"], "reported");
+        assert!((zone["latitude"].as_f64().unwrap() - 20.05).abs() < 1e-9);
+        assert!((zone["longitude"].as_f64().unwrap() - 10.05).abs() < 1e-9);
+        assert!(zone["affected_radius_km"].is_null());
+        assert_eq!(report.records[1]["affected_radius_km"], 2.5);
+
+        assert_eq!(report.zones.len(), 1);
+        let fence = &report.zones[0];
+        assert_eq!(fence.kind, FenceKind::IncidentZone);
+        assert_eq!(fence.subject_id, zone["id"].as_str().unwrap());
+        assert!(matches!(&fence.shape, Shape::Polygon { points } if points.len() == 5));
+
+        let failed: Vec<usize> = report.errors.iter().map(|e| e.index).collect();
+        assert_eq!(failed, [2, 3]);
+    }
+
+    #[test]
+    fn polygon_resources_become_staging_areas() {
+        let kml = r#"<kml><Placemark><name>Depot</name><Polygon><outerBoundaryIs><LinearRing>
+            <coordinates>10,20 10.1,20 10.1,20.1 10,20.1</coordinates>
+            </LinearRing></outerBoundaryIs></Polygon></Placemark></kml>"#;
+        let report = import_text("depot.kml", kml, Kind::Resource);
+
+        assert_eq!(report.format, Format::Kml);
+        assert_eq!(report.records[0]["name"], "Depot");
+        assert_eq!(report.zones[0].kind, FenceKind::Staging);
+        assert_eq!(
+            report.zones[0].subject_id,
+            report.records[0]["id"].as_str().unwrap()
+        );
+
+        let sos = import_text("depot.kml", kml, Kind::Sos);
+        assert!(sos.records.is_empty() && sos.zones.is_empty());
+        assert_eq!(sos.errors[0].message, "an SOS broadcast must be a point");
+    }
+
+    #[test]
+    fn exports_read_back_in() {
+        let incident = json!({
+            "id": "i1",
+            "title": "Fire <north>",
+            "description": "smoke & ash",
+            "severity": "high",
+            "latitude": -33.9,
+            "longitude": 151.2,
+        });
+        let route = json!({
+            "id": "r1",
+            "name": "Route A",
+            "waypoints": [{ "lat": 0.0, "lng": 0.0 }, { "lat": 0.0, "lng": 0.01 }],
+        });
+        let point = record_geometry(Kind::Incident, &incident).unwrap();
+        let line = record_geometry(Kind::EvacuationRoute, &route).unwrap();
+        assert!(record_geometry(Kind::Incident, &json!({ "id": "x" })).is_none());
+        let ring = vec![
+            Point { lat: 0.0, lng: 0.0 },
+            Point { lat: 0.0, lng: 1.0 },
+            Point { lat: 1.0, lng: 0.0 },
+        ];
+        let records = [
+            (point, &incident),
+            (line, &route),
+            (Geometry::Polygon(ring), &route),
+        ];
+
+        let geojson = to_geojson(Kind::Incident, &records).unwrap();
+        let (ok, failed) = features(parse_geojson(geojson.as_bytes()).unwrap());
+        assert!(failed.is_empty());
+        assert_eq!(ok[0].name.as_deref(), Some("Fire <north>"));
+        assert_eq!(ok[0].properties["severity"], "high");
+        assert!(!ok[0].properties.contains_key("latitude"));
+        assert!(matches!(&ok[1].geometry, Some(Geometry::Line(p)) if p.len() == 2));
+        assert!(matches!(&ok[2].geometry, Some(Geometry::Polygon(p)) if p.len() == 3));
+
+        let kml = to_kml(Kind::Incident, &records);
+        let (ok, failed) = features(parse_kml(&kml).unwrap());
+        assert!(failed.is_empty());
+        assert_eq!(ok[0].name.as_deref(), Some("Fire <north>"));
+        assert_eq!(ok[0].description.as_deref(), Some("smoke & ash"));
+        assert_eq!(ok[0].properties["id"], "i1");
+        assert!(matches!(ok[0].geometry, Some(Geometry::Point(p)) if p.lat == -33.9));
+        assert!(matches!(&ok[1].geometry, Some(Geometry::Line(p)) if p.len() == 2));
+        assert!(matches!(&ok[2].geometry, Some(Geometry::Polygon(p)) if p.len() == 3));
+
+        // waypoints come before routes
+        let gpx = to_gpx(Kind::EvacuationRoute, &records[..2]);
+        let (ok, failed) = features(parse_gpx(&gpx).unwrap());
+        assert!(failed.is_empty());
+        assert_eq!(ok[0].description.as_deref(), Some("smoke & ash"));
+        assert!(matches!(ok[0].geometry, Some(Geometry::Point(p)) if p.lng == 151.2));
+        assert_eq!(ok[1].name.as_deref(), Some("Route A"));
+        assert!(matches!(&ok[1].geometry, Some(Geometry::Line(p)) if p.len() == 2));
+    }
+}
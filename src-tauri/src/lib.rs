@@ -1,9 +1,43 @@
+mod avl;
+mod basemaps;
+mod cap;
+mod cap_feed;
+mod cluster;
+mod conflicts;
+mod coords;
+mod crypto;
+mod db;
+mod dead_letter;
+mod deep_link;
+mod error;
+mod evacuation;
+mod gazetteer;
+mod geo;
+mod geofence;
+mod gps;
+mod interchange;
+mod migrations;
+mod mirror;
+mod offline_queue;
+mod pmtiles;
+mod retry;
+mod routing;
+mod search;
+mod secrets;
+mod security;
+mod share;
+mod supabase;
+mod tiles;
+
 use tauri::{
     menu::{MenuBuilder, MenuItemBuilder},
     tray::TrayIconBuilder,
-    Listener,
     Manager,
 };
+use tauri_plugin_deep_link::DeepLinkExt;
+
+/// Id of the tray icon, so background services can update its tooltip.
+pub const TRAY_ID: &str = "main";
 
 #[tauri::command]
 fn greet(name: &str) -> String {
@@ -30,42 +64,60 @@ pub fn run() {
             }
 
             // Forward any deep link URL from the second instance's args
-            for arg in &args {
-                if arg.starts_with("disasterconnect://") {
-                    if let Some(window) = app.get_webview_window("main") {
-                        let js = format!(
-                            "window.__DEEP_LINK_URL__ = '{}'; window.dispatchEvent(new CustomEvent('deep-link', {{ detail: '{}' }}));",
-                            arg.replace('\'', "\\'"),
-                            arg.replace('\'', "\\'")
-                        );
-                        let _ = window.eval(&js);
-                    }
-                    break;
-                }
+            if let Some(url) = args
+                .iter()
+                .find(|arg| arg.starts_with(&format!("{}://", deep_link::SCHEME)))
+            {
+                deep_link::handle(app, url);
             }
         }))
+        .manage(supabase::SupabaseState::default())
+        .manage(deep_link::DeepLinks::default())
+        .manage(security::Vault::default())
+        .manage(secrets::Secrets::default())
+        .manage(basemaps::Basemaps::default())
+        .manage(routing::Router::default())
+        .manage(geofence::Geofencer::default())
+        .manage(gps::Gps::default())
+        .manage(avl::Avl::default())
+        .manage(cap_feed::CapWatcher::default())
+        .register_asynchronous_uri_scheme_protocol(tiles::SCHEME, |ctx, request, responder| {
+            let app = ctx.app_handle().clone();
+            tauri::async_runtime::spawn(async move {
+                responder.respond(tiles::serve(app, request).await);
+            });
+        })
+        .register_asynchronous_uri_scheme_protocol(basemaps::SCHEME, |ctx, request, responder| {
+            let app = ctx.app_handle().clone();
+            // Archive reads hit the disk; keep them off the main thread
+            tauri::async_runtime::spawn_blocking(move || {
+                responder.respond(basemaps::serve(&app, &request));
+            });
+        })
         .setup(|app| {
-            // Handle deep link URLs (e.g. disasterconnect://auth/callback#access_token=...)
-            #[cfg(desktop)]
-            {
-                let handle = app.handle().clone();
-                app.listen("deep-link://new-url", move |event| {
-                    if let Ok(urls) = serde_json::from_str::<Vec<String>>(event.payload()) {
-                        if let Some(url) = urls.first() {
-                            if let Some(window) = handle.get_webview_window("main") {
-                                let _ = window.show();
-                                let _ = window.set_focus();
-                                let js = format!(
-                                    "window.__DEEP_LINK_URL__ = '{}'; window.dispatchEvent(new CustomEvent('deep-link', {{ detail: '{}' }}));",
-                                    url.replace('\'', "\\'"),
-                                    url.replace('\'', "\\'")
-                                );
-                                let _ = window.eval(&js);
-                            }
-                        }
-                    }
-                });
+            app.manage(db::Db::new(app.handle())?);
+            security::unlock_at_startup(app.handle())?;
+            app.manage(tiles::TileCache::new(app.handle())?);
+            offline_queue::spawn_worker(app.handle().clone());
+            mirror::spawn_worker(app.handle().clone());
+            gps::start_saved(app.handle());
+            avl::spawn_pruner(app.handle().clone());
+            avl::start_saved(app.handle());
+
+            // Handle deep link URLs (e.g. disasterconnect://auth/callback#access_token=...),
+            // both the one the app was launched with and any that arrive later
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                for url in urls {
+                    deep_link::handle(app.handle(), url.as_str());
+                }
             }
+            let handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                for url in event.urls() {
+                    deep_link::handle(&handle, url.as_str());
+                }
+            });
+
             // Build tray menu
             let show = MenuItemBuilder::with_id("show", "Show DisasterConnect")
                 .build(app)?;
@@ -73,6 +125,8 @@ pub fn run() {
                 .build(app)?;
             let incidents = MenuItemBuilder::with_id("incidents", "View Incidents")
                 .build(app)?;
+            let review = MenuItemBuilder::with_id("review", "Review CAP Messages")
+                .build(app)?;
             let quit = MenuItemBuilder::with_id("quit", "Quit")
                 .build(app)?;
 
@@ -81,11 +135,12 @@ pub fn run() {
                 .separator()
                 .item(&dashboard)
                 .item(&incidents)
+                .item(&review)
                 .separator()
                 .item(&quit)
                 .build()?;
 
-            let _tray = TrayIconBuilder::new()
+            let _tray = TrayIconBuilder::with_id(TRAY_ID)
                 .tooltip("DisasterConnect")
                 .menu(&menu)
                 .on_menu_event(move |app, event| {
@@ -96,20 +151,9 @@ pub fn run() {
                                 let _ = window.set_focus();
                             }
                         }
-                        "dashboard" => {
-                            if let Some(window) = app.get_webview_window("main") {
-                                let _ = window.show();
-                                let _ = window.set_focus();
-                                let _ = window.eval("window.location.hash = '#/dashboard';");
-                            }
-                        }
-                        "incidents" => {
-                            if let Some(window) = app.get_webview_window("main") {
-                                let _ = window.show();
-                                let _ = window.set_focus();
-                                let _ = window.eval("window.location.hash = '#/incidents';");
-                            }
-                        }
+                        "dashboard" => deep_link::open(app, deep_link::Route::Dashboard),
+                        "incidents" => deep_link::open(app, deep_link::Route::Incidents),
+                        "review" => deep_link::open(app, deep_link::Route::CapReview),
                         "quit" => {
                             app.exit(0);
                         }
@@ -127,9 +171,112 @@ pub fn run() {
                 })
                 .build(app)?;
 
+            // Partner CAP feeds report into the tray, so start them after it
+            cap_feed::start_saved(app.handle());
+
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![greet])
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            deep_link::deep_link_ready,
+            supabase::supabase_configure,
+            offline_queue::offline_queue_enqueue,
+            offline_queue::offline_queue_list,
+            offline_queue::offline_queue_drain,
+            offline_queue::offline_queue_discard,
+            conflicts::sync_conflicts_list,
+            conflicts::sync_conflict_resolve,
+            dead_letter::dead_letters_list,
+            dead_letter::dead_letter_edit,
+            dead_letter::dead_letter_retry,
+            dead_letter::dead_letter_discard,
+            mirror::mirror_sync,
+            mirror::mirror_query,
+            mirror::mirror_get,
+            mirror::mirror_status,
+            search::search,
+            geo::geo_distance,
+            geo::geo_within_radius,
+            geo::geo_within_bounds,
+            geo::geo_nearest,
+            cluster::map_aggregate,
+            evacuation::evacuation_route_metrics,
+            evacuation::evacuation_speed_profiles,
+            evacuation::evacuation_speed_profiles_set,
+            routing::routing_import,
+            routing::routing_status,
+            routing::routing_route,
+            routing::routing_clear,
+            gazetteer::gazetteer_import,
+            gazetteer::gazetteer_status,
+            gazetteer::gazetteer_clear,
+            gazetteer::geocode_reverse,
+            gazetteer::geocode_search,
+            coords::coords_parse,
+            coords::coords_format,
+            coords::coords_convert,
+            coords::coords_preference,
+            coords::coords_preference_set,
+            gps::gps_start,
+            gps::gps_stop,
+            gps::gps_status,
+            gps::gps_fix,
+            gps::gps_config,
+            gps::gps_serial_ports,
+            avl::avl_status,
+            avl::avl_config,
+            avl::avl_configure,
+            avl::avl_units,
+            avl::avl_unit_assign,
+            avl::avl_unit_remove,
+            avl::avl_report,
+            avl::avl_latest,
+            avl::avl_track,
+            avl::avl_tracked,
+            interchange::interchange_import,
+            interchange::interchange_export,
+            cap::cap_config,
+            cap::cap_configure,
+            cap::cap_export,
+            cap::cap_import,
+            cap::cap_parse,
+            cap_feed::cap_watch_status,
+            cap_feed::cap_watch_poll,
+            cap_feed::cap_review_list,
+            cap_feed::cap_review_accept,
+            cap_feed::cap_review_dismiss,
+            geofence::geofence_check,
+            geofence::geofence_zones,
+            geofence::geofences_list,
+            geofence::geofence_add,
+            geofence::geofence_remove,
+            geofence::geofence_alerts,
+            security::security_status,
+            security::security_unlock,
+            security::security_lock,
+            security::security_set_passphrase,
+            security::security_use_device_key,
+            security::security_rotate_key,
+            security::secure_cache_write,
+            security::secure_cache_read,
+            security::secure_cache_delete,
+            secrets::secret_get,
+            secrets::secret_set,
+            secrets::secret_delete,
+            secrets::secrets_wipe,
+            share::share_link_create,
+            tiles::tiles_seed,
+            tiles::tiles_seed_cancel,
+            tiles::tiles_estimate,
+            tiles::tiles_status,
+            tiles::tiles_clear,
+            tiles::tiles_server,
+            tiles::tiles_set_server,
+            basemaps::basemap_import,
+            basemaps::basemaps_list,
+            basemaps::basemap_activate,
+            basemaps::basemap_remove,
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
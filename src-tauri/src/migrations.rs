@@ -0,0 +1,607 @@
+use std::path::{Path, PathBuf};
+
+use rusqlite::Connection;
+
+use crate::error::{Error, Result};
+
+/// Backups older than the most recent few are pruned after each migration.
+const KEEP_BACKUPS: usize = 3;
+
+/// One step of the local schema. Append new steps at the end and never edit
+/// one that has shipped; `PRAGMA user_version` records how far a database got.
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    pub sql: &'static str,
+}
+
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "offline queue, sync conflicts, dead letters and table mirror",
+        // IF NOT EXISTS so databases created before migrations existed adopt it
+        sql: "
+            CREATE TABLE IF NOT EXISTS offline_queue (
+                id          TEXT PRIMARY KEY,
+                table_name  TEXT NOT NULL,
+                operation   TEXT NOT NULL CHECK (operation IN ('insert', 'update', 'delete')),
+                payload     TEXT NOT NULL,
+                queued_at   TEXT NOT NULL,
+                retries     INTEGER NOT NULL DEFAULT 0,
+                last_error  TEXT,
+                -- What an update was based on, for conflict detection
+                base_updated_at  TEXT,
+                base             TEXT,
+                strategy         TEXT NOT NULL DEFAULT 'merge',
+                -- Unix millis before which a failed mutation is not retried
+                next_attempt_at  INTEGER
+            );
+
+            CREATE TABLE IF NOT EXISTS sync_conflicts (
+                id                 TEXT PRIMARY KEY,
+                table_name         TEXT NOT NULL,
+                record_id          TEXT NOT NULL,
+                local              TEXT NOT NULL,
+                base               TEXT,
+                remote             TEXT,
+                fields             TEXT NOT NULL,
+                base_updated_at    TEXT,
+                remote_updated_at  TEXT,
+                queued_at          TEXT NOT NULL,
+                detected_at        TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS dead_letters (
+                id               TEXT PRIMARY KEY,
+                table_name       TEXT NOT NULL,
+                operation        TEXT NOT NULL,
+                payload          TEXT NOT NULL,
+                queued_at        TEXT NOT NULL,
+                retries          INTEGER NOT NULL,
+                error            TEXT NOT NULL,
+                failed_at        TEXT NOT NULL,
+                base_updated_at  TEXT,
+                base             TEXT,
+                strategy         TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS mirror_records (
+                table_name  TEXT NOT NULL,
+                id          TEXT NOT NULL,
+                data        TEXT NOT NULL,
+                updated_at  TEXT,
+                PRIMARY KEY (table_name, id)
+            );
+
+            CREATE TABLE IF NOT EXISTS mirror_state (
+                table_name         TEXT PRIMARY KEY,
+                cursor_updated_at  TEXT,
+                cursor_id          TEXT,
+                synced_at          TEXT
+            );
+        ",
+    },
+    Migration {
+        version: 2,
+        description: "full-text search index over mirrored records",
+        // The index shares rowids with mirror_records and is kept in step by
+        // triggers, so every mirror pull updates it for free
+        sql: "
+            CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+                entity      UNINDEXED,
+                entity_id   UNINDEXED,
+                parent_id   UNINDEXED,
+                created_at  UNINDEXED,
+                title,
+                body,
+                tokenize = 'porter unicode61 remove_diacritics 2'
+            );
+
+            CREATE TRIGGER IF NOT EXISTS search_index_insert AFTER INSERT ON mirror_records
+            WHEN NEW.table_name IN ('incidents', 'incident_updates', 'messages', 'tasks', 'documents')
+            BEGIN
+                INSERT INTO search_index (rowid, entity, entity_id, parent_id, created_at, title, body)
+                VALUES (
+                    NEW.rowid,
+                    NEW.table_name,
+                    NEW.id,
+                    coalesce(json_extract(NEW.data, '$.incident_id'), json_extract(NEW.data, '$.channel_id')),
+                    json_extract(NEW.data, '$.created_at'),
+                    coalesce(json_extract(NEW.data, '$.title'), json_extract(NEW.data, '$.name'), ''),
+                    coalesce(json_extract(NEW.data, '$.description'), json_extract(NEW.data, '$.content'), '')
+                        || ' ' || coalesce(json_extract(NEW.data, '$.location_name'), '')
+                );
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS search_index_update AFTER UPDATE ON mirror_records
+            WHEN NEW.table_name IN ('incidents', 'incident_updates', 'messages', 'tasks', 'documents')
+            BEGIN
+                DELETE FROM search_index WHERE rowid = OLD.rowid;
+                INSERT INTO search_index (rowid, entity, entity_id, parent_id, created_at, title, body)
+                VALUES (
+                    NEW.rowid,
+                    NEW.table_name,
+                    NEW.id,
+                    coalesce(json_extract(NEW.data, '$.incident_id'), json_extract(NEW.data, '$.channel_id')),
+                    json_extract(NEW.data, '$.created_at'),
+                    coalesce(json_extract(NEW.data, '$.title'), json_extract(NEW.data, '$.name'), ''),
+                    coalesce(json_extract(NEW.data, '$.description'), json_extract(NEW.data, '$.content'), '')
+                        || ' ' || coalesce(json_extract(NEW.data, '$.location_name'), '')
+                );
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS search_index_delete AFTER DELETE ON mirror_records
+            BEGIN
+                DELETE FROM search_index WHERE rowid = OLD.rowid;
+            END;
+
+            INSERT INTO search_index (rowid, entity, entity_id, parent_id, created_at, title, body)
+            SELECT
+                rowid,
+                table_name,
+                id,
+                coalesce(json_extract(data, '$.incident_id'), json_extract(data, '$.channel_id')),
+                json_extract(data, '$.created_at'),
+                coalesce(json_extract(data, '$.title'), json_extract(data, '$.name'), ''),
+                coalesce(json_extract(data, '$.description'), json_extract(data, '$.content'), '')
+                    || ' ' || coalesce(json_extract(data, '$.location_name'), '')
+            FROM mirror_records
+            WHERE table_name IN ('incidents', 'incident_updates', 'messages', 'tasks', 'documents');
+        ",
+    },
+    Migration {
+        version: 3,
+        description: "imported MBTiles/PMTiles basemaps",
+        sql: "
+            CREATE TABLE basemaps (
+                id           TEXT PRIMARY KEY,
+                name         TEXT NOT NULL,
+                kind         TEXT NOT NULL CHECK (kind IN ('mbtiles', 'pmtiles')),
+                format       TEXT NOT NULL,
+                min_zoom     INTEGER NOT NULL,
+                max_zoom     INTEGER NOT NULL,
+                west         REAL,
+                south        REAL,
+                east         REAL,
+                north        REAL,
+                attribution  TEXT,
+                size_bytes   INTEGER NOT NULL,
+                imported_at  TEXT NOT NULL,
+                active       INTEGER NOT NULL DEFAULT 0
+            );
+
+            -- At most one basemap is in use
+            CREATE UNIQUE INDEX basemaps_active ON basemaps (active) WHERE active = 1;
+        ",
+    },
+    Migration {
+        version: 4,
+        description: "spatial index over mirrored incidents, resources and SOS broadcasts",
+        // Same scheme as the search index: the R*Tree shares rowids with
+        // mirror_records and triggers keep it current
+        sql: "
+            CREATE VIRTUAL TABLE geo_index USING rtree(
+                id,
+                min_lat, max_lat,
+                min_lng, max_lng
+            );
+
+            CREATE TRIGGER geo_index_insert AFTER INSERT ON mirror_records
+            WHEN NEW.table_name IN ('incidents', 'resources', 'sos_broadcasts')
+                AND json_type(NEW.data, '$.latitude') IN ('integer', 'real')
+                AND json_type(NEW.data, '$.longitude') IN ('integer', 'real')
+            BEGIN
+                INSERT INTO geo_index (id, min_lat, max_lat, min_lng, max_lng)
+                VALUES (
+                    NEW.rowid,
+                    json_extract(NEW.data, '$.latitude'),
+                    json_extract(NEW.data, '$.latitude'),
+                    json_extract(NEW.data, '$.longitude'),
+                    json_extract(NEW.data, '$.longitude')
+                );
+            END;
+
+            CREATE TRIGGER geo_index_update AFTER UPDATE ON mirror_records
+            BEGIN
+                DELETE FROM geo_index WHERE id = OLD.rowid;
+                INSERT INTO geo_index (id, min_lat, max_lat, min_lng, max_lng)
+                SELECT
+                    NEW.rowid,
+                    json_extract(NEW.data, '$.latitude'),
+                    json_extract(NEW.data, '$.latitude'),
+                    json_extract(NEW.data, '$.longitude'),
+                    json_extract(NEW.data, '$.longitude')
+                WHERE NEW.table_name IN ('incidents', 'resources', 'sos_broadcasts')
+                    AND json_type(NEW.data, '$.latitude') IN ('integer', 'real')
+                    AND json_type(NEW.data, '$.longitude') IN ('integer', 'real');
+            END;
+
+            CREATE TRIGGER geo_index_delete AFTER DELETE ON mirror_records
+            BEGIN
+                DELETE FROM geo_index WHERE id = OLD.rowid;
+            END;
+
+            INSERT INTO geo_index (id, min_lat, max_lat, min_lng, max_lng)
+            SELECT
+                rowid,
+                json_extract(data, '$.latitude'),
+                json_extract(data, '$.latitude'),
+                json_extract(data, '$.longitude'),
+                json_extract(data, '$.longitude')
+            FROM mirror_records
+            WHERE table_name IN ('incidents', 'resources', 'sos_broadcasts')
+                AND json_type(data, '$.latitude') IN ('integer', 'real')
+                AND json_type(data, '$.longitude') IN ('integer', 'real');
+        ",
+    },
+    Migration {
+        version: 5,
+        description: "geofences and the alerts they raised",
+        sql: "
+            -- Incident zones drawn as polygons and resource staging areas;
+            -- circular incident zones come straight from the incident
+            CREATE TABLE geofences (
+                id          TEXT PRIMARY KEY,
+                kind        TEXT NOT NULL CHECK (kind IN ('incident_zone', 'staging')),
+                subject_id  TEXT NOT NULL,
+                name        TEXT,
+                shape       TEXT NOT NULL,
+                created_at  TEXT NOT NULL
+            );
+
+            CREATE INDEX geofences_subject ON geofences (subject_id);
+
+            CREATE TABLE geofence_alerts (
+                id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                key         TEXT NOT NULL,
+                kind        TEXT NOT NULL,
+                subject_id  TEXT NOT NULL,
+                zone_id     TEXT NOT NULL,
+                title       TEXT NOT NULL,
+                body        TEXT NOT NULL,
+                latitude    REAL,
+                longitude   REAL,
+                raised_at   TEXT NOT NULL,
+                cleared_at  TEXT
+            );
+
+            -- One open alert per key, so a sync that sees the same SOS or
+            -- stray resource again doesn't notify twice
+            CREATE UNIQUE INDEX geofence_alerts_open ON geofence_alerts (key)
+                WHERE cleared_at IS NULL;
+        ",
+    },
+    Migration {
+        version: 6,
+        description: "offline gazetteer for geocoding",
+        sql: "
+            CREATE TABLE places (
+                id          INTEGER PRIMARY KEY,
+                name        TEXT NOT NULL,
+                kind        TEXT NOT NULL,
+                admin1      TEXT,
+                admin2      TEXT,
+                country     TEXT,
+                population  INTEGER NOT NULL DEFAULT 0,
+                latitude    REAL NOT NULL,
+                longitude   REAL NOT NULL
+            );
+
+            -- Shares rowids with places, like geo_index with mirror_records
+            CREATE VIRTUAL TABLE places_index USING rtree(
+                id, min_lat, max_lat, min_lng, max_lng
+            );
+
+            -- Names only; results are read back from places
+            CREATE VIRTUAL TABLE places_fts USING fts5(
+                name, alternate_names,
+                content = '',
+                tokenize = 'unicode61 remove_diacritics 2'
+            );
+
+            CREATE TABLE gazetteer_source (
+                id           INTEGER PRIMARY KEY CHECK (id = 1),
+                source       TEXT NOT NULL,
+                format       TEXT NOT NULL,
+                places       INTEGER NOT NULL,
+                imported_at  TEXT NOT NULL
+            );
+        ",
+    },
+    Migration {
+        version: 7,
+        description: "vehicle location trackers and breadcrumbs",
+        sql: "
+            CREATE TABLE avl_units (
+                device_id     TEXT PRIMARY KEY,
+                subject_kind  TEXT NOT NULL CHECK (subject_kind IN ('resource', 'team')),
+                subject_id    TEXT NOT NULL,
+                label         TEXT,
+                created_at    TEXT NOT NULL
+            );
+
+            CREATE TABLE avl_positions (
+                id            INTEGER PRIMARY KEY,
+                subject_kind  TEXT NOT NULL,
+                subject_id    TEXT NOT NULL,
+                device_id     TEXT,
+                latitude      REAL NOT NULL,
+                longitude     REAL NOT NULL,
+                speed_kmh     REAL,
+                course_deg    REAL,
+                altitude_m    REAL,
+                source        TEXT NOT NULL,
+                reported_at   TEXT NOT NULL,
+                received_at   TEXT NOT NULL
+            );
+
+            -- Playback reads one unit's track in time order
+            CREATE INDEX avl_positions_track
+                ON avl_positions (subject_kind, subject_id, reported_at);
+            -- Pruning and latest-position queries scan by time
+            CREATE INDEX avl_positions_reported ON avl_positions (reported_at);
+        ",
+    },
+    Migration {
+        version: 8,
+        description: "incoming CAP messages and their review queue",
+        sql: "
+            -- Every CAP message seen, so feeds and drop folders don't
+            -- suggest the same one twice
+            CREATE TABLE cap_messages (
+                sender       TEXT NOT NULL,
+                identifier   TEXT NOT NULL,
+                sent         TEXT NOT NULL,
+                msg_type     TEXT NOT NULL,
+                source       TEXT NOT NULL,
+                received_at  TEXT NOT NULL,
+                review_id    TEXT,
+                -- Why it was not suggested, if it wasn't
+                skipped      TEXT,
+                PRIMARY KEY (sender, identifier)
+            );
+
+            CREATE TABLE cap_review (
+                id             TEXT PRIMARY KEY,
+                sender         TEXT NOT NULL,
+                identifier     TEXT NOT NULL,
+                source         TEXT NOT NULL,
+                suggestion     TEXT NOT NULL CHECK (suggestion IN ('alert', 'incident')),
+                draft          TEXT NOT NULL,
+                -- Record an earlier message of the thread became
+                target_table   TEXT,
+                target_id      TEXT,
+                status         TEXT NOT NULL DEFAULT 'pending'
+                    CHECK (status IN ('pending', 'accepted', 'dismissed', 'superseded')),
+                superseded_by  TEXT,
+                -- Record accepting it created or updated
+                record_table   TEXT,
+                record_id      TEXT,
+                received_at    TEXT NOT NULL,
+                decided_at     TEXT
+            );
+            CREATE INDEX cap_review_status ON cap_review (status, received_at);
+
+            -- Feed entries already fetched, by link and last update
+            CREATE TABLE cap_feed_entries (
+                url      TEXT PRIMARY KEY,
+                updated  TEXT,
+                seen_at  TEXT NOT NULL
+            );
+        ",
+    },
+    Migration {
+        version: 9,
+        description: "when each mirrored table last had its deletions reconciled",
+        sql: "
+            ALTER TABLE mirror_state ADD COLUMN reconciled_at TEXT;
+        ",
+    },
+];
+
+pub fn latest() -> u32 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+fn user_version(conn: &Connection) -> Result<u32> {
+    Ok(conn.pragma_query_value(None, "user_version", |row| row.get(0))?)
+}
+
+fn is_empty(conn: &Connection) -> Result<bool> {
+    let tables: u32 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table'",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(tables == 0)
+}
+
+/// Copy the database next to itself before touching its schema. `VACUUM INTO`
+/// gives a consistent snapshot even with a live WAL.
+fn backup(conn: &Connection, db_path: &Path, version: u32) -> Result<PathBuf> {
+    let stamp = chrono::Utc::now().format("%Y%m%d%H%M%S");
+    let file_name = db_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(crate::db::DB_FILE);
+    let target = db_path.with_file_name(format!("{file_name}.v{version}-{stamp}.bak"));
+    conn.execute("VACUUM INTO ?1", [target.to_string_lossy()])?;
+    prune_backups(db_path, file_name);
+    Ok(target)
+}
+
+fn prune_backups(db_path: &Path, file_name: &str) {
+    let Some(dir) = db_path.parent() else { return };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    let prefix = format!("{file_name}.v");
+    let mut backups: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(&prefix) && n.ends_with(".bak"))
+        })
+        .collect();
+    // Names end in a sortable timestamp; newest first
+    let stamp = |p: &PathBuf| {
+        p.file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.rsplit('-').next())
+            .map(str::to_owned)
+    };
+    backups.sort_by_key(|p| std::cmp::Reverse(stamp(p)));
+    for old in backups.into_iter().skip(KEEP_BACKUPS) {
+        let _ = std::fs::remove_file(old);
+    }
+}
+
+/// Bring the database up to the latest schema. Refuses to touch a database
+/// written by a newer build, since downgrading it could lose data.
+pub fn run(conn: &mut Connection, db_path: &Path) -> Result<()> {
+    let current = user_version(conn)?;
+    let latest = latest();
+
+    if current > latest {
+        return Err(Error::SchemaTooNew {
+            found: current,
+            supported: latest,
+        });
+    }
+    if current == latest {
+        return Ok(());
+    }
+
+    if !is_empty(conn)? {
+        let path = backup(conn, db_path, current)?;
+        eprintln!("[migrations] Backed up database to {}", path.display());
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration.sql)?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+        tx.commit()?;
+        eprintln!(
+            "[migrations] Applied v{}: {}",
+            migration.version, migration.description
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("migrations-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn backups(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|n| n.ends_with(".bak"))
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn every_migration_applies_in_order() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(
+                migration.version as usize,
+                i + 1,
+                "{}",
+                migration.description
+            );
+        }
+
+        let mut conn = Connection::open_in_memory().unwrap();
+        run(&mut conn, Path::new(":memory:")).unwrap();
+        assert_eq!(user_version(&conn).unwrap(), latest());
+        // ALTERs from later migrations went through
+        conn.prepare("SELECT reconciled_at FROM mirror_state")
+            .unwrap();
+
+        // a second run has nothing to do
+        run(&mut conn, Path::new(":memory:")).unwrap();
+        assert_eq!(user_version(&conn).unwrap(), latest());
+    }
+
+    #[test]
+    fn refuses_a_newer_schema() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", latest() + 1)
+            .unwrap();
+
+        let err = run(&mut conn, Path::new(":memory:")).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::SchemaTooNew { found, supported } if found == latest() + 1 && supported == latest()
+        ));
+        assert_eq!(user_version(&conn).unwrap(), latest() + 1);
+        assert!(is_empty(&conn).unwrap());
+    }
+
+    #[test]
+    fn backs_up_before_upgrading() {
+        let dir = temp_dir();
+        let path = dir.join("app.db");
+        let mut conn = Connection::open(&path).unwrap();
+        conn.execute_batch(MIGRATIONS[0].sql).unwrap();
+        conn.pragma_update(None, "user_version", 1).unwrap();
+
+        run(&mut conn, &path).unwrap();
+        assert_eq!(user_version(&conn).unwrap(), latest());
+
+        let names = backups(&dir);
+        assert_eq!(names.len(), 1);
+        assert!(names[0].starts_with("app.db.v1-"), "{names:?}");
+        let backup = Connection::open(dir.join(&names[0])).unwrap();
+        assert_eq!(user_version(&backup).unwrap(), 1);
+
+        // a fresh database has nothing to back up
+        let fresh = dir.join("fresh.db");
+        run(&mut Connection::open(&fresh).unwrap(), &fresh).unwrap();
+        assert_eq!(backups(&dir).len(), 1);
+
+        drop((conn, backup));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn keeps_the_newest_backups() {
+        let dir = temp_dir();
+        let path = dir.join("app.db");
+        for name in [
+            "app.db.v3-20240105000000.bak",
+            "app.db.v1-20240101000000.bak",
+            "app.db.v4-20240110000000.bak",
+            "app.db.v2-20240102000000.bak",
+            "app.db.v5-20240120000000.bak",
+            "other.db.v1-20230101000000.bak",
+        ] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+
+        prune_backups(&path, "app.db");
+        assert_eq!(
+            backups(&dir),
+            [
+                "app.db.v3-20240105000000.bak",
+                "app.db.v4-20240110000000.bak",
+                "app.db.v5-20240120000000.bak",
+                "other.db.v1-20230101000000.bak",
+            ]
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,560 @@
+use std::collections::{BTreeMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::db::Db;
+use crate::error::{Error, Result};
+use crate::supabase::{Supabase, SupabaseState};
+
+const PULL_INTERVAL: Duration = Duration::from_secs(60);
+/// Deletions only show up in a scan of every id, so the background pull
+/// looks for them this often rather than every minute.
+const RECONCILE_INTERVAL: chrono::Duration = chrono::Duration::minutes(30);
+const PAGE_SIZE: usize = 500;
+
+pub const SYNCED_EVENT: &str = "mirror://synced";
+
+/// A Supabase table kept in the local read-through mirror.
+pub struct MirroredTable {
+    pub name: &'static str,
+    /// Column delta pulls are keyed on: `updated_at`, or `created_at` for
+    /// append-only tables. Tables without one are small enough to be
+    /// re-pulled in full.
+    pub cursor: Option<&'static str>,
+    /// Text columns matched by `search` in `mirror_query`.
+    pub search: &'static [&'static str],
+}
+
+/// The shapes in `src/types/database.ts` that responders need offline or
+/// that feed the local search index.
+pub const TABLES: &[MirroredTable] = &[
+    MirroredTable {
+        name: "incidents",
+        cursor: Some("updated_at"),
+        search: &["title", "description", "location_name"],
+    },
+    MirroredTable {
+        name: "resources",
+        cursor: Some("updated_at"),
+        search: &["name", "description", "location_name"],
+    },
+    MirroredTable {
+        name: "tasks",
+        cursor: Some("updated_at"),
+        search: &["title", "description"],
+    },
+    MirroredTable {
+        name: "teams",
+        cursor: Some("updated_at"),
+        search: &["name", "description"],
+    },
+    MirroredTable {
+        name: "alerts",
+        cursor: None,
+        search: &["title", "message"],
+    },
+    MirroredTable {
+        name: "evacuation_routes",
+        cursor: Some("updated_at"),
+        search: &["name", "description"],
+    },
+    MirroredTable {
+        name: "sos_broadcasts",
+        cursor: None,
+        search: &["message", "location_name"],
+    },
+    MirroredTable {
+        name: "incident_updates",
+        cursor: Some("created_at"),
+        search: &["content"],
+    },
+    MirroredTable {
+        name: "messages",
+        cursor: Some("created_at"),
+        search: &["content"],
+    },
+    MirroredTable {
+        name: "documents",
+        cursor: Some("created_at"),
+        search: &["name"],
+    },
+];
+
+pub fn table(name: &str) -> Result<&'static MirroredTable> {
+    TABLES
+        .iter()
+        .find(|t| t.name == name)
+        .ok_or_else(|| Error::Invalid(format!("{name} is not mirrored locally")))
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableReport {
+    pub upserted: usize,
+    pub removed: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorStatus {
+    pub table: String,
+    pub records: u32,
+    pub synced_at: Option<String>,
+}
+
+/// Filters understood by `mirror_query`, a small subset of what the hooks
+/// do against PostgREST.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorQuery {
+    pub table: String,
+    /// Column equality; `null` and empty strings are ignored like the hooks do.
+    #[serde(default)]
+    pub eq: Map<String, Value>,
+    /// Case-insensitive substring match over the table's text columns.
+    pub search: Option<String>,
+    pub order_by: Option<String>,
+    #[serde(default)]
+    pub ascending: bool,
+    pub limit: Option<u32>,
+}
+
+// ─── Storage ────────────────────────────────────────────────────
+
+fn is_column(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn upsert(conn: &Connection, table: &MirroredTable, row: &Value) -> Result<()> {
+    let Some(id) = row.get("id").and_then(Value::as_str) else {
+        return Ok(());
+    };
+    let updated_at = table
+        .cursor
+        .and_then(|c| row.get(c))
+        .and_then(Value::as_str);
+    conn.execute(
+        "INSERT INTO mirror_records (table_name, id, data, updated_at) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT (table_name, id) DO UPDATE SET data = ?3, updated_at = ?4",
+        params![table.name, id, row, updated_at],
+    )?;
+    Ok(())
+}
+
+fn load_cursor(conn: &Connection, table: &str) -> Result<Option<(String, String)>> {
+    let cursor = conn
+        .query_row(
+            "SELECT cursor_updated_at, cursor_id FROM mirror_state WHERE table_name = ?1",
+            [table],
+            |row| {
+                Ok((
+                    row.get::<_, Option<String>>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                ))
+            },
+        )
+        .optional()?;
+    Ok(match cursor {
+        Some((Some(updated_at), Some(id))) => Some((updated_at, id)),
+        _ => None,
+    })
+}
+
+fn save_cursor(conn: &Connection, table: &str, cursor: Option<(&str, &str)>) -> Result<()> {
+    conn.execute(
+        "INSERT INTO mirror_state (table_name, cursor_updated_at, cursor_id) VALUES (?1, ?2, ?3)
+         ON CONFLICT (table_name) DO UPDATE SET cursor_updated_at = ?2, cursor_id = ?3",
+        params![table, cursor.map(|c| c.0), cursor.map(|c| c.1)],
+    )?;
+    Ok(())
+}
+
+fn mark_synced(conn: &Connection, table: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO mirror_state (table_name, synced_at) VALUES (?1, ?2)
+         ON CONFLICT (table_name) DO UPDATE SET synced_at = ?2",
+        params![table, chrono::Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+fn reconcile_due(conn: &Connection, table: &str) -> Result<bool> {
+    let last: Option<String> = conn
+        .query_row(
+            "SELECT reconciled_at FROM mirror_state WHERE table_name = ?1",
+            [table],
+            |row| row.get(0),
+        )
+        .optional()?
+        .flatten();
+    Ok(last
+        .and_then(|at| chrono::DateTime::parse_from_rfc3339(&at).ok())
+        .is_none_or(|at| chrono::Utc::now() - at.to_utc() >= RECONCILE_INTERVAL))
+}
+
+fn mark_reconciled(conn: &Connection, table: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO mirror_state (table_name, reconciled_at) VALUES (?1, ?2)
+         ON CONFLICT (table_name) DO UPDATE SET reconciled_at = ?2",
+        params![table, chrono::Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// Drop local rows whose ids are no longer on the server.
+fn retain(conn: &mut Connection, table: &str, ids: &HashSet<String>) -> Result<usize> {
+    let local: Vec<String> = {
+        let mut stmt = conn.prepare("SELECT id FROM mirror_records WHERE table_name = ?1")?;
+        let rows = stmt.query_map([table], |row| row.get(0))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+    let tx = conn.transaction()?;
+    let mut removed = 0;
+    for id in local.iter().filter(|id| !ids.contains(*id)) {
+        removed += tx.execute(
+            "DELETE FROM mirror_records WHERE table_name = ?1 AND id = ?2",
+            params![table, id],
+        )?;
+    }
+    tx.commit()?;
+    Ok(removed)
+}
+
+pub fn query(conn: &Connection, q: &MirrorQuery) -> Result<Vec<Value>> {
+    let mirrored = table(&q.table)?;
+
+    let mut sql = String::from("SELECT data FROM mirror_records WHERE table_name = ?");
+    let mut args: Vec<SqlValue> = vec![SqlValue::Text(mirrored.name.into())];
+
+    for (column, value) in &q.eq {
+        if !is_column(column) {
+            return Err(Error::Invalid(format!("bad column name {column:?}")));
+        }
+        let arg = match value {
+            Value::Null => continue,
+            Value::String(s) if s.is_empty() => continue,
+            Value::String(s) => SqlValue::Text(s.clone()),
+            // json_extract hands booleans back as 0/1
+            Value::Bool(b) => SqlValue::Integer(*b as i64),
+            Value::Number(n) => match n.as_i64() {
+                Some(i) => SqlValue::Integer(i),
+                None => SqlValue::Real(n.as_f64().unwrap_or_default()),
+            },
+            _ => return Err(Error::Invalid(format!("can't filter {column} on {value}"))),
+        };
+        sql.push_str(&format!(" AND json_extract(data, '$.{column}') = ?"));
+        args.push(arg);
+    }
+
+    if let Some(search) = q.search.as_deref().filter(|s| !s.trim().is_empty()) {
+        let like = format!("%{}%", search.trim().to_lowercase());
+        let clauses: Vec<String> = mirrored
+            .search
+            .iter()
+            .map(|column| format!("lower(json_extract(data, '$.{column}')) LIKE ?"))
+            .collect();
+        sql.push_str(&format!(" AND ({})", clauses.join(" OR ")));
+        args.extend(mirrored.search.iter().map(|_| SqlValue::Text(like.clone())));
+    }
+
+    let order_by = q.order_by.as_deref().unwrap_or("created_at");
+    if !is_column(order_by) {
+        return Err(Error::Invalid(format!("bad column name {order_by:?}")));
+    }
+    sql.push_str(&format!(
+        " ORDER BY json_extract(data, '$.{order_by}') {}",
+        if q.ascending { "ASC" } else { "DESC" }
+    ));
+    if let Some(limit) = q.limit {
+        sql.push_str(&format!(" LIMIT {limit}"));
+    }
+
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params_from_iter(args), |row| row.get(0))?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+pub fn get(conn: &Connection, table_name: &str, id: &str) -> Result<Option<Value>> {
+    let mirrored = table(table_name)?;
+    Ok(conn
+        .query_row(
+            "SELECT data FROM mirror_records WHERE table_name = ?1 AND id = ?2",
+            params![mirrored.name, id],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+/// Every cached row of `table_name`, for the native modules that work over
+/// the local copy.
+pub fn all(conn: &Connection, table_name: &str) -> Result<Vec<Value>> {
+    let mut stmt = conn.prepare("SELECT data FROM mirror_records WHERE table_name = ?1")?;
+    let rows = stmt.query_map([table_name], |row| row.get(0))?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+pub fn status(conn: &Connection) -> Result<Vec<MirrorStatus>> {
+    TABLES
+        .iter()
+        .map(|table| -> Result<MirrorStatus> {
+            let records = conn.query_row(
+                "SELECT COUNT(*) FROM mirror_records WHERE table_name = ?1",
+                [table.name],
+                |row| row.get(0),
+            )?;
+            let synced_at = conn
+                .query_row(
+                    "SELECT synced_at FROM mirror_state WHERE table_name = ?1",
+                    [table.name],
+                    |row| row.get::<_, Option<String>>(0),
+                )
+                .optional()?
+                .flatten();
+            Ok(MirrorStatus {
+                table: table.name.into(),
+                records,
+                synced_at,
+            })
+        })
+        .collect()
+}
+
+// ─── Pulling ────────────────────────────────────────────────────
+
+static PULLING: AtomicBool = AtomicBool::new(false);
+
+struct PullGuard;
+
+impl PullGuard {
+    fn acquire() -> Option<Self> {
+        PULLING
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| PullGuard)
+    }
+}
+
+impl Drop for PullGuard {
+    fn drop(&mut self) {
+        PULLING.store(false, Ordering::Release);
+    }
+}
+
+/// Quote a value for use inside a PostgREST `or=(...)` filter.
+fn quoted(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// All ids currently on the server, paged by id.
+async fn remote_ids(client: &Supabase, table: &str) -> Result<HashSet<String>> {
+    let mut ids = HashSet::new();
+    let mut after: Option<String> = None;
+    loop {
+        let mut query = vec![
+            ("select", "id".to_owned()),
+            ("order", "id.asc".to_owned()),
+            ("limit", PAGE_SIZE.to_string()),
+        ];
+        if let Some(after) = &after {
+            query.push(("id", format!("gt.{after}")));
+        }
+        let page = client.select(table, &query).await?;
+        let full = page.len() == PAGE_SIZE;
+        for row in page {
+            if let Some(id) = row.get("id").and_then(Value::as_str) {
+                after = Some(id.to_owned());
+                ids.insert(id.to_owned());
+            }
+        }
+        if !full {
+            return Ok(ids);
+        }
+    }
+}
+
+/// Fetch rows changed since the stored `(updated_at, id)` cursor, then
+/// reconcile deletions, which a delta pull can't see, when `reconcile` is
+/// set or they were last looked for over [`RECONCILE_INTERVAL`] ago.
+async fn pull_delta(
+    client: &Supabase,
+    db: &Db,
+    table: &MirroredTable,
+    column: &str,
+    reconcile: bool,
+) -> Result<TableReport> {
+    let mut report = TableReport::default();
+    let mut cursor = db.with(|conn| load_cursor(conn, table.name))?;
+
+    loop {
+        let mut query = vec![
+            ("select", "*".to_owned()),
+            ("order", format!("{column}.asc,id.asc")),
+            ("limit", PAGE_SIZE.to_string()),
+        ];
+        if let Some((updated_at, id)) = &cursor {
+            let at = quoted(updated_at);
+            query.push((
+                "or",
+                format!(
+                    "({column}.gt.{at},and({column}.eq.{at},id.gt.{}))",
+                    quoted(id)
+                ),
+            ));
+        }
+
+        let page = client.select(table.name, &query).await?;
+        let full = page.len() == PAGE_SIZE;
+        let last = page.iter().rev().find_map(|row| {
+            let updated_at = row.get(column)?.as_str()?;
+            let id = row.get("id")?.as_str()?;
+            Some((updated_at.to_owned(), id.to_owned()))
+        });
+
+        db.with(|conn| {
+            let tx = conn.transaction()?;
+            for row in &page {
+                upsert(&tx, table, row)?;
+            }
+            if let Some((updated_at, id)) = &last {
+                save_cursor(&tx, table.name, Some((updated_at, id)))?;
+            }
+            tx.commit()?;
+            Ok(())
+        })?;
+        report.upserted += page.len();
+
+        if last.is_some() {
+            cursor = last;
+        }
+        if !full {
+            break;
+        }
+    }
+
+    if reconcile || db.with(|conn| reconcile_due(conn, table.name))? {
+        let ids = remote_ids(client, table.name).await?;
+        report.removed = db.with(|conn| {
+            let removed = retain(conn, table.name, &ids)?;
+            mark_reconciled(conn, table.name)?;
+            Ok(removed)
+        })?;
+    }
+    Ok(report)
+}
+
+/// Re-pull a table that has no change cursor.
+async fn pull_full(client: &Supabase, db: &Db, table: &MirroredTable) -> Result<TableReport> {
+    let mut rows = Vec::new();
+    let mut after: Option<String> = None;
+    loop {
+        let mut query = vec![
+            ("select", "*".to_owned()),
+            ("order", "id.asc".to_owned()),
+            ("limit", PAGE_SIZE.to_string()),
+        ];
+        if let Some(after) = &after {
+            query.push(("id", format!("gt.{after}")));
+        }
+        let page = client.select(table.name, &query).await?;
+        let full = page.len() == PAGE_SIZE;
+        after = page
+            .last()
+            .and_then(|row| row.get("id"))
+            .and_then(Value::as_str)
+            .map(str::to_owned);
+        rows.extend(page);
+        if !full {
+            break;
+        }
+    }
+
+    let ids: HashSet<String> = rows
+        .iter()
+        .filter_map(|row| row.get("id").and_then(Value::as_str).map(str::to_owned))
+        .collect();
+    db.with(|conn| {
+        let tx = conn.transaction()?;
+        for row in &rows {
+            upsert(&tx, table, row)?;
+        }
+        tx.commit()?;
+        Ok(())
+    })?;
+    let removed = db.with(|conn| retain(conn, table.name, &ids))?;
+    Ok(TableReport {
+        upserted: rows.len(),
+        removed,
+    })
+}
+
+/// Bring every mirrored table up to date, checking for deletions as well
+/// when `reconcile` is set. Does nothing while signed out or when another
+/// pull is already running.
+pub async fn pull(app: &AppHandle, reconcile: bool) -> Result<BTreeMap<String, TableReport>> {
+    let mut reports = BTreeMap::new();
+    let Some(_guard) = PullGuard::acquire() else {
+        return Ok(reports);
+    };
+    let Some(client) = app.state::<SupabaseState>().client() else {
+        return Ok(reports);
+    };
+    let db = app.state::<Db>();
+    if !db.is_unlocked() {
+        return Ok(reports);
+    }
+
+    for table in TABLES {
+        let report = match table.cursor {
+            Some(column) => pull_delta(&client, &db, table, column, reconcile).await?,
+            None => pull_full(&client, &db, table).await?,
+        };
+        db.with(|conn| mark_synced(conn, table.name))?;
+        reports.insert(table.name.to_owned(), report);
+    }
+
+    let _ = app.emit(SYNCED_EVENT, &reports);
+    if let Err(e) = crate::geofence::evaluate(app) {
+        eprintln!("[mirror] Geofence check failed: {e}");
+    }
+    Ok(reports)
+}
+
+pub fn spawn_worker(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = tokio::time::interval(PULL_INTERVAL);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = pull(&app, false).await {
+                eprintln!("[mirror] Pull failed: {e}");
+            }
+        }
+    });
+}
+
+// ─── Commands ───────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn mirror_sync(app: AppHandle) -> Result<BTreeMap<String, TableReport>> {
+    pull(&app, true).await
+}
+
+#[tauri::command]
+pub fn mirror_query(db: State<'_, Db>, query: MirrorQuery) -> Result<Vec<Value>> {
+    db.with(|conn| self::query(conn, &query))
+}
+
+#[tauri::command]
+pub fn mirror_get(db: State<'_, Db>, table: String, id: String) -> Result<Option<Value>> {
+    db.with(|conn| get(conn, &table, &id))
+}
+
+#[tauri::command]
+pub fn mirror_status(db: State<'_, Db>) -> Result<Vec<MirrorStatus>> {
+    db.with(|conn| status(conn))
+}
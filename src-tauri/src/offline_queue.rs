@@ -0,0 +1,650 @@
+use std::collections::{BTreeSet, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::conflicts::{self, Conflict, Strategy};
+use crate::db::Db;
+use crate::dead_letter;
+use crate::error::{Error, Result};
+use crate::gazetteer;
+use crate::retry::{self, Failure};
+use crate::supabase::{Supabase, SupabaseState};
+
+const REPLAY_INTERVAL: Duration = Duration::from_secs(30);
+
+pub const CHANGED_EVENT: &str = "offline-queue://changed";
+pub const DRAINED_EVENT: &str = "offline-queue://drained";
+
+/// Tables whose records get a `location_name` from the gazetteer when
+/// inserted with coordinates only.
+const NAMED_PLACE_TABLES: &[&str] = &["incidents", "resources", "sos_broadcasts"];
+
+// ─── Queued mutation shape ──────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Operation {
+    Insert,
+    Update,
+    Delete,
+}
+
+impl Operation {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Operation::Insert => "insert",
+            Operation::Update => "update",
+            Operation::Delete => "delete",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "insert" => Some(Operation::Insert),
+            "update" => Some(Operation::Update),
+            "delete" => Some(Operation::Delete),
+            _ => None,
+        }
+    }
+}
+
+/// Mirrors `QueuedMutation` in `src/stores/offline-store.ts`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedMutation {
+    pub id: String,
+    pub table: String,
+    pub operation: Operation,
+    pub payload: Value,
+    pub queued_at: String,
+    pub retries: u32,
+    pub last_error: Option<String>,
+    pub base_updated_at: Option<String>,
+    pub base: Option<Value>,
+    pub strategy: Strategy,
+    /// Unix millis of the next retry, `None` until the first failure.
+    pub next_attempt_at: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewMutation {
+    pub table: String,
+    pub operation: Operation,
+    pub payload: Value,
+    /// `updated_at` of the record the edit was made against.
+    #[serde(default)]
+    pub base_updated_at: Option<String>,
+    /// The record the edit was made against, needed for field-level merge.
+    #[serde(default)]
+    pub base: Option<Value>,
+    #[serde(default)]
+    pub strategy: Option<Strategy>,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DrainReport {
+    pub synced: u32,
+    /// Failed transiently; retried later with backoff.
+    pub failed: u32,
+    /// Rejected for good and moved to the dead-letter store.
+    pub dead_lettered: u32,
+    pub conflicts: u32,
+    pub tables: BTreeSet<String>,
+}
+
+// ─── Storage ────────────────────────────────────────────────────
+
+fn from_row(row: &Row) -> rusqlite::Result<QueuedMutation> {
+    let operation: String = row.get("operation")?;
+    Ok(QueuedMutation {
+        id: row.get("id")?,
+        table: row.get("table_name")?,
+        operation: Operation::parse(&operation).ok_or_else(|| {
+            rusqlite::Error::InvalidColumnType(0, operation, rusqlite::types::Type::Text)
+        })?,
+        payload: row.get("payload")?,
+        queued_at: row.get("queued_at")?,
+        retries: row.get("retries")?,
+        last_error: row.get("last_error")?,
+        base_updated_at: row.get("base_updated_at")?,
+        base: row.get("base")?,
+        strategy: Strategy::parse(&row.get::<_, String>("strategy")?).unwrap_or_default(),
+        next_attempt_at: row.get("next_attempt_at")?,
+    })
+}
+
+pub fn enqueue(conn: &Connection, mutation: NewMutation) -> Result<QueuedMutation> {
+    if mutation.table.is_empty() || !mutation.payload.is_object() {
+        return Err(Error::Invalid(
+            "a mutation needs a table and an object payload".into(),
+        ));
+    }
+    if mutation.operation != Operation::Insert && record_id(&mutation.payload).is_none() {
+        return Err(Error::Invalid(format!(
+            "{} payload must carry the record id",
+            mutation.operation.as_str()
+        )));
+    }
+
+    let mut payload = mutation.payload;
+    if mutation.operation == Operation::Insert
+        && NAMED_PLACE_TABLES.contains(&mutation.table.as_str())
+    {
+        // Best effort: an SOS sent without a name still queues without one
+        let _ = gazetteer::fill_location_name(conn, &mut payload);
+    }
+
+    let queued = QueuedMutation {
+        id: uuid::Uuid::new_v4().to_string(),
+        table: mutation.table,
+        operation: mutation.operation,
+        payload,
+        queued_at: chrono::Utc::now().to_rfc3339(),
+        retries: 0,
+        last_error: None,
+        base_updated_at: mutation.base_updated_at,
+        base: mutation.base,
+        strategy: mutation.strategy.unwrap_or_default(),
+        next_attempt_at: None,
+    };
+    conn.execute(
+        "INSERT INTO offline_queue (id, table_name, operation, payload, queued_at,
+             base_updated_at, base, strategy)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            queued.id,
+            queued.table,
+            queued.operation.as_str(),
+            queued.payload,
+            queued.queued_at,
+            queued.base_updated_at,
+            queued.base,
+            queued.strategy.as_str(),
+        ],
+    )?;
+    Ok(queued)
+}
+
+pub fn list(conn: &Connection) -> Result<Vec<QueuedMutation>> {
+    let mut stmt = conn.prepare("SELECT * FROM offline_queue ORDER BY queued_at, rowid")?;
+    let rows = stmt.query_map([], from_row)?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+pub fn remove(conn: &Connection, id: &str) -> Result<bool> {
+    Ok(conn.execute("DELETE FROM offline_queue WHERE id = ?1", [id])? > 0)
+}
+
+fn schedule_retry(conn: &Connection, mutation: &QueuedMutation, error: &str) -> Result<()> {
+    let delay = retry::backoff(mutation.retries);
+    let next_attempt_at = chrono::Utc::now().timestamp_millis() + delay.as_millis() as i64;
+    conn.execute(
+        "UPDATE offline_queue
+         SET retries = retries + 1, last_error = ?2, next_attempt_at = ?3
+         WHERE id = ?1",
+        params![mutation.id, error, next_attempt_at],
+    )?;
+    Ok(())
+}
+
+pub fn count(conn: &Connection) -> Result<u32> {
+    Ok(conn.query_row("SELECT COUNT(*) FROM offline_queue", [], |row| row.get(0))?)
+}
+
+fn record_id(payload: &Value) -> Option<&str> {
+    payload.get("id").and_then(Value::as_str)
+}
+
+/// The record a mutation touches. Mutations of one record must reach the
+/// server in the order they were made: an update sent before its insert
+/// patches no rows, which PostgREST reports as success.
+pub fn chain_key(mutation: &QueuedMutation) -> Option<(String, String)> {
+    record_id(&mutation.payload).map(|id| (mutation.table.clone(), id.to_owned()))
+}
+
+fn is_due(mutation: &QueuedMutation, now: i64) -> bool {
+    match mutation.next_attempt_at {
+        Some(at) => at <= now,
+        None => true,
+    }
+}
+
+/// The queue in replay order, minus what has to wait: mutations still
+/// backing off, and every later mutation of a record whose earlier one is
+/// backing off or didn't go through.
+struct Schedule {
+    pending: std::vec::IntoIter<QueuedMutation>,
+    /// Records with an earlier mutation that didn't go through this time
+    held: HashSet<(String, String)>,
+    now: i64,
+}
+
+impl Schedule {
+    fn new(pending: Vec<QueuedMutation>, now: i64) -> Self {
+        Self {
+            pending: pending.into_iter(),
+            held: HashSet::new(),
+            now,
+        }
+    }
+
+    /// `mutation` didn't go through; keep its record's later ones back.
+    fn hold(&mut self, mutation: &QueuedMutation) {
+        self.held.extend(chain_key(mutation));
+    }
+}
+
+impl Iterator for Schedule {
+    type Item = QueuedMutation;
+
+    fn next(&mut self) -> Option<QueuedMutation> {
+        for mutation in self.pending.by_ref() {
+            let key = chain_key(&mutation);
+            if key.as_ref().is_some_and(|key| self.held.contains(key)) {
+                continue;
+            }
+            if !is_due(&mutation, self.now) {
+                self.held.extend(key);
+                continue;
+            }
+            return Some(mutation);
+        }
+        None
+    }
+}
+
+// ─── Replay ─────────────────────────────────────────────────────
+
+static DRAINING: AtomicBool = AtomicBool::new(false);
+
+struct DrainGuard;
+
+impl DrainGuard {
+    fn acquire() -> Option<Self> {
+        DRAINING
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| DrainGuard)
+    }
+}
+
+impl Drop for DrainGuard {
+    fn drop(&mut self) {
+        DRAINING.store(false, Ordering::Release);
+    }
+}
+
+/// Send one mutation. Returns the conflict instead when an update can't be
+/// applied without overwriting someone else's edit.
+async fn replay(client: &Supabase, mutation: &QueuedMutation) -> Result<Option<Box<Conflict>>> {
+    match mutation.operation {
+        Operation::Insert => {
+            client.insert(&mutation.table, &mutation.payload).await?;
+        }
+        Operation::Update => {
+            let mut rest = mutation.payload.clone();
+            let id = rest
+                .as_object_mut()
+                .and_then(|o| o.remove("id"))
+                .and_then(|v| v.as_str().map(str::to_owned))
+                .ok_or_else(|| Error::Invalid("update payload has no id".into()))?;
+            if let Some(conflict) = conflicts::apply(client, mutation, &id, rest).await? {
+                return Ok(Some(conflict));
+            }
+        }
+        Operation::Delete => {
+            let id = record_id(&mutation.payload)
+                .ok_or_else(|| Error::Invalid("delete payload has no id".into()))?;
+            client.delete(&mutation.table, id).await?;
+        }
+    }
+    Ok(None)
+}
+
+/// Replay every queued mutation whose backoff has elapsed, in order. A
+/// record's later mutations wait behind one that is still backing off.
+/// Does nothing while signed out or when another drain is already running.
+pub async fn drain(app: &AppHandle) -> Result<DrainReport> {
+    let mut report = DrainReport::default();
+    let Some(_guard) = DrainGuard::acquire() else {
+        return Ok(report);
+    };
+    let Some(client) = app.state::<SupabaseState>().client() else {
+        return Ok(report);
+    };
+
+    let db = app.state::<Db>();
+    if !db.is_unlocked() {
+        return Ok(report);
+    }
+    let now = chrono::Utc::now().timestamp_millis();
+    let pending = db.with(|conn| list(conn))?;
+    if pending.is_empty() {
+        return Ok(report);
+    }
+
+    let mut schedule = Schedule::new(pending, now);
+    while let Some(mutation) = schedule.next() {
+        match replay(&client, &mutation).await {
+            Ok(None) => {
+                db.with(|conn| remove(conn, &mutation.id))?;
+                report.tables.insert(mutation.table);
+                report.synced += 1;
+            }
+            Ok(Some(conflict)) => {
+                // Park it in "needs review" rather than overwriting
+                db.with(|conn| {
+                    let tx = conn.transaction()?;
+                    conflicts::insert(&tx, &conflict)?;
+                    remove(&tx, &mutation.id)?;
+                    tx.commit()?;
+                    Ok(())
+                })?;
+                let _ = app.emit(conflicts::CONFLICT_EVENT, &*conflict);
+                report.conflicts += 1;
+            }
+            Err(e) => match retry::classify(&e) {
+                Failure::Permanent => {
+                    let buried = db.with(|conn| {
+                        let key = chain_key(&mutation);
+                        dead_letter::bury_chain(conn, &mutation, key.as_ref(), &e.to_string())
+                    })?;
+                    let _ = app.emit(dead_letter::CHANGED_EVENT, ());
+                    // Its dependents are gone from the queue with it
+                    schedule.hold(&mutation);
+                    report.dead_lettered += buried;
+                }
+                Failure::Transient => {
+                    db.with(|conn| schedule_retry(conn, &mutation, &e.to_string()))?;
+                    schedule.hold(&mutation);
+                    report.failed += 1;
+                    if matches!(e, Error::Http(_)) {
+                        // The link is down; the rest would fail the same way
+                        break;
+                    }
+                }
+            },
+        }
+    }
+
+    let remaining = db.with(|conn| count(conn))?;
+    let _ = app.emit(CHANGED_EVENT, remaining);
+    let _ = app.emit(DRAINED_EVENT, &report);
+    Ok(report)
+}
+
+/// Keep replaying in the background so the queue drains even while the
+/// window is hidden to the tray.
+pub fn spawn_worker(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = tokio::time::interval(REPLAY_INTERVAL);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = drain(&app).await {
+                eprintln!("[offline-queue] Replay failed: {e}");
+            }
+        }
+    });
+}
+
+// ─── Commands ───────────────────────────────────────────────────
+
+#[tauri::command]
+pub fn offline_queue_enqueue(
+    app: AppHandle,
+    db: State<'_, Db>,
+    mutation: NewMutation,
+) -> Result<QueuedMutation> {
+    let (queued, remaining) = db.with(|conn| Ok((enqueue(conn, mutation)?, count(conn)?)))?;
+    let _ = app.emit(CHANGED_EVENT, remaining);
+    Ok(queued)
+}
+
+#[tauri::command]
+pub fn offline_queue_list(db: State<'_, Db>) -> Result<Vec<QueuedMutation>> {
+    db.with(|conn| list(conn))
+}
+
+#[tauri::command]
+pub async fn offline_queue_drain(app: AppHandle) -> Result<DrainReport> {
+    drain(&app).await
+}
+
+#[tauri::command]
+pub fn offline_queue_discard(app: AppHandle, db: State<'_, Db>, id: String) -> Result<()> {
+    let remaining = db.with(|conn| {
+        if !remove(conn, &id)? {
+            return Err(Error::NotFound(format!("queued mutation {id}")));
+        }
+        count(conn)
+    })?;
+    let _ = app.emit(CHANGED_EVENT, remaining);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        for migration in crate::migrations::MIGRATIONS {
+            conn.execute_batch(migration.sql).unwrap();
+        }
+        conn
+    }
+
+    fn queue(conn: &Connection, table: &str, operation: Operation, payload: Value) -> String {
+        let mutation = NewMutation {
+            table: table.into(),
+            operation,
+            payload,
+            base_updated_at: None,
+            base: None,
+            strategy: None,
+        };
+        enqueue(conn, mutation).unwrap().id
+    }
+
+    fn ids(mutations: impl IntoIterator<Item = QueuedMutation>) -> Vec<String> {
+        mutations.into_iter().map(|m| m.id).collect()
+    }
+
+    #[test]
+    fn mutations_need_a_table_payload_and_id() {
+        let conn = db();
+        let new = |table: &str, operation, payload| NewMutation {
+            table: String::from(table),
+            operation,
+            payload,
+            base_updated_at: None,
+            base: None,
+            strategy: None,
+        };
+        let id = serde_json::json!({ "id": "t1" });
+        assert!(enqueue(&conn, new("", Operation::Update, id.clone())).is_err());
+        assert!(enqueue(&conn, new("tasks", Operation::Update, "t1".into())).is_err());
+        assert!(enqueue(
+            &conn,
+            new("tasks", Operation::Delete, serde_json::json!({}))
+        )
+        .is_err());
+        // Inserts may leave the id to the server
+        let queued = enqueue(
+            &conn,
+            new("tasks", Operation::Insert, serde_json::json!({})),
+        )
+        .unwrap();
+        assert_eq!(queued.strategy, Strategy::default());
+        assert_eq!(count(&conn).unwrap(), 1);
+    }
+
+    #[test]
+    fn queue_lists_in_the_order_changes_were_made() {
+        let conn = db();
+        let first = queue(
+            &conn,
+            "tasks",
+            Operation::Insert,
+            serde_json::json!({ "id": "t1" }),
+        );
+        let second = queue(
+            &conn,
+            "tasks",
+            Operation::Update,
+            serde_json::json!({ "id": "t1" }),
+        );
+        let third = queue(
+            &conn,
+            "alerts",
+            Operation::Delete,
+            serde_json::json!({ "id": "a1" }),
+        );
+        // Same timestamp: insertion order breaks the tie
+        conn.execute(
+            "UPDATE offline_queue SET queued_at = '2026-01-01T00:00:00Z'",
+            [],
+        )
+        .unwrap();
+        assert_eq!(
+            ids(list(&conn).unwrap()),
+            [first.as_str(), second.as_str(), third.as_str()]
+        );
+
+        assert!(remove(&conn, &second).unwrap());
+        assert!(!remove(&conn, &second).unwrap());
+        assert_eq!(ids(list(&conn).unwrap()), [first.as_str(), third.as_str()]);
+    }
+
+    #[test]
+    fn chain_keys_name_the_record() {
+        let conn = db();
+        queue(
+            &conn,
+            "tasks",
+            Operation::Update,
+            serde_json::json!({ "id": "x" }),
+        );
+        queue(
+            &conn,
+            "teams",
+            Operation::Update,
+            serde_json::json!({ "id": "x" }),
+        );
+        queue(
+            &conn,
+            "tasks",
+            Operation::Insert,
+            serde_json::json!({ "title": "new" }),
+        );
+        let keys: Vec<_> = list(&conn).unwrap().iter().map(chain_key).collect();
+        assert_eq!(
+            keys,
+            [
+                Some(("tasks".into(), "x".into())),
+                Some(("teams".into(), "x".into())),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn a_record_waits_behind_its_backing_off_mutation() {
+        let conn = db();
+        let insert = queue(
+            &conn,
+            "tasks",
+            Operation::Insert,
+            serde_json::json!({ "id": "t1" }),
+        );
+        let update = queue(
+            &conn,
+            "tasks",
+            Operation::Update,
+            serde_json::json!({ "id": "t1" }),
+        );
+        let other = queue(
+            &conn,
+            "tasks",
+            Operation::Update,
+            serde_json::json!({ "id": "t2" }),
+        );
+        let anonymous = queue(&conn, "tasks", Operation::Insert, serde_json::json!({}));
+        let now = chrono::Utc::now().timestamp_millis();
+
+        assert_eq!(
+            ids(Schedule::new(list(&conn).unwrap(), now)),
+            [
+                insert.as_str(),
+                update.as_str(),
+                other.as_str(),
+                anonymous.as_str()
+            ]
+        );
+
+        let pending = list(&conn).unwrap();
+        schedule_retry(&conn, &pending[0], "timed out").unwrap();
+        let retried = &list(&conn).unwrap()[0];
+        assert_eq!(retried.retries, 1);
+        assert_eq!(retried.last_error.as_deref(), Some("timed out"));
+        assert!(retried.next_attempt_at.unwrap() > now);
+        assert_eq!(
+            ids(Schedule::new(list(&conn).unwrap(), now)),
+            [other.as_str(), anonymous.as_str()]
+        );
+        // Once the backoff is over the chain goes again, in order
+        let later = retried.next_attempt_at.unwrap();
+        assert_eq!(
+            ids(Schedule::new(list(&conn).unwrap(), later)),
+            [
+                insert.as_str(),
+                update.as_str(),
+                other.as_str(),
+                anonymous.as_str()
+            ]
+        );
+    }
+
+    #[test]
+    fn a_failed_mutation_holds_back_the_rest_of_its_record() {
+        let conn = db();
+        let a1 = queue(
+            &conn,
+            "tasks",
+            Operation::Insert,
+            serde_json::json!({ "id": "a" }),
+        );
+        let b1 = queue(
+            &conn,
+            "tasks",
+            Operation::Update,
+            serde_json::json!({ "id": "b" }),
+        );
+        queue(
+            &conn,
+            "tasks",
+            Operation::Update,
+            serde_json::json!({ "id": "a" }),
+        );
+        let b2 = queue(
+            &conn,
+            "tasks",
+            Operation::Delete,
+            serde_json::json!({ "id": "b" }),
+        );
+        let now = chrono::Utc::now().timestamp_millis();
+
+        let mut schedule = Schedule::new(list(&conn).unwrap(), now);
+        let first = schedule.next().unwrap();
+        assert_eq!(first.id, a1);
+        schedule.hold(&first);
+        assert_eq!(ids(schedule), [b1.as_str(), b2.as_str()]);
+    }
+}
@@ -0,0 +1,311 @@
+//! Reader for PMTiles v3 archives: a header, a Hilbert-ordered directory of
+//! tile runs, and the tile data, all in one file read with range reads.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Mutex;
+
+use flate2::read::GzDecoder;
+
+use crate::error::{Error, Result};
+
+const HEADER_LEN: usize = 127;
+const MAGIC: &[u8] = b"PMTiles";
+/// Leaf directories nest at most this deep in practice.
+const MAX_DEPTH: u8 = 4;
+/// Largest directory, metadata block or tile we will read or inflate; real
+/// archives stay far below it, a corrupt header can claim anything.
+const MAX_READ: u64 = 32 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compression {
+    None,
+    Gzip,
+}
+
+impl Compression {
+    fn from_byte(b: u8) -> Result<Self> {
+        match b {
+            // 0 is "unknown", which in practice means uncompressed
+            0 | 1 => Ok(Compression::None),
+            2 => Ok(Compression::Gzip),
+            _ => Err(Error::Invalid(
+                "PMTiles archive uses an unsupported compression".into(),
+            )),
+        }
+    }
+
+    fn decode(self, data: Vec<u8>) -> Result<Vec<u8>> {
+        match self {
+            Compression::None => Ok(data),
+            Compression::Gzip => {
+                let mut out = Vec::new();
+                GzDecoder::new(data.as_slice())
+                    .take(MAX_READ + 1)
+                    .read_to_end(&mut out)?;
+                if out.len() as u64 > MAX_READ {
+                    return Err(Error::Invalid(
+                        "PMTiles archive holds an oversized block".into(),
+                    ));
+                }
+                Ok(out)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Header {
+    root_offset: u64,
+    root_length: u64,
+    metadata_offset: u64,
+    metadata_length: u64,
+    leaf_offset: u64,
+    tile_data_offset: u64,
+    internal_compression: Compression,
+    tile_compression: Compression,
+    /// 1 MVT, 2 PNG, 3 JPEG, 4 WebP, 5 AVIF.
+    pub tile_type: u8,
+    pub min_zoom: u8,
+    pub max_zoom: u8,
+    /// `[west, south, east, north]` in degrees.
+    pub bounds: [f64; 4],
+}
+
+impl Header {
+    fn parse(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < HEADER_LEN || &bytes[..7] != MAGIC {
+            return Err(Error::Invalid("not a PMTiles archive".into()));
+        }
+        if bytes[7] != 3 {
+            return Err(Error::Invalid(format!(
+                "PMTiles version {} is not supported",
+                bytes[7]
+            )));
+        }
+        let u64_at = |i: usize| u64::from_le_bytes(bytes[i..i + 8].try_into().unwrap());
+        let deg_at = |i: usize| {
+            f64::from(i32::from_le_bytes(bytes[i..i + 4].try_into().unwrap())) / 10_000_000.0
+        };
+        Ok(Self {
+            root_offset: u64_at(8),
+            root_length: u64_at(16),
+            metadata_offset: u64_at(24),
+            metadata_length: u64_at(32),
+            leaf_offset: u64_at(40),
+            tile_data_offset: u64_at(56),
+            internal_compression: Compression::from_byte(bytes[97])?,
+            tile_compression: Compression::from_byte(bytes[98])?,
+            tile_type: bytes[99],
+            min_zoom: bytes[100],
+            max_zoom: bytes[101],
+            bounds: [deg_at(102), deg_at(106), deg_at(110), deg_at(114)],
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    tile_id: u64,
+    offset: u64,
+    length: u64,
+    /// 0 marks a pointer to a leaf directory.
+    run_length: u32,
+}
+
+fn read_varint(data: &[u8], pos: &mut usize) -> Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *data
+            .get(*pos)
+            .ok_or_else(|| Error::Invalid("truncated PMTiles directory".into()))?;
+        *pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(Error::Invalid("bad varint in PMTiles directory".into()))
+}
+
+fn parse_directory(data: &[u8]) -> Result<Vec<Entry>> {
+    let mut pos = 0;
+    // Every entry takes at least four bytes, one per column
+    let count = usize::try_from(read_varint(data, &mut pos)?)
+        .ok()
+        .filter(|&n| n <= data.len() / 4)
+        .ok_or_else(|| Error::Invalid("PMTiles directory count is out of range".into()))?;
+    let mut entries = vec![
+        Entry {
+            tile_id: 0,
+            offset: 0,
+            length: 0,
+            run_length: 0,
+        };
+        count
+    ];
+
+    let mut last_id = 0u64;
+    for entry in entries.iter_mut() {
+        last_id += read_varint(data, &mut pos)?;
+        entry.tile_id = last_id;
+    }
+    for entry in entries.iter_mut() {
+        entry.run_length = read_varint(data, &mut pos)? as u32;
+    }
+    for entry in entries.iter_mut() {
+        entry.length = read_varint(data, &mut pos)?;
+    }
+    for i in 0..count {
+        let raw = read_varint(data, &mut pos)?;
+        // 0 means "right after the previous entry"
+        entries[i].offset = if raw == 0 && i > 0 {
+            entries[i - 1]
+                .offset
+                .checked_add(entries[i - 1].length)
+                .ok_or_else(|| Error::Invalid("bad offset in PMTiles directory".into()))?
+        } else {
+            raw.saturating_sub(1)
+        };
+    }
+    Ok(entries)
+}
+
+/// Position of a tile on the Hilbert curve that orders PMTiles directories.
+pub fn tile_id(z: u8, x: u32, y: u32) -> u64 {
+    let base: u64 = (0..z).map(|i| 1u64 << (2 * u32::from(i))).sum();
+    let n = 1u64 << z;
+    let (mut x, mut y) = (u64::from(x), u64::from(y));
+    let mut d = 0u64;
+    let mut s = n / 2;
+    while s > 0 {
+        let rx = u64::from(x & s > 0);
+        let ry = u64::from(y & s > 0);
+        d += s * s * ((3 * rx) ^ ry);
+        if ry == 0 {
+            if rx == 1 {
+                x = n - 1 - x;
+                y = n - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+    base + d
+}
+
+pub struct PmTiles {
+    file: Mutex<File>,
+    len: u64,
+    pub header: Header,
+    root: Vec<Entry>,
+}
+
+impl PmTiles {
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        let mut header = [0u8; HEADER_LEN];
+        file.read_exact(&mut header)
+            .map_err(|_| Error::Invalid("not a PMTiles archive".into()))?;
+        let header = Header::parse(&header)?;
+        let mut archive = Self {
+            file: Mutex::new(file),
+            len,
+            header,
+            root: Vec::new(),
+        };
+        archive.root = parse_directory(&archive.header.internal_compression.decode(
+            archive.read_range(0, archive.header.root_offset, archive.header.root_length)?,
+        )?)?;
+        Ok(archive)
+    }
+
+    /// The archive's JSON metadata (name, attribution, ...).
+    pub fn metadata(&self) -> Result<serde_json::Value> {
+        if self.header.metadata_length == 0 {
+            return Ok(serde_json::Value::Null);
+        }
+        let raw = self.read_range(0, self.header.metadata_offset, self.header.metadata_length)?;
+        Ok(serde_json::from_slice(
+            &self.header.internal_compression.decode(raw)?,
+        )?)
+    }
+
+    pub fn tile(&self, z: u8, x: u32, y: u32) -> Result<Option<Vec<u8>>> {
+        let id = tile_id(z, x, y);
+        let mut leaf: Vec<Entry>;
+        let mut entries: &[Entry] = &self.root;
+        for _ in 0..MAX_DEPTH {
+            // Last entry starting at or before `id`
+            let idx = entries.partition_point(|e| e.tile_id <= id);
+            let Some(entry) = idx.checked_sub(1).map(|i| entries[i]) else {
+                return Ok(None);
+            };
+            if entry.run_length == 0 {
+                let raw = self.read_range(self.header.leaf_offset, entry.offset, entry.length)?;
+                leaf = parse_directory(&self.header.internal_compression.decode(raw)?)?;
+                entries = &leaf;
+                continue;
+            }
+            if id - entry.tile_id >= u64::from(entry.run_length) {
+                return Ok(None);
+            }
+            let raw = self.read_range(self.header.tile_data_offset, entry.offset, entry.length)?;
+            return Ok(Some(self.header.tile_compression.decode(raw)?));
+        }
+        Err(Error::Invalid("PMTiles directories nest too deep".into()))
+    }
+
+    /// `length` bytes at `base + offset`, which must lie inside the file.
+    fn read_range(&self, base: u64, offset: u64, length: u64) -> Result<Vec<u8>> {
+        let start = base.checked_add(offset);
+        if length > MAX_READ
+            || start
+                .and_then(|s| s.checked_add(length))
+                .is_none_or(|end| end > self.len)
+        {
+            return Err(Error::Invalid(
+                "PMTiles archive points outside the file".into(),
+            ));
+        }
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        file.seek(SeekFrom::Start(start.unwrap_or_default()))?;
+        let mut buf = vec![0u8; length as usize];
+        file.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directory_round_trips() {
+        // Two entries: ids 0 and 1, run 1 each, lengths 10 and 20, the
+        // second right after the first
+        let data = [2, 0, 1, 1, 1, 10, 20, 1, 0];
+        let entries = parse_directory(&data).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[1].tile_id, entries[1].offset), (1, 10));
+        assert_eq!(entries[1].length, 20);
+    }
+
+    #[test]
+    fn oversized_directory_count_is_rejected() {
+        // Claims u32::MAX entries in a five-byte directory
+        let data = [0xff, 0xff, 0xff, 0xff, 0x0f];
+        assert!(parse_directory(&data).is_err());
+    }
+
+    #[test]
+    fn tile_ids_follow_the_hilbert_curve() {
+        assert_eq!(tile_id(0, 0, 0), 0);
+        assert_eq!(tile_id(1, 0, 0), 1);
+        assert_eq!(tile_id(1, 0, 1), 2);
+        assert_eq!(tile_id(1, 1, 1), 3);
+        assert_eq!(tile_id(1, 1, 0), 4);
+    }
+}
@@ -0,0 +1,139 @@
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::error::Error;
+
+const BASE_DELAY: Duration = Duration::from_secs(5);
+const MAX_DELAY: Duration = Duration::from_secs(30 * 60);
+
+/// Whether a failed replay is worth trying again later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// Flaky link, timeout, server hiccup, expired token: retry with backoff.
+    Transient,
+    /// The server will keep saying no (constraint violation, RLS denial,
+    /// malformed payload): retrying can't help, so it goes to dead letters.
+    Permanent,
+}
+
+pub fn classify(error: &Error) -> Failure {
+    match error {
+        Error::Http(_) | Error::NotConfigured => Failure::Transient,
+        Error::Supabase { status, code, .. } => classify_response(*status, code.as_deref()),
+        Error::Invalid(_) | Error::Json(_) | Error::NotFound(_) => Failure::Permanent,
+        // Local I/O or database trouble says nothing about the mutation itself
+        _ => Failure::Transient,
+    }
+}
+
+fn classify_response(status: u16, code: Option<&str>) -> Failure {
+    if let Some(code) = code {
+        // Postgres SQLSTATE classes: 22 data exception, 23 integrity
+        // constraint violation, 42 syntax error or access rule violation
+        // (42501 is how RLS denials surface)
+        if ["22", "23", "42"]
+            .iter()
+            .any(|class| code.starts_with(class))
+        {
+            return Failure::Permanent;
+        }
+    }
+    match status {
+        // Timeout, rate limited, expired JWT about to be refreshed
+        408 | 429 | 401 => Failure::Transient,
+        400..=499 => Failure::Permanent,
+        _ => Failure::Transient,
+    }
+}
+
+/// Delay before attempt number `retries + 1`: exponential with full jitter,
+/// so a fleet of laptops coming back online doesn't retry in lockstep.
+pub fn backoff(retries: u32) -> Duration {
+    let ceiling = BASE_DELAY
+        .saturating_mul(1u32.checked_shl(retries).unwrap_or(u32::MAX))
+        .min(MAX_DELAY);
+    let millis = rand::thread_rng().gen_range(0..=ceiling.as_millis() as u64);
+    Duration::from_millis(millis).max(Duration::from_secs(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, code: Option<&str>) -> Error {
+        Error::Supabase {
+            status,
+            code: code.map(str::to_owned),
+            message: "rejected".into(),
+        }
+    }
+
+    #[test]
+    fn server_rejections_are_permanent() {
+        for status in [400, 403, 404, 409, 422] {
+            assert_eq!(
+                classify(&response(status, None)),
+                Failure::Permanent,
+                "{status}"
+            );
+        }
+        // Unique violation, check constraint, RLS denial, bad value
+        for code in ["23505", "23514", "42501", "22P02"] {
+            assert_eq!(
+                classify(&response(400, Some(code))),
+                Failure::Permanent,
+                "{code}"
+            );
+        }
+        // A constraint violation stays permanent whatever status carries it
+        assert_eq!(classify(&response(500, Some("23505"))), Failure::Permanent);
+        assert_eq!(
+            classify(&Error::Invalid("no id".into())),
+            Failure::Permanent
+        );
+        assert_eq!(classify(&Error::NotFound("row".into())), Failure::Permanent);
+    }
+
+    #[test]
+    fn flaky_links_and_server_trouble_are_retried() {
+        for status in [401, 408, 429, 500, 502, 503, 504] {
+            assert_eq!(
+                classify(&response(status, None)),
+                Failure::Transient,
+                "{status}"
+            );
+        }
+        // Serialization failure and lock timeouts are worth another go
+        assert_eq!(classify(&response(500, Some("40001"))), Failure::Transient);
+        assert_eq!(
+            classify(&response(503, Some("PGRST000"))),
+            Failure::Transient
+        );
+
+        let network = reqwest::Client::new().get("not a url").build().unwrap_err();
+        assert_eq!(classify(&Error::Http(network)), Failure::Transient);
+        assert_eq!(classify(&Error::NotConfigured), Failure::Transient);
+        assert_eq!(classify(&Error::Locked), Failure::Transient);
+    }
+
+    #[test]
+    fn backoff_grows_within_bounds() {
+        let secs = Duration::from_secs;
+        // Ceiling doubles from 5 s and stops at 30 minutes, however many
+        // retries there have been
+        let cases = [(0, secs(5)), (1, secs(10)), (3, secs(40)), (9, MAX_DELAY)];
+        for (retries, ceiling) in cases
+            .into_iter()
+            .chain([31, 32, 100, u32::MAX].map(|r| (r, MAX_DELAY)))
+        {
+            for _ in 0..100 {
+                let delay = backoff(retries);
+                assert!(delay >= secs(1) && delay <= ceiling, "{retries}: {delay:?}");
+            }
+        }
+        // Full jitter: late retries spread over the whole range
+        assert!((0..100).any(|_| backoff(20) < secs(5 * 60)));
+        assert!((0..100).any(|_| backoff(20) > secs(25 * 60)));
+    }
+}
@@ -0,0 +1,1177 @@
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::{BinaryHeap, HashMap};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use bincode::Options;
+use osmpbf::{Element, ElementReader};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::error::{Error, Result};
+use crate::geo::{self, Point, EARTH_RADIUS_KM};
+
+const GRAPH_DIR: &str = "routing";
+const GRAPH_FILE: &str = "graph.bin";
+const STATUS_FILE: &str = "graph.json";
+/// Bumped whenever `Graph` changes shape; older files must be re-imported.
+const GRAPH_VERSION: u32 = 1;
+/// Side of a snapping grid cell, in degrees.
+const CELL_DEG: f64 = 0.01;
+/// Points further than this from any usable road are not snapped.
+const MAX_SNAP_KM: f64 = 2.0;
+const WALK_KMH: f64 = 5.0;
+/// Douglas-Peucker tolerance for the returned waypoints; road geometry
+/// finer than this adds points without changing the drawn line.
+const SIMPLIFY_M: f64 = 3.0;
+const MAX_STOPS: usize = 50;
+
+pub const IMPORTED_EVENT: &str = "routing://imported";
+
+static IMPORTING: AtomicBool = AtomicBool::new(false);
+
+struct ImportGuard;
+
+impl ImportGuard {
+    fn acquire() -> Option<Self> {
+        IMPORTING
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| ImportGuard)
+    }
+}
+
+impl Drop for ImportGuard {
+    fn drop(&mut self) {
+        IMPORTING.store(false, Ordering::Release);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TravelMode {
+    Driving,
+    Walking,
+}
+
+// ─── Road classes ───────────────────────────────────────────────
+
+const CAR: u8 = 1;
+const FOOT: u8 = 2;
+
+struct RoadClass {
+    highway: &'static str,
+    /// Typical speed for cars, in km/h.
+    speed_kmh: f64,
+    access: u8,
+}
+
+/// `highway=*` values we route over; the index is stored on each edge.
+const ROAD_CLASSES: &[RoadClass] = &[
+    RoadClass {
+        highway: "motorway",
+        speed_kmh: 90.0,
+        access: CAR,
+    },
+    RoadClass {
+        highway: "motorway_link",
+        speed_kmh: 50.0,
+        access: CAR,
+    },
+    RoadClass {
+        highway: "trunk",
+        speed_kmh: 70.0,
+        access: CAR | FOOT,
+    },
+    RoadClass {
+        highway: "trunk_link",
+        speed_kmh: 45.0,
+        access: CAR | FOOT,
+    },
+    RoadClass {
+        highway: "primary",
+        speed_kmh: 60.0,
+        access: CAR | FOOT,
+    },
+    RoadClass {
+        highway: "primary_link",
+        speed_kmh: 40.0,
+        access: CAR | FOOT,
+    },
+    RoadClass {
+        highway: "secondary",
+        speed_kmh: 50.0,
+        access: CAR | FOOT,
+    },
+    RoadClass {
+        highway: "secondary_link",
+        speed_kmh: 40.0,
+        access: CAR | FOOT,
+    },
+    RoadClass {
+        highway: "tertiary",
+        speed_kmh: 40.0,
+        access: CAR | FOOT,
+    },
+    RoadClass {
+        highway: "tertiary_link",
+        speed_kmh: 30.0,
+        access: CAR | FOOT,
+    },
+    RoadClass {
+        highway: "unclassified",
+        speed_kmh: 30.0,
+        access: CAR | FOOT,
+    },
+    RoadClass {
+        highway: "residential",
+        speed_kmh: 30.0,
+        access: CAR | FOOT,
+    },
+    RoadClass {
+        highway: "road",
+        speed_kmh: 30.0,
+        access: CAR | FOOT,
+    },
+    RoadClass {
+        highway: "service",
+        speed_kmh: 15.0,
+        access: CAR | FOOT,
+    },
+    RoadClass {
+        highway: "living_street",
+        speed_kmh: 10.0,
+        access: CAR | FOOT,
+    },
+    RoadClass {
+        highway: "track",
+        speed_kmh: 15.0,
+        access: CAR | FOOT,
+    },
+    RoadClass {
+        highway: "pedestrian",
+        speed_kmh: 0.0,
+        access: FOOT,
+    },
+    RoadClass {
+        highway: "footway",
+        speed_kmh: 0.0,
+        access: FOOT,
+    },
+    RoadClass {
+        highway: "path",
+        speed_kmh: 0.0,
+        access: FOOT,
+    },
+    RoadClass {
+        highway: "steps",
+        speed_kmh: 0.0,
+        access: FOOT,
+    },
+    RoadClass {
+        highway: "cycleway",
+        speed_kmh: 0.0,
+        access: FOOT,
+    },
+    RoadClass {
+        highway: "bridleway",
+        speed_kmh: 0.0,
+        access: FOOT,
+    },
+];
+
+const MAX_CAR_KMH: f64 = 90.0;
+
+/// A routable way from the extract, before its nodes have coordinates.
+struct Way {
+    refs: Vec<i64>,
+    class: u8,
+    access: u8,
+    /// 1 forward only, -1 backward only, 0 both ways (for cars).
+    oneway: i8,
+}
+
+impl Way {
+    fn from_tags<'a>(
+        tags: impl Iterator<Item = (&'a str, &'a str)>,
+        refs: Vec<i64>,
+    ) -> Option<Way> {
+        let tags: HashMap<&str, &str> = tags.collect();
+        let class = ROAD_CLASSES
+            .iter()
+            .position(|c| Some(&c.highway) == tags.get("highway"))?;
+        if tags.get("area") == Some(&"yes") || refs.len() < 2 {
+            return None;
+        }
+
+        let no = |key: &str| matches!(tags.get(key), Some(&"no") | Some(&"private"));
+        let yes = |key: &str| {
+            matches!(
+                tags.get(key),
+                Some(&"yes") | Some(&"designated") | Some(&"permissive")
+            )
+        };
+        let mut access = ROAD_CLASSES[class].access;
+        if no("access") {
+            access = 0;
+        }
+        if no("motor_vehicle") || no("motorcar") || no("vehicle") {
+            access &= !CAR;
+        }
+        if no("foot") {
+            access &= !FOOT;
+        } else if yes("foot") {
+            access |= FOOT;
+        }
+        if (yes("motor_vehicle") || yes("motorcar")) && ROAD_CLASSES[class].speed_kmh > 0.0 {
+            access |= CAR;
+        }
+        if access == 0 {
+            return None;
+        }
+
+        let oneway = match tags.get("oneway").copied() {
+            Some("yes" | "1" | "true") => 1,
+            Some("-1" | "reverse") => -1,
+            Some("no" | "false" | "0") => 0,
+            _ if matches!(
+                tags.get("junction").copied(),
+                Some("roundabout" | "circular")
+            ) =>
+            {
+                1
+            }
+            _ if ROAD_CLASSES[class].highway == "motorway" => 1,
+            _ => 0,
+        };
+        Some(Way {
+            refs,
+            class: class as u8,
+            access,
+            oneway,
+        })
+    }
+}
+
+// ─── Graph ──────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Edge {
+    to: u32,
+    length_m: f32,
+    class: u8,
+    access: u8,
+}
+
+impl Edge {
+    /// Seconds to travel the edge, or `None` if `mode` may not use it.
+    fn cost(&self, mode: TravelMode) -> Option<f64> {
+        let length = f64::from(self.length_m);
+        match mode {
+            TravelMode::Driving if self.access & CAR != 0 => {
+                Some(length / (ROAD_CLASSES[usize::from(self.class)].speed_kmh / 3.6))
+            }
+            TravelMode::Walking if self.access & FOOT != 0 => Some(length / (WALK_KMH / 3.6)),
+            _ => None,
+        }
+    }
+}
+
+/// Road network in compressed adjacency form: the edges leaving node `n`
+/// are `edges[offsets[n]..offsets[n + 1]]`.
+#[derive(Serialize, Deserialize)]
+pub struct Graph {
+    version: u32,
+    /// `(lat, lng)` per node.
+    coords: Vec<(f64, f64)>,
+    offsets: Vec<u32>,
+    edges: Vec<Edge>,
+    #[serde(skip)]
+    grid: HashMap<(i32, i32), Vec<u32>>,
+}
+
+fn cell(lat: f64, lng: f64) -> (i32, i32) {
+    (
+        (lat / CELL_DEG).floor() as i32,
+        (lng / CELL_DEG).floor() as i32,
+    )
+}
+
+/// The graph file encoding: what `bincode::serialize_into` writes, read
+/// back with a limit so a damaged length can't ask for more memory than the
+/// file holds.
+fn encoding(limit: u64) -> impl Options {
+    bincode::options()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .with_limit(limit)
+}
+
+fn osm_error(e: osmpbf::Error) -> Error {
+    Error::Invalid(format!("could not read the OSM extract: {e}"))
+}
+
+impl Graph {
+    /// Build the road graph from an `.osm.pbf` extract. Two passes: ways
+    /// first, to learn which nodes are on roads, then those nodes' positions.
+    fn from_pbf(path: &Path) -> Result<Self> {
+        let mut ways = Vec::new();
+        let mut ids: HashMap<i64, u32> = HashMap::new();
+        ElementReader::from_path(path)
+            .map_err(osm_error)?
+            .for_each(|element| {
+                if let Element::Way(way) = element {
+                    if let Some(way) = Way::from_tags(way.tags(), way.refs().collect()) {
+                        for id in &way.refs {
+                            ids.insert(*id, u32::MAX);
+                        }
+                        ways.push(way);
+                    }
+                }
+            })
+            .map_err(osm_error)?;
+        if ways.is_empty() {
+            return Err(Error::Invalid("the extract contains no roads".into()));
+        }
+
+        let mut coords = Vec::with_capacity(ids.len());
+        let mut place = |id: i64, lat: f64, lng: f64| {
+            if let Some(index) = ids.get_mut(&id) {
+                *index = coords.len() as u32;
+                coords.push((lat, lng));
+            }
+        };
+        ElementReader::from_path(path)
+            .map_err(osm_error)?
+            .for_each(|element| match element {
+                Element::Node(node) => place(node.id(), node.lat(), node.lon()),
+                Element::DenseNode(node) => place(node.id(), node.lat(), node.lon()),
+                _ => {}
+            })
+            .map_err(osm_error)?;
+
+        let mut directed: Vec<(u32, Edge)> = Vec::new();
+        for way in &ways {
+            for pair in way.refs.windows(2) {
+                // Extracts clipped at a border reference nodes they don't contain
+                let (Some(&a), Some(&b)) = (ids.get(&pair[0]), ids.get(&pair[1])) else {
+                    continue;
+                };
+                if a == u32::MAX || b == u32::MAX || a == b {
+                    continue;
+                }
+                let (pa, pb) = (coords[a as usize], coords[b as usize]);
+                let length_m = (geo::distance_km(
+                    Point {
+                        lat: pa.0,
+                        lng: pa.1,
+                    },
+                    Point {
+                        lat: pb.0,
+                        lng: pb.1,
+                    },
+                ) * 1000.0) as f32;
+                let foot = way.access & FOOT;
+                let car = way.access & CAR;
+                let forward = foot | if way.oneway >= 0 { car } else { 0 };
+                let backward = foot | if way.oneway <= 0 { car } else { 0 };
+                for (from, to, access) in [(a, b, forward), (b, a, backward)] {
+                    if access != 0 {
+                        directed.push((
+                            from,
+                            Edge {
+                                to,
+                                length_m,
+                                class: way.class,
+                                access,
+                            },
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(Self::from_edges(coords, directed))
+    }
+
+    /// Compress `(from, edge)` pairs into adjacency form.
+    fn from_edges(coords: Vec<(f64, f64)>, mut directed: Vec<(u32, Edge)>) -> Self {
+        directed.sort_unstable_by_key(|(from, _)| *from);
+
+        let mut offsets = vec![0u32; coords.len() + 1];
+        for (from, _) in &directed {
+            offsets[*from as usize + 1] += 1;
+        }
+        for i in 1..offsets.len() {
+            offsets[i] += offsets[i - 1];
+        }
+        let mut graph = Graph {
+            version: GRAPH_VERSION,
+            coords,
+            offsets,
+            edges: directed.into_iter().map(|(_, edge)| edge).collect(),
+            grid: HashMap::new(),
+        };
+        graph.index();
+        graph
+    }
+
+    fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path)?;
+        let limit = file.metadata()?.len();
+        let damaged = |e: &dyn std::fmt::Display| {
+            Error::Invalid(format!("the road network file is damaged: {e}"))
+        };
+        let mut graph: Graph = encoding(limit)
+            .deserialize_from(BufReader::new(file))
+            .map_err(|e| damaged(&e))?;
+        if graph.version != GRAPH_VERSION {
+            return Err(Error::Invalid(
+                "the road network was built by an older version; import the extract again".into(),
+            ));
+        }
+        graph.check().map_err(|e| damaged(&e))?;
+        graph.index();
+        Ok(graph)
+    }
+
+    /// Whether the adjacency arrays agree, so indexing them can't panic.
+    fn check(&self) -> std::result::Result<(), &'static str> {
+        let nodes = self.coords.len();
+        if self.offsets.len() != nodes + 1
+            || self.offsets.first() != Some(&0)
+            || self.offsets.last().map(|o| *o as usize) != Some(self.edges.len())
+            || self.offsets.windows(2).any(|w| w[0] > w[1])
+        {
+            return Err("edge offsets don't match the edges");
+        }
+        if self
+            .edges
+            .iter()
+            .any(|e| e.to as usize >= nodes || usize::from(e.class) >= ROAD_CLASSES.len())
+        {
+            return Err("an edge points outside the graph");
+        }
+        Ok(())
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension("bin.tmp");
+        encoding(u64::MAX)
+            .serialize_into(BufWriter::new(File::create(&tmp)?), self)
+            .map_err(|e| Error::Invalid(format!("could not save the road network: {e}")))?;
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    /// Grid of nodes that have at least one road leaving them, for snapping.
+    fn index(&mut self) {
+        self.grid.clear();
+        for (node, (lat, lng)) in self.coords.iter().enumerate() {
+            if self.offsets[node] != self.offsets[node + 1] {
+                self.grid
+                    .entry(cell(*lat, *lng))
+                    .or_default()
+                    .push(node as u32);
+            }
+        }
+    }
+
+    fn point(&self, node: u32) -> Point {
+        let (lat, lng) = self.coords[node as usize];
+        Point { lat, lng }
+    }
+
+    fn edges(&self, node: u32) -> &[Edge] {
+        let node = node as usize;
+        &self.edges[self.offsets[node] as usize..self.offsets[node + 1] as usize]
+    }
+
+    /// Closest node `mode` can leave from, within `MAX_SNAP_KM`.
+    fn snap(&self, p: Point, mode: TravelMode) -> Option<u32> {
+        let (row, col) = cell(p.lat, p.lng);
+        let rows = (MAX_SNAP_KM / (EARTH_RADIUS_KM * CELL_DEG.to_radians())).ceil() as i32;
+        let cols = (f64::from(rows) / p.lat.to_radians().cos().max(0.05)).ceil() as i32;
+        let mut best: Option<(f64, u32)> = None;
+        for r in row - rows..=row + rows {
+            for c in col - cols..=col + cols {
+                for &node in self.grid.get(&(r, c)).into_iter().flatten() {
+                    if !self.edges(node).iter().any(|e| e.cost(mode).is_some()) {
+                        continue;
+                    }
+                    let d = geo::distance_km(p, self.point(node));
+                    if d <= MAX_SNAP_KM && !best.is_some_and(|(bd, _)| bd <= d) {
+                        best = Some((d, node));
+                    }
+                }
+            }
+        }
+        best.map(|(_, node)| node)
+    }
+
+    /// A* from `from` to `to`; returns the node path and its cost in seconds.
+    fn shortest_path(
+        &self,
+        from: u32,
+        to: u32,
+        mode: TravelMode,
+        avoid: &[Area],
+    ) -> Option<(Vec<u32>, f64)> {
+        let max_speed = match mode {
+            TravelMode::Driving => MAX_CAR_KMH,
+            TravelMode::Walking => WALK_KMH,
+        } / 3.6;
+        let target = self.point(to);
+        let heuristic = |node: u32| geo::distance_km(self.point(node), target) * 1000.0 / max_speed;
+
+        let n = self.coords.len();
+        let mut cost = vec![f64::INFINITY; n];
+        let mut prev = vec![u32::MAX; n];
+        let mut heap = BinaryHeap::new();
+        cost[from as usize] = 0.0;
+        heap.push(Queued {
+            estimate: heuristic(from),
+            node: from,
+        });
+
+        while let Some(Queued { estimate, node }) = heap.pop() {
+            if node == to {
+                let mut path = vec![to];
+                let mut at = to;
+                while at != from {
+                    at = prev[at as usize];
+                    path.push(at);
+                }
+                path.reverse();
+                return Some((path, cost[to as usize]));
+            }
+            let here = cost[node as usize];
+            // Stale entry; the node was reached more cheaply since
+            if estimate > here + heuristic(node) + 1e-6 {
+                continue;
+            }
+            let a = self.point(node);
+            for edge in self.edges(node) {
+                let Some(step) = edge.cost(mode) else {
+                    continue;
+                };
+                let next = here + step;
+                if next >= cost[edge.to as usize] {
+                    continue;
+                }
+                if avoid.iter().any(|area| area.blocks(a, self.point(edge.to))) {
+                    continue;
+                }
+                cost[edge.to as usize] = next;
+                prev[edge.to as usize] = node;
+                heap.push(Queued {
+                    estimate: next + heuristic(edge.to),
+                    node: edge.to,
+                });
+            }
+        }
+        None
+    }
+}
+
+/// Min-heap entry for A*.
+struct Queued {
+    estimate: f64,
+    node: u32,
+}
+
+impl PartialEq for Queued {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == CmpOrdering::Equal
+    }
+}
+
+impl Eq for Queued {}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        // Reversed: BinaryHeap pops the largest
+        other
+            .estimate
+            .total_cmp(&self.estimate)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+// ─── Avoidance areas ────────────────────────────────────────────
+
+/// A closed area (collapsed bridge, flooded underpass) routes must not enter.
+struct Area {
+    ring: Vec<Point>,
+    /// `[west, south, east, north]`.
+    bbox: [f64; 4],
+}
+
+impl Area {
+    fn new(ring: Vec<Point>) -> Result<Self> {
+        if ring.len() < 3 {
+            return Err(Error::Invalid(
+                "an avoidance area needs at least three points".into(),
+            ));
+        }
+        for p in &ring {
+            p.validate()?;
+        }
+        let bbox = ring.iter().fold(
+            [
+                f64::INFINITY,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::NEG_INFINITY,
+            ],
+            |[w, s, e, n], p| [w.min(p.lng), s.min(p.lat), e.max(p.lng), n.max(p.lat)],
+        );
+        Ok(Self { ring, bbox })
+    }
+
+    fn contains(&self, p: Point) -> bool {
+        let mut inside = false;
+        let mut j = self.ring.len() - 1;
+        for i in 0..self.ring.len() {
+            let (a, b) = (self.ring[i], self.ring[j]);
+            if (a.lat > p.lat) != (b.lat > p.lat)
+                && p.lng < (b.lng - a.lng) * (p.lat - a.lat) / (b.lat - a.lat) + a.lng
+            {
+                inside = !inside;
+            }
+            j = i;
+        }
+        inside
+    }
+
+    /// Whether the road segment `a`-`b` enters the area.
+    fn blocks(&self, a: Point, b: Point) -> bool {
+        let [w, s, e, n] = self.bbox;
+        if a.lng.max(b.lng) < w
+            || a.lng.min(b.lng) > e
+            || a.lat.max(b.lat) < s
+            || a.lat.min(b.lat) > n
+        {
+            return false;
+        }
+        if self.contains(a) || self.contains(b) {
+            return true;
+        }
+        let mut j = self.ring.len() - 1;
+        for i in 0..self.ring.len() {
+            if crosses(a, b, self.ring[j], self.ring[i]) {
+                return true;
+            }
+            j = i;
+        }
+        false
+    }
+}
+
+fn crosses(a1: Point, a2: Point, b1: Point, b2: Point) -> bool {
+    let side = |o: Point, p: Point, q: Point| {
+        ((p.lng - o.lng) * (q.lat - o.lat) - (p.lat - o.lat) * (q.lng - o.lng)).signum()
+    };
+    side(a1, a2, b1) != side(a1, a2, b2) && side(b1, b2, a1) != side(b1, b2, a2)
+}
+
+// ─── Output ─────────────────────────────────────────────────────
+
+/// Drop points that lie within `SIMPLIFY_M` of the line through their
+/// neighbours (Douglas-Peucker).
+fn simplify(points: &[Point]) -> Vec<Point> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+    let origin = points[0];
+    let scale = origin.lat.to_radians().cos();
+    let xy: Vec<(f64, f64)> = points
+        .iter()
+        .map(|p| {
+            (
+                (p.lng - origin.lng).to_radians() * scale * EARTH_RADIUS_KM * 1000.0,
+                (p.lat - origin.lat).to_radians() * EARTH_RADIUS_KM * 1000.0,
+            )
+        })
+        .collect();
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+    let mut stack = vec![(0, points.len() - 1)];
+    while let Some((first, last)) = stack.pop() {
+        let (a, b) = (xy[first], xy[last]);
+        let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+        let len = dx.hypot(dy);
+        let mut farthest = (0.0, first);
+        for (i, p) in xy.iter().enumerate().take(last).skip(first + 1) {
+            let d = if len == 0.0 {
+                (p.0 - a.0).hypot(p.1 - a.1)
+            } else {
+                ((p.0 - a.0) * dy - (p.1 - a.1) * dx).abs() / len
+            };
+            if d > farthest.0 {
+                farthest = (d, i);
+            }
+        }
+        if farthest.0 > SIMPLIFY_M {
+            keep[farthest.1] = true;
+            stack.push((first, farthest.1));
+            stack.push((farthest.1, last));
+        }
+    }
+    points
+        .iter()
+        .zip(keep)
+        .filter_map(|(p, keep)| keep.then_some(*p))
+        .collect()
+}
+
+/// A route along the road network, with the field names of an
+/// `evacuation_routes` row so it can be saved as one.
+#[derive(Debug, Clone, Serialize)]
+pub struct RoutePlan {
+    pub waypoints: Vec<Point>,
+    pub distance_km: f64,
+    pub estimated_time_minutes: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteRequest {
+    /// Start, any intermediate stops, and end.
+    pub stops: Vec<Point>,
+    pub mode: TravelMode,
+    /// Polygons to route around.
+    #[serde(default)]
+    pub avoid: Vec<Vec<Point>>,
+}
+
+fn plan(graph: &Graph, request: RouteRequest) -> Result<RoutePlan> {
+    if request.stops.len() < 2 || request.stops.len() > MAX_STOPS {
+        return Err(Error::Invalid(format!(
+            "a route needs between 2 and {MAX_STOPS} stops"
+        )));
+    }
+    let avoid = request
+        .avoid
+        .into_iter()
+        .map(Area::new)
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut nodes = Vec::with_capacity(request.stops.len());
+    for (i, stop) in request.stops.iter().enumerate() {
+        stop.validate()?;
+        let node = graph.snap(*stop, request.mode).ok_or_else(|| {
+            Error::Invalid(format!(
+                "stop {} is more than {MAX_SNAP_KM} km from a usable road",
+                i + 1
+            ))
+        })?;
+        nodes.push(node);
+    }
+
+    let mut path: Vec<u32> = vec![nodes[0]];
+    let mut seconds = 0.0;
+    for (i, leg) in nodes.windows(2).enumerate() {
+        let (leg_path, leg_seconds) = graph
+            .shortest_path(leg[0], leg[1], request.mode, &avoid)
+            .ok_or_else(|| {
+                Error::Invalid(format!(
+                    "no road route from stop {} to stop {}",
+                    i + 1,
+                    i + 2
+                ))
+            })?;
+        path.extend_from_slice(&leg_path[1..]);
+        seconds += leg_seconds;
+    }
+
+    let points: Vec<Point> = path.iter().map(|node| graph.point(*node)).collect();
+    let distance_km: f64 = points
+        .windows(2)
+        .map(|pair| geo::distance_km(pair[0], pair[1]))
+        .sum();
+    Ok(RoutePlan {
+        waypoints: simplify(&points),
+        distance_km: (distance_km * 1000.0).round() / 1000.0,
+        estimated_time_minutes: (seconds / 60.0).ceil() as u32,
+    })
+}
+
+// ─── State & commands ───────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutingStatus {
+    /// File name of the imported extract.
+    pub source: String,
+    pub imported_at: String,
+    pub nodes: usize,
+    pub edges: usize,
+    /// `[west, south, east, north]` of the road network.
+    pub bounds: Option<[f64; 4]>,
+}
+
+/// The road graph, loaded from disk on first use.
+#[derive(Default)]
+pub struct Router {
+    graph: Mutex<Option<Arc<Graph>>>,
+}
+
+fn graph_dir(app: &AppHandle) -> Result<PathBuf> {
+    let dir = app.path().app_data_dir()?.join(GRAPH_DIR);
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+impl Router {
+    fn graph(&self, app: &AppHandle) -> Result<Arc<Graph>> {
+        let mut slot = self.graph.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(graph) = slot.as_ref() {
+            return Ok(graph.clone());
+        }
+        let path = graph_dir(app)?.join(GRAPH_FILE);
+        if !path.exists() {
+            return Err(Error::Invalid(
+                "no road network yet; import an OSM extract for your region first".into(),
+            ));
+        }
+        let graph = Arc::new(Graph::load(&path)?);
+        *slot = Some(graph.clone());
+        Ok(graph)
+    }
+
+    fn replace(&self, graph: Option<Arc<Graph>>) {
+        *self.graph.lock().unwrap_or_else(|e| e.into_inner()) = graph;
+    }
+}
+
+fn status_of(graph: &Graph, source: String) -> RoutingStatus {
+    let bounds = graph
+        .coords
+        .iter()
+        .fold(None, |acc: Option<[f64; 4]>, (lat, lng)| {
+            Some(match acc {
+                None => [*lng, *lat, *lng, *lat],
+                Some([w, s, e, n]) => [w.min(*lng), s.min(*lat), e.max(*lng), n.max(*lat)],
+            })
+        });
+    RoutingStatus {
+        source,
+        imported_at: chrono::Utc::now().to_rfc3339(),
+        nodes: graph.coords.len(),
+        edges: graph.edges.len(),
+        bounds,
+    }
+}
+
+/// Build the road network from an `.osm.pbf` extract (e.g. from Geofabrik)
+/// and keep it for offline routing. Replaces any earlier import.
+#[tauri::command]
+pub async fn routing_import(app: AppHandle, path: String) -> Result<RoutingStatus> {
+    let Some(_guard) = ImportGuard::acquire() else {
+        return Err(Error::Invalid(
+            "a road network import is already running".into(),
+        ));
+    };
+    let source = PathBuf::from(path);
+    let dir = graph_dir(&app)?;
+
+    let (graph, status) = tauri::async_runtime::spawn_blocking(move || -> Result<_> {
+        let graph = Graph::from_pbf(&source)?;
+        graph.save(&dir.join(GRAPH_FILE))?;
+        let name = source
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let status = status_of(&graph, name);
+        std::fs::write(dir.join(STATUS_FILE), serde_json::to_vec_pretty(&status)?)?;
+        Ok((graph, status))
+    })
+    .await??;
+
+    app.state::<Router>().replace(Some(Arc::new(graph)));
+    eprintln!(
+        "[routing] Imported {} ({} nodes, {} edges)",
+        status.source, status.nodes, status.edges
+    );
+    let _ = app.emit(IMPORTED_EVENT, &status);
+    Ok(status)
+}
+
+#[tauri::command]
+pub fn routing_status(app: AppHandle) -> Result<Option<RoutingStatus>> {
+    let path = graph_dir(&app)?.join(STATUS_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_slice(&std::fs::read(path)?)?))
+}
+
+/// Shortest route by road through `stops`, avoiding the given areas.
+#[tauri::command]
+pub async fn routing_route(app: AppHandle, request: RouteRequest) -> Result<RoutePlan> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let graph = app.state::<Router>().graph(&app)?;
+        plan(&graph, request)
+    })
+    .await?
+}
+
+#[tauri::command]
+pub fn routing_clear(app: AppHandle, router: State<'_, Router>) -> Result<()> {
+    router.replace(None);
+    let dir = graph_dir(&app)?;
+    for file in [GRAPH_FILE, STATUS_FILE] {
+        let path = dir.join(file);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn class(highway: &str) -> u8 {
+        ROAD_CLASSES
+            .iter()
+            .position(|c| c.highway == highway)
+            .unwrap() as u8
+    }
+
+    fn at(lat: f64, lng: f64) -> Point {
+        Point { lat, lng }
+    }
+
+    /// Two ways from A (0) to D (3), about 1.1 km square:
+    ///
+    /// ```text
+    /// B(1) ── C(2)
+    ///  │        │
+    /// A(0) ── D(3)
+    /// ```
+    /// A–D is a residential street, A–B–C–D a primary road, B–C one way
+    /// east, and a footway runs from A to E (4) north-west of A.
+    fn square() -> Graph {
+        let coords = vec![
+            (0.0, 0.0),
+            (0.01, 0.0),
+            (0.01, 0.01),
+            (0.0, 0.01),
+            (0.005, -0.005),
+        ];
+        let road = |from: u32, to: u32, highway: &str, access: u8| {
+            let (a, b) = (coords[from as usize], coords[to as usize]);
+            let length_m = (geo::distance_km(at(a.0, a.1), at(b.0, b.1)) * 1000.0) as f32;
+            (
+                from,
+                Edge {
+                    to,
+                    length_m,
+                    class: class(highway),
+                    access,
+                },
+            )
+        };
+        let edges = vec![
+            road(0, 3, "residential", CAR | FOOT),
+            road(3, 0, "residential", CAR | FOOT),
+            road(0, 1, "primary", CAR | FOOT),
+            road(1, 0, "primary", CAR | FOOT),
+            road(1, 2, "primary", CAR | FOOT),
+            road(2, 1, "primary", FOOT),
+            road(2, 3, "primary", CAR | FOOT),
+            road(3, 2, "primary", CAR | FOOT),
+            road(0, 4, "footway", FOOT),
+            road(4, 0, "footway", FOOT),
+        ];
+        Graph::from_edges(coords, edges)
+    }
+
+    #[test]
+    fn a_star_takes_the_quickest_way() {
+        let graph = square();
+        // The primary road is twice as fast but three times as long
+        let (path, seconds) = graph.shortest_path(0, 3, TravelMode::Driving, &[]).unwrap();
+        assert_eq!(path, [0, 3]);
+        assert!((seconds - 1112.0 / (30.0 / 3.6)).abs() < 5.0);
+
+        // Walking ignores road speeds
+        let (path, _) = graph.shortest_path(0, 2, TravelMode::Walking, &[]).unwrap();
+        assert_eq!(path.len(), 3);
+    }
+
+    #[test]
+    fn one_way_roads_and_footways_limit_driving() {
+        let graph = square();
+        // C to B is one way the other way for cars, so drive around
+        let (path, _) = graph.shortest_path(2, 1, TravelMode::Driving, &[]).unwrap();
+        assert_eq!(path, [2, 3, 0, 1]);
+        let (path, _) = graph.shortest_path(2, 1, TravelMode::Walking, &[]).unwrap();
+        assert_eq!(path, [2, 1]);
+
+        assert!(graph
+            .shortest_path(0, 4, TravelMode::Driving, &[])
+            .is_none());
+        assert!(graph
+            .shortest_path(0, 4, TravelMode::Walking, &[])
+            .is_some());
+    }
+
+    #[test]
+    fn avoided_areas_force_a_detour() {
+        let graph = square();
+        // A flooded patch across the residential street
+        let flood = Area::new(vec![
+            at(-0.001, 0.004),
+            at(0.001, 0.004),
+            at(0.001, 0.006),
+            at(-0.001, 0.006),
+        ])
+        .unwrap();
+        let (path, _) = graph
+            .shortest_path(0, 3, TravelMode::Driving, &[flood])
+            .unwrap();
+        assert_eq!(path, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn areas_block_segments_that_enter_them() {
+        let area = Area::new(vec![at(0.0, 0.0), at(0.0, 1.0), at(1.0, 1.0), at(1.0, 0.0)]).unwrap();
+        // Straight through, with both ends outside
+        assert!(area.blocks(at(0.5, -1.0), at(0.5, 2.0)));
+        // Starting inside
+        assert!(area.blocks(at(0.5, 0.5), at(0.5, 3.0)));
+        // Passing by, inside the bounding box of a triangle but not the triangle
+        let triangle = Area::new(vec![at(0.0, 0.0), at(1.0, 0.0), at(0.0, 1.0)]).unwrap();
+        assert!(!triangle.blocks(at(0.9, 0.8), at(0.8, 0.9)));
+        assert!(!area.blocks(at(2.0, 2.0), at(3.0, 3.0)));
+        assert!(Area::new(vec![at(0.0, 0.0), at(1.0, 1.0)]).is_err());
+    }
+
+    #[test]
+    fn simplify_drops_points_on_the_line() {
+        let line = [
+            at(0.0, 0.0),
+            at(0.0, 0.001),
+            at(0.0, 0.002),
+            at(0.001, 0.002),
+        ];
+        assert_eq!(
+            simplify(&line),
+            [at(0.0, 0.0), at(0.0, 0.002), at(0.001, 0.002)]
+        );
+        // A wobble under three metres is not a corner
+        let wobble = [at(0.0, 0.0), at(0.000_01, 0.001), at(0.0, 0.002)];
+        assert_eq!(simplify(&wobble).len(), 2);
+        assert_eq!(simplify(&line[..2]).len(), 2);
+    }
+
+    fn way(tags: &[(&'static str, &'static str)]) -> Option<Way> {
+        Way::from_tags(tags.iter().copied(), vec![1, 2])
+    }
+
+    #[test]
+    fn ways_take_access_and_direction_from_their_tags() {
+        let street = way(&[("highway", "residential")]).unwrap();
+        assert_eq!((street.access, street.oneway), (CAR | FOOT, 0));
+
+        let path = way(&[("highway", "footway")]).unwrap();
+        assert_eq!(path.access, FOOT);
+        assert_eq!(way(&[("highway", "motorway")]).unwrap().oneway, 1);
+        let roundabout = way(&[("highway", "primary"), ("junction", "roundabout")]).unwrap();
+        assert_eq!(roundabout.oneway, 1);
+        assert_eq!(
+            way(&[("highway", "primary"), ("oneway", "-1")])
+                .unwrap()
+                .oneway,
+            -1
+        );
+
+        let no_cars = way(&[("highway", "tertiary"), ("motor_vehicle", "no")]).unwrap();
+        assert_eq!(no_cars.access, FOOT);
+        assert!(way(&[("highway", "service"), ("access", "private")]).is_none());
+        assert!(way(&[("highway", "pedestrian"), ("area", "yes")]).is_none());
+        assert!(way(&[("highway", "proposed")]).is_none());
+        assert!(way(&[("building", "yes")]).is_none());
+        assert!(Way::from_tags([("highway", "primary")].into_iter(), vec![1]).is_none());
+    }
+
+    #[test]
+    fn snap_finds_the_nearest_road_the_mode_may_use() {
+        let graph = square();
+        assert_eq!(graph.snap(at(0.0098, 0.0002), TravelMode::Driving), Some(1));
+        // The footway end is closest, but cars can't leave from it
+        assert_eq!(graph.snap(at(0.005, -0.005), TravelMode::Walking), Some(4));
+        assert_eq!(graph.snap(at(0.004, -0.005), TravelMode::Driving), Some(0));
+        assert_eq!(graph.snap(at(1.0, 1.0), TravelMode::Walking), None);
+    }
+
+    #[test]
+    fn plans_route_through_every_stop() {
+        let graph = square();
+        let route = plan(
+            &graph,
+            RouteRequest {
+                stops: vec![at(0.0, 0.0), at(0.01, 0.0), at(0.01, 0.01)],
+                mode: TravelMode::Driving,
+                avoid: Vec::new(),
+            },
+        )
+        .unwrap();
+        assert_eq!(route.waypoints.len(), 3);
+        assert!((route.distance_km - 2.22).abs() < 0.01);
+        assert!(plan(
+            &graph,
+            RouteRequest {
+                stops: vec![at(0.0, 0.0)],
+                mode: TravelMode::Driving,
+                avoid: Vec::new(),
+            },
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn graph_files_round_trip_and_damage_is_caught() {
+        let dir = std::env::temp_dir().join(format!("routing-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(GRAPH_FILE);
+        square().save(&path).unwrap();
+
+        let loaded = Graph::load(&path).unwrap();
+        assert_eq!(loaded.coords.len(), 5);
+        assert_eq!(loaded.edges.len(), 10);
+        assert_eq!(loaded.snap(at(0.0, 0.0), TravelMode::Driving), Some(0));
+
+        // Cut off halfway
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() / 2]).unwrap();
+        assert!(Graph::load(&path).is_err());
+
+        // A coordinate count far beyond the file
+        let mut huge = bytes.clone();
+        huge[4..12].copy_from_slice(&u64::MAX.to_le_bytes());
+        std::fs::write(&path, &huge).unwrap();
+        assert!(Graph::load(&path).is_err());
+
+        // An edge to a node that doesn't exist
+        let mut graph = square();
+        graph.edges[0].to = 99;
+        graph.save(&path).unwrap();
+        assert!(Graph::load(&path).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
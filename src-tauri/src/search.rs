@@ -0,0 +1,293 @@
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params_from_iter, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::db::Db;
+use crate::error::{Error, Result};
+use crate::mirror;
+
+/// Mirrored tables that feed `search_index` (see migration v2).
+pub const ENTITIES: &[&str] = &[
+    "incidents",
+    "incident_updates",
+    "messages",
+    "tasks",
+    "documents",
+];
+
+const DEFAULT_LIMIT: u32 = 20;
+const MAX_LIMIT: u32 = 200;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchQuery {
+    pub text: String,
+    /// Restrict to some of `ENTITIES`; all of them when empty.
+    #[serde(default)]
+    pub entities: Vec<String>,
+    /// Only records created at or after this ISO timestamp.
+    pub since: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub entity: String,
+    pub id: String,
+    /// Incident of an update, task or document; channel of a message.
+    pub parent_id: Option<String>,
+    pub created_at: Option<String>,
+    /// Title with matches wrapped in `<mark>`. Messages and incident
+    /// updates have none of their own, so they get their incident's title,
+    /// or failing that the snippet.
+    pub title: String,
+    /// Best matching fragment of the body, matches wrapped in `<mark>`.
+    pub snippet: String,
+    /// Higher is better.
+    pub score: f64,
+}
+
+/// Turn free text into an FTS5 query: every word must match, the last one
+/// as a prefix so results show up while typing. Quoting each term keeps
+/// FTS5 operators in user input from being interpreted.
+pub fn fts_query(text: &str) -> Option<String> {
+    let terms: Vec<String> = text
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+    if terms.is_empty() {
+        return None;
+    }
+    Some(format!("{}*", terms.join(" ")))
+}
+
+pub fn query_index(conn: &Connection, query: &SearchQuery) -> Result<Vec<SearchHit>> {
+    let Some(matcher) = fts_query(&query.text) else {
+        return Ok(Vec::new());
+    };
+
+    // Title matches count ten times as much as body matches
+    let mut sql = String::from(
+        "SELECT entity, entity_id, parent_id, created_at,
+                highlight(search_index, 4, '<mark>', '</mark>'),
+                snippet(search_index, 5, '<mark>', '</mark>', '…', 16),
+                bm25(search_index, 0, 0, 0, 0, 10.0, 1.0) AS rank
+         FROM search_index
+         WHERE search_index MATCH ?",
+    );
+    let mut args = vec![SqlValue::Text(matcher)];
+
+    if !query.entities.is_empty() {
+        for entity in &query.entities {
+            if !ENTITIES.contains(&entity.as_str()) {
+                return Err(Error::Invalid(format!("{entity} is not searchable")));
+            }
+        }
+        let placeholders = vec!["?"; query.entities.len()].join(", ");
+        sql.push_str(&format!(" AND entity IN ({placeholders})"));
+        args.extend(query.entities.iter().cloned().map(SqlValue::Text));
+    }
+    if let Some(since) = &query.since {
+        sql.push_str(" AND created_at >= ?");
+        args.push(SqlValue::Text(since.clone()));
+    }
+
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    sql.push_str(&format!(" ORDER BY rank LIMIT {limit}"));
+
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params_from_iter(args), |row| {
+        Ok(SearchHit {
+            entity: row.get(0)?,
+            id: row.get(1)?,
+            parent_id: row.get(2)?,
+            created_at: row.get(3)?,
+            title: row.get(4)?,
+            snippet: row.get(5)?,
+            // bm25 is lower-is-better and negative
+            score: -row.get::<_, f64>(6)?,
+        })
+    })?;
+    let mut hits = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+    for hit in hits.iter_mut().filter(|hit| hit.title.is_empty()) {
+        hit.title = match parent_title(conn, hit)? {
+            Some(title) => title,
+            None => hit.snippet.trim().to_owned(),
+        };
+    }
+    Ok(hits)
+}
+
+/// Title of the incident a hit belongs to; a message's parent is its
+/// channel instead.
+fn parent_title(conn: &Connection, hit: &SearchHit) -> Result<Option<String>> {
+    let Some(parent_id) = hit
+        .parent_id
+        .as_deref()
+        .filter(|_| hit.entity != "messages")
+    else {
+        return Ok(None);
+    };
+    Ok(mirror::get(conn, "incidents", parent_id)?
+        .and_then(|incident| incident.get("title")?.as_str().map(str::to_owned))
+        .filter(|title| !title.is_empty()))
+}
+
+#[tauri::command]
+pub fn search(db: State<'_, Db>, query: SearchQuery) -> Result<Vec<SearchHit>> {
+    db.with(|conn| query_index(conn, &query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        for migration in crate::migrations::MIGRATIONS {
+            conn.execute_batch(migration.sql).unwrap();
+        }
+        conn
+    }
+
+    fn put(conn: &Connection, table: &str, data: Value) {
+        conn.execute(
+            "INSERT INTO mirror_records (table_name, id, data) VALUES (?1, ?2, ?3)",
+            rusqlite::params![table, data["id"].as_str().unwrap(), data.to_string()],
+        )
+        .unwrap();
+    }
+
+    fn search(
+        conn: &Connection,
+        text: &str,
+        entities: &[&str],
+        since: Option<&str>,
+    ) -> Vec<SearchHit> {
+        let query = SearchQuery {
+            text: text.into(),
+            entities: entities.iter().map(|e| e.to_string()).collect(),
+            since: since.map(str::to_owned),
+            limit: None,
+        };
+        query_index(conn, &query).unwrap()
+    }
+
+    fn ids(hits: &[SearchHit]) -> Vec<&str> {
+        let mut ids: Vec<&str> = hits.iter().map(|h| h.id.as_str()).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn fts_query_quotes_terms_and_prefixes_the_last() {
+        assert_eq!(fts_query("  "), None);
+        assert_eq!(fts_query("bridge"), Some("\"bridge\"*".into()));
+        assert_eq!(
+            fts_query("flood NEAR river"),
+            Some("\"flood\" \"NEAR\" \"river\"*".into())
+        );
+        assert_eq!(
+            fts_query("say \"hi\" OR -x"),
+            Some("\"say\" \"\"\"hi\"\"\" \"OR\" \"-x\"*".into())
+        );
+
+        // operators in user input are plain words, and the last word is a prefix
+        let conn = db();
+        put(
+            &conn,
+            "incidents",
+            json!({ "id": "i1", "title": "Bridge collapse", "description": "AND OR NOT" }),
+        );
+        assert_eq!(ids(&search(&conn, "brid", &[], None)), ["i1"]);
+        assert_eq!(ids(&search(&conn, "collapse OR", &[], None)), ["i1"]);
+        assert_eq!(ids(&search(&conn, "NOT collapse", &[], None)), ["i1"]);
+        assert_eq!(ids(&search(&conn, "(\"bridge", &[], None)), ["i1"]);
+        assert!(search(&conn, "bridge flood", &[], None).is_empty());
+    }
+
+    #[test]
+    fn filters_by_entity_and_date() {
+        let conn = db();
+        put(
+            &conn,
+            "incidents",
+            json!({ "id": "i1", "title": "Flood at the mill", "created_at": "2024-01-01T00:00:00Z" }),
+        );
+        put(
+            &conn,
+            "tasks",
+            json!({ "id": "t1", "title": "Sandbag the flood wall", "incident_id": "i1", "created_at": "2024-03-01T00:00:00Z" }),
+        );
+        put(
+            &conn,
+            "documents",
+            json!({ "id": "d1", "name": "Flood map", "incident_id": "i1", "created_at": "2024-02-01T00:00:00Z" }),
+        );
+
+        assert_eq!(ids(&search(&conn, "flood", &[], None)), ["d1", "i1", "t1"]);
+        assert_eq!(
+            ids(&search(&conn, "flood", &["tasks", "documents"], None)),
+            ["d1", "t1"]
+        );
+        assert_eq!(
+            ids(&search(&conn, "flood", &[], Some("2024-02-01T00:00:00Z"))),
+            ["d1", "t1"]
+        );
+        assert_eq!(
+            ids(&search(
+                &conn,
+                "flood",
+                &["incidents"],
+                Some("2024-02-01T00:00:00Z")
+            )),
+            Vec::<&str>::new()
+        );
+
+        let hits = search(&conn, "flood", &["tasks"], None);
+        assert_eq!(hits[0].parent_id.as_deref(), Some("i1"));
+        assert_eq!(hits[0].title, "Sandbag the <mark>flood</mark> wall");
+
+        let query = SearchQuery {
+            text: "flood".into(),
+            entities: vec!["profiles".into()],
+            since: None,
+            limit: None,
+        };
+        assert!(matches!(query_index(&conn, &query), Err(Error::Invalid(_))));
+    }
+
+    #[test]
+    fn untitled_hits_fall_back_to_incident_title_or_snippet() {
+        let conn = db();
+        put(
+            &conn,
+            "incidents",
+            json!({ "id": "i1", "title": "Mill fire" }),
+        );
+        put(
+            &conn,
+            "incident_updates",
+            json!({ "id": "u1", "incident_id": "i1", "content": "smoke drifting east" }),
+        );
+        put(
+            &conn,
+            "incident_updates",
+            json!({ "id": "u2", "incident_id": "gone", "content": "smoke cleared" }),
+        );
+        put(
+            &conn,
+            "messages",
+            json!({ "id": "m1", "channel_id": "i1", "content": "smoke seen from the road" }),
+        );
+
+        let hits = search(&conn, "smoke", &[], None);
+        let title = |id: &str| hits.iter().find(|h| h.id == id).unwrap().title.clone();
+        assert_eq!(title("u1"), "Mill fire");
+        assert_eq!(title("u2"), "<mark>smoke</mark> cleared");
+        assert_eq!(title("m1"), "<mark>smoke</mark> seen from the road");
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::crypto::{self, Key};
+use crate::error::Result;
+use crate::security::{self, Vault};
+use crate::supabase::SupabaseState;
+
+/// Sealed name → value map holding the Supabase session, under the
+/// keychain's session key.
+const SECRETS_FILE: &str = "session.sealed";
+/// The same, under a key derived from the database key, on devices without
+/// a keychain.
+const VAULT_SECRETS_FILE: &str = "session.vault.sealed";
+/// Where tauri-plugin-store used to keep the session in cleartext.
+const LEGACY_STORE_FILE: &str = "auth.json";
+const SESSION_KEY_PURPOSE: &str = "disasterconnect session v1";
+/// Keychain entry holding the key the session is sealed with.
+const KEYRING_SESSION_USER: &str = "session-key";
+
+pub const WIPED_EVENT: &str = "secrets://wiped";
+
+/// Serialises read-modify-write of the secrets file.
+#[derive(Default)]
+pub struct Secrets {
+    lock: Mutex<()>,
+}
+
+fn secrets_path(app: &AppHandle) -> Result<PathBuf> {
+    Ok(app.path().app_config_dir()?.join(SECRETS_FILE))
+}
+
+fn vault_secrets_path(app: &AppHandle) -> Result<PathBuf> {
+    Ok(app.path().app_config_dir()?.join(VAULT_SECRETS_FILE))
+}
+
+fn load(path: &Path, key: &Key) -> Result<BTreeMap<String, String>> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let plain = crypto::open(key, &std::fs::read(path)?)?;
+    Ok(serde_json::from_slice(&plain)?)
+}
+
+fn save(path: &Path, key: &Key, secrets: &BTreeMap<String, String>) -> Result<()> {
+    if secrets.is_empty() {
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        return Ok(());
+    }
+    let sealed = crypto::seal(key, &serde_json::to_vec(secrets)?)?;
+    let tmp = path.with_extension("sealed.tmp");
+    std::fs::write(&tmp, sealed)?;
+    std::fs::rename(tmp, path)?;
+    Ok(())
+}
+
+/// Where the session is sealed and the key to it. Session material gets
+/// its own key from the OS keychain, so the session can be restored at
+/// startup before the database is unlocked. A device without a keychain
+/// uses a key derived from the database key instead, readable once a
+/// passphrase has unlocked it. Which one is settled by the first seal and
+/// kept: once the keychain holds the key, failing to read it is an error
+/// rather than a reason to switch keys.
+fn session_store(app: &AppHandle, vault: &Vault) -> Result<(PathBuf, Key)> {
+    let path = vault_secrets_path(app)?;
+    if path.exists() {
+        return Ok((path, vault.key()?.derive(SESSION_KEY_PURPOSE)));
+    }
+    let keychain_path = secrets_path(app)?;
+    match security::keychain_key(KEYRING_SESSION_USER) {
+        Ok(key) => Ok((keychain_path, key)),
+        Err(e) if keychain_path.exists() => Err(e),
+        Err(e) => {
+            eprintln!("[secrets] {e}; sealing the session with the database key");
+            Ok((path, vault.key()?.derive(SESSION_KEY_PURPOSE)))
+        }
+    }
+}
+
+/// Re-seal a secrets file under a new key.
+fn reseal_file(path: &Path, old: &Key, new: &Key) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    match load(path, old) {
+        Ok(secrets) => save(path, new, &secrets),
+        // Unreadable under the old key; signing in again is all it costs
+        Err(_) => Ok(std::fs::remove_file(path)?),
+    }
+}
+
+/// Re-seal the secrets file after the database key changed, if it was
+/// sealed with a key derived from it.
+pub fn reseal(app: &AppHandle, old: &Key, new: &Key) -> Result<()> {
+    let secrets = app.state::<Secrets>();
+    let _guard = secrets.lock.lock().unwrap_or_else(|e| e.into_inner());
+    reseal_file(
+        &vault_secrets_path(app)?,
+        &old.derive(SESSION_KEY_PURPOSE),
+        &new.derive(SESSION_KEY_PURPOSE),
+    )
+}
+
+fn remove_files(paths: &[PathBuf]) -> Result<()> {
+    for path in paths {
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Remove every trace of the session from this device, the keychain's
+/// session key included. The key only matters if the keychain was in use,
+/// so failing to delete it is an error only then.
+fn wipe(app: &AppHandle) -> Result<()> {
+    let secrets = app.state::<Secrets>();
+    let _guard = secrets.lock.lock().unwrap_or_else(|e| e.into_inner());
+    let keychain_path = secrets_path(app)?;
+    let sealed_with_keychain = keychain_path.exists();
+    remove_files(&[
+        keychain_path,
+        vault_secrets_path(app)?,
+        app.path().app_data_dir()?.join(LEGACY_STORE_FILE),
+    ])?;
+    app.state::<SupabaseState>().configure(None);
+    match security::forget_keychain_key(KEYRING_SESSION_USER) {
+        Err(e) if sealed_with_keychain => Err(e),
+        _ => Ok(()),
+    }
+}
+
+// ─── Commands ───────────────────────────────────────────────────
+
+#[tauri::command]
+pub fn secret_get(
+    app: AppHandle,
+    secrets: State<'_, Secrets>,
+    vault: State<'_, Vault>,
+    name: String,
+) -> Result<Option<String>> {
+    let _guard = secrets.lock.lock().unwrap_or_else(|e| e.into_inner());
+    let (path, key) = session_store(&app, &vault)?;
+    Ok(load(&path, &key)?.remove(&name))
+}
+
+#[tauri::command]
+pub fn secret_set(
+    app: AppHandle,
+    secrets: State<'_, Secrets>,
+    vault: State<'_, Vault>,
+    name: String,
+    value: String,
+) -> Result<()> {
+    let _guard = secrets.lock.lock().unwrap_or_else(|e| e.into_inner());
+    let (path, key) = session_store(&app, &vault)?;
+    let mut all = load(&path, &key)?;
+    all.insert(name, value);
+    save(&path, &key, &all)
+}
+
+#[tauri::command]
+pub fn secret_delete(
+    app: AppHandle,
+    secrets: State<'_, Secrets>,
+    vault: State<'_, Vault>,
+    name: String,
+) -> Result<()> {
+    let _guard = secrets.lock.lock().unwrap_or_else(|e| e.into_inner());
+    let (path, key) = session_store(&app, &vault)?;
+    let mut all = load(&path, &key)?;
+    if all.remove(&name).is_some() {
+        save(&path, &key, &all)?;
+    }
+    Ok(())
+}
+
+/// "Sign out everywhere on this device": drop every stored session,
+/// including a leftover cleartext `auth.json`, and tell all windows.
+#[tauri::command]
+pub fn secrets_wipe(app: AppHandle) -> Result<()> {
+    wipe(&app)?;
+    let _ = app.emit(WIPED_EVENT, ());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("secrets-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn session() -> BTreeMap<String, String> {
+        BTreeMap::from([(
+            "sb-auth-token".to_owned(),
+            "{\"access_token\":\"a\"}".to_owned(),
+        )])
+    }
+
+    #[test]
+    fn sealed_secrets_open_only_with_their_key() {
+        let dir = temp_dir();
+        let path = dir.join(SECRETS_FILE);
+        let key = Key::generate();
+        assert!(load(&path, &key).unwrap().is_empty());
+
+        save(&path, &key, &session()).unwrap();
+        assert_eq!(load(&path, &key).unwrap(), session());
+        // Nothing readable on disk, and no other key opens it
+        let raw = std::fs::read(&path).unwrap();
+        assert!(!String::from_utf8_lossy(&raw).contains("access_token"));
+        assert!(load(&path, &Key::generate()).is_err());
+        assert!(!path.with_extension("sealed.tmp").exists());
+
+        // Saving nothing deletes the file
+        save(&path, &key, &BTreeMap::new()).unwrap();
+        assert!(!path.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reseal_moves_secrets_to_the_new_key() {
+        let dir = temp_dir();
+        let path = dir.join(VAULT_SECRETS_FILE);
+        let (old, new) = (Key::generate(), Key::generate());
+        reseal_file(&path, &old, &new).unwrap();
+        assert!(!path.exists());
+
+        save(&path, &old, &session()).unwrap();
+        reseal_file(&path, &old, &new).unwrap();
+        assert_eq!(load(&path, &new).unwrap(), session());
+        assert!(load(&path, &old).is_err());
+
+        // Not sealed with the old key: dropped rather than left unreadable
+        reseal_file(&path, &old, &new).unwrap();
+        assert!(!path.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn wipe_removes_every_session_file() {
+        let dir = temp_dir();
+        let key = Key::generate();
+        let paths = [
+            dir.join(SECRETS_FILE),
+            dir.join(VAULT_SECRETS_FILE),
+            dir.join(LEGACY_STORE_FILE),
+        ];
+        save(&paths[0], &key, &session()).unwrap();
+        std::fs::write(&paths[2], "{\"sb-auth-token\":\"plain\"}").unwrap();
+
+        remove_files(&paths).unwrap();
+        assert!(paths.iter().all(|p| !p.exists()));
+        remove_files(&paths).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,771 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::crypto::{self, KdfParams, Key};
+use crate::db::Db;
+use crate::error::{Error, Result};
+
+const KEYSTORE_FILE: &str = "keystore.json";
+const SECURE_CACHE_DIR: &str = "secure";
+const KEYRING_SERVICE: &str = "com.saqla.disasterconnect-app";
+const KEYRING_USER: &str = "local-database-key";
+/// Keychain entry for the key a device-mode rotation is moving to.
+const KEYRING_NEXT_USER: &str = "local-database-key-next";
+const CACHE_KEY_PURPOSE: &str = "disasterconnect cache files v1";
+
+pub const LOCK_EVENT: &str = "security://lock-changed";
+
+/// Where the database key comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyMode {
+    /// Random key kept in the OS keychain; unlocks on its own at startup.
+    Device,
+    /// Random key wrapped with a key derived from the user's passphrase.
+    Passphrase,
+}
+
+/// Unencrypted metadata needed to recover the database key. Holds nothing
+/// secret on its own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Keystore {
+    mode: KeyMode,
+    /// Base64 salt for the passphrase KDF.
+    #[serde(default)]
+    salt: Option<String>,
+    #[serde(default)]
+    kdf: Option<KdfParams>,
+    /// Base64 database key sealed with the passphrase-derived key.
+    #[serde(default)]
+    wrapped_key: Option<String>,
+    /// Key a rotation is moving to, recorded before the database is
+    /// rekeyed so that whichever key the file ends up under can open it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    next: Option<Box<Keystore>>,
+}
+
+impl Keystore {
+    fn device() -> Self {
+        Self {
+            mode: KeyMode::Device,
+            salt: None,
+            kdf: None,
+            wrapped_key: None,
+            next: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecurityStatus {
+    pub encrypted: bool,
+    pub unlocked: bool,
+    pub mode: Option<KeyMode>,
+}
+
+/// The unlocked database key, kept only in memory.
+#[derive(Default)]
+pub struct Vault {
+    key: RwLock<Option<Key>>,
+}
+
+impl Vault {
+    pub fn key(&self) -> Result<Key> {
+        self.key
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+            .ok_or(Error::Locked)
+    }
+
+    fn set(&self, key: Option<Key>) {
+        *self.key.write().unwrap_or_else(|e| e.into_inner()) = key;
+    }
+
+    /// Key for sealing cached files.
+    pub fn cache_key(&self) -> Result<Key> {
+        Ok(self.key()?.derive(CACHE_KEY_PURPOSE))
+    }
+}
+
+// ─── Keystore ───────────────────────────────────────────────────
+
+fn keystore_path(app: &AppHandle) -> Result<PathBuf> {
+    Ok(app.path().app_config_dir()?.join(KEYSTORE_FILE))
+}
+
+fn load_keystore(app: &AppHandle) -> Result<Option<Keystore>> {
+    read_keystore(&keystore_path(app)?)
+}
+
+fn read_keystore(path: &Path) -> Result<Option<Keystore>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_slice(&std::fs::read(path)?)?))
+}
+
+/// Replace the keystore atomically: a crash leaves either the old file or
+/// the new one, never a torn write.
+fn save_keystore(app: &AppHandle, keystore: &Keystore) -> Result<()> {
+    write_keystore(&keystore_path(app)?, keystore)
+}
+
+fn write_keystore(path: &Path, keystore: &Keystore) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    let mut file = std::fs::File::create(&tmp)?;
+    file.write_all(&serde_json::to_vec_pretty(keystore)?)?;
+    file.sync_all()?;
+    drop(file);
+    std::fs::rename(tmp, path)?;
+    Ok(())
+}
+
+fn keyring_entry() -> Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER).map_err(keyring_error)
+}
+
+fn next_keyring_entry() -> Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_NEXT_USER).map_err(keyring_error)
+}
+
+fn keyring_error(e: keyring::Error) -> Error {
+    Error::Invalid(format!("OS keychain unavailable: {e}"))
+}
+
+fn device_key() -> Result<Key> {
+    let secret = keyring_entry()?.get_secret().map_err(keyring_error)?;
+    Key::from_slice(&secret)
+}
+
+fn store_device_key(key: &Key) -> Result<()> {
+    keyring_entry()?
+        .set_secret(key.as_bytes())
+        .map_err(keyring_error)
+}
+
+fn forget_device_key() {
+    if let Ok(entry) = keyring_entry() {
+        let _ = entry.delete_credential();
+    }
+}
+
+/// A key of our own kept in the OS keychain under `user`, created on first
+/// use. Unlike the database key it is there before any unlock.
+pub fn keychain_key(user: &str) -> Result<Key> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, user).map_err(keyring_error)?;
+    match entry.get_secret() {
+        Ok(secret) => Key::from_slice(&secret),
+        Err(keyring::Error::NoEntry) => {
+            let key = Key::generate();
+            entry.set_secret(key.as_bytes()).map_err(keyring_error)?;
+            Ok(key)
+        }
+        Err(e) => Err(keyring_error(e)),
+    }
+}
+
+/// Delete the keychain key `keychain_key` made for `user`, if there is one.
+pub fn forget_keychain_key(user: &str) -> Result<()> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, user).map_err(keyring_error)?;
+    match entry.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(keyring_error(e)),
+    }
+}
+
+fn next_device_key() -> Result<Key> {
+    let secret = next_keyring_entry()?.get_secret().map_err(keyring_error)?;
+    Key::from_slice(&secret)
+}
+
+fn forget_next_device_key() {
+    if let Ok(entry) = next_keyring_entry() {
+        let _ = entry.delete_credential();
+    }
+}
+
+fn wrap(key: &Key, passphrase: &str) -> Result<Keystore> {
+    let salt = crypto::random_salt();
+    let kdf = KdfParams::default();
+    let kek = crypto::derive_from_passphrase(passphrase, &salt, kdf)?;
+    Ok(Keystore {
+        mode: KeyMode::Passphrase,
+        salt: Some(BASE64.encode(salt)),
+        kdf: Some(kdf),
+        wrapped_key: Some(BASE64.encode(crypto::seal(&kek, key.as_bytes())?)),
+        next: None,
+    })
+}
+
+fn unwrap(keystore: &Keystore, passphrase: &str) -> Result<Key> {
+    let (Some(salt), Some(wrapped)) = (&keystore.salt, &keystore.wrapped_key) else {
+        return Err(Error::Invalid("keystore is missing the wrapped key".into()));
+    };
+    let salt = BASE64
+        .decode(salt)
+        .map_err(|e| Error::Invalid(e.to_string()))?;
+    let wrapped = BASE64
+        .decode(wrapped)
+        .map_err(|e| Error::Invalid(e.to_string()))?;
+    let kek = crypto::derive_from_passphrase(passphrase, &salt, keystore.kdf.unwrap_or_default())?;
+    let key = crypto::open(&kek, &wrapped).map_err(|_| Error::WrongPassphrase)?;
+    Key::from_slice(&key)
+}
+
+// ─── Secure cache files ─────────────────────────────────────────
+
+fn cache_dir(app: &AppHandle) -> Result<PathBuf> {
+    let dir = app.path().app_cache_dir()?.join(SECURE_CACHE_DIR);
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn cache_path(app: &AppHandle, name: &str) -> Result<PathBuf> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        && !name.starts_with('.');
+    if !valid {
+        return Err(Error::Invalid(format!("bad cache file name {name:?}")));
+    }
+    Ok(cache_dir(app)?.join(name))
+}
+
+/// Re-seal every cached file under a new key.
+fn reseal_cache(dir: &Path, old: &Key, new: &Key) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+        match crypto::open(old, &std::fs::read(&path)?) {
+            Ok(plain) => std::fs::write(&path, crypto::seal(new, &plain)?)?,
+            // Unreadable under the old key; it is useless either way
+            Err(_) => std::fs::remove_file(&path)?,
+        }
+    }
+    Ok(())
+}
+
+// ─── Unlocking ──────────────────────────────────────────────────
+
+fn emit_status(app: &AppHandle) {
+    if let Ok(status) = status(app) {
+        let _ = app.emit(LOCK_EVENT, status);
+    }
+}
+
+fn status(app: &AppHandle) -> Result<SecurityStatus> {
+    let keystore = load_keystore(app)?;
+    Ok(SecurityStatus {
+        encrypted: keystore.is_some(),
+        unlocked: app.state::<Db>().is_unlocked(),
+        mode: keystore.map(|k| k.mode),
+    })
+}
+
+fn open_db(db: &Db, key: &Key) -> Result<()> {
+    // The keystore is written before the file is converted, so a crash in
+    // between leaves a plaintext database to finish encrypting now
+    if db.is_plaintext() {
+        db.encrypt(key)?;
+    }
+    db.unlock(key)
+}
+
+fn unlock_with(app: &AppHandle, key: Key) -> Result<()> {
+    open_db(&app.state::<Db>(), &key)?;
+    app.state::<Vault>().set(Some(key));
+    emit_status(app);
+    Ok(())
+}
+
+fn unlock_keystore(
+    app: &AppHandle,
+    keystore: Keystore,
+    current: impl Fn(&Keystore) -> Result<Key>,
+) -> Result<()> {
+    recover(
+        keystore,
+        current,
+        |key| unlock_with(app, key),
+        |keystore| save_keystore(app, keystore),
+    )
+}
+
+/// Unlock with the keystore's key or, when a rotation was cut short after
+/// the rekey, with the key it was moving to. Either way the keystore ends
+/// up describing a single key again.
+fn recover(
+    keystore: Keystore,
+    current: impl Fn(&Keystore) -> Result<Key>,
+    unlock: impl Fn(Key) -> Result<()>,
+    save: impl Fn(&Keystore) -> Result<()>,
+) -> Result<()> {
+    let failed = match current(&keystore).and_then(&unlock) {
+        Ok(()) => {
+            if keystore.next.is_some() {
+                abandon_rotation(keystore, &save)?;
+            }
+            return Ok(());
+        }
+        Err(e) => e,
+    };
+    let Some(next) = keystore.next else {
+        return Err(failed);
+    };
+    let key = match next.mode {
+        KeyMode::Device => next_device_key(),
+        KeyMode::Passphrase => current(&next),
+    };
+    let Ok(key) = key else {
+        return Err(failed);
+    };
+    unlock(key.clone())?;
+    finish_rotation(*next, &key, &save)
+}
+
+/// Move the database to `new`, described by `next`. The new key is
+/// recorded next to the old one before the rekey and made the only one
+/// after it, so a crash at any point leaves a keystore that opens the
+/// database (see `recover`).
+fn rotate(
+    db: &Db,
+    keystore: Keystore,
+    next: Keystore,
+    new: &Key,
+    save: impl Fn(&Keystore) -> Result<()>,
+) -> Result<()> {
+    let mut staged = keystore;
+    staged.next = Some(Box::new(next.clone()));
+    save(&staged)?;
+    if let Err(e) = db.rekey(new) {
+        abandon_rotation(staged, &save)?;
+        return Err(e);
+    }
+    finish_rotation(next, new, &save)
+}
+
+/// The rekey never happened: keep the current key and drop the staged one.
+fn abandon_rotation(mut keystore: Keystore, save: impl Fn(&Keystore) -> Result<()>) -> Result<()> {
+    let staged = keystore.next.take();
+    save(&keystore)?;
+    if staged.is_some_and(|next| next.mode == KeyMode::Device) {
+        forget_next_device_key();
+    }
+    Ok(())
+}
+
+/// The database is under the new key: make it the only one.
+fn finish_rotation(
+    next: Keystore,
+    key: &Key,
+    save: impl Fn(&Keystore) -> Result<()>,
+) -> Result<()> {
+    if next.mode == KeyMode::Device {
+        store_device_key(key)?;
+    }
+    save(&next)?;
+    if next.mode == KeyMode::Device {
+        forget_next_device_key();
+    }
+    Ok(())
+}
+
+/// Open the database at startup. Device-keyed databases open straight
+/// away, and a first run moves to a device key so data is never left in
+/// plaintext. Passphrase-protected databases stay locked until
+/// `security_unlock`; so does a first run without an OS keychain, until a
+/// passphrase is set with `security_set_passphrase`.
+pub fn unlock_at_startup(app: &AppHandle) -> Result<()> {
+    match load_keystore(app)? {
+        Some(keystore) if keystore.mode == KeyMode::Device => {
+            // Without the keychain entry the database can't be opened at all;
+            // stay locked rather than crash so the UI can explain
+            if let Err(e) = unlock_keystore(app, keystore, |_| device_key()) {
+                eprintln!("[security] Could not unlock with the device key: {e}");
+            }
+            Ok(())
+        }
+        Some(_) => Ok(()),
+        None => {
+            let key = Key::generate();
+            if let Err(e) = store_device_key(&key) {
+                // No keychain (e.g. Linux without a secret service): stay
+                // locked and have the user choose a passphrase instead
+                eprintln!("[security] {e}; waiting for a passphrase to encrypt with");
+                emit_status(app);
+                return Ok(());
+            }
+            save_keystore(app, &Keystore::device())?;
+            unlock_with(app, key)
+        }
+    }
+}
+
+// ─── Commands ───────────────────────────────────────────────────
+
+#[tauri::command]
+pub fn security_status(app: AppHandle) -> Result<SecurityStatus> {
+    status(&app)
+}
+
+#[tauri::command]
+pub async fn security_unlock(app: AppHandle, passphrase: String) -> Result<()> {
+    let keystore = load_keystore(&app)?.ok_or(Error::NotEncrypted)?;
+    if keystore.mode != KeyMode::Passphrase {
+        return Err(Error::Invalid(
+            "database is not passphrase protected".into(),
+        ));
+    }
+    // Argon2 is deliberately slow; keep it off the main thread
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        unlock_keystore(&handle, keystore, |keystore| unwrap(keystore, &passphrase))
+    })
+    .await?
+}
+
+#[tauri::command]
+pub fn security_lock(app: AppHandle, db: State<'_, Db>, vault: State<'_, Vault>) -> Result<()> {
+    let keystore = load_keystore(&app)?.ok_or(Error::NotEncrypted)?;
+    if keystore.mode != KeyMode::Passphrase {
+        return Err(Error::Invalid(
+            "a device-keyed database unlocks itself; set a passphrase first".into(),
+        ));
+    }
+    db.lock();
+    vault.set(None);
+    emit_status(&app);
+    Ok(())
+}
+
+/// Protect the existing key with a passphrase, or change the passphrase.
+/// On a device with no keychain, where the database has never been
+/// encrypted, this is what encrypts it.
+#[tauri::command]
+pub async fn security_set_passphrase(app: AppHandle, passphrase: String) -> Result<()> {
+    if passphrase.chars().count() < 8 {
+        return Err(Error::Invalid(
+            "passphrase must be at least 8 characters".into(),
+        ));
+    }
+    let (key, bootstrap) = match app.state::<Vault>().key() {
+        Ok(key) => (key, false),
+        Err(Error::Locked) if load_keystore(&app)?.is_none() => (Key::generate(), true),
+        Err(e) => return Err(e),
+    };
+    let wrapping = key.clone();
+    let keystore =
+        tauri::async_runtime::spawn_blocking(move || wrap(&wrapping, &passphrase)).await??;
+    save_keystore(&app, &keystore)?;
+    if bootstrap {
+        unlock_with(&app, key)?;
+    } else {
+        forget_device_key();
+        emit_status(&app);
+    }
+    Ok(())
+}
+
+/// Drop the passphrase and go back to a key held by the OS keychain.
+#[tauri::command]
+pub fn security_use_device_key(app: AppHandle, vault: State<'_, Vault>) -> Result<()> {
+    store_device_key(&vault.key()?)?;
+    save_keystore(&app, &Keystore::device())?;
+    emit_status(&app);
+    Ok(())
+}
+
+/// Re-encrypt the database and cached files under a fresh key. In
+/// passphrase mode the passphrase is needed to wrap the new key.
+#[tauri::command]
+pub async fn security_rotate_key(app: AppHandle, passphrase: Option<String>) -> Result<()> {
+    let keystore = load_keystore(&app)?.ok_or(Error::NotEncrypted)?;
+    let vault = app.state::<Vault>();
+    let old = vault.key()?;
+    let new = Key::generate();
+
+    // Wrap the new key before switching over, so a failure here leaves
+    // everything on the old key
+    let next = match keystore.mode {
+        KeyMode::Passphrase => {
+            let passphrase = passphrase
+                .ok_or_else(|| Error::Invalid("passphrase required to rotate the key".into()))?;
+            // Make sure it is the current passphrase, not a typo
+            let check = keystore.clone();
+            let pass = passphrase.clone();
+            let current =
+                tauri::async_runtime::spawn_blocking(move || unwrap(&check, &pass)).await??;
+            if current.as_bytes() != old.as_bytes() {
+                return Err(Error::WrongPassphrase);
+            }
+            let key = new.clone();
+            tauri::async_runtime::spawn_blocking(move || wrap(&key, &passphrase)).await??
+        }
+        KeyMode::Device => {
+            next_keyring_entry()?
+                .set_secret(new.as_bytes())
+                .map_err(keyring_error)?;
+            Keystore::device()
+        }
+    };
+
+    rotate(&app.state::<Db>(), keystore, next, &new, |keystore| {
+        save_keystore(&app, keystore)
+    })?;
+    reseal_cache(
+        &cache_dir(&app)?,
+        &old.derive(CACHE_KEY_PURPOSE),
+        &new.derive(CACHE_KEY_PURPOSE),
+    )?;
+    crate::secrets::reseal(&app, &old, &new)?;
+    vault.set(Some(new));
+    emit_status(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn secure_cache_write(
+    app: AppHandle,
+    vault: State<'_, Vault>,
+    name: String,
+    data: Vec<u8>,
+) -> Result<()> {
+    let sealed = crypto::seal(&vault.cache_key()?, &data)?;
+    std::fs::write(cache_path(&app, &name)?, sealed)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn secure_cache_read(
+    app: AppHandle,
+    vault: State<'_, Vault>,
+    name: String,
+) -> Result<Option<Vec<u8>>> {
+    let path = cache_path(&app, &name)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(crypto::open(
+        &vault.cache_key()?,
+        &std::fs::read(path)?,
+    )?))
+}
+
+#[tauri::command]
+pub fn secure_cache_delete(app: AppHandle, name: String) -> Result<()> {
+    let path = cache_path(&app, &name)?;
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSPHRASE: &str = "correct horse battery";
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("security-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// A passphrase-protected database with one incident in it, and its
+    /// keystore file.
+    fn protected(dir: &Path, key: &Key) -> (Db, PathBuf) {
+        let db = Db::at(dir.join(crate::db::DB_FILE));
+        db.unlock(key).unwrap();
+        db.with(|conn| {
+            conn.execute(
+                "INSERT INTO mirror_records (table_name, id, data) VALUES ('incidents', 'i1', '{}')",
+                [],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+        db.lock();
+        let path = dir.join(KEYSTORE_FILE);
+        write_keystore(&path, &wrap(key, PASSPHRASE).unwrap()).unwrap();
+        (db, path)
+    }
+
+    fn opens_with(db: &Db, key: &Key) -> bool {
+        db.lock();
+        let opened = db.unlock(key).is_ok()
+            && db
+                .with(|conn| {
+                    Ok(
+                        conn.query_row("SELECT count(*) FROM mirror_records", [], |row| {
+                            row.get::<_, i64>(0)
+                        })?,
+                    )
+                })
+                .is_ok_and(|n| n == 1);
+        db.lock();
+        opened
+    }
+
+    fn stored_key(path: &Path) -> Key {
+        let keystore = read_keystore(path).unwrap().unwrap();
+        assert!(keystore.next.is_none());
+        unwrap(&keystore, PASSPHRASE).unwrap()
+    }
+
+    #[test]
+    fn wrapped_keys_need_the_passphrase() {
+        let key = Key::generate();
+        let keystore = wrap(&key, PASSPHRASE).unwrap();
+        assert_eq!(keystore.mode, KeyMode::Passphrase);
+        assert_eq!(
+            unwrap(&keystore, PASSPHRASE).unwrap().as_bytes(),
+            key.as_bytes()
+        );
+        assert!(matches!(
+            unwrap(&keystore, "Correct horse battery"),
+            Err(Error::WrongPassphrase)
+        ));
+        assert!(unwrap(&Keystore::device(), PASSPHRASE).is_err());
+    }
+
+    #[test]
+    fn reseal_moves_cached_files_to_the_new_key() {
+        let dir = temp_dir();
+        let (old, new) = (Key::generate(), Key::generate());
+        std::fs::write(
+            dir.join("tiles.bin"),
+            crypto::seal(&old, b"tile data").unwrap(),
+        )
+        .unwrap();
+        std::fs::write(dir.join("stale.bin"), b"not sealed").unwrap();
+        std::fs::create_dir(dir.join("nested")).unwrap();
+
+        reseal_cache(&dir, &old, &new).unwrap();
+        let sealed = std::fs::read(dir.join("tiles.bin")).unwrap();
+        assert_eq!(crypto::open(&new, &sealed).unwrap(), b"tile data");
+        assert!(crypto::open(&old, &sealed).is_err());
+        assert!(!dir.join("stale.bin").exists());
+        assert!(dir.join("nested").is_dir());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn plaintext_databases_are_encrypted_in_place() {
+        let dir = temp_dir();
+        let path = dir.join(crate::db::DB_FILE);
+        {
+            let conn = rusqlite::Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE notes (body TEXT); INSERT INTO notes VALUES ('phone 0400 000 000');",
+            )
+            .unwrap();
+        }
+        let backup = dir.join(format!("{}.v0.bak", crate::db::DB_FILE));
+        std::fs::copy(&path, &backup).unwrap();
+        let db = Db::at(path.clone());
+        assert!(db.is_plaintext());
+
+        let key = Key::generate();
+        open_db(&db, &key).unwrap();
+        assert!(!db.is_plaintext());
+        assert!(!backup.exists());
+        let body: String = db
+            .with(|conn| Ok(conn.query_row("SELECT body FROM notes", [], |row| row.get(0))?))
+            .unwrap();
+        assert_eq!(body, "phone 0400 000 000");
+        let raw = std::fs::read(&path).unwrap();
+        assert!(!String::from_utf8_lossy(&raw).contains("0400"));
+
+        db.lock();
+        assert!(matches!(db.unlock(&Key::generate()), Err(Error::Crypto)));
+        // Already encrypted: opening again leaves the file alone
+        open_db(&db, &key).unwrap();
+        db.lock();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rotation_moves_the_database_to_the_new_key() {
+        let dir = temp_dir();
+        let old = Key::generate();
+        let (db, path) = protected(&dir, &old);
+        let save = |keystore: &Keystore| write_keystore(&path, keystore);
+
+        let new = Key::generate();
+        db.unlock(&old).unwrap();
+        let keystore = read_keystore(&path).unwrap().unwrap();
+        rotate(&db, keystore, wrap(&new, PASSPHRASE).unwrap(), &new, save).unwrap();
+
+        assert_eq!(stored_key(&path).as_bytes(), new.as_bytes());
+        assert!(opens_with(&db, &new));
+        assert!(!opens_with(&db, &old));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rotation_cut_short_recovers_whichever_key_the_file_is_under() {
+        let unlock_current = |keystore: &Keystore| unwrap(keystore, PASSPHRASE);
+        for rekeyed in [false, true] {
+            let dir = temp_dir();
+            let old = Key::generate();
+            let (db, path) = protected(&dir, &old);
+            let save = |keystore: &Keystore| write_keystore(&path, keystore);
+
+            // Crash after staging the new key, before or after the rekey
+            let new = Key::generate();
+            let mut staged = read_keystore(&path).unwrap().unwrap();
+            staged.next = Some(Box::new(wrap(&new, PASSPHRASE).unwrap()));
+            save(&staged).unwrap();
+            if rekeyed {
+                db.unlock(&old).unwrap();
+                db.rekey(&new).unwrap();
+                db.lock();
+            }
+
+            let staged = read_keystore(&path).unwrap().unwrap();
+            recover(staged, unlock_current, |key| open_db(&db, &key), save).unwrap();
+            assert!(db.is_unlocked());
+            let expected = if rekeyed { &new } else { &old };
+            assert_eq!(stored_key(&path).as_bytes(), expected.as_bytes());
+            assert!(opens_with(&db, expected));
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn recovery_with_the_wrong_passphrase_changes_nothing() {
+        let dir = temp_dir();
+        let old = Key::generate();
+        let (db, path) = protected(&dir, &old);
+        let mut staged = read_keystore(&path).unwrap().unwrap();
+        staged.next = Some(Box::new(wrap(&Key::generate(), PASSPHRASE).unwrap()));
+        write_keystore(&path, &staged).unwrap();
+
+        let result = recover(
+            staged,
+            |keystore| unwrap(keystore, "wrong passphrase"),
+            |key| open_db(&db, &key),
+            |keystore| write_keystore(&path, keystore),
+        );
+        assert!(matches!(result, Err(Error::WrongPassphrase)));
+        assert!(!db.is_unlocked());
+        assert!(read_keystore(&path).unwrap().unwrap().next.is_some());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,157 @@
+use std::io::Cursor;
+
+use base64::engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD as BASE64_URL};
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use qrcode::render::svg;
+use qrcode::{EcLevel, QrCode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+
+use crate::crypto::Key;
+use crate::deep_link::{self, Route};
+use crate::error::{Error, Result};
+use crate::supabase::SupabaseState;
+
+const SIGNING_KEY_PURPOSE: &str = "disasterconnect share links v1";
+/// Pixel size of one QR module in the PNG; big enough to scan off paper.
+const PNG_MODULE_PX: u32 = 8;
+const SVG_MIN_SIZE: u32 = 256;
+
+/// Records that can be shared as a link.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShareEntity {
+    Incident,
+    Task,
+    EvacuationRoute,
+    Alert,
+}
+
+impl ShareEntity {
+    fn route(self, id: String) -> Route {
+        match self {
+            ShareEntity::Incident => Route::Incident { id },
+            ShareEntity::Task => Route::Task { id },
+            ShareEntity::EvacuationRoute => Route::Evacuation { id },
+            ShareEntity::Alert => Route::Alert { id },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedLink {
+    pub url: String,
+    /// RFC 3339; `None` for links that never expire.
+    pub expires_at: Option<String>,
+    pub qr_svg: String,
+    /// Base64 PNG, ready for a `data:image/png;base64,` URL.
+    pub qr_png: String,
+}
+
+/// Links are signed with a key derived from the Supabase project, so every
+/// install of the same deployment can check them without sharing a secret
+/// out of band. The anon key is not truly secret; the signature proves a link
+/// came from this deployment's app and was not edited, not who made it.
+pub fn signing_key(supabase: &SupabaseState) -> Option<Key> {
+    let config = supabase.config()?;
+    let digest = Sha256::new()
+        .chain_update(config.url.trim_end_matches('/'))
+        .chain_update(b"\n")
+        .chain_update(&config.anon_key)
+        .finalize();
+    Key::from_slice(&digest)
+        .ok()
+        .map(|key| key.derive(SIGNING_KEY_PURPOSE))
+}
+
+/// What gets signed: the link target and its expiry.
+fn message(target: &str, exp: Option<i64>) -> String {
+    match exp {
+        Some(exp) => format!("{target}|{exp}"),
+        None => format!("{target}|"),
+    }
+}
+
+fn mac(key: &Key, target: &str, exp: Option<i64>) -> Hmac<Sha256> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key.as_bytes())
+        .expect("HMAC takes keys of any length");
+    mac.update(message(target, exp).as_bytes());
+    mac
+}
+
+pub fn sign(key: &Key, target: &str, exp: Option<i64>) -> String {
+    BASE64_URL.encode(mac(key, target, exp).finalize().into_bytes())
+}
+
+/// Constant-time check of a link signature.
+pub fn verify(key: &Key, target: &str, exp: Option<i64>, sig: &str) -> bool {
+    let Ok(sig) = BASE64_URL.decode(sig) else {
+        return false;
+    };
+    mac(key, target, exp).verify_slice(&sig).is_ok()
+}
+
+fn render_qr(url: &str) -> Result<(String, Vec<u8>)> {
+    // Medium error correction survives creases and smudges on printouts
+    let code = QrCode::with_error_correction_level(url.as_bytes(), EcLevel::M)
+        .map_err(|e| Error::Invalid(format!("link too long for a QR code: {e}")))?;
+
+    let svg = code
+        .render::<svg::Color>()
+        .min_dimensions(SVG_MIN_SIZE, SVG_MIN_SIZE)
+        .build();
+
+    let image = code
+        .render::<image::Luma<u8>>()
+        .module_dimensions(PNG_MODULE_PX, PNG_MODULE_PX)
+        .build();
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| Error::Invalid(format!("could not encode QR code: {e}")))?;
+
+    Ok((svg, png))
+}
+
+/// Build a signed `disasterconnect://` link to a record, plus a QR code of it
+/// for reports and printed briefings.
+#[tauri::command]
+pub fn share_link_create(
+    app: AppHandle,
+    entity: ShareEntity,
+    id: String,
+    expires_in_days: Option<u32>,
+) -> Result<SharedLink> {
+    let id = uuid::Uuid::parse_str(&id)
+        .map_err(|_| Error::Invalid(format!("{id:?} is not a record id")))?
+        .to_string();
+    let route = entity.route(id);
+    let target = deep_link::link_target(&route)
+        .ok_or_else(|| Error::Invalid("this screen can't be shared".into()))?;
+    let key = signing_key(&app.state::<SupabaseState>()).ok_or(Error::NotConfigured)?;
+
+    let expires_at = expires_in_days
+        .map(|days| {
+            chrono::Utc::now()
+                .checked_add_signed(chrono::Duration::days(i64::from(days)))
+                .ok_or_else(|| Error::Invalid(format!("{days} days is too far ahead")))
+        })
+        .transpose()?;
+    let exp = expires_at.map(|t| t.timestamp());
+    let mut url = format!("{}://{target}?", deep_link::SCHEME);
+    if let Some(exp) = exp {
+        url.push_str(&format!("exp={exp}&"));
+    }
+    url.push_str(&format!("sig={}", sign(&key, &target, exp)));
+
+    let (qr_svg, png) = render_qr(&url)?;
+    Ok(SharedLink {
+        url,
+        expires_at: expires_at.map(|t| t.to_rfc3339()),
+        qr_svg,
+        qr_png: BASE64.encode(png),
+    })
+}
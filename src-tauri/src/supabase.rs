@@ -0,0 +1,194 @@
+use std::sync::RwLock;
+
+use reqwest::{Client, Method, RequestBuilder, Response};
+use serde::Deserialize;
+use serde_json::Value;
+use tauri::State;
+
+use crate::error::{Error, Result};
+use crate::mirror;
+
+/// Connection details pushed from the webview whenever the auth session
+/// changes, so background work in Rust talks to PostgREST as the signed-in user.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupabaseConfig {
+    pub url: String,
+    pub anon_key: String,
+    pub access_token: Option<String>,
+}
+
+#[derive(Default)]
+pub struct SupabaseState {
+    http: Client,
+    config: RwLock<Option<SupabaseConfig>>,
+}
+
+impl SupabaseState {
+    pub fn configure(&self, config: Option<SupabaseConfig>) {
+        *self.config.write().unwrap_or_else(|e| e.into_inner()) = config;
+    }
+
+    pub fn config(&self) -> Option<SupabaseConfig> {
+        self.config
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// A client for the current session, or `None` until the frontend has
+    /// called `supabase_configure` with a signed-in session.
+    pub fn client(&self) -> Option<Supabase> {
+        let config = self.config()?;
+        config.access_token.as_ref()?;
+        Some(Supabase {
+            http: self.http.clone(),
+            config,
+        })
+    }
+}
+
+/// Minimal PostgREST client covering what the sync code needs.
+pub struct Supabase {
+    http: Client,
+    config: SupabaseConfig,
+}
+
+impl Supabase {
+    /// Only tables the app mirrors are reachable, so a table name from a
+    /// queued mutation can't point the request at another endpoint.
+    fn request(&self, method: Method, table: &str) -> Result<RequestBuilder> {
+        let table = mirror::table(table)?;
+        let url = format!(
+            "{}/rest/v1/{}",
+            self.config.url.trim_end_matches('/'),
+            table.name
+        );
+        let token = self
+            .config
+            .access_token
+            .as_deref()
+            .unwrap_or(&self.config.anon_key);
+        Ok(self
+            .http
+            .request(method, url)
+            .header("apikey", &self.config.anon_key)
+            .bearer_auth(token))
+    }
+
+    pub async fn select(&self, table: &str, query: &[(&str, String)]) -> Result<Vec<Value>> {
+        let res = send(self.request(Method::GET, table)?.query(query)).await?;
+        Ok(res.json().await?)
+    }
+
+    pub async fn insert(&self, table: &str, payload: &Value) -> Result<()> {
+        send(self.request(Method::POST, table)?.json(payload)).await?;
+        Ok(())
+    }
+
+    pub async fn update(&self, table: &str, id: &str, payload: &Value) -> Result<()> {
+        send(
+            self.request(Method::PATCH, table)?
+                .query(&[("id", format!("eq.{id}"))])
+                .json(payload),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// PATCH the row only if its `updated_at` is still `base`, returning how
+    /// many rows changed. Zero means it was edited (or deleted) since.
+    pub async fn update_if_unchanged(
+        &self,
+        table: &str,
+        id: &str,
+        base: &str,
+        payload: &Value,
+    ) -> Result<usize> {
+        let res = send(
+            self.request(Method::PATCH, table)?
+                .query(&[
+                    ("id", format!("eq.{id}")),
+                    ("updated_at", format!("eq.{base}")),
+                ])
+                .header("Prefer", "return=representation")
+                .json(payload),
+        )
+        .await?;
+        let rows: Vec<Value> = res.json().await?;
+        Ok(rows.len())
+    }
+
+    pub async fn delete(&self, table: &str, id: &str) -> Result<()> {
+        send(
+            self.request(Method::DELETE, table)?
+                .query(&[("id", format!("eq.{id}"))]),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+async fn send(req: RequestBuilder) -> Result<Response> {
+    let res = req.send().await?;
+    let status = res.status();
+    if status.is_success() {
+        return Ok(res);
+    }
+
+    // PostgREST errors look like { code, message, details, hint }
+    let body: Value = res.json().await.unwrap_or(Value::Null);
+    Err(Error::Supabase {
+        status: status.as_u16(),
+        code: body.get("code").and_then(Value::as_str).map(str::to_owned),
+        message: body
+            .get("message")
+            .and_then(Value::as_str)
+            .or(status.canonical_reason())
+            .unwrap_or("request failed")
+            .to_owned(),
+    })
+}
+
+#[tauri::command]
+pub fn supabase_configure(state: State<'_, SupabaseState>, config: Option<SupabaseConfig>) {
+    state.configure(config);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_only_reach_mirrored_tables() {
+        let client = Supabase {
+            http: Client::new(),
+            config: SupabaseConfig {
+                url: "https://project.supabase.co/".into(),
+                anon_key: "anon".into(),
+                access_token: Some("token".into()),
+            },
+        };
+        let request = client
+            .request(Method::GET, "incidents")
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(
+            request.url().as_str(),
+            "https://project.supabase.co/rest/v1/incidents"
+        );
+        for table in [
+            "profiles",
+            "rpc/delete_everything",
+            "../auth/v1/admin/users",
+            "incidents?select=*",
+            "",
+        ] {
+            assert!(matches!(
+                client.request(Method::DELETE, table),
+                Err(Error::Invalid(_))
+            ));
+        }
+    }
+}
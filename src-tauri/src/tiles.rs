@@ -0,0 +1,811 @@
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime};
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::error::{Error, Result};
+
+/// Scheme the webview loads tiles from: `tiles://localhost/{z}/{x}/{y}`
+/// (`http://tiles.localhost/...` on Windows).
+pub const SCHEME: &str = "tiles";
+const CACHE_DIR: &str = "tiles";
+const CONFIG_FILE: &str = "tiles.json";
+const OSM_UPSTREAM: &str = "https://tile.openstreetmap.org/{z}/{x}/{y}.png";
+const OSM_HOST: &str = "tile.openstreetmap.org";
+/// The OSM tile policy requires an identifying User-Agent.
+const USER_AGENT: &str = concat!("DisasterConnect/", env!("CARGO_PKG_VERSION"));
+const MAX_ZOOM: u8 = 19;
+/// Disk budget for cached tiles; the least recently used go first.
+const MAX_CACHE_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+/// Evict down to this share of the budget so we don't evict on every write.
+const EVICT_TO: f64 = 0.9;
+/// Check the budget after this many tiles fetched while browsing.
+const EVICT_EVERY: u32 = 500;
+/// Biggest area a single seed may cover. Zoom 16 over a city is ~20k tiles.
+const MAX_SEED_TILES: u64 = 100_000;
+const FETCH_TIMEOUT: Duration = Duration::from_secs(20);
+/// Pause between seed requests, to stay polite to the tile server.
+const SEED_DELAY: Duration = Duration::from_millis(50);
+
+pub const SEED_PROGRESS_EVENT: &str = "tiles://seed-progress";
+
+static SEEDING: AtomicBool = AtomicBool::new(false);
+static SEED_CANCELLED: AtomicBool = AtomicBool::new(false);
+
+struct SeedGuard;
+
+impl SeedGuard {
+    fn acquire() -> Option<Self> {
+        SEEDING
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| {
+                SEED_CANCELLED.store(false, Ordering::Release);
+                SeedGuard
+            })
+    }
+}
+
+impl Drop for SeedGuard {
+    fn drop(&mut self) {
+        SEEDING.store(false, Ordering::Release);
+    }
+}
+
+/// Where tiles come from when they are not cached.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TileServer {
+    /// URL template with `{z}`, `{x}` and `{y}`; OpenStreetMap when unset.
+    pub url: Option<String>,
+    /// Credit line shown on the map for a custom server.
+    pub attribution: Option<String>,
+}
+
+impl TileServer {
+    fn normalized(mut self) -> Self {
+        let blank = |value: &mut Option<String>| {
+            if value.as_deref().is_some_and(|v| v.trim().is_empty()) {
+                *value = None;
+            }
+        };
+        blank(&mut self.url);
+        blank(&mut self.attribution);
+        self
+    }
+
+    fn validate(&self) -> Result<()> {
+        let Some(template) = &self.url else {
+            return Ok(());
+        };
+        if !["{z}", "{x}", "{y}"].iter().all(|p| template.contains(p)) {
+            return Err(Error::Invalid(
+                "tile server URL needs {z}, {x} and {y} placeholders".into(),
+            ));
+        }
+        let url = reqwest::Url::parse(&tile_url(template, Tile { z: 0, x: 0, y: 0 }))
+            .map_err(|e| Error::Invalid(format!("tile server URL is not valid: {e}")))?;
+        if !matches!(url.scheme(), "https" | "http") {
+            return Err(Error::Invalid("tile server URL must be http(s)".into()));
+        }
+        Ok(())
+    }
+
+    fn template(&self) -> &str {
+        self.url.as_deref().unwrap_or(OSM_UPSTREAM)
+    }
+
+    /// The OpenStreetMap servers forbid bulk downloads, so areas can only
+    /// be saved offline from a server of our own choosing.
+    fn allows_seeding(&self) -> bool {
+        reqwest::Url::parse(&tile_url(self.template(), Tile { z: 0, x: 0, y: 0 }))
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_owned))
+            .is_some_and(|host| host != OSM_HOST && !host.ends_with(&format!(".{OSM_HOST}")))
+    }
+}
+
+fn tile_url(template: &str, tile: Tile) -> String {
+    template
+        .replace("{z}", &tile.z.to_string())
+        .replace("{x}", &tile.x.to_string())
+        .replace("{y}", &tile.y.to_string())
+}
+
+fn config_path(app: &AppHandle) -> Result<PathBuf> {
+    Ok(app.path().app_config_dir()?.join(CONFIG_FILE))
+}
+
+fn load_config(app: &AppHandle) -> TileServer {
+    config_path(app)
+        .ok()
+        .and_then(|path| std::fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice::<TileServer>(&bytes).ok())
+        .map(TileServer::normalized)
+        .filter(|server| server.validate().is_ok())
+        .unwrap_or_default()
+}
+
+fn save_config(app: &AppHandle, server: &TileServer) -> Result<()> {
+    let path = config_path(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(server)?)?;
+    std::fs::rename(tmp, path)?;
+    Ok(())
+}
+
+pub struct TileCache {
+    http: Client,
+    dir: PathBuf,
+    server: RwLock<TileServer>,
+    writes: AtomicU32,
+}
+
+impl TileCache {
+    pub fn new(app: &AppHandle) -> Result<Self> {
+        let dir = app.path().app_cache_dir()?.join(CACHE_DIR);
+        std::fs::create_dir_all(&dir)?;
+        let http = Client::builder()
+            .user_agent(USER_AGENT)
+            .timeout(FETCH_TIMEOUT)
+            .build()?;
+        Ok(Self {
+            http,
+            dir,
+            server: RwLock::new(load_config(app)),
+            writes: AtomicU32::new(0),
+        })
+    }
+
+    fn server(&self) -> TileServer {
+        self.server
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn clear(&self) -> Result<()> {
+        if self.dir.exists() {
+            std::fs::remove_dir_all(&self.dir)?;
+        }
+        std::fs::create_dir_all(&self.dir)?;
+        Ok(())
+    }
+
+    fn path(&self, tile: Tile, format: Format) -> PathBuf {
+        self.dir
+            .join(tile.z.to_string())
+            .join(tile.x.to_string())
+            .join(format!("{}.{}", tile.y, format.extension()))
+    }
+
+    /// The cached file for `tile`, in whichever format the server sent.
+    fn cached(&self, tile: Tile) -> Option<(PathBuf, Format)> {
+        Format::ALL
+            .into_iter()
+            .map(|format| (self.path(tile, format), format))
+            .find(|(path, _)| path.exists())
+    }
+
+    fn read(&self, tile: Tile) -> Option<(Vec<u8>, Format)> {
+        let (path, format) = self.cached(tile)?;
+        let bytes = std::fs::read(&path).ok()?;
+        // Modification time doubles as "last used" for eviction
+        if let Ok(file) = std::fs::File::options().append(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        Some((bytes, format))
+    }
+
+    fn write(&self, tile: Tile, format: Format, bytes: &[u8]) -> Result<()> {
+        let path = self.path(tile, format);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    async fn fetch(&self, tile: Tile) -> Result<(Vec<u8>, Format)> {
+        let url = tile_url(self.server().template(), tile);
+        let res = self.http.get(url).send().await?.error_for_status()?;
+        let content_type = res
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_owned);
+        let bytes = res.bytes().await?.to_vec();
+        // Don't cache an error page served with a 200
+        let format = Format::detect(content_type.as_deref(), &bytes).ok_or_else(|| {
+            Error::Invalid("the tile server sent something that isn't an image".into())
+        })?;
+        self.write(tile, format, &bytes)?;
+        if self.writes.fetch_add(1, Ordering::Relaxed) % EVICT_EVERY == EVICT_EVERY - 1 {
+            self.evict()?;
+        }
+        Ok((bytes, format))
+    }
+
+    /// Cached copy, or fetched and cached.
+    async fn get(&self, tile: Tile) -> Result<(Vec<u8>, Format)> {
+        match self.read(tile) {
+            Some(cached) => Ok(cached),
+            None => self.fetch(tile).await,
+        }
+    }
+
+    fn status(&self) -> CacheStatus {
+        let files = cached_files(&self.dir);
+        CacheStatus {
+            tiles: files.len() as u64,
+            bytes: files.iter().map(|f| f.1).sum(),
+            limit_bytes: MAX_CACHE_BYTES,
+        }
+    }
+
+    fn evict(&self) -> Result<u64> {
+        evict(&self.dir, MAX_CACHE_BYTES)
+    }
+}
+
+/// Drop least recently used tiles until the cache is back under `budget`
+/// bytes.
+fn evict(dir: &Path, budget: u64) -> Result<u64> {
+    let mut files = cached_files(dir);
+    let mut total: u64 = files.iter().map(|f| f.1).sum();
+    if total <= budget {
+        return Ok(0);
+    }
+    let target = (budget as f64 * EVICT_TO) as u64;
+    files.sort_by_key(|f| f.2);
+    let mut removed = 0;
+    for (path, size, _) in files {
+        if total <= target {
+            break;
+        }
+        std::fs::remove_file(path)?;
+        total = total.saturating_sub(size);
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// `(path, size, last used)` for every cached tile.
+fn cached_files(dir: &Path) -> Vec<(PathBuf, u64, SystemTime)> {
+    let mut out = Vec::new();
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(meta) = entry.metadata() else { continue };
+            if meta.is_dir() {
+                stack.push(entry.path());
+            } else if entry
+                .path()
+                .extension()
+                .and_then(|e| e.to_str())
+                .and_then(Format::from_extension)
+                .is_some()
+            {
+                let used = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                out.push((entry.path(), meta.len(), used));
+            }
+        }
+    }
+    out
+}
+
+/// Raster formats tile servers send. A tile is cached under the extension
+/// of its format and served back with the matching content type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Png,
+    Jpeg,
+    Webp,
+}
+
+impl Format {
+    const ALL: [Format; 3] = [Format::Png, Format::Jpeg, Format::Webp];
+
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Png => "png",
+            Format::Jpeg => "jpg",
+            Format::Webp => "webp",
+        }
+    }
+
+    fn from_extension(ext: &str) -> Option<Self> {
+        match ext {
+            "png" => Some(Format::Png),
+            "jpg" | "jpeg" => Some(Format::Jpeg),
+            "webp" => Some(Format::Webp),
+            _ => None,
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Format::Png => "image/png",
+            Format::Jpeg => "image/jpeg",
+            Format::Webp => "image/webp",
+        }
+    }
+
+    /// From the response's content type, or failing that (missing, or a
+    /// generic `application/octet-stream`) from the image's magic bytes.
+    fn detect(content_type: Option<&str>, bytes: &[u8]) -> Option<Self> {
+        let mime = content_type
+            .and_then(|t| t.split(';').next())
+            .map(|t| t.trim().to_ascii_lowercase());
+        match mime.as_deref() {
+            Some("image/png") => Some(Format::Png),
+            Some("image/jpeg" | "image/jpg") => Some(Format::Jpeg),
+            Some("image/webp") => Some(Format::Webp),
+            _ if bytes.starts_with(b"\x89PNG\r\n\x1a\n") => Some(Format::Png),
+            _ if bytes.starts_with(&[0xff, 0xd8, 0xff]) => Some(Format::Jpeg),
+            _ if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" => {
+                Some(Format::Webp)
+            }
+            _ => None,
+        }
+    }
+}
+
+// ─── Tile math ──────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tile {
+    pub z: u8,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl Tile {
+    fn new(z: u8, x: u32, y: u32) -> Option<Self> {
+        let n = 1u32.checked_shl(u32::from(z))?;
+        (z <= MAX_ZOOM && x < n && y < n).then_some(Self { z, x, y })
+    }
+
+    /// Parse the `/{z}/{x}/{y}` (optionally with an image extension) path of
+    /// a protocol request.
+    fn from_path(path: &str) -> Option<Self> {
+        let mut parts = path.trim_matches('/').split('/');
+        let z = parts.next()?.parse().ok()?;
+        let x = parts.next()?.parse().ok()?;
+        let last = parts.next()?;
+        let y = match last.split_once('.') {
+            Some((y, ext)) => Format::from_extension(ext).map(|_| y)?,
+            None => last,
+        };
+        let y = y.parse().ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Self::new(z, x, y)
+    }
+}
+
+/// Web Mercator tile containing a point.
+fn tile_xy(lat: f64, lng: f64, z: u8) -> (u32, u32) {
+    let n = f64::from(1u32 << z);
+    let lat = lat.clamp(-85.051_128, 85.051_128).to_radians();
+    let x = ((lng + 180.0) / 360.0 * n).floor();
+    let y = ((1.0 - lat.tan().asinh() / std::f64::consts::PI) / 2.0 * n).floor();
+    let max = n - 1.0;
+    (x.clamp(0.0, max) as u32, y.clamp(0.0, max) as u32)
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BoundingBox {
+    pub west: f64,
+    pub south: f64,
+    pub east: f64,
+    pub north: f64,
+}
+
+impl BoundingBox {
+    pub fn validate(&self) -> Result<()> {
+        let lat_ok = |v: f64| (-90.0..=90.0).contains(&v);
+        let lng_ok = |v: f64| (-180.0..=180.0).contains(&v);
+        if !(lat_ok(self.south) && lat_ok(self.north) && lng_ok(self.west) && lng_ok(self.east))
+            || self.south > self.north
+            || self.west > self.east
+        {
+            return Err(Error::Invalid("bounding box is not valid".into()));
+        }
+        Ok(())
+    }
+
+    /// Tile ranges `(z, xs, ys)` covering the box at each zoom.
+    fn ranges(
+        &self,
+        zooms: RangeInclusive<u8>,
+    ) -> Vec<(u8, RangeInclusive<u32>, RangeInclusive<u32>)> {
+        zooms
+            .map(|z| {
+                let (x0, y0) = tile_xy(self.north, self.west, z);
+                let (x1, y1) = tile_xy(self.south, self.east, z);
+                (z, x0..=x1, y0..=y1)
+            })
+            .collect()
+    }
+
+    fn tile_count(&self, zooms: RangeInclusive<u8>) -> u64 {
+        self.ranges(zooms)
+            .iter()
+            .map(|(_, xs, ys)| {
+                u64::from(xs.end() - xs.start() + 1) * u64::from(ys.end() - ys.start() + 1)
+            })
+            .sum()
+    }
+}
+
+fn zoom_range(min_zoom: u8, max_zoom: u8) -> Result<RangeInclusive<u8>> {
+    if min_zoom > max_zoom || max_zoom > MAX_ZOOM {
+        return Err(Error::Invalid(format!(
+            "zoom range must be within 0..={MAX_ZOOM}"
+        )));
+    }
+    Ok(min_zoom..=max_zoom)
+}
+
+// ─── Protocol ───────────────────────────────────────────────────
+
+fn respond(status: StatusCode, format: Option<Format>, body: Vec<u8>) -> Response<Vec<u8>> {
+    let mut response = Response::builder()
+        .status(status)
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*");
+    if let Some(format) = format {
+        response = response.header(header::CONTENT_TYPE, format.content_type());
+    }
+    response
+        .body(body)
+        .unwrap_or_else(|_| Response::new(Vec::new()))
+}
+
+/// Serve `tiles://localhost/{z}/{x}/{y}` from the cache, fetching on a miss.
+/// Offline misses come back as 404 so Leaflet just leaves the square empty.
+pub async fn serve(app: AppHandle, request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let Some(tile) = Tile::from_path(request.uri().path()) else {
+        return respond(StatusCode::BAD_REQUEST, None, Vec::new());
+    };
+    let cache = app.state::<TileCache>();
+    match cache.get(tile).await {
+        Ok((bytes, format)) => respond(StatusCode::OK, Some(format), bytes),
+        Err(_) => respond(StatusCode::NOT_FOUND, None, Vec::new()),
+    }
+}
+
+// ─── Seeding ────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeedProgress {
+    pub total: u64,
+    pub done: u64,
+    /// Already cached, not downloaded again.
+    pub skipped: u64,
+    pub failed: u64,
+    pub bytes: u64,
+    pub cancelled: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStatus {
+    pub tiles: u64,
+    pub bytes: u64,
+    pub limit_bytes: u64,
+}
+
+/// Download every tile in `area` between two zoom levels so the map works
+/// offline there. Emits `SEED_PROGRESS_EVENT` as it goes.
+#[tauri::command]
+pub async fn tiles_seed(
+    app: AppHandle,
+    area: BoundingBox,
+    min_zoom: u8,
+    max_zoom: u8,
+) -> Result<SeedProgress> {
+    area.validate()?;
+    let cache = app.state::<TileCache>();
+    if !cache.server().allows_seeding() {
+        return Err(Error::Invalid(
+            "the OpenStreetMap tile servers don't allow bulk downloads; \
+             set a tile server that does in Settings to save areas offline"
+                .into(),
+        ));
+    }
+    let zooms = zoom_range(min_zoom, max_zoom)?;
+    let total = area.tile_count(zooms.clone());
+    if total > MAX_SEED_TILES {
+        return Err(Error::Invalid(format!(
+            "{total} tiles is more than the {MAX_SEED_TILES} allowed in one seed; \
+             shrink the area or lower the maximum zoom"
+        )));
+    }
+    let Some(_guard) = SeedGuard::acquire() else {
+        return Err(Error::Invalid("a seed is already running".into()));
+    };
+
+    let mut progress = SeedProgress {
+        total,
+        ..Default::default()
+    };
+    'seed: for (z, xs, ys) in area.ranges(zooms) {
+        for x in xs {
+            for y in ys.clone() {
+                if SEED_CANCELLED.load(Ordering::Acquire) {
+                    progress.cancelled = true;
+                    break 'seed;
+                }
+                let Some(tile) = Tile::new(z, x, y) else {
+                    continue;
+                };
+                if cache.cached(tile).is_some() {
+                    progress.skipped += 1;
+                } else {
+                    match cache.fetch(tile).await {
+                        Ok((bytes, _)) => progress.bytes += bytes.len() as u64,
+                        Err(e) => {
+                            progress.failed += 1;
+                            // No point hammering a link that is down
+                            if matches!(e, Error::Http(ref err) if err.is_connect()) {
+                                break 'seed;
+                            }
+                        }
+                    }
+                    tokio::time::sleep(SEED_DELAY).await;
+                }
+                progress.done += 1;
+                if progress.done.is_multiple_of(25) {
+                    let _ = app.emit(SEED_PROGRESS_EVENT, &progress);
+                }
+            }
+        }
+    }
+
+    let evicted = cache.evict()?;
+    if evicted > 0 {
+        eprintln!("[tiles] Evicted {evicted} tiles to stay under the cache limit");
+    }
+    let _ = app.emit(SEED_PROGRESS_EVENT, &progress);
+    Ok(progress)
+}
+
+#[tauri::command]
+pub fn tiles_seed_cancel() {
+    SEED_CANCELLED.store(true, Ordering::Release);
+}
+
+/// Rough number of tiles a seed would download, to show before starting.
+#[tauri::command]
+pub fn tiles_estimate(area: BoundingBox, min_zoom: u8, max_zoom: u8) -> Result<u64> {
+    area.validate()?;
+    Ok(area.tile_count(zoom_range(min_zoom, max_zoom)?))
+}
+
+#[tauri::command]
+pub fn tiles_status(cache: State<'_, TileCache>) -> CacheStatus {
+    cache.status()
+}
+
+#[tauri::command]
+pub fn tiles_clear(cache: State<'_, TileCache>) -> Result<()> {
+    cache.clear()
+}
+
+#[tauri::command]
+pub fn tiles_server(cache: State<'_, TileCache>) -> TileServer {
+    cache.server()
+}
+
+/// Switch tile servers. Cached tiles from the old one are dropped so the
+/// two never mix on the map.
+#[tauri::command]
+pub fn tiles_set_server(
+    app: AppHandle,
+    cache: State<'_, TileCache>,
+    server: TileServer,
+) -> Result<TileServer> {
+    let server = server.normalized();
+    server.validate()?;
+    save_config(&app, &server)?;
+    let changed = {
+        let mut current = cache.server.write().unwrap_or_else(|e| e.into_inner());
+        let changed = current.template() != server.template();
+        *current = server.clone();
+        changed
+    };
+    if changed {
+        cache.clear()?;
+    }
+    Ok(server)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+    const JPEG: &[u8] = &[0xff, 0xd8, 0xff, 0xe0, 0, 0x10];
+
+    fn temp_cache() -> TileCache {
+        let dir = std::env::temp_dir().join(format!("tiles-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        TileCache {
+            http: Client::new(),
+            dir,
+            server: RwLock::default(),
+            writes: AtomicU32::new(0),
+        }
+    }
+
+    #[test]
+    fn protocol_paths_parse_to_tiles() {
+        assert_eq!(Tile::from_path("/3/2/5"), Some(Tile { z: 3, x: 2, y: 5 }));
+        assert_eq!(Tile::from_path("3/2/5.png"), Tile::new(3, 2, 5));
+        assert_eq!(Tile::from_path("/3/2/5.jpg"), Tile::new(3, 2, 5));
+        assert_eq!(Tile::from_path("/19/524287/0"), Tile::new(19, 524_287, 0));
+
+        for bad in [
+            "",
+            "/",
+            "/3/2",
+            "/3/2/5/1",
+            "/3/8/0",
+            "/3/0/8",
+            "/20/0/0",
+            "/255/0/0",
+            "/3/2/x",
+            "/3/-1/5",
+            "/3/2/5.svg",
+            "/3/2/5.png.png",
+            "/../2/5",
+        ] {
+            assert_eq!(Tile::from_path(bad), None, "{bad}");
+        }
+    }
+
+    #[test]
+    fn points_map_to_web_mercator_tiles() {
+        assert_eq!(tile_xy(0.0, 0.0, 0), (0, 0));
+        assert_eq!(tile_xy(10.0, -10.0, 1), (0, 0));
+        assert_eq!(tile_xy(-10.0, 10.0, 1), (1, 1));
+        // Sydney Opera House at zoom 16
+        assert_eq!(tile_xy(-33.8568, 151.2153, 16), (60295, 39325));
+        // Poles and the antimeridian clamp to the edge tiles
+        assert_eq!(tile_xy(90.0, -180.0, 4), (0, 0));
+        assert_eq!(tile_xy(-90.0, 180.0, 4), (15, 15));
+    }
+
+    #[test]
+    fn boxes_cover_whole_tile_ranges() {
+        let world = BoundingBox {
+            west: -180.0,
+            south: -85.0,
+            east: 180.0,
+            north: 85.0,
+        };
+        assert_eq!(world.tile_count(0..=2), 1 + 4 + 16);
+        let ranges = world.ranges(2..=2);
+        assert_eq!(ranges, [(2, 0..=3, 0..=3)]);
+
+        // A point-sized box is one tile at every zoom
+        let point = BoundingBox {
+            west: 151.2,
+            south: -33.9,
+            east: 151.2,
+            north: -33.9,
+        };
+        assert_eq!(point.tile_count(0..=19), 20);
+        let city = BoundingBox {
+            west: 151.1,
+            south: -33.95,
+            east: 151.3,
+            north: -33.8,
+        };
+        let ranges = city.ranges(10..=12);
+        assert_eq!(ranges.len(), 3);
+        for (z, xs, ys) in &ranges {
+            assert_eq!(*xs.start(), tile_xy(city.north, city.west, *z).0);
+            assert_eq!(*ys.end(), tile_xy(city.south, city.east, *z).1);
+        }
+        assert!(city.tile_count(16..=16) < MAX_SEED_TILES);
+
+        assert!(BoundingBox {
+            west: 10.0,
+            south: 0.0,
+            east: -10.0,
+            north: 1.0
+        }
+        .validate()
+        .is_err());
+        assert!(zoom_range(5, 4).is_err());
+        assert!(zoom_range(0, 20).is_err());
+    }
+
+    #[test]
+    fn tiles_keep_the_format_the_server_sent() {
+        assert_eq!(Format::detect(Some("image/png"), b""), Some(Format::Png));
+        assert_eq!(
+            Format::detect(Some("Image/JPEG; charset=binary"), b""),
+            Some(Format::Jpeg)
+        );
+        assert_eq!(Format::detect(Some("image/webp"), b""), Some(Format::Webp));
+        assert_eq!(
+            Format::detect(Some("application/octet-stream"), JPEG),
+            Some(Format::Jpeg)
+        );
+        assert_eq!(Format::detect(None, PNG), Some(Format::Png));
+        assert_eq!(
+            Format::detect(None, b"RIFF\0\0\0\0WEBPVP8 "),
+            Some(Format::Webp)
+        );
+        assert_eq!(Format::detect(Some("text/html"), b"<html>"), None);
+
+        let cache = temp_cache();
+        let tile = Tile::new(5, 3, 7).unwrap();
+        assert!(cache.read(tile).is_none());
+        cache.write(tile, Format::Jpeg, JPEG).unwrap();
+        assert_eq!(cache.read(tile), Some((JPEG.to_vec(), Format::Jpeg)));
+        assert!(cache.dir.join("5/3/7.jpg").exists());
+        assert!(!cache.dir.join("5/3/7.png").exists());
+
+        let response = respond(StatusCode::OK, Some(Format::Jpeg), JPEG.to_vec());
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "image/jpeg");
+        assert_eq!(cache.status().tiles, 1);
+        std::fs::remove_dir_all(&cache.dir).unwrap();
+    }
+
+    #[test]
+    fn eviction_drops_the_least_recently_used() {
+        let cache = temp_cache();
+        let start = SystemTime::now() - Duration::from_secs(3600);
+        for y in 0..10 {
+            let tile = Tile::new(4, 0, y).unwrap();
+            let format = if y % 2 == 0 {
+                Format::Png
+            } else {
+                Format::Jpeg
+            };
+            cache.write(tile, format, &[0; 100]).unwrap();
+            let path = cache.path(tile, format);
+            let file = std::fs::File::options().append(true).open(path).unwrap();
+            file.set_modified(start + Duration::from_secs(u64::from(y) * 60))
+                .unwrap();
+        }
+        // Not a tile: never counted or removed
+        std::fs::write(cache.dir.join("notes.txt"), [0; 500]).unwrap();
+
+        assert_eq!(evict(&cache.dir, 1000).unwrap(), 0);
+        // Down to 90% of 800 bytes: the three oldest go
+        assert_eq!(evict(&cache.dir, 800).unwrap(), 3);
+        let left: Vec<u32> = (0..10)
+            .filter(|&y| cache.cached(Tile::new(4, 0, y).unwrap()).is_some())
+            .collect();
+        assert_eq!(left, [3, 4, 5, 6, 7, 8, 9]);
+
+        // Reading a tile makes it recent again
+        cache.read(Tile::new(4, 0, 3).unwrap()).unwrap();
+        assert_eq!(evict(&cache.dir, 600).unwrap(), 2);
+        assert!(cache.cached(Tile::new(4, 0, 3).unwrap()).is_some());
+        assert!(cache.cached(Tile::new(4, 0, 4).unwrap()).is_none());
+        assert!(cache.dir.join("notes.txt").exists());
+        std::fs::remove_dir_all(&cache.dir).unwrap();
+    }
+}
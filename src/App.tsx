@@ -6,7 +6,9 @@ import { AppShell } from "@/components/layout/app-shell";
 import { QueryProvider } from "@/lib/query-provider";
 import { AuthLayout } from "@/components/layout/auth-layout";
 import { useRealtime } from "@/hooks/use-realtime";
+import { useGeofenceAlertListener } from "@/hooks/use-geofence";
 import { useDeepLink } from "@/hooks/use-deep-link";
+import { LockScreen } from "@/components/lock-screen";
 
 // Auth pages
 import LoginPage from "@/pages/login";
@@ -43,6 +45,7 @@ function DeepLinkHandler() {
 
 function RealtimeWrapper({ children }: { children: React.ReactNode }) {
   useRealtime();
+  useGeofenceAlertListener();
   return <>{children}</>;
 }
 
@@ -55,61 +58,63 @@ function App() {
 
   return (
     <QueryProvider>
-      <BrowserRouter>
-        <DeepLinkHandler />
-        <Routes>
-          {/* Public routes (with title bar) */}
-          <Route element={<AuthLayout />}>
-            <Route path="/login" element={<LoginPage />} />
-            <Route path="/signup" element={<SignupPage />} />
-            <Route path="/forgot-password" element={<ForgotPasswordPage />} />
-            <Route path="/reset-password" element={<ResetPasswordPage />} />
-          </Route>
+      <LockScreen>
+        <BrowserRouter>
+          <DeepLinkHandler />
+          <Routes>
+            {/* Public routes (with title bar) */}
+            <Route element={<AuthLayout />}>
+              <Route path="/login" element={<LoginPage />} />
+              <Route path="/signup" element={<SignupPage />} />
+              <Route path="/forgot-password" element={<ForgotPasswordPage />} />
+              <Route path="/reset-password" element={<ResetPasswordPage />} />
+            </Route>
 
-          {/* Protected routes (wrapped in AppShell) */}
-          <Route
-            element={
-              <ProtectedRoute>
-                <RealtimeWrapper>
-                  <AppShell />
-                </RealtimeWrapper>
-              </ProtectedRoute>
-            }
-          >
-            <Route path="/" element={<Navigate to="/dashboard" replace />} />
-            <Route path="/dashboard" element={<DashboardPage />} />
-            <Route path="/incidents" element={<IncidentsPage />} />
-            <Route path="/incidents/:id" element={<IncidentDetailPage />} />
-            <Route path="/resources" element={<ResourcesPage />} />
-            <Route path="/resources/:id" element={<ResourceDetailPage />} />
-            <Route path="/map" element={<MapPage />} />
-            <Route path="/tasks" element={<TasksPage />} />
-            <Route path="/messaging" element={<MessagingPage />} />
-            <Route path="/messaging/:channelId" element={<MessagingPage />} />
-            <Route path="/alerts" element={<AlertsPage />} />
-            <Route path="/teams" element={<TeamsPage />} />
-            <Route path="/donations" element={<DonationsPage />} />
-            <Route path="/reports" element={<ReportsPage />} />
-            <Route path="/analytics" element={<AnalyticsPage />} />
-            <Route path="/evacuation" element={<EvacuationPage />} />
-            <Route path="/documents" element={<DocumentsPage />} />
-            <Route path="/settings" element={<SettingsPage />} />
-            <Route path="/audit-log" element={
-              <ProtectedRoute allowedRoles={["administrator"]}>
-                <AuditLogPage />
-              </ProtectedRoute>
-            } />
-            <Route path="/admin" element={
-              <ProtectedRoute allowedRoles={["administrator"]}>
-                <AdminPage />
-              </ProtectedRoute>
-            } />
-          </Route>
+            {/* Protected routes (wrapped in AppShell) */}
+            <Route
+              element={
+                <ProtectedRoute>
+                  <RealtimeWrapper>
+                    <AppShell />
+                  </RealtimeWrapper>
+                </ProtectedRoute>
+              }
+            >
+              <Route path="/" element={<Navigate to="/dashboard" replace />} />
+              <Route path="/dashboard" element={<DashboardPage />} />
+              <Route path="/incidents" element={<IncidentsPage />} />
+              <Route path="/incidents/:id" element={<IncidentDetailPage />} />
+              <Route path="/resources" element={<ResourcesPage />} />
+              <Route path="/resources/:id" element={<ResourceDetailPage />} />
+              <Route path="/map" element={<MapPage />} />
+              <Route path="/tasks" element={<TasksPage />} />
+              <Route path="/messaging" element={<MessagingPage />} />
+              <Route path="/messaging/:channelId" element={<MessagingPage />} />
+              <Route path="/alerts" element={<AlertsPage />} />
+              <Route path="/teams" element={<TeamsPage />} />
+              <Route path="/donations" element={<DonationsPage />} />
+              <Route path="/reports" element={<ReportsPage />} />
+              <Route path="/analytics" element={<AnalyticsPage />} />
+              <Route path="/evacuation" element={<EvacuationPage />} />
+              <Route path="/documents" element={<DocumentsPage />} />
+              <Route path="/settings" element={<SettingsPage />} />
+              <Route path="/audit-log" element={
+                <ProtectedRoute allowedRoles={["administrator"]}>
+                  <AuditLogPage />
+                </ProtectedRoute>
+              } />
+              <Route path="/admin" element={
+                <ProtectedRoute allowedRoles={["administrator"]}>
+                  <AdminPage />
+                </ProtectedRoute>
+              } />
+            </Route>
 
-          {/* Catch-all */}
-          <Route path="*" element={<Navigate to="/" replace />} />
-        </Routes>
-      </BrowserRouter>
+            {/* Catch-all */}
+            <Route path="*" element={<Navigate to="/" replace />} />
+          </Routes>
+        </BrowserRouter>
+      </LockScreen>
     </QueryProvider>
   );
 }
@@ -0,0 +1,224 @@
+import { useState } from "react";
+import { Button } from "@/components/ui/button";
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+} from "@/components/ui/dialog";
+import {
+  DropdownMenu,
+  DropdownMenuContent,
+  DropdownMenuItem,
+  DropdownMenuSeparator,
+  DropdownMenuTrigger,
+} from "@/components/ui/dropdown-menu";
+import { ScrollArea } from "@/components/ui/scroll-area";
+import { Textarea } from "@/components/ui/textarea";
+import { SeverityBadge } from "@/components/incidents/severity-badge";
+import { AlertTypeBadge } from "@/components/alerts/alert-badges";
+import {
+  AlertTriangle,
+  ClipboardPaste,
+  FileDown,
+  FileUp,
+  Loader2,
+  Megaphone,
+  XCircle,
+} from "lucide-react";
+import { useExportCap, useParseCap, usePickCap, useSaveCapDrafts } from "@/hooks/use-cap";
+import { describeCapIssue, type CapImport } from "@/lib/cap";
+import type { AlertType, SeverityLevel } from "@/types/enums";
+
+interface CapMenuProps {
+  /** Alerts exported by "Export as CAP". */
+  exportIds: string[];
+}
+
+/**
+ * Exchange alerts with national warning systems as CAP 1.2. Imported
+ * messages are previewed as drafts, with schema and mapping errors, before
+ * any are saved.
+ */
+export function CapMenu({ exportIds }: CapMenuProps) {
+  const [imports, setImports] = useState<CapImport[] | null>(null);
+  const [excluded, setExcluded] = useState<Set<number>>(new Set());
+  const [pasting, setPasting] = useState(false);
+  const [xml, setXml] = useState("");
+  const pick = usePickCap();
+  const parse = useParseCap();
+  const save = useSaveCapDrafts();
+  const exportCap = useExportCap();
+  const busy = pick.isPending || parse.isPending || save.isPending || exportCap.isPending;
+
+  const review = (result: CapImport[]) => {
+    setExcluded(new Set());
+    setImports(result);
+  };
+
+  const handlePick = () =>
+    pick.mutate(undefined, {
+      onSuccess: (result) => {
+        if (result) review(result);
+      },
+    });
+
+  const handleParse = () =>
+    parse.mutate(xml, {
+      onSuccess: (result) => {
+        setPasting(false);
+        setXml("");
+        review([result]);
+      },
+    });
+
+  const accepted = (imports ?? []).flatMap((item, i) =>
+    item.draft && !excluded.has(i) ? [item.draft] : []
+  );
+
+  const toggle = (index: number) =>
+    setExcluded((prev) => {
+      const next = new Set(prev);
+      if (next.has(index)) next.delete(index);
+      else next.add(index);
+      return next;
+    });
+
+  const handleSave = () =>
+    save.mutate(accepted, { onSuccess: () => setImports(null) });
+
+  return (
+    <>
+      <DropdownMenu>
+        <DropdownMenuTrigger asChild>
+          <Button variant="outline" size="sm" className="gap-1.5" disabled={busy}>
+            {busy ? <Loader2 className="h-4 w-4 animate-spin" /> : <Megaphone className="h-4 w-4" />}
+            CAP
+          </Button>
+        </DropdownMenuTrigger>
+        <DropdownMenuContent align="end">
+          <DropdownMenuItem onSelect={handlePick}>
+            <FileUp className="h-4 w-4" />
+            Import CAP files…
+          </DropdownMenuItem>
+          <DropdownMenuItem onSelect={() => setPasting(true)}>
+            <ClipboardPaste className="h-4 w-4" />
+            Paste CAP message…
+          </DropdownMenuItem>
+          <DropdownMenuSeparator />
+          <DropdownMenuItem
+            disabled={exportIds.length === 0}
+            onSelect={() => exportCap.mutate(exportIds)}
+          >
+            <FileDown className="h-4 w-4" />
+            Export active alerts ({exportIds.length})
+          </DropdownMenuItem>
+        </DropdownMenuContent>
+      </DropdownMenu>
+
+      {/* Paste */}
+      <Dialog open={pasting} onOpenChange={setPasting}>
+        <DialogContent className="sm:max-w-lg">
+          <DialogHeader>
+            <DialogTitle>Paste CAP Message</DialogTitle>
+            <DialogDescription>
+              Paste the XML of a CAP 1.2 alert from a partner agency.
+            </DialogDescription>
+          </DialogHeader>
+          <Textarea
+            data-selectable
+            value={xml}
+            onChange={(e) => setXml(e.target.value)}
+            placeholder={'<alert xmlns="urn:oasis:names:tc:emergency:cap:1.2">…'}
+            className="min-h-48 font-mono text-xs"
+          />
+          <DialogFooter>
+            <Button variant="outline" onClick={() => setPasting(false)}>
+              Cancel
+            </Button>
+            <Button onClick={handleParse} disabled={!xml.trim() || parse.isPending}>
+              {parse.isPending && <Loader2 className="h-4 w-4 animate-spin" />}
+              Read
+            </Button>
+          </DialogFooter>
+        </DialogContent>
+      </Dialog>
+
+      {/* Review */}
+      <Dialog open={!!imports} onOpenChange={(open) => !open && setImports(null)}>
+        <DialogContent className="sm:max-w-2xl">
+          <DialogHeader>
+            <DialogTitle>Import CAP Alerts</DialogTitle>
+            <DialogDescription>
+              {accepted.length} of {imports?.length ?? 0} selected. Check each draft before
+              importing it.
+            </DialogDescription>
+          </DialogHeader>
+          <ScrollArea className="max-h-[60vh] rounded-md border">
+            <ul className="divide-y">
+              {(imports ?? []).map((item, i) => {
+                const draft = item.draft;
+                const record = draft?.record as
+                  | { title: string; type: AlertType; severity: SeverityLevel; affected_area: string | null }
+                  | undefined;
+                return (
+                  <li key={i} className="p-3 space-y-1.5 text-sm">
+                    <div className="flex items-start gap-2">
+                      {draft ? (
+                        <input
+                          type="checkbox"
+                          className="mt-1"
+                          checked={!excluded.has(i)}
+                          onChange={() => toggle(i)}
+                        />
+                      ) : (
+                        <XCircle className="h-4 w-4 shrink-0 mt-0.5 text-destructive" />
+                      )}
+                      <div className="min-w-0 flex-1 space-y-1">
+                        <p className="font-medium truncate">
+                          {record?.title ?? item.fileName ?? "Pasted message"}
+                        </p>
+                        {draft && record && (
+                          <div className="flex flex-wrap items-center gap-1.5 text-xs text-muted-foreground">
+                            <AlertTypeBadge type={record.type} />
+                            <SeverityBadge severity={record.severity} />
+                            <span>{draft.event}</span>
+                            {record.affected_area && <span>· {record.affected_area}</span>}
+                            <span>· {draft.sender}</span>
+                            {draft.msgType !== "Alert" && <span>· {draft.msgType}</span>}
+                          </div>
+                        )}
+                        {draft?.warnings.map((warning) => (
+                          <p key={warning} className="flex gap-1.5 text-xs text-amber-600 dark:text-amber-400">
+                            <AlertTriangle className="h-3.5 w-3.5 shrink-0 mt-0.5" />
+                            {warning}
+                          </p>
+                        ))}
+                        {item.errors.map((error, j) => (
+                          <p key={j} className="text-xs text-destructive">
+                            {describeCapIssue(error)}
+                          </p>
+                        ))}
+                      </div>
+                    </div>
+                  </li>
+                );
+              })}
+            </ul>
+          </ScrollArea>
+          <DialogFooter>
+            <Button variant="outline" onClick={() => setImports(null)}>
+              Cancel
+            </Button>
+            <Button onClick={handleSave} disabled={accepted.length === 0 || save.isPending}>
+              {save.isPending && <Loader2 className="h-4 w-4 animate-spin" />}
+              Import {accepted.length}
+            </Button>
+          </DialogFooter>
+        </DialogContent>
+      </Dialog>
+    </>
+  );
+}
@@ -0,0 +1,210 @@
+import { useState } from "react";
+import { Badge } from "@/components/ui/badge";
+import { Button } from "@/components/ui/button";
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogHeader,
+  DialogTitle,
+} from "@/components/ui/dialog";
+import { ScrollArea } from "@/components/ui/scroll-area";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "@/components/ui/select";
+import { SeverityBadge } from "@/components/incidents/severity-badge";
+import { AlertTypeBadge } from "@/components/alerts/alert-badges";
+import { AlertTriangle, Check, Inbox, Loader2, RefreshCw, X } from "lucide-react";
+import { formatDistanceToNow } from "date-fns";
+import {
+  useAcceptCapReview,
+  useCapReview,
+  useCapWatchStatus,
+  useDismissCapReview,
+  usePollCapSources,
+} from "@/hooks/use-cap";
+import type { CapReviewItem, CapSuggestion } from "@/lib/cap";
+import type { AlertType, SeverityLevel } from "@/types/enums";
+
+interface CapReviewQueueProps {
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+}
+
+/** Button showing how many partner CAP messages await review. */
+export function CapReviewButton({ onClick }: { onClick: () => void }) {
+  const { data: items } = useCapReview();
+  const { data: status } = useCapWatchStatus();
+  const pending = items?.length ?? 0;
+  if (!status?.watching && pending === 0) return null;
+
+  return (
+    <Button variant="outline" size="sm" className="gap-1.5" onClick={onClick}>
+      <Inbox className="h-4 w-4" />
+      Review
+      {pending > 0 && <Badge className="h-5 px-1.5">{pending}</Badge>}
+    </Button>
+  );
+}
+
+/**
+ * Messages picked up from partner CAP feeds and the drop folder, suggested
+ * as alerts or incidents. Nothing is created until someone accepts one.
+ */
+export function CapReviewQueue({ open, onOpenChange }: CapReviewQueueProps) {
+  const { data: items, isLoading } = useCapReview();
+  const { data: status } = useCapWatchStatus();
+  const poll = usePollCapSources();
+  const failing = (status?.sources ?? []).filter((s) => s.error);
+
+  return (
+    <Dialog open={open} onOpenChange={onOpenChange}>
+      <DialogContent className="sm:max-w-2xl">
+        <DialogHeader>
+          <DialogTitle>Incoming CAP Messages</DialogTitle>
+          <DialogDescription>
+            Warnings from partner feeds and the drop folder. Accept one to create (or update) the
+            alert or incident it suggests.
+          </DialogDescription>
+        </DialogHeader>
+
+        <div className="flex items-center justify-between gap-2 text-xs text-muted-foreground">
+          <span>
+            {status?.watching
+              ? `Watching ${status.sources.length} ${status.sources.length === 1 ? "source" : "sources"}`
+              : "No CAP feeds or drop folder configured"}
+          </span>
+          {status?.watching && (
+            <Button
+              variant="ghost"
+              size="sm"
+              className="gap-1.5"
+              onClick={() => poll.mutate()}
+              disabled={poll.isPending}
+            >
+              <RefreshCw className={`h-3.5 w-3.5 ${poll.isPending ? "animate-spin" : ""}`} />
+              Check now
+            </Button>
+          )}
+        </div>
+        {failing.map((source) => (
+          <p key={source.location} className="flex gap-1.5 text-xs text-destructive">
+            <AlertTriangle className="h-3.5 w-3.5 shrink-0 mt-0.5" />
+            <span className="break-all">
+              {source.location}: {source.error}
+            </span>
+          </p>
+        ))}
+
+        <ScrollArea className="max-h-[60vh] rounded-md border">
+          {isLoading ? (
+            <div className="flex justify-center p-6">
+              <Loader2 className="h-5 w-5 animate-spin text-muted-foreground" />
+            </div>
+          ) : !items?.length ? (
+            <p className="p-6 text-center text-sm text-muted-foreground">
+              Nothing waiting for review.
+            </p>
+          ) : (
+            <ul className="divide-y">
+              {items.map((item) => (
+                <ReviewRow key={item.id} item={item} />
+              ))}
+            </ul>
+          )}
+        </ScrollArea>
+      </DialogContent>
+    </Dialog>
+  );
+}
+
+function ReviewRow({ item }: { item: CapReviewItem }) {
+  const [kind, setKind] = useState<CapSuggestion>(item.suggestion);
+  const accept = useAcceptCapReview();
+  const dismiss = useDismissCapReview();
+  const busy = accept.isPending || dismiss.isPending;
+  const { draft } = item;
+  const record = draft.record as {
+    title: string;
+    type: AlertType;
+    severity: SeverityLevel;
+    affected_area: string | null;
+  };
+  const targetName = item.target?.table === "incidents" ? "incident" : "alert";
+
+  return (
+    <li className="p-3 space-y-1.5 text-sm">
+      <div className="flex items-start justify-between gap-2">
+        <div className="min-w-0 flex-1 space-y-1">
+          <p className="font-medium truncate">{record.title}</p>
+          <div className="flex flex-wrap items-center gap-1.5 text-xs text-muted-foreground">
+            <AlertTypeBadge type={record.type} />
+            <SeverityBadge severity={record.severity} />
+            <span>{draft.event}</span>
+            {record.affected_area && <span>· {record.affected_area}</span>}
+            <span>· {draft.sender}</span>
+            {draft.msgType !== "Alert" && <span>· {draft.msgType}</span>}
+          </div>
+          <p className="text-xs text-muted-foreground truncate" title={item.source}>
+            Received {formatDistanceToNow(new Date(item.receivedAt), { addSuffix: true })} from{" "}
+            {item.source}
+          </p>
+          {item.target && (
+            <p className="text-xs">
+              {draft.msgType === "Cancel"
+                ? `Cancels the ${targetName} an earlier message became`
+                : `Updates the ${targetName} an earlier message became`}
+            </p>
+          )}
+          {draft.warnings.map((warning) => (
+            <p key={warning} className="flex gap-1.5 text-xs text-amber-600 dark:text-amber-400">
+              <AlertTriangle className="h-3.5 w-3.5 shrink-0 mt-0.5" />
+              {warning}
+            </p>
+          ))}
+        </div>
+
+        <div className="flex shrink-0 items-center gap-1.5">
+          {!item.target && (
+            <Select value={kind} onValueChange={(v) => setKind(v as CapSuggestion)}>
+              <SelectTrigger className="h-8 w-[110px]">
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                <SelectItem value="alert">Alert</SelectItem>
+                <SelectItem value="incident">Incident</SelectItem>
+              </SelectContent>
+            </Select>
+          )}
+          <Button
+            size="sm"
+            className="gap-1"
+            disabled={busy}
+            onClick={() => accept.mutate({ id: item.id, kind: item.target ? undefined : kind })}
+          >
+            {accept.isPending ? (
+              <Loader2 className="h-3.5 w-3.5 animate-spin" />
+            ) : (
+              <Check className="h-3.5 w-3.5" />
+            )}
+            Accept
+          </Button>
+          <Button
+            variant="ghost"
+            size="icon"
+            className="h-8 w-8"
+            title="Dismiss"
+            disabled={busy}
+            onClick={() => dismiss.mutate(item.id)}
+          >
+            <X className="h-4 w-4" />
+          </Button>
+        </div>
+      </div>
+    </li>
+  );
+}
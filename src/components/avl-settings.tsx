@@ -0,0 +1,278 @@
+import { useEffect, useState } from "react";
+import { formatDistanceToNow } from "date-fns";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { Switch } from "@/components/ui/switch";
+import {
+    Select,
+    SelectContent,
+    SelectItem,
+    SelectTrigger,
+    SelectValue,
+} from "@/components/ui/select";
+import { Loader2, Radio, Trash2 } from "lucide-react";
+import { toast } from "sonner";
+import { useAssignAvlUnit, useAvlStatus, useAvlUnits, useRemoveAvlUnit } from "@/hooks/use-avl";
+import { useResources } from "@/hooks/use-resources";
+import { useTeams } from "@/hooks/use-teams";
+import {
+    configureAvl,
+    getAvlConfig,
+    LISTENER_LABELS,
+    parseUnitKey,
+    unitKey,
+    type AvlConfig,
+} from "@/lib/avl";
+
+const STATE_COLORS = {
+    listening: "text-emerald-500",
+    connecting: "text-muted-foreground",
+    error: "text-destructive",
+} as const;
+
+/** Tracker listeners, and which tracker belongs to which resource or team. */
+export function AvlSettings() {
+    const { data: status } = useAvlStatus();
+    const { data: units } = useAvlUnits();
+    const { data: resources } = useResources();
+    const { data: teams } = useTeams();
+    const assign = useAssignAvlUnit();
+    const remove = useRemoveAvlUnit();
+
+    const [config, setConfig] = useState<AvlConfig | null>(null);
+    const [saving, setSaving] = useState(false);
+    const [deviceId, setDeviceId] = useState("");
+    const [unit, setUnit] = useState("");
+
+    useEffect(() => {
+        getAvlConfig()
+            .then(setConfig)
+            .catch(() => {});
+    }, []);
+
+    const names = new Map<string, string>([
+        ...(resources ?? []).map((r) => [unitKey("resource", r.id), r.name] as [string, string]),
+        ...(teams ?? []).map((t) => [unitKey("team", t.id), t.name] as [string, string]),
+    ]);
+
+    const update = (patch: Partial<AvlConfig>) => setConfig((c) => (c ? { ...c, ...patch } : c));
+
+    const handleSave = async () => {
+        if (!config) return;
+        setSaving(true);
+        try {
+            await configureAvl(config);
+            toast.success(config.enabled ? "Tracking listeners restarted" : "Tracking stopped");
+        } catch (err) {
+            toast.error(`Could not save tracking settings: ${err}`);
+        } finally {
+            setSaving(false);
+        }
+    };
+
+    const handleAssign = async () => {
+        const subject = parseUnitKey(unit);
+        if (!deviceId.trim() || !subject) return;
+        try {
+            await assign.mutateAsync({ deviceId: deviceId.trim(), ...subject, label: names.get(unit) ?? null });
+            setDeviceId("");
+            setUnit("");
+        } catch {
+            // Reported by the mutation
+        }
+    };
+
+    if (!config) return null;
+
+    return (
+        <div className="space-y-3">
+            <div className="flex items-start justify-between gap-3">
+                <div>
+                    <p className="text-sm font-medium">Vehicle tracking</p>
+                    <p className="text-xs text-muted-foreground">
+                        Receive positions from vehicle and team trackers over UDP (NMEA, APRS or JSON), a local
+                        HTTP gateway or APRS-IS
+                    </p>
+                </div>
+                <Switch checked={config.enabled} onCheckedChange={(enabled) => update({ enabled })} />
+            </div>
+
+            <div className="grid gap-3 sm:grid-cols-2">
+                <div className="space-y-1">
+                    <Label className="text-xs">UDP listen address</Label>
+                    <Input
+                        value={config.udp ?? ""}
+                        onChange={(e) => update({ udp: e.target.value || null })}
+                        placeholder="0.0.0.0:5010"
+                    />
+                </div>
+                <div className="space-y-1">
+                    <Label className="text-xs">HTTP gateway address</Label>
+                    <Input
+                        value={config.http ?? ""}
+                        onChange={(e) => update({ http: e.target.value || null })}
+                        placeholder="127.0.0.1:8088"
+                    />
+                </div>
+                <div className="space-y-1">
+                    <Label className="text-xs">Shared token</Label>
+                    <Input
+                        type="password"
+                        value={config.token ?? ""}
+                        onChange={(e) => update({ token: e.target.value || null })}
+                        placeholder="Required for UDP and HTTP"
+                        title="Trackers send it as the first line of each datagram; gateways as a bearer token"
+                    />
+                </div>
+                <div className="space-y-1">
+                    <Label className="text-xs">Keep tracks for (days)</Label>
+                    <Input
+                        type="number"
+                        min="1"
+                        max="366"
+                        value={config.retentionDays}
+                        onChange={(e) => update({ retentionDays: Number(e.target.value) || 1 })}
+                    />
+                </div>
+                <div className="space-y-1">
+                    <Label className="text-xs">APRS-IS callsign</Label>
+                    <Input
+                        value={config.aprsIs?.callsign ?? ""}
+                        onChange={(e) =>
+                            update({
+                                aprsIs: e.target.value
+                                    ? { server: null, filter: null, ...config.aprsIs, callsign: e.target.value }
+                                    : null,
+                            })
+                        }
+                        placeholder="Leave empty to skip APRS-IS"
+                    />
+                </div>
+                {config.aprsIs && (
+                    <div className="space-y-1">
+                        <Label className="text-xs">APRS-IS filter</Label>
+                        <Input
+                            value={config.aprsIs.filter ?? ""}
+                            onChange={(e) =>
+                                update({ aprsIs: { ...config.aprsIs!, filter: e.target.value || null } })
+                            }
+                            placeholder="Assigned callsigns"
+                        />
+                    </div>
+                )}
+            </div>
+
+            <div className="flex items-center justify-between gap-3">
+                <div className="flex items-center gap-2">
+                    <Switch
+                        id="avl-sync"
+                        checked={config.syncResources}
+                        onCheckedChange={(syncResources) => update({ syncResources })}
+                    />
+                    <Label htmlFor="avl-sync" className="text-xs">
+                        Update resource positions for everyone
+                    </Label>
+                </div>
+                <Button size="sm" onClick={handleSave} disabled={saving} className="gap-1.5">
+                    {saving && <Loader2 className="h-3.5 w-3.5 animate-spin" />}
+                    Save
+                </Button>
+            </div>
+
+            {(status?.listeners.length ?? 0) > 0 && (
+                <div className="space-y-1 rounded-lg border p-3">
+                    {status!.listeners.map((l) => (
+                        <div key={`${l.kind}-${l.address}`} className="flex items-center gap-2 text-xs">
+                            <Radio className={`h-3.5 w-3.5 shrink-0 ${STATE_COLORS[l.state]}`} />
+                            <span className="font-medium">{LISTENER_LABELS[l.kind]}</span>
+                            <span className="font-mono text-muted-foreground">{l.address}</span>
+                            <span className="ml-auto truncate text-muted-foreground">
+                                {l.state === "error"
+                                    ? l.error
+                                    : l.lastReportAt
+                                      ? `${l.reports} reports, last ${formatDistanceToNow(new Date(l.lastReportAt), { addSuffix: true })}`
+                                      : l.state === "listening"
+                                        ? "Listening"
+                                        : "Connecting…"}
+                            </span>
+                        </div>
+                    ))}
+                </div>
+            )}
+
+            <div className="space-y-2">
+                <p className="text-xs font-medium text-muted-foreground">Trackers</p>
+                {(units ?? []).map((u) => (
+                    <div key={u.deviceId} className="flex items-center gap-2 text-sm">
+                        <span className="font-mono">{u.deviceId}</span>
+                        <span className="truncate text-muted-foreground">
+                            → {names.get(unitKey(u.subjectKind, u.subjectId)) ?? u.label ?? u.subjectId}
+                        </span>
+                        <Button
+                            size="icon"
+                            variant="ghost"
+                            className="ml-auto h-7 w-7"
+                            onClick={() => remove.mutate(u.deviceId)}
+                        >
+                            <Trash2 className="h-3.5 w-3.5" />
+                        </Button>
+                    </div>
+                ))}
+
+                {(status?.unassigned.length ?? 0) > 0 && (
+                    <div className="space-y-1">
+                        <p className="text-xs text-muted-foreground">Heard but not assigned</p>
+                        {status!.unassigned.map((d) => (
+                            <button
+                                key={d.deviceId}
+                                type="button"
+                                className="flex w-full items-center gap-2 rounded px-1 text-left text-xs hover:bg-muted"
+                                onClick={() => setDeviceId(d.deviceId)}
+                            >
+                                <span className="font-mono">{d.deviceId}</span>
+                                <span className="text-muted-foreground">
+                                    {d.source.toUpperCase()} · {d.reports} reports ·{" "}
+                                    {formatDistanceToNow(new Date(d.lastSeen), { addSuffix: true })}
+                                </span>
+                            </button>
+                        ))}
+                    </div>
+                )}
+
+                <div className="flex items-end gap-2">
+                    <div className="flex-1 space-y-1">
+                        <Label className="text-xs">Device id or callsign</Label>
+                        <Input value={deviceId} onChange={(e) => setDeviceId(e.target.value)} placeholder="VK2ABC-9" />
+                    </div>
+                    <div className="flex-1 space-y-1">
+                        <Label className="text-xs">Unit</Label>
+                        <Select value={unit} onValueChange={setUnit}>
+                            <SelectTrigger>
+                                <SelectValue placeholder="Resource or team" />
+                            </SelectTrigger>
+                            <SelectContent>
+                                {[...names].map(([key, name]) => (
+                                    <SelectItem key={key} value={key}>
+                                        {name}
+                                        <span className="ml-2 text-xs text-muted-foreground">
+                                            {key.startsWith("team:") ? "Team" : "Resource"}
+                                        </span>
+                                    </SelectItem>
+                                ))}
+                            </SelectContent>
+                        </Select>
+                    </div>
+                    <Button
+                        size="sm"
+                        className="h-9"
+                        onClick={handleAssign}
+                        disabled={!deviceId.trim() || !unit || assign.isPending}
+                    >
+                        Assign
+                    </Button>
+                </div>
+            </div>
+        </div>
+    );
+}
@@ -0,0 +1,269 @@
+import { useEffect, useState } from "react";
+import { open } from "@tauri-apps/plugin-dialog";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import {
+    Select,
+    SelectContent,
+    SelectItem,
+    SelectTrigger,
+    SelectValue,
+} from "@/components/ui/select";
+import { Textarea } from "@/components/ui/textarea";
+import { FolderOpen, Loader2, RefreshCw } from "lucide-react";
+import { toast } from "sonner";
+import { formatDistanceToNow } from "date-fns";
+import { useCapWatchStatus, usePollCapSources } from "@/hooks/use-cap";
+import { configureCap, getCapConfig, type CapConfig } from "@/lib/cap";
+import type { SeverityLevel } from "@/types/enums";
+
+const SEVERITIES: SeverityLevel[] = ["low", "medium", "high", "critical"];
+
+/**
+ * Who our CAP messages say they are from, and which partner feeds and drop
+ * folder are watched for theirs.
+ */
+export function CapSettings() {
+    const [config, setConfig] = useState<CapConfig | null>(null);
+    const [feeds, setFeeds] = useState("");
+    const [saving, setSaving] = useState(false);
+    const { data: status } = useCapWatchStatus();
+    const poll = usePollCapSources();
+
+    const load = (next: CapConfig) => {
+        setConfig(next);
+        setFeeds(next.feeds.join("\n"));
+    };
+
+    useEffect(() => {
+        getCapConfig()
+            .then(load)
+            .catch(() => {});
+    }, []);
+
+    const update = (patch: Partial<CapConfig>) => setConfig((c) => (c ? { ...c, ...patch } : c));
+
+    const pickFolder = async () => {
+        const path = await open({ directory: true, multiple: false });
+        if (typeof path === "string") update({ dropFolder: path });
+    };
+
+    const setRegion = (patch: { lat?: number; lng?: number; radiusKm?: number }) =>
+        setConfig((c) => {
+            if (!c) return c;
+            const region = c.region ?? { center: { lat: 0, lng: 0 }, radiusKm: 100 };
+            return {
+                ...c,
+                region: {
+                    center: { lat: patch.lat ?? region.center.lat, lng: patch.lng ?? region.center.lng },
+                    radiusKm: patch.radiusKm ?? region.radiusKm,
+                },
+            };
+        });
+
+    const handleSave = async () => {
+        if (!config) return;
+        setSaving(true);
+        try {
+            const list = feeds.split("\n").map((f) => f.trim()).filter(Boolean);
+            load(await configureCap({ ...config, feeds: list }));
+            toast.success("CAP settings saved");
+        } catch (err) {
+            toast.error(`Could not save CAP settings: ${err}`);
+        } finally {
+            setSaving(false);
+        }
+    };
+
+    if (!config) return null;
+
+    return (
+        <div className="space-y-3">
+            <div>
+                <p className="text-sm font-medium">Common Alerting Protocol</p>
+                <p className="text-xs text-muted-foreground">
+                    Alerts exported as CAP 1.2 for national warning systems and sirens carry this sender
+                </p>
+            </div>
+
+            <div className="grid gap-3 sm:grid-cols-2">
+                <div className="space-y-1">
+                    <Label className="text-xs">Sender id</Label>
+                    <Input
+                        value={config.sender ?? ""}
+                        onChange={(e) => update({ sender: e.target.value || null })}
+                        placeholder="alerts@agency.example"
+                    />
+                </div>
+                <div className="space-y-1">
+                    <Label className="text-xs">Sender name</Label>
+                    <Input
+                        value={config.senderName ?? ""}
+                        onChange={(e) => update({ senderName: e.target.value || null })}
+                        placeholder="County Emergency Management"
+                    />
+                </div>
+                <div className="space-y-1">
+                    <Label className="text-xs">Language</Label>
+                    <Input
+                        value={config.language}
+                        onChange={(e) => update({ language: e.target.value })}
+                        placeholder="en-US"
+                    />
+                </div>
+            </div>
+
+            <div className="pt-2">
+                <p className="text-sm font-medium">Partner feeds</p>
+                <p className="text-xs text-muted-foreground">
+                    CAP Atom or RSS feeds and a drop folder are watched in the background. New warnings
+                    wait on the Alerts page for review instead of being copied in by hand.
+                </p>
+            </div>
+
+            <div className="space-y-1">
+                <Label className="text-xs">Feed URLs (one per line)</Label>
+                <Textarea
+                    data-selectable
+                    value={feeds}
+                    onChange={(e) => setFeeds(e.target.value)}
+                    placeholder="https://alerts.weather.example/cap/atom.xml"
+                    className="min-h-20 font-mono text-xs"
+                />
+            </div>
+
+            <div className="space-y-1">
+                <Label className="text-xs">Drop folder</Label>
+                <div className="flex gap-2">
+                    <Input
+                        value={config.dropFolder ?? ""}
+                        onChange={(e) => update({ dropFolder: e.target.value || null })}
+                        placeholder="Folder partners copy CAP files into"
+                    />
+                    <Button variant="outline" size="icon" onClick={pickFolder} title="Choose folder">
+                        <FolderOpen className="h-4 w-4" />
+                    </Button>
+                </div>
+            </div>
+
+            <div className="grid gap-3 sm:grid-cols-2">
+                <div className="space-y-1">
+                    <Label className="text-xs">Check feeds every (minutes)</Label>
+                    <Input
+                        type="number"
+                        min={1}
+                        max={1440}
+                        value={config.pollMinutes}
+                        onChange={(e) => update({ pollMinutes: Number(e.target.value) })}
+                    />
+                </div>
+                <div className="space-y-1">
+                    <Label className="text-xs">Suggest from severity</Label>
+                    <Select
+                        value={config.minSeverity}
+                        onValueChange={(v) => update({ minSeverity: v as SeverityLevel })}
+                    >
+                        <SelectTrigger>
+                            <SelectValue />
+                        </SelectTrigger>
+                        <SelectContent>
+                            {SEVERITIES.map((s) => (
+                                <SelectItem key={s} value={s} className="capitalize">
+                                    {s}
+                                </SelectItem>
+                            ))}
+                        </SelectContent>
+                    </Select>
+                </div>
+            </div>
+
+            <div className="space-y-1">
+                <div className="flex items-center justify-between">
+                    <Label className="text-xs">Area of interest</Label>
+                    <Button
+                        variant="ghost"
+                        size="sm"
+                        className="h-6 text-xs"
+                        onClick={() => (config.region ? update({ region: null }) : setRegion({}))}
+                    >
+                        {config.region ? "Any area" : "Limit to an area"}
+                    </Button>
+                </div>
+                {config.region && (
+                    <div className="grid gap-2 sm:grid-cols-3">
+                        <Input
+                            type="number"
+                            step="any"
+                            value={config.region.center.lat}
+                            onChange={(e) => setRegion({ lat: Number(e.target.value) })}
+                            placeholder="Latitude"
+                        />
+                        <Input
+                            type="number"
+                            step="any"
+                            value={config.region.center.lng}
+                            onChange={(e) => setRegion({ lng: Number(e.target.value) })}
+                            placeholder="Longitude"
+                        />
+                        <Input
+                            type="number"
+                            min={1}
+                            value={config.region.radiusKm}
+                            onChange={(e) => setRegion({ radiusKm: Number(e.target.value) })}
+                            placeholder="Radius (km)"
+                        />
+                    </div>
+                )}
+            </div>
+
+            {status?.watching && (
+                <ul className="space-y-1 rounded-md border p-2 text-xs">
+                    {status.sources.map((source) => (
+                        <li key={`${source.kind}:${source.location}`} className="space-y-0.5">
+                            <div className="flex justify-between gap-2">
+                                <span className="truncate font-mono" title={source.location}>
+                                    {source.location}
+                                </span>
+                                <span className="shrink-0 text-muted-foreground">
+                                    {source.lastCheckedAt
+                                        ? `checked ${formatDistanceToNow(new Date(source.lastCheckedAt), { addSuffix: true })}`
+                                        : "not checked yet"}
+                                </span>
+                            </div>
+                            <p className="text-muted-foreground">
+                                {source.suggested} suggested · {source.skipped} skipped · {source.rejected} rejected
+                                {source.unreachable > 0 && ` · ${source.unreachable} unreachable`}
+                            </p>
+                            {source.error && <p className="text-destructive">{source.error}</p>}
+                            {source.lastProblem && (
+                                <p className="whitespace-pre-wrap text-amber-600" title={source.lastProblem}>
+                                    {source.lastProblem}
+                                </p>
+                            )}
+                        </li>
+                    ))}
+                </ul>
+            )}
+
+            <div className="flex justify-end gap-2">
+                {status?.watching && (
+                    <Button
+                        size="sm"
+                        variant="outline"
+                        onClick={() => poll.mutate()}
+                        disabled={poll.isPending}
+                        className="gap-1.5"
+                    >
+                        <RefreshCw className={`h-3.5 w-3.5 ${poll.isPending ? "animate-spin" : ""}`} />
+                        Check now
+                    </Button>
+                )}
+                <Button size="sm" onClick={handleSave} disabled={saving} className="gap-1.5">
+                    {saving && <Loader2 className="h-3.5 w-3.5 animate-spin" />}
+                    Save
+                </Button>
+            </div>
+        </div>
+    );
+}
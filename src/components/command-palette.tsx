@@ -11,6 +11,7 @@ import {
     CommandShortcut,
 } from "@/components/ui/command";
 import { supabase } from "@/lib/supabase";
+import { searchLocal, type SearchHit } from "@/lib/offline-cache";
 import { useAuthStore } from "@/stores/auth-store";
 import {
     LayoutDashboard,
@@ -30,6 +31,7 @@ import {
     ShieldCheck,
     Loader2,
     ScrollText,
+    Search,
 } from "lucide-react";
 
 // ─── Navigation pages ───────────────────────────────────────────
@@ -66,6 +68,40 @@ interface SearchResult {
     type: "incident" | "resource" | "task";
 }
 
+const HIT_LINKS: Record<SearchHit["entity"], (hit: SearchHit) => string> = {
+    incidents: (hit) => `/incidents/${hit.id}`,
+    incident_updates: (hit) => (hit.parentId ? `/incidents/${hit.parentId}` : "/incidents"),
+    messages: () => "/messaging",
+    tasks: () => "/tasks",
+    documents: () => "/documents",
+};
+
+const HIT_LABELS: Record<SearchHit["entity"], string> = {
+    incidents: "Incident",
+    incident_updates: "Incident update",
+    messages: "Message",
+    tasks: "Task",
+    documents: "Document",
+};
+
+/** Render FTS highlight markup as text, without trusting it as HTML. */
+function Marked({ text }: { text: string }) {
+    const parts = text.split(/<mark>(.*?)<\/mark>/g);
+    return (
+        <>
+            {parts.map((part, i) =>
+                i % 2 === 1 ? (
+                    <mark key={i} className="rounded-sm bg-yellow-200/70 px-0.5 text-foreground dark:bg-yellow-500/30">
+                        {part}
+                    </mark>
+                ) : (
+                    part
+                )
+            )}
+        </>
+    );
+}
+
 // ─── Component ──────────────────────────────────────────────────
 
 export function CommandPalette({
@@ -81,6 +117,7 @@ export function CommandPalette({
 
     const [query, setQuery] = useState("");
     const [results, setResults] = useState<SearchResult[]>([]);
+    const [textHits, setTextHits] = useState<SearchHit[]>([]);
     const [loading, setLoading] = useState(false);
 
     // ── Ctrl+K global shortcut ──────────────────────────────────
@@ -99,6 +136,7 @@ export function CommandPalette({
     useEffect(() => {
         if (!query || query.length < 2) {
             setResults([]);
+            setTextHits([]);
             return;
         }
 
@@ -112,6 +150,16 @@ export function CommandPalette({
                 const escaped = query.replace(/[%_\\]/g, "\\$&");
                 const term = `%${escaped}%`;
 
+                // Full-text search over the local mirror also covers messages,
+                // incident updates and documents, and works offline
+                searchLocal({ text: query, limit: 8 })
+                    .then((hits) => {
+                        if (!cancelled) setTextHits(hits);
+                    })
+                    .catch(() => {
+                        if (!cancelled) setTextHits([]);
+                    });
+
                 const [incidents, resources, tasks] = await Promise.all([
                     supabase
                         .from("incidents")
@@ -210,6 +258,7 @@ export function CommandPalette({
             if (!next) {
                 setQuery("");
                 setResults([]);
+                setTextHits([]);
             }
         },
         [onOpenChange]
@@ -218,6 +267,8 @@ export function CommandPalette({
     const incidentResults = results.filter((r) => r.type === "incident");
     const resourceResults = results.filter((r) => r.type === "resource");
     const taskResults = results.filter((r) => r.type === "task");
+    const shownIds = new Set(results.map((r) => r.id));
+    const otherHits = textHits.filter((h) => !shownIds.has(h.id));
 
     return (
         <CommandDialog open={open} onOpenChange={handleOpenChange}>
@@ -302,9 +353,34 @@ export function CommandPalette({
                     </CommandGroup>
                 )}
 
+                {otherHits.length > 0 && (
+                    <CommandGroup heading="Matching text">
+                        {otherHits.map((hit) => (
+                            <CommandItem
+                                key={`${hit.entity}-${hit.id}`}
+                                value={`text-${hit.entity}-${hit.id}`}
+                                onSelect={() => handleSelect(HIT_LINKS[hit.entity](hit))}
+                            >
+                                <Search className="text-muted-foreground" />
+                                <div className="flex flex-col gap-0.5 min-w-0">
+                                    <span className="truncate">
+                                        {hit.title ? <Marked text={hit.title} /> : HIT_LABELS[hit.entity]}
+                                    </span>
+                                    <span className="text-xs text-muted-foreground truncate">
+                                        {HIT_LABELS[hit.entity]}
+                                        {" \u00b7 "}
+                                        <Marked text={hit.snippet} />
+                                    </span>
+                                </div>
+                            </CommandItem>
+                        ))}
+                    </CommandGroup>
+                )}
+
                 {(incidentResults.length > 0 ||
                     resourceResults.length > 0 ||
-                    taskResults.length > 0) && <CommandSeparator />}
+                    taskResults.length > 0 ||
+                    otherHits.length > 0) && <CommandSeparator />}
 
                 {/* Page navigation */}
                 <CommandGroup heading="Pages">
@@ -0,0 +1,166 @@
+import { useState } from "react";
+import { Badge } from "@/components/ui/badge";
+import { Button } from "@/components/ui/button";
+import {
+    Dialog,
+    DialogContent,
+    DialogDescription,
+    DialogHeader,
+    DialogTitle,
+} from "@/components/ui/dialog";
+import { ScrollArea } from "@/components/ui/scroll-area";
+import { Loader2 } from "lucide-react";
+import { toast } from "sonner";
+import { formatDistanceToNow } from "date-fns";
+import { useOfflineStore, type ConflictResolution, type SyncConflict } from "@/stores/offline-store";
+
+interface ConflictReviewDialogProps {
+    open: boolean;
+    onOpenChange: (open: boolean) => void;
+}
+
+function show(value: unknown): string {
+    if (value === null || value === undefined || value === "") return "—";
+    return typeof value === "string" ? value : JSON.stringify(value);
+}
+
+/**
+ * Offline edits that collided with someone else's change. Each one is kept
+ * as ours, dropped in favour of theirs, or merged field by field.
+ */
+export function ConflictReviewDialog({ open, onOpenChange }: ConflictReviewDialogProps) {
+    const conflicts = useOfflineStore((s) => s.conflicts);
+
+    return (
+        <Dialog open={open} onOpenChange={onOpenChange}>
+            <DialogContent className="sm:max-w-2xl">
+                <DialogHeader>
+                    <DialogTitle>Changes Needing Review</DialogTitle>
+                    <DialogDescription>
+                        These records were edited by someone else while your change was waiting to sync.
+                    </DialogDescription>
+                </DialogHeader>
+
+                <ScrollArea className="max-h-[60vh] rounded-md border">
+                    {conflicts.length === 0 ? (
+                        <p className="p-6 text-center text-sm text-muted-foreground">
+                            Nothing waiting for review.
+                        </p>
+                    ) : (
+                        <ul className="divide-y">
+                            {conflicts.map((conflict) => (
+                                <ConflictRow key={conflict.id} conflict={conflict} />
+                            ))}
+                        </ul>
+                    )}
+                </ScrollArea>
+            </DialogContent>
+        </Dialog>
+    );
+}
+
+function ConflictRow({ conflict }: { conflict: SyncConflict }) {
+    const resolveConflict = useOfflineStore((s) => s.resolveConflict);
+    const [busy, setBusy] = useState(false);
+    // Fields where we keep our value when merging; theirs otherwise
+    const [mine, setMine] = useState<Set<string>>(() => new Set(conflict.fields));
+    const deleted = conflict.remote === null;
+    const fields = conflict.fields.length ? conflict.fields : Object.keys(conflict.local);
+
+    const resolve = async (resolution: ConflictResolution) => {
+        setBusy(true);
+        try {
+            await resolveConflict(conflict.id, resolution);
+        } catch (err) {
+            toast.error(`Could not resolve conflict: ${err}`);
+        } finally {
+            setBusy(false);
+        }
+    };
+
+    const merge = () => {
+        const payload = Object.fromEntries(
+            Object.entries(conflict.local).filter(([key]) => !conflict.fields.includes(key) || mine.has(key))
+        );
+        resolve({ kind: "merged", payload });
+    };
+
+    const toggle = (field: string) =>
+        setMine((current) => {
+            const next = new Set(current);
+            if (!next.delete(field)) next.add(field);
+            return next;
+        });
+
+    return (
+        <li className="space-y-2 p-3 text-sm">
+            <div className="flex items-center justify-between gap-2">
+                <div className="min-w-0">
+                    <p className="truncate font-medium">
+                        {show(conflict.local.title ?? conflict.remote?.title ?? conflict.recordId)}
+                    </p>
+                    <p className="text-xs text-muted-foreground">
+                        <span className="capitalize">{conflict.table.replace(/_/g, " ")}</span> · edited{" "}
+                        {formatDistanceToNow(new Date(conflict.queuedAt), { addSuffix: true })}
+                    </p>
+                </div>
+                {deleted && <Badge variant="destructive">Deleted by someone else</Badge>}
+            </div>
+
+            {!deleted && (
+                <table className="w-full text-xs">
+                    <thead className="text-muted-foreground">
+                        <tr>
+                            <th className="py-1 text-left font-normal">Field</th>
+                            <th className="py-1 text-left font-normal">Yours</th>
+                            <th className="py-1 text-left font-normal">Theirs</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        {fields.map((field) => {
+                            const keepMine = mine.has(field);
+                            return (
+                                <tr key={field} className="align-top">
+                                    <td className="py-1 pr-2 font-mono">{field}</td>
+                                    <td className="py-1 pr-2">
+                                        <button
+                                            type="button"
+                                            className={`rounded px-1 text-left ${keepMine ? "bg-primary/10 font-medium" : "text-muted-foreground"}`}
+                                            onClick={() => toggle(field)}
+                                        >
+                                            {show(conflict.local[field])}
+                                        </button>
+                                    </td>
+                                    <td className="py-1">
+                                        <button
+                                            type="button"
+                                            className={`rounded px-1 text-left ${keepMine ? "text-muted-foreground" : "bg-primary/10 font-medium"}`}
+                                            onClick={() => toggle(field)}
+                                        >
+                                            {show(conflict.remote?.[field])}
+                                        </button>
+                                    </td>
+                                </tr>
+                            );
+                        })}
+                    </tbody>
+                </table>
+            )}
+
+            <div className="flex justify-end gap-2">
+                {busy && <Loader2 className="h-4 w-4 animate-spin self-center text-muted-foreground" />}
+                <Button size="sm" variant="outline" disabled={busy} onClick={() => resolve({ kind: "keep_remote" })}>
+                    {deleted ? "Leave deleted" : "Keep theirs"}
+                </Button>
+                {!deleted && conflict.fields.length > 0 && (
+                    <Button size="sm" variant="outline" disabled={busy} onClick={merge}>
+                        Use selection
+                    </Button>
+                )}
+                <Button size="sm" disabled={busy} onClick={() => resolve({ kind: "keep_local" })}>
+                    {deleted ? "Restore mine" : "Keep mine"}
+                </Button>
+            </div>
+        </li>
+    );
+}
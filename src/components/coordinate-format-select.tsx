@@ -0,0 +1,44 @@
+import {
+    Select,
+    SelectContent,
+    SelectItem,
+    SelectTrigger,
+    SelectValue,
+} from "@/components/ui/select";
+import { useCoordinatePreference, useSetCoordinatePreference } from "@/hooks/use-coords";
+import { NOTATIONS, type Notation } from "@/lib/coords";
+
+/** How coordinates are shown across the app and in reports. */
+export function CoordinateFormatSelect() {
+    const { data: preference } = useCoordinatePreference();
+    const save = useSetCoordinatePreference();
+    const notation = preference?.notation ?? "decimal";
+
+    return (
+        <div className="flex items-center justify-between gap-4">
+            <div>
+                <p className="text-sm font-medium">Coordinate format</p>
+                <p className="text-xs text-muted-foreground">
+                    Used to show positions and in reports. Any format can be typed into location fields.
+                </p>
+            </div>
+            <Select
+                value={notation}
+                onValueChange={(value) => save.mutate(value as Notation)}
+                disabled={!preference || save.isPending}
+            >
+                <SelectTrigger className="w-56">
+                    <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                    {NOTATIONS.map((n) => (
+                        <SelectItem key={n.value} value={n.value}>
+                            <span>{n.label}</span>
+                            <span className="ml-2 font-mono text-xs text-muted-foreground">{n.example}</span>
+                        </SelectItem>
+                    ))}
+                </SelectContent>
+            </Select>
+        </div>
+    );
+}
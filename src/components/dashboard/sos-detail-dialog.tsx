@@ -0,0 +1,113 @@
+import { useNavigate } from "react-router-dom";
+import { formatDistanceToNow } from "date-fns";
+import { toast } from "sonner";
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+} from "@/components/ui/dialog";
+import { Badge } from "@/components/ui/badge";
+import { Button } from "@/components/ui/button";
+import { SeverityBadge } from "@/components/incidents/severity-badge";
+import { Loader2, MapPin } from "lucide-react";
+import { useResolveSOS, useSOS } from "@/hooks/use-sos";
+
+interface SOSDetailDialogProps {
+  /** The broadcast to show; the dialog is closed while null. */
+  id: string | null;
+  onClose: () => void;
+}
+
+/** One SOS broadcast, as opened from a notification, share link or search. */
+export function SOSDetailDialog({ id, onClose }: SOSDetailDialogProps) {
+  const navigate = useNavigate();
+  const { data: sos, isLoading, error } = useSOS(id);
+  const resolve = useResolveSOS();
+  const located = sos?.latitude != null && sos?.longitude != null;
+
+  const handleResolve = async () => {
+    if (!sos) return;
+    try {
+      await resolve.mutateAsync(sos.id);
+      toast.success("SOS marked as resolved");
+    } catch (err) {
+      toast.error(`Could not resolve SOS: ${err}`);
+    }
+  };
+
+  return (
+    <Dialog open={!!id} onOpenChange={(open) => !open && onClose()}>
+      <DialogContent className="sm:max-w-md">
+        <DialogHeader>
+          <DialogTitle>SOS Broadcast</DialogTitle>
+          <DialogDescription>
+            {sos
+              ? `Sent ${formatDistanceToNow(new Date(sos.created_at), { addSuffix: true })}`
+              : "Emergency broadcast"}
+          </DialogDescription>
+        </DialogHeader>
+
+        {isLoading ? (
+          <div className="flex justify-center py-6">
+            <Loader2 className="h-5 w-5 animate-spin text-muted-foreground" />
+          </div>
+        ) : !sos ? (
+          <p className="py-6 text-center text-sm text-muted-foreground">
+            {error ? "This SOS broadcast could not be found." : "Nothing to show."}
+          </p>
+        ) : (
+          <div className="space-y-3 text-sm">
+            <div className="flex items-center gap-2">
+              <SeverityBadge severity={sos.severity} />
+              {sos.is_active ? (
+                <Badge variant="destructive">Active</Badge>
+              ) : (
+                <Badge variant="secondary">Resolved</Badge>
+              )}
+            </div>
+            <p className="whitespace-pre-wrap">{sos.message || "No message"}</p>
+            {(sos.location_name || located) && (
+              <p className="flex items-center gap-1.5 text-muted-foreground">
+                <MapPin className="h-3.5 w-3.5 shrink-0" />
+                {sos.location_name ??
+                  `${sos.latitude!.toFixed(5)}, ${sos.longitude!.toFixed(5)}`}
+              </p>
+            )}
+          </div>
+        )}
+
+        {sos && (
+          <DialogFooter className="gap-2 sm:gap-0">
+            {located && (
+              <Button
+                variant="outline"
+                onClick={() =>
+                  navigate(`/map?lat=${sos.latitude}&lng=${sos.longitude}&zoom=15`)
+                }
+              >
+                Show on map
+              </Button>
+            )}
+            {sos.incident_id && (
+              <Button
+                variant="outline"
+                onClick={() => navigate(`/incidents/${sos.incident_id}`)}
+              >
+                Open incident
+              </Button>
+            )}
+            {sos.is_active && (
+              <Button onClick={handleResolve} disabled={resolve.isPending}>
+                {resolve.isPending && <Loader2 className="mr-1.5 h-4 w-4 animate-spin" />}
+                Resolve
+              </Button>
+            )}
+          </DialogFooter>
+        )}
+      </DialogContent>
+    </Dialog>
+  );
+}
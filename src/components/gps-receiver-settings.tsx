@@ -0,0 +1,278 @@
+import { useEffect, useState } from "react";
+import { open } from "@tauri-apps/plugin-dialog";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { Switch } from "@/components/ui/switch";
+import {
+    Select,
+    SelectContent,
+    SelectItem,
+    SelectTrigger,
+    SelectValue,
+} from "@/components/ui/select";
+import { FolderOpen, Loader2, RefreshCw, Satellite } from "lucide-react";
+import { toast } from "sonner";
+import { useFormattedCoordinate } from "@/hooks/use-coords";
+import { useGpsStatus } from "@/hooks/use-gps";
+import {
+    FIX_QUALITY_LABELS,
+    getGpsConfig,
+    GPS_SOURCE_TYPES,
+    listSerialPorts,
+    startGps,
+    stopGps,
+    type GpsSource,
+    type GpsSourceType,
+    type GpsStatus,
+    type SerialPortInfo,
+} from "@/lib/gps";
+
+const STATE_LABELS: Record<GpsStatus["state"], string> = {
+    stopped: "Not connected",
+    connecting: "Connecting…",
+    searching: "Connected, waiting for a fix",
+    fixed: "Fix",
+    error: "Error",
+};
+
+const BAUD_RATES = [4800, 9600, 19200, 38400, 57600, 115200];
+
+/** Connect the command post's GPS receiver. */
+export function GpsReceiverSettings() {
+    const { data: status } = useGpsStatus();
+    const [type, setType] = useState<GpsSourceType>("serial");
+    const [ports, setPorts] = useState<SerialPortInfo[]>([]);
+    const [serialPath, setSerialPath] = useState("");
+    const [baudRate, setBaudRate] = useState(9600);
+    const [address, setAddress] = useState("");
+    const [replayPath, setReplayPath] = useState("");
+    const [replaySpeed, setReplaySpeed] = useState("1");
+    const [repeat, setRepeat] = useState(false);
+    const [busy, setBusy] = useState(false);
+    const fix = status?.fix ?? null;
+    const coordinates = useFormattedCoordinate(fix?.point.lat, fix?.point.lng);
+    const running = !!status && status.state !== "stopped";
+
+    const refreshPorts = () =>
+        listSerialPorts()
+            .then(setPorts)
+            .catch((err) => toast.error(`${err}`));
+
+    // Start from the saved receiver
+    useEffect(() => {
+        getGpsConfig()
+            .then(({ source }) => {
+                if (!source) return;
+                setType(source.type);
+                if (source.type === "serial") {
+                    setSerialPath(source.path);
+                    setBaudRate(source.baudRate);
+                } else if (source.type === "tcp" || source.type === "gpsd") {
+                    setAddress(source.address ?? "");
+                } else if (source.type === "udp") {
+                    setAddress(source.bind);
+                } else {
+                    setReplayPath(source.path);
+                    setReplaySpeed(String(source.speed));
+                    setRepeat(source.repeat);
+                }
+            })
+            .catch(() => {});
+        refreshPorts();
+    }, []);
+
+    const source = (): GpsSource | null => {
+        switch (type) {
+            case "serial":
+                return serialPath ? { type, path: serialPath, baudRate } : null;
+            case "tcp":
+                return address.trim() ? { type, address: address.trim() } : null;
+            case "udp":
+                return { type, bind: address.trim() || "0.0.0.0:10110" };
+            case "gpsd":
+                return { type, address: address.trim() || null };
+            case "replay":
+                return replayPath ? { type, path: replayPath, speed: Number(replaySpeed) || 1, repeat } : null;
+        }
+    };
+
+    const handlePickLog = async () => {
+        const path = await open({
+            multiple: false,
+            directory: false,
+            filters: [{ name: "NMEA log", extensions: ["nmea", "txt", "log"] }],
+        });
+        if (typeof path === "string") setReplayPath(path);
+    };
+
+    const handleConnect = async () => {
+        const next = source();
+        if (!next) return;
+        setBusy(true);
+        try {
+            await startGps(next);
+        } catch (err) {
+            toast.error(`Could not start the receiver: ${err}`);
+        } finally {
+            setBusy(false);
+        }
+    };
+
+    const handleDisconnect = async () => {
+        setBusy(true);
+        try {
+            await stopGps();
+        } catch (err) {
+            toast.error(`${err}`);
+        } finally {
+            setBusy(false);
+        }
+    };
+
+    return (
+        <div className="space-y-3">
+            <div>
+                <p className="text-sm font-medium">GPS receiver</p>
+                <p className="text-xs text-muted-foreground">
+                    Fills in this post's position on SOS broadcasts and new incidents
+                </p>
+            </div>
+
+            <div className="grid gap-3 sm:grid-cols-[12rem_1fr] items-end">
+                <div className="space-y-1">
+                    <Label className="text-xs">Source</Label>
+                    <Select value={type} onValueChange={(v) => setType(v as GpsSourceType)}>
+                        <SelectTrigger>
+                            <SelectValue />
+                        </SelectTrigger>
+                        <SelectContent>
+                            {GPS_SOURCE_TYPES.map((t) => (
+                                <SelectItem key={t.value} value={t.value}>
+                                    {t.label}
+                                </SelectItem>
+                            ))}
+                        </SelectContent>
+                    </Select>
+                </div>
+
+                {type === "serial" && (
+                    <div className="flex items-end gap-2">
+                        <div className="flex-1 space-y-1">
+                            <Label className="text-xs">Port</Label>
+                            <Select value={serialPath} onValueChange={setSerialPath}>
+                                <SelectTrigger>
+                                    <SelectValue placeholder={ports.length ? "Choose a port" : "No ports found"} />
+                                </SelectTrigger>
+                                <SelectContent>
+                                    {ports.map((p) => (
+                                        <SelectItem key={p.path} value={p.path}>
+                                            {p.path}
+                                            {p.description && (
+                                                <span className="ml-2 text-xs text-muted-foreground">{p.description}</span>
+                                            )}
+                                        </SelectItem>
+                                    ))}
+                                </SelectContent>
+                            </Select>
+                        </div>
+                        <Button size="icon" variant="ghost" className="h-9 w-9" onClick={refreshPorts}>
+                            <RefreshCw className="h-4 w-4" />
+                        </Button>
+                        <div className="space-y-1">
+                            <Label className="text-xs">Baud</Label>
+                            <Select value={String(baudRate)} onValueChange={(v) => setBaudRate(Number(v))}>
+                                <SelectTrigger className="w-28">
+                                    <SelectValue />
+                                </SelectTrigger>
+                                <SelectContent>
+                                    {BAUD_RATES.map((rate) => (
+                                        <SelectItem key={rate} value={String(rate)}>
+                                            {rate}
+                                        </SelectItem>
+                                    ))}
+                                </SelectContent>
+                            </Select>
+                        </div>
+                    </div>
+                )}
+
+                {(type === "tcp" || type === "udp" || type === "gpsd") && (
+                    <div className="space-y-1">
+                        <Label className="text-xs">{type === "udp" ? "Listen on" : "Address"}</Label>
+                        <Input
+                            value={address}
+                            onChange={(e) => setAddress(e.target.value)}
+                            placeholder={
+                                type === "udp" ? "0.0.0.0:10110" : type === "gpsd" ? "127.0.0.1:2947" : "192.168.1.20:10110"
+                            }
+                        />
+                    </div>
+                )}
+
+                {type === "replay" && (
+                    <div className="flex items-end gap-2">
+                        <Button variant="outline" className="min-w-0 flex-1 justify-start gap-1.5" onClick={handlePickLog}>
+                            <FolderOpen className="h-4 w-4 shrink-0" />
+                            <span className="truncate">{replayPath || "Choose an NMEA log…"}</span>
+                        </Button>
+                        <div className="space-y-1">
+                            <Label className="text-xs">Speed</Label>
+                            <Input
+                                type="number"
+                                min="0.1"
+                                step="0.5"
+                                className="w-20"
+                                value={replaySpeed}
+                                onChange={(e) => setReplaySpeed(e.target.value)}
+                            />
+                        </div>
+                        <div className="flex h-9 items-center gap-2">
+                            <Switch checked={repeat} onCheckedChange={setRepeat} id="gps-repeat" />
+                            <Label htmlFor="gps-repeat" className="text-xs">
+                                Loop
+                            </Label>
+                        </div>
+                    </div>
+                )}
+            </div>
+
+            <div className="flex items-center justify-between gap-3 rounded-lg border p-3">
+                <div className="flex min-w-0 items-center gap-3">
+                    <Satellite
+                        className={`h-4 w-4 shrink-0 ${status?.state === "fixed" ? "text-emerald-500" : "text-muted-foreground"}`}
+                    />
+                    <div className="min-w-0">
+                        <p className="truncate text-sm font-medium">
+                            {status?.state === "fixed" && coordinates ? coordinates : STATE_LABELS[status?.state ?? "stopped"]}
+                        </p>
+                        <p className="truncate text-xs text-muted-foreground">
+                            {status?.state === "error"
+                                ? status.error
+                                : fix
+                                  ? [
+                                        FIX_QUALITY_LABELS[fix.quality],
+                                        fix.mode !== "none" && fix.mode.toUpperCase(),
+                                        fix.satellites != null && `${fix.satellites} satellites`,
+                                        fix.accuracyM != null && `±${Math.round(fix.accuracyM)} m`,
+                                    ]
+                                        .filter(Boolean)
+                                        .join(" · ")
+                                  : "No position yet"}
+                        </p>
+                    </div>
+                </div>
+                {running ? (
+                    <Button size="sm" variant="outline" onClick={handleDisconnect} disabled={busy}>
+                        Disconnect
+                    </Button>
+                ) : (
+                    <Button size="sm" onClick={handleConnect} disabled={busy || !source()} className="gap-1.5">
+                        {busy && <Loader2 className="h-3.5 w-3.5 animate-spin" />}
+                        Connect
+                    </Button>
+                )}
+            </div>
+        </div>
+    );
+}
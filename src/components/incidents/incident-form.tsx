@@ -24,7 +24,10 @@ import { LocationPicker, type LocationPickerValue } from "@/components/map/locat
 import { useCreateIncident, useUpdateIncident } from "@/hooks/use-incidents";
 import type { Incident } from "@/types/database";
 import type { IncidentType, SeverityLevel, IncidentStatus } from "@/types/enums";
-import { MapPin, Loader2 } from "lucide-react";
+import { useFormattedCoordinate } from "@/hooks/use-coords";
+import { useGpsFix } from "@/hooks/use-gps";
+import { getGpsFix } from "@/lib/gps";
+import { MapPin, Loader2, Satellite } from "lucide-react";
 import { toast } from "sonner";
 
 // ─── Schema ──────────────────────────────────────────────────────
@@ -102,6 +105,8 @@ export function IncidentForm({
 
   const [locationPickerOpen, setLocationPickerOpen] = useState(false);
   const [location, setLocation] = useState<LocationPickerValue | null>(null);
+  const coordinates = useFormattedCoordinate(location?.latitude, location?.longitude);
+  const gpsFix = useGpsFix();
 
   const {
     register,
@@ -153,6 +158,15 @@ export function IncidentForm({
         estimated_affected_people: "",
       });
       setLocation(null);
+      // Start from where the command post is, if its receiver has a fix
+      getGpsFix()
+        .then((fix) => {
+          if (!fix) return;
+          setLocation((current) =>
+            current ?? { latitude: fix.point.lat, longitude: fix.point.lng },
+          );
+        })
+        .catch(() => {});
     }
   }, [incident, open, reset]);
 
@@ -316,11 +330,29 @@ export function IncidentForm({
                     `${location.latitude.toFixed(4)}, ${location.longitude.toFixed(4)}`
                   : "Pick Location on Map"}
               </Button>
-              {location && (
+              {coordinates && (
                 <p className="text-xs text-muted-foreground font-mono">
-                  {location.latitude.toFixed(6)}, {location.longitude.toFixed(6)}
+                  {coordinates}
                 </p>
               )}
+              {gpsFix && (
+                <Button
+                  type="button"
+                  variant="ghost"
+                  size="sm"
+                  className="h-7 gap-1.5 px-2 text-xs"
+                  onClick={() =>
+                    setLocation({
+                      latitude: gpsFix.point.lat,
+                      longitude: gpsFix.point.lng,
+                    })
+                  }
+                >
+                  <Satellite className="h-3.5 w-3.5" />
+                  Use GPS position
+                  {gpsFix.accuracyM != null && ` (±${Math.round(gpsFix.accuracyM)} m)`}
+                </Button>
+              )}
             </div>
 
             {/* Affected Radius & People */}
@@ -0,0 +1,168 @@
+import { useQuery } from "@tanstack/react-query";
+import { useNavigate } from "react-router-dom";
+import { Card, CardContent, CardHeader, CardTitle } from "@/components/ui/card";
+import { Loader2 } from "lucide-react";
+import { SeverityBadge } from "@/components/incidents/severity-badge";
+import {
+  ResourceStatusBadge,
+  formatResourceType,
+} from "@/components/resources/resource-badges";
+import { formatDistance, nearest, withinRadius, type GeoHit } from "@/lib/geo";
+import type { Incident, Resource, SOSBroadcast } from "@/types/database";
+
+/** How far out to look for available resources. */
+const RESOURCE_RADIUS_KM = 25;
+const MAX_RESOURCES = 10;
+
+/**
+ * Available resources near an incident and SOS broadcasts inside its
+ * affected area, from the local cache.
+ */
+export function IncidentNearby({ incident }: { incident: Incident }) {
+  const navigate = useNavigate();
+  const center =
+    incident.latitude != null && incident.longitude != null
+      ? { lat: incident.latitude, lng: incident.longitude }
+      : null;
+  const radiusKm = incident.affected_radius_km;
+
+  const resources = useQuery({
+    queryKey: ["geo", "resources-near", incident.id, center],
+    queryFn: () =>
+      nearest<Resource>("resource", { center: center! }, MAX_RESOURCES, {
+        maxDistanceKm: RESOURCE_RADIUS_KM,
+        eq: { status: "available" },
+      }),
+    enabled: center !== null,
+  });
+
+  const sos = useQuery({
+    queryKey: ["geo", "sos-within", incident.id, center, radiusKm],
+    queryFn: () =>
+      withinRadius<SOSBroadcast>("sos", { center: center! }, {
+        radiusKm: radiusKm!,
+        eq: { is_active: true },
+      }),
+    enabled: center !== null && !!radiusKm,
+  });
+
+  if (!center) {
+    return (
+      <Card>
+        <CardContent className="py-8">
+          <p className="text-sm text-muted-foreground text-center">
+            Set a location on this incident to see what is nearby.
+          </p>
+        </CardContent>
+      </Card>
+    );
+  }
+
+  return (
+    <div className="grid gap-4 lg:grid-cols-2">
+      <Card>
+        <CardHeader className="pb-2">
+          <CardTitle className="text-sm font-medium">
+            Available resources within {RESOURCE_RADIUS_KM} km
+          </CardTitle>
+        </CardHeader>
+        <CardContent>
+          <NearbyList
+            query={resources}
+            empty="No available resources nearby."
+            render={(hit: GeoHit<Resource>) => (
+              <div
+                key={hit.id}
+                className="flex items-center gap-3 p-2.5 rounded-lg border border-border hover:bg-muted/30 transition-colors cursor-pointer"
+                onClick={() => navigate(`/resources/${hit.id}`)}
+              >
+                <div className="flex-1 min-w-0">
+                  <p className="text-sm font-medium truncate">{hit.record.name}</p>
+                  <p className="text-[10px] text-muted-foreground mt-0.5">
+                    {formatResourceType(hit.record.type)}
+                  </p>
+                </div>
+                <ResourceStatusBadge status={hit.record.status} />
+                <span className="text-xs text-muted-foreground shrink-0 tabular-nums">
+                  {formatDistance(hit.distanceKm ?? 0)}
+                </span>
+              </div>
+            )}
+          />
+        </CardContent>
+      </Card>
+
+      <Card>
+        <CardHeader className="pb-2">
+          <CardTitle className="text-sm font-medium">
+            Active SOS in affected area
+            {radiusKm ? ` (${radiusKm} km)` : ""}
+          </CardTitle>
+        </CardHeader>
+        <CardContent>
+          {!radiusKm ? (
+            <p className="text-sm text-muted-foreground text-center py-8">
+              Set an affected radius to check for SOS broadcasts.
+            </p>
+          ) : (
+            <NearbyList
+              query={sos}
+              empty="No active SOS broadcasts in the affected area."
+              render={(hit: GeoHit<SOSBroadcast>) => (
+                <div
+                  key={hit.id}
+                  className="flex items-center gap-3 p-2.5 rounded-lg border border-border hover:bg-muted/30 transition-colors cursor-pointer"
+                  onClick={() => navigate(`/dashboard?sos=${hit.id}`)}
+                >
+                  <div className="flex-1 min-w-0">
+                    <p className="text-sm font-medium truncate">
+                      {hit.record.message || "SOS"}
+                    </p>
+                    {hit.record.location_name && (
+                      <p className="text-[10px] text-muted-foreground mt-0.5">
+                        {hit.record.location_name}
+                      </p>
+                    )}
+                  </div>
+                  <SeverityBadge severity={hit.record.severity} />
+                  <span className="text-xs text-muted-foreground shrink-0 tabular-nums">
+                    {formatDistance(hit.distanceKm ?? 0)}
+                  </span>
+                </div>
+              )}
+            />
+          )}
+        </CardContent>
+      </Card>
+    </div>
+  );
+}
+
+function NearbyList<T>({
+  query,
+  empty,
+  render,
+}: {
+  query: { data?: GeoHit<T>[]; isLoading: boolean; error: unknown };
+  empty: string;
+  render: (hit: GeoHit<T>) => React.ReactNode;
+}) {
+  if (query.isLoading) {
+    return (
+      <div className="flex justify-center py-8">
+        <Loader2 className="h-5 w-5 animate-spin text-muted-foreground" />
+      </div>
+    );
+  }
+  if (query.error) {
+    return (
+      <p className="text-sm text-destructive text-center py-8">
+        {String(query.error)}
+      </p>
+    );
+  }
+  if (!query.data?.length) {
+    return <p className="text-sm text-muted-foreground text-center py-8">{empty}</p>;
+  }
+  return <div className="space-y-2">{query.data.map(render)}</div>;
+}
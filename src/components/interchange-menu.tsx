@@ -0,0 +1,124 @@
+import { useState } from "react";
+import { Button } from "@/components/ui/button";
+import {
+    Dialog,
+    DialogContent,
+    DialogDescription,
+    DialogFooter,
+    DialogHeader,
+    DialogTitle,
+} from "@/components/ui/dialog";
+import {
+    DropdownMenu,
+    DropdownMenuContent,
+    DropdownMenuItem,
+    DropdownMenuLabel,
+    DropdownMenuSeparator,
+    DropdownMenuTrigger,
+} from "@/components/ui/dropdown-menu";
+import { ScrollArea } from "@/components/ui/scroll-area";
+import { AlertTriangle, FileDown, FileUp, Globe, Loader2 } from "lucide-react";
+import {
+    useExportFeatures,
+    usePickFeatures,
+    useSaveImportedFeatures,
+} from "@/hooks/use-interchange";
+import {
+    describeFeatureError,
+    INTERCHANGE_FORMATS,
+    type ImportReport,
+    type InterchangeKind,
+} from "@/lib/interchange";
+
+interface InterchangeMenuProps {
+    kind: InterchangeKind;
+    /** Export just these records instead of everything cached. */
+    ids?: string[];
+}
+
+/**
+ * Import GeoJSON/KML/GPX from partner agencies and export ours. Imports are
+ * previewed first so rejected features can be checked before saving.
+ */
+export function InterchangeMenu({ kind, ids }: InterchangeMenuProps) {
+    const [report, setReport] = useState<ImportReport | null>(null);
+    const pick = usePickFeatures(kind);
+    const save = useSaveImportedFeatures(kind);
+    const exportFeatures = useExportFeatures(kind);
+    const busy = pick.isPending || save.isPending || exportFeatures.isPending;
+
+    const handleImport = () =>
+        pick.mutate(undefined, {
+            onSuccess: (result) => {
+                if (result) setReport(result);
+            },
+        });
+
+    const handleSave = () => {
+        if (!report) return;
+        save.mutate(report, { onSuccess: () => setReport(null) });
+    };
+
+    return (
+        <>
+            <DropdownMenu>
+                <DropdownMenuTrigger asChild>
+                    <Button variant="outline" size="sm" className="gap-1.5" disabled={busy}>
+                        {busy ? <Loader2 className="h-4 w-4 animate-spin" /> : <Globe className="h-4 w-4" />}
+                        GIS
+                    </Button>
+                </DropdownMenuTrigger>
+                <DropdownMenuContent align="end">
+                    <DropdownMenuItem onSelect={handleImport}>
+                        <FileUp className="h-4 w-4" />
+                        Import GeoJSON / KML / GPX…
+                    </DropdownMenuItem>
+                    <DropdownMenuSeparator />
+                    <DropdownMenuLabel>Export</DropdownMenuLabel>
+                    {INTERCHANGE_FORMATS.map((f) => (
+                        <DropdownMenuItem
+                            key={f.value}
+                            onSelect={() => exportFeatures.mutate({ format: f.value, ids })}
+                        >
+                            <FileDown className="h-4 w-4" />
+                            {f.label}
+                        </DropdownMenuItem>
+                    ))}
+                </DropdownMenuContent>
+            </DropdownMenu>
+
+            <Dialog open={!!report} onOpenChange={(open) => !open && setReport(null)}>
+                <DialogContent className="sm:max-w-lg">
+                    <DialogHeader>
+                        <DialogTitle>Import {report?.fileName}</DialogTitle>
+                        <DialogDescription>
+                            {report?.records.length ?? 0} ready to import
+                            {report?.errors.length ? `, ${report.errors.length} skipped` : ""}.
+                        </DialogDescription>
+                    </DialogHeader>
+                    {!!report?.errors.length && (
+                        <ScrollArea className="max-h-60 rounded-md border">
+                            <ul className="p-3 space-y-1.5 text-sm">
+                                {report.errors.map((error) => (
+                                    <li key={error.index} className="flex gap-2 text-amber-600 dark:text-amber-400">
+                                        <AlertTriangle className="h-4 w-4 shrink-0 mt-0.5" />
+                                        {describeFeatureError(error)}
+                                    </li>
+                                ))}
+                            </ul>
+                        </ScrollArea>
+                    )}
+                    <DialogFooter>
+                        <Button variant="outline" onClick={() => setReport(null)}>
+                            Cancel
+                        </Button>
+                        <Button onClick={handleSave} disabled={!report?.records.length || save.isPending}>
+                            {save.isPending && <Loader2 className="h-4 w-4 animate-spin" />}
+                            Import {report?.records.length ?? 0}
+                        </Button>
+                    </DialogFooter>
+                </DialogContent>
+            </Dialog>
+        </>
+    );
+}
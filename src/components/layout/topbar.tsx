@@ -37,9 +37,12 @@ import {
     User,
     LogOut,
     Loader2,
+    ShieldOff,
 } from "lucide-react";
 import { useNavigate } from "react-router-dom";
 import { useCreateSOS } from "@/hooks/use-sos";
+import { useGpsFix } from "@/hooks/use-gps";
+import { useFormattedCoordinate } from "@/hooks/use-coords";
 import { toast } from "sonner";
 import type { SeverityLevel } from "@/types/enums";
 import { CommandPalette } from "@/components/command-palette";
@@ -47,7 +50,7 @@ import { OfflineIndicator } from "@/components/offline-indicator";
 import { formatRole } from "@/lib/utils";
 
 export function TopBar() {
-    const { profile, signOut } = useAuthStore();
+    const { profile, signOut, signOutEverywhere } = useAuthStore();
     const navigate = useNavigate();
     const [sosOpen, setSOSOpen] = useState(false);
     const [sosMessage, setSOSMessage] = useState("");
@@ -55,6 +58,8 @@ export function TopBar() {
     const [sosConfirmed, setSOSConfirmed] = useState(false);
     const [cmdOpen, setCmdOpen] = useState(false);
     const createSOS = useCreateSOS();
+    const gpsFix = useGpsFix();
+    const gpsCoordinates = useFormattedCoordinate(gpsFix?.point.lat, gpsFix?.point.lng);
 
     const initials = profile
         ? `${profile.first_name[0] || ""}${profile.last_name[0] || ""}`.toUpperCase()
@@ -65,6 +70,15 @@ export function TopBar() {
         navigate("/login");
     };
 
+    const handleSignOutEverywhere = async () => {
+        try {
+            await signOutEverywhere();
+            navigate("/login");
+        } catch (err) {
+            toast.error(`Could not clear sessions: ${err}`);
+        }
+    };
+
     const handleSOS = async () => {
         if (!sosMessage.trim()) {
             toast.error("Please describe the emergency");
@@ -177,6 +191,13 @@ export function TopBar() {
                             <LogOut className="h-4 w-4" />
                             Sign out
                         </DropdownMenuItem>
+                        <DropdownMenuItem
+                            onClick={handleSignOutEverywhere}
+                            className="gap-2 py-2 text-destructive focus:text-destructive focus:bg-destructive/10"
+                        >
+                            <ShieldOff className="h-4 w-4" />
+                            Sign out everywhere on this device
+                        </DropdownMenuItem>
                     </DropdownMenuContent>
                 </DropdownMenu>
             </header>
@@ -226,7 +247,9 @@ export function TopBar() {
                             />
                         </div>
                         <p className="text-xs text-muted-foreground">
-                            Your location will be shared automatically with the broadcast.
+                            {gpsCoordinates
+                                ? `Your GPS position (${gpsCoordinates}) will be shared with the broadcast.`
+                                : "No GPS fix: the broadcast will go out without a position."}
                         </p>
                         <label className="flex items-center gap-2 cursor-pointer select-none">
                             <input
@@ -0,0 +1,117 @@
+import { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { Lock, Loader2 } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import {
+    Card,
+    CardContent,
+    CardDescription,
+    CardHeader,
+    CardTitle,
+} from "@/components/ui/card";
+import {
+    getSecurityStatus,
+    setPassphrase as protectWithPassphrase,
+    unlock,
+    LOCK_EVENT,
+    type SecurityStatus,
+} from "@/lib/security";
+
+/**
+ * Covers the app while the local database is locked: it is protected by a
+ * passphrase that hasn't been entered yet, or this device has no keychain
+ * and a passphrase has to be chosen before anything is stored.
+ */
+export function LockScreen({ children }: { children: React.ReactNode }) {
+    const [status, setStatus] = useState<SecurityStatus | null>(null);
+    const [passphrase, setPassphrase] = useState("");
+    const [confirm, setConfirm] = useState("");
+    const [error, setError] = useState<string | null>(null);
+    const [busy, setBusy] = useState(false);
+
+    useEffect(() => {
+        getSecurityStatus().then(setStatus).catch(() => setStatus(null));
+        const unlisten = listen<SecurityStatus>(LOCK_EVENT, (event) => setStatus(event.payload));
+        return () => {
+            unlisten.then((fn) => fn());
+        };
+    }, []);
+
+    if (!status || status.unlocked) return <>{children}</>;
+
+    const choosing = !status.encrypted;
+
+    const handleSubmit = async (e: React.FormEvent) => {
+        e.preventDefault();
+        if (choosing && passphrase !== confirm) {
+            setError("Passphrases don't match");
+            return;
+        }
+        setBusy(true);
+        setError(null);
+        try {
+            if (choosing) await protectWithPassphrase(passphrase);
+            else await unlock(passphrase);
+            setPassphrase("");
+            setConfirm("");
+        } catch (err) {
+            setError(String(err));
+        } finally {
+            setBusy(false);
+        }
+    };
+
+    return (
+        <div className="flex h-screen items-center justify-center bg-background p-4">
+            <Card className="w-full max-w-sm">
+                <CardHeader>
+                    <CardTitle className="flex items-center gap-2">
+                        <Lock className="h-5 w-5" />
+                        {choosing ? "Protect Offline Data" : "Unlock DisasterConnect"}
+                    </CardTitle>
+                    <CardDescription>
+                        {choosing
+                            ? "This device has no system keychain to hold the key for offline data. Choose a passphrase to encrypt it with; you'll enter it each time the app starts."
+                            : status.mode === "passphrase"
+                              ? "Offline data on this device is encrypted. Enter your passphrase to open it."
+                              : "The key for offline data on this device is missing from the system keychain."}
+                    </CardDescription>
+                </CardHeader>
+                {(choosing || status.mode === "passphrase") && (
+                    <CardContent>
+                        <form onSubmit={handleSubmit} className="space-y-4">
+                            <div className="space-y-2">
+                                <Label htmlFor="passphrase">Passphrase</Label>
+                                <Input
+                                    id="passphrase"
+                                    type="password"
+                                    autoFocus
+                                    value={passphrase}
+                                    onChange={(e) => setPassphrase(e.target.value)}
+                                />
+                            </div>
+                            {choosing && (
+                                <div className="space-y-2">
+                                    <Label htmlFor="confirm-passphrase">Confirm passphrase</Label>
+                                    <Input
+                                        id="confirm-passphrase"
+                                        type="password"
+                                        value={confirm}
+                                        onChange={(e) => setConfirm(e.target.value)}
+                                    />
+                                </div>
+                            )}
+                            {error && <p className="text-sm text-destructive">{error}</p>}
+                            <Button type="submit" className="w-full" disabled={busy || !passphrase}>
+                                {busy && <Loader2 className="mr-2 h-4 w-4 animate-spin" />}
+                                {choosing ? "Encrypt and continue" : "Unlock"}
+                            </Button>
+                        </form>
+                    </CardContent>
+                )}
+            </Card>
+        </div>
+    );
+}
@@ -0,0 +1,47 @@
+import { TileLayer } from "react-leaflet";
+import { useActiveBasemap, useTileServer } from "@/hooks/use-basemaps";
+import { basemapUrl } from "@/lib/basemaps";
+import { TILE_ATTRIBUTION, TILE_URL } from "@/lib/tiles";
+
+/**
+ * Basemap for every Leaflet map: the active imported basemap if there is one,
+ * otherwise the configured tile server (OSM by default) through the offline
+ * tile cache.
+ */
+export function BaseTileLayer() {
+    const basemap = useActiveBasemap();
+    const { data: server } = useTileServer();
+
+    if (basemap) {
+        const [west, south, east, north] = basemap.bounds ?? [];
+        return (
+            <TileLayer
+                key={basemap.id}
+                url={basemapUrl(basemap.id)}
+                attribution={basemap.attribution ?? undefined}
+                minZoom={basemap.minZoom}
+                maxNativeZoom={basemap.maxZoom}
+                maxZoom={19}
+                bounds={
+                    basemap.bounds
+                        ? [
+                              [south, west],
+                              [north, east],
+                          ]
+                        : undefined
+                }
+            />
+        );
+    }
+
+    // The cache is cleared when the server changes; the query busts the webview's copy
+    const upstream = server?.url ?? "";
+    return (
+        <TileLayer
+            key={upstream}
+            attribution={upstream ? (server?.attribution ?? undefined) : TILE_ATTRIBUTION}
+            url={upstream ? `${TILE_URL}?v=${encodeURIComponent(upstream)}` : TILE_URL}
+            maxZoom={19}
+        />
+    );
+}
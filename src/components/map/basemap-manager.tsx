@@ -0,0 +1,142 @@
+import { useEffect, useState } from "react";
+import { open } from "@tauri-apps/plugin-dialog";
+import { Button } from "@/components/ui/button";
+import { Badge } from "@/components/ui/badge";
+import { Input } from "@/components/ui/input";
+import { Check, Loader2, Map as MapIcon, Trash2, Upload } from "lucide-react";
+import {
+    useActivateBasemap,
+    useBasemaps,
+    useImportBasemap,
+    useRemoveBasemap,
+    useSetTileServer,
+    useTileServer,
+} from "@/hooks/use-basemaps";
+
+function formatSize(bytes: number) {
+    if (bytes >= 1024 ** 3) return `${(bytes / 1024 ** 3).toFixed(1)} GB`;
+    return `${(bytes / 1024 ** 2).toFixed(1)} MB`;
+}
+
+/** Import, switch between and remove offline MBTiles/PMTiles basemaps. */
+export function BasemapManager() {
+    const { data: basemaps = [], isLoading } = useBasemaps();
+    const importBasemap = useImportBasemap();
+    const activate = useActivateBasemap();
+    const remove = useRemoveBasemap();
+    const active = basemaps.find((b) => b.active);
+    const { data: server } = useTileServer();
+    const setServer = useSetTileServer();
+    const [serverUrl, setServerUrl] = useState("");
+    const [serverAttribution, setServerAttribution] = useState("");
+
+    useEffect(() => {
+        setServerUrl(server?.url ?? "");
+        setServerAttribution(server?.attribution ?? "");
+    }, [server]);
+
+    const serverDirty =
+        serverUrl.trim() !== (server?.url ?? "") || serverAttribution.trim() !== (server?.attribution ?? "");
+
+    const handleImport = async () => {
+        const path = await open({
+            multiple: false,
+            directory: false,
+            filters: [{ name: "Map tiles", extensions: ["mbtiles", "pmtiles"] }],
+        });
+        if (typeof path === "string") importBasemap.mutate(path);
+    };
+
+    return (
+        <div className="space-y-3">
+            <div className="flex items-center justify-between">
+                <p className="text-sm font-medium">Basemaps</p>
+                <Button size="sm" variant="outline" className="gap-1.5" onClick={handleImport} disabled={importBasemap.isPending}>
+                    {importBasemap.isPending ? <Loader2 className="h-3.5 w-3.5 animate-spin" /> : <Upload className="h-3.5 w-3.5" />}
+                    Import MBTiles / PMTiles
+                </Button>
+            </div>
+
+            <div className="space-y-3 rounded-lg border p-3">
+                <div className="flex items-center justify-between">
+                    <div>
+                        <p className="text-sm font-medium">{server?.url ? "Tile server" : "OpenStreetMap"}</p>
+                        <p className="text-xs text-muted-foreground">
+                            {server?.url
+                                ? "Online, with areas you viewed or saved cached offline"
+                                : "Online, with areas you viewed cached offline. Set a tile server to save areas offline."}
+                        </p>
+                    </div>
+                    {active ? (
+                        <Button size="sm" variant="ghost" onClick={() => activate.mutate(null)} disabled={activate.isPending}>
+                            Use
+                        </Button>
+                    ) : (
+                        <Badge variant="secondary" className="gap-1"><Check className="h-3 w-3" /> In use</Badge>
+                    )}
+                </div>
+                <div className="grid gap-2 sm:grid-cols-[2fr_1fr_auto]">
+                    <Input
+                        value={serverUrl}
+                        onChange={(e) => setServerUrl(e.target.value)}
+                        placeholder="https://tiles.example.org/{z}/{x}/{y}.png"
+                    />
+                    <Input
+                        value={serverAttribution}
+                        onChange={(e) => setServerAttribution(e.target.value)}
+                        placeholder="Attribution"
+                    />
+                    <Button
+                        size="sm"
+                        variant="outline"
+                        className="h-9"
+                        disabled={!serverDirty || setServer.isPending}
+                        onClick={() =>
+                            setServer.mutate({
+                                url: serverUrl.trim() || null,
+                                attribution: serverAttribution.trim() || null,
+                            })
+                        }
+                    >
+                        {setServer.isPending && <Loader2 className="mr-1.5 h-3.5 w-3.5 animate-spin" />}
+                        Save
+                    </Button>
+                </div>
+            </div>
+
+            {isLoading && <Loader2 className="h-4 w-4 animate-spin text-muted-foreground" />}
+
+            {basemaps.map((basemap) => (
+                <div key={basemap.id} className="flex items-center justify-between gap-3 rounded-lg border p-3">
+                    <div className="flex min-w-0 items-center gap-3">
+                        <MapIcon className="h-4 w-4 shrink-0 text-muted-foreground" />
+                        <div className="min-w-0">
+                            <p className="truncate text-sm font-medium">{basemap.name}</p>
+                            <p className="text-xs text-muted-foreground">
+                                {basemap.kind.toUpperCase()} · {basemap.format.toUpperCase()} · zoom {basemap.minZoom}–{basemap.maxZoom} · {formatSize(basemap.sizeBytes)}
+                            </p>
+                        </div>
+                    </div>
+                    <div className="flex shrink-0 items-center gap-1">
+                        {basemap.active ? (
+                            <Badge variant="secondary" className="gap-1"><Check className="h-3 w-3" /> In use</Badge>
+                        ) : (
+                            <Button size="sm" variant="ghost" onClick={() => activate.mutate(basemap.id)} disabled={activate.isPending}>
+                                Use
+                            </Button>
+                        )}
+                        <Button
+                            size="icon"
+                            variant="ghost"
+                            className="h-8 w-8 text-muted-foreground hover:text-destructive"
+                            onClick={() => remove.mutate(basemap.id)}
+                            disabled={remove.isPending}
+                        >
+                            <Trash2 className="h-4 w-4" />
+                        </Button>
+                    </div>
+                </div>
+            ))}
+        </div>
+    );
+}
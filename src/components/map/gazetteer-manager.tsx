@@ -0,0 +1,98 @@
+import { useEffect, useState } from "react";
+import { open } from "@tauri-apps/plugin-dialog";
+import { Button } from "@/components/ui/button";
+import { Loader2, MapPinned, Trash2, Upload } from "lucide-react";
+import { toast } from "sonner";
+import { format } from "date-fns";
+import {
+    clearGazetteer,
+    getGazetteerStatus,
+    importGazetteer,
+    type GazetteerStatus,
+} from "@/lib/geocode";
+
+/** Import the place gazetteer used to name locations offline. */
+export function GazetteerManager() {
+    const [status, setStatus] = useState<GazetteerStatus | null>(null);
+    const [importing, setImporting] = useState(false);
+
+    useEffect(() => {
+        getGazetteerStatus()
+            .then(setStatus)
+            .catch(() => setStatus(null));
+    }, []);
+
+    const handleImport = async () => {
+        const path = await open({
+            multiple: false,
+            directory: false,
+            filters: [
+                { name: "GeoNames dump or OpenStreetMap extract", extensions: ["txt", "tsv", "pbf"] },
+            ],
+        });
+        if (typeof path !== "string") return;
+        setImporting(true);
+        try {
+            const result = await importGazetteer(path);
+            setStatus(result);
+            toast.success(`Gazetteer ready: ${result.places.toLocaleString()} places`);
+        } catch (err) {
+            toast.error(`Import failed: ${err}`);
+        } finally {
+            setImporting(false);
+        }
+    };
+
+    const handleClear = async () => {
+        try {
+            await clearGazetteer();
+            setStatus(null);
+        } catch (err) {
+            toast.error(`${err}`);
+        }
+    };
+
+    return (
+        <div className="space-y-3">
+            <div className="flex items-center justify-between">
+                <div>
+                    <p className="text-sm font-medium">Place names</p>
+                    <p className="text-xs text-muted-foreground">Names locations and searches places, offline</p>
+                </div>
+                <Button size="sm" variant="outline" className="gap-1.5" onClick={handleImport} disabled={importing}>
+                    {importing ? <Loader2 className="h-3.5 w-3.5 animate-spin" /> : <Upload className="h-3.5 w-3.5" />}
+                    {importing ? "Importing…" : "Import gazetteer"}
+                </Button>
+            </div>
+            {status ? (
+                <div className="flex items-center justify-between gap-3 rounded-lg border p-3">
+                    <div className="flex min-w-0 items-center gap-3">
+                        <MapPinned className="h-4 w-4 shrink-0 text-muted-foreground" />
+                        <div className="min-w-0">
+                            <p className="truncate text-sm font-medium">{status.source}</p>
+                            <p className="text-xs text-muted-foreground">
+                                {status.places.toLocaleString()} places ·{" "}
+                                {status.format === "osm" ? "OpenStreetMap" : "GeoNames"} · imported{" "}
+                                {format(new Date(status.importedAt), "PP")}
+                            </p>
+                        </div>
+                    </div>
+                    <Button
+                        size="icon"
+                        variant="ghost"
+                        className="h-8 w-8 text-muted-foreground hover:text-destructive"
+                        onClick={handleClear}
+                        disabled={importing}
+                    >
+                        <Trash2 className="h-4 w-4" />
+                    </Button>
+                </div>
+            ) : (
+                <p className="text-xs text-muted-foreground">
+                    No gazetteer imported. Import a GeoNames dump (e.g. cities500.txt, with admin1CodesASCII.txt
+                    beside it) or an .osm.pbf extract for your region.
+                </p>
+            )}
+        </div>
+    );
+}
@@ -0,0 +1,89 @@
+import { useEffect, useState } from "react";
+import { Marker, Popup } from "react-leaflet";
+import L from "leaflet";
+import { formatDistanceToNow } from "date-fns";
+import { History } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { useLivePositions } from "@/hooks/use-avl";
+import { unitKey, type AvlPosition } from "@/lib/avl";
+
+/** A unit not heard from in this long is drawn greyed out. */
+const STALE_AFTER_MS = 10 * 60_000;
+
+const UNIT_COLORS = { resource: "#2563eb", team: "#7c3aed" } as const;
+
+/** A dot, with an arrow pointing along the course when the unit is moving. */
+function unitIcon(position: AvlPosition, stale: boolean): L.DivIcon {
+    const color = stale ? "#9ca3af" : UNIT_COLORS[position.subjectKind];
+    const moving = position.courseDeg != null && (position.speedKmh ?? 0) >= 2;
+    const arrow = moving
+        ? `<svg width="22" height="22" viewBox="0 0 22 22" style="position:absolute;inset:0;transform:rotate(${position.courseDeg}deg)">
+             <path d="M11 0 L15 7 L7 7 Z" fill="${color}" />
+           </svg>`
+        : "";
+    return L.divIcon({
+        className: "",
+        iconSize: [22, 22],
+        iconAnchor: [11, 11],
+        html: `<div style="position:relative;width:22px;height:22px">${arrow}
+                 <div style="position:absolute;left:5px;top:5px;width:12px;height:12px;border-radius:9999px;background:${color};border:2px solid white;box-shadow:0 0 2px rgba(0,0,0,.5)"></div>
+               </div>`,
+    });
+}
+
+/** Where tracked vehicles and teams are now, moved as reports arrive. */
+export function LiveUnitsLayer({ onPlayback }: { onPlayback: (key: string) => void }) {
+    const { data: positions } = useLivePositions();
+    // Re-render once a minute so units grey out as they go quiet
+    const [now, setNow] = useState(() => Date.now());
+    useEffect(() => {
+        const timer = setInterval(() => setNow(Date.now()), 60_000);
+        return () => clearInterval(timer);
+    }, []);
+
+    return (
+        <>
+            {(positions ?? []).map((position) => {
+                const key = unitKey(position.subjectKind, position.subjectId);
+                const stale = now - Date.parse(position.reportedAt) > STALE_AFTER_MS;
+                return (
+                    <Marker
+                        key={key}
+                        position={[position.point.lat, position.point.lng]}
+                        icon={unitIcon(position, stale)}
+                        zIndexOffset={1000}
+                    >
+                        <Popup>
+                            <div className="space-y-1 text-xs">
+                                <p className="text-sm font-semibold">
+                                    {position.label ?? position.deviceId ?? position.subjectId}
+                                </p>
+                                <p className="text-muted-foreground">
+                                    {position.subjectKind === "team" ? "Team" : "Resource"}
+                                    {position.deviceId && ` · ${position.deviceId}`}
+                                </p>
+                                <p>
+                                    {position.speedKmh != null && `${Math.round(position.speedKmh)} km/h`}
+                                    {position.speedKmh != null && position.courseDeg != null && " · "}
+                                    {position.courseDeg != null && `${Math.round(position.courseDeg)}°`}
+                                </p>
+                                <p className="text-muted-foreground">
+                                    Reported {formatDistanceToNow(new Date(position.reportedAt), { addSuffix: true })}
+                                </p>
+                                <Button
+                                    size="sm"
+                                    variant="outline"
+                                    className="mt-1 h-7 gap-1.5 text-xs"
+                                    onClick={() => onPlayback(key)}
+                                >
+                                    <History className="h-3.5 w-3.5" />
+                                    Play back track
+                                </Button>
+                            </div>
+                        </Popup>
+                    </Marker>
+                );
+            })}
+        </>
+    );
+}
@@ -1,10 +1,11 @@
-import { useState, useCallback } from "react";
+import { useState, useCallback, useEffect } from "react";
 import {
   MapContainer,
-  TileLayer,
   Marker,
+  useMap,
   useMapEvents,
 } from "react-leaflet";
+import { BaseTileLayer } from "@/components/map/base-tile-layer";
 import L from "leaflet";
 import "leaflet/dist/leaflet.css";
 import {
@@ -16,7 +17,15 @@ import {
 } from "@/components/ui/dialog";
 import { Button } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
-import { MapPin } from "lucide-react";
+import { Crosshair, MapPin } from "lucide-react";
+import { useFormattedCoordinate } from "@/hooks/use-coords";
+import { notationLabel, parseCoordinate } from "@/lib/coords";
+import {
+  placeLabel,
+  reverseGeocode,
+  searchPlaces,
+  type Place,
+} from "@/lib/geocode";
 
 // Fix default marker icon for Leaflet in bundlers
 const defaultIcon = L.icon({
@@ -44,6 +53,14 @@ function ClickHandler({
   return null;
 }
 
+function FlyTo({ position }: { position: [number, number] | null }) {
+  const map = useMap();
+  useEffect(() => {
+    if (position) map.flyTo(position, Math.max(map.getZoom(), 13));
+  }, [map, position]);
+  return null;
+}
+
 // ─── Location Picker Dialog ──────────────────────────────────────
 
 export interface LocationPickerValue {
@@ -73,10 +90,71 @@ export function LocationPicker({
   const [locationName, setLocationName] = useState(
     value?.location_name ?? ""
   );
+  // Typed names are kept; ones filled in from the gazetteer follow clicks
+  const [nameIsAuto, setNameIsAuto] = useState(!value?.location_name);
+  const [suggestions, setSuggestions] = useState<Place[]>([]);
+  const [flyTarget, setFlyTarget] = useState<[number, number] | null>(null);
+  const [coordinateText, setCoordinateText] = useState("");
+  const [coordinateHint, setCoordinateHint] = useState<string | null>(null);
+  const coordinates = useFormattedCoordinate(position?.[0], position?.[1]);
+
+  useEffect(() => {
+    const text = locationName.trim();
+    if (nameIsAuto || text.length < 2) {
+      setSuggestions([]);
+      return;
+    }
+    let cancelled = false;
+    const timer = setTimeout(() => {
+      const near = position ? { lat: position[0], lng: position[1] } : undefined;
+      searchPlaces(text, near, 6)
+        .then((places) => !cancelled && setSuggestions(places))
+        .catch(() => !cancelled && setSuggestions([]));
+    }, 250);
+    return () => {
+      cancelled = true;
+      clearTimeout(timer);
+    };
+    // Re-searching when the marker moves would reopen a dismissed list
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, [locationName, nameIsAuto]);
+
+  const handleLocationSelect = useCallback(
+    (lat: number, lng: number) => {
+      setPosition([lat, lng]);
+      if (!nameIsAuto && locationName.trim()) return;
+      reverseGeocode({ lat, lng })
+        .then((hit) => {
+          setLocationName(hit?.label ?? "");
+          setNameIsAuto(true);
+        })
+        .catch(() => {});
+    },
+    [nameIsAuto, locationName]
+  );
 
-  const handleLocationSelect = useCallback((lat: number, lng: number) => {
-    setPosition([lat, lng]);
-  }, []);
+  const handleCoordinateEntry = async () => {
+    if (!coordinateText.trim()) return;
+    const near = position ? { lat: position[0], lng: position[1] } : undefined;
+    try {
+      const parsed = await parseCoordinate(coordinateText, near);
+      const target: [number, number] = [parsed.point.lat, parsed.point.lng];
+      handleLocationSelect(...target);
+      setFlyTarget(target);
+      setCoordinateHint(`Read as ${notationLabel(parsed.notation)}`);
+    } catch (err) {
+      setCoordinateHint(`${err}`);
+    }
+  };
+
+  const handlePlaceSelect = (place: Place) => {
+    const target: [number, number] = [place.point.lat, place.point.lng];
+    setPosition(target);
+    setFlyTarget(target);
+    setLocationName(placeLabel(place));
+    setNameIsAuto(true);
+    setSuggestions([]);
+  };
 
   const handleConfirm = () => {
     if (!position) return;
@@ -103,14 +181,71 @@ export function LocationPicker({
           </DialogTitle>
         </DialogHeader>
 
-        {/* Location name input */}
-        <div>
+        {/* Location name input, doubling as place search */}
+        <div className="relative">
           <Input
             data-selectable
-            placeholder="Location name (e.g. City Hall, Sector 5)"
+            placeholder="Location name or place to search (e.g. City Hall, Sector 5)"
             value={locationName}
-            onChange={(e) => setLocationName(e.target.value)}
+            onChange={(e) => {
+              setLocationName(e.target.value);
+              setNameIsAuto(false);
+            }}
+            onKeyDown={(e) => e.key === "Escape" && setSuggestions([])}
           />
+          {suggestions.length > 0 && (
+            <ul className="absolute z-[1000] mt-1 w-full rounded-md border bg-popover p-1 shadow-md">
+              {suggestions.map((place) => (
+                <li key={place.id}>
+                  <button
+                    type="button"
+                    className="flex w-full items-center justify-between gap-2 rounded-sm px-2 py-1.5 text-left text-sm hover:bg-accent"
+                    onClick={() => handlePlaceSelect(place)}
+                  >
+                    <span className="truncate">{placeLabel(place)}</span>
+                    <span className="shrink-0 text-xs capitalize text-muted-foreground">
+                      {place.kind.replace(/_/g, " ")}
+                    </span>
+                  </button>
+                </li>
+              ))}
+            </ul>
+          )}
+        </div>
+
+        {/* Coordinates in any notation */}
+        <div className="space-y-1">
+          <div className="flex gap-2">
+            <Input
+              data-selectable
+              className="font-mono"
+              placeholder="Coordinates: decimal, DMS, UTM, MGRS/USNG or plus code"
+              value={coordinateText}
+              onChange={(e) => {
+                setCoordinateText(e.target.value);
+                setCoordinateHint(null);
+              }}
+              onKeyDown={(e) => {
+                if (e.key === "Enter") {
+                  e.preventDefault();
+                  handleCoordinateEntry();
+                }
+              }}
+            />
+            <Button
+              type="button"
+              variant="outline"
+              className="gap-1.5"
+              onClick={handleCoordinateEntry}
+              disabled={!coordinateText.trim()}
+            >
+              <Crosshair className="h-4 w-4" />
+              Go
+            </Button>
+          </div>
+          {coordinateHint && (
+            <p className="text-xs text-muted-foreground">{coordinateHint}</p>
+          )}
         </div>
 
         {/* Map */}
@@ -121,11 +256,9 @@ export function LocationPicker({
             style={{ height: "100%", width: "100%" }}
             scrollWheelZoom
           >
-            <TileLayer
-              attribution='&copy; <a href="https://www.openstreetmap.org/copyright">OpenStreetMap</a>'
-              url="https://{s}.tile.openstreetmap.org/{z}/{x}/{y}.png"
-            />
+            <BaseTileLayer />
             <ClickHandler onLocationSelect={handleLocationSelect} />
+            <FlyTo position={flyTarget} />
             {position && (
               <Marker position={position} icon={defaultIcon} />
             )}
@@ -133,10 +266,9 @@ export function LocationPicker({
         </div>
 
         {/* Coordinates display */}
-        {position && (
-          <div className="flex items-center gap-4 text-sm text-muted-foreground font-mono">
-            <span>Lat: {position[0].toFixed(6)}</span>
-            <span>Lng: {position[1].toFixed(6)}</span>
+        {coordinates && (
+          <div className="text-sm text-muted-foreground font-mono">
+            {coordinates}
           </div>
         )}
         {!position && (
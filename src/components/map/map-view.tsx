@@ -1,11 +1,11 @@
 import { useEffect, useRef } from "react";
 import {
   MapContainer,
-  TileLayer,
   CircleMarker,
   Popup,
   useMap,
 } from "react-leaflet";
+import { BaseTileLayer } from "@/components/map/base-tile-layer";
 import L from "leaflet";
 import "leaflet/dist/leaflet.css";
 import { useMapStore } from "@/stores/map-store";
@@ -203,10 +203,7 @@ export function DashboardMap({
       scrollWheelZoom
       zoomControl
     >
-      <TileLayer
-        attribution='&copy; <a href="https://www.openstreetmap.org/copyright">OpenStreetMap</a>'
-        url="https://{s}.tile.openstreetmap.org/{z}/{x}/{y}.png"
-      />
+      <BaseTileLayer />
       <MapStateSync />
       <FitBoundsOnData incidents={incidents} resources={resources} />
       {layers.incidents && <IncidentMarkers incidents={incidents} />}
@@ -0,0 +1,80 @@
+import { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import type L from "leaflet";
+import { Button } from "@/components/ui/button";
+import { Download, Loader2, X } from "lucide-react";
+import { toast } from "sonner";
+import {
+    cancelSeed,
+    estimateSeed,
+    seedTiles,
+    SEED_PROGRESS_EVENT,
+    type SeedProgress,
+} from "@/lib/tiles";
+
+/** How many zoom levels below the current view to download. */
+const EXTRA_ZOOM = 3;
+const MAX_SEED_ZOOM = 17;
+
+/** Downloads the tiles for the visible area so it can be viewed offline. */
+export function OfflineAreaButton({ map }: { map: L.Map | null }) {
+    const [progress, setProgress] = useState<SeedProgress | null>(null);
+    const seeding = progress !== null;
+
+    useEffect(() => {
+        const unlisten = listen<SeedProgress>(SEED_PROGRESS_EVENT, (event) => setProgress(event.payload));
+        return () => {
+            unlisten.then((fn) => fn());
+        };
+    }, []);
+
+    const handleSeed = async () => {
+        if (!map) return;
+        const b = map.getBounds();
+        const area = { west: b.getWest(), south: b.getSouth(), east: b.getEast(), north: b.getNorth() };
+        const minZoom = Math.max(0, Math.floor(map.getZoom()));
+        const maxZoom = Math.min(MAX_SEED_ZOOM, minZoom + EXTRA_ZOOM);
+
+        try {
+            const total = await estimateSeed(area, minZoom, maxZoom);
+            setProgress({ total, done: 0, skipped: 0, failed: 0, bytes: 0, cancelled: false });
+            const result = await seedTiles(area, minZoom, maxZoom);
+            if (result.cancelled) {
+                toast.info("Offline download cancelled");
+            } else {
+                const mb = (result.bytes / (1024 * 1024)).toFixed(1);
+                toast.success(`Saved ${result.done} tiles (${mb} MB) for offline use`, {
+                    description: result.failed > 0 ? `${result.failed} tiles could not be downloaded` : undefined,
+                });
+            }
+        } catch (err) {
+            toast.error(`Offline download failed: ${err}`);
+        } finally {
+            setProgress(null);
+        }
+    };
+
+    if (seeding) {
+        const pct = progress.total > 0 ? Math.round((progress.done / progress.total) * 100) : 0;
+        return (
+            <Button variant="outline" size="sm" className="w-full justify-start gap-1.5" onClick={() => cancelSeed()}>
+                <Loader2 className="h-3.5 w-3.5 animate-spin" />
+                {pct}%
+                <X className="ml-auto h-3.5 w-3.5" />
+            </Button>
+        );
+    }
+
+    return (
+        <Button
+            variant="outline"
+            size="sm"
+            className="w-full justify-start gap-1.5"
+            onClick={handleSeed}
+            disabled={!map}
+        >
+            <Download className="h-3.5 w-3.5" />
+            Save offline
+        </Button>
+    );
+}
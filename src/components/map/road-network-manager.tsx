@@ -0,0 +1,93 @@
+import { useEffect, useState } from "react";
+import { open } from "@tauri-apps/plugin-dialog";
+import { Button } from "@/components/ui/button";
+import { Loader2, Route, Trash2, Upload } from "lucide-react";
+import { toast } from "sonner";
+import { format } from "date-fns";
+import {
+    clearRoadNetwork,
+    getRoutingStatus,
+    importRoadNetwork,
+    type RoutingStatus,
+} from "@/lib/routing";
+
+/** Import the OpenStreetMap extract used for offline road routing. */
+export function RoadNetworkManager() {
+    const [status, setStatus] = useState<RoutingStatus | null>(null);
+    const [importing, setImporting] = useState(false);
+
+    useEffect(() => {
+        getRoutingStatus()
+            .then(setStatus)
+            .catch(() => setStatus(null));
+    }, []);
+
+    const handleImport = async () => {
+        const path = await open({
+            multiple: false,
+            directory: false,
+            filters: [{ name: "OpenStreetMap extract", extensions: ["pbf"] }],
+        });
+        if (typeof path !== "string") return;
+        setImporting(true);
+        try {
+            const result = await importRoadNetwork(path);
+            setStatus(result);
+            toast.success(`Road network ready: ${result.nodes.toLocaleString()} junctions and bends`);
+        } catch (err) {
+            toast.error(`Import failed: ${err}`);
+        } finally {
+            setImporting(false);
+        }
+    };
+
+    const handleClear = async () => {
+        try {
+            await clearRoadNetwork();
+            setStatus(null);
+        } catch (err) {
+            toast.error(`${err}`);
+        }
+    };
+
+    return (
+        <div className="space-y-3">
+            <div className="flex items-center justify-between">
+                <div>
+                    <p className="text-sm font-medium">Road network</p>
+                    <p className="text-xs text-muted-foreground">Routes evacuation paths along real roads, offline</p>
+                </div>
+                <Button size="sm" variant="outline" className="gap-1.5" onClick={handleImport} disabled={importing}>
+                    {importing ? <Loader2 className="h-3.5 w-3.5 animate-spin" /> : <Upload className="h-3.5 w-3.5" />}
+                    {importing ? "Building…" : "Import .osm.pbf"}
+                </Button>
+            </div>
+            {status ? (
+                <div className="flex items-center justify-between gap-3 rounded-lg border p-3">
+                    <div className="flex min-w-0 items-center gap-3">
+                        <Route className="h-4 w-4 shrink-0 text-muted-foreground" />
+                        <div className="min-w-0">
+                            <p className="truncate text-sm font-medium">{status.source}</p>
+                            <p className="text-xs text-muted-foreground">
+                                {status.edges.toLocaleString()} road segments · imported {format(new Date(status.importedAt), "PP")}
+                            </p>
+                        </div>
+                    </div>
+                    <Button
+                        size="icon"
+                        variant="ghost"
+                        className="h-8 w-8 text-muted-foreground hover:text-destructive"
+                        onClick={handleClear}
+                        disabled={importing}
+                    >
+                        <Trash2 className="h-4 w-4" />
+                    </Button>
+                </div>
+            ) : (
+                <p className="text-xs text-muted-foreground">
+                    No road network imported. Download an extract for your region (e.g. from Geofabrik) and import it here.
+                </p>
+            )}
+        </div>
+    );
+}
@@ -0,0 +1,237 @@
+import { useEffect, useMemo, useRef, useState } from "react";
+import { CircleMarker, Polyline, useMap } from "react-leaflet";
+import L from "leaflet";
+import { format } from "date-fns";
+import { Loader2, Pause, Play, SkipBack, X } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Card } from "@/components/ui/card";
+import {
+    Select,
+    SelectContent,
+    SelectItem,
+    SelectTrigger,
+    SelectValue,
+} from "@/components/ui/select";
+import { useTrack, useTrackedUnits } from "@/hooks/use-avl";
+import { parseUnitKey, positionAt, unitKey, type AvlPosition, type Track } from "@/lib/avl";
+
+const SPEEDS = [1, 10, 60, 300, 1800];
+const PLAYED_COLOR = "#2563eb";
+
+export interface Playback {
+    track: Track | undefined;
+    isLoading: boolean;
+    /** Playhead, in ms since the epoch. */
+    time: number | null;
+    setTime: (time: number) => void;
+    playing: boolean;
+    setPlaying: (playing: boolean) => void;
+    speed: number;
+    setSpeed: (speed: number) => void;
+    start: number | null;
+    end: number | null;
+}
+
+/** Playhead state for one unit's track, `key` being `resource:<id>` or `team:<id>`. */
+export function useTrackPlayback(key: string | null): Playback {
+    const query = useMemo(() => (key ? parseUnitKey(key) : null), [key]);
+    const { data: track, isLoading } = useTrack(query);
+    const [time, setTime] = useState<number | null>(null);
+    const [playing, setPlaying] = useState(false);
+    const [speed, setSpeed] = useState(60);
+
+    const positions = track?.positions;
+    const start = positions?.length ? Date.parse(positions[0].reportedAt) : null;
+    const end = positions?.length ? Date.parse(positions[positions.length - 1].reportedAt) : null;
+
+    // A new track starts from its beginning
+    useEffect(() => {
+        setTime(start);
+        setPlaying(false);
+    }, [key, start]);
+
+    // Advance the playhead in real time times the speed
+    useEffect(() => {
+        if (!playing || end == null) return;
+        let last = performance.now();
+        let frame = requestAnimationFrame(function step(now) {
+            const elapsed = (now - last) * speed;
+            last = now;
+            setTime((t) => Math.min((t ?? 0) + elapsed, end));
+            frame = requestAnimationFrame(step);
+        });
+        return () => cancelAnimationFrame(frame);
+    }, [playing, speed, end]);
+
+    useEffect(() => {
+        if (time != null && end != null && time >= end) setPlaying(false);
+    }, [time, end]);
+
+    return { track, isLoading, time, setTime, playing, setPlaying, speed, setSpeed, start, end };
+}
+
+/** The track, the part already played, and the unit at the playhead. */
+export function TrackPlaybackLayer({ playback }: { playback: Playback }) {
+    const map = useMap();
+    const { track, time } = playback;
+    const positions = track?.positions ?? [];
+    const fitted = useRef<string | null>(null);
+
+    const path = useMemo(
+        () => positions.map((p) => [p.point.lat, p.point.lng] as [number, number]),
+        [positions],
+    );
+
+    // Frame each track once, when it first loads
+    useEffect(() => {
+        if (!track || path.length === 0) return;
+        const key = unitKey(track.subjectKind, track.subjectId);
+        if (fitted.current === key) return;
+        fitted.current = key;
+        map.fitBounds(L.latLngBounds(path), { padding: [60, 60], maxZoom: 16 });
+    }, [map, track, path]);
+
+    if (path.length === 0 || time == null) return null;
+    const head = positionAt(positions, time);
+    const playedCount = positions.filter((p) => Date.parse(p.reportedAt) <= time).length;
+    const played: [number, number][] = path.slice(0, playedCount);
+    if (head) played.push([head.lat, head.lng]);
+
+    return (
+        <>
+            <Polyline positions={path} pathOptions={{ color: "#6b7280", weight: 3, opacity: 0.6, dashArray: "4 6" }} />
+            <Polyline positions={played} pathOptions={{ color: PLAYED_COLOR, weight: 4 }} />
+            {head && (
+                <CircleMarker
+                    center={[head.lat, head.lng]}
+                    radius={8}
+                    pathOptions={{ color: "#fff", weight: 2, fillColor: PLAYED_COLOR, fillOpacity: 1 }}
+                />
+            )}
+        </>
+    );
+}
+
+/** Pick a unit and scrub or play back where it went. */
+export function TrackPlaybackPanel({
+    unit,
+    onUnitChange,
+    onClose,
+    playback,
+}: {
+    unit: string | null;
+    onUnitChange: (key: string) => void;
+    onClose: () => void;
+    playback: Playback;
+}) {
+    const { data: tracked } = useTrackedUnits();
+    const { track, isLoading, time, setTime, playing, setPlaying, speed, setSpeed, start, end } = playback;
+    const positions = track?.positions ?? [];
+
+    // The breadcrumb at or just before the playhead, for its speed
+    const current = useMemo(() => {
+        if (time == null) return null;
+        let latest: AvlPosition | null = null;
+        for (const p of positions) {
+            if (Date.parse(p.reportedAt) > time) break;
+            latest = p;
+        }
+        return latest;
+    }, [positions, time]);
+
+    const handlePlay = () => {
+        if (time != null && end != null && time >= end && start != null) setTime(start);
+        setPlaying(!playing);
+    };
+
+    return (
+        <Card className="absolute bottom-14 left-1/2 z-10 w-[min(36rem,calc(100%-1.5rem))] -translate-x-1/2 space-y-2 p-3 shadow-lg">
+            <div className="flex items-center gap-2">
+                <Select value={unit ?? ""} onValueChange={onUnitChange}>
+                    <SelectTrigger className="h-8 flex-1">
+                        <SelectValue placeholder={tracked?.length ? "Choose a unit to play back" : "No tracks recorded"} />
+                    </SelectTrigger>
+                    <SelectContent>
+                        {(tracked ?? []).map((t) => {
+                            const key = unitKey(t.subjectKind, t.subjectId);
+                            return (
+                                <SelectItem key={key} value={key}>
+                                    {t.label ?? t.subjectId}
+                                    <span className="ml-2 text-xs text-muted-foreground">
+                                        {format(new Date(t.lastAt), "d MMM HH:mm")} · {t.points} points
+                                    </span>
+                                </SelectItem>
+                            );
+                        })}
+                    </SelectContent>
+                </Select>
+                <Button size="icon" variant="ghost" className="h-8 w-8" onClick={onClose}>
+                    <X className="h-4 w-4" />
+                </Button>
+            </div>
+
+            {isLoading && unit && (
+                <div className="flex items-center gap-2 text-xs text-muted-foreground">
+                    <Loader2 className="h-3.5 w-3.5 animate-spin" />
+                    Loading track…
+                </div>
+            )}
+
+            {track && positions.length > 0 && start != null && end != null && time != null && (
+                <>
+                    <div className="flex items-center gap-2">
+                        <Button
+                            size="icon"
+                            variant="ghost"
+                            className="h-8 w-8"
+                            onClick={() => {
+                                setPlaying(false);
+                                setTime(start);
+                            }}
+                        >
+                            <SkipBack className="h-4 w-4" />
+                        </Button>
+                        <Button size="icon" className="h-8 w-8" onClick={handlePlay}>
+                            {playing ? <Pause className="h-4 w-4" /> : <Play className="h-4 w-4" />}
+                        </Button>
+                        <input
+                            type="range"
+                            className="flex-1 accent-primary"
+                            min={start}
+                            max={end}
+                            step={1000}
+                            value={time}
+                            onChange={(e) => setTime(Number(e.target.value))}
+                        />
+                        <Select value={String(speed)} onValueChange={(v) => setSpeed(Number(v))}>
+                            <SelectTrigger className="h-8 w-20">
+                                <SelectValue />
+                            </SelectTrigger>
+                            <SelectContent>
+                                {SPEEDS.map((s) => (
+                                    <SelectItem key={s} value={String(s)}>
+                                        {s}×
+                                    </SelectItem>
+                                ))}
+                            </SelectContent>
+                        </Select>
+                    </div>
+                    <div className="flex flex-wrap items-center justify-between gap-x-3 text-xs text-muted-foreground">
+                        <span className="font-mono text-foreground">{format(new Date(time), "d MMM yyyy HH:mm:ss")}</span>
+                        {current?.speedKmh != null && <span>{Math.round(current.speedKmh)} km/h</span>}
+                        <span>
+                            {track.distanceKm.toFixed(1)} km
+                            {track.maxSpeedKmh != null && ` · max ${Math.round(track.maxSpeedKmh)} km/h`}
+                            {` · ${positions.length} points`}
+                            {track.truncated && " (truncated)"}
+                        </span>
+                    </div>
+                </>
+            )}
+
+            {track && positions.length === 0 && (
+                <p className="text-xs text-muted-foreground">No breadcrumbs recorded for this unit.</p>
+            )}
+        </Card>
+    );
+}
@@ -30,6 +30,7 @@ import {
 } from "@/hooks/use-resources";
 import type { Resource } from "@/types/database";
 import type { ResourceType, ResourceStatus } from "@/types/enums";
+import { useFormattedCoordinate } from "@/hooks/use-coords";
 import { MapPin, Loader2 } from "lucide-react";
 import { toast } from "sonner";
 
@@ -108,6 +109,7 @@ export function ResourceForm({
 
   const [locationPickerOpen, setLocationPickerOpen] = useState(false);
   const [location, setLocation] = useState<LocationPickerValue | null>(null);
+  const coordinates = useFormattedCoordinate(location?.latitude, location?.longitude);
 
   const {
     register,
@@ -307,9 +309,9 @@ export function ResourceForm({
                     `${location.latitude.toFixed(4)}, ${location.longitude.toFixed(4)}`
                   : "Pick Location on Map"}
               </Button>
-              {location && (
+              {coordinates && (
                 <p className="text-xs text-muted-foreground font-mono">
-                  {location.latitude.toFixed(6)}, {location.longitude.toFixed(6)}
+                  {coordinates}
                 </p>
               )}
             </div>
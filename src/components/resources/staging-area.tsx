@@ -0,0 +1,110 @@
+import { useState } from "react";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { Loader2, MapPinned, Trash2 } from "lucide-react";
+import {
+  useAddGeofence,
+  useGeofences,
+  useRemoveGeofence,
+} from "@/hooks/use-geofence";
+import { formatDistance } from "@/lib/geo";
+import type { Geofence } from "@/lib/geofence";
+
+interface StagingAreaProps {
+  resourceId: string;
+  latitude: number | null;
+  longitude: number | null;
+}
+
+function describe(fence: Geofence) {
+  if (fence.shape.type === "circle") {
+    return `Within ${formatDistance(fence.shape.radiusKm)} of ${fence.shape.center.lat.toFixed(4)}, ${fence.shape.center.lng.toFixed(4)}`;
+  }
+  return `Polygon of ${fence.shape.points.length} points`;
+}
+
+/**
+ * Where a resource is meant to stay. Leaving every staging area raises a
+ * geofence alert.
+ */
+export function StagingArea({ resourceId, latitude, longitude }: StagingAreaProps) {
+  const [radiusKm, setRadiusKm] = useState("0.5");
+  const { data: fences = [] } = useGeofences(resourceId);
+  const add = useAddGeofence();
+  const remove = useRemoveGeofence();
+  const staging = fences.filter((f) => f.kind === "staging");
+  const radius = Number(radiusKm);
+  const hasLocation = latitude != null && longitude != null;
+
+  const handleAdd = () => {
+    if (latitude == null || longitude == null) return;
+    add.mutate({
+      kind: "staging",
+      subjectId: resourceId,
+      name: null,
+      shape: {
+        type: "circle",
+        center: { lat: latitude, lng: longitude },
+        radiusKm: radius,
+      },
+    });
+  };
+
+  return (
+    <div className="space-y-3 p-4 border-t">
+      <p className="text-sm font-medium flex items-center gap-1.5">
+        <MapPinned className="h-4 w-4" />
+        Staging area
+      </p>
+      {staging.length === 0 ? (
+        <p className="text-xs text-muted-foreground">
+          No staging area. Set one to be alerted when this resource leaves it.
+        </p>
+      ) : (
+        <ul className="space-y-1">
+          {staging.map((fence) => (
+            <li key={fence.id} className="flex items-center justify-between text-xs">
+              <span>{fence.name ?? describe(fence)}</span>
+              <Button
+                variant="ghost"
+                size="icon"
+                className="h-6 w-6"
+                disabled={remove.isPending}
+                onClick={() => remove.mutate(fence.id)}
+              >
+                <Trash2 className="h-3.5 w-3.5" />
+              </Button>
+            </li>
+          ))}
+        </ul>
+      )}
+      <div className="flex items-end gap-2">
+        <div className="space-y-1">
+          <Label htmlFor="staging-radius" className="text-xs">
+            Radius (km)
+          </Label>
+          <Input
+            id="staging-radius"
+            type="number"
+            min={0.05}
+            step={0.1}
+            value={radiusKm}
+            onChange={(e) => setRadiusKm(e.target.value)}
+            className="h-8 w-24"
+          />
+        </div>
+        <Button
+          size="sm"
+          variant="outline"
+          className="gap-1.5"
+          disabled={!hasLocation || !(radius > 0) || add.isPending}
+          onClick={handleAdd}
+        >
+          {add.isPending && <Loader2 className="h-3.5 w-3.5 animate-spin" />}
+          Stage at current location
+        </Button>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,105 @@
+import { useEffect, useState } from "react";
+import {
+    Dialog,
+    DialogContent,
+    DialogDescription,
+    DialogHeader,
+    DialogTitle,
+} from "@/components/ui/dialog";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Copy, Download, Loader2 } from "lucide-react";
+import { toast } from "sonner";
+import { createShareLink, qrPngDataUrl, type ShareEntity, type SharedLink } from "@/lib/share";
+
+interface ShareDialogProps {
+    open: boolean;
+    onOpenChange: (open: boolean) => void;
+    entity: ShareEntity;
+    id: string;
+    title: string;
+}
+
+function download(href: string, filename: string) {
+    const a = document.createElement("a");
+    a.href = href;
+    a.download = filename;
+    a.click();
+}
+
+export function ShareDialog({ open, onOpenChange, entity, id, title }: ShareDialogProps) {
+    const [link, setLink] = useState<SharedLink | null>(null);
+    const [error, setError] = useState<string | null>(null);
+
+    useEffect(() => {
+        if (!open) return;
+        setLink(null);
+        setError(null);
+        createShareLink(entity, id)
+            .then(setLink)
+            .catch((err) => setError(String(err)));
+    }, [open, entity, id]);
+
+    const handleCopy = async () => {
+        if (!link) return;
+        await navigator.clipboard.writeText(link.url);
+        toast.success("Link copied");
+    };
+
+    const handleDownloadSvg = () => {
+        if (!link) return;
+        const url = URL.createObjectURL(new Blob([link.qrSvg], { type: "image/svg+xml" }));
+        download(url, `${entity}-${id}.svg`);
+        setTimeout(() => URL.revokeObjectURL(url), 1000);
+    };
+
+    return (
+        <Dialog open={open} onOpenChange={onOpenChange}>
+            <DialogContent className="max-w-sm">
+                <DialogHeader>
+                    <DialogTitle>Share “{title}”</DialogTitle>
+                    <DialogDescription>
+                        Anyone with DisasterConnect can scan the code or open the link to jump to this record.
+                    </DialogDescription>
+                </DialogHeader>
+
+                {error ? (
+                    <p className="text-sm text-destructive">{error}</p>
+                ) : !link ? (
+                    <div className="flex justify-center py-10">
+                        <Loader2 className="h-6 w-6 animate-spin text-muted-foreground" />
+                    </div>
+                ) : (
+                    <div className="space-y-3">
+                        <img
+                            src={qrPngDataUrl(link)}
+                            alt="QR code for this record"
+                            className="mx-auto h-48 w-48 rounded bg-white p-2"
+                        />
+                        <div className="flex gap-2">
+                            <Input readOnly value={link.url} className="font-mono text-xs" />
+                            <Button variant="outline" size="icon" onClick={handleCopy} title="Copy link">
+                                <Copy className="h-4 w-4" />
+                            </Button>
+                        </div>
+                        <div className="flex gap-2">
+                            <Button
+                                variant="outline"
+                                size="sm"
+                                className="flex-1 gap-1"
+                                onClick={() => download(qrPngDataUrl(link), `${entity}-${id}.png`)}
+                            >
+                                <Download className="h-3.5 w-3.5" />
+                                PNG
+                            </Button>
+                            <Button variant="outline" size="sm" className="flex-1 gap-1" onClick={handleDownloadSvg}>
+                                <Download className="h-3.5 w-3.5" />
+                                SVG
+                            </Button>
+                        </div>
+                    </div>
+                )}
+            </DialogContent>
+        </Dialog>
+    );
+}
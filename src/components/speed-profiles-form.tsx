@@ -0,0 +1,80 @@
+import { useEffect, useState } from "react";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { Loader2, Save } from "lucide-react";
+import { toast } from "sonner";
+import {
+    getSpeedProfiles,
+    setSpeedProfiles,
+    TRAVEL_MODES,
+    type SpeedProfiles,
+} from "@/lib/evacuation";
+
+/** Speeds behind the evacuation route travel-time estimates. */
+export function SpeedProfilesForm() {
+    const [profiles, setProfiles] = useState<SpeedProfiles | null>(null);
+    const [saving, setSaving] = useState(false);
+
+    useEffect(() => {
+        getSpeedProfiles()
+            .then(setProfiles)
+            .catch((err) => toast.error(`Could not load travel speeds: ${err}`));
+    }, []);
+
+    if (!profiles) return <Loader2 className="h-4 w-4 animate-spin text-muted-foreground" />;
+
+    const update = (mode: keyof SpeedProfiles, field: "speedKmh" | "overheadMinutes", value: string) => {
+        setProfiles({ ...profiles, [mode]: { ...profiles[mode], [field]: Number(value) } });
+    };
+
+    const handleSave = async () => {
+        setSaving(true);
+        try {
+            await setSpeedProfiles(profiles);
+            toast.success("Travel speeds saved");
+        } catch (err) {
+            toast.error(`${err}`);
+        } finally {
+            setSaving(false);
+        }
+    };
+
+    return (
+        <div className="space-y-3">
+            <div>
+                <p className="text-sm font-medium">Travel speeds</p>
+                <p className="text-xs text-muted-foreground">Used to estimate evacuation route times from their length</p>
+            </div>
+            <div className="grid grid-cols-[6rem_1fr_1fr] items-center gap-x-3 gap-y-2 max-w-md">
+                <span />
+                <Label className="text-xs">Speed (km/h)</Label>
+                <Label className="text-xs">Overhead (min)</Label>
+                {TRAVEL_MODES.map(({ value, label }) => (
+                    <div key={value} className="contents">
+                        <span className="text-sm">{label}</span>
+                        <Input
+                            type="number"
+                            min="0.1"
+                            step="0.1"
+                            value={profiles[value].speedKmh}
+                            onChange={(e) => update(value, "speedKmh", e.target.value)}
+                            data-selectable
+                        />
+                        <Input
+                            type="number"
+                            min="0"
+                            value={profiles[value].overheadMinutes}
+                            onChange={(e) => update(value, "overheadMinutes", e.target.value)}
+                            data-selectable
+                        />
+                    </div>
+                ))}
+            </div>
+            <Button size="sm" onClick={handleSave} disabled={saving} className="gap-1.5">
+                {saving ? <Loader2 className="h-3.5 w-3.5 animate-spin" /> : <Save className="h-3.5 w-3.5" />}
+                Save speeds
+            </Button>
+        </div>
+    );
+}
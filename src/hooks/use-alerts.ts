@@ -1,5 +1,7 @@
 import { useQuery, useMutation, useQueryClient } from "@tanstack/react-query";
 import { supabase } from "@/lib/supabase";
+import { isOfflineError, queryMirror } from "@/lib/offline-cache";
+import { queueUpdate } from "@/lib/offline-edit";
 import { useAuthStore } from "@/stores/auth-store";
 import type { AlertType, SeverityLevel } from "@/types/enums";
 
@@ -70,7 +72,18 @@ export function useAlerts(filters: AlertFilters = {}) {
       else if (filters.active === "inactive") query = query.eq("is_active", false);
 
       const { data, error } = await query;
-      if (error) throw error;
+      if (error) {
+        if (isOfflineError(error)) {
+          const isActive =
+            filters.active === "active" ? true : filters.active === "inactive" ? false : undefined;
+          return queryMirror<AlertRow>({
+            table: "alerts",
+            eq: { type: filters.type, severity: filters.severity, is_active: isActive },
+            search: filters.search,
+          });
+        }
+        throw error;
+      }
       return (data ?? []) as AlertRow[];
     },
   });
@@ -105,14 +118,8 @@ export function useUpdateAlert() {
 
   return useMutation({
     mutationFn: async ({ id, ...input }: UpdateAlertInput & { id: string }) => {
-      const { data, error } = await supabase
-        .from("alerts")
-        .update(input)
-        .eq("id", id)
-        .select()
-        .single();
-      if (error) throw error;
-      return data as AlertRow;
+      await queueUpdate(queryClient, "alerts", id, { ...input });
+      return id;
     },
     onSuccess: () => {
       queryClient.invalidateQueries({ queryKey: ["alerts"] });
@@ -143,11 +150,7 @@ export function useToggleAlert() {
 
   return useMutation({
     mutationFn: async ({ id, is_active }: { id: string; is_active: boolean }) => {
-      const { error } = await supabase
-        .from("alerts")
-        .update({ is_active })
-        .eq("id", id);
-      if (error) throw error;
+      await queueUpdate(queryClient, "alerts", id, { is_active });
     },
     onSuccess: () => {
       queryClient.invalidateQueries({ queryKey: ["alerts"] });
@@ -0,0 +1,119 @@
+import { useEffect } from "react";
+import { keepPreviousData, useMutation, useQuery, useQueryClient } from "@tanstack/react-query";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+import {
+    assignAvlUnit,
+    AVL_POSITION_EVENT,
+    AVL_STATUS_EVENT,
+    getAvlStatus,
+    getLatestPositions,
+    getTrack,
+    listAvlUnits,
+    listTrackedUnits,
+    removeAvlUnit,
+    unitKey,
+    type AvlPosition,
+    type AvlStatus,
+    type NewAvlUnit,
+    type TrackQuery,
+} from "@/lib/avl";
+
+// ─── Listeners ──────────────────────────────────────────────────
+
+/** Listener state and unassigned trackers, kept current from the AVL events. */
+export function useAvlStatus() {
+    const qc = useQueryClient();
+
+    useEffect(() => {
+        const unlisten = listen<AvlStatus>(AVL_STATUS_EVENT, ({ payload }) => {
+            qc.setQueryData(["avl", "status"], payload);
+        });
+        return () => {
+            unlisten.then((fn) => fn());
+        };
+    }, [qc]);
+
+    return useQuery({
+        queryKey: ["avl", "status"],
+        queryFn: getAvlStatus,
+        // Report counters are not pushed; poll for them
+        refetchInterval: 10_000,
+    });
+}
+
+// ─── Positions ──────────────────────────────────────────────────
+
+/** Each tracked unit's latest position, moved as reports arrive. */
+export function useLivePositions(enabled = true) {
+    const qc = useQueryClient();
+
+    useEffect(() => {
+        if (!enabled) return;
+        const unlisten = listen<AvlPosition>(AVL_POSITION_EVENT, ({ payload }) => {
+            qc.setQueryData<AvlPosition[]>(["avl", "latest"], (old) => {
+                const key = unitKey(payload.subjectKind, payload.subjectId);
+                const rest = (old ?? []).filter((p) => unitKey(p.subjectKind, p.subjectId) !== key);
+                return [...rest, payload];
+            });
+        });
+        return () => {
+            unlisten.then((fn) => fn());
+        };
+    }, [qc, enabled]);
+
+    return useQuery({
+        queryKey: ["avl", "latest"],
+        queryFn: () => getLatestPositions(),
+        enabled,
+        staleTime: Infinity,
+        retry: false,
+    });
+}
+
+export function useTrackedUnits(enabled = true) {
+    return useQuery({
+        queryKey: ["avl", "tracked"],
+        queryFn: listTrackedUnits,
+        enabled,
+        retry: false,
+    });
+}
+
+export function useTrack(query: TrackQuery | null) {
+    return useQuery({
+        queryKey: ["avl", "track", query],
+        queryFn: () => getTrack(query!),
+        enabled: !!query,
+        placeholderData: keepPreviousData,
+        retry: false,
+    });
+}
+
+// ─── Trackers ───────────────────────────────────────────────────
+
+export function useAvlUnits() {
+    return useQuery({
+        queryKey: ["avl", "units"],
+        queryFn: listAvlUnits,
+        retry: false,
+    });
+}
+
+export function useAssignAvlUnit() {
+    const qc = useQueryClient();
+    return useMutation({
+        mutationFn: (unit: NewAvlUnit) => assignAvlUnit(unit),
+        onSuccess: () => qc.invalidateQueries({ queryKey: ["avl"] }),
+        onError: (err) => toast.error(`Could not assign tracker: ${err}`),
+    });
+}
+
+export function useRemoveAvlUnit() {
+    const qc = useQueryClient();
+    return useMutation({
+        mutationFn: (deviceId: string) => removeAvlUnit(deviceId),
+        onSuccess: () => qc.invalidateQueries({ queryKey: ["avl", "units"] }),
+        onError: (err) => toast.error(`Could not remove tracker: ${err}`),
+    });
+}
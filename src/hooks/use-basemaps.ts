@@ -0,0 +1,86 @@
+import { useEffect } from "react";
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+import {
+    activateBasemap,
+    BASEMAPS_CHANGED_EVENT,
+    importBasemap,
+    listBasemaps,
+    removeBasemap,
+} from "@/lib/basemaps";
+import { getTileServer, setTileServer, type TileServer } from "@/lib/tiles";
+
+// ─── List Basemaps ──────────────────────────────────────────────
+
+export function useBasemaps() {
+    const qc = useQueryClient();
+
+    useEffect(() => {
+        const unlisten = listen(BASEMAPS_CHANGED_EVENT, () => {
+            qc.invalidateQueries({ queryKey: ["basemaps"] });
+        });
+        return () => {
+            unlisten.then((fn) => fn());
+        };
+    }, [qc]);
+
+    return useQuery({
+        queryKey: ["basemaps"],
+        queryFn: listBasemaps,
+        // Locked database: fall back to the OSM layer
+        retry: false,
+    });
+}
+
+export function useActiveBasemap() {
+    const { data } = useBasemaps();
+    return data?.find((b) => b.active) ?? null;
+}
+
+// ─── Manage Basemaps ────────────────────────────────────────────
+
+export function useImportBasemap() {
+    return useMutation({
+        mutationFn: (path: string) => importBasemap(path),
+        onSuccess: (basemap) => toast.success(`Imported ${basemap.name}`),
+        onError: (err) => toast.error(`Import failed: ${err}`),
+    });
+}
+
+export function useActivateBasemap() {
+    return useMutation({
+        mutationFn: (id: string | null) => activateBasemap(id),
+        onError: (err) => toast.error(`Could not switch basemap: ${err}`),
+    });
+}
+
+export function useRemoveBasemap() {
+    return useMutation({
+        mutationFn: (id: string) => removeBasemap(id),
+        onSuccess: () => toast.success("Basemap removed"),
+        onError: (err) => toast.error(`Could not remove basemap: ${err}`),
+    });
+}
+
+// ─── Tile Server ────────────────────────────────────────────────
+
+export function useTileServer() {
+    return useQuery({
+        queryKey: ["tile-server"],
+        queryFn: getTileServer,
+        retry: false,
+    });
+}
+
+export function useSetTileServer() {
+    const qc = useQueryClient();
+    return useMutation({
+        mutationFn: (server: TileServer) => setTileServer(server),
+        onSuccess: (server) => {
+            qc.setQueryData(["tile-server"], server);
+            toast.success(server.url ? "Tile server saved" : "Using OpenStreetMap");
+        },
+        onError: (err) => toast.error(`Could not change tile server: ${err}`),
+    });
+}
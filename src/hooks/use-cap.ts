@@ -0,0 +1,162 @@
+import { useEffect } from "react";
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+import { useAuthStore } from "@/stores/auth-store";
+import { useOfflineStore } from "@/stores/offline-store";
+import {
+    acceptCapReview,
+    CAP_REVIEW_EVENT,
+    CAP_STATUS_EVENT,
+    dismissCapReview,
+    exportCap,
+    getCapWatchStatus,
+    importCap,
+    listCapReview,
+    parseCap,
+    pollCapSources,
+    type CapDraft,
+    type CapSuggestion,
+    type CapWatchStatus,
+} from "@/lib/cap";
+
+// ─── Import ─────────────────────────────────────────────────────
+
+export function usePickCap() {
+    return useMutation({
+        mutationFn: importCap,
+        onError: (err) => toast.error(`Could not read CAP files: ${err}`),
+    });
+}
+
+export function useParseCap() {
+    return useMutation({
+        mutationFn: (xml: string) => parseCap(xml),
+        onError: (err) => toast.error(`Could not read CAP message: ${err}`),
+    });
+}
+
+/** Queue accepted drafts as alert inserts, so they sync like any offline edit. */
+export function useSaveCapDrafts() {
+    const qc = useQueryClient();
+    const userId = useAuthStore((s) => s.user?.id);
+
+    return useMutation({
+        mutationFn: async (drafts: CapDraft[]) => {
+            const { enqueue, drain } = useOfflineStore.getState();
+            for (const draft of drafts) {
+                await enqueue({
+                    table: "alerts",
+                    operation: "insert",
+                    payload: { ...draft.record, created_by: userId! },
+                });
+            }
+            await drain();
+            return drafts.length;
+        },
+        onSuccess: (count) => {
+            qc.invalidateQueries({ queryKey: ["alerts"] });
+            toast.success(`Imported ${count} ${count === 1 ? "alert" : "alerts"}`);
+        },
+        onError: (err) => toast.error(`Import failed: ${err}`),
+    });
+}
+
+// ─── Export ─────────────────────────────────────────────────────
+
+export function useExportCap() {
+    return useMutation({
+        mutationFn: (ids: string[]) => exportCap(ids),
+        onSuccess: (report) => {
+            if (!report) return;
+            const skipped = report.skipped ? ` (${report.skipped} not cached yet skipped)` : "";
+            toast.success(`Exported ${report.exported} CAP ${report.exported === 1 ? "message" : "messages"} to ${report.path}${skipped}`);
+        },
+        onError: (err) => toast.error(`CAP export failed: ${err}`),
+    });
+}
+
+// ─── Review queue ───────────────────────────────────────────────
+
+/** Messages from partner feeds awaiting review, refreshed as more arrive. */
+export function useCapReview() {
+    const qc = useQueryClient();
+
+    useEffect(() => {
+        const unlisten = listen<number>(CAP_REVIEW_EVENT, () => {
+            qc.invalidateQueries({ queryKey: ["cap", "review"] });
+            qc.invalidateQueries({ queryKey: ["cap", "watch"] });
+        });
+        return () => {
+            unlisten.then((fn) => fn());
+        };
+    }, [qc]);
+
+    return useQuery({
+        queryKey: ["cap", "review"],
+        queryFn: () => listCapReview("pending"),
+    });
+}
+
+export function useCapWatchStatus() {
+    const qc = useQueryClient();
+
+    useEffect(() => {
+        const unlisten = listen<CapWatchStatus>(CAP_STATUS_EVENT, ({ payload }) => {
+            qc.setQueryData(["cap", "watch"], payload);
+        });
+        return () => {
+            unlisten.then((fn) => fn());
+        };
+    }, [qc]);
+
+    return useQuery({
+        queryKey: ["cap", "watch"],
+        queryFn: getCapWatchStatus,
+    });
+}
+
+export function usePollCapSources() {
+    const qc = useQueryClient();
+
+    return useMutation({
+        mutationFn: pollCapSources,
+        onSuccess: (status) => {
+            qc.setQueryData(["cap", "watch"], status);
+            qc.invalidateQueries({ queryKey: ["cap", "review"] });
+        },
+        onError: (err) => toast.error(`Could not check CAP sources: ${err}`),
+    });
+}
+
+/** Accept a suggestion; the record is queued and synced like any offline edit. */
+export function useAcceptCapReview() {
+    const qc = useQueryClient();
+    const userId = useAuthStore((s) => s.user?.id);
+
+    return useMutation({
+        mutationFn: async ({ id, kind }: { id: string; kind?: CapSuggestion }) => {
+            const item = await acceptCapReview(id, userId!, kind);
+            await useOfflineStore.getState().drain();
+            return item;
+        },
+        onSuccess: (item) => {
+            qc.invalidateQueries({ queryKey: ["cap", "review"] });
+            qc.invalidateQueries({ queryKey: [item.record?.table ?? "alerts"] });
+            qc.invalidateQueries({ queryKey: ["dashboard"] });
+            const what = item.record?.table === "incidents" ? "incident" : "alert";
+            toast.success(item.target ? `Updated the ${what}` : `Created the ${what}`);
+        },
+        onError: (err) => toast.error(`Could not accept CAP message: ${err}`),
+    });
+}
+
+export function useDismissCapReview() {
+    const qc = useQueryClient();
+
+    return useMutation({
+        mutationFn: (id: string) => dismissCapReview(id),
+        onSuccess: () => qc.invalidateQueries({ queryKey: ["cap", "review"] }),
+        onError: (err) => toast.error(`Could not dismiss CAP message: ${err}`),
+    });
+}
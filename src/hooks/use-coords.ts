@@ -0,0 +1,47 @@
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query";
+import { toast } from "sonner";
+import {
+    formatCoordinates,
+    getCoordinatePreference,
+    setCoordinatePreference,
+    type Notation,
+} from "@/lib/coords";
+
+export function useCoordinatePreference() {
+    return useQuery({
+        queryKey: ["coords", "preference"],
+        queryFn: getCoordinatePreference,
+        staleTime: Infinity,
+    });
+}
+
+export function useSetCoordinatePreference() {
+    const qc = useQueryClient();
+
+    return useMutation({
+        mutationFn: (notation: Notation) => setCoordinatePreference({ notation }),
+        onSuccess: () => {
+            qc.invalidateQueries({ queryKey: ["coords"] });
+        },
+        onError: (err) => {
+            toast.error(`Could not save the coordinate format: ${err}`);
+        },
+    });
+}
+
+/** A position in the preferred notation, or decimal degrees until it's known. */
+export function useFormattedCoordinate(latitude: number | null | undefined, longitude: number | null | undefined) {
+    const { data: preference } = useCoordinatePreference();
+    const notation = preference?.notation ?? "decimal";
+    const hasPoint = latitude != null && longitude != null;
+
+    const { data } = useQuery({
+        queryKey: ["coords", "format", notation, latitude, longitude],
+        queryFn: async () => (await formatCoordinates([{ lat: latitude!, lng: longitude! }], notation))[0],
+        enabled: hasPoint && notation !== "decimal",
+        staleTime: Infinity,
+    });
+
+    if (!hasPoint) return null;
+    return notation === "decimal" || !data ? `${latitude.toFixed(6)}, ${longitude.toFixed(6)}` : data;
+}
@@ -1,74 +1,82 @@
 import { useEffect } from "react";
 import { useNavigate } from "react-router-dom";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { supabase } from "@/lib/supabase";
 
+/** A validated `disasterconnect://` link, parsed by `src-tauri/src/deep_link.rs`. */
+export type DeepLink =
+    | {
+          kind: "auth_callback";
+          accessToken: string;
+          refreshToken: string;
+          linkType: string | null;
+      }
+    | {
+          kind: "open";
+          route: DeepLinkRoute;
+          /** Router path for `route` */
+          path: string;
+          /** Carried a valid share-link signature, or came from the app itself */
+          signed: boolean;
+      };
+
+export type DeepLinkRoute =
+    | { screen: "dashboard" }
+    | { screen: "incidents" }
+    | { screen: "cap_review" }
+    | { screen: "incident" | "task" | "alert" | "sos" | "evacuation_route"; id: string }
+    | { screen: "map"; lat: number; lng: number; zoom: number | null };
+
 /**
- * Listens for deep link URLs from the Tauri backend.
+ * Listens for deep links from the Tauri backend.
  * When the user clicks a Supabase email link (verify / reset password),
  * the OS routes `disasterconnect://auth/callback#access_token=...` to this app.
- * This hook extracts the tokens and sets the Supabase session.
+ * The backend validates it and hands over the tokens, and this hook sets the
+ * Supabase session. Record links such as `disasterconnect://incidents/{id}`
+ * (and the tray menu) arrive as routes to navigate to, signed or not: they
+ * only name a record, which row-level security still guards. Expired share
+ * links never get here, the backend drops them.
  */
 export function useDeepLink() {
     const navigate = useNavigate();
 
     useEffect(() => {
-        async function handleDeepLink(url: string) {
-            console.log("[deep-link] Received URL:", url);
-
-            // The token fragment comes after # in the URL
-            // e.g. disasterconnect://auth/callback#access_token=xxx&refresh_token=yyy&type=signup
-            const hashIndex = url.indexOf("#");
-            if (hashIndex === -1) {
-                console.warn("[deep-link] No fragment found in URL");
-                return;
-            }
-
-            const fragment = url.substring(hashIndex + 1);
-            const params = new URLSearchParams(fragment);
-
-            const accessToken = params.get("access_token");
-            const refreshToken = params.get("refresh_token");
-            const type = params.get("type");
+        async function handleDeepLink(link: DeepLink) {
+            switch (link.kind) {
+                case "auth_callback": {
+                    const { error } = await supabase.auth.setSession({
+                        access_token: link.accessToken,
+                        refresh_token: link.refreshToken,
+                    });
 
-            console.log("[deep-link] Type:", type, "Has tokens:", !!accessToken && !!refreshToken);
-
-            if (accessToken && refreshToken) {
-                const { error } = await supabase.auth.setSession({
-                    access_token: accessToken,
-                    refresh_token: refreshToken,
-                });
-
-                if (error) {
-                    console.error("[deep-link] Failed to set session:", error.message);
-                } else {
-                    console.log("[deep-link] Session set, navigating for type:", type);
-                    if (type === "recovery") {
+                    if (error) {
+                        console.error("[deep-link] Failed to set session:", error.message);
+                    } else if (link.linkType === "recovery") {
                         navigate("/reset-password");
                     } else {
                         navigate("/dashboard");
                     }
+                    break;
                 }
+                case "open":
+                    navigate(link.path);
+                    break;
             }
         }
 
-        // Handle deep links dispatched from Tauri backend
-        function onDeepLink(event: Event) {
-            const url = (event as CustomEvent).detail;
-            if (typeof url === "string") {
-                handleDeepLink(url);
-            }
-        }
-
-        window.addEventListener("deep-link", onDeepLink);
+        const unlisten = listen<DeepLink>("deep-link://open", (event) => {
+            handleDeepLink(event.payload);
+        });
 
-        // Also check if a deep link was received before this hook mounted
-        if (typeof (window as any).__DEEP_LINK_URL__ === "string") {
-            handleDeepLink((window as any).__DEEP_LINK_URL__);
-            delete (window as any).__DEEP_LINK_URL__;
-        }
+        // Once we're listening, collect links that arrived while the app was loading
+        unlisten
+            .then(() => invoke<DeepLink[]>("deep_link_ready"))
+            .then((pending) => pending.forEach(handleDeepLink))
+            .catch((err) => console.error("[deep-link] Failed to subscribe:", err));
 
         return () => {
-            window.removeEventListener("deep-link", onDeepLink);
+            unlisten.then((fn) => fn());
         };
     }, [navigate]);
 }
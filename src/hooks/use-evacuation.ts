@@ -1,5 +1,6 @@
 import { useQuery, useMutation, useQueryClient } from "@tanstack/react-query";
 import { supabase } from "@/lib/supabase";
+import { isOfflineError, queryMirror } from "@/lib/offline-cache";
 import { useAuthStore } from "@/stores/auth-store";
 import type { EvacuationRoute } from "@/types/database";
 import { toast } from "sonner";
@@ -39,7 +40,16 @@ export function useEvacuationRoutes(filters: EvacRouteFilters = {}) {
             if (filters.search) q = q.ilike("name", `%${filters.search}%`);
 
             const { data, error } = await q;
-            if (error) throw error;
+            if (error) {
+                if (isOfflineError(error)) {
+                    return queryMirror<EvacuationRoute & { profiles: { first_name: string; last_name: string } | null }>({
+                        table: "evacuation_routes",
+                        eq: { incident_id: filters.incidentId, is_active: filters.activeOnly ? true : undefined },
+                        search: filters.search,
+                    });
+                }
+                throw error;
+            }
             return (data ?? []) as (EvacuationRoute & { profiles: { first_name: string; last_name: string } | null })[];
         },
     });
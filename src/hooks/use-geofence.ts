@@ -0,0 +1,70 @@
+import { useEffect } from "react";
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+import {
+    addGeofence,
+    GEOFENCE_ALERT_EVENT,
+    geofenceAlerts,
+    listGeofences,
+    removeGeofence,
+    type GeofenceAlert,
+    type NewGeofence,
+} from "@/lib/geofence";
+
+// ─── Alerts ─────────────────────────────────────────────────────
+
+/** Toast geofence alerts as they are raised. Call once in the app shell. */
+export function useGeofenceAlertListener() {
+    const qc = useQueryClient();
+
+    useEffect(() => {
+        const unlisten = listen<GeofenceAlert>(GEOFENCE_ALERT_EVENT, ({ payload }) => {
+            qc.invalidateQueries({ queryKey: ["geofence-alerts"] });
+            toast.warning(payload.title, {
+                description: payload.body,
+                duration: 10000,
+            });
+        });
+        return () => {
+            unlisten.then((fn) => fn());
+        };
+    }, [qc]);
+}
+
+export function useGeofenceAlerts(limit?: number) {
+    return useQuery({
+        queryKey: ["geofence-alerts", limit],
+        queryFn: () => geofenceAlerts(limit),
+        retry: false,
+    });
+}
+
+// ─── Geofences ──────────────────────────────────────────────────
+
+export function useGeofences(subjectId: string | undefined) {
+    return useQuery({
+        queryKey: ["geofences", subjectId],
+        queryFn: () => listGeofences(subjectId),
+        enabled: !!subjectId,
+        retry: false,
+    });
+}
+
+export function useAddGeofence() {
+    const qc = useQueryClient();
+    return useMutation({
+        mutationFn: (fence: NewGeofence) => addGeofence(fence),
+        onSuccess: () => qc.invalidateQueries({ queryKey: ["geofences"] }),
+        onError: (err) => toast.error(`Could not save geofence: ${err}`),
+    });
+}
+
+export function useRemoveGeofence() {
+    const qc = useQueryClient();
+    return useMutation({
+        mutationFn: (id: string) => removeGeofence(id),
+        onSuccess: () => qc.invalidateQueries({ queryKey: ["geofences"] }),
+        onError: (err) => toast.error(`Could not remove geofence: ${err}`),
+    });
+}
@@ -0,0 +1,42 @@
+import { useEffect } from "react";
+import { useQuery, useQueryClient } from "@tanstack/react-query";
+import { listen } from "@tauri-apps/api/event";
+import {
+    getGpsStatus,
+    GPS_FIX_EVENT,
+    GPS_STATUS_EVENT,
+    type GpsFix,
+    type GpsStatus,
+} from "@/lib/gps";
+
+/** Receiver state and last fix, kept current from the GPS events. */
+export function useGpsStatus() {
+    const qc = useQueryClient();
+
+    useEffect(() => {
+        const unlistenStatus = listen<GpsStatus>(GPS_STATUS_EVENT, ({ payload }) => {
+            qc.setQueryData(["gps", "status"], payload);
+        });
+        const unlistenFix = listen<GpsFix>(GPS_FIX_EVENT, ({ payload }) => {
+            qc.setQueryData<GpsStatus>(["gps", "status"], (old) =>
+                old ? { ...old, state: "fixed", error: null, fix: payload } : old,
+            );
+        });
+        return () => {
+            unlistenStatus.then((fn) => fn());
+            unlistenFix.then((fn) => fn());
+        };
+    }, [qc]);
+
+    return useQuery({
+        queryKey: ["gps", "status"],
+        queryFn: getGpsStatus,
+        staleTime: Infinity,
+    });
+}
+
+/** The current fix, or `null` when there is no receiver or no fresh fix. */
+export function useGpsFix(): GpsFix | null {
+    const { data } = useGpsStatus();
+    return data?.state === "fixed" ? data.fix : null;
+}
@@ -1,5 +1,8 @@
 import { useQuery, useMutation, useQueryClient } from "@tanstack/react-query";
 import { supabase } from "@/lib/supabase";
+import { withLocationName } from "@/lib/geocode";
+import { getMirrored, isOfflineError, queryMirror } from "@/lib/offline-cache";
+import { queueUpdate } from "@/lib/offline-edit";
 import { useAuthStore } from "@/stores/auth-store";
 import type { Incident } from "@/types/database";
 import type {
@@ -75,7 +78,16 @@ export function useIncidents(filters: IncidentFilters = {}) {
       }
 
       const { data, error } = await query;
-      if (error) throw error;
+      if (error) {
+        if (isOfflineError(error)) {
+          return queryMirror<IncidentWithProfile>({
+            table: "incidents",
+            eq: { type: filters.type, severity: filters.severity, status: filters.status },
+            search: filters.search,
+          });
+        }
+        throw error;
+      }
       return (data ?? []) as IncidentWithProfile[];
     },
   });
@@ -95,7 +107,13 @@ export function useIncident(id: string | undefined) {
         .eq("id", id!)
         .single();
 
-      if (error) throw error;
+      if (error) {
+        if (isOfflineError(error)) {
+          const cached = await getMirrored<IncidentWithProfile>("incidents", id!);
+          if (cached) return cached;
+        }
+        throw error;
+      }
       return data as IncidentWithProfile;
     },
     enabled: !!id,
@@ -113,7 +131,7 @@ export function useCreateIncident() {
       const { data, error } = await supabase
         .from("incidents")
         .insert({
-          ...input,
+          ...(await withLocationName(input)),
           created_by: userId!,
         })
         .select()
@@ -146,19 +164,12 @@ export function useUpdateIncident() {
         updateData.closed_at = new Date().toISOString();
       }
 
-      const { data, error } = await supabase
-        .from("incidents")
-        .update(updateData)
-        .eq("id", id)
-        .select()
-        .single();
-
-      if (error) throw error;
-      return data as Incident;
+      await queueUpdate(queryClient, "incidents", id, updateData);
+      return id;
     },
-    onSuccess: (data) => {
+    onSuccess: (id) => {
       queryClient.invalidateQueries({ queryKey: ["incidents"] });
-      queryClient.invalidateQueries({ queryKey: ["incidents", data.id] });
+      queryClient.invalidateQueries({ queryKey: ["incidents", id] });
       queryClient.invalidateQueries({ queryKey: ["dashboard"] });
     },
   });
@@ -0,0 +1,80 @@
+import { useMutation, useQueryClient } from "@tanstack/react-query";
+import { toast } from "sonner";
+import { useAuthStore } from "@/stores/auth-store";
+import { useOfflineStore } from "@/stores/offline-store";
+import { addGeofence } from "@/lib/geofence";
+import {
+    exportFeatures,
+    importFeatures,
+    INTERCHANGE_TABLES,
+    type ImportReport,
+    type InterchangeFormat,
+    type InterchangeKind,
+} from "@/lib/interchange";
+
+/** React Query keys listing each kind, refreshed after an import. */
+const QUERY_KEYS: Record<InterchangeKind, string[]> = {
+    incident: ["incidents", "dashboard"],
+    resource: ["resources", "dashboard"],
+    sos: ["sos", "incidents", "dashboard"],
+    evacuation_route: ["evacuation-routes"],
+};
+
+// ─── Import ─────────────────────────────────────────────────────
+
+export function usePickFeatures(kind: InterchangeKind) {
+    return useMutation({
+        mutationFn: () => importFeatures(kind),
+        onError: (err) => toast.error(`Could not read file: ${err}`),
+    });
+}
+
+/**
+ * Queue imported records as inserts, so they sync like any offline edit, and
+ * keep their polygons as incident zones / staging areas.
+ */
+export function useSaveImportedFeatures(kind: InterchangeKind) {
+    const qc = useQueryClient();
+    const userId = useAuthStore((s) => s.user?.id);
+
+    return useMutation({
+        mutationFn: async ({ records, zones }: ImportReport) => {
+            const owner = kind === "sos" ? "sender_id" : "created_by";
+            const { enqueue, drain } = useOfflineStore.getState();
+            for (const record of records) {
+                await enqueue({
+                    table: INTERCHANGE_TABLES[kind],
+                    operation: "insert",
+                    payload: { ...record, [owner]: userId! },
+                });
+            }
+            for (const zone of zones) {
+                await addGeofence(zone);
+            }
+            await drain();
+            return records.length;
+        },
+        onSuccess: (count) => {
+            for (const key of [...QUERY_KEYS[kind], "geofences"]) {
+                qc.invalidateQueries({ queryKey: [key] });
+            }
+            toast.success(`Imported ${count} ${count === 1 ? "feature" : "features"}`);
+        },
+        onError: (err) => toast.error(`Import failed: ${err}`),
+    });
+}
+
+// ─── Export ─────────────────────────────────────────────────────
+
+export function useExportFeatures(kind: InterchangeKind) {
+    return useMutation({
+        mutationFn: (vars: { format: InterchangeFormat; ids?: string[] }) =>
+            exportFeatures(kind, vars.format, vars.ids),
+        onSuccess: (report) => {
+            if (!report) return;
+            const skipped = report.skipped ? ` (${report.skipped} without a location skipped)` : "";
+            toast.success(`Exported ${report.exported} to ${report.path}${skipped}`);
+        },
+        onError: (err) => toast.error(`Export failed: ${err}`),
+    });
+}
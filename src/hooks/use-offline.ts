@@ -1,11 +1,9 @@
-import { useEffect, useCallback } from "react";
-import { useOfflineStore, type QueuedMutation } from "@/stores/offline-store";
-import { supabase } from "@/lib/supabase";
+import { useEffect } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { useOfflineStore, type DrainReport } from "@/stores/offline-store";
 import { toast } from "sonner";
 import { useQueryClient } from "@tanstack/react-query";
 
-const MAX_RETRIES = 3;
-
 // ─── Online/Offline detection ───────────────────────────────────
 
 export function useOfflineDetection() {
@@ -38,105 +36,76 @@ export function useOfflineDetection() {
     }, [setOnline]);
 }
 
-// ─── Sync engine — replays queued mutations ─────────────────────
+// ─── Sync engine — the Rust side replays, we mirror the results ─
 
 export function useOfflineSync() {
     const queryClient = useQueryClient();
     const {
         isOnline,
-        queue,
-        isSyncing,
-        dequeue,
-        incrementRetries,
-        setSyncing,
+        refreshQueue,
+        refreshConflicts,
+        refreshDeadLetters,
+        drain,
         setLastSynced,
     } = useOfflineStore();
 
-    const replayMutation = useCallback(
-        async (mutation: QueuedMutation): Promise<boolean> => {
-            try {
-                if (mutation.operation === "insert") {
-                    const { error } = await supabase
-                        .from(mutation.table)
-                        .insert(mutation.payload);
-                    if (error) throw error;
-                } else if (mutation.operation === "update") {
-                    const { id: recordId, ...rest } = mutation.payload;
-                    const { error } = await supabase
-                        .from(mutation.table)
-                        .update(rest)
-                        .eq("id", recordId as string);
-                    if (error) throw error;
-                } else if (mutation.operation === "delete") {
-                    const { error } = await supabase
-                        .from(mutation.table)
-                        .delete()
-                        .eq("id", mutation.payload.id as string);
-                    if (error) throw error;
-                }
-                return true;
-            } catch {
-                return false;
-            }
-        },
-        []
-    );
-
     useEffect(() => {
-        if (!isOnline || isSyncing || queue.length === 0) return;
-
-        const sync = async () => {
-            setSyncing(true);
-            let successCount = 0;
-            let failCount = 0;
-            const affectedTables = new Set<string>();
-
-            for (const mutation of queue) {
-                if (mutation.retries >= MAX_RETRIES) {
-                    // Give up after max retries
-                    dequeue(mutation.id);
-                    failCount++;
-                    continue;
-                }
+        refreshQueue().catch(() => {});
+        refreshConflicts().catch(() => {});
+        refreshDeadLetters().catch(() => {});
 
-                const ok = await replayMutation(mutation);
-                if (ok) {
-                    dequeue(mutation.id);
-                    affectedTables.add(mutation.table);
-                    successCount++;
-                } else {
-                    incrementRetries(mutation.id);
-                    failCount++;
-                }
-            }
+        const unlistenChanged = listen<number>("offline-queue://changed", () => {
+            refreshQueue().catch(() => {});
+        });
+
+        const unlistenConflict = listen("sync://conflict", () => {
+            refreshConflicts().catch(() => {});
+        });
 
-            if (successCount > 0) {
+        const unlistenDeadLetter = listen("dead-letter://changed", () => {
+            refreshDeadLetters().catch(() => {});
+        });
+
+        const unlistenDrained = listen<DrainReport>("offline-queue://drained", (event) => {
+            const { synced, deadLettered, conflicts, tables } = event.payload;
+
+            if (synced > 0) {
                 // Only invalidate queries for tables that were actually synced
-                for (const table of affectedTables) {
+                for (const table of tables) {
                     queryClient.invalidateQueries({ queryKey: [table] });
                 }
                 queryClient.invalidateQueries({ queryKey: ["dashboard"] });
                 setLastSynced(new Date().toISOString());
-                toast.success(`Synced ${successCount} pending change${successCount > 1 ? "s" : ""}`);
+                toast.success(`Synced ${synced} pending change${synced > 1 ? "s" : ""}`);
+            }
+
+            // Transient failures are retried with backoff, only report what
+            // the server rejected outright
+            if (deadLettered > 0) {
+                toast.error(`${deadLettered} change${deadLettered > 1 ? "s" : ""} rejected by the server`, {
+                    description: "Kept in failed changes so they can be fixed and retried.",
+                });
             }
 
-            if (failCount > 0) {
-                toast.error(`${failCount} change${failCount > 1 ? "s" : ""} failed to sync`);
+            if (conflicts > 0) {
+                toast.warning(`${conflicts} change${conflicts > 1 ? "s" : ""} need${conflicts > 1 ? "" : "s"} review`, {
+                    description: "Someone else edited the same record while you were offline.",
+                });
             }
+        });
 
-            setSyncing(false);
+        return () => {
+            unlistenChanged.then((fn) => fn());
+            unlistenConflict.then((fn) => fn());
+            unlistenDeadLetter.then((fn) => fn());
+            unlistenDrained.then((fn) => fn());
         };
+    }, [refreshQueue, refreshConflicts, refreshDeadLetters, setLastSynced, queryClient]);
 
-        sync();
-    }, [
-        isOnline,
-        queue,
-        isSyncing,
-        dequeue,
-        incrementRetries,
-        setSyncing,
-        setLastSynced,
-        replayMutation,
-        queryClient,
-    ]);
+    // Replay straight away when we come back online instead of waiting for
+    // the next background tick
+    useEffect(() => {
+        if (!isOnline) return;
+        drain().catch((err) => console.error("[offline] Drain failed:", err));
+    }, [isOnline, drain]);
 }
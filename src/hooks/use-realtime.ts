@@ -3,6 +3,7 @@ import { useQueryClient } from "@tanstack/react-query";
 import { supabase } from "@/lib/supabase";
 import { useAuthStore } from "@/stores/auth-store";
 import { toast } from "sonner";
+import { checkGeofences } from "@/lib/geofence";
 import type { Notification } from "@/types/database";
 
 /**
@@ -51,6 +52,7 @@ export function useRealtime() {
                 () => {
                     queryClient.invalidateQueries({ queryKey: ["incidents"] });
                     queryClient.invalidateQueries({ queryKey: ["dashboard"] });
+                    queryClient.invalidateQueries({ queryKey: ["map-aggregate"] });
                 }
             )
             .subscribe();
@@ -64,11 +66,14 @@ export function useRealtime() {
                 (payload) => {
                     queryClient.invalidateQueries({ queryKey: ["sos"] });
                     queryClient.invalidateQueries({ queryKey: ["dashboard"] });
+                    queryClient.invalidateQueries({ queryKey: ["map-aggregate"] });
                     const sos = payload.new as { message?: string };
                     toast.error("🚨 SOS BROADCAST", {
                         description: sos.message || "Emergency SOS broadcast received!",
                         duration: 10000,
                     });
+                    // Raises its own alert if the SOS is inside an incident zone
+                    checkGeofences("sos_broadcasts", payload.new).catch(() => {});
                 }
             )
             .subscribe();
@@ -79,9 +84,11 @@ export function useRealtime() {
             .on(
                 "postgres_changes",
                 { event: "UPDATE", schema: "public", table: "resources" },
-                () => {
+                (payload) => {
                     queryClient.invalidateQueries({ queryKey: ["resources"] });
                     queryClient.invalidateQueries({ queryKey: ["dashboard"] });
+                    queryClient.invalidateQueries({ queryKey: ["map-aggregate"] });
+                    checkGeofences("resources", payload.new).catch(() => {});
                 }
             )
             .subscribe();
@@ -126,7 +126,7 @@ export function useRecentIncidents(limit = 50, dateRange?: { from: string; to: s
         queryFn: async () => {
             let q = supabase
                 .from("incidents")
-                .select("id, title, type, severity, status, location_name, created_at, profiles!incidents_created_by_fkey(first_name, last_name)")
+                .select("id, title, type, severity, status, location_name, latitude, longitude, created_at, profiles!incidents_created_by_fkey(first_name, last_name)")
                 .order("created_at", { ascending: false })
                 .limit(limit);
             if (dateRange?.from) q = q.gte("created_at", dateRange.from);
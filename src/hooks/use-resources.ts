@@ -1,5 +1,8 @@
 import { useQuery, useMutation, useQueryClient } from "@tanstack/react-query";
 import { supabase } from "@/lib/supabase";
+import { withLocationName } from "@/lib/geocode";
+import { isOfflineError, queryMirror } from "@/lib/offline-cache";
+import { queueUpdate } from "@/lib/offline-edit";
 import { useAuthStore } from "@/stores/auth-store";
 import type { Resource, ResourceAssignment } from "@/types/database";
 import type { ResourceType, ResourceStatus } from "@/types/enums";
@@ -75,7 +78,20 @@ export function useResources(filters: ResourceFilters = {}) {
       }
 
       const { data, error } = await query;
-      if (error) throw error;
+      if (error) {
+        if (isOfflineError(error)) {
+          const maintenance =
+            filters.maintenance === "operational" || filters.maintenance === "under_maintenance"
+              ? filters.maintenance
+              : undefined;
+          return queryMirror<ResourceWithProfile>({
+            table: "resources",
+            eq: { type: filters.type, status: filters.status, maintenance_status: maintenance },
+            search: filters.search,
+          });
+        }
+        throw error;
+      }
       return (data ?? []) as ResourceWithProfile[];
     },
   });
@@ -113,7 +129,7 @@ export function useCreateResource() {
       const { data, error } = await supabase
         .from("resources")
         .insert({
-          ...input,
+          ...(await withLocationName(input)),
           created_by: userId!,
         })
         .select()
@@ -139,19 +155,12 @@ export function useUpdateResource() {
       id,
       ...input
     }: UpdateResourceInput & { id: string }) => {
-      const { data, error } = await supabase
-        .from("resources")
-        .update(input)
-        .eq("id", id)
-        .select()
-        .single();
-
-      if (error) throw error;
-      return data as Resource;
+      await queueUpdate(queryClient, "resources", id, { ...input });
+      return id;
     },
-    onSuccess: (data) => {
+    onSuccess: (id) => {
       queryClient.invalidateQueries({ queryKey: ["resources"] });
-      queryClient.invalidateQueries({ queryKey: ["resources", data.id] });
+      queryClient.invalidateQueries({ queryKey: ["resources", id] });
       queryClient.invalidateQueries({ queryKey: ["dashboard"] });
     },
   });
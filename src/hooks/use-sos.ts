@@ -1,5 +1,8 @@
 import { useQuery, useMutation, useQueryClient } from "@tanstack/react-query";
 import { supabase } from "@/lib/supabase";
+import { withLocationName } from "@/lib/geocode";
+import { withGpsPosition } from "@/lib/gps";
+import { getMirrored, isOfflineError, queryMirror } from "@/lib/offline-cache";
 import { useAuthStore } from "@/stores/auth-store";
 import type { SOSBroadcast } from "@/types/database";
 import type { SeverityLevel } from "@/types/enums";
@@ -16,13 +19,43 @@ export function useActiveSOS() {
                 .eq("is_active", true)
                 .order("created_at", { ascending: false });
 
-            if (error) throw error;
+            if (error) {
+                if (isOfflineError(error)) {
+                    return queryMirror<SOSBroadcast>({ table: "sos_broadcasts", eq: { is_active: true } });
+                }
+                throw error;
+            }
             return (data ?? []) as SOSBroadcast[];
         },
         refetchInterval: 15000,
     });
 }
 
+// ─── Single SOS Broadcast ────────────────────────────────────────
+
+export function useSOS(id: string | null) {
+    return useQuery({
+        queryKey: ["sos", id],
+        queryFn: async (): Promise<SOSBroadcast> => {
+            const { data, error } = await supabase
+                .from("sos_broadcasts")
+                .select("*")
+                .eq("id", id!)
+                .single();
+
+            if (error) {
+                if (isOfflineError(error)) {
+                    const cached = await getMirrored<SOSBroadcast>("sos_broadcasts", id!);
+                    if (cached) return cached;
+                }
+                throw error;
+            }
+            return data as SOSBroadcast;
+        },
+        enabled: !!id,
+    });
+}
+
 // ─── Create SOS Broadcast ────────────────────────────────────────
 
 export interface CreateSOSInput {
@@ -39,7 +72,9 @@ export function useCreateSOS() {
     const profile = useAuthStore((s) => s.profile);
 
     return useMutation({
-        mutationFn: async (input: CreateSOSInput) => {
+        mutationFn: async (raw: CreateSOSInput) => {
+            // The post's own receiver knows where the SOS was sent from
+            const input = await withLocationName(await withGpsPosition(raw));
             // 1. Create the SOS broadcast
             const { data: sos, error: sosError } = await supabase
                 .from("sos_broadcasts")
@@ -1,5 +1,6 @@
 import { useQuery, useMutation, useQueryClient } from "@tanstack/react-query";
 import { supabase } from "@/lib/supabase";
+import { isOfflineError, queryMirror } from "@/lib/offline-cache";
 import { useAuthStore } from "@/stores/auth-store";
 import type { Task, Profile } from "@/types/database";
 import type { TaskPriority, TaskStatus } from "@/types/enums";
@@ -65,7 +66,21 @@ export function useTasks(filters: TaskFilters = {}) {
             }
 
             const { data, error } = await query;
-            if (error) throw error;
+            if (error) {
+                if (isOfflineError(error)) {
+                    return queryMirror<TaskWithProfiles>({
+                        table: "tasks",
+                        eq: {
+                            priority: filters.priority,
+                            status: filters.status,
+                            assigned_to: filters.assignedTo,
+                            incident_id: filters.incidentId,
+                        },
+                        search: filters.search,
+                    });
+                }
+                throw error;
+            }
             return (data ?? []) as TaskWithProfiles[];
         },
     });
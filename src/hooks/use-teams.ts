@@ -1,5 +1,6 @@
 import { useQuery, useMutation, useQueryClient } from "@tanstack/react-query";
 import { supabase } from "@/lib/supabase";
+import { isOfflineError, queryMirror } from "@/lib/offline-cache";
 import { useAuthStore } from "@/stores/auth-store";
 import type { Team, TeamMember } from "@/types/database";
 
@@ -32,7 +33,12 @@ export function useTeams() {
                 .eq("is_active", true)
                 .order("created_at", { ascending: false });
 
-            if (error) throw error;
+            if (error) {
+                if (isOfflineError(error)) {
+                    return queryMirror<TeamWithMembers>({ table: "teams", eq: { is_active: true } });
+                }
+                throw error;
+            }
             return (data ?? []) as TeamWithMembers[];
         },
     });
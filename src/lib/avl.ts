@@ -0,0 +1,211 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { Point } from "@/lib/geo";
+
+/**
+ * Automatic vehicle location: trackers reporting over UDP (NMEA, APRS or
+ * JSON), a local HTTP gateway or APRS-IS, tied to resources and teams, with
+ * a breadcrumb history for playback (`src-tauri/src/avl.rs`).
+ */
+
+export type SubjectKind = "resource" | "team";
+
+export type ReportSource = "nmea" | "json" | "aprs" | "manual";
+
+export interface AvlPosition {
+    subjectKind: SubjectKind;
+    subjectId: string;
+    /** The unit's name, on live and latest positions. */
+    label: string | null;
+    deviceId: string | null;
+    point: Point;
+    speedKmh: number | null;
+    /** Degrees true. */
+    courseDeg: number | null;
+    altitudeM: number | null;
+    source: ReportSource;
+    reportedAt: string;
+    receivedAt: string;
+}
+
+export interface AprsIsConfig {
+    /** `host:port`; the worldwide rotation when empty. */
+    server: string | null;
+    callsign: string;
+    /** Server-side filter; the assigned callsigns when empty. */
+    filter: string | null;
+}
+
+export interface AvlConfig {
+    enabled: boolean;
+    udp: string | null;
+    http: string | null;
+    /** Shared secret: the first line of every UDP datagram, or the HTTP bearer token. */
+    token: string | null;
+    aprsIs: AprsIsConfig | null;
+    retentionDays: number;
+    /** Move resources on the shared map too. */
+    syncResources: boolean;
+}
+
+export type ListenerKind = "udp" | "http" | "aprs_is";
+
+export interface ListenerStatus {
+    kind: ListenerKind;
+    address: string;
+    state: "connecting" | "listening" | "error";
+    error: string | null;
+    reports: number;
+    lastReportAt: string | null;
+}
+
+/** A tracker we hear from that is not assigned to a unit. */
+export interface UnknownDevice {
+    deviceId: string;
+    source: ReportSource;
+    point: Point;
+    lastSeen: string;
+    reports: number;
+}
+
+export interface AvlStatus {
+    enabled: boolean;
+    listeners: ListenerStatus[];
+    unassigned: UnknownDevice[];
+}
+
+export interface AvlUnit {
+    deviceId: string;
+    subjectKind: SubjectKind;
+    subjectId: string;
+    label: string | null;
+    createdAt: string;
+}
+
+export interface NewAvlUnit {
+    deviceId: string;
+    subjectKind: SubjectKind;
+    subjectId: string;
+    label?: string | null;
+}
+
+export interface AvlReport {
+    deviceId?: string;
+    resourceId?: string;
+    teamId?: string;
+    lat: number;
+    lng: number;
+    time?: string;
+    speedKmh?: number;
+    courseDeg?: number;
+    altitudeM?: number;
+}
+
+export interface TrackQuery {
+    subjectKind: SubjectKind;
+    subjectId: string;
+    from?: string | null;
+    to?: string | null;
+}
+
+export interface Track {
+    subjectKind: SubjectKind;
+    subjectId: string;
+    label: string | null;
+    positions: AvlPosition[];
+    distanceKm: number;
+    maxSpeedKmh: number | null;
+    /** The range held more breadcrumbs than were returned. */
+    truncated: boolean;
+}
+
+export interface TrackedUnit {
+    subjectKind: SubjectKind;
+    subjectId: string;
+    label: string | null;
+    firstAt: string;
+    lastAt: string;
+    points: number;
+}
+
+export const AVL_POSITION_EVENT = "avl://position";
+export const AVL_STATUS_EVENT = "avl://status";
+
+export const LISTENER_LABELS: Record<ListenerKind, string> = {
+    udp: "UDP",
+    http: "HTTP gateway",
+    aprs_is: "APRS-IS",
+};
+
+/** `resource:<id>` or `team:<id>`, as used in `/map?track=`. */
+export function unitKey(kind: SubjectKind, id: string): string {
+    return `${kind}:${id}`;
+}
+
+export function parseUnitKey(key: string): { subjectKind: SubjectKind; subjectId: string } | null {
+    const [kind, ...rest] = key.split(":");
+    if ((kind !== "resource" && kind !== "team") || rest.length === 0) return null;
+    return { subjectKind: kind, subjectId: rest.join(":") };
+}
+
+export function getAvlStatus(): Promise<AvlStatus> {
+    return invoke<AvlStatus>("avl_status");
+}
+
+export function getAvlConfig(): Promise<AvlConfig> {
+    return invoke<AvlConfig>("avl_config");
+}
+
+/** Save the listener settings and restart them. */
+export function configureAvl(config: AvlConfig): Promise<AvlStatus> {
+    return invoke<AvlStatus>("avl_configure", { config });
+}
+
+export function listAvlUnits(): Promise<AvlUnit[]> {
+    return invoke<AvlUnit[]>("avl_units");
+}
+
+export function assignAvlUnit(unit: NewAvlUnit): Promise<AvlUnit> {
+    return invoke<AvlUnit>("avl_unit_assign", { unit });
+}
+
+export function removeAvlUnit(deviceId: string): Promise<void> {
+    return invoke("avl_unit_remove", { deviceId });
+}
+
+/** Record a position relayed by hand; `null` if it was not kept. */
+export function reportAvlPosition(report: AvlReport): Promise<AvlPosition | null> {
+    return invoke<AvlPosition | null>("avl_report", { report });
+}
+
+/** Each unit's last position within `maxAgeMinutes` (a day by default). */
+export function getLatestPositions(maxAgeMinutes?: number): Promise<AvlPosition[]> {
+    return invoke<AvlPosition[]>("avl_latest", { maxAgeMinutes: maxAgeMinutes ?? null });
+}
+
+export function getTrack(query: TrackQuery): Promise<Track> {
+    return invoke<Track>("avl_track", { query });
+}
+
+export function listTrackedUnits(): Promise<TrackedUnit[]> {
+    return invoke<TrackedUnit[]>("avl_tracked");
+}
+
+/**
+ * Where the unit was at `time` (ms), interpolated between the breadcrumbs
+ * either side. Positions must be in time order.
+ */
+export function positionAt(positions: AvlPosition[], time: number): Point | null {
+    if (positions.length === 0) return null;
+    const hi = positions.findIndex((p) => Date.parse(p.reportedAt) >= time);
+    if (hi === -1) return positions[positions.length - 1].point;
+    if (hi === 0) return positions[0].point;
+    const a = positions[hi - 1];
+    const b = positions[hi];
+    const ta = Date.parse(a.reportedAt);
+    const tb = Date.parse(b.reportedAt);
+    const f = tb > ta ? (time - ta) / (tb - ta) : 1;
+    return {
+        lat: a.point.lat + (b.point.lat - a.point.lat) * f,
+        lng: a.point.lng + (b.point.lng - a.point.lng) * f,
+    };
+}
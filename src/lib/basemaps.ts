@@ -0,0 +1,46 @@
+import { convertFileSrc, invoke } from "@tauri-apps/api/core";
+
+/**
+ * Basemaps imported from MBTiles/PMTiles archives (`src-tauri/src/basemaps.rs`),
+ * served through the `basemap://` protocol. The active one replaces the OSM
+ * layer on every map.
+ */
+
+export interface Basemap {
+    id: string;
+    name: string;
+    kind: "mbtiles" | "pmtiles";
+    format: string;
+    minZoom: number;
+    maxZoom: number;
+    /** `[west, south, east, north]`. */
+    bounds: [number, number, number, number] | null;
+    attribution: string | null;
+    sizeBytes: number;
+    importedAt: string;
+    active: boolean;
+}
+
+export const BASEMAPS_CHANGED_EVENT = "basemaps://changed";
+
+/** Leaflet URL template for an imported basemap. */
+export function basemapUrl(id: string): string {
+    return `${convertFileSrc("", "basemap")}${id}/{z}/{x}/{y}`;
+}
+
+export function listBasemaps(): Promise<Basemap[]> {
+    return invoke<Basemap[]>("basemaps_list");
+}
+
+export function importBasemap(path: string, name?: string): Promise<Basemap> {
+    return invoke<Basemap>("basemap_import", { path, name: name ?? null });
+}
+
+/** Use basemap `id`, or the online OSM layer with `null`. */
+export function activateBasemap(id: string | null): Promise<void> {
+    return invoke("basemap_activate", { id });
+}
+
+export function removeBasemap(id: string): Promise<void> {
+    return invoke("basemap_remove", { id });
+}
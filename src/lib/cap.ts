@@ -0,0 +1,178 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { Point } from "@/lib/geo";
+import type { ExportReport } from "@/lib/interchange";
+import type { SeverityLevel } from "@/types/enums";
+
+/**
+ * OASIS Common Alerting Protocol 1.2 export and import for alerts
+ * (`src-tauri/src/cap.rs`). Exports are checked against the CAP schema
+ * before they are written; imports are validated and mapped into alert
+ * drafts for review. Partner feeds and a drop folder are watched in the
+ * background (`src-tauri/src/cap_feed.rs`), queueing relevant messages as
+ * suggested alerts or incidents.
+ */
+
+export interface CapConfig {
+    /** How partners know us, e.g. `alerts@agency.example`. */
+    sender: string | null;
+    /** Agency name shown to the public. */
+    senderName: string | null;
+    /** Language of exported alerts, and the one preferred on import. */
+    language: string;
+    /** Partner CAP feeds (Atom or RSS, or a single CAP file) to poll. */
+    feeds: string[];
+    /** Folder partners drop CAP files into. */
+    dropFolder: string | null;
+    pollMinutes: number;
+    /** Incoming messages less severe than this are not suggested. */
+    minSeverity: SeverityLevel;
+    /** Only messages whose area reaches this circle are suggested. */
+    region: { center: Point; radiusKm: number } | null;
+}
+
+/** Where a message breaks the schema, or why it can't become an alert. */
+export interface CapIssue {
+    /** Element path such as `alert/info[1]/urgency`; empty for the whole file. */
+    path: string;
+    message: string;
+}
+
+export interface CapReference {
+    sender: string;
+    identifier: string;
+    sent: string;
+}
+
+export interface CapDraft {
+    identifier: string;
+    sender: string;
+    sent: string;
+    status: "Actual" | "Exercise" | "System" | "Test" | "Draft";
+    msgType: "Alert" | "Update" | "Cancel";
+    references: CapReference[];
+    event: string;
+    categories: string[];
+    language: string;
+    /** `alerts` row with a fresh id, missing `created_by`. */
+    record: Record<string, unknown>;
+    /** Where the alert is not exactly what the message said. */
+    warnings: string[];
+}
+
+export interface CapImport {
+    /** `null` for pasted text. */
+    fileName: string | null;
+    draft: CapDraft | null;
+    /** Schema and mapping errors; empty when there is a draft. */
+    errors: CapIssue[];
+}
+
+// ─── Watching ───────────────────────────────────────────────────
+
+export const CAP_REVIEW_EVENT = "cap://review";
+export const CAP_STATUS_EVENT = "cap://status";
+
+export interface CapSourceStatus {
+    kind: "feed" | "folder";
+    /** Feed URL or folder path. */
+    location: string;
+    lastCheckedAt: string | null;
+    error: string | null;
+    /** Messages queued for review since the watcher started. */
+    suggested: number;
+    /** Messages already seen, or not relevant. */
+    skipped: number;
+    /** Messages that are not valid CAP. */
+    rejected: number;
+    /** Feed entries whose message could not be fetched; retried next poll. */
+    unreachable: number;
+    /** Why the latest message was rejected or entry could not be fetched. */
+    lastProblem: string | null;
+}
+
+export interface CapWatchStatus {
+    watching: boolean;
+    sources: CapSourceStatus[];
+    /** Messages waiting for review. */
+    pending: number;
+}
+
+export type CapSuggestion = "alert" | "incident";
+export type CapReviewStatus = "pending" | "accepted" | "dismissed" | "superseded";
+
+export interface CapRecordRef {
+    table: "alerts" | "incidents";
+    id: string;
+}
+
+export interface CapReviewItem {
+    id: string;
+    /** Feed URL or dropped file it came from. */
+    source: string;
+    suggestion: CapSuggestion;
+    draft: CapDraft;
+    /** Record an earlier message of the thread became; accepting updates or closes it. */
+    target: CapRecordRef | null;
+    status: CapReviewStatus;
+    supersededBy: string | null;
+    /** Record accepting it created or updated. */
+    record: CapRecordRef | null;
+    receivedAt: string;
+    decidedAt: string | null;
+}
+
+export function getCapWatchStatus(): Promise<CapWatchStatus> {
+    return invoke<CapWatchStatus>("cap_watch_status");
+}
+
+/** Check every feed and the drop folder now. */
+export function pollCapSources(): Promise<CapWatchStatus> {
+    return invoke<CapWatchStatus>("cap_watch_poll");
+}
+
+export function listCapReview(status?: CapReviewStatus, limit?: number): Promise<CapReviewItem[]> {
+    return invoke<CapReviewItem[]>("cap_review_list", { status, limit });
+}
+
+/**
+ * Queue the alert or incident a message becomes (or the update to the
+ * record its thread became). `kind` overrides the suggestion.
+ */
+export function acceptCapReview(id: string, createdBy: string, kind?: CapSuggestion): Promise<CapReviewItem> {
+    return invoke<CapReviewItem>("cap_review_accept", { id, kind, createdBy });
+}
+
+export function dismissCapReview(id: string): Promise<CapReviewItem> {
+    return invoke<CapReviewItem>("cap_review_dismiss", { id });
+}
+
+// ─── Exchange ───────────────────────────────────────────────────
+
+export function getCapConfig(): Promise<CapConfig> {
+    return invoke<CapConfig>("cap_config");
+}
+
+export function configureCap(config: CapConfig): Promise<CapConfig> {
+    return invoke<CapConfig>("cap_configure", { config });
+}
+
+/**
+ * Export cached alerts as CAP: one to a file, several to a folder. `null`
+ * if the dialog was cancelled.
+ */
+export function exportCap(ids: string[]): Promise<ExportReport | null> {
+    return invoke<ExportReport | null>("cap_export", { ids });
+}
+
+/** Read CAP files the user picks; `null` if the dialog was cancelled. */
+export function importCap(): Promise<CapImport[] | null> {
+    return invoke<CapImport[] | null>("cap_import");
+}
+
+export function parseCap(xml: string): Promise<CapImport> {
+    return invoke<CapImport>("cap_parse", { xml });
+}
+
+export function describeCapIssue(issue: CapIssue): string {
+    return issue.path ? `${issue.path}: ${issue.message}` : issue.message;
+}
@@ -0,0 +1,65 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { Point } from "@/lib/geo";
+
+/**
+ * Coordinate notations: parse whatever was typed or radioed in, and write
+ * positions the way the user prefers (`src-tauri/src/coords.rs`).
+ */
+
+export type Notation = "decimal" | "dms" | "ddm" | "utm" | "mgrs" | "usng" | "plus_code";
+
+export const NOTATIONS: { value: Notation; label: string; example: string }[] = [
+    { value: "decimal", label: "Decimal degrees", example: "40.712800, -74.006000" },
+    { value: "dms", label: "Degrees, minutes, seconds", example: "40°42'46.1\"N 074°00'21.6\"W" },
+    { value: "ddm", label: "Degrees, decimal minutes", example: "40°42.768'N 074°00.360'W" },
+    { value: "utm", label: "UTM", example: "18T 583959 4507351" },
+    { value: "mgrs", label: "MGRS", example: "18TWL8395907350" },
+    { value: "usng", label: "USNG", example: "18T WL 83959 07350" },
+    { value: "plus_code", label: "Plus code", example: "87G7PX7V+4JC" },
+];
+
+export interface ParsedCoordinate {
+    point: Point;
+    /** What the text was written in. */
+    notation: Notation;
+}
+
+export interface FormattedCoordinate {
+    notation: Notation;
+    /** `null` where the notation doesn't reach, e.g. MGRS near the poles. */
+    text: string | null;
+}
+
+export interface CoordinatePreference {
+    notation: Notation;
+}
+
+export function notationLabel(notation: Notation): string {
+    return NOTATIONS.find((n) => n.value === notation)?.label ?? notation;
+}
+
+/**
+ * Read a position in any supported notation. `near` (e.g. the map centre)
+ * recovers short plus codes given without a locality.
+ */
+export function parseCoordinate(text: string, near?: Point): Promise<ParsedCoordinate> {
+    return invoke<ParsedCoordinate>("coords_parse", { text, near });
+}
+
+/** Write positions in `notation`, or the preferred one when omitted. */
+export function formatCoordinates(points: Point[], notation?: Notation): Promise<string[]> {
+    return invoke<string[]>("coords_format", { points, notation });
+}
+
+/** One position in every notation. */
+export function convertCoordinate(point: Point): Promise<FormattedCoordinate[]> {
+    return invoke<FormattedCoordinate[]>("coords_convert", { point });
+}
+
+export function getCoordinatePreference(): Promise<CoordinatePreference> {
+    return invoke<CoordinatePreference>("coords_preference");
+}
+
+export function setCoordinatePreference(preference: CoordinatePreference): Promise<void> {
+    return invoke("coords_preference_set", { preference });
+}
@@ -0,0 +1,65 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { Point } from "@/lib/geo";
+
+/**
+ * Evacuation route length, travel times and geometry checks computed from
+ * the waypoints (`src-tauri/src/evacuation.rs`).
+ */
+
+export type TravelMode = "walking" | "driving" | "bus";
+
+export const TRAVEL_MODES: { value: TravelMode; label: string }[] = [
+    { value: "walking", label: "Walking" },
+    { value: "driving", label: "Driving" },
+    { value: "bus", label: "Bus" },
+];
+
+export interface SpeedProfile {
+    speedKmh: number;
+    /** Fixed time on top of travel: loading, boarding, marshalling. */
+    overheadMinutes: number;
+}
+
+export type SpeedProfiles = Record<TravelMode, SpeedProfile>;
+
+/** Waypoint indexes refer to the route's `waypoints`. */
+export type RouteIssue =
+    | { kind: "too_few_waypoints" }
+    | { kind: "invalid_waypoint"; index: number }
+    | { kind: "repeated_waypoint"; index: number }
+    | { kind: "zero_length" }
+    | { kind: "self_intersection"; first: number; second: number };
+
+export interface RouteMetrics {
+    distanceKm: number;
+    /** Whole minutes per travel mode, rounded up. */
+    minutes: Record<TravelMode, number>;
+    issues: RouteIssue[];
+}
+
+export function routeMetrics(waypoints: Point[]): Promise<RouteMetrics> {
+    return invoke<RouteMetrics>("evacuation_route_metrics", { waypoints });
+}
+
+export function getSpeedProfiles(): Promise<SpeedProfiles> {
+    return invoke<SpeedProfiles>("evacuation_speed_profiles");
+}
+
+export function setSpeedProfiles(profiles: SpeedProfiles): Promise<void> {
+    return invoke("evacuation_speed_profiles_set", { profiles });
+}
+
+export function describeIssue(issue: RouteIssue): string {
+    switch (issue.kind) {
+        case "too_few_waypoints":
+            return "A route needs at least two waypoints";
+        case "invalid_waypoint":
+            return `Point ${issue.index + 1} is not a valid position`;
+        case "repeated_waypoint":
+            return `Point ${issue.index + 1} repeats the point before it`;
+        case "zero_length":
+            return "The route has no length";
+        case "self_intersection":
+            return `The route crosses itself (after points ${issue.first + 1} and ${issue.second + 1})`;
+    }
+}
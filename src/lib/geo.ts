@@ -0,0 +1,72 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { BoundingBox } from "@/lib/tiles";
+
+/**
+ * Radius, bounding-box and nearest-neighbour queries over the locally cached
+ * incidents, resources, teams and SOS broadcasts (`src-tauri/src/geo.rs`).
+ * Distances are great-circle kilometres.
+ */
+
+export type GeoEntity = "incident" | "resource" | "team" | "sos";
+
+export interface Point {
+    lat: number;
+    lng: number;
+}
+
+export interface GeoHit<T = Record<string, unknown>> {
+    entity: GeoEntity;
+    id: string;
+    point: Point;
+    /** From the query center; `null` for bounding-box queries. */
+    distanceKm: number | null;
+    record: T;
+}
+
+/** Center a query on a position or on a cached incident's location. */
+export type GeoOrigin = { center: Point } | { incidentId: string };
+
+type Filters = Record<string, string | number | boolean | null>;
+
+export function geoDistance(from: Point, to: Point): Promise<number> {
+    return invoke<number>("geo_distance", { from, to });
+}
+
+/**
+ * Records within `radiusKm`, nearest first. With an `incidentId` origin the
+ * radius defaults to the incident's `affected_radius_km`.
+ */
+export function withinRadius<T>(
+    entity: GeoEntity,
+    origin: GeoOrigin,
+    options: { radiusKm?: number; eq?: Filters; limit?: number } = {}
+): Promise<GeoHit<T>[]> {
+    return invoke<GeoHit<T>[]>("geo_within_radius", {
+        query: { entity, ...origin, ...options },
+    });
+}
+
+export function withinBounds<T>(
+    entity: GeoEntity,
+    bounds: BoundingBox,
+    options: { eq?: Filters; limit?: number } = {}
+): Promise<GeoHit<T>[]> {
+    return invoke<GeoHit<T>[]>("geo_within_bounds", {
+        query: { entity, bounds, ...options },
+    });
+}
+
+export function nearest<T>(
+    entity: GeoEntity,
+    origin: GeoOrigin,
+    k: number,
+    options: { maxDistanceKm?: number; eq?: Filters } = {}
+): Promise<GeoHit<T>[]> {
+    return invoke<GeoHit<T>[]>("geo_nearest", {
+        query: { entity, ...origin, k, ...options },
+    });
+}
+
+export function formatDistance(km: number): string {
+    return km < 1 ? `${Math.round(km * 1000)} m` : `${km.toFixed(1)} km`;
+}
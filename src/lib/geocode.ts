@@ -0,0 +1,85 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { Point } from "@/lib/geo";
+
+/**
+ * Offline geocoding against an imported GeoNames or OpenStreetMap place
+ * gazetteer (`src-tauri/src/gazetteer.rs`).
+ */
+
+export interface Place {
+    id: number;
+    name: string;
+    /** `city`, `town`, `village`, `hamlet`, `suburb`, … */
+    kind: string;
+    admin1: string | null;
+    admin2: string | null;
+    country: string | null;
+    population: number;
+    point: Point;
+}
+
+export interface ReverseHit {
+    place: Place;
+    distanceKm: number;
+    /** "Name, Admin1" or "12 km NE of Name, Admin1". */
+    label: string;
+}
+
+export interface GazetteerStatus {
+    source: string;
+    format: "geonames" | "osm";
+    places: number;
+    importedAt: string;
+}
+
+export const GAZETTEER_IMPORTED_EVENT = "gazetteer://imported";
+
+/** Load a GeoNames `.txt` dump or the places of an `.osm.pbf` extract. */
+export function importGazetteer(path: string): Promise<GazetteerStatus> {
+    return invoke<GazetteerStatus>("gazetteer_import", { path });
+}
+
+export function getGazetteerStatus(): Promise<GazetteerStatus | null> {
+    return invoke<GazetteerStatus | null>("gazetteer_status");
+}
+
+export function clearGazetteer(): Promise<void> {
+    return invoke("gazetteer_clear");
+}
+
+/** Nearest named place, or `null` when nothing is within 50 km. */
+export function reverseGeocode(point: Point): Promise<ReverseHit | null> {
+    return invoke<ReverseHit | null>("geocode_reverse", { point });
+}
+
+/** Autocomplete place names, preferring those close to `near`. */
+export function searchPlaces(text: string, near?: Point, limit?: number): Promise<Place[]> {
+    return invoke<Place[]>("geocode_search", { query: { text, near, limit } });
+}
+
+/** "Name, Admin1, CC" for a search result. */
+export function placeLabel(place: Place): string {
+    const parts = [place.name];
+    for (const part of [place.admin1, place.country]) {
+        if (part && !parts.includes(part)) parts.push(part);
+    }
+    return parts.join(", ");
+}
+
+/**
+ * Fill in `location_name` from the gazetteer when a record has coordinates
+ * but no name. Never fails; without a gazetteer the input comes back as is.
+ */
+export async function withLocationName<
+    T extends { latitude?: number | null; longitude?: number | null; location_name?: string | null },
+>(input: T): Promise<T> {
+    if (input.location_name?.trim() || input.latitude == null || input.longitude == null) {
+        return input;
+    }
+    try {
+        const hit = await reverseGeocode({ lat: input.latitude, lng: input.longitude });
+        return hit ? { ...input, location_name: hit.label } : input;
+    } catch {
+        return input;
+    }
+}
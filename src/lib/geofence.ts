@@ -0,0 +1,80 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { Point } from "@/lib/geo";
+
+/**
+ * Geofences and the alerts they raise (`src-tauri/src/geofence.rs`). Active
+ * incidents are zones (centre plus `affected_radius_km`, or drawn polygons);
+ * SOS broadcasts inside one and resources outside their staging area raise
+ * an alert once, with a native notification.
+ */
+
+export type GeofenceShape =
+    | { type: "circle"; center: Point; radiusKm: number }
+    | { type: "polygon"; points: Point[] };
+
+export type GeofenceKind = "incident_zone" | "staging";
+
+export interface Geofence {
+    id: string;
+    kind: GeofenceKind;
+    /** Incident for zones, resource for staging areas. */
+    subjectId: string;
+    name: string | null;
+    shape: GeofenceShape;
+    createdAt: string;
+}
+
+export type NewGeofence = Pick<Geofence, "kind" | "subjectId" | "name" | "shape">;
+
+export interface GeofenceZone {
+    incidentId: string;
+    title: string;
+    severity: string | null;
+    shape: GeofenceShape;
+}
+
+export type GeofenceAlertKind = "sos_in_zone" | "resource_left_staging";
+
+export interface GeofenceAlert {
+    id: number;
+    kind: GeofenceAlertKind;
+    /** The SOS broadcast or resource. */
+    subjectId: string;
+    /** The incident for SOS alerts, the staging geofence for resources. */
+    zoneId: string;
+    title: string;
+    body: string;
+    point: Point | null;
+    raisedAt: string;
+    clearedAt: string | null;
+}
+
+export const GEOFENCE_ALERT_EVENT = "geofence://alert";
+
+/** Check a Realtime record before the next mirror sync picks it up. */
+export function checkGeofences(
+    table: "sos_broadcasts" | "resources",
+    record: Record<string, unknown>
+): Promise<GeofenceAlert[]> {
+    return invoke<GeofenceAlert[]>("geofence_check", { table, record });
+}
+
+export function geofenceZones(): Promise<GeofenceZone[]> {
+    return invoke<GeofenceZone[]>("geofence_zones");
+}
+
+export function listGeofences(subjectId?: string): Promise<Geofence[]> {
+    return invoke<Geofence[]>("geofences_list", { subjectId: subjectId ?? null });
+}
+
+export function addGeofence(fence: NewGeofence): Promise<Geofence> {
+    return invoke<Geofence>("geofence_add", { fence });
+}
+
+export function removeGeofence(id: string): Promise<void> {
+    return invoke("geofence_remove", { id });
+}
+
+export function geofenceAlerts(limit?: number): Promise<GeofenceAlert[]> {
+    return invoke<GeofenceAlert[]>("geofence_alerts", { limit: limit ?? null });
+}
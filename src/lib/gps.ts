@@ -0,0 +1,125 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { Point } from "@/lib/geo";
+
+/**
+ * The command post's own position from a GPS receiver: NMEA over serial,
+ * TCP or UDP, a local gpsd, or a replayed log (`src-tauri/src/gps.rs`).
+ */
+
+export type GpsSource =
+    | { type: "serial"; path: string; baudRate: number }
+    | { type: "tcp"; address: string }
+    | { type: "udp"; bind: string }
+    | { type: "gpsd"; address: string | null }
+    | { type: "replay"; path: string; speed: number; repeat: boolean };
+
+export type GpsSourceType = GpsSource["type"];
+
+export type FixQuality =
+    | "no_fix"
+    | "gps"
+    | "dgps"
+    | "pps"
+    | "rtk_fixed"
+    | "rtk_float"
+    | "estimated"
+    | "manual"
+    | "simulated";
+
+export interface GpsFix {
+    point: Point;
+    altitudeM: number | null;
+    /** Estimated horizontal error. */
+    accuracyM: number | null;
+    quality: FixQuality;
+    mode: "none" | "2d" | "3d";
+    satellites: number | null;
+    hdop: number | null;
+    speedKmh: number | null;
+    courseDeg: number | null;
+    /** UTC time from the receiver. */
+    time: string | null;
+    receivedAt: string;
+}
+
+export type GpsState = "stopped" | "connecting" | "searching" | "fixed" | "error";
+
+export interface GpsStatus {
+    source: GpsSource | null;
+    state: GpsState;
+    error: string | null;
+    /** The last fix, even once stale. */
+    fix: GpsFix | null;
+}
+
+export interface GpsConfig {
+    source: GpsSource | null;
+    enabled: boolean;
+}
+
+export interface SerialPortInfo {
+    path: string;
+    description: string | null;
+}
+
+export const GPS_FIX_EVENT = "gps://fix";
+export const GPS_STATUS_EVENT = "gps://status";
+
+export const GPS_SOURCE_TYPES: { value: GpsSourceType; label: string }[] = [
+    { value: "serial", label: "Serial / USB receiver" },
+    { value: "gpsd", label: "gpsd" },
+    { value: "tcp", label: "NMEA over TCP" },
+    { value: "udp", label: "NMEA over UDP" },
+    { value: "replay", label: "Replay NMEA log" },
+];
+
+export const FIX_QUALITY_LABELS: Record<FixQuality, string> = {
+    no_fix: "No fix",
+    gps: "GPS",
+    dgps: "DGPS",
+    pps: "PPS",
+    rtk_fixed: "RTK fixed",
+    rtk_float: "RTK float",
+    estimated: "Dead reckoning",
+    manual: "Manual",
+    simulated: "Simulated",
+};
+
+/** Connect to a receiver; it is started again on the next launch. */
+export function startGps(source: GpsSource): Promise<GpsStatus> {
+    return invoke<GpsStatus>("gps_start", { source });
+}
+
+export function stopGps(): Promise<void> {
+    return invoke("gps_stop");
+}
+
+export function getGpsStatus(): Promise<GpsStatus> {
+    return invoke<GpsStatus>("gps_status");
+}
+
+/** Where we are now, or `null` without a fresh fix. */
+export function getGpsFix(): Promise<GpsFix | null> {
+    return invoke<GpsFix | null>("gps_fix");
+}
+
+export function getGpsConfig(): Promise<GpsConfig> {
+    return invoke<GpsConfig>("gps_config");
+}
+
+export function listSerialPorts(): Promise<SerialPortInfo[]> {
+    return invoke<SerialPortInfo[]>("gps_serial_ports");
+}
+
+/** Fill in `latitude`/`longitude` from the receiver when a record has none. */
+export async function withGpsPosition<T extends { latitude?: number | null; longitude?: number | null }>(
+    input: T,
+): Promise<T> {
+    if (input.latitude != null && input.longitude != null) return input;
+    try {
+        const fix = await getGpsFix();
+        return fix ? { ...input, latitude: fix.point.lat, longitude: fix.point.lng } : input;
+    } catch {
+        return input;
+    }
+}
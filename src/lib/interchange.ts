@@ -0,0 +1,72 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { NewGeofence } from "@/lib/geofence";
+
+/**
+ * GeoJSON, KML and GPX exchange with partner agencies
+ * (`src-tauri/src/interchange.rs`). Both directions go through the native
+ * file dialog; cancelling it resolves to `null`.
+ */
+
+export type InterchangeFormat = "geojson" | "kml" | "gpx";
+
+export type InterchangeKind = "incident" | "resource" | "sos" | "evacuation_route";
+
+export const INTERCHANGE_FORMATS: { value: InterchangeFormat; label: string }[] = [
+    { value: "geojson", label: "GeoJSON" },
+    { value: "kml", label: "KML" },
+    { value: "gpx", label: "GPX" },
+];
+
+/** Mirror table each kind is stored in. */
+export const INTERCHANGE_TABLES: Record<InterchangeKind, string> = {
+    incident: "incidents",
+    resource: "resources",
+    sos: "sos_broadcasts",
+    evacuation_route: "evacuation_routes",
+};
+
+export interface FeatureError {
+    /** Position of the feature in the file, from 0. */
+    index: number;
+    name: string | null;
+    message: string;
+}
+
+export interface ImportReport {
+    fileName: string;
+    format: InterchangeFormat;
+    /** New records with fresh ids, missing `created_by` / `sender_id`. */
+    records: Record<string, unknown>[];
+    /** Polygons of the imported incidents and resources, kept as geofences. */
+    zones: NewGeofence[];
+    errors: FeatureError[];
+}
+
+export interface ExportReport {
+    path: string;
+    exported: number;
+    /** Records left out because they have no location. */
+    skipped: number;
+}
+
+export function importFeatures(kind: InterchangeKind): Promise<ImportReport | null> {
+    return invoke<ImportReport | null>("interchange_import", { kind });
+}
+
+/** Export every cached record of `kind`, or just `ids`. */
+export function exportFeatures(
+    kind: InterchangeKind,
+    format: InterchangeFormat,
+    ids?: string[]
+): Promise<ExportReport | null> {
+    return invoke<ExportReport | null>("interchange_export", {
+        kind,
+        format,
+        ids: ids ?? null,
+    });
+}
+
+export function describeFeatureError(error: FeatureError): string {
+    const which = error.name ? `"${error.name}"` : `Feature ${error.index + 1}`;
+    return `${which}: ${error.message}`;
+}
@@ -0,0 +1,53 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { GeoEntity, Point } from "@/lib/geo";
+
+/**
+ * Viewport clustering and heatmap cells computed from the local cache
+ * (`src-tauri/src/cluster.rs`), so the map only draws what is visible.
+ */
+
+export interface Bounds {
+    west: number;
+    south: number;
+    east: number;
+    north: number;
+}
+
+export interface MapQuery {
+    /** Clamped to the world on the Rust side. */
+    bounds: Bounds;
+    zoom: number;
+    entities: GeoEntity[];
+    /** Only open incidents, usable resources and live SOS broadcasts. */
+    activeOnly?: boolean;
+    eq?: Record<string, string | number | boolean | null>;
+    heatmap?: boolean;
+}
+
+export interface Cluster {
+    entity: GeoEntity;
+    /** Mean position of the members. */
+    point: Point;
+    count: number;
+    bounds: Bounds;
+    /** Highest severity among the members. */
+    severity: string | null;
+    /** The record itself when `count` is 1. */
+    record: Record<string, unknown> | null;
+}
+
+export interface HeatCell {
+    point: Point;
+    weight: number;
+}
+
+export interface MapAggregate {
+    clusters: Cluster[];
+    heat: HeatCell[];
+    maxWeight: number;
+    total: number;
+}
+
+export function mapAggregate(query: MapQuery): Promise<MapAggregate> {
+    return invoke<MapAggregate>("map_aggregate", { query });
+}
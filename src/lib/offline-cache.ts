@@ -0,0 +1,90 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/**
+ * Read-through fallback to the local mirror kept by the Rust side
+ * (`src-tauri/src/mirror.rs`), so the app still shows the last known state
+ * when Supabase can't be reached.
+ */
+
+export type MirroredTable =
+    | "incidents"
+    | "resources"
+    | "tasks"
+    | "teams"
+    | "alerts"
+    | "evacuation_routes"
+    | "sos_broadcasts"
+    | "incident_updates"
+    | "messages"
+    | "documents";
+
+/** Emitted after every mirror pull, with a report per table. */
+export const MIRROR_SYNCED_EVENT = "mirror://synced";
+
+export interface MirrorQuery {
+    table: MirroredTable;
+    /** Column equality; null and empty values are ignored */
+    eq?: Record<string, string | number | boolean | null | undefined>;
+    /** Case-insensitive substring match over the table's text columns */
+    search?: string;
+    /** Defaults to `created_at` */
+    orderBy?: string;
+    ascending?: boolean;
+    limit?: number;
+}
+
+export interface MirrorStatus {
+    table: MirroredTable;
+    records: number;
+    syncedAt: string | null;
+}
+
+/** True when a Supabase error means we couldn't reach the server at all. */
+export function isOfflineError(error: { message?: string } | null | undefined): boolean {
+    if (typeof navigator !== "undefined" && !navigator.onLine) return true;
+    return /failed to fetch|network|load failed/i.test(error?.message ?? "");
+}
+
+export function queryMirror<T>(query: MirrorQuery): Promise<T[]> {
+    return invoke<T[]>("mirror_query", { query });
+}
+
+export function getMirrored<T>(table: MirroredTable, id: string): Promise<T | null> {
+    return invoke<T | null>("mirror_get", { table, id });
+}
+
+export function getMirrorStatus(): Promise<MirrorStatus[]> {
+    return invoke<MirrorStatus[]>("mirror_status");
+}
+
+// ─── Full-text search ───────────────────────────────────────────
+
+export type SearchEntity = "incidents" | "incident_updates" | "messages" | "tasks" | "documents";
+
+export interface SearchQuery {
+    text: string;
+    /** All entities when empty */
+    entities?: SearchEntity[];
+    /** Only records created at or after this ISO timestamp */
+    since?: string;
+    limit?: number;
+}
+
+export interface SearchHit {
+    entity: SearchEntity;
+    id: string;
+    /** Incident of an update, task or document; channel of a message */
+    parentId: string | null;
+    createdAt: string | null;
+    /** Title with matches wrapped in `<mark>` */
+    title: string;
+    /** Best matching fragment, matches wrapped in `<mark>` */
+    snippet: string;
+    /** Higher is better */
+    score: number;
+}
+
+/** Ranked full-text search over the locally mirrored records. */
+export function searchLocal(query: SearchQuery): Promise<SearchHit[]> {
+    return invoke<SearchHit[]>("search", { query });
+}
@@ -0,0 +1,52 @@
+import type { QueryClient } from "@tanstack/react-query";
+import { getMirrored, type MirroredTable } from "@/lib/offline-cache";
+import { useOfflineStore, type ConflictStrategy, type DrainReport } from "@/stores/offline-store";
+
+/**
+ * The record as the user last saw it: from whatever query has it cached,
+ * else from the local mirror.
+ */
+async function findBase(
+    queryClient: QueryClient,
+    table: MirroredTable,
+    id: string
+): Promise<Record<string, unknown> | null> {
+    for (const [, data] of queryClient.getQueriesData<unknown>({ queryKey: [table] })) {
+        const rows = Array.isArray(data) ? data : [data];
+        const hit = rows.find(
+            (row): row is Record<string, unknown> =>
+                !!row && typeof row === "object" && (row as { id?: unknown }).id === id
+        );
+        if (hit) return hit;
+    }
+    return getMirrored<Record<string, unknown>>(table, id);
+}
+
+/**
+ * Queue an edit based on the version the user was looking at, then try to
+ * sync it. The server only takes it if nobody changed the record since;
+ * otherwise it is merged or set aside for review.
+ */
+export async function queueUpdate(
+    queryClient: QueryClient,
+    table: MirroredTable,
+    id: string,
+    patch: Record<string, unknown>,
+    strategy: ConflictStrategy = "merge"
+): Promise<DrainReport> {
+    const base = await findBase(queryClient, table, id).catch(() => null);
+    const baseUpdatedAt = typeof base?.updated_at === "string" ? base.updated_at : null;
+
+    const { enqueue, drain, refreshConflicts } = useOfflineStore.getState();
+    await enqueue({
+        table,
+        operation: "update",
+        payload: { ...patch, id },
+        baseUpdatedAt,
+        base,
+        strategy,
+    });
+    const report = await drain();
+    if (report.conflicts > 0) await refreshConflicts();
+    return report;
+}
@@ -0,0 +1,45 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { Point } from "@/lib/geo";
+import type { EvacuationRoute } from "@/types/database";
+
+/**
+ * Offline road routing over an imported OpenStreetMap extract
+ * (`src-tauri/src/routing.rs`).
+ */
+
+export type RoutingMode = "driving" | "walking";
+
+export interface RoutingStatus {
+    source: string;
+    importedAt: string;
+    nodes: number;
+    edges: number;
+    /** `[west, south, east, north]`. */
+    bounds: [number, number, number, number] | null;
+}
+
+/** The `EvacuationRoute` fields it fills in, named as they are there. */
+export type RoutePlan = Pick<EvacuationRoute, "waypoints"> & {
+    distance_km: number;
+    estimated_time_minutes: number;
+};
+
+export const ROUTING_IMPORTED_EVENT = "routing://imported";
+
+/** Build the road network from an `.osm.pbf` extract. Takes a while for big regions. */
+export function importRoadNetwork(path: string): Promise<RoutingStatus> {
+    return invoke<RoutingStatus>("routing_import", { path });
+}
+
+export function getRoutingStatus(): Promise<RoutingStatus | null> {
+    return invoke<RoutingStatus | null>("routing_status");
+}
+
+export function clearRoadNetwork(): Promise<void> {
+    return invoke("routing_clear");
+}
+
+/** Shortest route by road through `stops`, never entering an `avoid` polygon. */
+export function routeByRoad(stops: Point[], mode: RoutingMode, avoid: Point[][] = []): Promise<RoutePlan> {
+    return invoke<RoutePlan>("routing_route", { request: { stops, mode, avoid } });
+}
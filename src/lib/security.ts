@@ -0,0 +1,55 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/**
+ * Encryption at rest for the local database and cached files
+ * (`src-tauri/src/security.rs`). The key lives in the OS keychain, or is
+ * wrapped with a passphrase the user types at startup.
+ */
+
+export type KeyMode = "device" | "passphrase";
+
+export interface SecurityStatus {
+    encrypted: boolean;
+    unlocked: boolean;
+    mode: KeyMode | null;
+}
+
+export const LOCK_EVENT = "security://lock-changed";
+
+export function getSecurityStatus(): Promise<SecurityStatus> {
+    return invoke<SecurityStatus>("security_status");
+}
+
+export function unlock(passphrase: string): Promise<void> {
+    return invoke("security_unlock", { passphrase });
+}
+
+export function lock(): Promise<void> {
+    return invoke("security_lock");
+}
+
+export function setPassphrase(passphrase: string): Promise<void> {
+    return invoke("security_set_passphrase", { passphrase });
+}
+
+export function switchToDeviceKey(): Promise<void> {
+    return invoke("security_use_device_key");
+}
+
+export function rotateKey(passphrase?: string): Promise<void> {
+    return invoke("security_rotate_key", { passphrase: passphrase ?? null });
+}
+
+/** Store bytes in the app cache, sealed with a key derived from the database key. */
+export function writeSecureCache(name: string, data: Uint8Array): Promise<void> {
+    return invoke("secure_cache_write", { name, data: Array.from(data) });
+}
+
+export async function readSecureCache(name: string): Promise<Uint8Array | null> {
+    const data = await invoke<number[] | null>("secure_cache_read", { name });
+    return data ? new Uint8Array(data) : null;
+}
+
+export function deleteSecureCache(name: string): Promise<void> {
+    return invoke("secure_cache_delete", { name });
+}
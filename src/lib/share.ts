@@ -0,0 +1,33 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/**
+ * Signed `disasterconnect://` links to records, with QR codes for reports and
+ * printed briefings (`src-tauri/src/share.rs`).
+ */
+
+export type ShareEntity = "incident" | "task" | "evacuation_route" | "alert";
+
+export interface SharedLink {
+    url: string;
+    expiresAt: string | null;
+    qrSvg: string;
+    /** Base64 PNG */
+    qrPng: string;
+}
+
+export function createShareLink(
+    entity: ShareEntity,
+    id: string,
+    expiresInDays?: number
+): Promise<SharedLink> {
+    return invoke<SharedLink>("share_link_create", {
+        entity,
+        id,
+        expiresInDays: expiresInDays ?? null,
+    });
+}
+
+/** `data:` URL for the PNG, e.g. for jsPDF's `addImage`. */
+export function qrPngDataUrl(link: SharedLink): string {
+    return `data:image/png;base64,${link.qrPng}`;
+}
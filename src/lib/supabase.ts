@@ -1,42 +1,78 @@
 import { createClient } from "@supabase/supabase-js";
 import { load, type Store } from "@tauri-apps/plugin-store";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { LOCK_EVENT, type SecurityStatus } from "@/lib/security";
 
 const supabaseUrl = import.meta.env.VITE_SUPABASE_URL;
 const supabaseAnonKey = import.meta.env.VITE_SUPABASE_ANON_KEY;
 
-// Lazy-init Tauri store
-let store: Store | null = null;
-async function getStore(): Promise<Store> {
-    if (!store) {
-        store = await load("auth.json", { autoSave: true, defaults: {} });
+// Sessions used to live in cleartext in auth.json via tauri-plugin-store;
+// read from there once so existing users stay signed in, and delete it once
+// the session is safely in the secret store
+let legacyStore: Store | null = null;
+async function getLegacyStore(): Promise<Store> {
+    if (!legacyStore) {
+        legacyStore = await load("auth.json", { autoSave: false, defaults: {} });
     }
-    return store;
+    return legacyStore;
 }
 
-// Custom storage adapter using tauri-plugin-store for desktop persistence
-const tauriStorage = {
+async function readLegacyItem(key: string): Promise<string | null> {
+    try {
+        const s = await getLegacyStore();
+        return (await s.get<string>(key)) ?? null;
+    } catch {
+        return null;
+    }
+}
+
+async function dropLegacyItem(key: string): Promise<void> {
+    try {
+        const s = await getLegacyStore();
+        await s.delete(key);
+        await s.save();
+    } catch {
+        // Left for the next start, or "sign out everywhere", to remove
+    }
+}
+
+// Items that couldn't be read because the secret store was locked (no
+// keychain, passphrase not entered yet); restored once it unlocks
+const lockedItems = new Set<string>();
+
+// Storage adapter backed by the encrypted secret store in Rust
+// (`src-tauri/src/secrets.rs`)
+const secureStorage = {
     getItem: async (key: string): Promise<string | null> => {
+        let value: string | null;
         try {
-            const s = await getStore();
-            return (await s.get<string>(key)) ?? null;
+            value = await invoke<string | null>("secret_get", { name: key });
         } catch {
+            lockedItems.add(key);
             return null;
         }
+        if (value !== null) return value;
+
+        const legacy = await readLegacyItem(key);
+        if (legacy !== null) {
+            try {
+                await invoke("secret_set", { name: key, value: legacy });
+                await dropLegacyItem(key);
+            } catch (err) {
+                console.error("Could not move the session into secure storage:", err);
+            }
+        }
+        return legacy;
     },
+    // Errors reach supabase-js, so a session that can't be stored is
+    // reported rather than silently lost at the next start
     setItem: async (key: string, value: string): Promise<void> => {
-        try {
-            const s = await getStore();
-            await s.set(key, value);
-            await s.save();
-        } catch {
-            // Silent fail — app can still work without persistence
-        }
+        await invoke("secret_set", { name: key, value });
     },
     removeItem: async (key: string): Promise<void> => {
         try {
-            const s = await getStore();
-            await s.delete(key);
-            await s.save();
+            await invoke("secret_delete", { name: key });
         } catch {
             // Silent fail
         }
@@ -47,6 +83,43 @@ export const supabase = createClient(supabaseUrl, supabaseAnonKey, {
     auth: {
         autoRefreshToken: true,
         persistSession: true,
-        storage: tauriStorage,
+        storage: secureStorage,
     },
 });
+
+// Hand the session to the Rust side so it can replay the offline queue
+// while the window is hidden. The project details go over even when signed
+// out, since share links are checked against them.
+supabase.auth.onAuthStateChange((_event, session) => {
+    invoke("supabase_configure", {
+        config: {
+            url: supabaseUrl,
+            anonKey: supabaseAnonKey,
+            accessToken: session?.access_token ?? null,
+        },
+    }).catch(() => {});
+});
+
+// Without a keychain the session is sealed under the database key, so it
+// can only be read after the passphrase is entered
+listen<SecurityStatus>(LOCK_EVENT, async ({ payload }) => {
+    if (!payload.unlocked || lockedItems.size === 0) return;
+    const { data } = await supabase.auth.getSession();
+    if (data.session) return;
+    for (const key of lockedItems) {
+        const stored = await secureStorage.getItem(key);
+        if (!stored) continue;
+        try {
+            const session = JSON.parse(stored);
+            if (session?.access_token && session?.refresh_token) {
+                await supabase.auth.setSession({
+                    access_token: session.access_token,
+                    refresh_token: session.refresh_token,
+                });
+            }
+        } catch {
+            // Not a session; nothing to restore
+        }
+    }
+    lockedItems.clear();
+}).catch(() => {});
@@ -0,0 +1,75 @@
+import { convertFileSrc, invoke } from "@tauri-apps/api/core";
+
+/**
+ * Map tiles served by the Rust tile cache (`src-tauri/src/tiles.rs`) through
+ * the `tiles://` protocol, so areas viewed or seeded once keep working offline.
+ */
+
+/** Leaflet URL template for cached tiles. */
+export const TILE_URL = `${convertFileSrc("", "tiles")}{z}/{x}/{y}.png`;
+
+export const TILE_ATTRIBUTION =
+    '&copy; <a href="https://www.openstreetmap.org/copyright">OpenStreetMap</a>';
+
+/** The server cached tiles are fetched from; OpenStreetMap when `url` is empty. */
+export interface TileServer {
+    /** URL template with `{z}`, `{x}` and `{y}`. */
+    url: string | null;
+    attribution: string | null;
+}
+
+export interface BoundingBox {
+    west: number;
+    south: number;
+    east: number;
+    north: number;
+}
+
+export interface SeedProgress {
+    total: number;
+    done: number;
+    skipped: number;
+    failed: number;
+    bytes: number;
+    cancelled: boolean;
+}
+
+export interface TileCacheStatus {
+    tiles: number;
+    bytes: number;
+    limitBytes: number;
+}
+
+export const SEED_PROGRESS_EVENT = "tiles://seed-progress";
+
+export function estimateSeed(area: BoundingBox, minZoom: number, maxZoom: number): Promise<number> {
+    return invoke<number>("tiles_estimate", { area, minZoom, maxZoom });
+}
+
+export function seedTiles(area: BoundingBox, minZoom: number, maxZoom: number): Promise<SeedProgress> {
+    return invoke<SeedProgress>("tiles_seed", { area, minZoom, maxZoom });
+}
+
+export function cancelSeed(): Promise<void> {
+    return invoke("tiles_seed_cancel");
+}
+
+export function getTileCacheStatus(): Promise<TileCacheStatus> {
+    return invoke<TileCacheStatus>("tiles_status");
+}
+
+export function clearTileCache(): Promise<void> {
+    return invoke("tiles_clear");
+}
+
+export function getTileServer(): Promise<TileServer> {
+    return invoke<TileServer>("tiles_server");
+}
+
+/**
+ * Switch tile servers. Areas can only be saved offline from a custom server;
+ * the OpenStreetMap servers don't allow bulk downloads.
+ */
+export function setTileServer(server: TileServer): Promise<TileServer> {
+    return invoke<TileServer>("tiles_set_server", { server });
+}
@@ -1,4 +1,5 @@
-import { useState, useMemo } from "react";
+import { useState, useMemo, useEffect } from "react";
+import { useSearchParams } from "react-router-dom";
 import { Button } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
 import { Card, CardContent } from "@/components/ui/card";
@@ -29,6 +30,8 @@ import { Switch } from "@/components/ui/switch";
 import { SeverityBadge } from "@/components/incidents/severity-badge";
 import { AlertTypeBadge } from "@/components/alerts/alert-badges";
 import { AlertForm } from "@/components/alerts/alert-form";
+import { CapMenu } from "@/components/alerts/cap-menu";
+import { CapReviewButton, CapReviewQueue } from "@/components/alerts/cap-review-queue";
 import {
   useAlerts,
   useDeleteAlert,
@@ -36,6 +39,7 @@ import {
   type AlertRow,
   type AlertFilters,
 } from "@/hooks/use-alerts";
+import { useExportCap } from "@/hooks/use-cap";
 import { useAuthStore } from "@/stores/auth-store";
 import type { AlertType, SeverityLevel } from "@/types/enums";
 import {
@@ -49,6 +53,7 @@ import {
   AlertTriangle,
   MapPin,
   Download,
+  FileDown,
 } from "lucide-react";
 import { formatDistanceToNow, format } from "date-fns";
 import { DataTablePagination } from "@/components/data-table-pagination";
@@ -99,6 +104,7 @@ export default function AlertsPage() {
   const isAdmin = useAuthStore((s) => s.profile?.role === "administrator");
   const deleteMutation = useDeleteAlert();
   const toggleMutation = useToggleAlert();
+  const exportCap = useExportCap();
 
   const filters: AlertFilters = {
     search: search || undefined,
@@ -120,6 +126,11 @@ export default function AlertsPage() {
     [allAlerts]
   );
 
+  const activeAlertIds = useMemo(
+    () => (allAlerts ?? []).filter((a) => a.is_active).map((a) => a.id),
+    [allAlerts]
+  );
+
   // Client-side pagination
   const paginatedAlerts = useMemo(() => {
     if (!allAlerts) return [];
@@ -137,6 +148,29 @@ export default function AlertsPage() {
     setPage(1);
   };
 
+  // Deep links land here as /alerts?alert={id}, and the tray's CAP review
+  // item as /alerts?review=cap
+  const [searchParams, setSearchParams] = useSearchParams();
+  const linkedAlertId = searchParams.get("alert");
+  const [reviewOpen, setReviewOpen] = useState(false);
+  const reviewLinked = searchParams.get("review") === "cap";
+  useEffect(() => {
+    if (!reviewLinked) return;
+    setReviewOpen(true);
+    setSearchParams({}, { replace: true });
+  }, [reviewLinked, setSearchParams]);
+  useEffect(() => {
+    if (!linkedAlertId || isLoading) return;
+    const alert = (allAlerts ?? []).find((a) => a.id === linkedAlertId);
+    if (alert) {
+      setEditingAlert(alert);
+      setFormOpen(true);
+    } else {
+      toast.error("Linked alert not found");
+    }
+    setSearchParams({}, { replace: true });
+  }, [linkedAlertId, isLoading, allAlerts, setSearchParams]);
+
   const handleEdit = (e: React.MouseEvent, alert: AlertRow) => {
     e.stopPropagation();
     setEditingAlert(alert);
@@ -234,6 +268,8 @@ export default function AlertsPage() {
             <Download className="h-4 w-4" />
             Export CSV
           </Button>
+          <CapReviewButton onClick={() => setReviewOpen(true)} />
+          <CapMenu exportIds={activeAlertIds} />
           <Button
             onClick={() => {
               setEditingAlert(null);
@@ -452,6 +488,18 @@ export default function AlertsPage() {
                           >
                             <Pencil className="h-3.5 w-3.5" />
                           </Button>
+                          <Button
+                            variant="ghost"
+                            size="icon-xs"
+                            onClick={(e) => {
+                              e.stopPropagation();
+                              exportCap.mutate([alert.id]);
+                            }}
+                            disabled={exportCap.isPending}
+                            aria-label="Export alert as CAP"
+                          >
+                            <FileDown className="h-3.5 w-3.5" />
+                          </Button>
                           {(isAdmin || alert.created_by === userId) && (
                             <Button
                               variant="ghost"
@@ -493,6 +541,9 @@ export default function AlertsPage() {
         </CardContent>
       </Card>
 
+      {/* Incoming CAP messages */}
+      <CapReviewQueue open={reviewOpen} onOpenChange={setReviewOpen} />
+
       {/* Alert Form Dialog */}
       <AlertForm
         open={formOpen}
@@ -1,3 +1,4 @@
+import { useSearchParams } from "react-router-dom";
 import { useAuthStore } from "@/stores/auth-store";
 import { useMapStore } from "@/stores/map-store";
 import { Badge } from "@/components/ui/badge";
@@ -14,6 +15,7 @@ import {
   SeverityChart,
   ResourceStatusChart,
 } from "@/components/dashboard/dashboard-charts";
+import { SOSDetailDialog } from "@/components/dashboard/sos-detail-dialog";
 import { DashboardMap } from "@/components/map/map-view";
 
 import {
@@ -29,6 +31,17 @@ export default function DashboardPage() {
   const { profile } = useAuthStore();
   const toggleLayer = useMapStore((s) => s.toggleLayer);
   const layers = useMapStore((s) => s.layers);
+  // `?sos=<id>` from notifications, share links and the nearby-SOS list
+  const [searchParams, setSearchParams] = useSearchParams();
+  const sosId = searchParams.get("sos");
+  const closeSOS = () =>
+    setSearchParams(
+      (params) => {
+        params.delete("sos");
+        return params;
+      },
+      { replace: true }
+    );
 
   const { data: stats, isLoading: statsLoading } = useDashboardStats();
   const { data: incidents, isLoading: incidentsLoading } =
@@ -152,6 +165,8 @@ export default function DashboardPage() {
           />
         </div>
       </div>
+
+      <SOSDetailDialog id={sosId} onClose={closeSOS} />
     </div>
   );
 }
@@ -1,4 +1,5 @@
-import { useState, useMemo } from "react";
+import { useState, useMemo, useEffect } from "react";
+import { useSearchParams } from "react-router-dom";
 import {
     useEvacuationRoutes,
     useCreateEvacRoute,
@@ -41,13 +42,26 @@ import {
     Navigation,
     Eye,
     EyeOff,
+    AlertTriangle,
 } from "lucide-react";
 import { format } from "date-fns";
 import type { EvacuationRoute } from "@/types/database";
 import { ConfirmDeleteDialog } from "@/components/confirm-delete-dialog";
+import { InterchangeMenu } from "@/components/interchange-menu";
+import {
+    describeIssue,
+    routeMetrics,
+    TRAVEL_MODES,
+    type RouteMetrics,
+    type TravelMode,
+} from "@/lib/evacuation";
+import { routeByRoad } from "@/lib/routing";
+import type { Point } from "@/lib/geo";
+import { toast } from "sonner";
 
 /* ── Lazy Leaflet (only loaded when map is shown) ──────────────── */
-import { MapContainer, TileLayer, Polyline, Marker, Popup, useMapEvents } from "react-leaflet";
+import { MapContainer, Polyline, Polygon, Marker, Popup, useMapEvents } from "react-leaflet";
+import { BaseTileLayer } from "@/components/map/base-tile-layer";
 import L from "leaflet";
 import "leaflet/dist/leaflet.css";
 
@@ -77,6 +91,19 @@ export default function EvacuationPage() {
     const openCreate = () => { setEditing(null); setDialogOpen(true); };
     const openEdit = (r: typeof routes[0]) => { setEditing(r); setDialogOpen(true); };
 
+    // Deep links land here as /evacuation?route={id}
+    const [searchParams, setSearchParams] = useSearchParams();
+    const linkedRouteId = searchParams.get("route");
+    useEffect(() => {
+        if (!linkedRouteId || isLoading) return;
+        const route = routes.find((r) => r.id === linkedRouteId);
+        if (route) {
+            setEditing(route);
+            setDialogOpen(true);
+        }
+        setSearchParams({}, { replace: true });
+    }, [linkedRouteId, isLoading, routes, setSearchParams]);
+
     const handleSubmit = (form: EvacRouteFormData) => {
         if (editing) {
             updateMut.mutate({ id: editing.id, ...form }, { onSuccess: () => setDialogOpen(false) });
@@ -109,6 +136,7 @@ export default function EvacuationPage() {
                         {showMap ? <EyeOff className="h-3.5 w-3.5" /> : <Eye className="h-3.5 w-3.5" />}
                         {showMap ? "Hide Map" : "Show Map"}
                     </Button>
+                    <InterchangeMenu kind="evacuation_route" />
                     <Button className="gap-1.5" onClick={openCreate}>
                         <Plus className="h-4 w-4" />
                         New Route
@@ -149,10 +177,7 @@ export default function EvacuationPage() {
                 <Card className="overflow-hidden">
                     <CardContent className="p-0 h-[350px]">
                         <MapContainer center={center} zoom={12} className="h-full w-full" style={{ zIndex: 0 }}>
-                            <TileLayer
-                                attribution='&copy; <a href="https://www.openstreetmap.org">OSM</a>'
-                                url="https://{s}.tile.openstreetmap.org/{z}/{x}/{y}.png"
-                            />
+                            <BaseTileLayer />
                             {routes.map((route, idx) => {
                                 const positions = (route.waypoints ?? []).map((w) => [w.lat, w.lng] as [number, number]);
                                 if (positions.length < 2) return null;
@@ -325,8 +350,38 @@ function EvacRouteDialog({
     const [capacity, setCapacity] = useState("");
     const [incidentId, setIncidentId] = useState("");
     const [isActive, setIsActive] = useState(true);
+    const [travelMode, setTravelMode] = useState<TravelMode>("driving");
+    const [metrics, setMetrics] = useState<RouteMetrics | null>(null);
+    const [followRoads, setFollowRoads] = useState(false);
+    const [routing, setRouting] = useState(false);
+    // Road closures to route around, and the one being drawn
+    const [closures, setClosures] = useState<Point[][]>([]);
+    const [drawing, setDrawing] = useState<Point[] | null>(null);
+
+    // Distance and time come from the waypoints whenever there is a route to measure
+    useEffect(() => {
+        if (waypoints.length < 2) {
+            setMetrics(null);
+            return;
+        }
+        let stale = false;
+        routeMetrics(waypoints)
+            .then((m) => {
+                if (stale) return;
+                setMetrics(m);
+                setDistanceKm(m.distanceKm.toString());
+                setEstMinutes(m.minutes[travelMode].toString());
+            })
+            .catch(() => {
+                if (!stale) setMetrics(null);
+            });
+        return () => {
+            stale = true;
+        };
+    }, [waypoints, travelMode]);
 
     const handleOpenChange = (v: boolean) => {
+        if (v) setDrawing(null);
         if (v && editing) {
             setName(editing.name);
             setDescription(editing.description ?? "");
@@ -347,6 +402,32 @@ function EvacRouteDialog({
         setWaypoints((w) => [...w, { lat: Math.round(lat * 10000) / 10000, lng: Math.round(lng * 10000) / 10000 }]);
     };
 
+    const handleMapClick = async (lat: number, lng: number) => {
+        if (drawing) {
+            setDrawing([...drawing, { lat, lng }]);
+            return;
+        }
+        const last = waypoints[waypoints.length - 1];
+        if (!followRoads || !last) {
+            addWaypoint(lat, lng);
+            return;
+        }
+        setRouting(true);
+        try {
+            const plan = await routeByRoad([last, { lat, lng }], travelMode === "walking" ? "walking" : "driving", closures);
+            setWaypoints((w) => [...w, ...plan.waypoints.slice(1)]);
+        } catch (err) {
+            toast.error(`${err}`);
+        } finally {
+            setRouting(false);
+        }
+    };
+
+    const finishClosure = () => {
+        if (drawing && drawing.length >= 3) setClosures((c) => [...c, drawing]);
+        setDrawing(null);
+    };
+
     const removeWaypoint = (idx: number) => {
         setWaypoints((w) => w.filter((_, i) => i !== idx));
     };
@@ -383,17 +464,39 @@ function EvacRouteDialog({
                     <div className="grid grid-cols-3 gap-3">
                         <div>
                             <Label className="text-xs">Distance (km)</Label>
-                            <Input type="number" min="0" step="0.1" value={distanceKm} onChange={(e) => setDistanceKm(e.target.value)} data-selectable />
+                            <Input type="number" min="0" step="0.1" value={distanceKm} onChange={(e) => setDistanceKm(e.target.value)} readOnly={metrics !== null} data-selectable />
                         </div>
                         <div>
                             <Label className="text-xs">Est. Time (min)</Label>
-                            <Input type="number" min="0" value={estMinutes} onChange={(e) => setEstMinutes(e.target.value)} data-selectable />
+                            <Input type="number" min="0" value={estMinutes} onChange={(e) => setEstMinutes(e.target.value)} readOnly={metrics !== null} data-selectable />
                         </div>
                         <div>
                             <Label className="text-xs">Capacity</Label>
                             <Input type="number" min="0" value={capacity} onChange={(e) => setCapacity(e.target.value)} data-selectable />
                         </div>
                     </div>
+                    {metrics && (
+                        <div className="space-y-2">
+                            <div className="flex items-center gap-2">
+                                <Label className="text-xs">Time for</Label>
+                                <Select value={travelMode} onValueChange={(v) => setTravelMode(v as TravelMode)}>
+                                    <SelectTrigger className="h-7 w-28 text-xs"><SelectValue /></SelectTrigger>
+                                    <SelectContent>
+                                        {TRAVEL_MODES.map((m) => <SelectItem key={m.value} value={m.value}>{m.label}</SelectItem>)}
+                                    </SelectContent>
+                                </Select>
+                                <span className="text-xs text-muted-foreground">
+                                    {TRAVEL_MODES.map((m) => `${m.label} ${metrics.minutes[m.value]} min`).join(" · ")}
+                                </span>
+                            </div>
+                            {metrics.issues.map((issue, i) => (
+                                <p key={i} className="flex items-center gap-1.5 text-xs text-amber-600 dark:text-amber-400">
+                                    <AlertTriangle className="h-3 w-3 shrink-0" />
+                                    {describeIssue(issue)}
+                                </p>
+                            ))}
+                        </div>
+                    )}
                     <div className="grid grid-cols-2 gap-3">
                         <div>
                             <Label className="text-xs">Linked Incident</Label>
@@ -413,7 +516,41 @@ function EvacRouteDialog({
 
                     {/* Waypoints Map */}
                     <div>
-                        <Label className="text-xs mb-1 block">Waypoints — click map to add ({waypoints.length} points)</Label>
+                        <div className="flex items-center justify-between mb-1">
+                            <Label className="text-xs">
+                                {drawing
+                                    ? `Click map to outline the closure (${drawing.length} points)`
+                                    : `Waypoints — click map to add (${waypoints.length} points)`}
+                            </Label>
+                            {routing && <Loader2 className="h-3.5 w-3.5 animate-spin text-muted-foreground" />}
+                        </div>
+                        <div className="flex flex-wrap items-center gap-2 mb-2">
+                            <Switch checked={followRoads} onCheckedChange={setFollowRoads} />
+                            <span className="text-xs text-muted-foreground">Follow roads</span>
+                            {followRoads && (
+                                drawing ? (
+                                    <>
+                                        <Button type="button" size="sm" variant="outline" className="h-7 text-xs" onClick={finishClosure} disabled={drawing.length < 3}>
+                                            Finish closure
+                                        </Button>
+                                        <Button type="button" size="sm" variant="ghost" className="h-7 text-xs" onClick={() => setDrawing(null)}>
+                                            Cancel
+                                        </Button>
+                                    </>
+                                ) : (
+                                    <>
+                                        <Button type="button" size="sm" variant="outline" className="h-7 text-xs" onClick={() => setDrawing([])}>
+                                            Add road closure
+                                        </Button>
+                                        {closures.length > 0 && (
+                                            <Button type="button" size="sm" variant="ghost" className="h-7 text-xs" onClick={() => setClosures([])}>
+                                                Clear {closures.length} closure{closures.length > 1 ? "s" : ""}
+                                            </Button>
+                                        )}
+                                    </>
+                                )
+                            )}
+                        </div>
                         <div className="h-[200px] rounded-md overflow-hidden border">
                             <MapContainer
                                 center={waypoints.length > 0 ? [waypoints[0].lat, waypoints[0].lng] : [33.6844, 73.0479]}
@@ -421,11 +558,17 @@ function EvacRouteDialog({
                                 className="h-full w-full"
                                 style={{ zIndex: 0 }}
                             >
-                                <TileLayer
-                                    attribution='&copy; OSM'
-                                    url="https://{s}.tile.openstreetmap.org/{z}/{x}/{y}.png"
-                                />
-                                <ClickToAddWaypoint onAdd={addWaypoint} />
+                                <BaseTileLayer />
+                                <ClickToAddWaypoint onAdd={handleMapClick} />
+                                {closures.map((ring, i) => (
+                                    <Polygon key={i} positions={ring.map((p) => [p.lat, p.lng] as [number, number])} pathOptions={{ color: "#ef4444", weight: 2 }} />
+                                ))}
+                                {drawing && drawing.length > 0 && (
+                                    <Polyline
+                                        positions={drawing.map((p) => [p.lat, p.lng] as [number, number])}
+                                        pathOptions={{ color: "#ef4444", weight: 2, dashArray: "4 4" }}
+                                    />
+                                )}
                                 {waypoints.length >= 2 && (
                                     <Polyline
                                         positions={waypoints.map((w) => [w.lat, w.lng] as [number, number])}
@@ -21,8 +21,10 @@ import {
   StatusBadge,
 } from "@/components/incidents/severity-badge";
 import { IncidentForm } from "@/components/incidents/incident-form";
+import { IncidentNearby } from "@/components/incidents/incident-nearby";
 import { DashboardMap } from "@/components/map/map-view";
 import { AssignmentDialog } from "@/components/resources/assignment-dialog";
+import { ShareDialog } from "@/components/share-dialog";
 import {
   ResourceStatusBadge,
   formatResourceType,
@@ -43,6 +45,7 @@ import {
   useAddIncidentUpdate,
 } from "@/hooks/use-incidents";
 import { useAuthStore } from "@/stores/auth-store";
+import { useFormattedCoordinate } from "@/hooks/use-coords";
 import {
   useIncidentResources,
   useReleaseResource,
@@ -66,6 +69,7 @@ import {
   Package,
   Plus,
   X,
+  QrCode,
 } from "lucide-react";
 import { format, formatDistanceToNow } from "date-fns";
 import { toast } from "sonner";
@@ -89,8 +93,10 @@ export default function IncidentDetailPage() {
   const deleteMutation = useDeleteIncident();
   const addUpdateMutation = useAddIncidentUpdate();
   const releaseMutation = useReleaseResource();
+  const coordinates = useFormattedCoordinate(incident?.latitude, incident?.longitude);
 
   const [formOpen, setFormOpen] = useState(false);
+  const [shareOpen, setShareOpen] = useState(false);
   const [closeDialogOpen, setCloseDialogOpen] = useState(false);
   const [deleteDialogOpen, setDeleteDialogOpen] = useState(false);
   const [assignDialogOpen, setAssignDialogOpen] = useState(false);
@@ -210,6 +216,15 @@ export default function IncidentDetailPage() {
         </div>
 
         <div className="flex items-center gap-1.5">
+          <Button
+            variant="outline"
+            size="sm"
+            className="gap-1"
+            onClick={() => setShareOpen(true)}
+          >
+            <QrCode className="h-3.5 w-3.5" />
+            Share
+          </Button>
           <Button
             variant="outline"
             size="sm"
@@ -296,10 +311,9 @@ export default function IncidentDetailPage() {
               )}
             </div>
 
-            {incident.latitude && incident.longitude && (
+            {coordinates && (
               <div className="text-xs text-muted-foreground font-mono">
-                Coordinates: {incident.latitude.toFixed(6)},{" "}
-                {incident.longitude.toFixed(6)}
+                Coordinates: {coordinates}
               </div>
             )}
 
@@ -383,6 +397,10 @@ export default function IncidentDetailPage() {
           <TabsTrigger value="tasks" className="gap-1">
             Tasks
           </TabsTrigger>
+          <TabsTrigger value="nearby" className="gap-1">
+            <MapPin className="h-3.5 w-3.5" />
+            Nearby
+          </TabsTrigger>
         </TabsList>
 
         {/* Timeline Tab */}
@@ -638,6 +656,11 @@ export default function IncidentDetailPage() {
         {/* Tasks Tab — linked tasks for this incident */}
         <TabsContent value="tasks">
           <IncidentTasks incidentId={incident.id} /></TabsContent>
+
+        {/* Nearby Tab — resources and SOS around the incident */}
+        <TabsContent value="nearby">
+          <IncidentNearby incident={incident} />
+        </TabsContent>
       </Tabs>
 
       {/* Assignment Dialog */}
@@ -648,6 +671,15 @@ export default function IncidentDetailPage() {
         incidentTitle={incident.title}
       />
 
+      {/* Share Dialog */}
+      <ShareDialog
+        open={shareOpen}
+        onOpenChange={setShareOpen}
+        entity="incident"
+        id={incident.id}
+        title={incident.title}
+      />
+
       {/* Edit Form Dialog */}
       <IncidentForm
         open={formOpen}
@@ -1,6 +1,7 @@
 import { useState, useMemo } from "react";
 import { useNavigate } from "react-router-dom";
 import { Button } from "@/components/ui/button";
+import { InterchangeMenu } from "@/components/interchange-menu";
 import { Input } from "@/components/ui/input";
 import { Card, CardContent } from "@/components/ui/card";
 import {
@@ -190,6 +191,7 @@ export default function IncidentsPage() {
             <Download className="h-4 w-4" />
             Export CSV
           </Button>
+          <InterchangeMenu kind="incident" />
           <Button
             onClick={() => {
               setEditingIncident(null);
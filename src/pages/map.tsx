@@ -1,19 +1,24 @@
-import { useEffect, useRef, useMemo } from "react";
+import { useEffect, useRef, useMemo, useState } from "react";
 import {
   MapContainer,
-  TileLayer,
   CircleMarker,
   Circle,
-  Popup,
   useMap,
 } from "react-leaflet";
+import { keepPreviousData, useQuery, useQueryClient } from "@tanstack/react-query";
+import { listen } from "@tauri-apps/api/event";
+import { BaseTileLayer } from "@/components/map/base-tile-layer";
+import { OfflineAreaButton } from "@/components/map/offline-area-button";
+import { LiveUnitsLayer } from "@/components/map/live-units-layer";
+import {
+  TrackPlaybackLayer,
+  TrackPlaybackPanel,
+  useTrackPlayback,
+} from "@/components/map/track-playback";
 import L from "leaflet";
 import "leaflet/dist/leaflet.css";
-import "leaflet.markercluster/dist/MarkerCluster.css";
-import "leaflet.markercluster/dist/MarkerCluster.Default.css";
-import { MarkerClusterGroup } from "leaflet.markercluster";
 import "leaflet.heat";
-import { useNavigate } from "react-router-dom";
+import { useNavigate, useSearchParams } from "react-router-dom";
 import { useMapStore } from "@/stores/map-store";
 import {
   useActiveIncidents,
@@ -22,6 +27,9 @@ import {
   type MapResource,
 } from "@/hooks/use-dashboard";
 import type { SeverityLevel } from "@/types/enums";
+import { mapAggregate, type Cluster } from "@/lib/map-aggregate";
+import { reverseGeocode } from "@/lib/geocode";
+import { MIRROR_SYNCED_EVENT } from "@/lib/offline-cache";
 import { Button } from "@/components/ui/button";
 import { Card } from "@/components/ui/card";
 import { Separator } from "@/components/ui/separator";
@@ -32,6 +40,9 @@ import {
   CircleDot,
   Layers,
   Loader2,
+  Siren,
+  Navigation,
+  History,
 } from "lucide-react";
 
 // ─── Helpers ────────────────────────────────────────────────────
@@ -63,6 +74,7 @@ const SEVERITY_RADIUS: Record<SeverityLevel, number> = {
 };
 
 const RESOURCE_COLOR = "#3b82f6";
+const SOS_COLOR = "#be123c";
 
 // ─── Map State Sync ──────────────────────────────────────────────
 
@@ -118,161 +130,251 @@ function FitBounds({
   return null;
 }
 
-// ─── Heatmap Layer ───────────────────────────────────────────────
+// ─── Focus from deep link ────────────────────────────────────────
 
-function HeatmapLayer({ incidents }: { incidents: MapIncident[] }) {
+function FocusPoint({ point, zoom }: { point: [number, number]; zoom: number | null }) {
   const map = useMap();
+  const [lat, lng] = point;
 
   useEffect(() => {
-    const points: [number, number, number][] = incidents
-      .filter((i) => i.latitude != null && i.longitude != null)
-      .map((i) => {
-        const intensity =
-          i.severity === "critical"
-            ? 1.0
-            : i.severity === "high"
-            ? 0.7
-            : i.severity === "medium"
-            ? 0.4
-            : 0.2;
-        return [i.latitude!, i.longitude!, intensity];
-      });
+    map.setView([lat, lng], zoom ?? Math.max(map.getZoom(), 14));
+  }, [map, lat, lng, zoom]);
 
-    const heat = (L as any).heatLayer(points, {
-      radius: 30,
-      blur: 20,
-      maxZoom: 14,
-      gradient: { 0.2: "#22c55e", 0.4: "#eab308", 0.7: "#f97316", 1.0: "#dc2626" },
-    });
+  return (
+    <CircleMarker
+      center={[lat, lng]}
+      radius={8}
+      pathOptions={{ color: "#7c3aed", fillColor: "#7c3aed", fillOpacity: 0.4 }}
+    />
+  );
+}
 
-    map.addLayer(heat);
+// ─── Viewport Aggregates ─────────────────────────────────────────
+
+type ViewportEntity = "incident" | "resource" | "sos";
+
+function viewOf(map: L.Map) {
+  const b = map.getBounds();
+  return {
+    bounds: {
+      west: b.getWest(),
+      south: b.getSouth(),
+      east: b.getEast(),
+      north: b.getNorth(),
+    },
+    zoom: Math.round(map.getZoom()),
+  };
+}
+
+/** Clusters and heat cells for the current viewport, from the local cache. */
+function useViewportAggregate(entities: ViewportEntity[], heatmap: boolean) {
+  const map = useMap();
+  const qc = useQueryClient();
+  const [view, setView] = useState(() => viewOf(map));
+
+  useEffect(() => {
+    const onMoveEnd = () => setView(viewOf(map));
+    map.on("moveend", onMoveEnd);
     return () => {
-      map.removeLayer(heat);
+      map.off("moveend", onMoveEnd);
     };
-  }, [map, incidents]);
+  }, [map]);
 
-  return null;
+  useEffect(() => {
+    const unlisten = listen(MIRROR_SYNCED_EVENT, () => {
+      qc.invalidateQueries({ queryKey: ["map-aggregate"] });
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [qc]);
+
+  return useQuery({
+    queryKey: ["map-aggregate", view, entities, heatmap],
+    queryFn: () =>
+      mapAggregate({ ...view, entities, activeOnly: true, heatmap }),
+    enabled: entities.length > 0,
+    placeholderData: keepPreviousData,
+    retry: false,
+  });
 }
 
-// ─── Clustering Layer ────────────────────────────────────────────
+function popupHtml(cluster: Cluster): string {
+  const r = (cluster.record ?? {}) as Record<string, string | null>;
+  const id = escapeHtml(String(r.id ?? ""));
+  const location = r.location_name
+    ? `<p style="font-size:11px;margin:4px 0 0;color:#888">${escapeHtml(r.location_name)}</p>`
+    : "";
+  const row = (label: string, value: string | null, color?: string) =>
+    value
+      ? `<p style="font-size:12px;margin:0"><span style="color:#888">${label}:</span> <span${color ? ` style="color:${color};font-weight:500"` : ""}>${escapeHtml(value.replace(/_/g, " "))}</span></p>`
+      : "";
+  const link = (path: string) =>
+    `<p style="margin:6px 0 0"><a href="#" class="map-link" data-path="${path}" style="font-size:12px;color:#0f766e">View Details &rarr;</a></p>`;
+
+  switch (cluster.entity) {
+    case "incident":
+      return `<div style="min-width:180px">
+          <p style="font-weight:600;margin:0 0 4px">${escapeHtml(r.title ?? "Incident")}</p>
+          ${row("Severity", r.severity, markerColor(cluster))}
+          ${row("Status", r.status)}
+          ${location}
+          ${link(`/incidents/${id}`)}
+        </div>`;
+    case "resource":
+      return `<div style="min-width:160px">
+          <p style="font-weight:600;margin:0 0 4px">${escapeHtml(r.name ?? "Resource")}</p>
+          ${row("Type", r.type)}
+          ${row("Status", r.status)}
+          ${location}
+          ${link(`/resources/${id}`)}
+        </div>`;
+    default:
+      return `<div style="min-width:160px">
+          <p style="font-weight:600;margin:0 0 4px">SOS</p>
+          <p style="font-size:12px;margin:0 0 4px">${escapeHtml(r.message ?? "")}</p>
+          ${row("Severity", r.severity, markerColor(cluster))}
+          ${location}
+        </div>`;
+  }
+}
 
-function ClusteredIncidentMarkers({
-  incidents,
+function markerColor(cluster: Cluster): string {
+  if (cluster.entity === "resource") return RESOURCE_COLOR;
+  if (cluster.entity === "sos") return SOS_COLOR;
+  return SEVERITY_COLORS[cluster.severity as SeverityLevel] ?? "#eab308";
+}
+
+function clusterMarker(cluster: Cluster, map: L.Map): L.Layer {
+  const color = markerColor(cluster);
+  const { point, bounds } = cluster;
+
+  if (cluster.count === 1) {
+    const marker = L.circleMarker([point.lat, point.lng], {
+      radius:
+        cluster.entity === "resource"
+          ? 6
+          : SEVERITY_RADIUS[cluster.severity as SeverityLevel] ?? 7,
+      color,
+      fillColor: color,
+      fillOpacity: 0.7,
+      weight: 2,
+    });
+    marker.bindPopup(popupHtml(cluster));
+    if (!cluster.record?.location_name) {
+      // Look up a name for a record that came with coordinates only, once
+      // someone actually opens it
+      marker.once("popupopen", () => {
+        reverseGeocode(point)
+          .then((hit) => {
+            if (!hit) return;
+            marker.setPopupContent(
+              popupHtml({ ...cluster, record: { ...cluster.record, location_name: hit.label } })
+            );
+          })
+          .catch(() => {});
+      });
+    }
+    return marker;
+  }
+
+  const size = cluster.count < 10 ? 30 : cluster.count < 100 ? 36 : 44;
+  const marker = L.marker([point.lat, point.lng], {
+    icon: L.divIcon({
+      className: "",
+      iconSize: [size, size],
+      html: `<div style="width:${size}px;height:${size}px;border-radius:9999px;background:${color}cc;border:3px solid ${color}55;background-clip:padding-box;color:#fff;font:600 12px/1 sans-serif;display:flex;align-items:center;justify-content:center">${cluster.count}</div>`,
+    }),
+  });
+  marker.on("click", () => {
+    if (bounds.west === bounds.east && bounds.south === bounds.north) {
+      map.setView([point.lat, point.lng], map.getZoom() + 2);
+    } else {
+      map.fitBounds(
+        [
+          [bounds.south, bounds.west],
+          [bounds.north, bounds.east],
+        ],
+        { padding: [40, 40] }
+      );
+    }
+  });
+  return marker;
+}
+
+function AggregateLayers({
+  show,
+  heatmap,
   navigate,
 }: {
-  incidents: MapIncident[];
+  show: ViewportEntity[];
+  heatmap: boolean;
   navigate: (path: string) => void;
 }) {
   const map = useMap();
+  // Heat comes from incidents and SOS even when their markers are hidden
+  const entities = useMemo(
+    () =>
+      Array.from(
+        new Set<ViewportEntity>([
+          ...show,
+          ...(heatmap ? (["incident", "sos"] as const) : []),
+        ])
+      ),
+    [show, heatmap]
+  );
+  const { data } = useViewportAggregate(entities, heatmap);
 
   useEffect(() => {
-    const cluster = new MarkerClusterGroup({
-      maxClusterRadius: 50,
-      chunkedLoading: true,
-      showCoverageOnHover: false,
-    });
-
-    for (const inc of incidents) {
-      if (inc.latitude == null || inc.longitude == null) continue;
-      const color = SEVERITY_COLORS[inc.severity] ?? "#eab308";
-      const marker = L.circleMarker([inc.latitude, inc.longitude], {
-        radius: SEVERITY_RADIUS[inc.severity] ?? 7,
-        color,
-        fillColor: color,
-        fillOpacity: 0.7,
-        weight: 2,
-      });
-
-      marker.bindPopup(
-        `<div style="min-width:180px">
-          <p style="font-weight:600;margin:0 0 4px">${escapeHtml(inc.title)}</p>
-          <p style="font-size:12px;margin:0"><span style="color:#888">Severity:</span> <span style="color:${color};font-weight:500">${escapeHtml(inc.severity)}</span></p>
-          <p style="font-size:12px;margin:0"><span style="color:#888">Status:</span> ${escapeHtml(inc.status.replace("_", " "))}</p>
-          ${inc.location_name ? `<p style="font-size:11px;margin:4px 0 0;color:#888">${escapeHtml(inc.location_name)}</p>` : ""}
-          <p style="margin:6px 0 0"><a href="#" class="incident-link" data-id="${escapeHtml(inc.id)}" style="font-size:12px;color:#0f766e">View Details &rarr;</a></p>
-        </div>`
-      );
-
-      cluster.addLayer(marker);
+    if (!data) return;
+    const group = L.layerGroup();
+    for (const cluster of data.clusters) {
+      if (show.includes(cluster.entity as ViewportEntity)) {
+        group.addLayer(clusterMarker(cluster, map));
+      }
     }
-
-    map.addLayer(cluster);
+    map.addLayer(group);
 
     // Handle popup link clicks
     const handleClick = (e: Event) => {
       const target = e.target as HTMLElement;
-      if (target.classList.contains("incident-link")) {
+      if (target.classList.contains("map-link")) {
         e.preventDefault();
-        const id = target.getAttribute("data-id");
-        if (id) navigate(`/incidents/${id}`);
+        const path = target.getAttribute("data-path");
+        if (path) navigate(path);
       }
     };
     map.getContainer().addEventListener("click", handleClick);
 
     return () => {
-      map.removeLayer(cluster);
+      map.removeLayer(group);
       map.getContainer().removeEventListener("click", handleClick);
     };
-  }, [map, incidents, navigate]);
+  }, [map, data, show, navigate]);
 
-  return null;
-}
+  useEffect(() => {
+    if (!heatmap || !data?.heat.length) return;
+    const max = data.maxWeight || 1;
+    const points: [number, number, number][] = data.heat.map((c) => [
+      c.point.lat,
+      c.point.lng,
+      c.weight / max,
+    ]);
 
-// ─── Resource Markers (simple, no clustering) ────────────────────
+    const heat = (L as any).heatLayer(points, {
+      radius: 30,
+      blur: 20,
+      max: 1,
+      gradient: { 0.2: "#22c55e", 0.4: "#eab308", 0.7: "#f97316", 1.0: "#dc2626" },
+    });
 
-function ResourceMarkers({
-  resources,
-  navigate,
-}: {
-  resources: MapResource[];
-  navigate: (path: string) => void;
-}) {
-  return (
-    <>
-      {resources
-        .filter((r) => r.latitude != null && r.longitude != null)
-        .map((res) => (
-          <CircleMarker
-            key={res.id}
-            center={[res.latitude!, res.longitude!]}
-            radius={6}
-            pathOptions={{
-              color: RESOURCE_COLOR,
-              fillColor: RESOURCE_COLOR,
-              fillOpacity: 0.6,
-              weight: 2,
-            }}
-          >
-            <Popup>
-              <div className="text-sm min-w-[160px]">
-                <p className="font-semibold mb-1">{res.name}</p>
-                <p className="text-xs capitalize">
-                  <span className="text-muted-foreground">Type:</span>{" "}
-                  {res.type.replace(/_/g, " ")}
-                </p>
-                <p className="text-xs capitalize">
-                  <span className="text-muted-foreground">Status:</span>{" "}
-                  {res.status}
-                </p>
-                {res.location_name && (
-                  <p className="text-xs mt-1 text-muted-foreground">
-                    {res.location_name}
-                  </p>
-                )}
-                <p className="mt-1.5">
-                  <button
-                    className="text-xs text-primary hover:underline"
-                    onClick={() => navigate(`/resources/${res.id}`)}
-                  >
-                    View Details &rarr;
-                  </button>
-                </p>
-              </div>
-            </Popup>
-          </CircleMarker>
-        ))}
-    </>
-  );
+    map.addLayer(heat);
+    return () => {
+      map.removeLayer(heat);
+    };
+  }, [map, data, heatmap]);
+
+  return null;
 }
 
 // ─── Alert Radius Circles ────────────────────────────────────────
@@ -310,6 +412,32 @@ export default function MapPage() {
   const zoom = useMapStore((s) => s.zoom);
   const layers = useMapStore((s) => s.layers);
   const toggleLayer = useMapStore((s) => s.toggleLayer);
+  const [map, setMap] = useState<L.Map | null>(null);
+
+  // Deep links land here as /map?lat=&lng=&zoom=, or /map?track=resource:<id>
+  const [searchParams, setSearchParams] = useSearchParams();
+  const linkedPoint = useMemo((): [number, number] | null => {
+    const lat = Number(searchParams.get("lat"));
+    const lng = Number(searchParams.get("lng"));
+    if (!searchParams.has("lat") || !searchParams.has("lng")) return null;
+    return Number.isFinite(lat) && Number.isFinite(lng) ? [lat, lng] : null;
+  }, [searchParams]);
+  const linkedZoom = searchParams.has("zoom") ? Number(searchParams.get("zoom")) : null;
+
+  // Track playback is open while ?track= is set; empty until a unit is picked
+  const playbackOpen = searchParams.has("track");
+  const trackKey = searchParams.get("track") || null;
+  const playback = useTrackPlayback(trackKey);
+  const setTrack = (key: string | null) =>
+    setSearchParams(
+      (params) => {
+        const next = new URLSearchParams(params);
+        if (key === null) next.delete("track");
+        else next.set("track", key);
+        return next;
+      },
+      { replace: true }
+    );
 
   const { data: incidents, isLoading: incLoading } = useActiveIncidents();
   const { data: resources, isLoading: resLoading } = useActiveResources();
@@ -324,6 +452,14 @@ export default function MapPage() {
 
   const isLoading = incLoading || resLoading;
 
+  const visibleEntities = useMemo(() => {
+    const entities: ViewportEntity[] = [];
+    if (layers.incidents) entities.push("incident");
+    if (layers.sos) entities.push("sos");
+    if (layers.resources) entities.push("resource");
+    return entities;
+  }, [layers.incidents, layers.sos, layers.resources]);
+
   return (
     <div className="map-page-container relative h-full -m-4 sm:-m-6">
       {/* Full-screen map */}
@@ -342,40 +478,34 @@ export default function MapPage() {
           style={{ height: "100%", width: "100%" }}
           scrollWheelZoom
           zoomControl
+          ref={setMap}
         >
-          <TileLayer
-            attribution='&copy; <a href="https://www.openstreetmap.org/copyright">OpenStreetMap</a>'
-            url="https://{s}.tile.openstreetmap.org/{z}/{x}/{y}.png"
-          />
+          <BaseTileLayer />
           <MapStateSync />
-          <FitBounds
-            incidents={incidents ?? []}
-            resources={resources ?? []}
-          />
-
-          {/* Incident markers (clustered) */}
-          {layers.incidents && (
-            <ClusteredIncidentMarkers
-              incidents={geoIncidents}
-              navigate={navigate}
-            />
-          )}
-
-          {/* Resource markers */}
-          {layers.resources && (
-            <ResourceMarkers
+          {linkedPoint ? (
+            <FocusPoint point={linkedPoint} zoom={linkedZoom} />
+          ) : (
+            <FitBounds
+              incidents={incidents ?? []}
               resources={resources ?? []}
-              navigate={navigate}
             />
           )}
 
-          {/* Heatmap overlay */}
-          {layers.heatmap && <HeatmapLayer incidents={geoIncidents} />}
+          {/* Markers and heatmap, clustered natively per viewport */}
+          <AggregateLayers
+            show={visibleEntities}
+            heatmap={layers.heatmap}
+            navigate={navigate}
+          />
 
           {/* Alert radius circles */}
           {layers.alertRadius && (
             <AlertRadiusCircles incidents={geoIncidents} />
           )}
+
+          {/* Trackers, and the track being played back */}
+          {layers.units && <LiveUnitsLayer onPlayback={setTrack} />}
+          {trackKey && <TrackPlaybackLayer playback={playback} />}
         </MapContainer>
       )}
 
@@ -392,6 +522,12 @@ export default function MapPage() {
           icon={<MapPin className="h-3.5 w-3.5" />}
           label="Incidents"
         />
+        <LayerToggle
+          active={layers.sos}
+          onClick={() => toggleLayer("sos")}
+          icon={<Siren className="h-3.5 w-3.5" />}
+          label="SOS"
+        />
         <LayerToggle
           active={layers.resources}
           onClick={() => toggleLayer("resources")}
@@ -410,8 +546,32 @@ export default function MapPage() {
           icon={<CircleDot className="h-3.5 w-3.5" />}
           label="Alert Radius"
         />
+        <LayerToggle
+          active={layers.units}
+          onClick={() => toggleLayer("units")}
+          icon={<Navigation className="h-3.5 w-3.5" />}
+          label="Live Units"
+        />
+        <LayerToggle
+          active={playbackOpen}
+          onClick={() => setTrack(playbackOpen ? null : "")}
+          icon={<History className="h-3.5 w-3.5" />}
+          label="Track Playback"
+        />
+
+        <Separator className="my-1" />
+        <OfflineAreaButton map={map} />
       </Card>
 
+      {playbackOpen && (
+        <TrackPlaybackPanel
+          unit={trackKey}
+          onUnitChange={setTrack}
+          onClose={() => setTrack(null)}
+          playback={playback}
+        />
+      )}
+
       {/* Legend (bottom-left) */}
       <Card className="absolute bottom-3 left-3 z-10 px-3 py-2 shadow-lg">
         <div className="flex items-center gap-3 text-xs text-muted-foreground">
@@ -420,6 +580,7 @@ export default function MapPage() {
           <LegendItem color="#eab308" label="Medium" />
           <LegendItem color="#22c55e" label="Low" />
           <Separator orientation="vertical" className="h-3" />
+          <LegendItem color={SOS_COLOR} label="SOS" />
           <LegendItem color="#3b82f6" label="Resource" />
         </div>
       </Card>
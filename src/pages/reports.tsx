@@ -26,6 +26,7 @@ import { save } from "@tauri-apps/plugin-dialog";
 import { writeFile } from "@tauri-apps/plugin-fs";
 import { format } from "date-fns";
 import { toast } from "sonner";
+import { formatCoordinates } from "@/lib/coords";
 
 type ReportType = "situation" | "incidents" | "resources" | "donations";
 
@@ -106,15 +107,23 @@ export default function ReportsPage() {
 
             // ── Incident Detail Table ────────────────
             if (reportType === "incidents" && recentIncidents.length > 0) {
+                // Positions in the notation the user reads them in
+                const located = recentIncidents.filter((i: any) => i.latitude != null && i.longitude != null);
+                const formatted = await formatCoordinates(
+                    located.map((i: any) => ({ lat: i.latitude, lng: i.longitude })),
+                ).catch(() => located.map((i: any) => `${i.latitude.toFixed(6)}, ${i.longitude.toFixed(6)}`));
+                const coordinates = new Map(located.map((i: any, index) => [i.id, formatted[index]]));
+
                 autoTable(doc, {
                     startY: y,
-                    head: [["Title", "Type", "Severity", "Status", "Location", "Created"]],
+                    head: [["Title", "Type", "Severity", "Status", "Location", "Coordinates", "Created"]],
                     body: recentIncidents.map((i: any) => [
                         i.title,
                         formatLabel(i.type),
                         formatLabel(i.severity),
                         formatLabel(i.status),
                         i.location_name ?? "—",
+                        coordinates.get(i.id) ?? "—",
                         format(new Date(i.created_at), "MMM d, yyyy"),
                     ]),
                     theme: "grid",
@@ -23,6 +23,7 @@ import {
   formatResourceType,
 } from "@/components/resources/resource-badges";
 import { ResourceForm } from "@/components/resources/resource-form";
+import { StagingArea } from "@/components/resources/staging-area";
 import { DashboardMap } from "@/components/map/map-view";
 import {
   useResource,
@@ -31,6 +32,7 @@ import {
   useResourceAssignments,
 } from "@/hooks/use-resources";
 import { useAuthStore } from "@/stores/auth-store";
+import { unitKey } from "@/lib/avl";
 import type { MapResource } from "@/hooks/use-dashboard";
 import {
   ArrowLeft,
@@ -45,6 +47,7 @@ import {
   Clock,
   Loader2,
   AlertTriangle,
+  History,
 } from "lucide-react";
 import { format, formatDistanceToNow } from "date-fns";
 import { toast } from "sonner";
@@ -167,6 +170,17 @@ export default function ResourceDetailPage() {
         </div>
 
         <div className="flex items-center gap-1.5">
+          <Button
+            variant="outline"
+            size="sm"
+            className="gap-1"
+            onClick={() =>
+              navigate(`/map?track=${encodeURIComponent(unitKey("resource", resource.id))}`)
+            }
+          >
+            <History className="h-3.5 w-3.5" />
+            Track
+          </Button>
           <Button
             variant="outline"
             size="sm"
@@ -333,6 +347,11 @@ export default function ResourceDetailPage() {
                 </p>
               </div>
             )}
+            <StagingArea
+              resourceId={resource.id}
+              latitude={resource.latitude}
+              longitude={resource.longitude}
+            />
           </CardContent>
         </Card>
       </div>
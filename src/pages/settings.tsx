@@ -28,21 +28,33 @@ import {
     CheckCircle2,
     Eye,
     EyeOff,
+    Map as MapIcon,
+    Megaphone,
 } from "lucide-react";
 import { toast } from "sonner";
 import { formatRole } from "@/lib/utils";
 import { AvatarUpload } from "@/components/avatar-upload";
+import { BasemapManager } from "@/components/map/basemap-manager";
+import { SpeedProfilesForm } from "@/components/speed-profiles-form";
+import { RoadNetworkManager } from "@/components/map/road-network-manager";
+import { GazetteerManager } from "@/components/map/gazetteer-manager";
+import { CoordinateFormatSelect } from "@/components/coordinate-format-select";
+import { GpsReceiverSettings } from "@/components/gps-receiver-settings";
+import { AvlSettings } from "@/components/avl-settings";
+import { CapSettings } from "@/components/cap-settings";
 import { getVersion } from "@tauri-apps/api/app";
 
 // ─── Types ───────────────────────────────────────────────────────
 
-type SettingsSection = "profile" | "appearance" | "notifications" | "security" | "about";
+type SettingsSection = "profile" | "appearance" | "notifications" | "maps" | "alerting" | "security" | "about";
 type NotifPrefs = Record<string, boolean>;
 
 const SECTIONS: { id: SettingsSection; label: string; icon: typeof User }[] = [
     { id: "profile", label: "Profile", icon: User },
     { id: "appearance", label: "Appearance", icon: Palette },
     { id: "notifications", label: "Notifications", icon: Bell },
+    { id: "maps", label: "Maps & Routes", icon: MapIcon },
+    { id: "alerting", label: "Alert Exchange", icon: Megaphone },
     { id: "security", label: "Security", icon: Shield },
     { id: "about", label: "About", icon: Info },
 ];
@@ -412,6 +424,72 @@ export default function SettingsPage() {
                         </Card>
                     )}
 
+                    {/* ── Maps & Routes Section ───────────────────── */}
+                    {activeSection === "maps" && (
+                        <Card className="page-header-gradient">
+                            <CardContent className="pt-6 space-y-5">
+                                <div className="flex items-center gap-3 mb-2">
+                                    <div className="stat-icon-container bg-primary/10">
+                                        <MapIcon className="h-4 w-4 text-primary" />
+                                    </div>
+                                    <div>
+                                        <h2 className="text-base font-semibold">Maps & Routes</h2>
+                                        <p className="text-xs text-muted-foreground">Coordinates, GPS, offline basemaps and evacuation route estimates</p>
+                                    </div>
+                                </div>
+
+                                <Separator />
+
+                                <CoordinateFormatSelect />
+
+                                <Separator />
+
+                                <GpsReceiverSettings />
+
+                                <Separator />
+
+                                <AvlSettings />
+
+                                <Separator />
+
+                                <BasemapManager />
+
+                                <Separator />
+
+                                <RoadNetworkManager />
+
+                                <Separator />
+
+                                <GazetteerManager />
+
+                                <Separator />
+
+                                <SpeedProfilesForm />
+                            </CardContent>
+                        </Card>
+                    )}
+
+                    {/* ── Alert Exchange Section ──────────────────── */}
+                    {activeSection === "alerting" && (
+                        <Card className="page-header-gradient">
+                            <CardContent className="pt-6 space-y-5">
+                                <div className="flex items-center gap-3 mb-2">
+                                    <div className="stat-icon-container bg-primary/10">
+                                        <Megaphone className="h-4 w-4 text-primary" />
+                                    </div>
+                                    <div>
+                                        <h2 className="text-base font-semibold">Alert Exchange</h2>
+                                        <p className="text-xs text-muted-foreground">Sharing alerts with partner agencies and warning systems</p>
+                                    </div>
+                                </div>
+
+                                <Separator />
+
+                                <CapSettings />
+                            </CardContent>
+                        </Card>
+                    )}
+
                     {/* ── Security Section ────────────────────────── */}
                     {activeSection === "security" && (
                         <Card className="page-header-gradient">
@@ -1,4 +1,5 @@
-import { useState } from "react";
+import { useEffect, useState } from "react";
+import { useSearchParams } from "react-router-dom";
 import {
     useTasks,
     useCreateTask,
@@ -520,6 +521,21 @@ export default function TasksPage() {
         setFormOpen(true);
     };
 
+    // Deep links land here as /tasks?task={id}
+    const [searchParams, setSearchParams] = useSearchParams();
+    const linkedTaskId = searchParams.get("task");
+    useEffect(() => {
+        if (!linkedTaskId || isLoading) return;
+        const task = tasks.find((t) => t.id === linkedTaskId);
+        if (task) {
+            setEditTask(task);
+            setFormOpen(true);
+        } else {
+            toast.error("Linked task not found");
+        }
+        setSearchParams({}, { replace: true });
+    }, [linkedTaskId, isLoading, tasks, setSearchParams]);
+
     const handleDelete = async (id: string) => {
         try {
             await deleteTask.mutateAsync(id);
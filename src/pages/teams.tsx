@@ -1,4 +1,5 @@
 import { useState } from "react";
+import { useNavigate } from "react-router-dom";
 import {
     useTeams,
     useTeam,
@@ -44,10 +45,12 @@ import {
     Shield,
     ChevronUp,
     ChevronDown,
+    History,
 } from "lucide-react";
 import { format } from "date-fns";
 import { toast } from "sonner";
 import { RoleGate } from "@/components/role-gate";
+import { unitKey } from "@/lib/avl";
 
 // ─── Team Card ───────────────────────────────────────────────────
 
@@ -99,6 +102,7 @@ function TeamDetail({
     teamId: string;
     onBack: () => void;
 }) {
+    const navigate = useNavigate();
     const userId = useAuthStore((s) => s.user?.id);
     const userRole = useAuthStore((s) => s.profile?.role);
     const { data: team, isLoading } = useTeam(teamId);
@@ -183,6 +187,14 @@ function TeamDetail({
                         <p className="text-sm text-muted-foreground">{team.description}</p>
                     )}
                 </div>
+                <Button
+                    size="sm"
+                    variant="outline"
+                    onClick={() => navigate(`/map?track=${encodeURIComponent(unitKey("team", teamId))}`)}
+                >
+                    <History className="h-4 w-4 mr-1.5" />
+                    Track
+                </Button>
                 {canManageMembers && (
                     <Button size="sm" onClick={() => setAddOpen(true)}>
                         <UserPlus className="h-4 w-4 mr-1.5" />
@@ -1,4 +1,5 @@
 import { create } from "zustand";
+import { invoke } from "@tauri-apps/api/core";
 import { supabase } from "@/lib/supabase";
 import type { Profile } from "@/types";
 import type { Session, User } from "@supabase/supabase-js";
@@ -24,6 +25,8 @@ interface AuthState {
         password: string
     ) => Promise<{ error: string | null }>;
     signOut: () => Promise<void>;
+    /** Sign out and wipe every stored session on this device */
+    signOutEverywhere: () => Promise<void>;
     resetPassword: (email: string) => Promise<{ error: string | null }>;
     updatePassword: (password: string) => Promise<{ error: string | null }>;
     fetchProfile: () => Promise<void>;
@@ -105,6 +108,12 @@ export const useAuthStore = create<AuthState>((set, get) => ({
         set({ user: null, profile: null, session: null });
     },
 
+    signOutEverywhere: async () => {
+        await supabase.auth.signOut({ scope: "local" }).catch(() => {});
+        await invoke("secrets_wipe");
+        set({ user: null, profile: null, session: null });
+    },
+
     resetPassword: async (email) => {
         const { error } = await supabase.auth.resetPasswordForEmail(email, {
             redirectTo: "disasterconnect://auth/callback",
@@ -5,10 +5,13 @@ interface MapState {
     zoom: number;
     layers: {
         incidents: boolean;
+        sos: boolean;
         resources: boolean;
         heatmap: boolean;
         alertRadius: boolean;
         routes: boolean;
+        /** Live tracker positions. */
+        units: boolean;
     };
 
     setCenter: (center: [number, number]) => void;
@@ -22,10 +25,12 @@ export const useMapStore = create<MapState>((set) => ({
     zoom: 10,
     layers: {
         incidents: true,
+        sos: true,
         resources: true,
         heatmap: false,
         alertRadius: false,
         routes: false,
+        units: true,
     },
 
     setCenter: (center) => set({ center }),
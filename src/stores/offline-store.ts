@@ -1,8 +1,10 @@
 import { create } from "zustand";
 import { persist, createJSONStorage } from "zustand/middleware";
+import { invoke } from "@tauri-apps/api/core";
 
 // ─── Queued mutation shape ──────────────────────────────────────
 
+/** Mirrors `QueuedMutation` in `src-tauri/src/offline_queue.rs`. */
 export interface QueuedMutation {
     id: string;
     table: string;
@@ -12,61 +14,165 @@ export interface QueuedMutation {
     queuedAt: string;
     /** Number of times we tried to replay this mutation */
     retries: number;
+    /** Error from the last failed replay, if any */
+    lastError: string | null;
+    /** `updated_at` of the record this update was based on */
+    baseUpdatedAt: string | null;
+    /** The record this update was based on, used for field-level merge */
+    base: Record<string, unknown> | null;
+    strategy: ConflictStrategy;
+    /** Unix millis of the next retry, `null` until the first failure */
+    nextAttemptAt: number | null;
+}
+
+export type ConflictStrategy = "last_writer_wins" | "merge" | "manual";
+
+export type NewMutation = Pick<QueuedMutation, "table" | "operation" | "payload"> &
+    Partial<Pick<QueuedMutation, "baseUpdatedAt" | "base" | "strategy">>;
+
+/** An offline update that collided with someone else's edit. */
+export interface SyncConflict {
+    id: string;
+    table: string;
+    recordId: string;
+    local: Record<string, unknown>;
+    base: Record<string, unknown> | null;
+    /** `null` when the record was deleted on the server */
+    remote: Record<string, unknown> | null;
+    /** Fields edited on both sides */
+    fields: string[];
+    baseUpdatedAt: string | null;
+    remoteUpdatedAt: string | null;
+    queuedAt: string;
+    detectedAt: string;
+}
+
+/** A mutation the server rejected for good (constraint violation, RLS denial). */
+export interface DeadLetter {
+    id: string;
+    table: string;
+    operation: QueuedMutation["operation"];
+    payload: Record<string, unknown>;
+    queuedAt: string;
+    retries: number;
+    error: string;
+    failedAt: string;
+    baseUpdatedAt: string | null;
+    strategy: ConflictStrategy;
+}
+
+export type ConflictResolution =
+    | { kind: "keep_local" }
+    | { kind: "keep_remote" }
+    | { kind: "merged"; payload: Record<string, unknown> };
+
+export interface DrainReport {
+    synced: number;
+    /** Failed transiently, retried later with backoff */
+    failed: number;
+    /** Rejected for good and moved to the dead-letter store */
+    deadLettered: number;
+    conflicts: number;
+    tables: string[];
 }
 
 // ─── Store ──────────────────────────────────────────────────────
 
+/**
+ * The queue itself lives in the local SQLite database and is owned by the
+ * Rust side, which keeps replaying it even while the window is hidden.
+ * This store only mirrors it for the UI.
+ */
 interface OfflineState {
     isOnline: boolean;
     queue: QueuedMutation[];
+    /** Updates that need review before they can be synced */
+    conflicts: SyncConflict[];
+    /** Mutations the server rejected, kept for inspection and retry */
+    deadLetters: DeadLetter[];
     isSyncing: boolean;
     lastSyncedAt: string | null;
 
     setOnline: (online: boolean) => void;
-    enqueue: (mutation: Omit<QueuedMutation, "id" | "queuedAt" | "retries">) => void;
-    dequeue: (id: string) => void;
-    incrementRetries: (id: string) => void;
-    clearQueue: () => void;
+    enqueue: (mutation: NewMutation) => Promise<void>;
+    discard: (id: string) => Promise<void>;
+    refreshQueue: () => Promise<void>;
+    refreshConflicts: () => Promise<void>;
+    resolveConflict: (id: string, resolution: ConflictResolution) => Promise<void>;
+    refreshDeadLetters: () => Promise<void>;
+    editDeadLetter: (id: string, payload: Record<string, unknown>) => Promise<void>;
+    retryDeadLetter: (id: string) => Promise<void>;
+    discardDeadLetter: (id: string) => Promise<void>;
+    drain: () => Promise<DrainReport>;
     setSyncing: (syncing: boolean) => void;
     setLastSynced: (iso: string) => void;
 }
 
 export const useOfflineStore = create<OfflineState>()(
     persist(
-        (set) => ({
+        (set, get) => ({
             isOnline: typeof navigator !== "undefined" ? navigator.onLine : true,
             queue: [],
+            conflicts: [],
+            deadLetters: [],
             isSyncing: false,
             lastSyncedAt: null,
 
             setOnline: (online) => set({ isOnline: online }),
 
-            enqueue: (mutation) =>
-                set((state) => ({
-                    queue: [
-                        ...state.queue,
-                        {
-                            ...mutation,
-                            id: crypto.randomUUID(),
-                            queuedAt: new Date().toISOString(),
-                            retries: 0,
-                        },
-                    ],
-                })),
-
-            dequeue: (id) =>
-                set((state) => ({
-                    queue: state.queue.filter((m) => m.id !== id),
-                })),
-
-            incrementRetries: (id) =>
-                set((state) => ({
-                    queue: state.queue.map((m) =>
-                        m.id === id ? { ...m, retries: m.retries + 1 } : m
-                    ),
-                })),
-
-            clearQueue: () => set({ queue: [] }),
+            enqueue: async (mutation) => {
+                await invoke("offline_queue_enqueue", { mutation });
+                await get().refreshQueue();
+            },
+
+            discard: async (id) => {
+                await invoke("offline_queue_discard", { id });
+                await get().refreshQueue();
+            },
+
+            refreshQueue: async () => {
+                const queue = await invoke<QueuedMutation[]>("offline_queue_list");
+                set({ queue });
+            },
+
+            refreshConflicts: async () => {
+                const conflicts = await invoke<SyncConflict[]>("sync_conflicts_list");
+                set({ conflicts });
+            },
+
+            resolveConflict: async (id, resolution) => {
+                await invoke("sync_conflict_resolve", { id, resolution });
+                await Promise.all([get().refreshConflicts(), get().refreshQueue()]);
+            },
+
+            refreshDeadLetters: async () => {
+                const deadLetters = await invoke<DeadLetter[]>("dead_letters_list");
+                set({ deadLetters });
+            },
+
+            editDeadLetter: async (id, payload) => {
+                await invoke("dead_letter_edit", { id, payload });
+                await get().refreshDeadLetters();
+            },
+
+            retryDeadLetter: async (id) => {
+                await invoke("dead_letter_retry", { id });
+                await Promise.all([get().refreshDeadLetters(), get().refreshQueue()]);
+            },
+
+            discardDeadLetter: async (id) => {
+                await invoke("dead_letter_discard", { id });
+                await get().refreshDeadLetters();
+            },
+
+            drain: async () => {
+                set({ isSyncing: true });
+                try {
+                    return await invoke<DrainReport>("offline_queue_drain");
+                } finally {
+                    set({ isSyncing: false });
+                }
+            },
 
             setSyncing: (syncing) => set({ isSyncing: syncing }),
 
@@ -76,7 +182,6 @@ export const useOfflineStore = create<OfflineState>()(
             name: "offline-queue",
             storage: createJSONStorage(() => localStorage),
             partialize: (state) => ({
-                queue: state.queue,
                 lastSyncedAt: state.lastSyncedAt,
             }),
         }